@@ -0,0 +1,431 @@
+//! `driftdb bench`: throughput and latency of a workload against a database
+//!
+//! The built-in workloads run on a scratch table, `driftdb_bench`, which is
+//! created (and preloaded for the read workloads) before the clock starts
+//! and dropped afterwards unless `--keep` is given. A custom workload is a
+//! file of SQL templates, one statement per line, run against whatever
+//! tables they name; each operation picks a line at random and fills its
+//! placeholders:
+//!
+//! - `{seq}`: an integer unique to the run, counting up from `--rows` + 1
+//! - `{key}`: a random integer from 1 to `--rows`, e.g. a preloaded key
+//! - `{int}`: a random integer from 0 to 999,999
+//! - `{text}`: a random 12-letter string
+//!
+//! Workers share one engine, as sessions of a server do, so with more than
+//! one the numbers include the time spent waiting for it.
+
+use anyhow::{Context, Result};
+use driftdb_core::durability::SyncMode;
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::Engine;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub const BENCH_TABLE: &str = "driftdb_bench";
+
+/// Rows per batch when preloading the bench table
+const PRELOAD_BATCH: usize = 1_000;
+
+/// Rows a range-scan operation reads
+const SCAN_ROWS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    /// INSERTs of new rows
+    Insert,
+    /// SELECTs of one row by primary key
+    Lookup,
+    /// SELECTs of a range of 100 primary keys
+    Scan,
+    /// 70% lookups, 20% inserts, 10% updates
+    Mixed,
+}
+
+impl std::str::FromStr for Workload {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "insert" => Ok(Workload::Insert),
+            "lookup" => Ok(Workload::Lookup),
+            "scan" => Ok(Workload::Scan),
+            "mixed" => Ok(Workload::Mixed),
+            other => Err(anyhow::anyhow!(
+                "unknown workload '{}'; expected insert, lookup, scan, mixed or custom",
+                other
+            )),
+        }
+    }
+}
+
+pub struct BenchOptions {
+    pub duration: Duration,
+    pub concurrency: usize,
+    /// Rows preloaded for the read workloads, and the range of `{key}`
+    pub rows: u64,
+    pub synchronous: SyncMode,
+    /// Codec for the bench table's segments
+    pub compression: Option<String>,
+    /// Leave the bench table in place afterwards
+    pub keep: bool,
+}
+
+/// What a run measured
+pub struct BenchReport {
+    pub workload: String,
+    pub operations: u64,
+    pub errors: u64,
+    pub elapsed: Duration,
+    /// Sorted latencies of the successful operations
+    latencies: Vec<Duration>,
+    pub bytes_written: u64,
+    pub first_error: Option<String>,
+}
+
+impl BenchReport {
+    pub fn ops_per_sec(&self) -> f64 {
+        self.operations as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// Latency at `percentile` (0-100) of the successful operations
+    pub fn latency(&self, percentile: f64) -> Duration {
+        if self.latencies.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile / 100.0 * (self.latencies.len() - 1) as f64).round() as usize;
+        self.latencies[rank.min(self.latencies.len() - 1)]
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let micros = |d: Duration| d.as_micros() as u64;
+        serde_json::json!({
+            "workload": self.workload,
+            "operations": self.operations,
+            "errors": self.errors,
+            "seconds": self.elapsed.as_secs_f64(),
+            "ops_per_sec": self.ops_per_sec(),
+            "latency_us": {
+                "p50": micros(self.latency(50.0)),
+                "p95": micros(self.latency(95.0)),
+                "p99": micros(self.latency(99.0)),
+                "max": micros(self.latency(100.0)),
+            },
+            "bytes_written": self.bytes_written,
+        })
+    }
+
+    pub fn print(&self) {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        println!("Workload:       {}", self.workload);
+        println!(
+            "Operations:     {} in {:.2}s ({} errors)",
+            self.operations,
+            self.elapsed.as_secs_f64(),
+            self.errors
+        );
+        println!("Throughput:     {:.1} ops/sec", self.ops_per_sec());
+        println!(
+            "Latency:        p50 {:.3}ms  p95 {:.3}ms  p99 {:.3}ms  max {:.3}ms",
+            ms(self.latency(50.0)),
+            ms(self.latency(95.0)),
+            ms(self.latency(99.0)),
+            ms(self.latency(100.0))
+        );
+        println!("Bytes written:  {}", self.bytes_written);
+        if let Some(error) = &self.first_error {
+            println!("First error:    {}", error);
+        }
+    }
+}
+
+/// A statement to time, built by a worker for each operation
+type NextStatement = dyn Fn(&mut Rng, &AtomicU64) -> String + Send + Sync;
+
+/// Run a built-in workload, or with `templates` the custom one they make
+pub fn run(
+    mut engine: Engine,
+    workload: &str,
+    templates: Option<&Path>,
+    options: &BenchOptions,
+) -> Result<BenchReport> {
+    let data_dir = engine.base_path().to_path_buf();
+    engine
+        .durability()
+        .set_mode(options.synchronous)
+        .context("Failed to set sync mode")?;
+
+    let rows = options.rows.max(1);
+    let next: Box<NextStatement> = match (workload, templates) {
+        ("custom", Some(path)) => {
+            let templates: Vec<String> = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with("--"))
+                .map(str::to_string)
+                .collect();
+            if templates.is_empty() {
+                return Err(anyhow::anyhow!("{} has no statements", path.display()));
+            }
+            Box::new(move |rng, seq| {
+                let template = &templates[rng.below(templates.len() as u64) as usize];
+                fill_template(template, rng, seq, rows)
+            })
+        }
+        ("custom", None) => {
+            return Err(anyhow::anyhow!(
+                "the custom workload needs --template <file>"
+            ))
+        }
+        (name, _) => {
+            let workload: Workload = name.parse()?;
+            prepare_table(&mut engine, workload, rows, options)?;
+            Box::new(move |rng, seq| builtin_statement(workload, rng, seq, rows))
+        }
+    };
+
+    let bytes_before = dir_size(&data_dir);
+    let engine = Arc::new(Mutex::new(engine));
+    let seq = Arc::new(AtomicU64::new(rows + 1));
+    let next: Arc<NextStatement> = next.into();
+    let started = Instant::now();
+    let deadline = started + options.duration;
+
+    let workers: Vec<_> = (0..options.concurrency.max(1))
+        .map(|worker| {
+            let (engine, seq, next) = (engine.clone(), seq.clone(), next.clone());
+            std::thread::spawn(move || {
+                let mut rng = Rng::seeded(worker as u64);
+                let mut latencies = Vec::new();
+                let (mut errors, mut first_error) = (0u64, None);
+                while Instant::now() < deadline {
+                    let sql = next(&mut rng, &seq);
+                    let op_started = Instant::now();
+                    let result = execute_sql(&mut engine.lock().unwrap(), &sql);
+                    let latency = op_started.elapsed();
+                    match result {
+                        Ok(_) => latencies.push(latency),
+                        Err(e) => {
+                            errors += 1;
+                            first_error.get_or_insert_with(|| format!("{}: {}", sql, e));
+                        }
+                    }
+                }
+                (latencies, errors, first_error)
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::new();
+    let (mut errors, mut first_error) = (0, None);
+    for worker in workers {
+        let (worker_latencies, worker_errors, worker_error) = worker
+            .join()
+            .map_err(|_| anyhow::anyhow!("a bench worker panicked"))?;
+        latencies.extend(worker_latencies);
+        errors += worker_errors;
+        if first_error.is_none() {
+            first_error = worker_error;
+        }
+    }
+    let elapsed = started.elapsed();
+
+    let mut engine = Arc::try_unwrap(engine)
+        .map_err(|_| anyhow::anyhow!("bench workers still hold the engine"))?
+        .into_inner()
+        .map_err(|_| anyhow::anyhow!("a bench worker panicked"))?;
+    // Back to full, which fsyncs everything the run deferred
+    engine
+        .durability()
+        .set_mode(SyncMode::Full)
+        .context("Failed to sync benchmark writes")?;
+    let bytes_written = dir_size(&data_dir).saturating_sub(bytes_before);
+    if workload != "custom" && !options.keep {
+        execute_sql(&mut engine, &format!("DROP TABLE {}", BENCH_TABLE))
+            .context("Failed to drop the bench table")?;
+    }
+
+    latencies.sort_unstable();
+    Ok(BenchReport {
+        workload: workload.to_string(),
+        operations: latencies.len() as u64,
+        errors,
+        elapsed,
+        latencies,
+        bytes_written,
+        first_error,
+    })
+}
+
+/// Create the bench table afresh, preloaded with `rows` rows unless the
+/// workload only inserts
+fn prepare_table(
+    engine: &mut Engine,
+    workload: Workload,
+    rows: u64,
+    options: &BenchOptions,
+) -> Result<()> {
+    if engine.table_exists(BENCH_TABLE) {
+        execute_sql(engine, &format!("DROP TABLE {}", BENCH_TABLE))
+            .context("Failed to drop the previous bench table")?;
+    }
+    execute_sql(
+        engine,
+        &format!(
+            "CREATE TABLE {} (id INTEGER PRIMARY KEY, name VARCHAR, value INTEGER, payload VARCHAR)",
+            BENCH_TABLE
+        ),
+    )
+    .context("Failed to create the bench table")?;
+    if let Some(compression) = &options.compression {
+        execute_sql(
+            engine,
+            &format!(
+                "ALTER TABLE {} SET (compression = '{}')",
+                BENCH_TABLE, compression
+            ),
+        )
+        .context("Failed to set the bench table's compression")?;
+    }
+    if workload == Workload::Insert {
+        return Ok(());
+    }
+
+    let mut rng = Rng::seeded(u64::MAX);
+    let mut id = 1;
+    while id <= rows {
+        let end = (id + PRELOAD_BATCH as u64).min(rows + 1);
+        let batch = (id..end)
+            .map(|id| {
+                serde_json::json!({
+                    "id": id,
+                    "name": format!("name-{}", id),
+                    "value": rng.below(1_000_000),
+                    "payload": rng.text(64),
+                })
+            })
+            .collect();
+        engine
+            .append_events_batch(BENCH_TABLE, batch)
+            .context("Failed to preload the bench table")?;
+        id = end;
+    }
+    Ok(())
+}
+
+fn builtin_statement(workload: Workload, rng: &mut Rng, seq: &AtomicU64, rows: u64) -> String {
+    let insert = |rng: &mut Rng| {
+        let id = seq.fetch_add(1, Ordering::Relaxed);
+        format!(
+            "INSERT INTO {} (id, name, value, payload) VALUES ({}, 'name-{}', {}, '{}')",
+            BENCH_TABLE,
+            id,
+            id,
+            rng.below(1_000_000),
+            rng.text(64)
+        )
+    };
+    let lookup = |rng: &mut Rng| {
+        format!(
+            "SELECT * FROM {} WHERE id = {}",
+            BENCH_TABLE,
+            1 + rng.below(rows)
+        )
+    };
+    match workload {
+        Workload::Insert => insert(rng),
+        Workload::Lookup => lookup(rng),
+        Workload::Scan => {
+            let start = 1 + rng.below(rows.saturating_sub(SCAN_ROWS).max(1));
+            format!(
+                "SELECT * FROM {} WHERE id >= {} AND id < {}",
+                BENCH_TABLE,
+                start,
+                start + SCAN_ROWS
+            )
+        }
+        Workload::Mixed => match rng.below(10) {
+            0..=6 => lookup(rng),
+            7 | 8 => insert(rng),
+            _ => format!(
+                "UPDATE {} SET value = {} WHERE id = {}",
+                BENCH_TABLE,
+                rng.below(1_000_000),
+                1 + rng.below(rows)
+            ),
+        },
+    }
+}
+
+/// Fill a custom workload line's placeholders
+fn fill_template(template: &str, rng: &mut Rng, seq: &AtomicU64, rows: u64) -> String {
+    let mut sql = template.to_string();
+    while let Some(at) = sql.find("{seq}") {
+        let value = seq.fetch_add(1, Ordering::Relaxed).to_string();
+        sql.replace_range(at..at + "{seq}".len(), &value);
+    }
+    while let Some(at) = sql.find("{key}") {
+        let value = (1 + rng.below(rows)).to_string();
+        sql.replace_range(at..at + "{key}".len(), &value);
+    }
+    while let Some(at) = sql.find("{int}") {
+        let value = rng.below(1_000_000).to_string();
+        sql.replace_range(at..at + "{int}".len(), &value);
+    }
+    while let Some(at) = sql.find("{text}") {
+        let value = rng.text(12);
+        sql.replace_range(at..at + "{text}".len(), &value);
+    }
+    sql
+}
+
+/// Bytes of every file under `dir`
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// xorshift64*: a fast generator for workload values, not for anything
+/// that needs to be unpredictable
+struct Rng(u64);
+
+impl Rng {
+    fn seeded(stream: u64) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self((nanos ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15)) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A value in `0..bound`
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound.max(1)
+    }
+
+    fn text(&mut self, len: usize) -> String {
+        (0..len)
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect()
+    }
+}
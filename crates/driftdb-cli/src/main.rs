@@ -1,12 +1,20 @@
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use driftdb_core::{Engine, Query, QueryResult};
+use driftdb_core::durability::SyncMode;
+use driftdb_core::migration_runner::MigrationRunner;
+use driftdb_core::optimizer::{AnalyzeConfig, HistogramKind};
+use driftdb_core::{Engine, Event, EventSubscription, EventType, QueryResult};
+use std::collections::HashSet;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::EnvFilter;
 
 mod backup;
+mod bench;
 
 #[derive(Parser)]
 #[command(name = "driftdb")]
@@ -36,6 +44,21 @@ enum Commands {
         #[arg(short, long, conflicts_with = "execute")]
         file: Option<PathBuf>,
     },
+    /// Check SQL for risky patterns and schema problems without executing it
+    Lint {
+        /// SQL query to check
+        #[arg(short, long, conflicts_with = "file")]
+        execute: Option<String>,
+        /// SQL file to check, one statement per line
+        #[arg(short, long, conflicts_with = "execute")]
+        file: Option<PathBuf>,
+        /// Database directory to check table and column references against
+        #[arg(short, long)]
+        data: Option<PathBuf>,
+        /// Output reports as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Ingest data from JSONL file
     Ingest {
         /// Database directory path
@@ -47,6 +70,23 @@ enum Commands {
         /// JSONL file to ingest
         #[arg(short, long)]
         file: PathBuf,
+        /// Sync mode while ingesting: full, async or fsync_off. Every row
+        /// is fsynced before the command returns, whichever is chosen.
+        #[arg(long, default_value = "full")]
+        synchronous: String,
+        /// Load an empty table straight into its segments, skipping
+        /// per-row checks and fsyncs. A crash mid-load loses the whole
+        /// load, and the table comes back empty.
+        #[arg(long, conflicts_with = "synchronous")]
+        bulk: bool,
+        /// Check every line (JSON syntax, column types, primary key,
+        /// constraints) and report the bad ones without writing anything
+        #[arg(long, conflicts_with = "bulk")]
+        dry_run: bool,
+        /// What a bad line does to a real run: abort stops at it, skip
+        /// reports it and carries on with the next
+        #[arg(long, default_value = "abort", conflicts_with = "bulk")]
+        on_error: String,
     },
     /// Select data from a table
     Select {
@@ -99,11 +139,34 @@ enum Commands {
         #[arg(short, long)]
         table: String,
     },
-    /// Check and repair database integrity
+    /// Check database integrity and optionally repair it
     Doctor {
         /// Database directory path
         #[arg(short, long)]
         data: PathBuf,
+        /// Apply the suggested fixes; refused unless every one provably
+        /// keeps the data recoverable
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Re-check primary keys, foreign keys and CHECK constraints against
+    /// current data, listing each violating row
+    Verify {
+        /// Database directory path
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Table name (optional, checks all tables if not specified)
+        #[arg(short, long)]
+        table: Option<String>,
+    },
+    /// Show physical storage statistics for tables
+    Stats {
+        /// Database directory path
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Table name (optional, shows all tables if not specified)
+        #[arg(short, long)]
+        table: Option<String>,
     },
     /// Analyze tables and update optimizer statistics
     Analyze {
@@ -113,6 +176,110 @@ enum Commands {
         /// Table name (optional, analyzes all tables if not specified)
         #[arg(short, long)]
         table: Option<String>,
+        /// Rows sampled from each larger table
+        #[arg(long, default_value = "30000")]
+        sample_rows: usize,
+        /// Buckets per column histogram
+        #[arg(long, default_value = "100")]
+        buckets: usize,
+        /// Build equal-width rather than equal-depth histograms for
+        /// numeric columns
+        #[arg(long)]
+        equi_width: bool,
+    },
+    /// Clone a database through a snapshot stream
+    Clone {
+        /// Source database directory, or `-` to read a snapshot from stdin
+        #[arg(long)]
+        from: String,
+        /// Target database directory, or `-` to write the snapshot to stdout
+        #[arg(long)]
+        to: String,
+    },
+    /// Apply or revert versioned SQL migrations
+    Migrate {
+        /// Database directory path
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Directory of V<version>__<name>.sql files, with optional
+        /// V<version>__<name>.down.sql files to revert them
+        #[arg(short = 'm', long, default_value = "migrations")]
+        dir: PathBuf,
+        /// Revert the most recently applied migration
+        #[arg(long)]
+        down: bool,
+        /// List applied and pending migrations without changing anything
+        #[arg(long, conflicts_with = "down")]
+        status: bool,
+        /// Instead of running migrations, print the statements that bring
+        /// the database to the schema declared in this file
+        #[arg(long, conflicts_with_all = ["down", "status"])]
+        target: Option<PathBuf>,
+        /// Run the statements printed for --target
+        #[arg(long, requires = "target")]
+        apply: bool,
+        /// Allow --apply to drop tables and columns
+        #[arg(long, requires = "apply")]
+        allow_destructive: bool,
+    },
+    /// Print a table's events (inserts, updates, deletes) as they are
+    /// written, like `tail -f`. Takes no lock, so it can follow a database
+    /// another process has open.
+    Watch {
+        /// Database directory path
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Table name
+        #[arg(short, long)]
+        table: String,
+        /// Keep waiting for new events until interrupted, instead of
+        /// exiting after the ones already written
+        #[arg(short, long)]
+        follow: bool,
+        /// Start at this sequence rather than at the last 10 events
+        #[arg(long)]
+        from: Option<u64>,
+        /// Output format: pretty (one line per event) or json (one JSON
+        /// object per line)
+        #[arg(long, default_value = "pretty")]
+        format: String,
+    },
+    /// Measure throughput and latency of a workload: insert, lookup
+    /// (point reads by primary key), scan (range reads), mixed, or custom
+    /// (SQL templates from --template)
+    Bench {
+        /// Database directory path
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Workload: insert, lookup, scan, mixed or custom
+        #[arg(short, long, default_value = "mixed")]
+        workload: String,
+        /// Seconds to run for
+        #[arg(long, default_value = "10")]
+        duration: u64,
+        /// Concurrent workers
+        #[arg(short, long, default_value = "1")]
+        concurrency: usize,
+        /// Rows preloaded for the read workloads, and the range of {key}
+        #[arg(long, default_value = "10000")]
+        rows: u64,
+        /// File of SQL templates for the custom workload, one per line,
+        /// with {seq}, {key}, {int} and {text} placeholders
+        #[arg(long)]
+        template: Option<PathBuf>,
+        /// Sync mode during the run: full, async or fsync_off
+        #[arg(long, default_value = "full")]
+        synchronous: String,
+        /// Segment compression for the bench table: none, zstd, lz4 or
+        /// brotli[:quality]
+        #[arg(long)]
+        compression: Option<String>,
+        /// Keep the bench table afterwards
+        #[arg(long)]
+        keep: bool,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Backup and restore operations
     Backup {
@@ -138,6 +305,8 @@ fn main() -> Result<()> {
         .with_env_filter(
             EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("driftdb=info")),
         )
+        // Keep stdout for command output so `--json` stays parseable
+        .with_writer(std::io::stderr)
         .init();
 
     let cli = Cli::parse();
@@ -188,34 +357,112 @@ fn main() -> Result<()> {
                 }
             }
         }
-        Commands::Ingest { data, table, file } => {
-            let mut engine = Engine::open(&data).context("Failed to open database")?;
+        Commands::Lint {
+            execute,
+            file,
+            data,
+            json,
+        } => {
+            let queries = if let Some(query) = execute {
+                vec![query]
+            } else if let Some(file) = file {
+                let content = fs::read_to_string(&file).context("Failed to read SQL file")?;
+                content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty() && !line.trim().starts_with("--"))
+                    .map(String::from)
+                    .collect()
+            } else {
+                return Err(anyhow::anyhow!("Must provide either -e or -f"));
+            };
+            let engine = match data {
+                Some(data) => {
+                    Some(Engine::open_read_only(&data).context("Failed to open database")?)
+                }
+                None => None,
+            };
 
-            let file = fs::File::open(&file).context("Failed to open JSONL file")?;
-            let reader = BufReader::new(file);
+            let mut failed = 0;
+            for query_str in queries {
+                let mut report = driftdb_core::sql_lint::analyze(&query_str);
+                if let Some(engine) = &engine {
+                    report.check_schema(engine);
+                }
+                if !report.is_clean() {
+                    failed += 1;
+                }
 
-            let mut count = 0;
-            for line in reader.lines() {
-                let line = line?;
-                if line.trim().is_empty() {
+                if json {
+                    println!("{}", serde_json::to_string(&report)?);
                     continue;
                 }
+                println!("{}", query_str.trim());
+                if let Some(error) = &report.parse_error {
+                    println!("  parse error: {}", error);
+                }
+                println!("  category: {:?}", report.category);
+                println!("  tables: {}", report.tables.join(", "));
+                println!("  columns: {}", report.columns.join(", "));
+                for risk in &report.risks {
+                    println!("  risk: {}", risk.description());
+                }
+                for issue in &report.schema_issues {
+                    println!("  schema: {}", issue);
+                }
+            }
 
-                let data: serde_json::Value =
-                    serde_json::from_str(&line).context("Failed to parse JSON")?;
-
-                let query = Query::Insert {
-                    table: table.clone(),
-                    data,
-                };
-
-                engine
-                    .execute_query(query)
-                    .context("Failed to insert row")?;
-                count += 1;
+            if failed > 0 {
+                return Err(anyhow::anyhow!("{} statement(s) failed lint", failed));
+            }
+        }
+        Commands::Ingest {
+            data,
+            table,
+            file,
+            synchronous,
+            bulk,
+            dry_run,
+            on_error,
+        } => {
+            let mut engine = Engine::open(&data).context("Failed to open database")?;
+            let reader =
+                BufReader::new(fs::File::open(&file).context("Failed to open JSONL file")?);
+            if bulk {
+                return bulk_ingest(&engine, &table, reader);
+            }
+            if dry_run {
+                return dry_run_ingest(&engine, &table, reader);
             }
+            let skip_bad_lines = match on_error.as_str() {
+                "abort" => false,
+                "skip" => true,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "--on-error: expected abort or skip, got '{}'",
+                        other
+                    ))
+                }
+            };
+            let synchronous: SyncMode = synchronous
+                .parse()
+                .map_err(|e| anyhow::anyhow!("--synchronous: {}", e))?;
+            engine.durability().set_mode(synchronous)?;
 
-            println!("Ingested {} rows into table '{}'", count, table);
+            let result = ingest(&mut engine, &table, reader, skip_bad_lines);
+            // Back to full, which fsyncs everything the import deferred
+            engine
+                .durability()
+                .set_mode(SyncMode::Full)
+                .context("Failed to sync ingested rows")?;
+            let (count, skipped) = result?;
+            if skipped > 0 {
+                println!(
+                    "Ingested {} rows into table '{}', skipped {} invalid rows",
+                    count, table, skipped
+                );
+            } else {
+                println!("Ingested {} rows into table '{}'", count, table);
+            }
         }
         Commands::Select {
             data,
@@ -316,17 +563,91 @@ fn main() -> Result<()> {
                 println!("{}", message)
             }
         }
-        Commands::Doctor { data } => {
+        Commands::Doctor { data, repair } => {
+            let engine = Engine::open(&data).context("Failed to open database")?;
+
+            let report = if repair {
+                engine.repair()
+            } else {
+                engine.diagnose()
+            }
+            .context("Failed to run doctor")?;
+
+            for line in report.lines() {
+                println!("{}", line);
+            }
+            if report.refused.is_some() {
+                anyhow::bail!("Repair refused; nothing was changed");
+            }
+            if !repair && !report.is_healthy() && report.can_repair() {
+                println!("Run with --repair to apply the fixes");
+            }
+        }
+        Commands::Verify { data, table } => {
             let engine = Engine::open(&data).context("Failed to open database")?;
 
-            let report = engine.doctor().context("Failed to run doctor")?;
+            let report = match table {
+                Some(table) => engine.verify_constraints(&table),
+                None => engine.verify_all_constraints(),
+            }
+            .context("Failed to verify constraints")?;
 
-            for line in report {
+            for line in report.lines() {
                 println!("{}", line);
             }
+            if !report.is_clean() {
+                anyhow::bail!("{} constraint violations found", report.violations.len());
+            }
+        }
+        Commands::Stats { data, table } => {
+            let engine = Engine::open_read_only(&data).context("Failed to open database")?;
+
+            let tables = match table {
+                Some(table) => vec![table],
+                None => {
+                    let mut tables = engine.list_tables();
+                    tables.sort();
+                    tables
+                }
+            };
+
+            for table in tables {
+                let info = engine
+                    .table_storage_info(&table)
+                    .with_context(|| format!("Failed to read stats for table '{}'", table))?;
+                println!("{}:", info.table);
+                println!("  Segments: {}", info.segment_count);
+                println!("  Snapshots: {}", info.snapshot_count);
+                println!("  Events: {}", info.event_count);
+                println!("  Live rows: {}", info.live_rows);
+                println!("  Dead versions: {:.1}%", info.dead_version_ratio * 100.0);
+                println!(
+                    "  Last compaction sequence: {}",
+                    info.last_compaction_sequence
+                );
+                println!("  Size on disk: {} bytes", info.total_bytes);
+                println!("  Logical size: {} bytes", info.logical_bytes);
+                println!("  Disk/logical ratio: {:.2}", info.disk_to_logical_ratio());
+            }
         }
-        Commands::Analyze { data, table } => {
+        Commands::Analyze {
+            data,
+            table,
+            sample_rows,
+            buckets,
+            equi_width,
+        } => {
             let engine = Engine::open(&data).context("Failed to open database")?;
+            engine.query_optimizer().set_analyze_config(AnalyzeConfig {
+                sample_rows,
+                histogram_buckets: buckets,
+                numeric_histogram: if equi_width {
+                    HistogramKind::EquiWidth
+                } else {
+                    HistogramKind::EquiDepth
+                },
+                ..AnalyzeConfig::default()
+            });
 
             // Create optimizer to store the statistics
             let optimizer = driftdb_core::optimizer::QueryOptimizer::new();
@@ -342,7 +663,7 @@ fn main() -> Result<()> {
                     ))?;
 
                 println!("Table: {}", stats.table_name);
-                println!("  Rows: {}", stats.row_count);
+                println!("  Rows: {} ({})", stats.row_count, stats.collection_method);
                 println!("  Average row size: {} bytes", stats.avg_row_size);
                 println!("  Total size: {} bytes", stats.total_size_bytes);
                 println!("  Columns analyzed: {}", stats.column_stats.len());
@@ -351,9 +672,20 @@ fn main() -> Result<()> {
                 for (col_name, col_stats) in &stats.column_stats {
                     println!("  Column '{}':", col_name);
                     println!("    Distinct values: {}", col_stats.distinct_values);
-                    println!("    Null count: {}", col_stats.null_count);
-                    if col_stats.histogram.is_some() {
-                        println!("    Histogram: ✓");
+                    println!("    Null fraction: {:.3}", col_stats.null_fraction);
+                    if let Some(histogram) = &col_stats.histogram {
+                        println!(
+                            "    Histogram: {} buckets, {:?}",
+                            histogram.bucket_count, histogram.kind
+                        );
+                    }
+                    if let Some(mcv) = col_stats.most_common_values.first() {
+                        println!(
+                            "    Most common: {} ({:.1}%, {} tracked)",
+                            mcv.value,
+                            mcv.frequency * 100.0,
+                            col_stats.most_common_values.len()
+                        );
                     }
                 }
 
@@ -382,9 +714,172 @@ fn main() -> Result<()> {
                 println!("\n✓ Statistics updated for all tables");
             }
         }
+        Commands::Clone { from, to } => {
+            let info = match (from.as_str(), to.as_str()) {
+                ("-", "-") => anyhow::bail!("--from and --to cannot both be '-'"),
+                ("-", to) => Engine::import_snapshot(to, std::io::stdin().lock())
+                    .context("Failed to import snapshot")?,
+                (from, to) => {
+                    let engine = Engine::open_read_only(from).context("Failed to open database")?;
+                    if to == "-" {
+                        engine
+                            .export_snapshot(std::io::BufWriter::new(std::io::stdout().lock()))
+                            .context("Failed to export snapshot")?
+                    } else {
+                        let mut buffer = Vec::new();
+                        engine
+                            .export_snapshot(&mut buffer)
+                            .context("Failed to export snapshot")?;
+                        Engine::import_snapshot(to, buffer.as_slice())
+                            .context("Failed to import snapshot")?
+                    }
+                }
+            };
+
+            // Keep stdout clean when it carries the snapshot itself
+            eprintln!(
+                "Cloned {} table(s), {} event(s), up to sequence {}",
+                info.sequences.len(),
+                info.events,
+                info.max_sequence()
+            );
+            for (table, sequence) in &info.sequences {
+                eprintln!("  {}: sequence {}", table, sequence);
+            }
+        }
+        Commands::Watch {
+            data,
+            table,
+            follow,
+            from,
+            format,
+        } => {
+            let json = match format.as_str() {
+                "pretty" => false,
+                "json" => true,
+                other => {
+                    return Err(anyhow::anyhow!(
+                        "--format: expected pretty or json, got '{}'",
+                        other
+                    ))
+                }
+            };
+            let mut subscription = EventSubscription::open(&data, &table)
+                .with_context(|| format!("Failed to watch table '{}'", table))?;
+            let start = match from {
+                Some(sequence) => sequence.saturating_sub(1),
+                None => subscription.position().saturating_sub(10),
+            };
+            subscription.seek(start)?;
+            watch(&mut subscription, follow, json)?;
+        }
+        Commands::Bench {
+            data,
+            workload,
+            duration,
+            concurrency,
+            rows,
+            template,
+            synchronous,
+            compression,
+            keep,
+            json,
+        } => {
+            let engine = Engine::open(&data).context("Failed to open database")?;
+            let synchronous: SyncMode = synchronous
+                .parse()
+                .map_err(|e| anyhow::anyhow!("--synchronous: {}", e))?;
+            let options = bench::BenchOptions {
+                duration: Duration::from_secs(duration),
+                concurrency,
+                rows,
+                synchronous,
+                compression,
+                keep,
+            };
+            let report = bench::run(engine, &workload, template.as_deref(), &options)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+            } else {
+                report.print();
+            }
+        }
         Commands::Backup { command } => {
             backup::run(command)?;
         }
+        Commands::Migrate {
+            data,
+            dir,
+            down,
+            status,
+            target,
+            apply,
+            allow_destructive,
+        } => {
+            let mut engine = Engine::open(&data).context("Failed to open database")?;
+
+            if let Some(target) = target {
+                let declared = fs::read_to_string(&target)
+                    .with_context(|| format!("Failed to read {}", target.display()))?;
+                let plan = MigrationRunner::plan(&engine, &declared)?;
+                for change in &plan.changes {
+                    if change.destructive {
+                        println!("-- destructive");
+                    }
+                    println!("{};", change.sql);
+                }
+                for difference in &plan.unsupported {
+                    println!("-- unsupported: {}", difference);
+                }
+                if plan.is_empty() {
+                    println!("-- Database matches {}", target.display());
+                }
+
+                if apply {
+                    if !allow_destructive && plan.destructive().next().is_some() {
+                        return Err(anyhow::anyhow!(
+                            "Plan drops tables or columns; pass --allow-destructive to apply it"
+                        ));
+                    }
+                    let count = plan.apply(&mut engine, allow_destructive)?;
+                    println!("Applied {} schema change(s)", count);
+                }
+                return Ok(());
+            }
+            let runner = MigrationRunner::from_dir(&dir)
+                .with_context(|| format!("Failed to load migrations from {}", dir.display()))?;
+
+            if status {
+                let status = runner.status(&mut engine)?;
+                for applied in &status.applied {
+                    println!(
+                        "applied  {:>6}  {}  ({})",
+                        applied.version, applied.name, applied.applied_at
+                    );
+                }
+                for migration in runner
+                    .migrations()
+                    .filter(|m| status.pending.contains(&m.version))
+                {
+                    println!("pending  {:>6}  {}", migration.version, migration.name);
+                }
+                runner.verify(&mut engine)?;
+            } else if down {
+                match runner.down(&mut engine)? {
+                    Some(version) => println!("Reverted migration {}", version),
+                    None => println!("No migrations to revert"),
+                }
+            } else {
+                let applied = runner.up(&mut engine)?;
+                if applied.is_empty() {
+                    println!("Database is up to date");
+                } else {
+                    for version in &applied {
+                        println!("Applied migration {}", version);
+                    }
+                }
+            }
+        }
         Commands::Optimize {
             data,
             enable,
@@ -448,3 +943,223 @@ fn parse_as_of_to_temporal(as_of: &str) -> Result<String> {
     // Assume ISO8601 timestamp
     Ok(format!("FOR SYSTEM_TIME AS OF '{}'", as_of))
 }
+
+/// A JSONL line as a row of `table`, checked as an insert would check it
+/// and against `keys`, the keys of rows read before it that aren't in
+/// the table yet. The row's key joins `keys` once it passes.
+fn check_line(
+    engine: &Engine,
+    table: &str,
+    line: &str,
+    keys: &mut HashSet<String>,
+) -> Result<serde_json::Value> {
+    let mut row: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| anyhow::anyhow!("invalid JSON: {}", e))?;
+    let key = engine.check_insert_row(table, &mut row)?;
+    if !keys.insert(key.to_string()) {
+        return Err(anyhow::anyhow!(
+            "Primary key violation: {} already exists earlier in the file",
+            key
+        ));
+    }
+    Ok(row)
+}
+
+/// `ingest`: rows go in batches, each checked as a whole and written with
+/// one sync. A bad line stops the run, or with `skip_bad_lines` is
+/// reported and left out. Returns the rows ingested and skipped.
+fn ingest(
+    engine: &mut Engine,
+    table: &str,
+    reader: impl BufRead,
+    skip_bad_lines: bool,
+) -> Result<(usize, usize)> {
+    const BATCH_ROWS: usize = 1_000;
+
+    let (mut count, mut skipped) = (0, 0);
+    let mut batch = Vec::with_capacity(BATCH_ROWS);
+    let mut batch_keys = HashSet::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match check_line(engine, table, &line, &mut batch_keys) {
+            Ok(row) => batch.push(row),
+            Err(e) if skip_bad_lines => {
+                eprintln!("line {}: {}", number + 1, e);
+                skipped += 1;
+            }
+            Err(e) => {
+                return Err(e.context(format!(
+                    "line {}: {} rows were ingested before it",
+                    number + 1,
+                    count
+                )))
+            }
+        }
+        if batch.len() == BATCH_ROWS {
+            count += batch.len();
+            engine
+                .append_events_batch(table, std::mem::take(&mut batch))
+                .context("Failed to insert rows")?;
+            batch_keys.clear();
+        }
+    }
+    count += batch.len();
+    engine
+        .append_events_batch(table, batch)
+        .context("Failed to insert rows")?;
+    Ok((count, skipped))
+}
+
+/// `ingest --dry-run`: every line is checked and the bad ones reported
+/// with their line numbers, then a count of valid and invalid rows.
+/// Nothing is written; the command fails if any row would be rejected.
+fn dry_run_ingest(engine: &Engine, table: &str, reader: impl BufRead) -> Result<()> {
+    if !engine.table_exists(table) {
+        return Err(anyhow::anyhow!("Table '{}' does not exist", table));
+    }
+    let (mut valid, mut invalid) = (0, 0);
+    let mut keys = HashSet::new();
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match check_line(engine, table, &line, &mut keys) {
+            Ok(_) => valid += 1,
+            Err(e) => {
+                println!("line {}: {}", number + 1, e);
+                invalid += 1;
+            }
+        }
+    }
+    println!(
+        "Dry run for table '{}': {} valid rows, {} invalid rows; nothing was written",
+        table, valid, invalid
+    );
+    if invalid > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} rows are invalid",
+            invalid,
+            valid + invalid
+        ));
+    }
+    Ok(())
+}
+
+/// `watch`: print the subscription's events, then with `follow` keep
+/// printing new ones until Ctrl-C (or SIGTERM), which ends the command
+/// cleanly after the events already read. A closed stdout, as when piped
+/// into `head`, ends it the same way.
+fn watch(subscription: &mut EventSubscription, follow: bool, json: bool) -> Result<()> {
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGINT, signal_hook::consts::SIGTERM] {
+        signal_hook::flag::register(signal, stop.clone())
+            .context("Failed to install signal handler")?;
+    }
+
+    let stdout = std::io::stdout();
+    loop {
+        let events = if follow {
+            subscription.next_events(Duration::from_millis(200))?
+        } else {
+            subscription.poll()?
+        };
+        if !follow && events.is_empty() {
+            break;
+        }
+        let mut out = stdout.lock();
+        for event in &events {
+            let line = if json {
+                serde_json::to_string(&event.change_record())?
+            } else {
+                format_event(event)
+            };
+            if let Err(e) = writeln!(out, "{}", line).and_then(|_| out.flush()) {
+                if e.kind() == std::io::ErrorKind::BrokenPipe {
+                    return Ok(());
+                }
+                return Err(e.into());
+            }
+        }
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+    if follow {
+        eprintln!(
+            "Stopped watching table '{}' at sequence {}",
+            subscription.table(),
+            subscription.position()
+        );
+    }
+    Ok(())
+}
+
+fn event_kind(event: &Event) -> &'static str {
+    match event.event_type {
+        EventType::Insert => "INSERT",
+        EventType::Patch => "UPDATE",
+        EventType::SoftDelete => "DELETE",
+    }
+}
+
+fn event_time(event: &Event) -> String {
+    event
+        .timestamp
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_else(|_| event.timestamp.to_string())
+}
+
+/// One event as `#<sequence> <time> <kind> <key> <payload>`
+fn format_event(event: &Event) -> String {
+    let mut line = format!(
+        "#{} {} {} {}",
+        event.sequence,
+        event_time(event),
+        event_kind(event),
+        event.primary_key
+    );
+    if !event.payload.is_null() {
+        line.push(' ');
+        line.push_str(&event.payload.to_string());
+    }
+    line
+}
+
+/// `ingest --bulk`: rows go to the table's segments in batches, and the
+/// load only counts once `finish_bulk_load` returns
+fn bulk_ingest(engine: &Engine, table: &str, reader: impl BufRead) -> Result<()> {
+    const BATCH_ROWS: usize = 10_000;
+
+    engine
+        .begin_bulk_load(table)
+        .context("Failed to start bulk load")?;
+    let mut batch = Vec::with_capacity(BATCH_ROWS);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        batch.push(serde_json::from_str(&line).context("Failed to parse JSON")?);
+        if batch.len() == BATCH_ROWS {
+            engine
+                .bulk_load_rows(table, std::mem::take(&mut batch))
+                .context("Failed to load rows")?;
+        }
+    }
+    engine
+        .bulk_load_rows(table, batch)
+        .context("Failed to load rows")?;
+
+    let summary = engine
+        .finish_bulk_load(table)
+        .context("Failed to finish bulk load")?;
+    println!(
+        "Bulk loaded {} rows ({} keys) into table '{}'",
+        summary.rows, summary.keys, table
+    );
+    Ok(())
+}
@@ -123,6 +123,145 @@ fn test_ingest_jsonl() {
         .stdout(predicate::str::contains("Ingested 3 rows"));
 }
 
+#[test]
+fn test_ingest_bulk() {
+    let db = TestDb::new();
+
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+
+    driftdb()
+        .arg("sql")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-e")
+        .arg("CREATE TABLE orders (id INTEGER, product VARCHAR, PRIMARY KEY (id))")
+        .assert()
+        .success();
+
+    let jsonl_path = create_jsonl_file(
+        &db.dir,
+        "orders.jsonl",
+        &[
+            r#"{"id": 1, "product": "Widget"}"#,
+            r#"{"id": 2, "product": "Gadget"}"#,
+        ],
+    );
+    let ingest = || {
+        let mut cmd = driftdb();
+        cmd.arg("ingest")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-t")
+            .arg("orders")
+            .arg("-f")
+            .arg(jsonl_path.to_str().unwrap())
+            .arg("--bulk");
+        cmd
+    };
+
+    ingest()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Bulk loaded 2 rows"));
+
+    // Bulk loads only go into empty tables
+    ingest()
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("empty table"));
+}
+
+#[test]
+fn test_ingest_dry_run_and_on_error() {
+    let db = TestDb::new();
+
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+
+    driftdb()
+        .arg("sql")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-e")
+        .arg("CREATE TABLE orders (id INTEGER, product VARCHAR(10), quantity INTEGER, PRIMARY KEY (id))")
+        .assert()
+        .success();
+
+    let jsonl_path = create_jsonl_file(
+        &db.dir,
+        "orders.jsonl",
+        &[
+            r#"{"id": 1, "product": "Widget", "quantity": 10}"#,
+            r#"{"id": 2, "product": "Gadget", "quantity": "five"}"#,
+            r#"{"id": 3, "product": "Gizmo""#,
+            r#"{"product": "Sprocket", "quantity": 1}"#,
+            r#"{"id": 1, "product": "Again", "quantity": 2}"#,
+            r#"{"id": 6, "product": "Thingamajig", "quantity": 1}"#,
+            r#"{"id": 7, "product": "Doohickey", "quantity": 3}"#,
+        ],
+    );
+    let ingest = |args: &[&str]| {
+        let mut cmd = driftdb();
+        cmd.arg("ingest")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-t")
+            .arg("orders")
+            .arg("-f")
+            .arg(jsonl_path.to_str().unwrap())
+            .args(args);
+        cmd
+    };
+    let products = || {
+        let output = driftdb()
+            .arg("sql")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-e")
+            .arg("SELECT product FROM orders")
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    ingest(&["--dry-run"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("line 2:"))
+        .stdout(predicate::str::contains("line 3: invalid JSON"))
+        .stdout(predicate::str::contains(
+            "line 4: Invalid query: Missing primary key field",
+        ))
+        .stdout(predicate::str::contains("line 5: Primary key violation"))
+        .stdout(predicate::str::contains(
+            "line 6: Validation error: value too long",
+        ))
+        .stdout(predicate::str::contains("2 valid rows, 5 invalid rows"));
+    // Nothing was written
+    assert!(!products().contains("Widget"));
+
+    // Aborting keeps nothing from the batch the bad line was in
+    ingest(&[])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("line 2"));
+    assert!(!products().contains("Widget"));
+
+    ingest(&["--on-error", "skip"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Ingested 2 rows into table 'orders', skipped 5 invalid rows",
+        ))
+        .stderr(predicate::str::contains("line 3: invalid JSON"));
+    let ingested = products();
+    assert!(ingested.contains("Widget") && ingested.contains("Doohickey"));
+
+    ingest(&["--on-error", "retry"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected abort or skip"));
+}
+
 #[test]
 fn test_select_with_where_clause() {
     let db = TestDb::new();
@@ -222,9 +361,59 @@ fn test_doctor_command() {
         .arg("-d")
         .arg(db.path_str())
         .assert()
+        .success()
+        .stdout(predicate::str::contains("No problems found"));
+
+    // Nothing to repair, so repair succeeds too
+    driftdb()
+        .arg("doctor")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("--repair")
+        .assert()
         .success();
 }
 
+#[test]
+fn test_verify_command() {
+    let db = TestDb::new();
+
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+
+    let sql_path = create_sql_file(
+        &db.dir,
+        "constraints.sql",
+        &[
+            "CREATE TABLE teams (id INTEGER, name VARCHAR, PRIMARY KEY (id))",
+            "CREATE TABLE players (id INTEGER, team_id INTEGER REFERENCES teams(id), age INTEGER CHECK (age >= 16), PRIMARY KEY (id))",
+            "INSERT INTO teams VALUES (1, 'Rovers')",
+            "INSERT INTO players VALUES (1, 1, 21)",
+        ],
+    );
+    driftdb()
+        .arg("sql")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-f")
+        .arg(sql_path.to_str().unwrap())
+        .assert()
+        .success();
+
+    // Primary key, foreign key and CHECK on players
+    driftdb()
+        .arg("verify")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-t")
+        .arg("players")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Checked 3 constraints on 1 tables",
+        ))
+        .stdout(predicate::str::contains("No violations found"));
+}
+
 #[test]
 fn test_sql_file_execution() {
     let db = TestDb::new();
@@ -380,3 +569,237 @@ fn test_select_json_output() {
         .stdout(predicate::str::contains("["))
         .stdout(predicate::str::contains("]"));
 }
+
+#[test]
+fn test_lint_flags_risks_and_dropped_columns() {
+    let db = TestDb::new();
+
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+
+    for sql in [
+        "CREATE TABLE users (id INTEGER, name VARCHAR, nickname VARCHAR, PRIMARY KEY (id))",
+        "ALTER TABLE users DROP COLUMN nickname",
+    ] {
+        driftdb()
+            .arg("sql")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-e")
+            .arg(sql)
+            .assert()
+            .success();
+    }
+
+    // A parameterized query against existing columns is clean
+    driftdb()
+        .arg("lint")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-e")
+        .arg("SELECT id, name FROM users WHERE id = $1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tables: users"));
+
+    driftdb()
+        .arg("lint")
+        .arg("-d")
+        .arg(db.path_str())
+        .arg("-e")
+        .arg("SELECT nickname FROM users")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("column \"nickname\" was dropped"));
+
+    // No database needed for pattern checks
+    driftdb()
+        .arg("lint")
+        .arg("--json")
+        .arg("-e")
+        .arg(r#"SELECT * FROM users WHERE name = '" + name + "'"#)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"string_concatenation\""));
+}
+
+#[test]
+fn test_watch_prints_table_events() {
+    let db = TestDb::new();
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+    let sql = |statement: &str| {
+        driftdb()
+            .arg("sql")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-e")
+            .arg(statement)
+            .assert()
+            .success();
+    };
+    sql("CREATE TABLE orders (id INTEGER, status VARCHAR, PRIMARY KEY (id))");
+    sql("INSERT INTO orders (id, status) VALUES (1, 'new')");
+    sql("UPDATE orders SET status = 'paid' WHERE id = 1");
+    sql("DELETE FROM orders WHERE id = 1");
+
+    driftdb()
+        .args(["watch", "-d", db.path_str(), "-t", "orders"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#1 "))
+        .stdout(predicate::str::contains("INSERT 1 {"))
+        .stdout(predicate::str::contains(r#""status":"new""#))
+        .stdout(predicate::str::contains("UPDATE 1 {"))
+        .stdout(predicate::str::contains(r#""status":"paid""#))
+        .stdout(predicate::str::contains("DELETE 1"));
+
+    let output = driftdb()
+        .args(["watch", "-d", db.path_str(), "-t", "orders"])
+        .args(["--from", "3", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let lines: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["sequence"], 3);
+    assert_eq!(lines[0]["type"], "delete");
+    assert_eq!(lines[0]["table"], "orders");
+
+    driftdb()
+        .args(["watch", "-d", db.path_str(), "-t", "orders"])
+        .args(["--format", "xml"])
+        .assert()
+        .failure();
+    driftdb()
+        .args(["watch", "-d", db.path_str(), "-t", "missing"])
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_watch_follow_sees_writes_from_another_process() {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    let db = TestDb::new();
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+    let sql = |statement: &str| {
+        driftdb()
+            .arg("sql")
+            .arg("-d")
+            .arg(db.path_str())
+            .arg("-e")
+            .arg(statement)
+            .assert()
+            .success();
+    };
+    sql("CREATE TABLE orders (id INTEGER, status VARCHAR, PRIMARY KEY (id))");
+
+    let mut watcher = std::process::Command::new(env!("CARGO_BIN_EXE_driftdb"))
+        .args(["watch", "-d", db.path_str(), "-t", "orders", "--follow"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    sql("INSERT INTO orders (id, status) VALUES (7, 'new')");
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    // Ctrl-C ends the watch cleanly
+    let status = std::process::Command::new("kill")
+        .args(["-INT", &watcher.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert!(watcher.wait().unwrap().success());
+
+    let mut stdout = String::new();
+    watcher
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut stdout)
+        .unwrap();
+    assert!(stdout.contains("INSERT 7 {"), "{}", stdout);
+    let mut stderr = String::new();
+    watcher
+        .stderr
+        .take()
+        .unwrap()
+        .read_to_string(&mut stderr)
+        .unwrap();
+    assert!(
+        stderr.contains("Stopped watching table 'orders' at sequence 1"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_bench_reports_builtin_and_custom_workloads() {
+    let db = TestDb::new();
+    driftdb().arg("init").arg(db.path_str()).assert().success();
+
+    driftdb()
+        .args(["bench", "-d", db.path_str(), "-w", "mixed"])
+        .args(["--duration", "1", "-c", "2", "--rows", "500"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Workload:       mixed"))
+        .stdout(predicate::str::contains("ops/sec"))
+        .stdout(predicate::str::contains("p99"))
+        .stdout(predicate::str::contains("(0 errors)"));
+    // The bench table is dropped afterwards
+    driftdb()
+        .args([
+            "sql",
+            "-d",
+            db.path_str(),
+            "-e",
+            "SELECT * FROM driftdb_bench",
+        ])
+        .assert()
+        .failure();
+
+    driftdb()
+        .args(["sql", "-d", db.path_str(), "-e"])
+        .arg("CREATE TABLE kv (k INTEGER PRIMARY KEY, v VARCHAR)")
+        .assert()
+        .success();
+    let templates = tempfile::TempDir::new().unwrap();
+    let template = create_sql_file(
+        &templates,
+        "workload.sql",
+        &[
+            "-- writes, then reads of what may have been written",
+            "INSERT INTO kv (k, v) VALUES ({seq}, '{text}')",
+            "SELECT * FROM kv WHERE k = {key}",
+        ],
+    );
+    let output = driftdb()
+        .args(["bench", "-d", db.path_str(), "-w", "custom", "--json"])
+        .args(["--duration", "1", "--rows", "10", "--synchronous", "async"])
+        .arg("--template")
+        .arg(&template)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["workload"], "custom");
+    assert_eq!(report["errors"], 0);
+    assert!(report["operations"].as_u64().unwrap() > 0);
+    assert!(report["bytes_written"].as_u64().unwrap() > 0);
+    assert!(
+        report["latency_us"]["p50"].as_u64().unwrap()
+            <= report["latency_us"]["max"].as_u64().unwrap()
+    );
+
+    driftdb()
+        .args(["bench", "-d", db.path_str(), "-w", "custom"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--template"));
+}
@@ -0,0 +1,116 @@
+//! Derive macros for the DriftDB client library
+//!
+//! Use them through `driftdb_client`, which re-exports them:
+//!
+//! ```ignore
+//! use driftdb_client::FromRow;
+//!
+//! #[derive(FromRow)]
+//! struct User {
+//!     id: i64,
+//!     #[driftdb(rename = "display_name")]
+//!     name: String,
+//!     #[driftdb(default)]
+//!     tags: Option<String>,
+//! }
+//! ```
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implement `driftdb_client::FromRow` by reading each field from the
+/// column of the same name.
+///
+/// Field attributes:
+/// - `#[driftdb(rename = "col")]` reads the field from column `col`
+/// - `#[driftdb(default)]` uses `Default::default()` when the column is
+///   missing or NULL
+#[proc_macro_derive(FromRow, attributes(driftdb))]
+pub fn derive_from_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    name,
+                    "FromRow can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "FromRow can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut initializers = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let options = FieldOptions::parse(field)?;
+        let column = options.rename.unwrap_or_else(|| ident.to_string());
+        initializers.push(if options.default {
+            quote! {
+                #ident: match row.value(#column) {
+                    None | Some(::driftdb_client::Value::Null) => ::core::default::Default::default(),
+                    Some(_) => row.get(#column)?,
+                }
+            }
+        } else {
+            quote! { #ident: row.get(#column)? }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::driftdb_client::FromRow for #name #ty_generics #where_clause {
+            fn from_row(row: &::driftdb_client::Row) -> ::driftdb_client::Result<Self> {
+                ::core::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+/// What a field's `#[driftdb(...)]` attributes ask for
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<String>,
+    default: bool,
+}
+
+impl FieldOptions {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut options = FieldOptions::default();
+        for attr in &field.attrs {
+            if !attr.path().is_ident("driftdb") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    let column: LitStr = meta.value()?.parse()?;
+                    options.rename = Some(column.value());
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    options.default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown driftdb attribute; expected `rename` or `default`"))
+                }
+            })?;
+        }
+        Ok(options)
+    }
+}
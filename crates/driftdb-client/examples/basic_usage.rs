@@ -48,9 +48,9 @@ async fn main() -> Result<()> {
 
     println!("Found {} users:", rows.len());
     for row in &rows {
-        let id = row.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let email = row.get("email").and_then(|v| v.as_str()).unwrap_or("?");
+        let id = row.value("id").and_then(|v| v.as_i64()).unwrap_or(0);
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let email = row.value("email").and_then(|v| v.as_str()).unwrap_or("?");
 
         println!("  - User #{}: {} <{}>", id, name, email);
     }
@@ -68,8 +68,8 @@ async fn main() -> Result<()> {
     let rows = client.query("SELECT * FROM users WHERE id = 2").await?;
 
     if let Some(row) = rows.first() {
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let email = row.get("email").and_then(|v| v.as_str()).unwrap_or("?");
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let email = row.value("email").and_then(|v| v.as_str()).unwrap_or("?");
         println!("  {} now has email: {}", name, email);
     }
     println!();
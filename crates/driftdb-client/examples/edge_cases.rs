@@ -166,7 +166,7 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    if let Some(val) = row.get("name") {
+                    if let Some(val) = row.value("name") {
                         if val.is_null() {
                             println!("✓ NULL stored and retrieved correctly");
                         } else {
@@ -202,8 +202,8 @@ async fn test_data_types(client: &Client) {
                         .await
                     {
                         if rows.len() == 2 {
-                            let t = rows[0].get("active").and_then(|v| v.as_bool());
-                            let f = rows[1].get("active").and_then(|v| v.as_bool());
+                            let t = rows[0].value("active").and_then(|v| v.as_bool());
+                            let f = rows[1].value("active").and_then(|v| v.as_bool());
                             if t == Some(true) && f == Some(false) {
                                 println!("✓ Booleans work correctly");
                             } else {
@@ -234,7 +234,7 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    if let Some(val) = row.get("name").and_then(|v| v.as_str()) {
+                    if let Some(val) = row.value("name").and_then(|v| v.as_str()) {
                         if val.is_empty() {
                             println!("✓ Empty string preserved");
                         } else {
@@ -269,7 +269,7 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    if let Some(val) = row.get("name").and_then(|v| v.as_str()) {
+                    if let Some(val) = row.value("name").and_then(|v| v.as_str()) {
                         if val == special {
                             println!("✓ Special characters preserved");
                         } else {
@@ -304,7 +304,7 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    if let Some(val) = row.get("name").and_then(|v| v.as_str()) {
+                    if let Some(val) = row.value("name").and_then(|v| v.as_str()) {
                         if val == unicode {
                             println!("✓ Unicode preserved");
                         } else {
@@ -339,7 +339,7 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    if let Some(val) = row.get("count").and_then(|v| v.as_i64()) {
+                    if let Some(val) = row.value("count").and_then(|v| v.as_i64()) {
                         if val == large {
                             println!("✓ Large integer preserved");
                         } else {
@@ -370,8 +370,8 @@ async fn test_data_types(client: &Client) {
                 .await
             {
                 if let Some(row) = rows.first() {
-                    let c = row.get("count").and_then(|v| v.as_i64());
-                    let p = row.get("price").and_then(|v| v.as_i64());
+                    let c = row.value("count").and_then(|v| v.as_i64());
+                    let p = row.value("price").and_then(|v| v.as_i64());
                     if c == Some(0) && p == Some(0) {
                         println!("✓ Zero values preserved");
                     } else {
@@ -539,15 +539,15 @@ async fn test_complex_queries(client: &Client) {
     match client.query("SELECT users.name, orders.amount FROM users JOIN orders ON users.id = orders.user_id WHERE users.id = 1 ORDER BY orders.amount").await {
         Ok(rows) => {
             if rows.len() == 2 {
-                let name1 = rows[0].get("name").and_then(|v| v.as_str());
-                let name2 = rows[1].get("name").and_then(|v| v.as_str());
-                let amt1 = rows[0].get("amount").and_then(|v| v.as_i64());
-                let amt2 = rows[1].get("amount").and_then(|v| v.as_i64());
+                let name1 = rows[0].value("name").and_then(|v| v.as_str());
+                let name2 = rows[1].value("name").and_then(|v| v.as_str());
+                let amt1 = rows[0].value("amount").and_then(|v| v.as_i64());
+                let amt2 = rows[1].value("amount").and_then(|v| v.as_i64());
 
                 if name1 == Some("Alice") && name2 == Some("Alice") && amt1 == Some(100) && amt2 == Some(200) {
                     println!("✓ JOIN works");
                 } else {
-                    println!("⚠️  Unexpected results: {:?}, {:?}", rows[0].get("name"), rows[1].get("amount"));
+                    println!("⚠️  Unexpected results: {:?}, {:?}", rows[0].value("name"), rows[1].value("amount"));
                 }
             } else {
                 println!("⚠️  Expected 2 rows, got {}", rows.len());
@@ -565,10 +565,10 @@ async fn test_complex_queries(client: &Client) {
         Ok(rows) => {
             if rows.len() == 4 {
                 // Should be: (2,150), (2,250), (1,100), (1,200)
-                let check = rows[0].get("user_id").and_then(|v| v.as_i64()) == Some(2)
-                    && rows[0].get("amount").and_then(|v| v.as_i64()) == Some(150)
-                    && rows[3].get("user_id").and_then(|v| v.as_i64()) == Some(1)
-                    && rows[3].get("amount").and_then(|v| v.as_i64()) == Some(200);
+                let check = rows[0].value("user_id").and_then(|v| v.as_i64()) == Some(2)
+                    && rows[0].value("amount").and_then(|v| v.as_i64()) == Some(150)
+                    && rows[3].value("user_id").and_then(|v| v.as_i64()) == Some(1)
+                    && rows[3].value("amount").and_then(|v| v.as_i64()) == Some(200);
 
                 if check {
                     println!("✓ Multi-column ORDER BY works");
@@ -603,7 +603,7 @@ async fn test_complex_queries(client: &Client) {
     {
         Ok(rows) => {
             if rows.len() == 1 {
-                if let Some(amt) = rows[0].get("amount").and_then(|v| v.as_i64()) {
+                if let Some(amt) = rows[0].value("amount").and_then(|v| v.as_i64()) {
                     if amt == 200 {
                         println!("✓ Complex WHERE works");
                     } else {
@@ -49,9 +49,12 @@ async fn main() -> Result<()> {
         .await?;
     println!(
         "  ✓ Retrieved: {} - {}",
-        rows[0].get("name").and_then(|v| v.as_str()).unwrap_or("?"),
         rows[0]
-            .get("description")
+            .value("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("?"),
+        rows[0]
+            .value("description")
             .and_then(|v| v.as_str())
             .unwrap_or("?")
     );
@@ -84,12 +87,15 @@ async fn main() -> Result<()> {
     println!("\n  ✓ Retrieved safely:");
     println!(
         "     Name: {}",
-        rows[0].get("name").and_then(|v| v.as_str()).unwrap_or("?")
+        rows[0]
+            .value("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
     );
     println!(
         "     Description: {}",
         rows[0]
-            .get("description")
+            .value("description")
             .and_then(|v| v.as_str())
             .unwrap_or("?")
     );
@@ -109,7 +115,7 @@ async fn main() -> Result<()> {
     if !rows.is_empty() {
         println!(
             "     ID: {}",
-            rows[0].get("id").and_then(|v| v.as_i64()).unwrap_or(0)
+            rows[0].value("id").and_then(|v| v.as_i64()).unwrap_or(0)
         );
     }
 
@@ -139,8 +145,8 @@ async fn main() -> Result<()> {
     for row in &rows {
         println!(
             "     - {} (id={})",
-            row.get("name").and_then(|v| v.as_str()).unwrap_or("?"),
-            row.get("id").and_then(|v| v.as_i64()).unwrap_or(0)
+            row.value("name").and_then(|v| v.as_str()).unwrap_or("?"),
+            row.value("id").and_then(|v| v.as_i64()).unwrap_or(0)
         );
     }
 
@@ -168,12 +174,15 @@ async fn main() -> Result<()> {
     println!("  → Inserted and retrieved unicode/emoji:");
     println!(
         "     Name: {}",
-        rows[0].get("name").and_then(|v| v.as_str()).unwrap_or("?")
+        rows[0]
+            .value("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("?")
     );
     println!(
         "     Description: {}",
         rows[0]
-            .get("description")
+            .value("description")
             .and_then(|v| v.as_str())
             .unwrap_or("?")
     );
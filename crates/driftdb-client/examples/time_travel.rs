@@ -115,8 +115,8 @@ async fn main() -> Result<()> {
 
     println!("Found {} versions in history:", rows.len());
     for (idx, row) in rows.iter().enumerate() {
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let price = row.get("price").and_then(|v| v.as_i64()).unwrap_or(0);
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let price = row.value("price").and_then(|v| v.as_i64()).unwrap_or(0);
         println!(
             "  Version {}: {} - ${:.2}",
             idx + 1,
@@ -135,8 +135,8 @@ async fn main() -> Result<()> {
 async fn print_product(client: &Client, sql: &str) -> Result<()> {
     let rows = client.query(sql).await?;
     if let Some(row) = rows.first() {
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let price = row.get("price").and_then(|v| v.as_i64()).unwrap_or(0);
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let price = row.value("price").and_then(|v| v.as_i64()).unwrap_or(0);
         println!("   Product: {} - ${:.2}", name, price as f64 / 100.0);
     }
     Ok(())
@@ -144,8 +144,8 @@ async fn print_product(client: &Client, sql: &str) -> Result<()> {
 
 fn print_row_details(rows: &[driftdb_client::types::Row]) {
     if let Some(row) = rows.first() {
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let price = row.get("price").and_then(|v| v.as_i64()).unwrap_or(0);
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let price = row.value("price").and_then(|v| v.as_i64()).unwrap_or(0);
         println!("   Name: {}", name);
         println!("   Price: ${:.2}", price as f64 / 100.0);
     } else {
@@ -152,8 +152,8 @@ async fn print_balances(client: &Client) -> Result<()> {
         .await?;
 
     for row in &rows {
-        let name = row.get("name").and_then(|v| v.as_str()).unwrap_or("?");
-        let balance = row.get("balance").and_then(|v| v.as_i64()).unwrap_or(0);
+        let name = row.value("name").and_then(|v| v.as_str()).unwrap_or("?");
+        let balance = row.value("balance").and_then(|v| v.as_i64()).unwrap_or(0);
         println!("    {} : ${:.2}", name, balance as f64 / 100.0);
     }
 
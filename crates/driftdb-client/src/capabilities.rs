@@ -0,0 +1,168 @@
+//! What the connected server supports
+//!
+//! DriftDB reports its release in the `driftdb_version` ParameterStatus and
+//! its optional features in `driftdb_features` at startup, alongside the
+//! PostgreSQL-compatible `server_version`.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use tracing::warn;
+
+/// An optional server feature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Parse/Bind/Execute and prepared statements
+    ExtendedQuery,
+    /// `COPY ... FROM STDIN` / `TO STDOUT`
+    Copy,
+    /// `LISTEN` / `NOTIFY`
+    ListenNotify,
+    /// `FOR SYSTEM_TIME` time-travel queries
+    Temporal,
+    /// Cancelling a running statement from another connection
+    CancelRequest,
+    /// `SHOW COLUMNS FROM` / `SHOW INDEXES FROM`
+    SchemaIntrospection,
+}
+
+impl Feature {
+    /// The name the server advertises the feature under
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::ExtendedQuery => "extended_query",
+            Feature::Copy => "copy",
+            Feature::ListenNotify => "listen_notify",
+            Feature::Temporal => "temporal",
+            Feature::CancelRequest => "cancel_request",
+            Feature::SchemaIntrospection => "schema_introspection",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The server's version and features, as reported when connecting
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerCapabilities {
+    /// The PostgreSQL-compatible `server_version`, e.g. `14.0 (DriftDB 0.11.0)`
+    pub server_version: Option<String>,
+    /// The DriftDB release, e.g. `0.11.0`
+    pub driftdb_version: Option<String>,
+    /// Advertised feature names, including ones this client doesn't know
+    pub features: BTreeSet<String>,
+}
+
+impl ServerCapabilities {
+    /// Build from the startup ParameterStatus values
+    pub fn from_parameters<'a>(parameter: impl Fn(&str) -> Option<&'a str>) -> Self {
+        let server_version = parameter("server_version").map(str::to_string);
+        // Servers from before `driftdb_version` put it in `server_version`
+        let driftdb_version = parameter("driftdb_version")
+            .map(str::to_string)
+            .or_else(|| server_version.as_deref().and_then(embedded_driftdb_version));
+        let features = parameter("driftdb_features")
+            .unwrap_or("")
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self {
+            server_version,
+            driftdb_version,
+            features,
+        }
+    }
+
+    /// Whether the server advertised `feature`
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.features.contains(feature.as_str())
+    }
+
+    /// Log a warning if the server's release isn't compatible with this
+    /// client's: a different major version, or minor while below 1.0
+    pub(crate) fn warn_on_version_mismatch(&self) {
+        let Some(server) = &self.driftdb_version else {
+            return;
+        };
+        let client = env!("CARGO_PKG_VERSION");
+        if !compatible(server, client) {
+            warn!(
+                "DriftDB server {} may not be compatible with driftdb-client {}",
+                server, client
+            );
+        }
+    }
+}
+
+/// `0.2.0` from `14.0 (DriftDB 0.2.0)`
+fn embedded_driftdb_version(server_version: &str) -> Option<String> {
+    let start = server_version.find("DriftDB ")? + "DriftDB ".len();
+    let version = server_version[start..].trim_end_matches(')').trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+fn compatible(a: &str, b: &str) -> bool {
+    let parts = |v: &str| -> Vec<u64> {
+        v.split(['.', '-', '+'])
+            .take(2)
+            .map(|p| p.parse().unwrap_or(0))
+            .collect()
+    };
+    match (parts(a).as_slice(), parts(b).as_slice()) {
+        ([0, minor_a, ..], [0, minor_b, ..]) => minor_a == minor_b,
+        ([major_a, ..], [major_b, ..]) => major_a == major_b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn capabilities(params: &[(&str, &str)]) -> ServerCapabilities {
+        let params: HashMap<&str, &str> = params.iter().copied().collect();
+        ServerCapabilities::from_parameters(|name| params.get(name).copied())
+    }
+
+    #[test]
+    fn test_parses_startup_parameters() {
+        let caps = capabilities(&[
+            ("server_version", "14.0 (DriftDB 0.11.0-alpha)"),
+            ("driftdb_version", "0.11.0-alpha"),
+            (
+                "driftdb_features",
+                "extended_query,temporal, cancel_request,vector_search",
+            ),
+        ]);
+        assert_eq!(caps.driftdb_version.as_deref(), Some("0.11.0-alpha"));
+        assert!(caps.supports(Feature::Temporal));
+        assert!(caps.supports(Feature::CancelRequest));
+        assert!(!caps.supports(Feature::Copy));
+        assert!(caps.features.contains("vector_search"));
+    }
+
+    #[test]
+    fn test_older_servers() {
+        let caps = capabilities(&[("server_version", "14.0 (DriftDB 0.2.0)")]);
+        assert_eq!(caps.driftdb_version.as_deref(), Some("0.2.0"));
+        assert!(caps.features.is_empty());
+
+        let caps = capabilities(&[("server_version", "15.4")]);
+        assert_eq!(caps.driftdb_version, None);
+        assert_eq!(caps.server_version.as_deref(), Some("15.4"));
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        assert!(compatible("0.11.0-alpha", "0.11.3"));
+        assert!(!compatible("0.2.0", "0.11.0-alpha"));
+        assert!(compatible("1.4.0", "1.0.2"));
+        assert!(!compatible("2.0.0", "1.9.0"));
+    }
+}
@@ -1,18 +1,41 @@
 //! DriftDB client connection and query execution
 
+use crate::capabilities::ServerCapabilities;
 use crate::error::{Error, Result};
+use crate::from_row::FromRow;
 use crate::query::Query;
+use crate::schema::{quote_ident, ColumnSchema, IndexSchema, TableSchema};
+use crate::script::{returns_rows, split_script, QueryOutcome};
 use crate::transaction::Transaction;
-use crate::types::{Row, Value};
+use crate::types::{decode_bytea, encode_bytea, Row, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 use tokio_postgres::{Client as PgClient, NoTls};
-use tracing::{debug, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// How long `ping()` waits for the server before calling the connection dead
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// With auto-reconnect, a connection idle this long is pinged before use
+const DEFAULT_PING_AFTER_IDLE: Duration = Duration::from_secs(60);
 
 /// DriftDB client for executing queries
 ///
 /// The client maintains a connection to a DriftDB server and provides
 /// methods for executing queries, transactions, and time-travel operations.
 pub struct Client {
-    inner: PgClient,
+    inner: RwLock<Arc<PgClient>>,
+    connection_string: String,
+    auto_reconnect: bool,
+    ping_after_idle: Duration,
+    last_used: Mutex<Instant>,
+    /// Whether the statements sent so far left a transaction open
+    in_transaction: AtomicBool,
+    reconnecting: tokio::sync::Mutex<()>,
+    /// As reported by the server on the current connection
+    capabilities: RwLock<Arc<ServerCapabilities>>,
 }
 
 impl Client {
@@ -46,9 +69,104 @@ impl Client {
 
         debug!("Connection string: {}", connection_string);
 
-        let (client, connection) = tokio_postgres::connect(&connection_string, NoTls)
+        let (client, capabilities) = Self::open(&connection_string).await?;
+        info!("Successfully connected to DriftDB");
+        Ok(Self {
+            inner: RwLock::new(Arc::new(client)),
+            connection_string,
+            auto_reconnect: false,
+            ping_after_idle: DEFAULT_PING_AFTER_IDLE,
+            last_used: Mutex::new(Instant::now()),
+            in_transaction: AtomicBool::new(false),
+            reconnecting: tokio::sync::Mutex::new(()),
+            capabilities: RwLock::new(Arc::new(capabilities)),
+        })
+    }
+
+    /// Re-establish a dropped connection before the next statement
+    ///
+    /// When enabled, a statement first checks the connection: if it has
+    /// closed, or has been idle longer than `ping_after_idle` (60 seconds
+    /// by default) and fails a [`ping`](Self::ping), the client connects
+    /// and authenticates again before running it. Statements inside an
+    /// open transaction are never retried on a new connection: the
+    /// transaction died with the old one, so they fail with
+    /// [`Error::Transaction`] and the next statement reconnects.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::Client;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::connect("localhost:5433")
+    ///     .await?
+    ///     .with_auto_reconnect(true);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_auto_reconnect(mut self, enabled: bool) -> Self {
+        self.auto_reconnect = enabled;
+        self
+    }
+
+    /// How long the connection may sit idle before auto-reconnect pings it
+    pub fn with_ping_after_idle(mut self, idle: Duration) -> Self {
+        self.ping_after_idle = idle;
+        self
+    }
+
+    /// Check the connection with a cheap round trip
+    ///
+    /// Returns `false` if the connection has closed or the server doesn't
+    /// answer within 5 seconds. Never reconnects.
+    pub async fn ping(&self) -> bool {
+        let client = self.pg();
+        if client.is_closed() {
+            return false;
+        }
+        let alive = matches!(
+            tokio::time::timeout(PING_TIMEOUT, client.simple_query("SELECT 1")).await,
+            Ok(Ok(_))
+        );
+        if alive {
+            self.touch();
+        }
+        alive
+    }
+
+    /// Replace the connection with a new one, authenticating again
+    ///
+    /// Any transaction open on the old connection is lost.
+    pub async fn reconnect(&self) -> Result<()> {
+        let stale = self.pg();
+        self.reconnect_from(&stale).await
+    }
+
+    async fn reconnect_from(&self, stale: &Arc<PgClient>) -> Result<()> {
+        let _reconnecting = self.reconnecting.lock().await;
+        // Someone else already replaced it while we waited
+        if !Arc::ptr_eq(stale, &self.pg()) {
+            return Ok(());
+        }
+        info!("Reconnecting to DriftDB");
+        let (client, capabilities) = Self::open(&self.connection_string).await?;
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(client);
+        *self.capabilities.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(capabilities);
+        self.in_transaction.store(false, Ordering::SeqCst);
+        self.touch();
+        Ok(())
+    }
+
+    async fn open(connection_string: &str) -> Result<(PgClient, ServerCapabilities)> {
+        let (client, connection) = tokio_postgres::connect(connection_string, NoTls)
             .await
-            .map_err(|e| Error::Connection(e.to_string()))?;
+            .map_err(|e| match Error::from(e) {
+                Error::Protocol(e) => Error::Connection(e.to_string()),
+                other => other,
+            })?;
+        let capabilities = ServerCapabilities::from_parameters(|name| connection.parameter(name));
+        capabilities.warn_on_version_mismatch();
 
         // Spawn connection handler
         tokio::spawn(async move {
@@ -57,8 +175,87 @@ impl Client {
             }
         });
 
-        info!("Successfully connected to DriftDB");
-        Ok(Self { inner: client })
+        Ok((client, capabilities))
+    }
+
+    /// The server's version and optional features, as it reported them
+    /// when this connection was opened
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::{Client, Feature};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// if client.server_capabilities().supports(Feature::Copy) {
+    ///     // bulk load with COPY
+    /// } else {
+    ///     // fall back to batched INSERTs
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn server_capabilities(&self) -> Arc<ServerCapabilities> {
+        self.capabilities
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// The DriftDB release of the server, e.g. `0.11.0`; for a server that
+    /// isn't DriftDB, its PostgreSQL `server_version`
+    pub async fn server_version(&self) -> Result<String> {
+        let capabilities = self.server_capabilities();
+        capabilities
+            .driftdb_version
+            .clone()
+            .or_else(|| capabilities.server_version.clone())
+            .ok_or_else(|| Error::Query("Server did not report its version".to_string()))
+    }
+
+    /// The current connection
+    fn pg(&self) -> Arc<PgClient> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// The connection to run the next statement on, reconnecting first if
+    /// auto-reconnect is on and the current one is gone
+    async fn connection(&self) -> Result<Arc<PgClient>> {
+        let client = self.pg();
+        if !self.auto_reconnect {
+            return Ok(client);
+        }
+        let idle = self
+            .last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed();
+        if !client.is_closed() && (idle < self.ping_after_idle || self.ping().await) {
+            return Ok(client);
+        }
+
+        if self.in_transaction.swap(false, Ordering::SeqCst) {
+            warn!("Connection lost inside an open transaction");
+            // Leave the dead connection for the next statement to replace
+            return Err(Error::Transaction(
+                "connection lost inside an open transaction; it was rolled back".to_string(),
+            ));
+        }
+        self.reconnect_from(&client).await?;
+        Ok(self.pg())
+    }
+
+    /// Record a statement that ran, tracking transaction boundaries
+    fn finished(&self, sql: &str) {
+        self.touch();
+        if let Some(open) = transaction_effect(sql) {
+            self.in_transaction.store(open, Ordering::SeqCst);
+        }
     }
 
     /// Execute a SQL statement that doesn't return rows
@@ -83,11 +280,8 @@ impl Client {
         // has incomplete support for the PostgreSQL extended query protocol
         // (Parse/Bind/Describe/Execute/Sync message sequence). The simple query
         // protocol sends SQL directly and works reliably for all operations.
-        let messages = self
-            .inner
-            .simple_query(sql)
-            .await
-            .map_err(|e| Error::Query(e.to_string()))?;
+        let messages = self.connection().await?.simple_query(sql).await?;
+        self.finished(sql);
 
         // Count affected rows from CommandComplete messages
         let mut rows = 0u64;
@@ -131,11 +325,8 @@ impl Client {
     ) -> Result<u64> {
         debug!("Executing SQL with {} params: {}", params.len(), sql);
 
-        let rows = self
-            .inner
-            .execute(sql, params)
-            .await
-            .map_err(|e| Error::Query(e.to_string()))?;
+        let rows = self.connection().await?.execute(sql, params).await?;
+        self.finished(sql);
 
         debug!("Affected {} rows", rows);
         Ok(rows)
@@ -149,7 +340,7 @@ impl Client {
     /// method should be preferred once server support is available.
     ///
     /// Replaces $1, $2, etc. placeholders with escaped string values.
-    /// Only supports string, integer, and boolean values.
+    /// Supports every `Value`; bytes are sent as a bytea hex literal.
     ///
     /// # Example
     ///
@@ -172,6 +363,58 @@ impl Client {
         self.execute(&escaped_sql).await
     }
 
+    /// Insert `rows` into `table` as one batch: a single multi-row INSERT
+    /// in its own transaction (or the one already open), which the server
+    /// writes with one batched append at COMMIT. Either every row goes in
+    /// or, on any error, none does. Returns the number of rows inserted.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::Client;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// use driftdb_client::types::Value;
+    /// client.insert_batch(
+    ///     "users",
+    ///     &["id", "name"],
+    ///     &[
+    ///         vec![Value::Int(1), Value::Text("Alice".to_string())],
+    ///         vec![Value::Int(2), Value::Text("Bob".to_string())],
+    ///     ],
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn insert_batch(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: &[Vec<Value>],
+    ) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let sql = Self::batch_insert_sql(table, columns, rows)?;
+        // Inside the caller's transaction the rows go in with its COMMIT
+        if self.in_transaction.load(Ordering::SeqCst) {
+            return self.execute(&sql).await;
+        }
+
+        self.execute("BEGIN").await?;
+        let inserted = match self.execute(&sql).await {
+            Ok(inserted) => inserted,
+            Err(e) => {
+                // The connection may be gone, in which case so is the transaction
+                let _ = self.execute("ROLLBACK").await;
+                return Err(e);
+            }
+        };
+        self.execute("COMMIT").await?;
+        Ok(inserted)
+    }
+
     /// Execute a query and return all rows
     ///
     /// # Example
@@ -193,11 +436,8 @@ impl Client {
 
         // NOTE: We use simple_query() instead of the prepared statement protocol.
         // See execute() method for detailed explanation.
-        let messages = self
-            .inner
-            .simple_query(sql)
-            .await
-            .map_err(|e| Error::Query(e.to_string()))?;
+        let messages = self.connection().await?.simple_query(sql).await?;
+        self.finished(sql);
 
         // Extract rows from messages
         let mut rows = Vec::new();
@@ -210,6 +450,37 @@ impl Client {
         Ok(rows)
     }
 
+    /// Execute a query that must return exactly one row
+    ///
+    /// Fails with [`Error::NoRows`] if it returns none and
+    /// [`Error::TooManyRows`] if it returns more than one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::Client;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// let row = client.query_one("SELECT * FROM users WHERE id = 1").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_one(&self, sql: &str) -> Result<Row> {
+        self.query_opt(sql).await?.ok_or(Error::NoRows)
+    }
+
+    /// Execute a query that returns at most one row
+    ///
+    /// Fails with [`Error::TooManyRows`] if it returns more than one.
+    pub async fn query_opt(&self, sql: &str) -> Result<Option<Row>> {
+        let mut rows = self.query(sql).await?;
+        match rows.len() {
+            0 | 1 => Ok(rows.pop()),
+            n => Err(Error::TooManyRows(n)),
+        }
+    }
+
     /// Execute a query with parameters and return all rows (safe from SQL injection)
     ///
     /// Uses PostgreSQL-style placeholders ($1, $2, etc.) to safely interpolate values.
@@ -240,11 +511,8 @@ impl Client {
     ) -> Result<Vec<Row>> {
         debug!("Querying with {} params: {}", params.len(), sql);
 
-        let pg_rows = self
-            .inner
-            .query(sql, params)
-            .await
-            .map_err(|e| Error::Query(e.to_string()))?;
+        let pg_rows = self.connection().await?.query(sql, params).await?;
+        self.finished(sql);
 
         let rows: Vec<Row> = pg_rows
             .into_iter()
@@ -309,6 +577,100 @@ impl Client {
             .map_err(Error::from)
     }
 
+    /// Run a multi-statement script, such as a migration file
+    ///
+    /// The script is split with [`split_script`](crate::script::split_script),
+    /// so semicolons in strings, dollar-quoted bodies and comments are
+    /// safe. Statements run in order on one connection; the first failure
+    /// stops the script with [`Error::Script`], naming the statement and
+    /// its line. Statements before it have already run: wrap the script in
+    /// `BEGIN`/`COMMIT` to make it all-or-nothing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::{Client, QueryOutcome};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// let outcomes = client
+    ///     .execute_script(&std::fs::read_to_string("migrations/003_users.sql")?)
+    ///     .await?;
+    /// for outcome in outcomes {
+    ///     if let QueryOutcome::Affected(n) = outcome {
+    ///         println!("{} rows affected", n);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_script(&self, script: &str) -> Result<Vec<QueryOutcome>> {
+        let client = self.connection().await?;
+        let mut outcomes = Vec::new();
+        for (index, statement) in split_script(script).into_iter().enumerate() {
+            debug!("Script statement {}: {}", index + 1, statement.sql);
+            let messages = match client.simple_query(&statement.sql).await {
+                Ok(messages) => messages,
+                Err(e) => {
+                    return Err(Error::Script {
+                        index,
+                        line: statement.line,
+                        statement: statement.sql,
+                        source: Box::new(e.into()),
+                    })
+                }
+            };
+            self.finished(&statement.sql);
+
+            let mut rows = Vec::new();
+            let mut affected = 0;
+            for msg in messages {
+                match msg {
+                    tokio_postgres::SimpleQueryMessage::Row(row) => {
+                        rows.push(self.simple_row_to_row(row))
+                    }
+                    tokio_postgres::SimpleQueryMessage::CommandComplete(count) => affected = count,
+                    _ => {}
+                }
+            }
+            outcomes.push(if rows.is_empty() && !returns_rows(&statement.sql) {
+                QueryOutcome::Affected(affected)
+            } else {
+                QueryOutcome::Rows(rows)
+            });
+        }
+        Ok(outcomes)
+    }
+
+    /// Run `sql` unless `cancel` fires first. On cancel the server is
+    /// asked to stop the statement and this returns [`Error::Cancelled`]
+    /// without waiting for it; tokio-postgres discards the abandoned
+    /// statement's responses, so the connection stays usable.
+    pub(crate) async fn query_cancellable(
+        &self,
+        sql: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<Row>> {
+        if cancel.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        let server = self.connection().await?.cancel_token();
+        tokio::select! {
+            // A result that is already in wins over a late cancel
+            biased;
+            rows = self.query(sql) => rows,
+            _ = cancel.cancelled() => {
+                debug!("Cancelling query: {}", sql);
+                tokio::spawn(async move {
+                    if let Err(e) = server.cancel_query(NoTls).await {
+                        warn!("Failed to send cancel request: {}", e);
+                    }
+                });
+                Err(Error::Cancelled)
+            }
+        }
+    }
+
     /// Start building a query with builder pattern
     ///
     /// # Example
@@ -347,7 +709,9 @@ impl Client {
     /// # }
     /// ```
     pub async fn begin(&self) -> Result<Transaction> {
-        Transaction::begin(&self.inner).await
+        let transaction = Transaction::begin(&*self.connection().await?).await?;
+        self.finished("BEGIN");
+        Ok(transaction)
     }
 
     /// Helper function to escape parameters and replace $N placeholders
@@ -357,36 +721,69 @@ impl Client {
         // Replace placeholders in reverse order to avoid issues with $1 vs $10
         for (idx, param) in params.iter().enumerate().rev() {
             let placeholder = format!("${}", idx + 1);
-            let escaped_value = match param {
-                Value::Null => "NULL".to_string(),
-                Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
-                Value::Int(i) => i.to_string(),
-                Value::Float(f) => f.to_string(),
-                Value::Text(s) => {
-                    // Escape single quotes by doubling them (SQL standard)
-                    let escaped = s.replace('\'', "''");
-                    format!("'{}'", escaped)
-                }
-                Value::Bytes(_) => {
-                    return Err(Error::Query(
-                        "Byte arrays are not supported in escaped queries".to_string(),
-                    ));
-                }
-                Value::Json(j) => {
-                    // Serialize JSON and escape it as a string
-                    let json_str = serde_json::to_string(j)
-                        .map_err(|e| Error::Query(format!("Failed to serialize JSON: {}", e)))?;
-                    let escaped = json_str.replace('\'', "''");
-                    format!("'{}'", escaped)
-                }
-            };
-
-            result = result.replace(&placeholder, &escaped_value);
+            result = result.replace(&placeholder, &Self::sql_literal(param)?);
         }
 
         Ok(result)
     }
 
+    /// `value` as an escaped SQL literal
+    fn sql_literal(value: &Value) -> Result<String> {
+        Ok(match value {
+            Value::Null => "NULL".to_string(),
+            Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+            Value::Int(i) => i.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::Text(s) => {
+                // Escape single quotes by doubling them (SQL standard)
+                let escaped = s.replace('\'', "''");
+                format!("'{}'", escaped)
+            }
+            // Hex digits need no escaping
+            Value::Bytes(b) => format!("'{}'", encode_bytea(b)),
+            Value::Json(j) => {
+                // Serialize JSON and escape it as a string
+                let json_str = serde_json::to_string(j)?;
+                let escaped = json_str.replace('\'', "''");
+                format!("'{}'", escaped)
+            }
+        })
+    }
+
+    /// One multi-row INSERT of `rows` into `table`
+    fn batch_insert_sql(table: &str, columns: &[&str], rows: &[Vec<Value>]) -> Result<String> {
+        let mut sql = format!(
+            "INSERT INTO {} ({}) VALUES ",
+            quote_ident(table),
+            columns
+                .iter()
+                .map(|column| quote_ident(column))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        for (i, row) in rows.iter().enumerate() {
+            if row.len() != columns.len() {
+                return Err(Error::Query(format!(
+                    "batch row {} has {} values for {} columns",
+                    i + 1,
+                    row.len(),
+                    columns.len()
+                )));
+            }
+            let values = row
+                .iter()
+                .map(Self::sql_literal)
+                .collect::<Result<Vec<_>>>()?;
+            if i > 0 {
+                sql.push_str(", ");
+            }
+            sql.push('(');
+            sql.push_str(&values.join(", "));
+            sql.push(')');
+        }
+        Ok(sql)
+    }
+
     /// Convert a SimpleQueryRow to our Row type
     fn simple_row_to_row(&self, simple_row: tokio_postgres::SimpleQueryRow) -> Row {
         let columns: Vec<String> = simple_row
@@ -399,8 +796,11 @@ impl Client {
             .map(|idx| {
                 match simple_row.get(idx) {
                     Some(s) => {
-                        // Try to parse as different types
-                        if s == "t" || s == "true" {
+                        // Try to parse as different types. bytea arrives
+                        // in hex format, which text rarely resembles.
+                        if let Some(bytes) = decode_bytea(s) {
+                            Value::Bytes(bytes)
+                        } else if s == "t" || s == "true" {
                             Value::Bool(true)
                         } else if s == "f" || s == "false" {
                             Value::Bool(false)
@@ -429,22 +829,7 @@ impl Client {
             .collect();
 
         let values: Vec<Value> = (0..pg_row.len())
-            .map(|idx| {
-                // Try to get the value as different types
-                if let Ok(v) = pg_row.try_get::<_, Option<bool>>(idx) {
-                    v.map(Value::Bool).unwrap_or(Value::Null)
-                } else if let Ok(v) = pg_row.try_get::<_, Option<i64>>(idx) {
-                    v.map(Value::Int).unwrap_or(Value::Null)
-                } else if let Ok(v) = pg_row.try_get::<_, Option<f64>>(idx) {
-                    v.map(Value::Float).unwrap_or(Value::Null)
-                } else if let Ok(v) = pg_row.try_get::<_, Option<String>>(idx) {
-                    v.map(Value::Text).unwrap_or(Value::Null)
-                } else if let Ok(v) = pg_row.try_get::<_, Option<Vec<u8>>>(idx) {
-                    v.map(Value::Bytes).unwrap_or(Value::Null)
-                } else {
-                    Value::Null
-                }
-            })
+            .map(|idx| pg_row.try_get::<_, Value>(idx).unwrap_or(Value::Null))
             .collect();
 
         Row::new(columns, values)
@@ -476,6 +861,63 @@ impl Client {
             .ok_or_else(|| Error::Query("Failed to get current sequence".to_string()))
     }
 
+    /// Names of the tables in the database, sorted
+    pub async fn list_tables(&self) -> Result<Vec<String>> {
+        let mut tables = self
+            .query("SHOW TABLES")
+            .await?
+            .iter()
+            .map(|row| row.get_by_idx(0))
+            .collect::<Result<Vec<String>>>()?;
+        tables.sort();
+        Ok(tables)
+    }
+
+    /// A table's columns, primary key and indexes, from the server catalog
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::Client;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// let schema = client.describe_table("users").await?;
+    /// for column in &schema.columns {
+    ///     println!("{} {}", column.name, column.data_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn describe_table(&self, table: &str) -> Result<TableSchema> {
+        let columns = self
+            .query(&format!("SHOW COLUMNS FROM {}", quote_ident(table)))
+            .await?
+            .iter()
+            .map(ColumnSchema::from_row)
+            .collect::<Result<Vec<_>>>()?;
+        let indexes = self.list_indexes(table).await?;
+        Ok(TableSchema {
+            name: table.to_string(),
+            primary_key: columns
+                .iter()
+                .filter(|c| c.primary_key)
+                .map(|c| c.name.clone())
+                .collect(),
+            columns,
+            indexes,
+        })
+    }
+
+    /// A table's indexes, the primary key's first
+    pub async fn list_indexes(&self, table: &str) -> Result<Vec<IndexSchema>> {
+        self.query(&format!("SHOW INDEXES FROM {}", quote_ident(table)))
+            .await?
+            .iter()
+            .map(IndexSchema::from_row)
+            .collect()
+    }
+
     /// Close the connection gracefully
     pub async fn close(self) -> Result<()> {
         // The connection will be closed when client is dropped
@@ -484,7 +926,70 @@ impl Client {
     }
 }
 
+/// Whether `sql` opens (`Some(true)`) or ends (`Some(false)`) a
+/// transaction
+fn transaction_effect(sql: &str) -> Option<bool> {
+    let mut words = sql
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_uppercase());
+    match words.next()?.as_str() {
+        "BEGIN" | "START" => Some(true),
+        "COMMIT" | "END" | "ABORT" => Some(false),
+        // ROLLBACK TO SAVEPOINT keeps the transaction open
+        "ROLLBACK" => match words.next().as_deref() {
+            Some("TO") => None,
+            _ => Some(false),
+        },
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // Connection tests require running server - see integration_tests.rs
+
+    use super::*;
+
+    #[test]
+    fn test_transaction_effect() {
+        assert_eq!(transaction_effect("BEGIN"), Some(true));
+        assert_eq!(transaction_effect("  start transaction;"), Some(true));
+        assert_eq!(transaction_effect("COMMIT;"), Some(false));
+        assert_eq!(transaction_effect("rollback"), Some(false));
+        assert_eq!(transaction_effect("ROLLBACK TO SAVEPOINT s1"), None);
+        assert_eq!(transaction_effect("SELECT * FROM users"), None);
+        assert_eq!(transaction_effect(""), None);
+    }
+
+    #[test]
+    fn test_escape_params_bytes() {
+        let sql = Client::escape_params(
+            "INSERT INTO blobs (id, data) VALUES ($1, $2)",
+            &[Value::Int(1), Value::Bytes(vec![0x00, 0x27, 0xff])],
+        )
+        .unwrap();
+        assert_eq!(sql, r"INSERT INTO blobs (id, data) VALUES (1, '\x0027ff')");
+    }
+
+    #[test]
+    fn test_batch_insert_sql() {
+        let sql = Client::batch_insert_sql(
+            "users",
+            &["id", "name"],
+            &[
+                vec![Value::Int(1), Value::Text("O'Reilly".to_string())],
+                vec![Value::Int(2), Value::Null],
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            r#"INSERT INTO "users" ("id", "name") VALUES (1, 'O''Reilly'), (2, NULL)"#
+        );
+
+        assert!(
+            Client::batch_insert_sql("users", &["id", "name"], &[vec![Value::Int(1)]]).is_err()
+        );
+    }
 }
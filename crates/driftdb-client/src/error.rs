@@ -5,6 +5,19 @@ use thiserror::Error;
 /// Result type alias for DriftDB client operations
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// SQLSTATE codes worth branching on
+pub mod sqlstate {
+    pub const NOT_NULL_VIOLATION: &str = "23502";
+    pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+    pub const UNIQUE_VIOLATION: &str = "23505";
+    pub const CHECK_VIOLATION: &str = "23514";
+    pub const DEADLOCK_DETECTED: &str = "40P01";
+    pub const UNDEFINED_TABLE: &str = "42P01";
+    pub const INSUFFICIENT_PRIVILEGE: &str = "42501";
+    pub const TOO_MANY_CONNECTIONS: &str = "53300";
+    pub const QUERY_CANCELED: &str = "57014";
+}
+
 /// Errors that can occur when using the DriftDB client
 #[derive(Debug, Error)]
 pub enum Error {
@@ -12,25 +25,91 @@ pub enum Error {
     #[error("Failed to connect to DriftDB: {0}")]
     Connection(String),
 
-    /// Query execution failed
+    /// The connection to the server was closed
+    #[error("Connection to DriftDB closed")]
+    ConnectionClosed,
+
+    /// The operation did not finish in time
+    #[error("Operation timed out: {0}")]
+    Timeout(String),
+
+    /// The server rejected the credentials
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+
+    /// The server reported an error for a statement
+    #[error("{message} (SQLSTATE {code})")]
+    SqlError {
+        /// PostgreSQL SQLSTATE code, e.g. `23505` for a unique violation
+        code: String,
+        message: String,
+    },
+
+    /// Query execution failed on the client side
     #[error("Query execution failed: {0}")]
     Query(String),
 
+    /// A row has no column of this name
+    #[error("Column \"{0}\" not found in row")]
+    ColumnNotFound(String),
+
+    /// A column's value could not be converted to the requested type
+    #[error("Column \"{column}\": expected {expected}, found {found}")]
+    ColumnType {
+        column: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    /// A column is NULL but the requested type can't hold NULL
+    #[error("Column \"{column}\" is NULL, expected {expected}")]
+    UnexpectedNull {
+        column: String,
+        expected: &'static str,
+    },
+
+    /// A column index past the end of the row
+    #[error("Column index {index} out of range for a row of {columns} columns")]
+    ColumnIndexOutOfRange { index: usize, columns: usize },
+
+    /// A query expected to return a row returned none
+    #[error("Query returned no rows")]
+    NoRows,
+
+    /// A query expected to return at most one row returned more
+    #[error("Query returned {0} rows, expected at most one")]
+    TooManyRows(usize),
+
+    /// The query was cancelled through its `CancellationToken`
+    #[error("Query cancelled")]
+    Cancelled,
+
+    /// A statement of a script failed; the ones before it ran
+    #[error("Statement {} of the script (line {line}) failed: {source}", .index + 1)]
+    Script {
+        /// 0-based position of the statement in the script
+        index: usize,
+        line: usize,
+        statement: String,
+        #[source]
+        source: Box<Error>,
+    },
+
     /// Transaction error
     #[error("Transaction error: {0}")]
     Transaction(String),
 
-    /// Deserialization error
-    #[error("Failed to deserialize result: {0}")]
-    Deserialization(#[from] serde_json::Error),
+    /// Converting a value to or from JSON failed
+    #[error("Failed to serialize value: {0}")]
+    Serialization(#[from] serde_json::Error),
 
     /// Invalid time-travel specification
     #[error("Invalid time-travel specification: {0}")]
     InvalidTimeTravel(String),
 
-    /// PostgreSQL protocol error
+    /// PostgreSQL protocol error not covered by a more specific variant
     #[error("PostgreSQL protocol error: {0}")]
-    Protocol(#[from] tokio_postgres::Error),
+    Protocol(#[source] tokio_postgres::Error),
 
     /// I/O error
     #[error("I/O error: {0}")]
@@ -41,6 +120,65 @@ pub enum Error {
     Other(String),
 }
 
+impl Error {
+    /// The SQLSTATE code of a server-reported error
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::SqlError { code, .. } => Some(code),
+            Error::Script { source, .. } => source.code(),
+            _ => None,
+        }
+    }
+
+    /// Whether the statement violated a unique or primary key constraint
+    pub fn is_unique_violation(&self) -> bool {
+        self.code() == Some(sqlstate::UNIQUE_VIOLATION)
+    }
+
+    /// Whether the server aborted the transaction to break a deadlock.
+    /// The message names the transactions in the cycle and the rows each
+    /// was waiting for; retrying the whole transaction may succeed.
+    pub fn is_deadlock(&self) -> bool {
+        self.code() == Some(sqlstate::DEADLOCK_DETECTED)
+    }
+
+    /// Whether retrying on a new connection might succeed
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            Error::Connection(_) | Error::ConnectionClosed | Error::Timeout(_)
+        ) || self.code() == Some(sqlstate::TOO_MANY_CONNECTIONS)
+    }
+}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(e: tokio_postgres::Error) -> Self {
+        if let Some(db) = e.as_db_error() {
+            let code = db.code().code();
+            return if code.starts_with("28") {
+                Error::Auth(db.message().to_string())
+            } else if code == sqlstate::QUERY_CANCELED {
+                Error::Timeout(db.message().to_string())
+            } else {
+                Error::SqlError {
+                    code: code.to_string(),
+                    message: db.message().to_string(),
+                }
+            };
+        }
+        if e.is_closed() {
+            return Error::ConnectionClosed;
+        }
+        let timed_out = std::error::Error::source(&e)
+            .and_then(|source| source.downcast_ref::<std::io::Error>())
+            .is_some_and(|io| io.kind() == std::io::ErrorKind::TimedOut);
+        if timed_out {
+            return Error::Timeout(e.to_string());
+        }
+        Error::Protocol(e)
+    }
+}
+
 impl From<String> for Error {
     fn from(s: String) -> Self {
         Error::Other(s)
@@ -52,3 +190,70 @@ impl From<&str> for Error {
         Error::Other(s.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_error_code() {
+        let err = Error::SqlError {
+            code: sqlstate::UNIQUE_VIOLATION.to_string(),
+            message: "duplicate key value violates unique constraint".to_string(),
+        };
+        assert_eq!(err.code(), Some("23505"));
+        assert!(err.is_unique_violation());
+        assert!(!err.is_connection_error());
+        assert_eq!(
+            err.to_string(),
+            "duplicate key value violates unique constraint (SQLSTATE 23505)"
+        );
+
+        assert_eq!(Error::NoRows.code(), None);
+        assert!(!err.is_deadlock());
+
+        let deadlock = Error::SqlError {
+            code: sqlstate::DEADLOCK_DETECTED.to_string(),
+            message: "Query error: Deadlock detected: transaction 2 waits for a lock on \
+                      users:1 held by transaction 1; transaction 1 waits for a lock on \
+                      users:2 held by transaction 2; transaction 2 was aborted"
+                .to_string(),
+        };
+        assert!(deadlock.is_deadlock());
+        assert!(!deadlock.is_unique_violation());
+        assert!(!Error::TooManyRows(2).is_unique_violation());
+        assert!(Error::ConnectionClosed.is_connection_error());
+    }
+
+    #[test]
+    fn test_script_error_names_the_statement() {
+        let err = Error::Script {
+            index: 2,
+            line: 14,
+            statement: "INSERT INTO users VALUES (1)".to_string(),
+            source: Box::new(Error::SqlError {
+                code: sqlstate::UNIQUE_VIOLATION.to_string(),
+                message: "duplicate key".to_string(),
+            }),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Statement 3 of the script (line 14) failed: duplicate key (SQLSTATE 23505)"
+        );
+        assert!(err.is_unique_violation());
+    }
+
+    #[test]
+    fn test_source_chaining() {
+        use std::error::Error as _;
+
+        let json_err = serde_json::from_str::<i64>("nope").unwrap_err();
+        let err = Error::from(json_err);
+        assert!(matches!(err, Error::Serialization(_)));
+        assert!(err.source().is_some());
+
+        let io_err = std::io::Error::new(std::io::ErrorKind::TimedOut, "slow");
+        assert!(Error::from(io_err).source().is_some());
+        assert!(Error::NoRows.source().is_none());
+    }
+}
@@ -0,0 +1,231 @@
+//! Explicit row-to-struct mapping
+//!
+//! [`FromRow`] builds a value from a [`Row`] column by column, usually via
+//! `#[derive(FromRow)]`. Each column is converted with [`FromValue`], so a
+//! mismatch names the column and both types instead of surfacing as an
+//! opaque serde error.
+
+use crate::error::Result;
+use crate::types::{decode_bytea, Row, Value};
+
+/// Build a value from a query result row
+///
+/// # Example
+///
+/// ```no_run
+/// # use driftdb_client::{Client, FromRow};
+/// #[derive(FromRow)]
+/// struct User {
+///     id: i64,
+///     #[driftdb(rename = "display_name")]
+///     name: String,
+///     #[driftdb(default)]
+///     nickname: Option<String>,
+/// }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = Client::connect("localhost:5433").await?;
+/// let row = client.query_one("SELECT * FROM users WHERE id = 1").await?;
+/// let user = User::from_row(&row)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait FromRow: Sized {
+    fn from_row(row: &Row) -> Result<Self>;
+}
+
+/// Convert a single column value into a Rust type
+pub trait FromValue: Sized {
+    /// Type name used in mismatch errors
+    const EXPECTED: &'static str;
+
+    /// Convert `value`, or `None` if it isn't this type
+    fn from_value(value: &Value) -> Option<Self>;
+
+    /// What a NULL becomes; an error unless the type can hold it
+    fn from_null() -> Option<Self> {
+        None
+    }
+}
+
+impl Value {
+    /// Name of this value's type, as shown in errors
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "NULL",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Text(_) => "text",
+            Value::Bytes(_) => "bytea",
+            Value::Json(_) => "json",
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    const EXPECTED: &'static str = T::EXPECTED;
+
+    fn from_value(value: &Value) -> Option<Self> {
+        T::from_value(value).map(Some)
+    }
+
+    fn from_null() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl FromValue for Value {
+    const EXPECTED: &'static str = "any value";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+
+    fn from_null() -> Option<Self> {
+        Some(Value::Null)
+    }
+}
+
+impl FromValue for bool {
+    const EXPECTED: &'static str = "bool";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromValue for i64 {
+    const EXPECTED: &'static str = "i64";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64()
+    }
+}
+
+impl FromValue for i32 {
+    const EXPECTED: &'static str = "i32";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64().and_then(|i| i32::try_from(i).ok())
+    }
+}
+
+impl FromValue for i16 {
+    const EXPECTED: &'static str = "i16";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64().and_then(|i| i16::try_from(i).ok())
+    }
+}
+
+impl FromValue for u64 {
+    const EXPECTED: &'static str = "u64";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_i64().and_then(|i| u64::try_from(i).ok())
+    }
+}
+
+impl FromValue for f64 {
+    const EXPECTED: &'static str = "f64";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64()
+    }
+}
+
+impl FromValue for f32 {
+    const EXPECTED: &'static str = "f32";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_f64().map(|f| f as f32)
+    }
+}
+
+impl FromValue for String {
+    const EXPECTED: &'static str = "text";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Text(s) => Some(s.clone()),
+            // The simple query protocol sends every value as text, so text
+            // columns holding digits or t/f arrive parsed
+            Value::Int(i) => Some(i.to_string()),
+            Value::Float(f) => Some(f.to_string()),
+            Value::Bool(b) => Some(if *b { "t" } else { "f" }.to_string()),
+            Value::Json(j) => Some(j.to_string()),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    const EXPECTED: &'static str = "bytea";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Bytes(b) => Some(b.clone()),
+            Value::Text(s) => decode_bytea(s),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for serde_json::Value {
+    const EXPECTED: &'static str = "json";
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Json(j) => Some(j.clone()),
+            Value::Text(s) => serde_json::from_str(s).ok(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert<T: FromValue>(value: Value) -> Option<T> {
+        T::from_value(&value)
+    }
+
+    #[test]
+    fn test_numeric_conversions() {
+        assert_eq!(convert::<i64>(Value::Int(7)), Some(7));
+        assert_eq!(convert::<i32>(Value::Int(7)), Some(7));
+        assert_eq!(convert::<f64>(Value::Int(7)), Some(7.0));
+        assert_eq!(convert::<f32>(Value::Float(9.5)), Some(9.5));
+        assert_eq!(convert::<i64>(Value::Text("7x".to_string())), None);
+    }
+
+    #[test]
+    fn test_out_of_range_integers_are_rejected() {
+        assert_eq!(convert::<u64>(Value::Int(-1)), None);
+        assert_eq!(convert::<i32>(Value::Int(1 << 40)), None);
+        assert_eq!(convert::<i16>(Value::Int(40_000)), None);
+    }
+
+    #[test]
+    fn test_text_bytes_and_json() {
+        assert_eq!(convert::<String>(Value::Int(42)), Some("42".to_string()));
+        assert_eq!(
+            convert::<Vec<u8>>(Value::Text("\\x0aff".to_string())),
+            Some(vec![0x0a, 0xff])
+        );
+        assert_eq!(
+            convert::<serde_json::Value>(Value::Text("{\"a\":1}".to_string())),
+            Some(serde_json::json!({"a": 1}))
+        );
+        assert_eq!(convert::<bool>(Value::Int(1)), None);
+    }
+
+    #[test]
+    fn test_nulls_need_an_option() {
+        assert_eq!(<i64 as FromValue>::from_null(), None);
+        assert_eq!(<Option<i64> as FromValue>::from_null(), Some(None));
+        assert_eq!(<Value as FromValue>::from_null(), Some(Value::Null));
+    }
+}
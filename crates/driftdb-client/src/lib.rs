@@ -6,6 +6,7 @@
 //!
 //! - **Async/await API** - Built on tokio for high performance
 //! - **Type-safe queries** - Deserialize results directly into Rust structs using serde
+//! - **Row mapping** - `#[derive(FromRow)]` maps columns to fields with errors naming the column
 //! - **Time-travel queries** - First-class support for temporal queries
 //! - **Transaction support** - ACID transactions with BEGIN/COMMIT/ROLLBACK
 //! - **Connection pooling** - Efficient connection management (coming soon)
@@ -53,17 +54,27 @@
 //! }
 //! ```
 
+pub mod capabilities;
 pub mod client;
 pub mod error;
+pub mod from_row;
 pub mod query;
+pub mod schema;
+pub mod script;
 pub mod transaction;
 pub mod types;
 
+pub use capabilities::{Feature, ServerCapabilities};
 pub use client::Client;
+pub use driftdb_client_derive::FromRow;
 pub use error::{Error, Result};
+pub use from_row::{FromRow, FromValue};
 pub use query::Query;
+pub use schema::{ColumnSchema, IndexSchema, TableSchema};
+pub use script::QueryOutcome;
+pub use tokio_util::sync::CancellationToken;
 pub use transaction::Transaction;
-pub use types::{Row, TimeTravel, Value};
+pub use types::{FutureTimestamp, Row, TimeTravel, Value};
 
 #[cfg(test)]
 mod tests {
@@ -2,7 +2,8 @@
 
 use crate::client::Client;
 use crate::error::Result;
-use crate::types::{Row, TimeTravel};
+use crate::types::{FutureTimestamp, Row, TimeTravel};
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 
 /// Query builder with support for time-travel queries
@@ -13,6 +14,8 @@ pub struct Query<'a> {
     client: &'a Client,
     sql: String,
     time_travel: Option<TimeTravel>,
+    cancel: Option<CancellationToken>,
+    future_timestamps: FutureTimestamp,
 }
 
 impl<'a> Query<'a> {
@@ -22,6 +25,8 @@ impl<'a> Query<'a> {
             client,
             sql,
             time_travel: None,
+            cancel: None,
+            future_timestamps: FutureTimestamp::default(),
         }
     }
 
@@ -48,6 +53,48 @@ impl<'a> Query<'a> {
         self
     }
 
+    /// How to treat an `as_of` timestamp later than now: rejected with
+    /// [`Error::InvalidTimeTravel`](crate::Error::InvalidTimeTravel) by
+    /// default, or clamped to now
+    pub fn future_timestamps(mut self, policy: FutureTimestamp) -> Self {
+        self.future_timestamps = policy;
+        self
+    }
+
+    /// Cancel the query when `token` fires
+    ///
+    /// The server is sent a cancel request and the query returns
+    /// [`Error::Cancelled`](crate::Error::Cancelled) at once. The server
+    /// abandons a running `SELECT`; a write it has started still
+    /// completes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use driftdb_client::{CancellationToken, Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = Client::connect("localhost:5433").await?;
+    /// let token = CancellationToken::new();
+    /// // e.g. fired when the HTTP request that wanted the rows is dropped
+    /// let _guard = token.clone().drop_guard();
+    /// match client
+    ///     .query_builder("SELECT * FROM events")
+    ///     .with_cancel(token)
+    ///     .execute()
+    ///     .await
+    /// {
+    ///     Err(Error::Cancelled) => println!("gave up"),
+    ///     rows => println!("{} rows", rows?.len()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_cancel(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
     /// Execute the query and return all rows
     ///
     /// # Example
@@ -65,9 +112,9 @@ impl<'a> Query<'a> {
     /// # }
     /// ```
     pub async fn execute(self) -> Result<Vec<Row>> {
-        let sql = self.build_sql();
+        let sql = self.build_sql()?;
         debug!("Executing query: {}", sql);
-        self.client.query(&sql).await
+        self.run(&sql).await
     }
 
     /// Execute the query and deserialize into typed structs
@@ -91,14 +138,30 @@ impl<'a> Query<'a> {
     /// # }
     /// ```
     pub async fn execute_as<T: serde::de::DeserializeOwned>(self) -> Result<Vec<T>> {
-        let sql = self.build_sql();
+        let sql = self.build_sql()?;
         debug!("Executing typed query: {}", sql);
-        self.client.query_as(&sql).await
+        self.run(&sql)
+            .await?
+            .into_iter()
+            .map(|row| row.deserialize())
+            .collect::<std::result::Result<Vec<T>, _>>()
+            .map_err(Into::into)
+    }
+
+    async fn run(&self, sql: &str) -> Result<Vec<Row>> {
+        match &self.cancel {
+            Some(token) => self.client.query_cancellable(sql, token).await,
+            None => self.client.query(sql).await,
+        }
     }
 
     /// Build the final SQL with time-travel clause
-    fn build_sql(&self) -> String {
-        match &self.time_travel {
+    fn build_sql(&self) -> Result<String> {
+        let time_travel = match &self.time_travel {
+            Some(tt) => Some(tt.resolve(chrono::Utc::now(), self.future_timestamps)?),
+            None => None,
+        };
+        Ok(match &time_travel {
             Some(tt) => {
                 // Insert time-travel clause before WHERE/ORDER BY/LIMIT
                 // Simple approach: append after FROM clause
@@ -138,7 +201,7 @@ impl<'a> Query<'a> {
                 }
             }
             None => self.sql.clone(),
-        }
+        })
     }
 }
 
@@ -0,0 +1,156 @@
+//! Schema introspection
+//!
+//! [`Client::describe_table`](crate::Client::describe_table) and friends
+//! read the server's catalog (`SHOW TABLES`, `SHOW COLUMNS FROM`,
+//! `SHOW INDEXES FROM`) into these types, so tools like migration runners
+//! don't have to parse DDL or catalog rows themselves.
+
+use crate::error::Result;
+use crate::from_row::FromRow;
+use crate::types::Row;
+use serde::{Deserialize, Serialize};
+
+/// A table's columns and indexes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    pub name: String,
+    /// In declaration order
+    pub columns: Vec<ColumnSchema>,
+    /// Primary key columns
+    pub primary_key: Vec<String>,
+    /// The primary key's index first
+    pub indexes: Vec<IndexSchema>,
+}
+
+impl TableSchema {
+    /// The column called `name`
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|c| c.name == name)
+    }
+}
+
+/// One column of a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// Declared type, lowercased, e.g. `integer` or `varchar(255)`
+    pub data_type: String,
+    pub nullable: bool,
+    /// `DEFAULT` expression as SQL text
+    pub default: Option<String>,
+    pub primary_key: bool,
+}
+
+impl FromRow for ColumnSchema {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            name: row.get("column_name")?,
+            data_type: row.get("data_type")?,
+            nullable: row.get("is_nullable")?,
+            default: row.get("column_default")?,
+            primary_key: row.get("is_primary_key")?,
+        })
+    }
+}
+
+/// One index of a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexSchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    /// `btree`, or `gin` for JSONB containment indexes
+    pub method: String,
+    pub unique: bool,
+    pub primary: bool,
+}
+
+impl FromRow for IndexSchema {
+    fn from_row(row: &Row) -> Result<Self> {
+        Ok(Self {
+            name: row.get("index_name")?,
+            columns: vec![row.get("column_name")?],
+            method: row.get("method")?,
+            unique: row.get("is_unique")?,
+            primary: row.get("is_primary")?,
+        })
+    }
+}
+
+/// `name` as a quoted identifier for a catalog command
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Value;
+
+    fn row(columns: &[&str], values: Vec<Value>) -> Row {
+        Row::new(columns.iter().map(|c| c.to_string()).collect(), values)
+    }
+
+    #[test]
+    fn test_catalog_rows() {
+        // As the simple query protocol delivers them: booleans as t/f,
+        // digits parsed as numbers
+        let column = ColumnSchema::from_row(&row(
+            &[
+                "column_name",
+                "data_type",
+                "is_nullable",
+                "column_default",
+                "is_primary_key",
+            ],
+            vec![
+                Value::Text("visits".to_string()),
+                Value::Text("integer".to_string()),
+                Value::Bool(true),
+                Value::Int(0),
+                Value::Bool(false),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(column.default.as_deref(), Some("0"));
+        assert!(column.nullable);
+
+        let index = IndexSchema::from_row(&row(
+            &[
+                "index_name",
+                "column_name",
+                "method",
+                "is_unique",
+                "is_primary",
+            ],
+            vec![
+                Value::Text("users_pkey".to_string()),
+                Value::Text("id".to_string()),
+                Value::Text("btree".to_string()),
+                Value::Bool(true),
+                Value::Bool(true),
+            ],
+        ))
+        .unwrap();
+        assert_eq!(index.columns, ["id"]);
+    }
+
+    #[test]
+    fn test_schema_serializes() {
+        let schema = TableSchema {
+            name: "users".to_string(),
+            columns: vec![ColumnSchema {
+                name: "id".to_string(),
+                data_type: "integer".to_string(),
+                nullable: false,
+                default: None,
+                primary_key: true,
+            }],
+            primary_key: vec!["id".to_string()],
+            indexes: vec![],
+        };
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["columns"][0]["data_type"], "integer");
+        assert_eq!(serde_json::from_value::<TableSchema>(json).unwrap(), schema);
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+}
@@ -0,0 +1,274 @@
+//! Multi-statement scripts
+//!
+//! [`Client::execute_script`](crate::Client::execute_script) splits a
+//! script with [`split_script`] and runs the statements one at a time, so a
+//! failure can name the statement that caused it.
+
+use crate::types::Row;
+
+/// What one statement of a script produced
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryOutcome {
+    /// Rows from a query, possibly none
+    Rows(Vec<Row>),
+    /// Rows affected by any other statement
+    Affected(u64),
+}
+
+impl QueryOutcome {
+    /// The rows, if the statement was a query
+    pub fn rows(&self) -> Option<&[Row]> {
+        match self {
+            QueryOutcome::Rows(rows) => Some(rows),
+            QueryOutcome::Affected(_) => None,
+        }
+    }
+
+    /// The affected row count, if the statement wasn't a query
+    pub fn affected(&self) -> Option<u64> {
+        match self {
+            QueryOutcome::Rows(_) => None,
+            QueryOutcome::Affected(count) => Some(*count),
+        }
+    }
+}
+
+/// One statement of a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStatement {
+    /// The statement without its terminating `;` or comments
+    pub sql: String,
+    /// 1-based line of the script the statement starts on
+    pub line: usize,
+}
+
+/// Split `script` into statements at top-level semicolons
+///
+/// Semicolons inside quoted strings and identifiers, `E'...'` strings,
+/// dollar-quoted bodies (`$$ ... $$`, `$fn$ ... $fn$`) and comments don't
+/// end a statement. Comments are removed, since the server may reject
+/// statements containing them; statements left empty are skipped.
+pub fn split_script(script: &str) -> Vec<ScriptStatement> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut start_line = None;
+    let mut line = 1;
+    let mut i = 0;
+
+    let finish = |current: &mut String, start_line: &mut Option<usize>, out: &mut Vec<_>| {
+        if let Some(line) = start_line.take() {
+            out.push(ScriptStatement {
+                sql: current.trim().to_string(),
+                line,
+            });
+        }
+        current.clear();
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        // Comments become whitespace
+        if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            current.push(' ');
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            // Block comments nest in PostgreSQL
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+            }
+            current.push(' ');
+            continue;
+        }
+
+        if c == ';' {
+            finish(&mut current, &mut start_line, &mut statements);
+            i += 1;
+            continue;
+        }
+
+        if !c.is_whitespace() && start_line.is_none() {
+            start_line = Some(line);
+        }
+
+        // Everything up to `end` is copied verbatim
+        let end = match c {
+            '\'' => {
+                let escapes = i > 0
+                    && matches!(chars[i - 1], 'E' | 'e')
+                    && (i < 2 || !is_ident_char(chars[i - 2]));
+                quoted_end(&chars, i, '\'', escapes)
+            }
+            '"' => quoted_end(&chars, i, '"', false),
+            '$' if i == 0 || !is_ident_char(chars[i - 1]) => {
+                dollar_quoted_end(&chars, i).unwrap_or(i + 1)
+            }
+            _ => i + 1,
+        };
+        for &c in &chars[i..end] {
+            if c == '\n' {
+                line += 1;
+            }
+            current.push(c);
+        }
+        i = end;
+    }
+    finish(&mut current, &mut start_line, &mut statements);
+    statements
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// End of the string opened by `quote` at `start`. A doubled quote is part
+/// of the string; so is a backslash-escaped one in an `E'...'` string.
+fn quoted_end(chars: &[char], start: usize, quote: char, escapes: bool) -> usize {
+    let mut i = start + 1;
+    while i < chars.len() {
+        if escapes && chars[i] == '\\' {
+            i += 2;
+        } else if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    chars.len()
+}
+
+/// End of the dollar-quoted string whose opening tag starts at `start`, or
+/// `None` if no tag starts there (e.g. a `$1` parameter)
+fn dollar_quoted_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < chars.len() && chars[i] != '$' {
+        let valid = chars[i] == '_'
+            || chars[i].is_alphabetic()
+            || (i > start + 1 && chars[i].is_ascii_digit());
+        if !valid {
+            return None;
+        }
+        i += 1;
+    }
+    if i >= chars.len() {
+        return None;
+    }
+    let tag = &chars[start..=i];
+    let body = i + 1;
+    Some(
+        (body..chars.len())
+            .find(|&j| chars[j..].starts_with(tag))
+            .map_or(chars.len(), |j| j + tag.len()),
+    )
+}
+
+/// Whether a statement returns rows even when it returns none
+pub(crate) fn returns_rows(sql: &str) -> bool {
+    let keyword = sql
+        .split(|c: char| c.is_whitespace() || c == '(')
+        .find(|w| !w.is_empty())
+        .unwrap_or("")
+        .to_ascii_uppercase();
+    matches!(
+        keyword.as_str(),
+        "SELECT" | "WITH" | "SHOW" | "VALUES" | "TABLE" | "EXPLAIN"
+    ) || sql.to_ascii_uppercase().contains(" RETURNING ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqls(script: &str) -> Vec<String> {
+        split_script(script).into_iter().map(|s| s.sql).collect()
+    }
+
+    #[test]
+    fn test_splits_at_top_level_semicolons() {
+        assert_eq!(
+            sqls("CREATE TABLE t (id INT PRIMARY KEY);\nINSERT INTO t VALUES (1);;  \n"),
+            [
+                "CREATE TABLE t (id INT PRIMARY KEY)",
+                "INSERT INTO t VALUES (1)"
+            ]
+        );
+        assert_eq!(sqls("SELECT 1"), ["SELECT 1"]);
+        assert!(sqls("  ;\n -- nothing here\n").is_empty());
+    }
+
+    #[test]
+    fn test_quotes_keep_semicolons() {
+        assert_eq!(
+            sqls("INSERT INTO t VALUES ('a;b', 'it''s; fine'); SELECT \"odd;name\" FROM t"),
+            [
+                "INSERT INTO t VALUES ('a;b', 'it''s; fine')",
+                "SELECT \"odd;name\" FROM t"
+            ]
+        );
+        assert_eq!(
+            sqls(r"SELECT E'\';' AS x; SELECT 2"),
+            [r"SELECT E'\';' AS x", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_dollar_quoted_bodies_are_not_split() {
+        let script = "CREATE FUNCTION f() RETURNS INT AS $body$\n  SELECT 1; SELECT $$x;y$$;\n$body$ LANGUAGE sql;\nSELECT $1, a$b FROM t; SELECT $$;$$";
+        assert_eq!(
+            sqls(script),
+            [
+                "CREATE FUNCTION f() RETURNS INT AS $body$\n  SELECT 1; SELECT $$x;y$$;\n$body$ LANGUAGE sql",
+                "SELECT $1, a$b FROM t",
+                "SELECT $$;$$"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments_are_removed_and_lines_tracked() {
+        let script = "-- migration 3\n/* add users; /* nested; */ table */\nCREATE TABLE users (id INT); -- trailing;\n\nINSERT INTO users VALUES ('--not a comment');";
+        let statements = split_script(script);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0].sql, "CREATE TABLE users (id INT)");
+        assert_eq!(statements[0].line, 3);
+        assert_eq!(
+            statements[1].sql,
+            "INSERT INTO users VALUES ('--not a comment')"
+        );
+        assert_eq!(statements[1].line, 5);
+    }
+
+    #[test]
+    fn test_returns_rows() {
+        assert!(returns_rows("select * from t"));
+        assert!(returns_rows("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(returns_rows("(SELECT 1)"));
+        assert!(returns_rows("DELETE FROM t WHERE id = 1 RETURNING id"));
+        assert!(!returns_rows("UPDATE t SET a = 1"));
+    }
+}
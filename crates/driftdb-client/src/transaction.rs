@@ -24,10 +24,7 @@ impl Transaction {
         // Need to use a workaround since we can't store the lifetime easily
         // In practice, this would require refactoring the Client to support this better
         // For now, we'll execute BEGIN manually
-        client
-            .execute("BEGIN", &[])
-            .await
-            .map_err(|e| Error::Transaction(format!("Failed to begin transaction: {}", e)))?;
+        client.execute("BEGIN", &[]).await?;
 
         Ok(Self {
             _marker: std::marker::PhantomData,
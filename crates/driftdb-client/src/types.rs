@@ -1,7 +1,14 @@
 //! Core types for the DriftDB client library
 
+use crate::error::Error;
+use crate::from_row::FromValue;
+use bytes::BytesMut;
+use chrono::{DateTime, SecondsFormat, Utc};
+use postgres_types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::time::Duration;
 
 /// Time-travel query specification
 ///
@@ -11,8 +18,11 @@ pub enum TimeTravel {
     /// Query at a specific sequence number
     Sequence(u64),
 
-    /// Query at a specific timestamp (ISO 8601 format)
-    Timestamp(String),
+    /// Query at a specific point in time
+    Timestamp(DateTime<Utc>),
+
+    /// Query the state from this long ago, e.g. five minutes
+    Relative(Duration),
 
     /// Query between two sequence numbers
     Between { start: u64, end: u64 },
@@ -26,13 +36,57 @@ impl TimeTravel {
     pub fn to_sql(&self) -> String {
         match self {
             TimeTravel::Sequence(seq) => format!("FOR SYSTEM_TIME AS OF @SEQ:{}", seq),
-            TimeTravel::Timestamp(ts) => format!("FOR SYSTEM_TIME AS OF '{}'", ts),
+            TimeTravel::Timestamp(ts) => format!(
+                "FOR SYSTEM_TIME AS OF '{}'",
+                ts.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            ),
+            TimeTravel::Relative(ago) => match relative_to(Utc::now(), *ago) {
+                Some(ts) => TimeTravel::Timestamp(ts).to_sql(),
+                // Before the earliest representable time, so before any data
+                None => "FOR SYSTEM_TIME AS OF @SEQ:0".to_string(),
+            },
             TimeTravel::Between { start, end } => {
                 format!("FOR SYSTEM_TIME BETWEEN @SEQ:{} AND @SEQ:{}", start, end)
             }
             TimeTravel::All => "FOR SYSTEM_TIME ALL".to_string(),
         }
     }
+
+    /// Pin a relative time to a timestamp and check it isn't later than
+    /// `now`, handling a future one as `policy` says
+    pub fn resolve(&self, now: DateTime<Utc>, policy: FutureTimestamp) -> Result<Self, Error> {
+        let ts = match self {
+            TimeTravel::Timestamp(ts) => *ts,
+            TimeTravel::Relative(ago) => relative_to(now, *ago).ok_or_else(|| {
+                Error::InvalidTimeTravel(format!("{:?} ago is out of range", ago))
+            })?,
+            other => return Ok(other.clone()),
+        };
+        if ts <= now {
+            return Ok(TimeTravel::Timestamp(ts));
+        }
+        match policy {
+            FutureTimestamp::Reject => Err(Error::InvalidTimeTravel(format!(
+                "{} is in the future",
+                ts.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            ))),
+            FutureTimestamp::Clamp => Ok(TimeTravel::Timestamp(now)),
+        }
+    }
+}
+
+/// What a time-travel query does with a timestamp in the future
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FutureTimestamp {
+    /// Fail with [`Error::InvalidTimeTravel`]
+    #[default]
+    Reject,
+    /// Query the current state instead
+    Clamp,
+}
+
+fn relative_to(now: DateTime<Utc>, ago: Duration) -> Option<DateTime<Utc>> {
+    now.checked_sub_signed(chrono::Duration::from_std(ago).ok()?)
 }
 
 /// A value returned from a query
@@ -82,14 +136,122 @@ impl Value {
         }
     }
 
+    /// Try to get the bytes of a `bytea` value
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
     /// Check if value is null
     pub fn is_null(&self) -> bool {
         matches!(self, Value::Null)
     }
 }
 
+impl From<Vec<u8>> for Value {
+    fn from(bytes: Vec<u8>) -> Self {
+        Value::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(bytes: &[u8]) -> Self {
+        Value::Bytes(bytes.to_vec())
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn StdError + Sync + Send>> {
+        match self {
+            Value::Null => Ok(IsNull::Yes),
+            Value::Bool(b) => b.to_sql(ty, out),
+            Value::Int(i) => match *ty {
+                Type::INT2 => i16::try_from(*i)?.to_sql(ty, out),
+                Type::INT4 => i32::try_from(*i)?.to_sql(ty, out),
+                _ => i.to_sql(ty, out),
+            },
+            Value::Float(f) => match *ty {
+                Type::FLOAT4 => (*f as f32).to_sql(ty, out),
+                _ => f.to_sql(ty, out),
+            },
+            Value::Text(s) => s.to_sql(ty, out),
+            Value::Bytes(b) => b.to_sql(ty, out),
+            Value::Json(j) => j.to_string().to_sql(ty, out),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <bool as ToSql>::accepts(ty)
+            || <i64 as ToSql>::accepts(ty)
+            || <i32 as ToSql>::accepts(ty)
+            || <i16 as ToSql>::accepts(ty)
+            || <f64 as ToSql>::accepts(ty)
+            || <f32 as ToSql>::accepts(ty)
+            || <String as ToSql>::accepts(ty)
+            || <Vec<u8> as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for Value {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(match *ty {
+            Type::BOOL => Value::Bool(bool::from_sql(ty, raw)?),
+            Type::INT2 => Value::Int(i16::from_sql(ty, raw)? as i64),
+            Type::INT4 => Value::Int(i32::from_sql(ty, raw)? as i64),
+            Type::INT8 => Value::Int(i64::from_sql(ty, raw)?),
+            Type::FLOAT4 => Value::Float(f32::from_sql(ty, raw)? as f64),
+            Type::FLOAT8 => Value::Float(f64::from_sql(ty, raw)?),
+            Type::BYTEA => Value::Bytes(Vec::<u8>::from_sql(ty, raw)?),
+            Type::JSON => Value::Json(serde_json::from_slice(raw)?),
+            // Binary jsonb carries a version byte before the text
+            Type::JSONB => Value::Json(serde_json::from_slice(raw.get(1..).unwrap_or(&[]))?),
+            _ => Value::Text(String::from_sql(ty, raw)?),
+        })
+    }
+
+    fn from_sql_null(_ty: &Type) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        Ok(Value::Null)
+    }
+
+    accepts!(
+        BOOL, INT2, INT4, INT8, FLOAT4, FLOAT8, BYTEA, JSON, JSONB, TEXT, VARCHAR, BPCHAR, NAME,
+        UNKNOWN
+    );
+}
+
+/// Encode bytes in PostgreSQL's bytea hex format (`\x` then two hex digits
+/// per byte)
+pub fn encode_bytea(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("\\x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+/// Decode text in PostgreSQL's bytea hex format, or `None` if it isn't
+pub fn decode_bytea(text: &str) -> Option<Vec<u8>> {
+    let hex = text.strip_prefix("\\x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 /// A row returned from a query
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Row {
     columns: Vec<String>,
     values: Vec<Value>,
@@ -101,19 +263,88 @@ impl Row {
         Self { columns, values }
     }
 
-    /// Get value by column name
-    pub fn get(&self, column: &str) -> Option<&Value> {
-        self.columns
-            .iter()
-            .position(|c| c == column)
+    /// Position of `column`. An exact match wins; otherwise the name is
+    /// matched ignoring ASCII case, since the server returns names as
+    /// written in the query while PostgreSQL folds unquoted ones to lower
+    /// case.
+    fn column_index(&self, column: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == column).or_else(|| {
+            self.columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(column))
+        })
+    }
+
+    /// Get the raw value of a column by name
+    pub fn value(&self, column: &str) -> Option<&Value> {
+        self.column_index(column)
             .and_then(|idx| self.values.get(idx))
     }
 
-    /// Get value by index
+    /// Get the raw value of a column by index
     pub fn get_idx(&self, idx: usize) -> Option<&Value> {
         self.values.get(idx)
     }
 
+    /// Get a column converted to `T`
+    ///
+    /// Fails with [`Error::ColumnNotFound`] if there is no such column,
+    /// [`Error::UnexpectedNull`] if it is NULL and `T` is not an `Option`,
+    /// and [`Error::ColumnType`] if its value is of another type.
+    ///
+    /// ```
+    /// # use driftdb_client::{Row, Value};
+    /// # fn main() -> driftdb_client::Result<()> {
+    /// let row = Row::new(vec!["count".to_string()], vec![Value::Int(3)]);
+    /// let count: i64 = row.get("count")?;
+    /// # assert_eq!(count, 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get<T: FromValue>(&self, column: &str) -> crate::error::Result<T> {
+        let idx = self
+            .column_index(column)
+            .ok_or_else(|| Error::ColumnNotFound(column.to_string()))?;
+        self.convert(idx)
+    }
+
+    /// Get a column converted to `T`, or `None` if it is missing, NULL or
+    /// of another type
+    pub fn try_get<T: FromValue>(&self, column: &str) -> Option<T> {
+        self.get::<Option<T>>(column).ok().flatten()
+    }
+
+    /// Get the column at `idx` converted to `T`
+    pub fn get_by_idx<T: FromValue>(&self, idx: usize) -> crate::error::Result<T> {
+        if idx >= self.values.len() {
+            return Err(Error::ColumnIndexOutOfRange {
+                index: idx,
+                columns: self.values.len(),
+            });
+        }
+        self.convert(idx)
+    }
+
+    fn convert<T: FromValue>(&self, idx: usize) -> crate::error::Result<T> {
+        let column = || {
+            self.columns
+                .get(idx)
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", idx))
+        };
+        match &self.values[idx] {
+            Value::Null => T::from_null().ok_or_else(|| Error::UnexpectedNull {
+                column: column(),
+                expected: T::EXPECTED,
+            }),
+            value => T::from_value(value).ok_or_else(|| Error::ColumnType {
+                column: column(),
+                expected: T::EXPECTED,
+                found: value.type_name(),
+            }),
+        }
+    }
+
     /// Get all column names
     pub fn columns(&self) -> &[String] {
         &self.columns
@@ -151,10 +382,16 @@ mod tests {
             TimeTravel::Sequence(42).to_sql(),
             "FOR SYSTEM_TIME AS OF @SEQ:42"
         );
+        let ts = "2025-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
         assert_eq!(
-            TimeTravel::Timestamp("2025-01-01T00:00:00Z".to_string()).to_sql(),
+            TimeTravel::Timestamp(ts).to_sql(),
             "FOR SYSTEM_TIME AS OF '2025-01-01T00:00:00Z'"
         );
+        let ts = "2025-01-01T00:00:00.250Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            TimeTravel::Timestamp(ts).to_sql(),
+            "FOR SYSTEM_TIME AS OF '2025-01-01T00:00:00.250Z'"
+        );
         assert_eq!(
             TimeTravel::Between { start: 10, end: 20 }.to_sql(),
             "FOR SYSTEM_TIME BETWEEN @SEQ:10 AND @SEQ:20"
@@ -162,6 +399,44 @@ mod tests {
         assert_eq!(TimeTravel::All.to_sql(), "FOR SYSTEM_TIME ALL");
     }
 
+    #[test]
+    fn test_resolve_time_travel() {
+        let now = "2025-06-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let resolve = |tt: TimeTravel, policy| match tt.resolve(now, policy) {
+            Ok(TimeTravel::Timestamp(ts)) => Ok(ts.to_rfc3339()),
+            Ok(other) => Ok(other.to_sql()),
+            Err(e) => Err(e.to_string()),
+        };
+
+        assert_eq!(
+            resolve(
+                TimeTravel::Relative(Duration::from_secs(300)),
+                FutureTimestamp::Reject
+            ),
+            Ok("2025-06-01T11:55:00+00:00".to_string())
+        );
+        assert!(TimeTravel::Relative(Duration::MAX)
+            .resolve(now, FutureTimestamp::Reject)
+            .is_err());
+
+        let future = TimeTravel::Timestamp(now + chrono::Duration::hours(1));
+        assert_eq!(
+            resolve(future.clone(), FutureTimestamp::Reject),
+            Err(
+                "Invalid time-travel specification: 2025-06-01T13:00:00Z is in the future"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            resolve(future, FutureTimestamp::Clamp),
+            Ok("2025-06-01T12:00:00+00:00".to_string())
+        );
+        assert_eq!(
+            resolve(TimeTravel::Sequence(7), FutureTimestamp::Reject),
+            Ok("FOR SYSTEM_TIME AS OF @SEQ:7".to_string())
+        );
+    }
+
     #[test]
     fn test_value_conversions() {
         let v = Value::Int(42);
@@ -174,6 +449,45 @@ mod tests {
         assert_eq!(v.as_i64(), None);
     }
 
+    #[test]
+    fn test_bytes_value() {
+        let v = Value::from(vec![0xde, 0xad, 0x00]);
+        assert_eq!(v.as_bytes(), Some(&[0xde, 0xad, 0x00][..]));
+        assert_eq!(v.as_str(), None);
+        assert_eq!(Value::Text("x".to_string()).as_bytes(), None);
+    }
+
+    #[test]
+    fn test_bytea_hex_round_trip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let text = encode_bytea(&bytes);
+        assert!(text.starts_with("\\x00010203"));
+        assert_eq!(decode_bytea(&text), Some(bytes));
+        assert_eq!(decode_bytea("\\x"), Some(vec![]));
+        assert_eq!(decode_bytea("\\xabc"), None);
+        assert_eq!(decode_bytea("\\xzz"), None);
+        assert_eq!(decode_bytea("plain text"), None);
+    }
+
+    #[test]
+    fn test_bytes_to_and_from_sql() {
+        let mut buf = BytesMut::new();
+        let v = Value::Bytes(vec![1, 2, 3]);
+        assert!(matches!(
+            v.to_sql(&Type::BYTEA, &mut buf).unwrap(),
+            IsNull::No
+        ));
+        assert_eq!(&buf[..], &[1, 2, 3]);
+        assert_eq!(Value::from_sql(&Type::BYTEA, &buf).unwrap(), v);
+
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            Value::Null.to_sql(&Type::BYTEA, &mut buf).unwrap(),
+            IsNull::Yes
+        ));
+        assert_eq!(Value::from_sql_null(&Type::BYTEA).unwrap(), Value::Null);
+    }
+
     #[test]
     fn test_row_access() {
         let row = Row::new(
@@ -181,8 +495,70 @@ mod tests {
             vec![Value::Int(1), Value::Text("Alice".to_string())],
         );
 
-        assert_eq!(row.get("id").and_then(|v| v.as_i64()), Some(1));
-        assert_eq!(row.get("name").and_then(|v| v.as_str()), Some("Alice"));
-        assert_eq!(row.get("missing"), None);
+        assert_eq!(row.value("id").and_then(|v| v.as_i64()), Some(1));
+        assert_eq!(row.value("name").and_then(|v| v.as_str()), Some("Alice"));
+        assert_eq!(row.value("missing"), None);
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        let row = Row::new(
+            vec!["count".to_string(), "Name".to_string(), "note".to_string()],
+            vec![Value::Int(3), Value::Text("Alice".to_string()), Value::Null],
+        );
+
+        let count: i64 = row.get("count").unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(row.get::<String>("Name").unwrap(), "Alice");
+        assert_eq!(row.get::<Option<String>>("note").unwrap(), None);
+        assert_eq!(row.get_by_idx::<i32>(0).unwrap(), 3);
+        assert_eq!(row.try_get::<i64>("count"), Some(3));
+        assert_eq!(row.try_get::<i64>("Name"), None);
+        assert_eq!(row.try_get::<String>("note"), None);
+        assert_eq!(row.try_get::<String>("missing"), None);
+    }
+
+    #[test]
+    fn test_column_names_match_case_insensitively() {
+        let row = Row::new(
+            vec![
+                "total".to_string(),
+                "Total".to_string(),
+                "Mixed".to_string(),
+            ],
+            vec![Value::Int(1), Value::Int(2), Value::Int(3)],
+        );
+        // An exact match wins over a case-insensitive one
+        assert_eq!(row.get::<i64>("Total").unwrap(), 2);
+        assert_eq!(row.get::<i64>("total").unwrap(), 1);
+        assert_eq!(row.get::<i64>("mixed").unwrap(), 3);
+        assert_eq!(row.get::<i64>("TOTAL").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_getter_errors_are_distinct() {
+        let row = Row::new(
+            vec!["id".to_string(), "note".to_string()],
+            vec![Value::Text("x".to_string()), Value::Null],
+        );
+        assert!(matches!(
+            row.get::<i64>("missing"),
+            Err(Error::ColumnNotFound(c)) if c == "missing"
+        ));
+        assert!(matches!(
+            row.get::<String>("note"),
+            Err(Error::UnexpectedNull { column, expected: "text" }) if column == "note"
+        ));
+        assert!(matches!(
+            row.get::<i64>("id"),
+            Err(Error::ColumnType { column, expected: "i64", found: "text" }) if column == "id"
+        ));
+        assert!(matches!(
+            row.get_by_idx::<i64>(5),
+            Err(Error::ColumnIndexOutOfRange {
+                index: 5,
+                columns: 2
+            })
+        ));
     }
 }
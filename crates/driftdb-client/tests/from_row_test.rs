@@ -0,0 +1,98 @@
+//! `#[derive(FromRow)]`: column mapping, renames, defaults and errors
+//! naming the offending column. Runs without a server.
+
+use driftdb_client::{Error, FromRow, Row, Value};
+
+#[derive(Debug, PartialEq, FromRow)]
+struct User {
+    id: i64,
+    #[driftdb(rename = "display_name")]
+    name: String,
+    email: Option<String>,
+    #[driftdb(default)]
+    login_count: i32,
+    #[driftdb(default)]
+    avatar: Option<Vec<u8>>,
+}
+
+fn row(columns: &[&str], values: Vec<Value>) -> Row {
+    Row::new(columns.iter().map(|c| c.to_string()).collect(), values)
+}
+
+#[test]
+fn maps_columns_to_fields() {
+    let r = row(
+        &["id", "display_name", "email", "login_count", "avatar"],
+        vec![
+            Value::Int(1),
+            Value::Text("Alice".to_string()),
+            Value::Null,
+            Value::Int(42),
+            Value::Bytes(vec![0x89, 0x50]),
+        ],
+    );
+    assert_eq!(
+        User::from_row(&r).unwrap(),
+        User {
+            id: 1,
+            name: "Alice".to_string(),
+            email: None,
+            login_count: 42,
+            avatar: Some(vec![0x89, 0x50]),
+        }
+    );
+}
+
+#[test]
+fn defaults_fill_missing_and_null_columns() {
+    let r = row(
+        &["id", "display_name", "email", "login_count"],
+        vec![
+            Value::Int(2),
+            Value::Text("Bob".to_string()),
+            Value::Text("bob@example.com".to_string()),
+            Value::Null,
+        ],
+    );
+    let user = User::from_row(&r).unwrap();
+    assert_eq!(user.login_count, 0);
+    assert_eq!(user.avatar, None);
+    assert_eq!(user.email.as_deref(), Some("bob@example.com"));
+}
+
+#[test]
+fn errors_name_the_offending_column() {
+    // Renamed column missing: the error names the column, not the field
+    let r = row(&["id", "email"], vec![Value::Int(3), Value::Null]);
+    match User::from_row(&r).unwrap_err() {
+        Error::ColumnNotFound(column) => assert_eq!(column, "display_name"),
+        other => panic!("expected ColumnNotFound, got {:?}", other),
+    }
+
+    let r = row(
+        &["id", "display_name", "email"],
+        vec![
+            Value::Text("three".to_string()),
+            Value::Text("Carol".to_string()),
+            Value::Null,
+        ],
+    );
+    let err = User::from_row(&r).unwrap_err();
+    assert_eq!(err.to_string(), "Column \"id\": expected i64, found text");
+
+    // A present value of the wrong type is an error even with a default
+    let r = row(
+        &["id", "display_name", "email", "login_count"],
+        vec![
+            Value::Int(4),
+            Value::Text("Dan".to_string()),
+            Value::Null,
+            Value::Float(1.5),
+        ],
+    );
+    let err = User::from_row(&r).unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ColumnType { ref column, expected: "i32", found: "float" } if column == "login_count"
+    ));
+}
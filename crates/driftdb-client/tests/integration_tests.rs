@@ -7,7 +7,9 @@
 //! cargo run --release --bin driftdb-server -- --data-path /tmp/driftdb-test --auth-method trust
 //! ```
 
-use driftdb_client::{Client, Result, TimeTravel};
+use driftdb_client::{
+    CancellationToken, Client, Error, Feature, QueryOutcome, Result, TimeTravel, Value,
+};
 use serde::Deserialize;
 
 /// Helper to check if server is running
@@ -36,7 +38,7 @@ async fn test_simple_query() -> Result<()> {
     let rows = client.query("SELECT 1 as num").await?;
 
     assert_eq!(rows.len(), 1);
-    assert_eq!(rows[0].get("num").and_then(|v| v.as_i64()), Some(1));
+    assert_eq!(rows[0].value("num").and_then(|v| v.as_i64()), Some(1));
 
     Ok(())
 }
@@ -68,8 +70,11 @@ async fn test_create_and_query_table() -> Result<()> {
     let rows = client.query("SELECT * FROM test_users ORDER BY id").await?;
 
     assert_eq!(rows.len(), 2);
-    assert_eq!(rows[0].get("name").and_then(|v| v.as_str()), Some("Alice"));
-    assert_eq!(rows[1].get("name").and_then(|v| v.as_str()), Some("Bob"));
+    assert_eq!(
+        rows[0].value("name").and_then(|v| v.as_str()),
+        Some("Alice")
+    );
+    assert_eq!(rows[1].value("name").and_then(|v| v.as_str()), Some("Bob"));
 
     // Cleanup
     client.execute("DROP TABLE test_users").await?;
@@ -170,7 +175,10 @@ async fn test_time_travel() -> Result<()> {
     let current = client
         .query("SELECT value FROM time_travel_test WHERE id = 1")
         .await?;
-    assert_eq!(current[0].get("value").and_then(|v| v.as_str()), Some("v2"));
+    assert_eq!(
+        current[0].value("value").and_then(|v| v.as_str()),
+        Some("v2")
+    );
 
     // Query historical state
     let historical = client
@@ -180,7 +188,7 @@ async fn test_time_travel() -> Result<()> {
         .await?;
 
     assert_eq!(
-        historical[0].get("value").and_then(|v| v.as_str()),
+        historical[0].value("value").and_then(|v| v.as_str()),
         Some("v1")
     );
 
@@ -230,3 +238,196 @@ async fn test_transactions() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_bytea_round_trip() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433").await?;
+
+    let _ = client.execute("DROP TABLE bytea_test").await;
+    client
+        .execute("CREATE TABLE bytea_test (id BIGINT PRIMARY KEY, payload BYTEA)")
+        .await?;
+
+    // Every byte value, including NUL and bytes that aren't valid UTF-8
+    let payload: Vec<u8> = (0..=255).collect();
+    client
+        .execute_escaped(
+            "INSERT INTO bytea_test (id, payload) VALUES ($1, $2)",
+            &[Value::Int(1), Value::Bytes(payload.clone())],
+        )
+        .await?;
+    client
+        .execute_escaped(
+            "INSERT INTO bytea_test (id, payload) VALUES ($1, $2)",
+            &[Value::Int(2), Value::Null],
+        )
+        .await?;
+
+    let rows = client
+        .query("SELECT id, payload FROM bytea_test ORDER BY id")
+        .await?;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0].value("payload").and_then(|v| v.as_bytes()),
+        Some(&payload[..])
+    );
+    assert!(rows[1].value("payload").is_some_and(|v| v.is_null()));
+
+    client.execute("DROP TABLE bytea_test").await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_ping_and_reconnect() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433")
+        .await?
+        .with_auto_reconnect(true);
+    assert!(client.ping().await);
+
+    // A fresh connection replaces the old one and keeps working
+    client.reconnect().await?;
+    assert!(client.ping().await);
+    let rows = client.query("SELECT 1 as num").await?;
+    assert_eq!(rows[0].get::<i64>("num")?, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_query_cancellation() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433").await?;
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let cancelled = client
+        .query_builder("SELECT 1 as num")
+        .with_cancel(token)
+        .execute()
+        .await;
+    assert!(matches!(cancelled, Err(Error::Cancelled)));
+
+    // An unfired token doesn't get in the way, and the connection is
+    // still usable after a cancel
+    let rows = client
+        .query_builder("SELECT 1 as num")
+        .with_cancel(CancellationToken::new())
+        .execute()
+        .await?;
+    assert_eq!(rows[0].get::<i64>("num")?, 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_schema_introspection() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433").await?;
+    let _ = client.execute("DROP TABLE test_introspect").await;
+    client
+        .execute("CREATE TABLE test_introspect (id BIGINT PRIMARY KEY, email TEXT, visits INTEGER DEFAULT 0)")
+        .await?;
+    client
+        .execute("CREATE INDEX ON test_introspect (email)")
+        .await?;
+
+    assert!(client
+        .list_tables()
+        .await?
+        .contains(&"test_introspect".to_string()));
+
+    let schema = client.describe_table("test_introspect").await?;
+    assert_eq!(schema.primary_key, ["id"]);
+    let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["id", "email", "visits"]);
+    assert_eq!(
+        schema.column("visits").and_then(|c| c.default.as_deref()),
+        Some("0")
+    );
+    assert!(schema.indexes.iter().any(|i| i.columns == ["email"]));
+
+    assert!(client.describe_table("no_such_table").await.is_err());
+
+    client.execute("DROP TABLE test_introspect").await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_execute_script() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433").await?;
+    let _ = client.execute("DROP TABLE test_script").await;
+
+    let outcomes = client
+        .execute_script(
+            "-- create and fill\n\
+             CREATE TABLE test_script (id BIGINT PRIMARY KEY, note TEXT);\n\
+             INSERT INTO test_script (id, note) VALUES (1, 'a; b'), (2, 'c');\n\
+             SELECT * FROM test_script WHERE id = 3;\n",
+        )
+        .await?;
+    assert_eq!(outcomes.len(), 3);
+    assert_eq!(outcomes[1], QueryOutcome::Affected(2));
+    assert_eq!(outcomes[2].rows().map(|r| r.len()), Some(0));
+
+    let err = client
+        .execute_script(
+            "INSERT INTO test_script (id, note) VALUES (3, 'd');\n\
+             INSERT INTO test_script (id, note) VALUES (1, 'dup');\n\
+             INSERT INTO test_script (id, note) VALUES (4, 'never');",
+        )
+        .await
+        .unwrap_err();
+    match err {
+        Error::Script { index, line, .. } => assert_eq!((index, line), (1, 2)),
+        other => panic!("expected a script error, got {:?}", other),
+    }
+    let rows = client.query("SELECT id FROM test_script").await?;
+    assert_eq!(rows.len(), 3);
+
+    client.execute("DROP TABLE test_script").await?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore = "Requires running DriftDB server"]
+async fn test_server_capabilities() -> Result<()> {
+    if !server_available().await {
+        return Ok(());
+    }
+
+    let client = Client::connect("localhost:5433").await?;
+    let capabilities = client.server_capabilities();
+    assert!(capabilities.supports(Feature::Temporal));
+    assert!(capabilities.supports(Feature::ExtendedQuery));
+    assert_eq!(
+        client.server_version().await?,
+        env!("CARGO_PKG_VERSION"),
+        "server and client come from the same workspace"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Deep pagination: `LIMIT n OFFSET m` against keyset pagination
+//!
+//! OFFSET still has to walk past every skipped row, so its cost grows
+//! with the page number. A keyset page (`WHERE id > $last_seen ORDER BY
+//! id LIMIT n`) starts its index walk at the last row already seen and
+//! costs the same on page 1 and page 1000.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::Engine;
+use tempfile::TempDir;
+
+const ROWS: usize = 20_000;
+const PAGE: usize = 50;
+
+fn setup() -> (Engine, SessionContext, TempDir) {
+    let temp_dir = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp_dir.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, name VARCHAR)",
+        &mut ctx,
+    )
+    .unwrap();
+    for i in 0..ROWS {
+        execute_sql_in_session(
+            &mut engine,
+            &format!("INSERT INTO items (id, name) VALUES ({}, 'item {}')", i, i),
+            &mut ctx,
+        )
+        .unwrap();
+    }
+    (engine, ctx, temp_dir)
+}
+
+fn bench_deep_pages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pagination");
+    let (mut engine, mut ctx, _temp) = setup();
+
+    for skipped in [0, 1_000, 10_000, ROWS - PAGE] {
+        group.bench_with_input(
+            BenchmarkId::new("offset", skipped),
+            &skipped,
+            |b, &skipped| {
+                let sql = format!(
+                    "SELECT * FROM items ORDER BY id LIMIT {} OFFSET {}",
+                    PAGE, skipped
+                );
+                b.iter(|| black_box(execute_sql_in_session(&mut engine, &sql, &mut ctx).unwrap()));
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("keyset", skipped),
+            &skipped,
+            |b, &skipped| {
+                let sql = format!(
+                    "SELECT * FROM items WHERE id > {} ORDER BY id LIMIT {}",
+                    skipped as i64 - 1,
+                    PAGE
+                );
+                b.iter(|| black_box(execute_sql_in_session(&mut engine, &sql, &mut ctx).unwrap()));
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_deep_pages);
+criterion_main!(benches);
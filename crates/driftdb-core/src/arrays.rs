@@ -0,0 +1,354 @@
+//! ARRAY columns
+//!
+//! A column declared `INT[]`, `TEXT[]` or `ARRAY<...>` stores its value as
+//! a JSON array. Writes accept `ARRAY[1, 2, 3]` expressions or PostgreSQL
+//! text literals such as `'{1,2,3}'` and `'{"a b",c,NULL}'`, converted to
+//! the declared element type. This module holds the literal parser and the
+//! array operators:
+//!
+//! - `a @> b` is true when every element of `b` is in `a`; `&&` when the
+//!   two share an element. NULL elements never match.
+//! - `arr[i]` is 1-based and NULL out of range; `arr[lo:hi]` is inclusive.
+//! - `x op ANY(arr)` / `x op ALL(arr)` compare `x` against each element.
+
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Column types stored as arrays
+pub fn is_array_type(col_type: &str) -> bool {
+    element_type(col_type).is_some()
+}
+
+/// Declared element type of an array column type: `INT[]` and `ARRAY<INT>`
+/// give `INT`, a bare `ARRAY` gives an empty string (untyped).
+pub fn element_type(col_type: &str) -> Option<String> {
+    let upper = col_type.trim().to_ascii_uppercase();
+    if let Some(elem) = upper.strip_suffix("[]") {
+        // `INT[][]` is still an INT array
+        return Some(elem.trim_end_matches("[]").trim().to_string());
+    }
+    if upper == "ARRAY" {
+        return Some(String::new());
+    }
+    upper
+        .strip_prefix("ARRAY<")
+        .and_then(|rest| rest.strip_suffix('>'))
+        .map(|elem| elem.trim().to_string())
+}
+
+/// Value written to an array column of element type `elem_type`
+pub fn coerce(value: Value, elem_type: &str) -> Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::String(text) => parse_literal(&text, elem_type),
+        Value::Array(items) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::Array(_) => coerce(item, elem_type),
+                scalar => coerce_element(scalar, elem_type),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Value::Array),
+        other => Err(DriftError::InvalidQuery(format!(
+            "malformed array literal: \"{}\"",
+            other
+        ))),
+    }
+}
+
+/// Parse a PostgreSQL array literal like `{1,2,3}` or `{{1,2},{3,4}}`.
+/// An empty `elem_type` keeps numbers as numbers and everything else as
+/// text.
+pub fn parse_literal(text: &str, elem_type: &str) -> Result<Value> {
+    let malformed = || DriftError::InvalidQuery(format!("malformed array literal: \"{}\"", text));
+    let mut chars = text.trim().chars().peekable();
+    let value = parse_braced(&mut chars, elem_type).ok_or_else(malformed)??;
+    if chars.any(|c| !c.is_whitespace()) {
+        return Err(malformed());
+    }
+    Ok(value)
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+/// `None` on a syntax error, `Some(Err)` when an element doesn't convert
+fn parse_braced(chars: &mut Chars<'_>, elem_type: &str) -> Option<Result<Value>> {
+    if chars.next()? != '{' {
+        return None;
+    }
+    let mut items = Vec::new();
+    skip_whitespace(chars);
+    if chars.peek() == Some(&'}') {
+        chars.next();
+        return Some(Ok(Value::Array(items)));
+    }
+    loop {
+        skip_whitespace(chars);
+        let item = match chars.peek()? {
+            '{' => match parse_braced(chars, elem_type)? {
+                Ok(nested) => nested,
+                Err(e) => return Some(Err(e)),
+            },
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => text.push(chars.next()?),
+                        c => text.push(c),
+                    }
+                }
+                match coerce_element(Value::String(text), elem_type) {
+                    Ok(v) => v,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            _ => {
+                let mut text = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ',' || c == '}' {
+                        break;
+                    }
+                    text.push(c);
+                    chars.next();
+                }
+                let text = text.trim();
+                if text.is_empty() {
+                    return None;
+                }
+                if text.eq_ignore_ascii_case("NULL") {
+                    Value::Null
+                } else {
+                    match coerce_element(Value::String(text.to_string()), elem_type) {
+                        Ok(v) => v,
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            }
+        };
+        items.push(item);
+        skip_whitespace(chars);
+        match chars.next()? {
+            ',' => continue,
+            '}' => return Some(Ok(Value::Array(items))),
+            _ => return None,
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Chars<'_>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Convert one element to the declared element type
+fn coerce_element(value: Value, elem_type: &str) -> Result<Value> {
+    // `VARCHAR(20)` and `NUMERIC(10,2)` convert like their base type
+    let elem_type = elem_type.split('(').next().unwrap_or_default().trim();
+    let numeric = matches!(
+        elem_type,
+        "INT"
+            | "INTEGER"
+            | "BIGINT"
+            | "SMALLINT"
+            | "INT2"
+            | "INT4"
+            | "INT8"
+            | "REAL"
+            | "FLOAT"
+            | "FLOAT4"
+            | "FLOAT8"
+            | "DOUBLE"
+            | "DOUBLE PRECISION"
+            | "NUMERIC"
+            | "DECIMAL"
+    );
+    let boolean = matches!(elem_type, "BOOL" | "BOOLEAN");
+    match value {
+        Value::String(text) if numeric || elem_type.is_empty() => {
+            if let Ok(i) = text.parse::<i64>() {
+                Ok(Value::from(i))
+            } else if let Some(n) = text
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+            {
+                Ok(Value::Number(n))
+            } else if numeric {
+                Err(DriftError::InvalidQuery(format!(
+                    "invalid input syntax for type {}: \"{}\"",
+                    elem_type.to_lowercase(),
+                    text
+                )))
+            } else {
+                Ok(Value::String(text))
+            }
+        }
+        Value::String(text) if boolean => match text.to_ascii_lowercase().as_str() {
+            "t" | "true" => Ok(Value::Bool(true)),
+            "f" | "false" => Ok(Value::Bool(false)),
+            _ => Err(DriftError::InvalidQuery(format!(
+                "invalid input syntax for type boolean: \"{}\"",
+                text
+            ))),
+        },
+        Value::Number(n) if !numeric && !boolean && !elem_type.is_empty() => {
+            Ok(Value::String(n.to_string()))
+        }
+        other => Ok(other),
+    }
+}
+
+/// Read an operator's operand as an array when the other side is one: a
+/// text literal like `'{1,2}'` becomes the array it spells.
+pub fn operand(value: &Value, other: &Value) -> Value {
+    match (value, other) {
+        (Value::String(text), Value::Array(_)) => {
+            parse_literal(text, "").unwrap_or_else(|_| value.clone())
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Whether `a` and `b` are both arrays of scalars, so `@>` follows array
+/// rather than JSONB containment (the two differ for arrays of documents).
+pub fn both_plain_arrays(a: &Value, b: &Value) -> bool {
+    [a, b]
+        .iter()
+        .all(|v| v.is_array() && elements(v).iter().all(|e| !e.is_object()))
+}
+
+fn elements_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, _) | (_, Value::Null) => false,
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// Leaf elements, flattening multi-dimensional arrays
+fn elements(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(elements).collect(),
+        scalar => vec![scalar],
+    }
+}
+
+/// `a @> b`
+pub fn contains(a: &Value, b: &Value) -> bool {
+    let haystack = elements(a);
+    elements(b)
+        .into_iter()
+        .all(|x| haystack.iter().any(|y| elements_equal(x, y)))
+}
+
+/// `a && b`
+pub fn overlaps(a: &Value, b: &Value) -> bool {
+    let haystack = elements(a);
+    elements(b)
+        .into_iter()
+        .any(|x| haystack.iter().any(|y| elements_equal(x, y)))
+}
+
+/// `arr[index]`, 1-based
+pub fn subscript(array: &Value, index: &Value) -> Value {
+    let (Value::Array(items), Some(i)) = (array, index.as_i64()) else {
+        return Value::Null;
+    };
+    if i < 1 {
+        return Value::Null;
+    }
+    usize::try_from(i - 1)
+        .ok()
+        .and_then(|i| items.get(i))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+/// `arr[lower:upper]`, 1-based and inclusive; a missing bound is open
+pub fn slice(array: &Value, lower: Option<&Value>, upper: Option<&Value>) -> Value {
+    let Value::Array(items) = array else {
+        return Value::Null;
+    };
+    let lower = lower.and_then(Value::as_i64).unwrap_or(1).max(1);
+    let upper = upper
+        .and_then(Value::as_i64)
+        .unwrap_or(items.len() as i64)
+        .min(items.len() as i64);
+    if lower > upper {
+        return Value::Array(Vec::new());
+    }
+    Value::Array(items[(lower - 1) as usize..upper as usize].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn column_types() {
+        assert_eq!(element_type("INT[]").as_deref(), Some("INT"));
+        assert_eq!(element_type("text[][]").as_deref(), Some("TEXT"));
+        assert_eq!(element_type("ARRAY<VARCHAR>").as_deref(), Some("VARCHAR"));
+        assert_eq!(element_type("ARRAY").as_deref(), Some(""));
+        assert!(!is_array_type("VARCHAR"));
+        assert!(!is_array_type("JSONB"));
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(parse_literal("{1,2,3}", "INT").unwrap(), json!([1, 2, 3]));
+        assert_eq!(
+            parse_literal(r#"{admin, "a b", "q\"x", NULL}"#, "TEXT").unwrap(),
+            json!(["admin", "a b", "q\"x", null])
+        );
+        assert_eq!(
+            parse_literal("{{1,2},{3,4}}", "INT").unwrap(),
+            json!([[1, 2], [3, 4]])
+        );
+        assert_eq!(parse_literal("{}", "INT").unwrap(), json!([]));
+        assert_eq!(
+            parse_literal("{t,false}", "BOOL").unwrap(),
+            json!([true, false])
+        );
+        assert!(parse_literal("{1,2", "INT").is_err());
+        assert!(parse_literal("1,2", "INT").is_err());
+        assert!(parse_literal("{1,x}", "INT").is_err());
+        assert_eq!(coerce(json!([1, 2]), "TEXT").unwrap(), json!(["1", "2"]));
+    }
+
+    #[test]
+    fn containment_and_overlap() {
+        let a = json!([1, 2, 3, 3]);
+        assert!(contains(&a, &json!([3, 1])));
+        assert!(contains(&a, &json!([])));
+        assert!(contains(&a, &json!([2.0])));
+        assert!(!contains(&a, &json!([1, 4])));
+        assert!(!contains(&json!([1, null]), &json!([null])));
+        assert!(contains(&json!([[1, 2], [3, 4]]), &json!([4, 1])));
+
+        assert!(overlaps(&a, &json!([9, 3])));
+        assert!(!overlaps(&a, &json!([9, 8])));
+        assert!(!overlaps(&a, &json!([])));
+        assert!(!overlaps(&json!([null]), &json!([null])));
+    }
+
+    #[test]
+    fn subscripts() {
+        let a = json!(["a", "b", "c"]);
+        assert_eq!(subscript(&a, &json!(1)), json!("a"));
+        assert_eq!(subscript(&a, &json!(3)), json!("c"));
+        assert_eq!(subscript(&a, &json!(0)), Value::Null);
+        assert_eq!(subscript(&a, &json!(4)), Value::Null);
+        assert_eq!(
+            slice(&a, Some(&json!(2)), Some(&json!(3))),
+            json!(["b", "c"])
+        );
+        assert_eq!(slice(&a, None, Some(&json!(1))), json!(["a"]));
+        assert_eq!(slice(&a, Some(&json!(3)), Some(&json!(1))), json!([]));
+    }
+}
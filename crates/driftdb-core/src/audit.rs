@@ -812,7 +812,12 @@ mod tests {
 
     #[test]
     fn test_risk_score_calculation() {
-        let system = AuditSystem::new(AuditConfig::default()).unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let system = AuditSystem::new(AuditConfig {
+            log_file_path: dir.path().join("audit.log"),
+            ..AuditConfig::default()
+        })
+        .unwrap();
 
         let event = AuditEvent {
             id: Uuid::new_v4(),
@@ -0,0 +1,182 @@
+//! Background auto-analyze
+//!
+//! The engine counts rows written to each table since its last whole-table
+//! `ANALYZE`. The auto-analyzer checks those counts on a fixed interval and
+//! re-analyzes every table past `threshold`, so planner statistics follow
+//! the data without a manual `ANALYZE`.
+//!
+//! Collecting statistics only reads the table, so it runs under the
+//! engine's read lock. Writes that land while a table is being analyzed
+//! stay counted towards its next run. The analyzer is disabled by default.
+
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::engine::Engine;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoAnalyzeConfig {
+    pub enabled: bool,
+    /// How often change counts are checked
+    pub check_interval_secs: u64,
+    /// Re-analyze a table once more than this many rows were written to
+    /// it since its last analyze
+    pub threshold: u64,
+}
+
+impl Default for AutoAnalyzeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 60,
+            threshold: 1_000,
+        }
+    }
+}
+
+pub struct AutoAnalyzer {
+    config: AutoAnalyzeConfig,
+}
+
+impl AutoAnalyzer {
+    pub fn new(config: AutoAnalyzeConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &AutoAnalyzeConfig {
+        &self.config
+    }
+
+    /// Analyze every table past the threshold, returning their names
+    pub fn run_once(&self, engine: &Engine) -> Vec<String> {
+        let mut analyzed = Vec::new();
+        for table in engine.tables_due_for_analyze(self.config.threshold) {
+            let changes = engine.changes_since_analyze(&table);
+            let start = Instant::now();
+            match engine.analyze_table(&table, None) {
+                Ok(()) => {
+                    info!(
+                        "Auto-analyzed table '{}' after {} changes in {:?}",
+                        table,
+                        changes,
+                        start.elapsed()
+                    );
+                    analyzed.push(table);
+                }
+                Err(e) => warn!("Auto-analyze of table '{}' failed: {}", table, e),
+            }
+        }
+        analyzed
+    }
+
+    /// Run the analyzer on a background thread until the handle is stopped
+    /// or dropped
+    pub fn spawn(self, engine: Arc<RwLock<Engine>>) -> AutoAnalyzerHandle {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let interval = Duration::from_secs(self.config.check_interval_secs.max(1));
+
+        let thread = std::thread::Builder::new()
+            .name("driftdb-autoanalyze".to_string())
+            .spawn(move || {
+                info!(
+                    "Auto-analyze started, re-analyzing tables after {} changes",
+                    self.config.threshold
+                );
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    if self.run_once(&engine.read()).is_empty() {
+                        debug!("No tables due for analyze");
+                    }
+                }
+                info!("Auto-analyze stopped");
+            })
+            .expect("failed to spawn auto-analyze thread");
+
+        AutoAnalyzerHandle {
+            stop: Some(stop_tx),
+            thread: Some(thread),
+        }
+    }
+}
+
+pub struct AutoAnalyzerHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AutoAnalyzerHandle {
+    /// Stop the analyzer, waiting for an analyze in progress to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for AutoAnalyzerHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql_bridge::{execute_sql_in_session, SessionContext};
+    use tempfile::TempDir;
+
+    fn run(engine: &mut Engine, sql: &str) {
+        execute_sql_in_session(engine, sql, &mut SessionContext::new()).unwrap();
+    }
+
+    #[test]
+    fn analyzes_tables_past_the_threshold() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = Engine::init(temp.path()).unwrap();
+        run(&mut engine, "CREATE TABLE hot (id INTEGER PRIMARY KEY)");
+        run(&mut engine, "CREATE TABLE cold (id INTEGER PRIMARY KEY)");
+        for id in 0..5 {
+            run(
+                &mut engine,
+                &format!("INSERT INTO hot (id) VALUES ({})", id),
+            );
+        }
+        run(&mut engine, "INSERT INTO cold (id) VALUES (1)");
+
+        let analyzer = AutoAnalyzer::new(AutoAnalyzeConfig {
+            enabled: true,
+            threshold: 3,
+            ..Default::default()
+        });
+        assert_eq!(analyzer.run_once(&engine), ["hot"]);
+        assert_eq!(engine.changes_since_analyze("hot"), 0);
+        assert_eq!(engine.changes_since_analyze("cold"), 1);
+        assert_eq!(
+            engine.query_optimizer().statistics_row_count("hot"),
+            Some(5)
+        );
+
+        // Nothing new since
+        assert!(analyzer.run_once(&engine).is_empty());
+    }
+
+    #[test]
+    fn config_defaults_fill_missing_fields() {
+        let parsed: AutoAnalyzeConfig =
+            serde_json::from_str(r#"{"enabled": true, "threshold": 50}"#).unwrap();
+        assert!(parsed.enabled);
+        assert_eq!(parsed.threshold, 50);
+        assert_eq!(parsed.check_interval_secs, 60);
+    }
+}
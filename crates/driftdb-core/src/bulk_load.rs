@@ -0,0 +1,113 @@
+//! Loading a fresh table without per-row overhead
+//!
+//! A bulk load appends rows straight to the table's segments: no WAL or
+//! transaction, no per-row primary key lookup, index update or fsync,
+//! and meta saved once per batch. [`Engine::finish_bulk_load`] fsyncs the
+//! segments, rebuilds the indexes and takes a snapshot.
+//!
+//! The contract is all or nothing. The table must be empty to start and
+//! is marked incomplete until the load finishes; a crash before then
+//! loses the whole load, which is discarded the next time the table is
+//! opened. Constraints aren't checked either (a repeated key keeps its
+//! last row), so run `driftdb verify` after loading untrusted data.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::events::Event;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BulkLoadSummary {
+    /// Rows appended by the load
+    pub rows: u64,
+    /// Distinct keys after the load, lower than `rows` if keys repeated
+    pub keys: u64,
+}
+
+impl Engine {
+    /// Start a bulk load into `table`, which must be empty
+    pub fn begin_bulk_load(&self, table: &str) -> Result<()> {
+        self.ensure_writable("BULK LOAD")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        storage.begin_bulk_load()?;
+        info!("Started bulk load into '{}'", table);
+        Ok(())
+    }
+
+    /// Append `rows` to a table being bulk loaded, returning how many
+    /// were written. Every row needs its primary key column.
+    pub fn bulk_load_rows(&self, table: &str, rows: Vec<Value>) -> Result<usize> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        let pk_field = storage.schema().primary_key.clone();
+
+        let events = rows
+            .into_iter()
+            .map(|row| {
+                let key = row
+                    .get(&pk_field)
+                    .filter(|key| !key.is_null())
+                    .cloned()
+                    .ok_or_else(|| {
+                        DriftError::InvalidQuery(format!(
+                            "Missing primary key field '{}'",
+                            pk_field
+                        ))
+                    })?;
+                Ok(Event::new_insert(table.to_string(), key, row))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let count = events.len();
+        storage.append_bulk(events)?;
+        self.record_changes(table, count as u64);
+        Ok(count)
+    }
+
+    /// Make a bulk load durable: fsync the segments, rebuild the table's
+    /// indexes, snapshot it and clear the incomplete mark
+    pub fn finish_bulk_load(&self, table: &str) -> Result<BulkLoadSummary> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        if !storage.is_bulk_loading() {
+            return Err(DriftError::Other(format!(
+                "table '{}' has no bulk load in progress",
+                table
+            )));
+        }
+        storage.sync()?;
+
+        let state = storage.reconstruct_state_at(None)?;
+        if let Some(index_mgr) = self.indexes.get(table) {
+            let mut index_mgr = index_mgr.write();
+            let active = index_mgr.indexed_column_names();
+            index_mgr.rebuild_from_state(&state, &active)?;
+        }
+        let summary = BulkLoadSummary {
+            rows: storage.last_sequence(),
+            keys: state.len() as u64,
+        };
+        drop(state);
+
+        // The marker goes last, so a crash anywhere before it still
+        // discards the load as a whole
+        if let Some(snapshot_mgr) = self.snapshots.get(table) {
+            snapshot_mgr.create_snapshot(storage, summary.rows)?;
+        }
+        storage.finish_bulk_load()?;
+        info!(
+            "Finished bulk load into '{}': {} rows, {} keys",
+            table, summary.rows, summary.keys
+        );
+        Ok(summary)
+    }
+}
@@ -0,0 +1,153 @@
+//! BYTEA columns
+//!
+//! A `BYTEA` column stores each value in PostgreSQL's hex output format:
+//! `\x` followed by two lowercase hex digits per byte. That is also the
+//! text the wire protocol sends for bytea, so values leave the server
+//! unchanged. Input is accepted in both PostgreSQL forms (hex, and the
+//! older escape format with `\\` and `\ooo` octal escapes) as well as a
+//! JSON array of byte values from document inserts. Fixed-width lowercase
+//! hex sorts the same way as the bytes, so comparisons and indexes need no
+//! separate binary encoding.
+
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Column types stored as byte strings
+pub fn is_bytea_type(col_type: &str) -> bool {
+    col_type.eq_ignore_ascii_case("BYTEA")
+}
+
+/// Validate a value written into a BYTEA column and return its canonical
+/// form. NULL passes through.
+pub fn parse(value: Value) -> Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::String(text) => Ok(Value::String(encode(&decode(&text)?))),
+        Value::Array(items) => {
+            let bytes = items
+                .iter()
+                .map(|item| {
+                    item.as_u64()
+                        .filter(|b| *b <= u8::MAX as u64)
+                        .map(|b| b as u8)
+                        .ok_or_else(|| invalid(&item.to_string()))
+                })
+                .collect::<Result<Vec<u8>>>()?;
+            Ok(Value::String(encode(&bytes)))
+        }
+        other => Err(invalid(&other.to_string())),
+    }
+}
+
+/// Hex output format: `\x` and two lowercase digits per byte
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(2 + bytes.len() * 2);
+    out.push_str("\\x");
+    for byte in bytes {
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+/// Read a bytea literal in hex or escape format
+pub fn decode(text: &str) -> Result<Vec<u8>> {
+    match text
+        .strip_prefix("\\x")
+        .or_else(|| text.strip_prefix("\\X"))
+    {
+        Some(hex) => decode_hex(hex).ok_or_else(|| invalid(text)),
+        None => decode_escape(text).ok_or_else(|| invalid(text)),
+    }
+}
+
+/// Hex digit pairs, optionally separated by whitespace
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(hex.len() / 2);
+    let mut digits = hex.chars().filter(|c| !c.is_whitespace());
+    while let Some(high) = digits.next() {
+        let low = digits.next()?;
+        bytes.push((high.to_digit(16)? * 16 + low.to_digit(16)?) as u8);
+    }
+    Some(bytes)
+}
+
+/// Escape format: the text's own bytes, with `\\` for a backslash and
+/// `\ooo` for an octal byte
+fn decode_escape(text: &str) -> Option<Vec<u8>> {
+    let raw = text.as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] != b'\\' {
+            bytes.push(raw[i]);
+            i += 1;
+        } else if raw.get(i + 1) == Some(&b'\\') {
+            bytes.push(b'\\');
+            i += 2;
+        } else {
+            let octal = std::str::from_utf8(raw.get(i + 1..i + 4)?).ok()?;
+            if !octal.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+                return None;
+            }
+            bytes.push(u8::from_str_radix(octal, 8).ok()?);
+            i += 4;
+        }
+    }
+    Some(bytes)
+}
+
+fn invalid(text: &str) -> DriftError {
+    DriftError::InvalidQuery(format!("invalid input syntax for type bytea: \"{}\"", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_accepts_postgres_input_forms() {
+        let canonical = json!("\\xdead00ff");
+        for value in [
+            json!("\\xdead00ff"),
+            json!("\\XDEAD00FF"),
+            json!("\\xde ad 00 ff"),
+            json!("\\336\\255\\000\\377"),
+            json!([222, 173, 0, 255]),
+        ] {
+            assert_eq!(parse(value.clone()).unwrap(), canonical, "{}", value);
+        }
+        assert_eq!(parse(json!("ab\\\\")).unwrap(), json!("\\x61625c"));
+        assert_eq!(parse(json!("")).unwrap(), json!("\\x"));
+        assert_eq!(parse(Value::Null).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_rejects_malformed_values() {
+        for value in [
+            json!("\\xabc"),
+            json!("\\xzz"),
+            json!("bad\\escape"),
+            json!("\\400"),
+            json!([256]),
+            json!(42),
+        ] {
+            let err = parse(value.clone()).unwrap_err();
+            assert!(err.to_string().contains("type bytea"), "{}: {}", value, err);
+        }
+    }
+
+    #[test]
+    fn test_round_trips_every_byte() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode(&encode(&bytes)).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_text_order_matches_byte_order() {
+        let low = encode(&[0x0f, 0xff]);
+        let high = encode(&[0xa0, 0x00]);
+        assert!(low < high);
+    }
+}
@@ -0,0 +1,661 @@
+//! Changefeeds: a table's row changes streamed to an outside system
+//!
+//! `CREATE CHANGEFEED [name] FOR TABLE t INTO 'sink' [WITH (...)]` keeps a
+//! thread following the table with an [`EventSubscription`] and handing
+//! its events, in sequence order and in batches, to the sink:
+//!
+//! - `webhook://host[:port]/path` POSTs each batch as one JSON document,
+//!   `{"changefeed": ..., "table": ..., "events": [...]}`, and takes any
+//!   2xx response as the acknowledgement
+//! - `file:///path` appends each event as a line of JSON
+//!
+//! Each event is an [`Event::change_record`]. Delivery is at least once:
+//! the sequence of the last acknowledged event is written to
+//! `changefeeds/<name>.cursor` only after the sink accepts the batch, a
+//! failed delivery is retried with backoff until it goes through, and
+//! a changefeed picks up after its cursor when the database is opened
+//! again. A sink can therefore see a batch twice, never miss one.
+//!
+//! Options: `cursor` is where a new changefeed starts, `'now'` (the
+//! default), `'beginning'` or a sequence to deliver the events after;
+//! `batch_size` caps the events per batch (100); `format` is `'json'`.
+//! Changefeeds are persisted to `changefeeds.json` and don't run while
+//! the engine is read-only.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::events::Event;
+use crate::subscription::EventSubscription;
+
+/// Events per batch unless `batch_size` says otherwise
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// How long a changefeed thread waits for events before checking whether
+/// it was stopped
+const WAKE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Bounds of the backoff between failed deliveries
+const MIN_RETRY_DELAY: Duration = Duration::from_millis(100);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Connect, read and write timeout of a webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangefeedFormat {
+    Json,
+}
+
+/// A changefeed as created, persisted in `changefeeds.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangefeedDefinition {
+    pub name: String,
+    pub table: String,
+    /// Sink URI, `webhook://...` or `file://...`
+    pub sink: String,
+    pub format: ChangefeedFormat,
+    pub batch_size: usize,
+}
+
+/// Where a new changefeed starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangefeedCursor {
+    /// After the table's current last event
+    Now,
+    /// After the event with this sequence; 0 is the whole table
+    After(u64),
+}
+
+/// A changefeed and how its delivery is going, for `SHOW CHANGEFEEDS`
+#[derive(Debug, Clone)]
+pub struct ChangefeedInfo {
+    pub definition: ChangefeedDefinition,
+    /// Sequence of the last event the sink acknowledged
+    pub acknowledged: u64,
+    /// Why the last delivery failed, until one succeeds
+    pub last_error: Option<String>,
+    pub running: bool,
+}
+
+/// `CREATE CHANGEFEED [name] FOR TABLE t INTO 'sink' [WITH (option =
+/// value, ...)]`. The name is empty when the statement leaves it out.
+pub fn parse_create_changefeed(sql: &str) -> Result<(ChangefeedDefinition, ChangefeedCursor)> {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    if !statement.to_uppercase().starts_with("CREATE CHANGEFEED") {
+        return Err(DriftError::Parse("expected CREATE CHANGEFEED".to_string()));
+    }
+    let rest = &statement["CREATE CHANGEFEED".len()..];
+    let upper = rest.to_uppercase();
+    let for_at = upper
+        .find("FOR TABLE ")
+        .ok_or_else(|| DriftError::Parse("CREATE CHANGEFEED needs FOR TABLE".to_string()))?;
+    let name = rest[..for_at].trim().trim_matches('"').to_string();
+    let rest = rest[for_at + "FOR TABLE ".len()..].trim_start();
+
+    let table_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let table = rest[..table_end].to_string();
+    let rest = rest[table_end..].trim_start();
+    if !rest.to_uppercase().starts_with("INTO ") {
+        return Err(DriftError::Parse(
+            "CREATE CHANGEFEED needs INTO 'sink'".to_string(),
+        ));
+    }
+    let rest = rest["INTO ".len()..].trim_start();
+    let (sink, rest) = quoted(rest)
+        .ok_or_else(|| DriftError::Parse("the changefeed sink must be a quoted URI".to_string()))?;
+
+    let mut definition = ChangefeedDefinition {
+        name,
+        table,
+        sink,
+        format: ChangefeedFormat::Json,
+        batch_size: DEFAULT_BATCH_SIZE,
+    };
+    let mut cursor = ChangefeedCursor::Now;
+    let rest = rest.trim();
+    if !rest.is_empty() {
+        let options = rest
+            .get(..5)
+            .filter(|with| with.eq_ignore_ascii_case("WITH "))
+            .map(|_| rest[5..].trim())
+            .and_then(|list| list.strip_prefix('(')?.strip_suffix(')'))
+            .ok_or_else(|| {
+                DriftError::Parse(format!("unexpected '{}' in CREATE CHANGEFEED", rest))
+            })?;
+        for option in options.split(',') {
+            let (key, value) = option.split_once('=').ok_or_else(|| {
+                DriftError::Parse(format!(
+                    "expected option = value in CREATE CHANGEFEED, got '{}'",
+                    option.trim()
+                ))
+            })?;
+            let value = value.trim();
+            let value = quoted(value)
+                .map(|(v, _)| v)
+                .unwrap_or_else(|| value.to_string());
+            match key.trim().to_lowercase().as_str() {
+                "cursor" => {
+                    cursor = match value.to_lowercase().as_str() {
+                        "now" => ChangefeedCursor::Now,
+                        "beginning" => ChangefeedCursor::After(0),
+                        _ => ChangefeedCursor::After(value.parse().map_err(|_| {
+                            DriftError::InvalidQuery(format!(
+                                "cursor must be 'now', 'beginning' or a sequence, got '{}'",
+                                value
+                            ))
+                        })?),
+                    }
+                }
+                "batch_size" => {
+                    definition.batch_size =
+                        value.parse().ok().filter(|size| *size > 0).ok_or_else(|| {
+                            DriftError::InvalidQuery(format!(
+                                "batch_size must be a positive integer, got '{}'",
+                                value
+                            ))
+                        })?
+                }
+                "format" if value.eq_ignore_ascii_case("json") => {}
+                "format" => {
+                    return Err(DriftError::InvalidQuery(format!(
+                        "unsupported changefeed format '{}'; only json is supported",
+                        value
+                    )))
+                }
+                other => {
+                    return Err(DriftError::InvalidQuery(format!(
+                        "unknown changefeed option '{}'",
+                        other
+                    )))
+                }
+            }
+        }
+    }
+    Ok((definition, cursor))
+}
+
+/// A single-quoted string at the start of `text` and what follows it
+fn quoted(text: &str) -> Option<(String, &str)> {
+    let body = text.strip_prefix('\'')?;
+    let mut value = String::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().map(|(_, next)| *next) == Some('\'') {
+                chars.next();
+                value.push('\'');
+            } else {
+                return Some((value, &body[i + 1..]));
+            }
+        } else {
+            value.push(c);
+        }
+    }
+    None
+}
+
+/// Where a changefeed delivers its batches
+trait ChangefeedSink: Send {
+    fn deliver(&mut self, feed: &ChangefeedDefinition, events: &[Event]) -> Result<()>;
+}
+
+fn open_sink(uri: &str) -> Result<Box<dyn ChangefeedSink>> {
+    if let Some(rest) = uri.strip_prefix("webhook://") {
+        let (authority, path) = match rest.find('/') {
+            Some(at) => (&rest[..at], &rest[at..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| DriftError::InvalidQuery(format!("invalid port in '{}'", uri)))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(DriftError::InvalidQuery(format!(
+                "webhook sink '{}' has no host",
+                uri
+            )));
+        }
+        return Ok(Box::new(WebhookSink {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        }));
+    }
+    if let Some(path) = uri.strip_prefix("file://") {
+        if path.is_empty() {
+            return Err(DriftError::InvalidQuery(format!(
+                "file sink '{}' has no path",
+                uri
+            )));
+        }
+        return Ok(Box::new(FileSink {
+            path: PathBuf::from(path),
+        }));
+    }
+    Err(DriftError::InvalidQuery(format!(
+        "unsupported changefeed sink '{}'; use webhook://host[:port]/path or file:///path",
+        uri
+    )))
+}
+
+/// POSTs each batch over plain HTTP/1.1
+struct WebhookSink {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl ChangefeedSink for WebhookSink {
+    fn deliver(&mut self, feed: &ChangefeedDefinition, events: &[Event]) -> Result<()> {
+        let body = json!({
+            "changefeed": feed.name,
+            "table": feed.table,
+            "events": events.iter().map(Event::change_record).collect::<Vec<_>>(),
+        })
+        .to_string();
+
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| DriftError::Other(format!("cannot resolve '{}'", self.host)))?;
+        let mut stream = TcpStream::connect_timeout(&address, WEBHOOK_TIMEOUT)?;
+        stream.set_read_timeout(Some(WEBHOOK_TIMEOUT))?;
+        stream.set_write_timeout(Some(WEBHOOK_TIMEOUT))?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.path,
+            self.host,
+            self.port,
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        match status_line.split_whitespace().nth(1) {
+            Some(status) if status.starts_with('2') => Ok(()),
+            _ => Err(DriftError::Other(format!(
+                "webhook answered '{}'",
+                status_line
+            ))),
+        }
+    }
+}
+
+/// Appends each event as a JSON line, synced before the batch counts
+struct FileSink {
+    path: PathBuf,
+}
+
+impl ChangefeedSink for FileSink {
+    fn deliver(&mut self, _feed: &ChangefeedDefinition, events: &[Event]) -> Result<()> {
+        let mut lines = String::new();
+        for event in events {
+            lines.push_str(&event.change_record().to_string());
+            lines.push('\n');
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct DeliveryStatus {
+    acknowledged: u64,
+    last_error: Option<String>,
+}
+
+/// A running changefeed's thread, stopped and joined on drop
+struct ChangefeedWorker {
+    stop: Arc<AtomicBool>,
+    status: Arc<Mutex<DeliveryStatus>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ChangefeedWorker {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// A changefeed known to the engine, running unless the engine is
+/// read-only
+pub(crate) struct Changefeed {
+    definition: ChangefeedDefinition,
+    worker: Option<ChangefeedWorker>,
+}
+
+fn cursor_path(base_path: &Path, name: &str) -> PathBuf {
+    base_path
+        .join("changefeeds")
+        .join(format!("{}.cursor", name))
+}
+
+fn read_cursor(path: &Path) -> Result<u64> {
+    let text = fs::read_to_string(path)?;
+    text.trim().parse().map_err(|_| {
+        DriftError::Other(format!(
+            "changefeed cursor {} is not a sequence",
+            path.display()
+        ))
+    })
+}
+
+/// Replace the cursor file so a crash leaves the old cursor or the new one
+fn write_cursor(path: &Path, acknowledged: u64) -> Result<()> {
+    let tmp = path.with_extension("cursor.tmp");
+    fs::write(&tmp, acknowledged.to_string())?;
+    fs::File::open(&tmp)?.sync_all()?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Sleep for `delay`, waking early if `stop` is set. Returns whether it was.
+fn sleep_unless_stopped(stop: &AtomicBool, delay: Duration) -> bool {
+    let deadline = Instant::now() + delay;
+    while Instant::now() < deadline {
+        if stop.load(Ordering::Acquire) {
+            return true;
+        }
+        std::thread::sleep(WAKE_INTERVAL.min(deadline - Instant::now()));
+    }
+    stop.load(Ordering::Acquire)
+}
+
+fn start_worker(
+    definition: &ChangefeedDefinition,
+    mut subscription: EventSubscription,
+    cursor_path: PathBuf,
+) -> Result<ChangefeedWorker> {
+    let mut sink = open_sink(&definition.sink)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let status = Arc::new(Mutex::new(DeliveryStatus {
+        acknowledged: subscription.position(),
+        last_error: None,
+    }));
+    let feed = definition.clone();
+    let (thread_stop, thread_status) = (stop.clone(), status.clone());
+    let thread = std::thread::Builder::new()
+        .name(format!("driftdb-changefeed-{}", feed.name))
+        .spawn(move || {
+            let stop = thread_stop;
+            let mut retry_delay = MIN_RETRY_DELAY;
+            // Record a failure and return how long to wait before retrying
+            let fail = |error: String, retry_delay: &mut Duration| {
+                warn!("Changefeed '{}': {}", feed.name, error);
+                thread_status.lock().last_error = Some(error);
+                let delay = *retry_delay;
+                *retry_delay = (delay * 2).min(MAX_RETRY_DELAY);
+                delay
+            };
+            while !stop.load(Ordering::Acquire) {
+                let events = match subscription.next_events(WAKE_INTERVAL) {
+                    Ok(events) => events,
+                    Err(e) => {
+                        let delay = fail(
+                            format!("reading table '{}' failed: {}", feed.table, e),
+                            &mut retry_delay,
+                        );
+                        sleep_unless_stopped(&stop, delay);
+                        continue;
+                    }
+                };
+                for batch in events.chunks(feed.batch_size) {
+                    // Retry until the sink takes the batch: nothing after
+                    // it may be acknowledged first
+                    loop {
+                        if stop.load(Ordering::Acquire) {
+                            return;
+                        }
+                        match sink.deliver(&feed, batch) {
+                            Ok(()) => break,
+                            Err(e) => {
+                                let delay =
+                                    fail(format!("delivery failed: {}", e), &mut retry_delay);
+                                if sleep_unless_stopped(&stop, delay) {
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                    let acknowledged = batch.last().map(|e| e.sequence).unwrap_or(0);
+                    let mut status = thread_status.lock();
+                    if let Err(e) = write_cursor(&cursor_path, acknowledged) {
+                        // The batch is delivered again after a restart
+                        warn!("Changefeed '{}': saving cursor failed: {}", feed.name, e);
+                    }
+                    status.acknowledged = acknowledged;
+                    status.last_error = None;
+                    drop(status);
+                    retry_delay = MIN_RETRY_DELAY;
+                }
+            }
+        })
+        .map_err(|e| DriftError::Other(format!("failed to start changefeed thread: {}", e)))?;
+
+    Ok(ChangefeedWorker {
+        stop,
+        status,
+        thread: Some(thread),
+    })
+}
+
+impl Engine {
+    /// `CREATE CHANGEFEED`: persist the changefeed, set its cursor and
+    /// start delivering. An empty name becomes `<table>_changefeed`.
+    pub fn create_changefeed(
+        &mut self,
+        mut definition: ChangefeedDefinition,
+        cursor: ChangefeedCursor,
+    ) -> Result<String> {
+        self.ensure_writable("CREATE CHANGEFEED")?;
+        let last_sequence = self
+            .table_sequence(&definition.table)
+            .ok_or_else(|| DriftError::TableNotFound(definition.table.clone()))?;
+        open_sink(&definition.sink)?;
+        if definition.name.is_empty() {
+            let feeds = self.changefeeds.lock();
+            let base = format!("{}_changefeed", definition.table.replace('.', "_"));
+            definition.name = (1..)
+                .map(|n| match n {
+                    1 => base.clone(),
+                    n => format!("{}_{}", base, n),
+                })
+                .find(|name| !feeds.contains_key(name))
+                .unwrap_or(base);
+        }
+        let name = definition.name.clone();
+        if self.changefeeds.lock().contains_key(&name) {
+            return Err(DriftError::Other(format!(
+                "changefeed \"{}\" already exists",
+                name
+            )));
+        }
+
+        let start = match cursor {
+            ChangefeedCursor::Now => last_sequence,
+            ChangefeedCursor::After(sequence) => sequence,
+        };
+        fs::create_dir_all(self.base_path().join("changefeeds"))?;
+        write_cursor(&cursor_path(self.base_path(), &name), start)?;
+        let worker = self.start_changefeed(&definition)?;
+        self.changefeeds.lock().insert(
+            name.clone(),
+            Changefeed {
+                definition,
+                worker: Some(worker),
+            },
+        );
+        self.save_changefeeds()?;
+        info!("Changefeed '{}' started after sequence {}", name, start);
+        Ok(name)
+    }
+
+    /// `DROP CHANGEFEED`: stop it and forget its cursor
+    pub fn drop_changefeed(&mut self, name: &str) -> Result<()> {
+        self.ensure_writable("DROP CHANGEFEED")?;
+        let removed =
+            self.changefeeds.lock().remove(name).ok_or_else(|| {
+                DriftError::Other(format!("changefeed \"{}\" does not exist", name))
+            })?;
+        // Joins the delivery thread
+        drop(removed);
+        let cursor = cursor_path(self.base_path(), name);
+        if cursor.exists() {
+            fs::remove_file(cursor)?;
+        }
+        self.save_changefeeds()
+    }
+
+    pub fn changefeed_exists(&self, name: &str) -> bool {
+        self.changefeeds.lock().contains_key(name)
+    }
+
+    /// Every changefeed with its delivery progress, by name
+    pub fn list_changefeeds(&self) -> Vec<ChangefeedInfo> {
+        self.changefeeds
+            .lock()
+            .values()
+            .map(|feed| {
+                let (acknowledged, last_error) = match &feed.worker {
+                    Some(worker) => {
+                        let status = worker.status.lock();
+                        (status.acknowledged, status.last_error.clone())
+                    }
+                    None => (
+                        read_cursor(&cursor_path(self.base_path(), &feed.definition.name))
+                            .unwrap_or(0),
+                        None,
+                    ),
+                };
+                ChangefeedInfo {
+                    definition: feed.definition.clone(),
+                    acknowledged,
+                    last_error,
+                    running: feed.worker.is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Load `changefeeds.json` and, unless the engine is read-only, resume
+    /// each changefeed after its cursor
+    pub(crate) fn load_changefeeds(&self) -> Result<()> {
+        let file = self.base_path().join("changefeeds.json");
+        if !file.exists() {
+            return Ok(());
+        }
+        let definitions: Vec<ChangefeedDefinition> =
+            serde_json::from_str(&fs::read_to_string(file)?)?;
+        let mut feeds = self.changefeeds.lock();
+        for definition in definitions {
+            feeds.insert(
+                definition.name.clone(),
+                Changefeed {
+                    definition,
+                    worker: None,
+                },
+            );
+        }
+        drop(feeds);
+        if !self.is_read_only() {
+            self.resume_changefeeds();
+        }
+        Ok(())
+    }
+
+    /// Start the changefeeds that aren't running. One that can't start
+    /// (its table is gone, its cursor unreadable) is logged and left
+    /// stopped.
+    pub(crate) fn resume_changefeeds(&self) {
+        let mut feeds = self.changefeeds.lock();
+        for feed in feeds.values_mut().filter(|feed| feed.worker.is_none()) {
+            match self.start_changefeed(&feed.definition) {
+                Ok(worker) => feed.worker = Some(worker),
+                Err(e) => warn!(
+                    "Changefeed '{}' could not be resumed: {}",
+                    feed.definition.name, e
+                ),
+            }
+        }
+    }
+
+    /// Stop every changefeed, leaving each to resume after its cursor
+    pub(crate) fn pause_changefeeds(&self) {
+        for feed in self.changefeeds.lock().values_mut() {
+            feed.worker = None;
+        }
+    }
+
+    fn start_changefeed(&self, definition: &ChangefeedDefinition) -> Result<ChangefeedWorker> {
+        let cursor_path = cursor_path(self.base_path(), &definition.name);
+        let mut subscription = self.subscribe(&definition.table)?;
+        subscription.seek(read_cursor(&cursor_path)?)?;
+        start_worker(definition, subscription, cursor_path)
+    }
+
+    fn save_changefeeds(&self) -> Result<()> {
+        let definitions: Vec<ChangefeedDefinition> = self
+            .changefeeds
+            .lock()
+            .values()
+            .map(|feed| feed.definition.clone())
+            .collect();
+        let json_data = serde_json::to_string_pretty(&definitions)?;
+        fs::write(self.base_path().join("changefeeds.json"), json_data)?;
+        Ok(())
+    }
+}
+
+/// `SHOW CHANGEFEEDS` rows
+pub fn changefeed_rows(engine: &Engine) -> Vec<Value> {
+    engine
+        .list_changefeeds()
+        .into_iter()
+        .map(|info| {
+            json!({
+                "name": info.definition.name,
+                "table": info.definition.table,
+                "sink": info.definition.sink,
+                "format": "json",
+                "batch_size": info.definition.batch_size,
+                "acknowledged_sequence": info.acknowledged,
+                "table_sequence": engine.table_sequence(&info.definition.table),
+                "running": info.running,
+                "last_error": info.last_error,
+            })
+        })
+        .collect()
+}
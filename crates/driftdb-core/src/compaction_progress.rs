@@ -0,0 +1,244 @@
+//! Progress of table compactions
+//!
+//! [`Engine::compact_table`](crate::Engine::compact_table) and the
+//! compaction scheduler report each phase to the engine's
+//! [`CompactionTracker`], which keeps the running or last finished
+//! compaction of every table. `VACUUM` holds the engine's write lock, so
+//! the tracker has its own: take it once with
+//! [`Engine::compaction_tracker`](crate::Engine::compaction_tracker) and
+//! read it without touching the engine.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde_json::{json, Value};
+
+/// Where a compaction is, in the order it runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactionPhase {
+    /// Writing the latest snapshot's rows to the compacted segment
+    WritingSnapshot,
+    /// Copying events newer than the snapshot out of the old segments
+    ReadingSegments,
+    /// Removing segments the snapshot fully covers
+    Cleaning,
+    /// Moving the compacted segment into place and reopening the table
+    Swapping,
+    Done,
+}
+
+impl CompactionPhase {
+    /// Share of the whole compaction done before and after this phase.
+    /// Rewriting rows dominates, so the split is by typical cost rather
+    /// than evenly.
+    fn span(self) -> (f64, f64) {
+        match self {
+            Self::WritingSnapshot => (0.0, 50.0),
+            Self::ReadingSegments => (50.0, 90.0),
+            Self::Cleaning => (90.0, 95.0),
+            Self::Swapping => (95.0, 100.0),
+            Self::Done => (100.0, 100.0),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::WritingSnapshot => "writing snapshot",
+            Self::ReadingSegments => "reading segments",
+            Self::Cleaning => "cleaning",
+            Self::Swapping => "swapping",
+            Self::Done => "done",
+        }
+    }
+}
+
+impl fmt::Display for CompactionPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One table's compaction
+#[derive(Debug, Clone)]
+pub struct CompactionProgress {
+    pub table: String,
+    pub phase: CompactionPhase,
+    /// Work finished in the current phase: rows while writing the
+    /// snapshot, segments after that
+    pub phase_done: u64,
+    pub phase_total: u64,
+    /// Why the compaction stopped early; `phase` is where it failed
+    pub error: Option<String>,
+    pub started_at: SystemTime,
+    started: Instant,
+    finished: Option<Instant>,
+}
+
+impl CompactionProgress {
+    pub fn new(table: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            phase: CompactionPhase::WritingSnapshot,
+            phase_done: 0,
+            phase_total: 0,
+            error: None,
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+            finished: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// Estimated share of the compaction done, 0–100
+    pub fn percent(&self) -> f64 {
+        let (start, end) = self.phase.span();
+        let within = if self.phase_total == 0 {
+            0.0
+        } else {
+            (self.phase_done as f64 / self.phase_total as f64).min(1.0)
+        };
+        start + (end - start) * within
+    }
+
+    /// Time since the compaction started, or how long it took
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+
+    /// Time left at the rate so far, once there is a rate to go by
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let percent = self.percent();
+        if self.is_finished() || percent <= 0.0 {
+            return None;
+        }
+        Some(self.elapsed().mul_f64((100.0 - percent) / percent))
+    }
+
+    /// The row `SHOW COMPACTION PROGRESS` and the admin API report
+    pub fn to_json(&self) -> Value {
+        let started_at = self
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let status = match (&self.error, self.is_finished()) {
+            (Some(_), _) => "failed",
+            (None, true) => "finished",
+            (None, false) => "running",
+        };
+        json!({
+            "table": self.table,
+            "status": status,
+            "phase": self.phase.as_str(),
+            "phase_done": self.phase_done,
+            "phase_total": self.phase_total,
+            "percent": (self.percent() * 10.0).round() / 10.0,
+            "elapsed_ms": self.elapsed().as_millis() as u64,
+            "estimated_remaining_ms": self.estimated_remaining().map(|d| d.as_millis() as u64),
+            "started_at_ms": started_at,
+            "error": self.error,
+        })
+    }
+
+    pub(crate) fn enter(&mut self, phase: CompactionPhase, total: u64) {
+        self.phase = phase;
+        self.phase_done = 0;
+        self.phase_total = total;
+    }
+
+    pub(crate) fn finish(&mut self, error: Option<String>) {
+        if error.is_none() {
+            self.enter(CompactionPhase::Done, 0);
+        }
+        self.error = error;
+        self.finished = Some(Instant::now());
+    }
+}
+
+/// The running or last finished compaction of each table
+#[derive(Debug, Default)]
+pub struct CompactionTracker {
+    compactions: RwLock<HashMap<String, CompactionProgress>>,
+}
+
+impl CompactionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, table: &str) -> Option<CompactionProgress> {
+        self.compactions.read().get(table).cloned()
+    }
+
+    /// Every table's compaction, running ones first, then by table
+    pub fn all(&self) -> Vec<CompactionProgress> {
+        let mut all: Vec<CompactionProgress> = self.compactions.read().values().cloned().collect();
+        all.sort_by(|a, b| {
+            a.is_finished()
+                .cmp(&b.is_finished())
+                .then_with(|| a.table.cmp(&b.table))
+        });
+        all
+    }
+
+    pub fn running(&self) -> Vec<CompactionProgress> {
+        self.all()
+            .into_iter()
+            .filter(|p| !p.is_finished())
+            .collect()
+    }
+
+    pub(crate) fn update(&self, progress: &CompactionProgress) {
+        self.compactions
+            .write()
+            .insert(progress.table.clone(), progress.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_follows_phases() {
+        let mut progress = CompactionProgress::new("t");
+        assert_eq!(progress.percent(), 0.0);
+        assert_eq!(progress.estimated_remaining(), None);
+
+        progress.enter(CompactionPhase::WritingSnapshot, 200);
+        progress.phase_done = 100;
+        assert_eq!(progress.percent(), 25.0);
+        assert!(progress.estimated_remaining().is_some());
+
+        progress.enter(CompactionPhase::ReadingSegments, 4);
+        progress.phase_done = 2;
+        assert_eq!(progress.percent(), 70.0);
+
+        progress.finish(None);
+        assert_eq!(progress.percent(), 100.0);
+        assert_eq!(progress.to_json()["status"], "finished");
+    }
+
+    #[test]
+    fn failures_stay_in_their_phase() {
+        let tracker = CompactionTracker::new();
+        let mut progress = CompactionProgress::new("t");
+        progress.enter(CompactionPhase::Cleaning, 3);
+        tracker.update(&progress);
+        assert_eq!(tracker.running().len(), 1);
+
+        progress.finish(Some("disk full".to_string()));
+        tracker.update(&progress);
+        assert!(tracker.running().is_empty());
+
+        let row = tracker.get("t").unwrap().to_json();
+        assert_eq!(row["status"], "failed");
+        assert_eq!(row["phase"], "cleaning");
+        assert_eq!(row["error"], "disk full");
+    }
+}
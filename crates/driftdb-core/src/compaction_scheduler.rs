@@ -0,0 +1,497 @@
+//! Background compaction scheduler
+//!
+//! Compacting a table (snapshot + `VACUUM`) is otherwise manual. The
+//! scheduler checks every table on a fixed interval and compacts the ones
+//! that crossed a threshold in their [`CompactionPolicy`]:
+//!
+//! - events written since the table's last snapshot,
+//! - the share of stored events that are superseded versions, or
+//! - time since the table was last compacted (only if it has new writes).
+//!
+//! The engine's lock is only held to look the tables up. Measuring a table
+//! and compacting it work on its storage directly: a compaction snapshots
+//! the table and rewrites its segments as `VACUUM FULL` does, which lets
+//! reads and appends to the table carry on until the final swap. Each tick
+//! compacts at most one table, and each table's thresholds are scaled by a
+//! random jitter so tables that fill up together are not all compacted on
+//! the same tick.
+//!
+//! Compaction folds history before the snapshot into current rows, so
+//! time travel to earlier sequences is lost for compacted tables. The
+//! scheduler is disabled by default, and enabled by default by a config
+//! file.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::compaction_progress::{CompactionPhase, CompactionProgress, CompactionTracker};
+use crate::engine::Engine;
+use crate::errors::Result;
+use crate::snapshot::SnapshotManager;
+use crate::storage::TableStorage;
+
+/// When a table should be compacted. A threshold of 0 disables that check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompactionPolicy {
+    /// Compact once this many events were written since the last snapshot
+    pub max_events_since_snapshot: u64,
+    /// Compact once this fraction of stored events are superseded versions
+    pub max_dead_version_ratio: f64,
+    /// Compact at least this often while the table receives writes
+    pub max_interval_secs: u64,
+    /// New events needed before the dead-version ratio is measured, which
+    /// means reading the whole table
+    pub min_events_for_ratio: u64,
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self {
+            max_events_since_snapshot: 100_000,
+            max_dead_version_ratio: 0.5,
+            max_interval_secs: 24 * 3600,
+            min_events_for_ratio: 10_000,
+        }
+    }
+}
+
+/// Scheduler settings with per-table policy overrides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompactionConfig {
+    /// Off by default, but on when a config file leaves it out
+    #[serde(default = "enabled_in_file")]
+    pub enabled: bool,
+    /// How often tables are checked
+    pub check_interval_secs: u64,
+    /// Thresholds are scaled by a random factor in `1.0..=1.0 + jitter`
+    pub jitter: f64,
+    /// Policy for tables without an override
+    pub default_policy: CompactionPolicy,
+    /// Per-table policies, replacing `default_policy` for that table
+    pub tables: HashMap<String, CompactionPolicy>,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            check_interval_secs: 60,
+            jitter: 0.2,
+            default_policy: CompactionPolicy::default(),
+            tables: HashMap::new(),
+        }
+    }
+}
+
+fn enabled_in_file() -> bool {
+    true
+}
+
+impl CompactionConfig {
+    pub fn policy_for(&self, table: &str) -> &CompactionPolicy {
+        self.tables.get(table).unwrap_or(&self.default_policy)
+    }
+}
+
+/// Table figures the scheduler decides on, from [`Engine::compaction_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionStats {
+    pub events_since_snapshot: u64,
+    /// Events stored in the table's segments, if counted
+    pub stored_events: Option<u64>,
+    /// Rows in the current state, if counted
+    pub live_rows: Option<u64>,
+}
+
+impl CompactionStats {
+    /// Share of stored events that no longer describe a live row
+    pub fn dead_version_ratio(&self) -> Option<f64> {
+        match (self.stored_events, self.live_rows) {
+            (Some(stored), Some(live)) if stored > 0 => {
+                Some(stored.saturating_sub(live) as f64 / stored as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// [`Engine::compaction_stats`] for a table's storage. Events count from
+/// its latest snapshot or compaction, whichever is later.
+pub(crate) fn table_compaction_stats(
+    storage: &TableStorage,
+    snapshot_mgr: &SnapshotManager,
+    count_versions: bool,
+) -> Result<CompactionStats> {
+    let last_snapshot = snapshot_mgr.list_snapshots()?.last().copied().unwrap_or(0);
+    let folded_through = last_snapshot.max(storage.last_compaction_sequence());
+    let mut stats = CompactionStats {
+        events_since_snapshot: storage.last_sequence().saturating_sub(folded_through),
+        stored_events: None,
+        live_rows: None,
+    };
+    if count_versions {
+        stats.stored_events = Some(storage.read_events_with_limit(Some(usize::MAX))?.len() as u64);
+        stats.live_rows = Some(storage.reconstruct_state_at(None)?.len() as u64);
+    }
+    Ok(stats)
+}
+
+/// A table the scheduler looked up, to work on without the engine's lock
+struct TableHandles {
+    name: String,
+    storage: Arc<TableStorage>,
+    snapshots: Arc<SnapshotManager>,
+}
+
+/// Why a table was compacted
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompactionReason {
+    EventsSinceSnapshot(u64),
+    DeadVersionRatio(f64),
+    Interval(Duration),
+}
+
+impl fmt::Display for CompactionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventsSinceSnapshot(n) => write!(f, "{} events since last snapshot", n),
+            Self::DeadVersionRatio(r) => write!(f, "{:.0}% dead versions", r * 100.0),
+            Self::Interval(d) => write!(f, "{}s since last compaction", d.as_secs()),
+        }
+    }
+}
+
+impl CompactionPolicy {
+    /// Whether a table needs the dead-version ratio measured
+    pub fn wants_version_count(&self, stats: &CompactionStats) -> bool {
+        self.max_dead_version_ratio > 0.0
+            && stats.events_since_snapshot >= self.min_events_for_ratio.max(1)
+    }
+
+    /// Check the thresholds, scaled by `jitter` (>= 1.0)
+    pub fn due(
+        &self,
+        stats: &CompactionStats,
+        since_last: Duration,
+        jitter: f64,
+    ) -> Option<CompactionReason> {
+        if stats.events_since_snapshot == 0 {
+            return None;
+        }
+
+        let max_events = (self.max_events_since_snapshot as f64 * jitter) as u64;
+        if self.max_events_since_snapshot > 0 && stats.events_since_snapshot >= max_events {
+            return Some(CompactionReason::EventsSinceSnapshot(
+                stats.events_since_snapshot,
+            ));
+        }
+
+        if self.max_dead_version_ratio > 0.0 {
+            if let Some(ratio) = stats.dead_version_ratio() {
+                if ratio >= (self.max_dead_version_ratio * jitter).min(1.0) {
+                    return Some(CompactionReason::DeadVersionRatio(ratio));
+                }
+            }
+        }
+
+        let max_interval = Duration::from_secs(self.max_interval_secs).mul_f64(jitter);
+        if self.max_interval_secs > 0 && since_last >= max_interval {
+            return Some(CompactionReason::Interval(since_last));
+        }
+
+        None
+    }
+}
+
+pub struct CompactionScheduler {
+    config: CompactionConfig,
+    started: Instant,
+    last_compacted: HashMap<String, Instant>,
+    jitter: HashMap<String, f64>,
+    /// Table the next pass starts after, so one hot table can't starve
+    /// the rest
+    cursor: Option<String>,
+}
+
+impl CompactionScheduler {
+    pub fn new(config: CompactionConfig) -> Self {
+        Self {
+            config,
+            started: Instant::now(),
+            last_compacted: HashMap::new(),
+            jitter: HashMap::new(),
+            cursor: None,
+        }
+    }
+
+    pub fn config(&self) -> &CompactionConfig {
+        &self.config
+    }
+
+    fn jitter_for(&mut self, table: &str) -> f64 {
+        let spread = self.config.jitter.max(0.0);
+        *self
+            .jitter
+            .entry(table.to_string())
+            .or_insert_with(|| 1.0 + rand::random::<f64>() * spread)
+    }
+
+    /// Find the next table that is due, in round-robin order. The engine's
+    /// read lock is only held to look the tables up.
+    fn next_due(
+        &mut self,
+        engine: &RwLock<Engine>,
+    ) -> Result<Option<(TableHandles, CompactionReason)>> {
+        let mut tables = {
+            let engine = engine.read();
+            if engine.is_read_only() {
+                return Ok(None);
+            }
+            let mut tables = Vec::new();
+            for name in engine.list_tables() {
+                let (storage, snapshots) = engine.compaction_handles(&name)?;
+                tables.push(TableHandles {
+                    name,
+                    storage,
+                    snapshots,
+                });
+            }
+            tables
+        };
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(cursor) = &self.cursor {
+            let split = tables.partition_point(|t| &t.name <= cursor);
+            tables.rotate_left(split);
+        }
+
+        for table in tables {
+            let policy = self.config.policy_for(&table.name).clone();
+            let mut stats = table_compaction_stats(&table.storage, &table.snapshots, false)?;
+            if policy.wants_version_count(&stats) {
+                stats = table_compaction_stats(&table.storage, &table.snapshots, true)?;
+            }
+
+            let since_last = self
+                .last_compacted
+                .get(&table.name)
+                .copied()
+                .unwrap_or(self.started)
+                .elapsed();
+            let jitter = self.jitter_for(&table.name);
+            if let Some(reason) = policy.due(&stats, since_last, jitter) {
+                return Ok(Some((table, reason)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Compact at most one due table. The engine's lock is only taken to
+    /// look tables up; foreground reads and writes go on meanwhile.
+    pub fn run_once(
+        &mut self,
+        engine: &RwLock<Engine>,
+    ) -> Result<Option<(String, CompactionReason)>> {
+        let Some((table, reason)) = self.next_due(engine)? else {
+            return Ok(None);
+        };
+
+        let tracker = {
+            let engine = engine.read();
+            if engine.is_read_only() || !engine.list_tables().contains(&table.name) {
+                return Ok(None);
+            }
+            engine.compaction_tracker()
+        };
+        let start = Instant::now();
+        compact(&table, &tracker)?;
+        info!(
+            "Compacted table '{}' ({}) in {:?}",
+            table.name,
+            reason,
+            start.elapsed()
+        );
+
+        let table = table.name;
+        self.last_compacted.insert(table.clone(), Instant::now());
+        // Draw a new jitter for the table's next round
+        self.jitter.remove(&table);
+        self.cursor = Some(table.clone());
+        Ok(Some((table, reason)))
+    }
+
+    /// Run the scheduler on a background thread until the handle is
+    /// stopped or dropped
+    pub fn spawn(mut self, engine: Arc<RwLock<Engine>>) -> CompactionSchedulerHandle {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let interval = Duration::from_secs(self.config.check_interval_secs.max(1));
+
+        let thread = std::thread::Builder::new()
+            .name("driftdb-compaction".to_string())
+            .spawn(move || {
+                info!(
+                    "Compaction scheduler started, checking every {:?}",
+                    interval
+                );
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    match self.run_once(&engine) {
+                        Ok(Some(_)) => {}
+                        Ok(None) => debug!("No tables due for compaction"),
+                        Err(e) => warn!("Scheduled compaction failed: {}", e),
+                    }
+                }
+                info!("Compaction scheduler stopped");
+            })
+            .expect("failed to spawn compaction thread");
+
+        CompactionSchedulerHandle {
+            stop: Some(stop_tx),
+            thread: Some(thread),
+        }
+    }
+}
+
+/// Snapshot `table` and fold its history into the snapshot's rows, as
+/// [`Engine::compact_table`] does, but through
+/// [`TableStorage::vacuum_full`], which appends don't wait on. A table
+/// with a retention policy folds only what the policy lets go.
+fn compact(table: &TableHandles, tracker: &CompactionTracker) -> Result<()> {
+    let storage = &table.storage;
+    let mut progress = CompactionProgress::new(&table.name);
+    progress.enter(CompactionPhase::WritingSnapshot, 1);
+    tracker.update(&progress);
+
+    let result = table
+        .snapshots
+        .create_snapshot(storage, storage.last_sequence())
+        .and_then(|()| {
+            progress.enter(CompactionPhase::Swapping, 1);
+            tracker.update(&progress);
+            if storage.schema().retention.is_unbounded() {
+                storage.vacuum_full().map(drop)
+            } else {
+                storage.prune_history().map(drop)
+            }
+        });
+    progress.finish(result.as_ref().err().map(|e| e.to_string()));
+    tracker.update(&progress);
+    result
+}
+
+pub struct CompactionSchedulerHandle {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CompactionSchedulerHandle {
+    /// Stop the scheduler, waiting for a compaction in progress to finish
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for CompactionSchedulerHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(events: u64, stored: Option<u64>, live: Option<u64>) -> CompactionStats {
+        CompactionStats {
+            events_since_snapshot: events,
+            stored_events: stored,
+            live_rows: live,
+        }
+    }
+
+    #[test]
+    fn thresholds_trigger_compaction() {
+        let policy = CompactionPolicy {
+            max_events_since_snapshot: 100,
+            max_dead_version_ratio: 0.5,
+            max_interval_secs: 60,
+            min_events_for_ratio: 10,
+        };
+        let recent = Duration::from_secs(1);
+
+        assert_eq!(policy.due(&stats(0, None, None), recent * 1000, 1.0), None);
+        assert_eq!(policy.due(&stats(50, None, None), recent, 1.0), None);
+        assert_eq!(
+            policy.due(&stats(100, None, None), recent, 1.0),
+            Some(CompactionReason::EventsSinceSnapshot(100))
+        );
+        assert_eq!(
+            policy.due(&stats(20, Some(100), Some(30)), recent, 1.0),
+            Some(CompactionReason::DeadVersionRatio(0.7))
+        );
+        assert_eq!(
+            policy.due(&stats(1, None, None), Duration::from_secs(60), 1.0),
+            Some(CompactionReason::Interval(Duration::from_secs(60)))
+        );
+    }
+
+    #[test]
+    fn jitter_raises_thresholds() {
+        let policy = CompactionPolicy {
+            max_events_since_snapshot: 100,
+            ..Default::default()
+        };
+        let recent = Duration::from_secs(1);
+        assert!(policy.due(&stats(110, None, None), recent, 1.2).is_none());
+        assert!(policy.due(&stats(120, None, None), recent, 1.2).is_some());
+    }
+
+    #[test]
+    fn version_count_waits_for_enough_events() {
+        let policy = CompactionPolicy {
+            min_events_for_ratio: 10,
+            ..Default::default()
+        };
+        assert!(!policy.wants_version_count(&stats(9, None, None)));
+        assert!(policy.wants_version_count(&stats(10, None, None)));
+    }
+
+    #[test]
+    fn table_overrides_replace_the_default_policy() {
+        let mut config = CompactionConfig::default();
+        let hot = CompactionPolicy {
+            max_events_since_snapshot: 10,
+            ..Default::default()
+        };
+        config.tables.insert("hot".to_string(), hot.clone());
+
+        assert_eq!(config.policy_for("hot"), &hot);
+        assert_eq!(config.policy_for("cold"), &config.default_policy);
+
+        let parsed: CompactionConfig = serde_json::from_str(
+            r#"{"enabled": true, "tables": {"hot": {"max_events_since_snapshot": 10}}}"#,
+        )
+        .unwrap();
+        assert!(parsed.enabled);
+        let from_file: CompactionConfig = serde_json::from_str("{}").unwrap();
+        assert!(from_file.enabled);
+        assert!(!CompactionConfig::default().enabled);
+        assert_eq!(parsed.policy_for("hot").max_events_since_snapshot, 10);
+        assert_eq!(parsed.policy_for("hot").max_dead_version_ratio, 0.5);
+    }
+}
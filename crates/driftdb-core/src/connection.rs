@@ -4,6 +4,7 @@
 //! - Configurable pool size limits
 //! - Connection health checking
 //! - Automatic cleanup of idle connections
+//! - Warmup of the minimum connections and pre-ping before hand-out
 //! - Fair scheduling and backpressure
 //! - Rate limiting per client
 
@@ -26,6 +27,7 @@ use crate::wal::{WalConfig, WalManager};
 
 /// Connection pool configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PoolConfig {
     /// Minimum number of connections to maintain
     pub min_connections: usize,
@@ -43,6 +45,12 @@ pub struct PoolConfig {
     pub max_concurrent_per_client: usize,
     /// Queue size for pending requests
     pub max_queue_size: usize,
+    /// Create `min_connections` when the pool is created, so the first
+    /// requests don't pay for them; otherwise the first health check does
+    pub warmup: bool,
+    /// Check each connection before handing it out, replacing it if it
+    /// is broken
+    pub pre_ping: bool,
 }
 
 impl Default for PoolConfig {
@@ -56,6 +64,8 @@ impl Default for PoolConfig {
             rate_limit_per_client: Some(1000), // 1000 req/s per client
             max_concurrent_per_client: 10,
             max_queue_size: 1000,
+            warmup: true,
+            pre_ping: false,
         }
     }
 }
@@ -121,6 +131,17 @@ impl Connection {
     pub fn has_transaction(&self) -> bool {
         self.current_transaction.is_some()
     }
+
+    /// Whether the connection can be handed out: idle, not past
+    /// `idle_timeout`, and not holding a transaction that has ended
+    pub fn is_usable(&self, idle_timeout: Duration) -> bool {
+        self.is_idle()
+            && !self.is_expired(idle_timeout)
+            && self
+                .current_transaction
+                .as_ref()
+                .is_none_or(|txn| txn.lock().is_active())
+    }
 }
 
 /// Rate limiter using token bucket algorithm
@@ -224,6 +245,10 @@ impl ClientSession {
     }
 }
 
+/// Checks the backend behind a connection for pre-ping, returning
+/// whether it is reachable
+pub type ConnectionPinger = Arc<dyn Fn(&Connection) -> bool + Send + Sync>;
+
 /// Connection pool manager
 pub struct ConnectionPool {
     config: PoolConfig,
@@ -235,6 +260,8 @@ pub struct ConnectionPool {
     metrics: Arc<Metrics>,
     transaction_manager: Arc<TransactionManager>,
     shutdown: Arc<AtomicU64>, // 0 = running, 1 = shutting down
+    pinger: Option<ConnectionPinger>,
+    recreated: Arc<AtomicU64>,
 }
 
 impl ConnectionPool {
@@ -255,14 +282,25 @@ impl ConnectionPool {
             metrics,
             transaction_manager,
             shutdown: Arc::new(AtomicU64::new(0)),
+            pinger: None,
+            recreated: Arc::new(AtomicU64::new(0)),
         };
 
         // Pre-create minimum connections
-        pool.ensure_min_connections()?;
+        if pool.config.warmup {
+            pool.ensure_min_connections()?;
+        }
 
         Ok(pool)
     }
 
+    /// Check the backend as well as the connection itself when
+    /// `pre_ping` is on
+    pub fn with_pinger(mut self, pinger: ConnectionPinger) -> Self {
+        self.pinger = Some(pinger);
+        self
+    }
+
     /// Ensure minimum connections exist
     fn ensure_min_connections(&self) -> Result<()> {
         let current = self.connections.read().len();
@@ -274,19 +312,52 @@ impl ConnectionPool {
         Ok(())
     }
 
-    /// Create a new connection
+    /// Create a new connection and make it available
     fn create_connection(&self, client_addr: Option<SocketAddr>) -> Result<u64> {
+        let conn_id = self.open_connection(client_addr);
+        self.available.lock().push_back(conn_id);
+        Ok(conn_id)
+    }
+
+    /// Create a new connection for the caller to use
+    fn open_connection(&self, client_addr: Option<SocketAddr>) -> u64 {
         let conn_id = self.next_conn_id.fetch_add(1, Ordering::SeqCst);
         let conn = Arc::new(Mutex::new(Connection::new(conn_id, client_addr)));
 
         self.connections.write().insert(conn_id, conn);
-        self.available.lock().push_back(conn_id);
         self.metrics
             .active_connections
             .fetch_add(1, Ordering::Relaxed);
 
         debug!("Created connection {}", conn_id);
-        Ok(conn_id)
+        conn_id
+    }
+
+    /// Pre-ping: whether the connection and its backend are usable
+    fn ping(&self, conn_id: u64) -> bool {
+        let Some(conn) = self.connections.read().get(&conn_id).cloned() else {
+            return false;
+        };
+        let conn = conn.lock();
+        conn.is_usable(self.config.idle_timeout)
+            && self.pinger.as_ref().is_none_or(|ping| ping(&conn))
+    }
+
+    /// Replace a connection that failed its pre-ping with a new one,
+    /// which must pass in turn
+    fn recreate_connection(&self, conn_id: u64, client_addr: SocketAddr) -> Result<u64> {
+        self.remove_connection(conn_id);
+        let new_id = self.open_connection(Some(client_addr));
+        self.recreated.fetch_add(1, Ordering::Relaxed);
+        debug!("Recreated broken connection {} as {}", conn_id, new_id);
+        if self.ping(new_id) {
+            Ok(new_id)
+        } else {
+            self.remove_connection(new_id);
+            Err(DriftError::Other(
+                "Connection failed pre-ping: backend unavailable".to_string(),
+            ))
+        }
     }
 
     /// Acquire a connection from the pool
@@ -339,11 +410,16 @@ impl ConnectionPool {
                 id
             } else if self.connections.read().len() < self.config.max_connections {
                 drop(available); // Release lock before creating
-                self.create_connection(Some(client_addr))?
+                self.open_connection(Some(client_addr))
             } else {
                 return Err(DriftError::Other("No connections available".to_string()));
             }
         };
+        let conn_id = if self.config.pre_ping && !self.ping(conn_id) {
+            self.recreate_connection(conn_id, client_addr)?
+        } else {
+            conn_id
+        };
 
         // Mark connection as active
         {
@@ -471,6 +547,7 @@ impl ConnectionPool {
             active_connections: active_count,
             connections_with_transactions: with_transactions,
             total_requests_handled: total_requests,
+            connections_recreated: self.recreated.load(Ordering::Relaxed),
         }
     }
 }
@@ -487,6 +564,8 @@ impl Clone for ConnectionPool {
             metrics: self.metrics.clone(),
             transaction_manager: self.transaction_manager.clone(),
             shutdown: self.shutdown.clone(),
+            pinger: self.pinger.clone(),
+            recreated: self.recreated.clone(),
         }
     }
 }
@@ -544,6 +623,8 @@ pub struct PoolStats {
     pub total_created: u64,
     pub connections_with_transactions: usize,
     pub total_requests_handled: u64,
+    /// Connections replaced after failing a pre-ping
+    pub connections_recreated: u64,
 }
 
 /// Engine pool that manages connections to a DriftDB Engine
@@ -568,7 +649,11 @@ impl EnginePool {
         )?);
         let transaction_manager = Arc::new(TransactionManager::new_with_deps(wal, metrics.clone()));
 
-        let connection_pool = ConnectionPool::new(config, metrics, transaction_manager)?;
+        // Pre-ping checks that the data directory is still reachable,
+        // without waiting on the engine lock behind a long write
+        let data_dir = engine.read().base_path().to_path_buf();
+        let connection_pool = ConnectionPool::new(config, metrics, transaction_manager)?
+            .with_pinger(Arc::new(move |_| data_dir.is_dir()));
 
         Ok(Self {
             engine,
@@ -698,6 +783,54 @@ mod tests {
         assert_eq!(stats.available_connections, 1);
     }
 
+    #[tokio::test]
+    async fn test_warmup_and_pre_ping() {
+        let temp_dir = TempDir::new().unwrap();
+        let wal = Arc::new(
+            WalManager::new(temp_dir.path().join("test.wal"), WalConfig::default()).unwrap(),
+        );
+        let metrics = Arc::new(Metrics::new());
+        let tx_mgr = Arc::new(TransactionManager::new_with_deps(wal, metrics.clone()));
+
+        // Without warmup the pool starts empty
+        let config = PoolConfig {
+            min_connections: 2,
+            warmup: false,
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(config, metrics.clone(), tx_mgr.clone()).unwrap();
+        assert_eq!(pool.stats().total_connections, 0);
+
+        // A backend that goes down breaks the pooled connections
+        let backend_up = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let up = backend_up.clone();
+        let config = PoolConfig {
+            min_connections: 2,
+            pre_ping: true,
+            ..Default::default()
+        };
+        let pool = ConnectionPool::new(config, metrics, tx_mgr)
+            .unwrap()
+            .with_pinger(Arc::new(move |conn| {
+                up.load(Ordering::Acquire) || conn.id > 2
+            }));
+        assert_eq!(pool.stats().total_connections, 2);
+
+        backend_up.store(false, Ordering::Release);
+        let client_addr = "127.0.0.1:12345".parse().unwrap();
+        let guard = pool.acquire(client_addr).await.unwrap();
+        assert!(guard.id() > 2);
+        drop(guard);
+        let stats = pool.stats();
+        assert_eq!(stats.connections_recreated, 1);
+        assert_eq!(stats.total_connections, 2);
+
+        // A replacement that fails too is an error, not a broken connection
+        let pool = pool.with_pinger(Arc::new(|_| false));
+        assert!(pool.acquire(client_addr).await.is_err());
+        assert_eq!(pool.stats().connections_recreated, 2);
+    }
+
     #[test]
     fn test_rate_limiter() {
         let limiter = RateLimiter::new(10); // 10 tokens per second
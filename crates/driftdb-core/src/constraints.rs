@@ -701,6 +701,17 @@ mod tests {
             name: "users".to_string(),
             primary_key: "id".to_string(),
             columns: vec![],
+            changes: vec![],
+            defaults: Default::default(),
+            enums: Default::default(),
+            checks: vec![],
+            unique: Default::default(),
+            foreign_keys: vec![],
+            compression: Default::default(),
+            bloom_filter_fpp: None,
+            bloom_filter_columns: Default::default(),
+            storage: Default::default(),
+            retention: Default::default(),
         };
 
         // This should fail
@@ -0,0 +1,467 @@
+//! Exact decimals for DECIMAL/NUMERIC columns
+//!
+//! Row values are `serde_json::Value`, whose fractional numbers are f64.
+//! Any decimal with at most [`MAX_EXACT_DIGITS`] significant digits
+//! survives a round trip through f64 (its shortest representation is the
+//! decimal itself). So a DECIMAL column stores each value rounded to the
+//! declared scale as a JSON number when the number reads back as the same
+//! decimal, and as its exact decimal text when it wouldn't.
+//!
+//! Arithmetic is where rounding errors creep in (`0.1 + 0.2`), so SUM, AVG
+//! and `+ - *` over fractional numbers or decimal text read each operand
+//! back as its decimal text and compute on [`Decimal`], a 128-bit
+//! fixed-point value. Comparisons between a number and decimal text go
+//! through [`Decimal`] too.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Significant digits an f64 is guaranteed to carry exactly
+pub const MAX_EXACT_DIGITS: u32 = 15;
+
+/// Largest scale kept by division and parsing
+const MAX_SCALE: u32 = 30;
+
+/// `mantissa * 10^-scale`
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+fn pow10(exp: u32) -> Option<i128> {
+    10i128.checked_pow(exp)
+}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Parse decimal text such as `-12.50`, `.5` or `1e-7`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (number, exponent) = match text.find(['e', 'E']) {
+            Some(i) => (&text[..i], text[i + 1..].parse::<i32>().ok()?),
+            None => (text, 0),
+        };
+        let (negative, digits) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if int_part.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+        let mut mantissa: i128 = 0;
+        for c in int_part.chars().chain(frac_part.chars()) {
+            let digit = c.to_digit(10)? as i128;
+            mantissa = mantissa.checked_mul(10)?.checked_add(digit)?;
+        }
+        let mut scale = frac_part.len() as i32 - exponent;
+        if scale < 0 {
+            mantissa = mantissa.checked_mul(pow10(scale.unsigned_abs())?)?;
+            scale = 0;
+        }
+        let value = Self::new(if negative { -mantissa } else { mantissa }, scale as u32);
+        if value.scale > MAX_SCALE {
+            return value.round(MAX_SCALE);
+        }
+        Some(value)
+    }
+
+    /// Read a row value as a decimal: numbers by their shortest decimal
+    /// representation, strings by their text
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Number(n) => Self::parse(&n.to_string()),
+            Value::String(s) => Self::parse(s),
+            _ => None,
+        }
+    }
+
+    /// The value as a row value. Integers become JSON integers and other
+    /// values JSON numbers when the f64 reads back as the same decimal;
+    /// anything else is returned as its exact text.
+    pub fn to_value(self) -> Value {
+        if self.scale == 0 {
+            if let Ok(i) = i64::try_from(self.mantissa) {
+                return Value::from(i);
+            }
+        } else if let Some(n) = self
+            .to_string()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+        {
+            if Self::parse(&n.to_string()).is_some_and(|back| back == self) {
+                return Value::Number(n);
+            }
+        }
+        Value::String(self.to_string())
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Digits in the mantissa, ignoring trailing fractional zeros
+    pub fn significant_digits(&self) -> u32 {
+        let normalized = self.normalize();
+        normalized
+            .mantissa
+            .unsigned_abs()
+            .checked_ilog10()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Digits before the decimal point
+    pub fn integer_digits(&self) -> u32 {
+        let int_part = self.mantissa.unsigned_abs() / 10u128.pow(self.scale);
+        if int_part == 0 {
+            0
+        } else {
+            int_part.ilog10() + 1
+        }
+    }
+
+    /// Drop trailing fractional zeros
+    pub fn normalize(self) -> Self {
+        let mut value = self;
+        while value.scale > 0 && value.mantissa % 10 == 0 {
+            value.mantissa /= 10;
+            value.scale -= 1;
+        }
+        value
+    }
+
+    /// Round (half away from zero) or pad to `scale` fractional digits
+    pub fn round(self, scale: u32) -> Option<Self> {
+        match scale.cmp(&self.scale) {
+            Ordering::Equal => Some(self),
+            Ordering::Greater => Some(Self::new(
+                self.mantissa.checked_mul(pow10(scale - self.scale)?)?,
+                scale,
+            )),
+            Ordering::Less => {
+                let divisor = pow10(self.scale - scale)?;
+                let quotient = self.mantissa / divisor;
+                let remainder = (self.mantissa % divisor).abs();
+                let rounded = if remainder * 2 >= divisor {
+                    quotient + self.mantissa.signum()
+                } else {
+                    quotient
+                };
+                Some(Self::new(rounded, scale))
+            }
+        }
+    }
+
+    /// Round to at most `digits` significant digits
+    pub fn round_significant(self, digits: u32) -> Option<Self> {
+        let excess = self.significant_digits().saturating_sub(digits);
+        let normalized = self.normalize();
+        if excess == 0 || normalized.scale == 0 {
+            return Some(normalized);
+        }
+        normalized
+            .round(normalized.scale.saturating_sub(excess))
+            .map(Self::normalize)
+    }
+
+    /// Both values at their common scale
+    fn aligned(self, other: Self) -> Option<(i128, i128, u32)> {
+        let scale = self.scale.max(other.scale);
+        Some((
+            self.round(scale)?.mantissa,
+            other.round(scale)?.mantissa,
+            scale,
+        ))
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Self::new(a.checked_add(b)?, scale))
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (a, b, scale) = self.aligned(other)?;
+        Some(Self::new(a.checked_sub(b)?, scale))
+    }
+
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let value = Self::new(
+            self.mantissa.checked_mul(other.mantissa)?,
+            self.scale + other.scale,
+        );
+        if value.scale > MAX_SCALE {
+            return value.round(MAX_SCALE);
+        }
+        Some(value)
+    }
+
+    /// `self / other` rounded to `scale` fractional digits; `None` on
+    /// division by zero or overflow
+    pub fn checked_div(self, other: Self, scale: u32) -> Option<Self> {
+        if other.mantissa == 0 {
+            return None;
+        }
+        // self.m / 10^a / (other.m / 10^b) * 10^s, with one extra digit
+        // for rounding
+        let shift = (scale + 1 + other.scale) as i64 - self.scale as i64;
+        let (numerator, denominator) = if shift >= 0 {
+            (
+                self.mantissa.checked_mul(pow10(shift as u32)?)?,
+                other.mantissa,
+            )
+        } else {
+            (
+                self.mantissa,
+                other
+                    .mantissa
+                    .checked_mul(pow10(shift.unsigned_abs() as u32)?)?,
+            )
+        };
+        Self::new(numerator / denominator, scale + 1).round(scale)
+    }
+
+    /// Exact sum of the values; `None` if any isn't a number or the sum
+    /// overflows
+    pub fn sum<'a>(values: impl IntoIterator<Item = &'a Value>) -> Option<Self> {
+        values.into_iter().try_fold(Self::new(0, 0), |acc, v| {
+            acc.checked_add(Self::from_value(v)?)
+        })
+    }
+
+    /// Average of the values to [`MAX_EXACT_DIGITS`] significant digits,
+    /// or as many as the widest value carries, with at least one
+    /// fractional digit
+    pub fn average(values: &[Value]) -> Option<Self> {
+        let sum = Self::sum(values)?;
+        let count = Self::new(values.len() as i128, 0);
+        let scale = values
+            .iter()
+            .filter_map(Self::from_value)
+            .map(|d| d.scale)
+            .max()
+            .unwrap_or(0);
+        let digits = values
+            .iter()
+            .filter_map(Self::from_value)
+            .map(|d| d.significant_digits())
+            .fold(MAX_EXACT_DIGITS, u32::max);
+        let avg = sum
+            .checked_div(count, (scale + digits + 1).min(MAX_SCALE))?
+            .round_significant(digits)?;
+        // Averages are fractional even when whole, as they were as floats
+        if avg.scale == 0 {
+            avg.round(1)
+        } else {
+            Some(avg)
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.normalize(), other.normalize());
+        match a.aligned(b) {
+            Some((a, b, _)) => a.cmp(&b),
+            // Aligning overflowed: the values differ by more than i128 can
+            // resolve at this scale, so f64 orders them correctly
+            None => {
+                let to_f64 = |d: Decimal| d.mantissa as f64 / 10f64.powi(d.scale as i32);
+                to_f64(a).total_cmp(&to_f64(b))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int_part, frac_part)
+    }
+}
+
+/// Declared `DECIMAL(precision, scale)` / `NUMERIC(...)` column type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecimalSpec {
+    pub precision: Option<u32>,
+    pub scale: Option<u32>,
+}
+
+impl DecimalSpec {
+    /// Parse a column type such as `NUMERIC(10,2)`, `DECIMAL(5)` or `DEC`
+    pub fn from_col_type(col_type: &str) -> Option<Self> {
+        let upper = col_type.trim().to_ascii_uppercase();
+        let (name, args) = match upper.split_once('(') {
+            Some((name, rest)) => (name.trim(), Some(rest.strip_suffix(')')?)),
+            None => (upper.as_str(), None),
+        };
+        if !matches!(name, "DECIMAL" | "NUMERIC" | "DEC") {
+            return None;
+        }
+        let mut parts = args
+            .into_iter()
+            .flat_map(|a| a.split(','))
+            .map(|p| p.trim().parse::<u32>().ok());
+        let precision = parts.next().flatten();
+        let scale = parts.next().flatten().or(precision.map(|_| 0));
+        Some(Self { precision, scale })
+    }
+
+    /// Value written to a column of this type: parsed, rounded to the
+    /// scale and checked against the precision
+    pub fn coerce(&self, value: Value) -> Result<Value> {
+        if value.is_null() {
+            return Ok(value);
+        }
+        let parsed = Decimal::from_value(&value).ok_or_else(|| {
+            DriftError::InvalidQuery(format!("invalid input syntax for type numeric: {}", value))
+        })?;
+        let decimal = match self.scale {
+            Some(scale) => parsed.round(scale),
+            None => Some(parsed),
+        }
+        .ok_or_else(|| DriftError::InvalidQuery("numeric field overflow".to_string()))?;
+
+        if let Some(precision) = self.precision {
+            let scale = self.scale.unwrap_or(0);
+            if decimal.integer_digits() > precision.saturating_sub(scale) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "numeric field overflow: a field with precision {}, scale {} must round to an absolute value less than 10^{}",
+                    precision,
+                    scale,
+                    precision.saturating_sub(scale)
+                )));
+            }
+        }
+        Ok(decimal.to_value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn d(text: &str) -> Decimal {
+        Decimal::parse(text).unwrap()
+    }
+
+    #[test]
+    fn point_one_plus_point_two_is_point_three() {
+        let sum = d("0.1").checked_add(d("0.2")).unwrap();
+        assert_eq!(sum, d("0.3"));
+        assert_eq!(sum.to_string(), "0.3");
+        assert_eq!(sum.to_value(), json!(0.3));
+
+        let from_json = Decimal::sum(&[json!(0.1), json!(0.2)]).unwrap();
+        assert_eq!(from_json.to_value(), json!(0.3));
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(d("-12.50").to_string(), "-12.50");
+        assert_eq!(d(".5").to_string(), "0.5");
+        assert_eq!(d("1e-7").to_string(), "0.0000001");
+        assert_eq!(d("2.5E3").to_string(), "2500");
+        assert_eq!(d("-0.05").to_string(), "-0.05");
+        assert!(Decimal::parse("abc").is_none());
+        assert!(Decimal::parse("1.2.3").is_none());
+        assert!(Decimal::parse("").is_none());
+    }
+
+    #[test]
+    fn rounding_is_half_away_from_zero() {
+        assert_eq!(d("2.345").round(2).unwrap().to_string(), "2.35");
+        assert_eq!(d("-2.345").round(2).unwrap().to_string(), "-2.35");
+        assert_eq!(d("2.344").round(2).unwrap().to_string(), "2.34");
+        assert_eq!(d("7").round(2).unwrap().to_string(), "7.00");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(d("1.10").checked_sub(d("0.3")).unwrap(), d("0.8"));
+        assert_eq!(d("19.99").checked_mul(d("3")).unwrap(), d("59.97"));
+        assert_eq!(d("1").checked_div(d("3"), 4).unwrap().to_string(), "0.3333");
+        assert_eq!(d("2").checked_div(d("3"), 2).unwrap().to_string(), "0.67");
+        assert!(d("1").checked_div(d("0"), 2).is_none());
+        assert!(d("0.1") < d("0.2"));
+        assert!(d("-1") < d("0.001"));
+    }
+
+    #[test]
+    fn cents_sum_exactly() {
+        let cents: Vec<Value> = (0..1000).map(|_| json!(0.01)).collect();
+        assert_eq!(Decimal::sum(&cents).unwrap().to_value(), json!(10.0));
+        assert_eq!(
+            Decimal::average(&[json!(0.1), json!(0.2)])
+                .unwrap()
+                .to_value(),
+            json!(0.15)
+        );
+    }
+
+    #[test]
+    fn column_spec() {
+        let spec = DecimalSpec::from_col_type("NUMERIC(10,2)").unwrap();
+        assert_eq!(spec.precision, Some(10));
+        assert_eq!(spec.scale, Some(2));
+        assert_eq!(
+            DecimalSpec::from_col_type("decimal(5)").unwrap().scale,
+            Some(0)
+        );
+        assert_eq!(
+            DecimalSpec::from_col_type("NUMERIC").unwrap(),
+            DecimalSpec {
+                precision: None,
+                scale: None
+            }
+        );
+        assert!(DecimalSpec::from_col_type("INT").is_none());
+
+        assert_eq!(spec.coerce(json!("19.999")).unwrap(), json!(20.0));
+        assert_eq!(spec.coerce(json!(0.125)).unwrap(), json!(0.13));
+        assert!(spec.coerce(json!(123456789.0)).is_err());
+        assert!(spec.coerce(json!("12x")).is_err());
+        let wide = DecimalSpec::from_col_type("NUMERIC(30,10)").unwrap();
+        assert_eq!(
+            wide.coerce(json!("12345678.1234567891")).unwrap(),
+            json!("12345678.1234567891")
+        );
+        assert_eq!(
+            Decimal::average(&[json!("12345678.1234567891"), json!("0.0000000001")])
+                .unwrap()
+                .to_value(),
+            json!("6172839.0617283946")
+        );
+    }
+}
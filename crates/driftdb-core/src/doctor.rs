@@ -0,0 +1,694 @@
+//! Consistency checks and repair for a database directory
+//!
+//! [`Engine::diagnose`] checks every table's segments, segment index and
+//! snapshots and the write-ahead logs, reporting each problem with a
+//! suggested fix.
+//! [`Engine::repair`] applies the fixes, but only when every one of them
+//! provably keeps the data recoverable: each table's current state must
+//! still follow from its newest readable snapshot plus an unbroken run of
+//! events after it, and no readable WAL entry may be lost. If any problem
+//! can't be fixed that way, repair changes nothing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::engine::Engine;
+use crate::errors::Result;
+use crate::snapshot::Snapshot;
+use crate::storage::table_storage::VACUUM_STAGING_FILE;
+use crate::storage::TableStorage;
+use crate::wal::{WalEntry, WalManager, WalOperation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// What repair does about a finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Fix {
+    /// Nothing on disk needs to change
+    None,
+    /// Delete the file
+    Remove,
+    /// Cut the file off at this byte offset
+    Truncate(u64),
+    /// Rebuild the table's segment index from the segments
+    RebuildSegmentIndex,
+    /// A fix is needed but can't be shown to keep the data recoverable
+    Unproven(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Table the problem belongs to; `None` for the write-ahead logs
+    pub table: Option<String>,
+    pub path: PathBuf,
+    pub problem: String,
+    pub suggestion: String,
+    pub fix: Fix,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let subject = self.table.as_deref().unwrap_or("wal");
+        write!(
+            f,
+            "[{}] {}: {}: {}",
+            severity,
+            subject,
+            self.path.display(),
+            self.problem
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub tables_checked: usize,
+    pub findings: Vec<Finding>,
+    /// Fixes [`Engine::repair`] applied, one line each
+    pub repaired: Vec<String>,
+    /// Why [`Engine::repair`] refused to change anything
+    pub refused: Option<String>,
+}
+
+impl DoctorReport {
+    pub fn is_healthy(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Whether every finding's fix is proven safe
+    pub fn can_repair(&self) -> bool {
+        !self
+            .findings
+            .iter()
+            .any(|finding| matches!(finding.fix, Fix::Unproven(_)))
+    }
+
+    /// The report as text, one finding per line with its suggestion
+    /// indented beneath
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("Checked {} tables", self.tables_checked)];
+        if self.is_healthy() {
+            lines.push("No problems found".to_string());
+        }
+        for finding in &self.findings {
+            lines.push(finding.to_string());
+            let safety = match &finding.fix {
+                Fix::None => "no repair needed".to_string(),
+                Fix::Remove | Fix::Truncate(_) | Fix::RebuildSegmentIndex => {
+                    "safe to repair".to_string()
+                }
+                Fix::Unproven(reason) => format!("not safe to repair: {}", reason),
+            };
+            lines.push(format!("    {} ({})", finding.suggestion, safety));
+        }
+        for repaired in &self.repaired {
+            lines.push(format!("Repaired: {}", repaired));
+        }
+        if let Some(reason) = &self.refused {
+            lines.push(format!("Repair refused: {}", reason));
+        }
+        lines
+    }
+}
+
+impl Engine {
+    /// Check segments, snapshots and write-ahead logs without changing
+    /// anything
+    pub fn diagnose(&self) -> Result<DoctorReport> {
+        let mut report = DoctorReport::default();
+        let mut tables: Vec<_> = self.tables.iter().collect();
+        tables.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, storage) in tables {
+            report.findings.extend(check_table(name, storage)?);
+            report.tables_checked += 1;
+        }
+        for wal_path in [
+            self.base_path().join("wal.log"),
+            self.base_path().join("wal").join("wal.log"),
+        ] {
+            report.findings.extend(check_wal(&wal_path, |table| {
+                self.tables.contains_key(table)
+            })?);
+        }
+        Ok(report)
+    }
+
+    /// Diagnose, then apply every fix, or none of them if any can't be
+    /// proven to keep the data recoverable
+    pub fn repair(&self) -> Result<DoctorReport> {
+        self.ensure_writable("REPAIR")?;
+        let mut report = self.diagnose()?;
+        let unproven: Vec<String> = report
+            .findings
+            .iter()
+            .filter_map(|finding| match &finding.fix {
+                Fix::Unproven(reason) => Some(format!("{}: {}", finding.path.display(), reason)),
+                _ => None,
+            })
+            .collect();
+        if !unproven.is_empty() {
+            report.refused = Some(unproven.join("; "));
+            return Ok(report);
+        }
+
+        for finding in &report.findings {
+            match finding.fix {
+                Fix::Remove => {
+                    fs::remove_file(&finding.path)?;
+                    report
+                        .repaired
+                        .push(format!("removed {}", finding.path.display()));
+                }
+                Fix::Truncate(at) => {
+                    OpenOptions::new()
+                        .write(true)
+                        .open(&finding.path)?
+                        .set_len(at)?;
+                    report.repaired.push(format!(
+                        "truncated {} at byte {}",
+                        finding.path.display(),
+                        at
+                    ));
+                }
+                // Rebuilt in the loop below
+                Fix::None | Fix::Unproven(_) | Fix::RebuildSegmentIndex => {}
+            }
+        }
+        // After the fixes above, so the index describes the segments as
+        // they are left
+        let mut reindexed = BTreeSet::new();
+        for finding in &report.findings {
+            if finding.fix != Fix::RebuildSegmentIndex {
+                continue;
+            }
+            let Some(table) = &finding.table else {
+                continue;
+            };
+            if !reindexed.insert(table.clone()) {
+                continue;
+            }
+            if let Some(storage) = self.tables.get(table) {
+                storage.rebuild_segment_index()?;
+                report
+                    .repaired
+                    .push(format!("rebuilt the segment index of {}", table));
+            }
+        }
+        for line in &report.repaired {
+            info!("Doctor {}", line);
+        }
+        Ok(report)
+    }
+}
+
+/// Which events and snapshots a table could rebuild its state from
+struct Chain {
+    last_sequence: u64,
+    compacted_through: u64,
+    /// Sequences readable from each segment file
+    events: BTreeMap<PathBuf, BTreeSet<u64>>,
+    /// Readable snapshots at or below `last_sequence`
+    snapshots: BTreeMap<PathBuf, u64>,
+}
+
+impl Chain {
+    /// Newest point the current state can be rebuilt from without events
+    fn base(&self) -> u64 {
+        self.snapshots
+            .values()
+            .copied()
+            .chain([self.compacted_through])
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Runs of sequences after the last compaction found in no segment
+    fn missing(&self) -> Vec<(u64, u64)> {
+        let present: BTreeSet<u64> = self.events.values().flatten().copied().collect();
+        let mut runs: Vec<(u64, u64)> = Vec::new();
+        for sequence in self.compacted_through + 1..=self.last_sequence {
+            if present.contains(&sequence) {
+                continue;
+            }
+            match runs.last_mut() {
+                Some((_, end)) if *end + 1 == sequence => *end = sequence,
+                _ => runs.push((sequence, sequence)),
+            }
+        }
+        runs
+    }
+
+    /// The first run of missing events no snapshot covers
+    fn unrecoverable(&self) -> Option<(u64, u64)> {
+        let base = self.base();
+        self.missing()
+            .into_iter()
+            .find(|(_, end)| *end > base)
+            .map(|(start, end)| (start.max(base + 1), end))
+    }
+
+    /// The chain without `path`'s events or snapshot, or why it wouldn't
+    /// be recoverable any more
+    fn without(&self, path: &Path) -> std::result::Result<Chain, String> {
+        let mut chain = Chain {
+            last_sequence: self.last_sequence,
+            compacted_through: self.compacted_through,
+            events: self.events.clone(),
+            snapshots: self.snapshots.clone(),
+        };
+        chain.events.remove(path);
+        chain.snapshots.remove(path);
+        match chain.unrecoverable() {
+            None => Ok(chain),
+            Some(run) => Err(format!(
+                "without it, events {} exist nowhere else",
+                describe_run(run)
+            )),
+        }
+    }
+}
+
+fn describe_run((start, end): (u64, u64)) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{}-{}", start, end)
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    Ok(paths)
+}
+
+fn check_table(table: &str, storage: &TableStorage) -> Result<Vec<Finding>> {
+    let stats = storage.get_table_stats();
+    let finding = |severity, path: &Path, problem: String, suggestion: String, fix| Finding {
+        severity,
+        table: Some(table.to_string()),
+        path: path.to_path_buf(),
+        problem,
+        suggestion,
+        fix,
+    };
+    let mut chain = Chain {
+        last_sequence: stats.sequence_count,
+        compacted_through: stats.last_compaction_sequence,
+        events: BTreeMap::new(),
+        snapshots: BTreeMap::new(),
+    };
+
+    // Files the table doesn't reference, corrupt frames, and segments
+    // whose recorded sequence bounds don't cover what they hold
+    let recorded = storage.segment_bounds();
+    let mut orphans = Vec::new();
+    let mut corrupt = Vec::new();
+    let mut unbounded = Vec::new();
+    for path in sorted_entries(&storage.path().join("segments"))? {
+        let segment_id = (path.extension().and_then(|e| e.to_str()) == Some("seg"))
+            .then(|| path.file_stem()?.to_str()?.parse::<u64>().ok())
+            .flatten();
+        match segment_id {
+            Some(id) if id <= stats.segment_count => {}
+            Some(_) => orphans.push((
+                path.clone(),
+                "segment past the table's last segment, left by an interrupted rotation",
+            )),
+            None if file_name(&path) == "compacted.seg" => {
+                orphans.push((path.clone(), "output of an interrupted VACUUM"))
+            }
+            None if file_name(&path) == VACUUM_STAGING_FILE => {
+                orphans.push((path.clone(), "output of an interrupted VACUUM FULL"))
+            }
+            None => {
+                orphans.push((path, "not a segment file"));
+                continue;
+            }
+        }
+        let (events, corrupt_at) = storage
+            .segment_at(path.clone())
+            .open_reader()?
+            .read_verified_prefix()?;
+        if let (Some(id), Some(min), Some(max)) = (
+            segment_id.filter(|&id| id <= stats.segment_count),
+            events.iter().map(|event| event.sequence).min(),
+            events.iter().map(|event| event.sequence).max(),
+        ) {
+            let problem = match recorded.get(&id) {
+                None => Some("the segment index has no entry for it".to_string()),
+                Some(bounds) if bounds.min_sequence > min || bounds.max_sequence < max => {
+                    Some(format!(
+                        "the segment index records sequences {}-{}",
+                        bounds.min_sequence, bounds.max_sequence
+                    ))
+                }
+                Some(_) => None,
+            };
+            if let Some(problem) = problem {
+                unbounded.push((path.clone(), format!("{}-{}", min, max), problem));
+            }
+        }
+        let sequences = events
+            .iter()
+            .map(|event| event.sequence)
+            .filter(|&sequence| sequence > 0)
+            .collect();
+        chain.events.insert(path.clone(), sequences);
+        if let Some(at) = corrupt_at {
+            corrupt.push((path, at, events.len()));
+        }
+    }
+
+    // Snapshots that can't be read or point past the last event
+    let mut bad_snapshots = Vec::new();
+    let mut stale_temp = Vec::new();
+    for path in sorted_entries(&storage.path().join("snapshots"))? {
+        if file_name(&path).ends_with(".tmp") {
+            stale_temp.push(path);
+            continue;
+        }
+        let Some(sequence) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        if sequence > chain.last_sequence {
+            bad_snapshots.push((
+                path,
+                format!(
+                    "snapshot at sequence {} but the table's last event is {}",
+                    sequence, chain.last_sequence
+                ),
+            ));
+        } else if let Err(e) = Snapshot::load_from_file(&path) {
+            bad_snapshots.push((path, format!("unreadable snapshot: {}", e)));
+        } else {
+            chain.snapshots.insert(path, sequence);
+        }
+    }
+
+    let mut findings = Vec::new();
+    let base = chain.base();
+    let unrecoverable = chain.unrecoverable();
+    for run in chain.missing() {
+        let (severity, suggestion, fix) = match unrecoverable {
+            Some(lost) if run.1 >= lost.0 => (
+                Severity::Error,
+                "restore the table from a backup".to_string(),
+                Fix::Unproven(format!(
+                    "no segment or snapshot holds events {}",
+                    describe_run(lost)
+                )),
+            ),
+            _ => (
+                Severity::Warning,
+                format!(
+                    "history before sequence {} is incomplete, but the current state is recoverable",
+                    base
+                ),
+                Fix::None,
+            ),
+        };
+        findings.push(finding(
+            severity,
+            &storage.path().join("segments"),
+            format!(
+                "sequence chain has a gap: events {} are missing",
+                describe_run(run)
+            ),
+            suggestion,
+            fix,
+        ));
+    }
+
+    // Corrupt frames hold nothing readable, so truncating them loses
+    // nothing the chain counts on
+    for (path, at, readable) in corrupt {
+        let fix = match unrecoverable {
+            None => Fix::Truncate(at),
+            Some(run) => Fix::Unproven(format!(
+                "events {} may only exist past the damage",
+                describe_run(run)
+            )),
+        };
+        findings.push(finding(
+            Severity::Error,
+            &path,
+            format!(
+                "corrupt frame at byte {} after {} readable events",
+                at, readable
+            ),
+            format!("truncate the segment at byte {}", at),
+            fix,
+        ));
+    }
+
+    // Stale bounds let reads that pick segments by sequence skip events
+    // the segment holds
+    for (path, held, problem) in unbounded {
+        findings.push(finding(
+            Severity::Error,
+            &path,
+            format!(
+                "segment holds sequences {}, but {}; reads can skip events in it",
+                held, problem
+            ),
+            "rebuild the table's segment index".to_string(),
+            Fix::RebuildSegmentIndex,
+        ));
+    }
+
+    // Each removal is checked against the chain left by the ones before it
+    for (path, reason) in orphans {
+        let fix = match chain.without(&path) {
+            Ok(rest) => {
+                chain = rest;
+                Fix::Remove
+            }
+            Err(reason) => Fix::Unproven(reason),
+        };
+        findings.push(finding(
+            Severity::Warning,
+            &path,
+            format!("orphaned file: {}", reason),
+            "delete the file".to_string(),
+            fix,
+        ));
+    }
+    for (path, problem) in bad_snapshots {
+        let fix = match chain.without(&path) {
+            Ok(rest) => {
+                chain = rest;
+                Fix::Remove
+            }
+            Err(reason) => Fix::Unproven(reason),
+        };
+        findings.push(finding(
+            Severity::Error,
+            &path,
+            problem,
+            "delete the snapshot; reads fall back to an older one and the events after it"
+                .to_string(),
+            fix,
+        ));
+    }
+    for path in stale_temp {
+        findings.push(finding(
+            Severity::Warning,
+            &path,
+            "half-written snapshot left by an interrupted checkpoint".to_string(),
+            "delete the file; it is never read".to_string(),
+            Fix::Remove,
+        ));
+    }
+
+    Ok(findings)
+}
+
+/// One line of a write-ahead log
+struct WalLine {
+    offset: u64,
+    entry: Option<WalEntry>,
+}
+
+fn read_wal(path: &Path) -> Result<Vec<WalLine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&buf);
+        if !text.trim().is_empty() {
+            let entry = serde_json::from_str::<WalEntry>(text.trim())
+                .ok()
+                .filter(WalEntry::has_valid_checksum);
+            lines.push(WalLine { offset, entry });
+        }
+        offset += read as u64;
+    }
+    Ok(lines)
+}
+
+fn operation_table(operation: &WalOperation) -> Option<&str> {
+    match operation {
+        WalOperation::Insert { table, .. }
+        | WalOperation::Update { table, .. }
+        | WalOperation::Delete { table, .. }
+        | WalOperation::CreateTable { table, .. }
+        | WalOperation::DropTable { table }
+        | WalOperation::CreateIndex { table, .. }
+        | WalOperation::DropIndex { table, .. } => Some(table),
+        _ => None,
+    }
+}
+
+fn check_wal(path: &Path, table_exists: impl Fn(&str) -> bool) -> Result<Vec<Finding>> {
+    let finding = |severity, path: &Path, problem: String, suggestion: &str, fix| Finding {
+        severity,
+        table: None,
+        path: path.to_path_buf(),
+        problem,
+        suggestion: suggestion.to_string(),
+        fix,
+    };
+    let mut findings = Vec::new();
+    let lines = if path.exists() {
+        read_wal(path)?
+    } else {
+        Vec::new()
+    };
+
+    // A damaged final entry is a torn write; damage with valid entries
+    // after it is not something truncation can fix
+    if let Some(first_bad) = lines.iter().position(|line| line.entry.is_none()) {
+        let offset = lines[first_bad].offset;
+        let valid_after = lines[first_bad..]
+            .iter()
+            .filter(|line| line.entry.is_some())
+            .count();
+        let fix = if valid_after == 0 {
+            Fix::Truncate(offset)
+        } else {
+            Fix::Unproven(format!("{} valid entries follow the damage", valid_after))
+        };
+        findings.push(finding(
+            Severity::Error,
+            path,
+            format!("damaged entry at byte {}", offset),
+            "truncate the log before the damaged entry",
+            fix,
+        ));
+    }
+
+    // Entries for tables that no longer exist, and weren't dropped
+    let mut dropped = BTreeSet::new();
+    let mut unknown: BTreeMap<String, usize> = BTreeMap::new();
+    for entry in lines.iter().rev().filter_map(|line| line.entry.as_ref()) {
+        let Some(table) = operation_table(&entry.operation) else {
+            continue;
+        };
+        if matches!(entry.operation, WalOperation::DropTable { .. }) {
+            dropped.insert(table.to_string());
+        } else if !table_exists(table) && !dropped.contains(table) {
+            *unknown.entry(table.to_string()).or_default() += 1;
+        }
+    }
+    if !unknown.is_empty() {
+        let tables: Vec<String> = unknown
+            .iter()
+            .map(|(table, count)| format!("{} ({} entries)", table, count))
+            .collect();
+        findings.push(finding(
+            Severity::Warning,
+            path,
+            format!("entries for tables that don't exist: {}", tables.join(", ")),
+            "nothing to do; recovery skips them and the next checkpoint drops them",
+            Fix::None,
+        ));
+    }
+
+    // A log moved aside by a checkpoint is redundant once the current log
+    // holds the checkpoint and every entry after it
+    let rotated = WalManager::rotated_path(path);
+    if rotated.exists() {
+        let current: BTreeSet<u64> = lines
+            .iter()
+            .filter_map(|line| line.entry.as_ref())
+            .map(|entry| entry.sequence)
+            .collect();
+        let checkpoint = lines
+            .iter()
+            .filter_map(|line| match line.entry.as_ref()?.operation {
+                WalOperation::Checkpoint { sequence } => Some(sequence),
+                _ => None,
+            })
+            .max();
+        let fix = match checkpoint {
+            None => Fix::Unproven("the current log holds no checkpoint".to_string()),
+            Some(checkpoint) => {
+                let old = read_wal(&rotated)?;
+                let damaged = old.iter().filter(|line| line.entry.is_none()).count();
+                let missing = old
+                    .iter()
+                    .filter_map(|line| line.entry.as_ref())
+                    .filter(|entry| {
+                        entry.sequence > checkpoint && !current.contains(&entry.sequence)
+                    })
+                    .count();
+                if damaged > 0 {
+                    Fix::Unproven(format!("{} of its entries are damaged", damaged))
+                } else if missing > 0 {
+                    Fix::Unproven(format!(
+                        "{} entries after checkpoint {} aren't in the current log",
+                        missing, checkpoint
+                    ))
+                } else {
+                    Fix::Remove
+                }
+            }
+        };
+        findings.push(finding(
+            Severity::Warning,
+            &rotated,
+            "log left behind by a checkpoint".to_string(),
+            "delete the file",
+            fix,
+        ));
+    }
+
+    Ok(findings)
+}
@@ -0,0 +1,322 @@
+//! How hard writes are pushed to disk
+//!
+//! Every write reaches the operating system before the statement returns;
+//! the [`SyncMode`] decides when it is also fsynced, which is what makes
+//! it survive a power loss or OS crash:
+//!
+//! - `full`: the segment and WAL are fsynced on every write, so nothing
+//!   acknowledged is ever lost
+//! - `async`: a background flusher fsyncs every `async_commit_interval`
+//!   (200ms by default), so an OS crash loses at most the writes of the
+//!   last interval
+//! - `fsync_off`: nothing is fsynced until the mode goes back to `full`
+//!   (or [`Durability::sync_pending`] is called); an OS crash can lose
+//!   everything written since, and leave a torn segment tail for
+//!   `driftdb doctor` to repair. For bulk loads and data that can be
+//!   rebuilt.
+//!
+//! A process crash loses nothing in any mode. The engine-wide mode can
+//! be overridden per session with `SET synchronous_commit`.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::{mpsc, Arc, Weak};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::errors::{DriftError, Result};
+
+/// Default time between background fsyncs in `async` mode
+pub const DEFAULT_ASYNC_COMMIT_INTERVAL: Duration = Duration::from_millis(200);
+
+thread_local! {
+    /// The current session's `SET synchronous_commit`, mirrored from
+    /// `SessionContext.synchronous_commit` by sql_bridge
+    static SESSION_MODE: Cell<Option<SyncMode>> = const { Cell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncMode {
+    Full,
+    Async,
+    FsyncOff,
+}
+
+impl SyncMode {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => SyncMode::Async,
+            2 => SyncMode::FsyncOff,
+            _ => SyncMode::Full,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            SyncMode::Full => 0,
+            SyncMode::Async => 1,
+            SyncMode::FsyncOff => 2,
+        }
+    }
+}
+
+impl fmt::Display for SyncMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SyncMode::Full => "full",
+            SyncMode::Async => "async",
+            SyncMode::FsyncOff => "fsync_off",
+        })
+    }
+}
+
+/// Accepts the mode names plus PostgreSQL's `synchronous_commit` values:
+/// `on`, `local`, `remote_write` and `remote_apply` are `full`, and `off`
+/// is `async`
+impl FromStr for SyncMode {
+    type Err = DriftError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().trim_matches('\'').to_lowercase().as_str() {
+            "full" | "on" | "true" | "local" | "remote_write" | "remote_apply" => {
+                Ok(SyncMode::Full)
+            }
+            "async" | "off" | "false" => Ok(SyncMode::Async),
+            "fsync_off" => Ok(SyncMode::FsyncOff),
+            other => Err(DriftError::InvalidQuery(format!(
+                "invalid value for synchronous_commit: \"{}\" (expected full, async or fsync_off)",
+                other
+            ))),
+        }
+    }
+}
+
+/// The session's mode override, if it set one
+pub(crate) fn session_mode() -> Option<SyncMode> {
+    SESSION_MODE.with(|mode| mode.get())
+}
+
+/// Replace the session's mode override, returning the previous one
+pub(crate) fn set_session_mode(mode: Option<SyncMode>) -> Option<SyncMode> {
+    SESSION_MODE.with(|cell| cell.replace(mode))
+}
+
+/// A file written with a deferred fsync
+pub(crate) trait SyncTarget: Send + Sync {
+    fn sync_to_disk(&self) -> Result<()>;
+}
+
+/// Fsync counters since the engine opened
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DurabilityStats {
+    /// Fsyncs skipped at write time and left to a later sync
+    pub deferred_writes: u64,
+    /// Fsyncs of deferred files, by the flusher or [`Durability::sync_pending`]
+    pub deferred_syncs: u64,
+}
+
+/// The engine-wide sync mode and the files waiting for a deferred fsync
+pub struct Durability {
+    mode: AtomicU8,
+    interval_ms: AtomicU64,
+    /// Files written since their last fsync, by address so each is
+    /// synced once however often it was written
+    pending: Mutex<HashMap<usize, Weak<dyn SyncTarget>>>,
+    /// Dropping the sender stops the flusher thread
+    flusher: Mutex<Option<mpsc::Sender<()>>>,
+    deferred_writes: AtomicU64,
+    deferred_syncs: AtomicU64,
+}
+
+impl Durability {
+    pub fn new(mode: SyncMode) -> Arc<Self> {
+        Arc::new(Self {
+            mode: AtomicU8::new(mode.as_u8()),
+            interval_ms: AtomicU64::new(DEFAULT_ASYNC_COMMIT_INTERVAL.as_millis() as u64),
+            pending: Mutex::new(HashMap::new()),
+            flusher: Mutex::new(None),
+            deferred_writes: AtomicU64::new(0),
+            deferred_syncs: AtomicU64::new(0),
+        })
+    }
+
+    /// The engine-wide mode
+    pub fn mode(&self) -> SyncMode {
+        SyncMode::from_u8(self.mode.load(Ordering::Relaxed))
+    }
+
+    /// Change the engine-wide mode. Moving to `full` first fsyncs
+    /// everything written under a weaker mode.
+    pub fn set_mode(&self, mode: SyncMode) -> Result<()> {
+        self.mode.store(mode.as_u8(), Ordering::Relaxed);
+        if mode == SyncMode::Full {
+            self.sync_pending()?;
+        }
+        Ok(())
+    }
+
+    /// The mode writes on this thread use: the session's override, or
+    /// the engine-wide mode
+    pub fn effective_mode(&self) -> SyncMode {
+        session_mode().unwrap_or_else(|| self.mode())
+    }
+
+    pub fn async_commit_interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.load(Ordering::Relaxed))
+    }
+
+    /// Time between background fsyncs in `async` mode, at least 1ms
+    pub fn set_async_commit_interval(&self, interval: Duration) {
+        self.interval_ms
+            .store((interval.as_millis() as u64).max(1), Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> DurabilityStats {
+        DurabilityStats {
+            deferred_writes: self.deferred_writes.load(Ordering::Relaxed),
+            deferred_syncs: self.deferred_syncs.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Note that `target` was written without an fsync. In `async` mode
+    /// the flusher picks it up within one interval.
+    pub(crate) fn defer(self: &Arc<Self>, target: Arc<dyn SyncTarget>, mode: SyncMode) {
+        self.deferred_writes.fetch_add(1, Ordering::Relaxed);
+        let key = Arc::as_ptr(&target) as *const () as usize;
+        self.pending.lock().insert(key, Arc::downgrade(&target));
+        if mode == SyncMode::Async {
+            self.ensure_flusher();
+        }
+    }
+
+    /// Fsync every file written without one, returning how many were
+    /// synced
+    pub fn sync_pending(&self) -> Result<usize> {
+        let pending: Vec<_> = self.pending.lock().drain().map(|(_, t)| t).collect();
+        let mut synced = 0;
+        for target in pending.iter().filter_map(Weak::upgrade) {
+            target.sync_to_disk()?;
+            synced += 1;
+        }
+        self.deferred_syncs
+            .fetch_add(synced as u64, Ordering::Relaxed);
+        Ok(synced)
+    }
+
+    fn ensure_flusher(self: &Arc<Self>) {
+        let mut flusher = self.flusher.lock();
+        if flusher.is_some() {
+            return;
+        }
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let durability = Arc::downgrade(self);
+        let spawned = std::thread::Builder::new()
+            .name("driftdb-flusher".to_string())
+            .spawn(move || {
+                while let Some(interval) = durability.upgrade().map(|d| d.async_commit_interval()) {
+                    if let Err(mpsc::RecvTimeoutError::Disconnected) =
+                        stop_rx.recv_timeout(interval)
+                    {
+                        break;
+                    }
+                    let Some(durability) = durability.upgrade() else {
+                        break;
+                    };
+                    match durability.sync_pending() {
+                        Ok(0) => {}
+                        Ok(synced) => debug!("Flusher synced {} files", synced),
+                        Err(e) => warn!("Background fsync failed: {}", e),
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => *flusher = Some(stop_tx),
+            Err(e) => warn!("Failed to start flusher thread: {}", e),
+        }
+    }
+}
+
+impl Drop for Durability {
+    fn drop(&mut self) {
+        self.flusher.get_mut().take();
+        if let Err(e) = self.sync_pending() {
+            warn!("Final fsync failed: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    impl SyncTarget for Counter {
+        fn sync_to_disk(&self) -> Result<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parses_mode_names_and_postgres_values() {
+        assert_eq!("full".parse::<SyncMode>().unwrap(), SyncMode::Full);
+        assert_eq!("'on'".parse::<SyncMode>().unwrap(), SyncMode::Full);
+        assert_eq!("OFF".parse::<SyncMode>().unwrap(), SyncMode::Async);
+        assert_eq!("fsync_off".parse::<SyncMode>().unwrap(), SyncMode::FsyncOff);
+        assert!("sometimes".parse::<SyncMode>().is_err());
+        assert_eq!(SyncMode::FsyncOff.to_string(), "fsync_off");
+    }
+
+    #[test]
+    fn session_override_wins() {
+        let durability = Durability::new(SyncMode::Full);
+        let prev = set_session_mode(Some(SyncMode::FsyncOff));
+        assert_eq!(durability.effective_mode(), SyncMode::FsyncOff);
+        set_session_mode(prev);
+        assert_eq!(durability.effective_mode(), SyncMode::Full);
+    }
+
+    #[test]
+    fn deferred_targets_sync_once_and_on_return_to_full() {
+        let durability = Durability::new(SyncMode::FsyncOff);
+        let target = Arc::new(Counter::default());
+        for _ in 0..3 {
+            durability.defer(target.clone(), SyncMode::FsyncOff);
+        }
+        assert_eq!(target.0.load(Ordering::Relaxed), 0);
+
+        durability.set_mode(SyncMode::Full).unwrap();
+        assert_eq!(target.0.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            durability.stats(),
+            DurabilityStats {
+                deferred_writes: 3,
+                deferred_syncs: 1
+            }
+        );
+    }
+
+    #[test]
+    fn flusher_syncs_async_writes() {
+        let durability = Durability::new(SyncMode::Async);
+        durability.set_async_commit_interval(Duration::from_millis(5));
+        let target = Arc::new(Counter::default());
+        durability.defer(target.clone(), SyncMode::Async);
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while target.0.load(Ordering::Relaxed) == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(target.0.load(Ordering::Relaxed), 1);
+    }
+}
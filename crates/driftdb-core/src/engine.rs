@@ -1,8 +1,9 @@
-use parking_lot::RwLock;
-use std::collections::{HashMap, HashSet};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 const MAX_TABLES: usize = 1000;
 use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
@@ -14,19 +15,25 @@ use crate::audit::{
 use crate::backup_enhanced::{
     BackupConfig, BackupResult, EnhancedBackupManager, RestoreOptions, RestoreResult,
 };
+use crate::changefeed::Changefeed;
+use crate::compaction_progress::{CompactionPhase, CompactionProgress, CompactionTracker};
+use crate::compaction_scheduler::CompactionStats;
 use crate::consensus::{ConsensusConfig, ConsensusEngine};
 use crate::constraints::ConstraintManager;
 use crate::distributed_coordinator::{ClusterStatus, DistributedCoordinator};
+use crate::durability::{Durability, SyncMode};
 use crate::encryption::{EncryptionConfig, EncryptionService};
+use crate::enums::EnumType;
 use crate::error_recovery::{RecoveryConfig, RecoveryManager, RecoveryResult};
 use crate::errors::{DriftError, Result};
-use crate::events::Event;
+use crate::events::{Event, EventType};
 use crate::fulltext::{SearchConfig, SearchManager, SearchQuery, SearchResults};
 use crate::index::IndexManager;
 use crate::monitoring::{MonitoringConfig, MonitoringSystem, SystemMetrics};
 use crate::mvcc::IsolationLevel as MVCCIsolationLevel;
 use crate::observability::Metrics;
 use crate::optimizer::QueryOptimizer;
+use crate::partitioning::PartitionScheme;
 use crate::procedures::{ProcedureDefinition, ProcedureManager, ProcedureResult};
 use crate::query::{Query, QueryResult};
 use crate::query_cancellation::{
@@ -38,13 +45,24 @@ use crate::replication::{NodeRole, ReplicationConfig, ReplicationCoordinator};
 use crate::schema::{ColumnDef, Schema};
 use crate::security_monitor::{SecurityConfig, SecurityMonitor};
 use crate::sequences::SequenceManager;
+use crate::settings::GlobalSettings;
 use crate::snapshot::SnapshotManager;
+use crate::snapshot_stream::{
+    SnapshotInfo, SnapshotReader, SnapshotRecord, SnapshotWriter, SNAPSHOT_FORMAT_VERSION,
+};
+use crate::spill::SpillManager;
 use crate::stats::{DatabaseStatistics, QueryExecution, StatisticsManager, StatsConfig};
-use crate::storage::{Segment, TableStorage};
+use crate::storage::{
+    ChunkFilterStats, Compression, KeyFilterStats, LocalFileBackend, RetentionPolicy, Segment,
+    StorageBackend, StorageFormat, TableStorage, TierStats, VacuumStats,
+};
 use crate::transaction::{IsolationLevel, TransactionManager};
 use crate::transaction_coordinator::{TransactionCoordinator, TransactionStats};
-use crate::triggers::{TriggerDefinition, TriggerManager};
-use crate::views::{ViewBuilder, ViewDefinition, ViewManager};
+use crate::triggers::{SavedTriggers, TriggerDefinition, TriggerManager};
+use crate::views::{
+    MaterializedViewData, MaterializedViewStatus, RefreshMode, ViewBuilder, ViewDefinition,
+    ViewManager,
+};
 use crate::wal::{WalConfig, WalManager};
 
 /// Table statistics
@@ -54,6 +72,50 @@ pub struct TableStats {
     pub size_bytes: u64,
 }
 
+/// Physical storage figures for one table, from
+/// [`Engine::table_storage_info`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableStorageInfo {
+    pub table: String,
+    pub segment_count: u64,
+    pub snapshot_count: u64,
+    /// Events stored in the table's segments
+    pub event_count: u64,
+    pub live_rows: u64,
+    /// Share of stored events that are superseded versions of a row
+    pub dead_version_ratio: f64,
+    /// Snapshot sequence of the last compaction, 0 if never compacted
+    pub last_compaction_sequence: u64,
+    /// Bytes on disk: segments, snapshots, indexes and metadata
+    pub total_bytes: u64,
+    /// Size of the current rows serialized as JSON
+    pub logical_bytes: u64,
+}
+
+impl TableStorageInfo {
+    /// How many bytes on disk each byte of current data takes; what
+    /// compaction could win back
+    pub fn disk_to_logical_ratio(&self) -> f64 {
+        if self.logical_bytes == 0 {
+            return 0.0;
+        }
+        self.total_bytes as f64 / self.logical_bytes as f64
+    }
+}
+
+/// One index on a table, from [`Engine::list_indexes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    pub name: String,
+    pub column: String,
+    /// `btree`, or `gin` for JSONB containment indexes
+    pub method: &'static str,
+    pub unique: bool,
+    /// The primary key, which rows are stored by rather than a secondary
+    /// index
+    pub primary: bool,
+}
+
 /// What the latest buffered event for a PK is. Used internally for
 /// computing `PkVisibility::*` against a transaction's write set.
 #[derive(Debug, Clone, Copy)]
@@ -87,18 +149,27 @@ pub enum PkVisibility {
 pub struct Engine {
     base_path: PathBuf,
     pub(crate) tables: HashMap<String, Arc<TableStorage>>,
-    indexes: HashMap<String, Arc<RwLock<IndexManager>>>,
-    snapshots: HashMap<String, Arc<SnapshotManager>>,
+    pub(crate) indexes: HashMap<String, Arc<RwLock<IndexManager>>>,
+    pub(crate) snapshots: HashMap<String, Arc<SnapshotManager>>,
     transaction_manager: Arc<RwLock<TransactionManager>>,
-    constraint_manager: Arc<RwLock<ConstraintManager>>,
+    pub(crate) constraint_manager: Arc<RwLock<ConstraintManager>>,
     sequence_manager: Arc<SequenceManager>,
     view_manager: Arc<ViewManager>,
+    /// `CREATE TYPE ... AS ENUM` types by name, persisted to `types.json`
+    enum_types: RwLock<BTreeMap<String, EnumType>>,
+    /// `CREATE SCHEMA` namespaces other than `public`, persisted to
+    /// `schemas.json`
+    schemas: RwLock<BTreeSet<String>>,
     search_manager: Arc<SearchManager>,
     trigger_manager: Arc<TriggerManager>,
     procedure_manager: Arc<ProcedureManager>,
     stats_manager: Arc<RwLock<StatisticsManager>>,
     wal_manager: Arc<WalManager>,
+    durability: Arc<Durability>,
     encryption_service: Option<Arc<EncryptionService>>,
+    /// Where table segments are stored; local files unless the engine was
+    /// opened with another backend
+    storage_backend: Arc<dyn StorageBackend>,
     consensus_engine: Option<Arc<ConsensusEngine>>,
     replication_coordinator: Option<Arc<ReplicationCoordinator>>,
     raft_node: Option<Arc<RaftNode>>,
@@ -109,15 +180,65 @@ pub struct Engine {
     backup_manager: Option<Arc<parking_lot::RwLock<EnhancedBackupManager>>>,
     audit_system: Option<Arc<AuditSystem>>,
     security_monitor: Option<Arc<SecurityMonitor>>,
-    query_performance: Option<Arc<QueryPerformanceOptimizer>>,
+    pub(crate) query_performance: Option<Arc<QueryPerformanceOptimizer>>,
     query_cancellation: Arc<QueryCancellationManager>,
     /// Cost-based planner whose output drives access-method selection in
     /// [`Engine::select`]. Initialized empty; index registrations are
     /// pushed in by table-load/create/index sites below.
     pub(crate) query_optimizer: Arc<QueryOptimizer>,
+    /// Rows written to each table since its last whole-table `ANALYZE`,
+    /// which the auto-analyzer compares against its threshold
+    changes_since_analyze: RwLock<HashMap<String, u64>>,
+    compaction_tracker: Arc<CompactionTracker>,
+    /// `work_mem` and the directory sorts and hash joins spill to when
+    /// their input outgrows it
+    spill: Arc<SpillManager>,
+    /// Runtime parameters set with `SET GLOBAL`
+    settings: GlobalSettings,
+    /// `CREATE CHANGEFEED` feeds by name, persisted to `changefeeds.json`,
+    /// with the threads delivering them
+    pub(crate) changefeeds: Mutex<BTreeMap<String, Changefeed>>,
+    /// `PARTITION BY` tables by name, persisted to `partitions.json`
+    pub(crate) partitions: RwLock<BTreeMap<String, PartitionScheme>>,
+    /// Hot-standby mode: every write fails with [`DriftError::ReadOnly`]
+    /// while reads, including time travel, keep working.
+    read_only: bool,
+}
+
+/// Statement name reported when a read-only engine rejects `event`.
+fn event_operation(event: &Event) -> &'static str {
+    match event.event_type {
+        crate::events::EventType::Insert => "INSERT",
+        crate::events::EventType::Patch => "UPDATE",
+        crate::events::EventType::SoftDelete => "DELETE",
+    }
 }
 
 impl Engine {
+    /// Switch read-only mode on or off at runtime, e.g. when a replica is
+    /// promoted.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only {
+            self.pause_changefeeds();
+        } else {
+            self.resume_changefeeds();
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Fail with [`DriftError::ReadOnly`] if the engine is read-only.
+    /// `operation` names the rejected statement in the error.
+    pub fn ensure_writable(&self, operation: &str) -> Result<()> {
+        if self.read_only {
+            return Err(DriftError::ReadOnly(operation.to_string()));
+        }
+        Ok(())
+    }
+
     /// Get the base path of the database
     pub fn base_path(&self) -> &Path {
         &self.base_path
@@ -129,7 +250,45 @@ impl Engine {
         &self.query_optimizer
     }
 
+    /// Memory limit and temporary directory for sorts and hash joins
+    pub fn spill_manager(&self) -> &SpillManager {
+        &self.spill
+    }
+
+    /// When writes are fsynced; see [`crate::durability`]
+    pub fn durability(&self) -> &Durability {
+        &self.durability
+    }
+
+    /// Runtime parameters every session sees unless it sets its own; see
+    /// [`crate::settings`]
+    pub fn settings(&self) -> &GlobalSettings {
+        &self.settings
+    }
+
+    /// Version of the catalog: table schemas, indexes and planner
+    /// statistics. It moves on every DDL statement and ANALYZE, and plans
+    /// cached at an older version are re-planned, so a new index is used
+    /// by the very next query.
+    pub fn catalog_version(&self) -> u64 {
+        self.query_optimizer.catalog_version()
+    }
+
+    /// Record a change to the catalog, invalidating cached plans
+    fn catalog_changed(&self) {
+        self.query_optimizer.invalidate_plans();
+    }
+
     pub fn open<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+        Self::open_with_backend(base_path, LocalFileBackend::shared())
+    }
+
+    /// [`Engine::open`] for a database whose segments are kept in
+    /// `storage_backend`
+    pub fn open_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        storage_backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
         let base_path = base_path.as_ref().to_path_buf();
 
         if !base_path.exists() {
@@ -139,10 +298,11 @@ impl Engine {
             )));
         }
 
-        let wal_manager = Arc::new(WalManager::new(
-            base_path.clone().join("wal.log"),
-            WalConfig::default(),
-        )?);
+        let durability = Durability::new(SyncMode::Full);
+        let wal_manager = Arc::new(
+            WalManager::new(base_path.clone().join("wal.log"), WalConfig::default())?
+                .with_durability(durability.clone()),
+        );
 
         let metrics = Arc::new(Metrics::new());
         let monitoring = Arc::new(MonitoringSystem::new(metrics, MonitoringConfig::default()));
@@ -163,18 +323,23 @@ impl Engine {
             tables: HashMap::new(),
             indexes: HashMap::new(),
             snapshots: HashMap::new(),
-            transaction_manager: Arc::new(RwLock::new(TransactionManager::new_with_path(
+            transaction_manager: Arc::new(RwLock::new(TransactionManager::new_with_durability(
                 &base_path,
+                durability.clone(),
             )?)),
             constraint_manager: Arc::new(RwLock::new(ConstraintManager::new())),
             view_manager: Arc::new(ViewManager::new()),
+            enum_types: RwLock::new(BTreeMap::new()),
+            schemas: RwLock::new(BTreeSet::new()),
             search_manager: Arc::new(SearchManager::new()),
             trigger_manager: Arc::new(TriggerManager::new()),
             procedure_manager: Arc::new(ProcedureManager::new()),
             stats_manager: Arc::new(RwLock::new(StatisticsManager::new(StatsConfig::default()))),
             sequence_manager: Arc::new(SequenceManager::new()),
             wal_manager: wal_manager.clone(),
+            durability,
             encryption_service: None,
+            storage_backend,
             consensus_engine: None,
             replication_coordinator: None,
             raft_node: None,
@@ -188,6 +353,13 @@ impl Engine {
             query_performance: None,
             query_cancellation,
             query_optimizer: Arc::new(QueryOptimizer::new()),
+            changes_since_analyze: RwLock::new(HashMap::new()),
+            compaction_tracker: Arc::new(CompactionTracker::new()),
+            spill: Arc::new(SpillManager::new(base_path.join("tmp"))),
+            settings: GlobalSettings::default(),
+            changefeeds: Mutex::new(BTreeMap::new()),
+            partitions: RwLock::new(BTreeMap::new()),
+            read_only: false,
         };
 
         let tables_dir = base_path.join("tables");
@@ -206,6 +378,18 @@ impl Engine {
         if views_file.exists() {
             engine.load_views()?;
         }
+        engine.load_enum_types()?;
+        engine.load_triggers()?;
+        engine.load_schemas()?;
+        engine.load_role_settings()?;
+        engine.load_changefeeds()?;
+        engine.load_partitions()?;
+
+        match engine.spill.remove_stale() {
+            Ok(0) => {}
+            Ok(n) => info!("Removed {} spill directories left by an earlier run", n),
+            Err(e) => warn!("Failed to remove stale spill files: {}", e),
+        }
 
         // Note: Recovery is disabled in sync open - use open_async for recovery
         info!("Engine opened successfully (recovery disabled in sync mode)");
@@ -213,6 +397,14 @@ impl Engine {
         Ok(engine)
     }
 
+    /// Open an existing database as a read-only standby. Equivalent to
+    /// [`Engine::open`] followed by `set_read_only(true)`.
+    pub fn open_read_only<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+        let mut engine = Self::open(base_path)?;
+        engine.set_read_only(true);
+        Ok(engine)
+    }
+
     /// Open database with full async recovery support
     pub async fn open_async<P: AsRef<Path>>(base_path: P) -> Result<Self> {
         let engine = Self::open(base_path)?;
@@ -232,12 +424,24 @@ impl Engine {
     }
 
     pub fn init<P: AsRef<Path>>(base_path: P) -> Result<Self> {
+        Self::init_with_backend(base_path, LocalFileBackend::shared())
+    }
+
+    /// [`Engine::init`] with table segments kept in `storage_backend`,
+    /// e.g. a [`MemoryBackend`](crate::storage::MemoryBackend) in tests
+    pub fn init_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        storage_backend: Arc<dyn StorageBackend>,
+    ) -> Result<Self> {
         let base_path = base_path.as_ref().to_path_buf();
         fs::create_dir_all(&base_path)?;
         fs::create_dir_all(base_path.join("tables"))?;
 
         let wal_path = base_path.join("wal.log");
-        let wal_manager = Arc::new(WalManager::new(wal_path, WalConfig::default())?);
+        let durability = Durability::new(SyncMode::Full);
+        let wal_manager = Arc::new(
+            WalManager::new(wal_path, WalConfig::default())?.with_durability(durability.clone()),
+        );
 
         let metrics = Arc::new(Metrics::new());
         let monitoring = Arc::new(MonitoringSystem::new(metrics, MonitoringConfig::default()));
@@ -258,18 +462,23 @@ impl Engine {
             tables: HashMap::new(),
             indexes: HashMap::new(),
             snapshots: HashMap::new(),
-            transaction_manager: Arc::new(RwLock::new(TransactionManager::new_with_path(
+            transaction_manager: Arc::new(RwLock::new(TransactionManager::new_with_durability(
                 &base_path,
+                durability.clone(),
             )?)),
             constraint_manager: Arc::new(RwLock::new(ConstraintManager::new())),
             sequence_manager: Arc::new(SequenceManager::new()),
             view_manager: Arc::new(ViewManager::new()),
+            enum_types: RwLock::new(BTreeMap::new()),
+            schemas: RwLock::new(BTreeSet::new()),
             search_manager: Arc::new(SearchManager::new()),
             trigger_manager: Arc::new(TriggerManager::new()),
             procedure_manager: Arc::new(ProcedureManager::new()),
             stats_manager: Arc::new(RwLock::new(StatisticsManager::new(Default::default()))),
             wal_manager: wal_manager.clone(),
+            durability,
             encryption_service: None,
+            storage_backend,
             consensus_engine: None,
             replication_coordinator: None,
             raft_node: None,
@@ -283,21 +492,49 @@ impl Engine {
             query_performance: None,
             query_cancellation,
             query_optimizer: Arc::new(QueryOptimizer::new()),
+            changes_since_analyze: RwLock::new(HashMap::new()),
+            compaction_tracker: Arc::new(CompactionTracker::new()),
+            spill: Arc::new(SpillManager::new(base_path.join("tmp"))),
+            settings: GlobalSettings::default(),
+            changefeeds: Mutex::new(BTreeMap::new()),
+            partitions: RwLock::new(BTreeMap::new()),
+            read_only: false,
         })
     }
 
     fn load_table(&mut self, table_name: &str) -> Result<()> {
-        let storage = Arc::new(TableStorage::open(
-            &self.base_path,
-            table_name,
-            self.encryption_service.clone(),
-        )?);
+        let storage = Arc::new(
+            TableStorage::open_with_backend(
+                &self.base_path,
+                table_name,
+                self.encryption_service.clone(),
+                self.storage_backend.clone(),
+            )?
+            .with_durability(self.durability.clone()),
+        );
+
+        let discarded_load = storage.discard_incomplete_bulk_load()?;
+        if discarded_load {
+            warn!(
+                "Discarded unfinished bulk load into '{}'; the table is empty",
+                table_name
+            );
+        }
 
         let mut index_mgr = IndexManager::new(storage.path());
         index_mgr.load_indexes(&storage.schema().indexed_columns())?;
+        if discarded_load {
+            let active = index_mgr.indexed_column_names();
+            index_mgr.rebuild_from_state(&HashMap::new(), &active)?;
+        }
 
         let snapshot_mgr = SnapshotManager::new(storage.path());
 
+        let foreign_keys = storage.schema().foreign_keys.clone();
+        if !foreign_keys.is_empty() {
+            crate::fk::register(table_name, foreign_keys);
+        }
+
         self.tables.insert(table_name.to_string(), storage.clone());
         self.indexes
             .insert(table_name.to_string(), Arc::new(RwLock::new(index_mgr)));
@@ -305,6 +542,9 @@ impl Engine {
             .insert(table_name.to_string(), Arc::new(snapshot_mgr));
 
         self.register_indexes_with_optimizer(table_name);
+        if !storage.schema().storage.is_row() {
+            self.query_optimizer.set_columnar(table_name, true);
+        }
         Ok(())
     }
 
@@ -541,6 +781,7 @@ impl Engine {
         primary_key: &str,
         indexed_columns: Vec<String>,
     ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
         if self.tables.contains_key(name) {
             return Err(DriftError::Other(format!(
                 "Table '{}' already exists",
@@ -573,11 +814,15 @@ impl Engine {
         let schema = Schema::new(name.to_string(), primary_key.to_string(), columns);
         schema.validate()?;
 
-        let storage = Arc::new(TableStorage::create(
-            &self.base_path,
-            schema.clone(),
-            self.encryption_service.clone(),
-        )?);
+        let storage = Arc::new(
+            TableStorage::create_with_backend(
+                &self.base_path,
+                schema.clone(),
+                self.encryption_service.clone(),
+                self.storage_backend.clone(),
+            )?
+            .with_durability(self.durability.clone()),
+        );
 
         let mut index_mgr = IndexManager::new(storage.path());
         index_mgr.load_indexes(&schema.indexed_columns())?;
@@ -591,6 +836,7 @@ impl Engine {
             .insert(name.to_string(), Arc::new(snapshot_mgr));
 
         self.register_indexes_with_optimizer(name);
+        self.catalog_changed();
         Ok(())
     }
 
@@ -600,6 +846,7 @@ impl Engine {
         primary_key: &str,
         columns: Vec<ColumnDef>,
     ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
         if self.tables.contains_key(name) {
             return Err(DriftError::Other(format!(
                 "Table '{}' already exists",
@@ -616,11 +863,15 @@ impl Engine {
         let schema = Schema::new(name.to_string(), primary_key.to_string(), columns);
         schema.validate()?;
 
-        let storage = Arc::new(TableStorage::create(
-            &self.base_path,
-            schema.clone(),
-            self.encryption_service.clone(),
-        )?);
+        let storage = Arc::new(
+            TableStorage::create_with_backend(
+                &self.base_path,
+                schema.clone(),
+                self.encryption_service.clone(),
+                self.storage_backend.clone(),
+            )?
+            .with_durability(self.durability.clone()),
+        );
 
         let mut index_mgr = IndexManager::new(storage.path());
         index_mgr.load_indexes(&schema.indexed_columns())?;
@@ -634,27 +885,44 @@ impl Engine {
             .insert(name.to_string(), Arc::new(snapshot_mgr));
 
         self.register_indexes_with_optimizer(name);
+        self.catalog_changed();
         Ok(())
     }
 
     /// Drop a table and all its associated data
     pub fn drop_table(&mut self, name: &str) -> Result<()> {
+        self.ensure_writable("DROP TABLE")?;
         // Check if table exists
         if !self.tables.contains_key(name) {
             return Err(DriftError::TableNotFound(name.to_string()));
         }
 
+        // A partitioned table takes its partitions with it
+        for partition in self.forget_partitioning(name)? {
+            if self.tables.contains_key(&partition) {
+                self.drop_table(&partition)?;
+            }
+        }
+
         // Remove from all internal structures
-        self.tables.remove(name);
+        let storage = self.tables.remove(name);
         self.indexes.remove(name);
         self.snapshots.remove(name);
 
-        // Delete physical files
-        let table_path = self.base_path.join(name);
-        if table_path.exists() {
-            std::fs::remove_dir_all(&table_path)?;
+        // Delete segments through the backend, which finds them on
+        // whichever tier they are, then the table's other files
+        if let Some(storage) = storage {
+            let table_path = storage.path().to_path_buf();
+            drop(storage);
+            for segment in self.storage_backend.list(&table_path.join("segments"))? {
+                self.storage_backend.delete(&segment)?;
+            }
+            if table_path.exists() {
+                std::fs::remove_dir_all(&table_path)?;
+            }
         }
 
+        self.query_optimizer.forget_table(name);
         Ok(())
     }
 
@@ -665,6 +933,7 @@ impl Engine {
         column_name: &str,
         _index_name: Option<&str>,
     ) -> Result<()> {
+        self.ensure_writable("CREATE INDEX")?;
         // Check if table exists
         let storage = self
             .tables
@@ -711,7 +980,57 @@ impl Engine {
         Ok(())
     }
 
+    /// Create a GIN index on a JSONB column, used to narrow `@>` queries
+    pub fn create_gin_index(&mut self, table_name: &str, column_name: &str) -> Result<()> {
+        self.ensure_writable("CREATE INDEX")?;
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?
+            .clone();
+
+        if let Some(column) = storage
+            .schema()
+            .columns
+            .iter()
+            .find(|c| c.name == column_name)
+        {
+            if !crate::jsonb::is_json_type(&column.col_type) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "data type {} has no default operator class for access method \"gin\"",
+                    column.col_type
+                )));
+            }
+        }
+
+        let index_mgr = self
+            .indexes
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+        let state = storage.reconstruct_state_at(None)?;
+        index_mgr
+            .write()
+            .build_gin_index_from_data(column_name, &state)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Primary keys of rows that may contain `query` in `column`, from the
+    /// column's GIN index. `None` when the index can't narrow the search.
+    pub fn lookup_by_gin_index(
+        &self,
+        table_name: &str,
+        column: &str,
+        query: &serde_json::Value,
+    ) -> Option<HashSet<String>> {
+        self.indexes
+            .get(table_name)?
+            .read()
+            .gin_candidates(column, query)
+    }
+
     pub fn apply_event(&mut self, event: Event) -> Result<u64> {
+        self.ensure_writable(event_operation(&event))?;
         let storage = self
             .tables
             .get(&event.table_name)
@@ -719,6 +1038,11 @@ impl Engine {
             .clone();
 
         let sequence = storage.append_event(event.clone())?;
+        self.record_changes(&event.table_name, 1);
+        if event.event_type != EventType::SoftDelete {
+            self.query_optimizer
+                .widen_column_ranges(&event.table_name, &event.payload);
+        }
 
         if let Some(index_mgr) = self.indexes.get(&event.table_name) {
             let mut index_mgr = index_mgr.write();
@@ -736,7 +1060,42 @@ impl Engine {
         Ok(sequence)
     }
 
+    /// Append `events`, all for `table_name`, with one storage write and
+    /// one index update, returning the last sequence written
+    fn apply_events(&mut self, table_name: &str, mut events: Vec<Event>) -> Result<u64> {
+        if let Some(event) = events.first() {
+            self.ensure_writable(event_operation(event))?;
+        }
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?
+            .clone();
+
+        let sequence = storage.append_events(&mut events)?;
+        self.record_changes(table_name, events.len() as u64);
+        for event in events
+            .iter()
+            .filter(|e| e.event_type != EventType::SoftDelete)
+        {
+            self.query_optimizer
+                .widen_column_ranges(table_name, &event.payload);
+        }
+
+        if let Some(index_mgr) = self.indexes.get(table_name) {
+            let mut index_mgr = index_mgr.write();
+            let active: HashSet<String> = index_mgr.indexed_column_names();
+            for event in &events {
+                index_mgr.update_indexes(event, &active)?;
+            }
+            index_mgr.save_all()?;
+        }
+
+        Ok(sequence)
+    }
+
     pub fn create_snapshot(&self, table_name: &str) -> Result<()> {
+        self.ensure_writable("CHECKPOINT")?;
         let storage = self
             .tables
             .get(table_name)
@@ -756,11 +1115,60 @@ impl Engine {
     }
 
     pub fn compact_table(&self, table_name: &str) -> Result<()> {
+        self.compact_table_with_progress(table_name, |_| {})
+    }
+
+    /// [`Self::compact_table`], calling `on_progress` as it moves through
+    /// the phases. Progress also goes to [`Self::compaction_tracker`].
+    pub fn compact_table_with_progress(
+        &self,
+        table_name: &str,
+        mut on_progress: impl FnMut(&CompactionProgress),
+    ) -> Result<()> {
+        self.ensure_writable("VACUUM")?;
+        if !self.tables.contains_key(table_name) {
+            return Err(DriftError::TableNotFound(table_name.to_string()));
+        }
+        let mut progress = CompactionProgress::new(table_name);
+        let result = self.compact_table_phases(table_name, &mut progress, &mut on_progress);
+        progress.finish(result.as_ref().err().map(|e| e.to_string()));
+        self.compaction_tracker.update(&progress);
+        on_progress(&progress);
+        result
+    }
+
+    fn compact_table_phases(
+        &self,
+        table_name: &str,
+        progress: &mut CompactionProgress,
+        on_progress: &mut impl FnMut(&CompactionProgress),
+    ) -> Result<()> {
+        let mut report = |progress: &CompactionProgress| {
+            self.compaction_tracker.update(progress);
+            on_progress(progress);
+        };
+
         let storage = self
             .tables
             .get(table_name)
             .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
 
+        // Folding up to the latest snapshot would lose history a retention
+        // policy keeps, so such a table folds only what the policy lets go
+        let retention = storage.schema().retention;
+        if !retention.is_unbounded() {
+            progress.enter(CompactionPhase::Swapping, 1);
+            report(progress);
+            if let Some(stats) = storage.prune_history()? {
+                info!(
+                    "VACUUM {}: pruned history, reclaimed {} bytes",
+                    table_name,
+                    stats.bytes_reclaimed()
+                );
+            }
+            return Ok(());
+        }
+
         let snapshot_mgr = self
             .snapshots
             .get(table_name)
@@ -782,10 +1190,23 @@ impl Engine {
 
         let segments_dir = storage.path().join("segments");
         let compacted_path = segments_dir.join("compacted.seg");
-        let compacted_segment = Segment::new(compacted_path, 0);
+        let backend = storage.backend();
+        // Compacted history is written with the table's current codec
+        let compacted_segment = Segment::new(compacted_path, 0)
+            .with_backend(backend.clone())
+            .with_compression(storage.compression());
         let mut writer = compacted_segment.create()?;
 
+        progress.enter(
+            CompactionPhase::WritingSnapshot,
+            latest_snapshot.state.len() as u64,
+        );
+        report(progress);
         for (pk, row_str) in latest_snapshot.state {
+            progress.phase_done += 1;
+            if progress.phase_done.is_multiple_of(1024) {
+                report(progress);
+            }
             // Parse the JSON string back to Value
             let row: serde_json::Value = match serde_json::from_str(&row_str) {
                 Ok(val) => val,
@@ -804,22 +1225,17 @@ impl Engine {
 
         writer.sync()?;
 
-        let mut segment_files: Vec<_> = fs::read_dir(&segments_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s == "seg" && !entry.path().to_string_lossy().contains("compacted"))
-                    .unwrap_or(false)
-            })
+        let segment_files: Vec<_> = storage
+            .segment_files()?
+            .into_iter()
+            .filter(|path| !path.to_string_lossy().contains("compacted"))
             .collect();
 
-        segment_files.sort_by_key(|entry| entry.path());
-
-        for entry in segment_files {
-            let segment = Segment::new(entry.path(), 0);
+        progress.enter(CompactionPhase::ReadingSegments, segment_files.len() as u64);
+        report(progress);
+        let mut folded = Vec::new();
+        for path in segment_files {
+            let segment = Segment::new(path.clone(), 0).with_backend(backend.clone());
             let mut reader = segment.open_reader()?;
             let events = reader.read_all_events()?;
 
@@ -832,57 +1248,129 @@ impl Engine {
             }
 
             if !has_post_snapshot_events {
-                fs::remove_file(entry.path())?;
+                folded.push(path);
             }
+            progress.phase_done += 1;
+            report(progress);
         }
 
         writer.sync()?;
 
+        progress.enter(CompactionPhase::Cleaning, folded.len() as u64);
+        report(progress);
+        for path in folded {
+            backend.delete(&path)?;
+            progress.phase_done += 1;
+            report(progress);
+        }
+
+        progress.enter(CompactionPhase::Swapping, 1);
+        report(progress);
         let final_path = segments_dir.join("00000001.seg");
-        fs::rename(segments_dir.join("compacted.seg"), final_path)?;
+        backend.rename(&segments_dir.join("compacted.seg"), &final_path)?;
+        storage.reopen_after_compaction(latest_snapshot_seq)?;
 
         Ok(())
     }
 
-    pub fn doctor(&self) -> Result<Vec<String>> {
-        let mut report = Vec::new();
-
-        for (table_name, storage) in &self.tables {
-            report.push(format!("Checking table: {}", table_name));
-
-            let segments_dir = storage.path().join("segments");
-            let mut segment_files: Vec<_> = fs::read_dir(&segments_dir)?
-                .filter_map(|entry| entry.ok())
-                .filter(|entry| {
-                    entry
-                        .path()
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .map(|s| s == "seg")
-                        .unwrap_or(false)
-                })
-                .collect();
-
-            segment_files.sort_by_key(|entry| entry.path());
-
-            for entry in segment_files {
-                let segment = Segment::new(entry.path(), 0);
-                let mut reader = segment.open_reader()?;
-
-                if let Some(corrupt_pos) = reader.verify_and_find_corruption()? {
-                    report.push(format!(
-                        "  Found corruption in {} at position {}, truncating...",
-                        entry.path().display(),
-                        corrupt_pos
-                    ));
-                    segment.truncate_at(corrupt_pos)?;
-                } else {
-                    report.push(format!("  Segment {} is healthy", entry.path().display()));
-                }
-            }
+    /// `VACUUM FULL`: rewrite a table's segments as one segment of its
+    /// current rows, deleting its old segments and snapshots, so disk use
+    /// drops to what the live rows take. Time travel to before it is lost.
+    pub fn vacuum_full(&self, table_name: &str) -> Result<VacuumStats> {
+        self.ensure_writable("VACUUM")?;
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+        let stats = storage.vacuum_full()?;
+        info!(
+            "VACUUM FULL {}: {} rows, reclaimed {} bytes",
+            table_name,
+            stats.rows,
+            stats.bytes_reclaimed()
+        );
+        Ok(stats)
+    }
+
+    /// The backend table segments are stored in
+    pub fn storage_backend(&self) -> Arc<dyn StorageBackend> {
+        self.storage_backend.clone()
+    }
+
+    /// Move every table's cold segments to the storage backend's cold
+    /// tier, returning how many moved. A backend without tiers moves none.
+    pub fn relocate_cold_segments(&self) -> Result<usize> {
+        let mut moved = 0;
+        for storage in self.tables.values() {
+            moved += self
+                .storage_backend
+                .relocate_cold(&storage.path().join("segments"))?;
         }
+        Ok(moved)
+    }
+
+    /// Move all of a table's segments to the storage backend's cold tier
+    /// now, whatever their age, returning how many moved. The active
+    /// segment is closed first, so later writes start a new hot one.
+    pub fn archive_table(&self, name: &str) -> Result<usize> {
+        let storage = self
+            .tables
+            .get(name)
+            .ok_or_else(|| DriftError::TableNotFound(name.to_string()))?;
+        if self.storage_backend.tier_stats().is_none() {
+            return Err(DriftError::InvalidQuery(
+                "the storage backend has no cold tier to archive to".to_string(),
+            ));
+        }
+        storage.seal_active_segment()?;
+        self.storage_backend
+            .archive(&storage.path().join("segments"))
+    }
+
+    /// Hot reads, cache hits and cold reads of a tiered storage backend
+    pub fn storage_tier_stats(&self) -> Option<TierStats> {
+        self.storage_backend.tier_stats()
+    }
+
+    /// Progress of running and recently finished compactions. Shared, so
+    /// it can be read while a compaction holds the engine's write lock.
+    pub fn compaction_tracker(&self) -> Arc<CompactionTracker> {
+        self.compaction_tracker.clone()
+    }
+
+    /// Figures the compaction scheduler decides on. Counting stored
+    /// versions reads the whole table, so it only happens when
+    /// `count_versions` is set.
+    pub fn compaction_stats(
+        &self,
+        table_name: &str,
+        count_versions: bool,
+    ) -> Result<CompactionStats> {
+        let (storage, snapshot_mgr) = self.compaction_handles(table_name)?;
+        crate::compaction_scheduler::table_compaction_stats(&storage, &snapshot_mgr, count_versions)
+    }
+
+    /// A table's storage and snapshots, shared so the compaction scheduler
+    /// can measure and compact the table without holding the engine's lock
+    pub(crate) fn compaction_handles(
+        &self,
+        table_name: &str,
+    ) -> Result<(Arc<TableStorage>, Arc<SnapshotManager>)> {
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+        let snapshot_mgr = self
+            .snapshots
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+        Ok((storage.clone(), snapshot_mgr.clone()))
+    }
 
-        Ok(report)
+    /// [`Engine::diagnose`] as text. Nothing is changed on disk; see
+    /// [`Engine::repair`].
+    pub fn doctor(&self) -> Result<Vec<String>> {
+        Ok(self.diagnose()?.lines())
     }
 
     // Transaction support methods
@@ -896,9 +1384,15 @@ impl Engine {
             txn_mgr.simple_commit(txn_id)?
         };
 
-        // Apply all events from the committed transaction
-        for event in events {
-            self.apply_event(event)?;
+        // Apply the committed events, each run for one table as a batch
+        let mut events = events.into_iter().peekable();
+        while let Some(first) = events.next() {
+            let table_name = first.table_name.clone();
+            let mut run = vec![first];
+            while let Some(event) = events.next_if(|event| event.table_name == table_name) {
+                run.push(event);
+            }
+            self.apply_events(&table_name, run)?;
         }
 
         Ok(())
@@ -909,9 +1403,23 @@ impl Engine {
     }
 
     pub fn apply_event_in_transaction(&self, txn_id: u64, event: Event) -> Result<()> {
+        self.ensure_writable(event_operation(&event))?;
         self.transaction_manager.write().add_write(txn_id, event)
     }
 
+    /// Fail with a conflict when an open transaction has a pending write
+    /// to this row. Auto-commit writes check this before applying, as
+    /// buffered writes do in `apply_event_in_transaction`.
+    pub fn check_write_conflict(
+        &self,
+        table_name: &str,
+        primary_key: &serde_json::Value,
+    ) -> Result<()> {
+        self.transaction_manager
+            .read()
+            .check_write_conflict(None, table_name, primary_key)
+    }
+
     /// SAVEPOINT support — push/release/rollback-to operate on the
     /// transaction's savepoint stack. See `TransactionManager` for
     /// the snapshot-based mechanics.
@@ -973,7 +1481,7 @@ impl Engine {
         let buffer_view = self
             .transaction_manager
             .read()
-            .write_set_event_kind(txn_id, &pk_str)?;
+            .write_set_event_kind(txn_id, table_name, &pk_str)?;
         match buffer_view {
             Some(BufferEventKind::Active) => Ok(PkVisibility::Active),
             Some(BufferEventKind::Deleted) => Ok(PkVisibility::Deleted),
@@ -987,6 +1495,34 @@ impl Engine {
         }
     }
 
+    /// The rows of `table` this transaction has written, by primary key:
+    /// `Some(row)` for a pending insert or update, `None` for a pending
+    /// delete. SQL updates buffer the whole row, so the row is complete.
+    pub fn rows_written_in_transaction(
+        &self,
+        txn_id: u64,
+        table: &str,
+    ) -> Result<Vec<(serde_json::Value, Option<serde_json::Value>)>> {
+        let txn_mgr = self.transaction_manager.read();
+        let active_txns = txn_mgr.active_transactions.read();
+        let txn = active_txns
+            .get(&txn_id)
+            .ok_or_else(|| DriftError::Other(format!("Transaction {} not found", txn_id)))?;
+        let txn_guard = txn.lock();
+        Ok(txn_guard
+            .write_set
+            .values()
+            .filter(|event| event.table_name == table)
+            .map(|event| {
+                let row = match event.event_type {
+                    EventType::SoftDelete => None,
+                    _ => Some(event.payload.clone()),
+                };
+                (event.primary_key.clone(), row)
+            })
+            .collect())
+    }
+
     pub fn read_in_transaction(
         &self,
         txn_id: u64,
@@ -1001,12 +1537,16 @@ impl Engine {
             let txn_guard = txn.lock();
 
             // Check write set first (read-your-writes)
-            // Keys in write_set are stored as JSON-serialized primary keys (e.g., "\"post1\""),
+            // Keys in write_set are (table, JSON-serialized primary key) (e.g., "\"post1\""),
             // so also try the JSON-quoted form if the plain key doesn't match.
-            if let Some(event) = txn_guard.write_set.get(key) {
+            let plain_key = (table.to_string(), key.to_string());
+            if let Some(event) = txn_guard.write_set.get(&plain_key) {
                 return Ok(Some(event.payload.clone()));
             }
-            let json_key = serde_json::Value::String(key.to_string()).to_string();
+            let json_key = (
+                table.to_string(),
+                serde_json::Value::String(key.to_string()).to_string(),
+            );
             if let Some(event) = txn_guard.write_set.get(&json_key) {
                 return Ok(Some(event.payload.clone()));
             }
@@ -1071,16 +1611,7 @@ impl Engine {
                 // at or before the requested instant. Returning `None` here would
                 // silently fall through to "current state" — a wrong-data result
                 // for a temporal database.
-                let target_sequence = match as_of {
-                    Some(crate::query::AsOf::Sequence(seq)) => Some(*seq),
-                    Some(crate::query::AsOf::Timestamp(ts)) => storage
-                        .read_all_events()?
-                        .iter()
-                        .filter(|e| e.timestamp <= *ts)
-                        .map(|e| e.sequence)
-                        .max(),
-                    Some(crate::query::AsOf::Now) | None => None,
-                };
+                let target_sequence = storage.as_of_sequence(as_of.as_ref())?;
 
                 // Check for cancellation before reconstruction
                 if cancellation_token.is_cancelled() {
@@ -1121,6 +1652,57 @@ impl Engine {
         self.tables.keys().cloned().collect()
     }
 
+    /// A copy of a table's current schema
+    pub fn table_schema(&self, table_name: &str) -> Result<Schema> {
+        self.tables
+            .get(table_name)
+            .map(|storage| storage.schema().clone())
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))
+    }
+
+    /// A table's indexes: the primary key first, then secondary indexes by
+    /// column. Index names given to `CREATE INDEX` aren't kept, so
+    /// secondary indexes are reported as `idx_<table>_<column>`.
+    pub fn list_indexes(&self, table_name: &str) -> Result<Vec<IndexInfo>> {
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+        let schema = storage.schema();
+
+        let mut indexes = vec![IndexInfo {
+            name: format!("{}_pkey", table_name),
+            column: schema.primary_key.clone(),
+            method: "btree",
+            unique: true,
+            primary: true,
+        }];
+
+        // Columns declared indexed at CREATE TABLE plus later CREATE INDEX
+        // ones, which only the index manager knows about
+        let mut btree: BTreeSet<String> = schema.indexed_columns().into_iter().collect();
+        let mut gin = BTreeSet::new();
+        if let Some(index_mgr) = self.indexes.get(table_name) {
+            let index_mgr = index_mgr.read();
+            btree.extend(index_mgr.indexed_column_names());
+            gin.extend(index_mgr.gin_column_names());
+        }
+        btree.remove(&schema.primary_key);
+
+        for (columns, method) in [(btree, "btree"), (gin, "gin")] {
+            for column in columns {
+                indexes.push(IndexInfo {
+                    name: format!("idx_{}_{}", table_name, column),
+                    unique: method == "btree" && schema.unique.contains(&column),
+                    column,
+                    method,
+                    primary: false,
+                });
+            }
+        }
+        Ok(indexes)
+    }
+
     /// Get storage size information for a table
     pub fn get_table_size(&self, table_name: &str) -> Result<u64> {
         let storage = self
@@ -1157,6 +1739,41 @@ impl Engine {
         })
     }
 
+    /// Physical storage figures for a table. Reads every stored event, so
+    /// this costs a full scan of the table.
+    pub fn table_storage_info(&self, table_name: &str) -> Result<TableStorageInfo> {
+        let storage = self
+            .tables
+            .get(table_name)
+            .ok_or_else(|| DriftError::TableNotFound(table_name.to_string()))?;
+
+        let stats = storage.get_table_stats();
+        let event_count = storage.read_events_with_limit(Some(usize::MAX))?.len() as u64;
+        let state = storage.reconstruct_state_at(None)?;
+        let live_rows = state.len() as u64;
+        let logical_bytes = state
+            .values()
+            .map(|row| serde_json::to_vec(row).map(|bytes| bytes.len() as u64))
+            .sum::<std::result::Result<u64, _>>()?;
+        let dead_version_ratio = if event_count == 0 {
+            0.0
+        } else {
+            event_count.saturating_sub(live_rows) as f64 / event_count as f64
+        };
+
+        Ok(TableStorageInfo {
+            table: table_name.to_string(),
+            segment_count: stats.segment_count,
+            snapshot_count: stats.snapshot_count,
+            event_count,
+            live_rows,
+            dead_version_ratio,
+            last_compaction_sequence: stats.last_compaction_sequence,
+            total_bytes: storage.calculate_size_bytes()?,
+            logical_bytes,
+        })
+    }
+
     /// Get total database size across all tables
     pub fn get_total_database_size(&self) -> u64 {
         let mut total_size = 0u64;
@@ -1172,26 +1789,32 @@ impl Engine {
 
     /// Create a view
     pub fn create_view(&self, definition: ViewDefinition) -> Result<()> {
+        self.ensure_writable("CREATE VIEW")?;
         self.view_manager.create_view(definition)?;
         // Save views to disk after creating
         self.save_views()?;
+        self.catalog_changed();
         Ok(())
     }
 
     /// Create a view using builder pattern
     pub fn create_view_from_sql(&self, name: &str, sql: &str) -> Result<()> {
+        self.ensure_writable("CREATE VIEW")?;
         let view = ViewBuilder::new(name, sql).build()?;
         self.view_manager.create_view(view)?;
         // Save views to disk after creating
         self.save_views()?;
+        self.catalog_changed();
         Ok(())
     }
 
     /// Drop a view
     pub fn drop_view(&self, view_name: &str, cascade: bool) -> Result<()> {
+        self.ensure_writable("DROP VIEW")?;
         self.view_manager.drop_view(view_name, cascade)?;
         // Save views to disk after dropping
         self.save_views()?;
+        self.catalog_changed();
         Ok(())
     }
 
@@ -1212,30 +1835,271 @@ impl Engine {
         self.view_manager.list_views()
     }
 
-    /// Refresh a materialized view
-    pub fn refresh_materialized_view(&self, view_name: &str) -> Result<()> {
-        self.view_manager.refresh_materialized_view(view_name)
+    /// Refresh a materialized view and return the mode actually applied.
+    ///
+    /// An incremental refresh re-evaluates the view only for source rows
+    /// with events in the log since the last refresh. It falls back to a
+    /// full recompute when the view isn't a plain single-table SELECT (see
+    /// [`MaterializedViewStatus::incremental`]), hasn't been populated yet,
+    /// or the catalog changed since. Either way the new contents are built
+    /// before they replace the old ones.
+    pub fn refresh_materialized_view(
+        &mut self,
+        view_name: &str,
+        mode: RefreshMode,
+    ) -> Result<RefreshMode> {
+        self.ensure_writable("REFRESH MATERIALIZED VIEW")?;
+        self.refresh_view_contents(view_name, mode)
+    }
+
+    /// How far a materialized view lags behind its source tables
+    pub fn materialized_view_status(&self, view_name: &str) -> Result<MaterializedViewStatus> {
+        let view = self.materialized_view(view_name)?;
+        let contents = self.view_manager.materialized_contents(view_name);
+
+        let mut status = MaterializedViewStatus {
+            name: view.name.clone(),
+            populated: contents.is_some(),
+            last_refresh_sequence: 0,
+            current_sequence: 0,
+            pending_events: 0,
+            incremental: crate::sql_bridge::incremental_view_source(self, &view.query).is_some(),
+        };
+        for table in self.view_source_tables(&view) {
+            let storage = &self.tables[&table];
+            let current = storage.last_sequence();
+            let refreshed = contents
+                .as_ref()
+                .and_then(|c| c.source_sequences.get(&table).copied())
+                .unwrap_or(0);
+            // A recreated table starts its sequence over
+            let since = if refreshed <= current { refreshed } else { 0 };
+            status.pending_events += storage.read_events_after_sequence(since)?.len() as u64;
+            status.current_sequence = status.current_sequence.max(current);
+            status.last_refresh_sequence = status.last_refresh_sequence.max(refreshed);
+        }
+        Ok(status)
     }
 
-    /// Get view statistics
-    pub fn get_view_stats(&self) -> crate::views::ViewStatistics {
-        self.view_manager.statistics()
+    /// Stored rows of a materialized view. Contents aren't persisted, so a
+    /// view is populated on first read after it is loaded.
+    pub(crate) fn materialized_view_rows(
+        &mut self,
+        view_name: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        if let Some(data) = self.view_manager.get_cached_data(view_name) {
+            return Ok(data);
+        }
+        self.refresh_view_contents(view_name, RefreshMode::Full)?;
+        Ok(self
+            .view_manager
+            .get_cached_data(view_name)
+            .unwrap_or_default())
     }
 
-    /// Create a full-text search index
-    pub fn create_search_index(
-        &self,
-        name: String,
-        table: String,
-        column: String,
-        config: SearchConfig,
-    ) -> Result<()> {
-        self.search_manager
-            .create_index(name, table, column, config)
+    /// Sequence of the last event written to `table`
+    pub fn table_sequence(&self, table: &str) -> Option<u64> {
+        self.tables
+            .get(table)
+            .map(|storage| storage.last_sequence())
     }
 
-    /// Drop a search index
-    pub fn drop_search_index(&self, name: &str) -> Result<()> {
+    /// The tables a query over `relations` reads, with their current
+    /// sequences. Names are resolved against `search_path` and views are
+    /// followed to the tables under them. While none of these sequences
+    /// nor the catalog version move, the query returns the same rows.
+    ///
+    /// `None` when that can't be told: a name is ambiguous or unknown, or
+    /// is a materialized view, whose contents change on refresh rather
+    /// than with its sources.
+    pub fn read_dependencies(
+        &self,
+        relations: &[String],
+        search_path: &[String],
+    ) -> Option<BTreeMap<String, u64>> {
+        let search_path = if search_path.is_empty() {
+            crate::search_path::default_search_path()
+        } else {
+            search_path.to_vec()
+        };
+        let mut pending = relations.to_vec();
+        let mut seen = HashSet::new();
+        let mut dependencies = BTreeMap::new();
+
+        while let Some(relation) = pending.pop() {
+            if !seen.insert(relation.clone()) {
+                continue;
+            }
+            let parts: Vec<&str> = relation.split('.').collect();
+            let name = crate::search_path::resolve(&search_path, &parts, |name| {
+                self.tables.contains_key(name)
+            })
+            .ok()?;
+            if let Some(sequence) = self.table_sequence(&name) {
+                dependencies.insert(name, sequence);
+            } else if let Some(view) = self.view_manager.get_view(&name) {
+                if view.is_materialized {
+                    return None;
+                }
+                pending.extend(view.dependencies.iter().cloned());
+            } else {
+                return None;
+            }
+        }
+        Some(dependencies)
+    }
+
+    fn materialized_view(&self, view_name: &str) -> Result<ViewDefinition> {
+        let view = self.view_manager.get_view(view_name).ok_or_else(|| {
+            DriftError::InvalidQuery(format!("View '{}' does not exist", view_name))
+        })?;
+        if !view.is_materialized {
+            return Err(DriftError::InvalidQuery(format!(
+                "View '{}' is not materialized",
+                view_name
+            )));
+        }
+        Ok(view)
+    }
+
+    /// Tables (not views) a view reads from
+    fn view_source_tables(&self, view: &ViewDefinition) -> Vec<String> {
+        let mut tables: Vec<String> = view
+            .dependencies
+            .iter()
+            .filter(|name| self.tables.contains_key(*name))
+            .cloned()
+            .collect();
+        tables.sort();
+        tables
+    }
+
+    fn refresh_view_contents(&mut self, view_name: &str, mode: RefreshMode) -> Result<RefreshMode> {
+        let view = self.materialized_view(view_name)?;
+        let source = crate::sql_bridge::incremental_view_source(self, &view.query);
+
+        let mut tables = self.view_source_tables(&view);
+        if let Some((table, _)) = &source {
+            if !tables.contains(table) {
+                tables.push(table.clone());
+            }
+        }
+        // Taken before the query runs, so the contents reflect at least these
+        let source_sequences: HashMap<String, u64> = tables
+            .iter()
+            .map(|table| (table.clone(), self.tables[table].last_sequence()))
+            .collect();
+        let catalog_version = self.catalog_version();
+
+        if mode == RefreshMode::Incremental {
+            if let (Some((table, pk)), Some(previous)) =
+                (&source, self.view_manager.materialized_contents(view_name))
+            {
+                if let Some((data, row_keys)) =
+                    self.apply_view_deltas(&view, table, pk, previous, catalog_version)?
+                {
+                    self.view_manager.replace_materialized_contents(
+                        view_name,
+                        MaterializedViewData::new(
+                            data,
+                            Some(row_keys),
+                            source_sequences,
+                            catalog_version,
+                        ),
+                    );
+                    return Ok(RefreshMode::Incremental);
+                }
+            }
+        }
+
+        let (data, row_keys) = crate::sql_bridge::materialize_view_query(
+            self,
+            &view.query,
+            source.as_ref().map(|(_, pk)| pk.as_str()),
+        )?;
+        self.view_manager.replace_materialized_contents(
+            view_name,
+            MaterializedViewData::new(data, row_keys, source_sequences, catalog_version),
+        );
+        info!("Materialized view '{}' refreshed", view_name);
+        Ok(RefreshMode::Full)
+    }
+
+    /// Bring `previous` contents up to date by re-evaluating the view for
+    /// each source row changed in the event log since they were computed.
+    /// `None` when only a full refresh can do that.
+    fn apply_view_deltas(
+        &mut self,
+        view: &ViewDefinition,
+        table: &str,
+        pk: &str,
+        previous: MaterializedViewData,
+        catalog_version: u64,
+    ) -> Result<Option<(Vec<serde_json::Value>, Vec<String>)>> {
+        let (Some(row_keys), Some(&since)) =
+            (previous.row_keys, previous.source_sequences.get(table))
+        else {
+            return Ok(None);
+        };
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        // Schema changes can change rows without events, and a recreated
+        // table starts its sequence over
+        if previous.catalog_version != catalog_version || storage.last_sequence() < since {
+            return Ok(None);
+        }
+
+        let mut changed = HashSet::new();
+        let mut changed_keys = Vec::new();
+        for event in storage.read_events_after_sequence(since)? {
+            if changed.insert(event.primary_key.to_string()) {
+                changed_keys.push(event.primary_key);
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut keys = Vec::new();
+        for (row, key) in previous.data.into_iter().zip(row_keys) {
+            if !changed.contains(&key) {
+                data.push(row);
+                keys.push(key);
+            }
+        }
+        for key in &changed_keys {
+            for row in crate::sql_bridge::view_rows_for_key(self, &view.query, pk, key)? {
+                data.push(row);
+                keys.push(key.to_string());
+            }
+        }
+        debug!(
+            "Materialized view '{}' applied {} changed source rows",
+            view.name,
+            changed_keys.len()
+        );
+        Ok(Some((data, keys)))
+    }
+
+    /// Get view statistics
+    pub fn get_view_stats(&self) -> crate::views::ViewStatistics {
+        self.view_manager.statistics()
+    }
+
+    /// Create a full-text search index
+    pub fn create_search_index(
+        &self,
+        name: String,
+        table: String,
+        column: String,
+        config: SearchConfig,
+    ) -> Result<()> {
+        self.search_manager
+            .create_index(name, table, column, config)
+    }
+
+    /// Drop a search index
+    pub fn drop_search_index(&self, name: &str) -> Result<()> {
         self.search_manager.drop_index(name)
     }
 
@@ -1273,18 +2137,23 @@ impl Engine {
 
     /// Create a trigger
     pub fn create_trigger(&self, definition: TriggerDefinition) -> Result<()> {
-        self.trigger_manager.create_trigger(definition)
+        self.ensure_writable("CREATE TRIGGER")?;
+        self.trigger_manager.create_trigger(definition)?;
+        self.save_triggers()
     }
 
     /// Drop a trigger
     pub fn drop_trigger(&self, trigger_name: &str) -> Result<()> {
-        self.trigger_manager.drop_trigger(trigger_name)
+        self.ensure_writable("DROP TRIGGER")?;
+        self.trigger_manager.drop_trigger(trigger_name)?;
+        self.save_triggers()
     }
 
     /// Enable or disable a trigger
     pub fn set_trigger_enabled(&self, trigger_name: &str, enabled: bool) -> Result<()> {
         self.trigger_manager
-            .set_trigger_enabled(trigger_name, enabled)
+            .set_trigger_enabled(trigger_name, enabled)?;
+        self.save_triggers()
     }
 
     /// List all triggers
@@ -1302,6 +2171,35 @@ impl Engine {
         self.trigger_manager.statistics()
     }
 
+    /// Create (or replace) a trigger function
+    pub fn create_trigger_function(
+        &self,
+        function: crate::triggers::TriggerFunction,
+        or_replace: bool,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE FUNCTION")?;
+        self.trigger_manager.create_function(function, or_replace)?;
+        self.save_triggers()
+    }
+
+    /// Drop a trigger function
+    pub fn drop_trigger_function(&self, name: &str) -> Result<()> {
+        self.ensure_writable("DROP FUNCTION")?;
+        self.trigger_manager.drop_function(name)?;
+        self.save_triggers()
+    }
+
+    /// Get a trigger function by name
+    pub fn get_trigger_function(&self, name: &str) -> Option<crate::triggers::TriggerFunction> {
+        self.trigger_manager.get_function(name)
+    }
+
+    /// Shared handle to the trigger manager, so the SQL bridge can fire
+    /// triggers while holding `&mut Engine` for the trigger bodies.
+    pub(crate) fn trigger_manager(&self) -> Arc<TriggerManager> {
+        self.trigger_manager.clone()
+    }
+
     /// Execute triggers for an event
     pub fn execute_triggers(
         &self,
@@ -1423,14 +2321,11 @@ impl Engine {
         &self,
         table_name: &str,
     ) -> Result<crate::optimizer::TableStatistics> {
-        use crate::optimizer::{
-            ColumnStatistics, Histogram, HistogramBucket, IndexStatistics, TableStatistics,
-        };
-
-        const HIGH_CARD_CAP: usize = 10_000;
-        const HISTOGRAM_BUCKETS: usize = 100;
+        use crate::optimizer::{ColumnStatistics, IndexStatistics, TableStatistics};
+        use rand::seq::IteratorRandom;
 
         let start = std::time::Instant::now();
+        let config = self.query_optimizer.analyze_config();
 
         let storage = self
             .tables
@@ -1442,18 +2337,29 @@ impl Engine {
         let current_state = storage.reconstruct_state_at(None)?;
         let row_count = current_state.len();
 
-        let total_size: usize = current_state.values().map(|v| v.to_string().len()).sum();
-        let avg_row_size = total_size.checked_div(row_count).unwrap_or(0);
+        // Column statistics come from a random sample of at most
+        // `sample_rows` rows, so their cost stays flat as tables grow
+        let sampled = row_count > config.sample_rows;
+        let sample: Vec<&serde_json::Value> = if sampled {
+            current_state
+                .values()
+                .choose_multiple(&mut rand::thread_rng(), config.sample_rows)
+        } else {
+            current_state.values().collect()
+        };
+
+        let sample_size: usize = sample.iter().map(|v| v.to_string().len()).sum();
+        let avg_row_size = sample_size.checked_div(sample.len()).unwrap_or(0);
         let total_size_bytes = storage.calculate_size_bytes()?;
 
-        // Schemaless column discovery: walk every row's keys, not just
-        // schema.columns. DriftDB tables are append-typed within a
+        // Schemaless column discovery: walk every sampled row's keys, not
+        // just schema.columns. DriftDB tables are append-typed within a
         // schema but the column set can grow by what INSERTs carry.
         // Sampling only what the declared schema knows would miss
         // any post-CREATE-TABLE columns.
         let mut observed_columns: std::collections::BTreeSet<String> =
             std::collections::BTreeSet::new();
-        for row in current_state.values() {
+        for row in &sample {
             if let serde_json::Value::Object(obj) = row {
                 for k in obj.keys() {
                     observed_columns.insert(k.clone());
@@ -1463,120 +2369,20 @@ impl Engine {
 
         let mut column_stats = HashMap::new();
         for column_name in &observed_columns {
-            // distinct-value tracking with a cap. Once we exceed
-            // HIGH_CARD_CAP unique values we stop populating the set;
-            // distinct_count reports the cap value (a floor on the
-            // real cardinality). Bounds memory on wide tables.
-            let mut distinct_keys: Option<std::collections::HashSet<String>> =
-                Some(std::collections::HashSet::new());
-            let mut null_count: usize = 0;
-            let mut min_value: Option<serde_json::Value> = None;
-            let mut max_value: Option<serde_json::Value> = None;
-            let mut values: Vec<serde_json::Value> = Vec::with_capacity(row_count);
-
-            for row in current_state.values() {
-                let val = match row.get(column_name) {
-                    Some(v) if !v.is_null() => v,
-                    _ => {
-                        // DriftDB schemaless: missing column ≡ NULL ≡
-                        // increments null_count. The bug-fix slice
-                        // for the predicate module already aligned
-                        // on this; ANALYZE follows.
-                        null_count += 1;
-                        continue;
-                    }
-                };
-                // min/max via the canonical JSON ordering. The
-                // previous string-conversion approach gave wrong
-                // results for numeric columns (lex-order on stringified
-                // numbers).
-                use std::cmp::Ordering;
-                if let Some(cur) = &min_value {
-                    if crate::query::predicate::compare_json_values(val, cur) == Ordering::Less {
-                        min_value = Some(val.clone());
-                    }
-                } else {
-                    min_value = Some(val.clone());
+            // DriftDB schemaless: missing column ≡ NULL ≡ increments
+            // the null count. The predicate module aligned on this;
+            // ANALYZE follows.
+            let mut values = Vec::with_capacity(sample.len());
+            let mut nulls = 0;
+            for row in &sample {
+                match row.get(column_name) {
+                    Some(v) if !v.is_null() => values.push(v.clone()),
+                    _ => nulls += 1,
                 }
-                if let Some(cur) = &max_value {
-                    if crate::query::predicate::compare_json_values(val, cur) == Ordering::Greater {
-                        max_value = Some(val.clone());
-                    }
-                } else {
-                    max_value = Some(val.clone());
-                }
-                if let Some(set) = &mut distinct_keys {
-                    set.insert(val.to_string());
-                    if set.len() > HIGH_CARD_CAP {
-                        distinct_keys = None;
-                    }
-                }
-                values.push(val.clone());
             }
-
-            let distinct_count = distinct_keys
-                .as_ref()
-                .map(|s| s.len())
-                .unwrap_or(HIGH_CARD_CAP);
-
-            // Equi-depth histogram: sort values, divide into N
-            // buckets each holding ~total/N entries. Each bucket's
-            // bounds are its first/last value after sort.
-            // Significantly better than the prior implementation,
-            // which sliced an unsorted Vec into N chunks — those
-            // buckets had no monotonic ordering of bounds, making
-            // them useless for selectivity estimation.
-            let histogram = if values.len() >= HISTOGRAM_BUCKETS {
-                values.sort_by(crate::query::predicate::compare_json_values);
-                let bucket_count = HISTOGRAM_BUCKETS.min(values.len());
-                let bucket_size = values.len() / bucket_count;
-                let mut buckets = Vec::with_capacity(bucket_count);
-                for i in 0..bucket_count {
-                    let start_idx = i * bucket_size;
-                    let end_idx = if i + 1 == bucket_count {
-                        values.len()
-                    } else {
-                        (i + 1) * bucket_size
-                    };
-                    let slice = &values[start_idx..end_idx];
-                    if slice.is_empty() {
-                        continue;
-                    }
-                    let lo = slice.first().unwrap().clone();
-                    let hi = slice.last().unwrap().clone();
-                    let mut bucket_distinct: std::collections::HashSet<String> =
-                        std::collections::HashSet::new();
-                    for v in slice {
-                        bucket_distinct.insert(v.to_string());
-                    }
-                    buckets.push(HistogramBucket {
-                        lower_bound: lo.clone(),
-                        upper_bound: hi.clone(),
-                        frequency: slice.len(),
-                        min_value: lo,
-                        max_value: hi,
-                        distinct_count: bucket_distinct.len(),
-                    });
-                }
-                let count = buckets.len();
-                Some(Histogram {
-                    buckets,
-                    bucket_count: count,
-                })
-            } else {
-                None
-            };
-
             column_stats.insert(
                 column_name.clone(),
-                ColumnStatistics {
-                    column_name: column_name.clone(),
-                    distinct_values: distinct_count,
-                    null_count,
-                    min_value,
-                    max_value,
-                    histogram,
-                },
+                ColumnStatistics::from_sample(column_name, values, nulls, row_count, &config),
             );
         }
 
@@ -1620,11 +2426,82 @@ impl Engine {
             column_statistics: column_stats,
             index_stats,
             last_updated: chrono::Utc::now().timestamp() as u64,
-            collection_method: "scan".to_string(),
+            collection_method: if sampled { "sample" } else { "scan" }.to_string(),
             collection_duration_ms: start.elapsed().as_millis() as u64,
         })
     }
 
+    /// `ANALYZE table [(columns)]`: collect statistics and hand them to the
+    /// optimizer. With `columns`, only those columns are refreshed and the
+    /// rest of the table's statistics are kept; the change count the
+    /// auto-analyzer watches is only reset by a whole-table analyze.
+    pub fn analyze_table(&self, table_name: &str, columns: Option<&[String]>) -> Result<()> {
+        let changes_before = self.changes_since_analyze(table_name);
+        let mut stats = self.collect_table_statistics(table_name)?;
+
+        if let Some(columns) = columns {
+            let schema = self.table_schema(table_name)?;
+            for column in columns {
+                if !schema.has_column(column) && !stats.column_stats.contains_key(column) {
+                    return Err(DriftError::InvalidQuery(format!(
+                        "column \"{}\" of relation \"{}\" does not exist",
+                        column, table_name
+                    )));
+                }
+            }
+            let mut merged = self
+                .query_optimizer
+                .table_statistics(table_name)
+                .map(|previous| previous.column_stats)
+                .unwrap_or_default();
+            for column in columns {
+                match stats.column_stats.remove(column) {
+                    Some(column_stats) => merged.insert(column.clone(), column_stats),
+                    None => merged.remove(column),
+                };
+            }
+            stats.column_stats = merged.clone();
+            stats.column_statistics = merged;
+        } else if let Some(count) = self.changes_since_analyze.write().get_mut(table_name) {
+            // Writes that landed while the table was being read still count
+            *count = count.saturating_sub(changes_before);
+        }
+
+        self.query_optimizer.update_statistics(table_name, stats);
+        Ok(())
+    }
+
+    /// Rows written to `table_name` since its last whole-table `ANALYZE`
+    pub fn changes_since_analyze(&self, table_name: &str) -> u64 {
+        self.changes_since_analyze
+            .read()
+            .get(table_name)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Tables with more than `threshold` rows written since their last
+    /// whole-table `ANALYZE`
+    pub fn tables_due_for_analyze(&self, threshold: u64) -> Vec<String> {
+        let mut due: Vec<String> = self
+            .changes_since_analyze
+            .read()
+            .iter()
+            .filter(|(table, count)| **count > threshold && self.tables.contains_key(*table))
+            .map(|(table, _)| table.clone())
+            .collect();
+        due.sort();
+        due
+    }
+
+    pub(crate) fn record_changes(&self, table_name: &str, rows: u64) {
+        *self
+            .changes_since_analyze
+            .write()
+            .entry(table_name.to_string())
+            .or_insert(0) += rows;
+    }
+
     /// Analyze all tables and update optimizer statistics
     pub fn analyze_all_tables(&self, optimizer: &crate::optimizer::QueryOptimizer) -> Result<()> {
         for table_name in self.list_tables() {
@@ -1662,6 +2539,14 @@ impl Engine {
         }
     }
 
+    /// Get the columns a table declares `UNIQUE`, not counting its primary key
+    pub fn get_unique_columns(&self, table_name: &str) -> BTreeSet<String> {
+        self.tables
+            .get(table_name)
+            .map(|storage| storage.schema().unique.clone())
+            .unwrap_or_default()
+    }
+
     /// Look up rows using an index
     pub fn lookup_by_index(
         &self,
@@ -1733,6 +2618,11 @@ impl Engine {
 
     /// Insert a record into a table (for SQL INSERT support)
     pub fn insert_record(&mut self, table_name: &str, mut record: serde_json::Value) -> Result<()> {
+        self.ensure_writable("INSERT")?;
+        let target = self.partition_for_row(table_name, &record)?;
+        if target != table_name {
+            return self.insert_record(&target, record);
+        }
         let storage = self
             .tables
             .get(table_name)
@@ -1740,13 +2630,13 @@ impl Engine {
             .clone();
 
         // Get the schema for validation
-        let schema = storage.schema();
+        let schema = storage.schema().clone();
 
         // Validate constraints and apply defaults
         {
             let constraint_mgr = self.constraint_manager.write();
             constraint_mgr
-                .validate_insert(schema, &mut record, self)
+                .validate_insert(&schema, &mut record, self)
                 .map_err(|e| DriftError::Other(format!("Constraint violation: {}", e)))?;
         }
 
@@ -1767,7 +2657,7 @@ impl Engine {
         }
 
         // Extract primary key from record
-        let primary_key_field = &storage.schema().primary_key;
+        let primary_key_field = &schema.primary_key;
         let primary_key = record
             .get(primary_key_field)
             .ok_or_else(|| {
@@ -1781,6 +2671,39 @@ impl Engine {
         Ok(())
     }
 
+    /// Insert `rows` into `table_name` as one batch, returning the last
+    /// sequence written. Every row is checked with
+    /// [`Engine::check_insert_row`], and against the rest of the batch,
+    /// before any is written; one bad row rejects the whole batch. The
+    /// rows then go to storage under a single lock with one sync, and the
+    /// indexes are updated and saved once, so a bulk ingest doesn't pay
+    /// per-row overhead.
+    pub fn append_events_batch(
+        &mut self,
+        table_name: &str,
+        rows: Vec<serde_json::Value>,
+    ) -> Result<u64> {
+        self.ensure_writable("INSERT")?;
+        let mut events = Vec::with_capacity(rows.len());
+        let mut batch_keys = HashSet::with_capacity(rows.len());
+        for mut record in rows {
+            let primary_key = self.check_insert_row(table_name, &mut record)?;
+            if !batch_keys.insert(primary_key.to_string()) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "Primary key violation: {} already exists",
+                    primary_key
+                )));
+            }
+            events.push(Event::new_insert(
+                table_name.to_string(),
+                primary_key,
+                record,
+            ));
+        }
+
+        self.apply_events(table_name, events)
+    }
+
     /// Update a record in a table (for SQL UPDATE support)
     pub fn update_record(
         &mut self,
@@ -1788,6 +2711,7 @@ impl Engine {
         primary_key: serde_json::Value,
         record: serde_json::Value,
     ) -> Result<()> {
+        self.ensure_writable("UPDATE")?;
         let _storage = self
             .tables
             .get(table_name)
@@ -1807,6 +2731,7 @@ impl Engine {
         table_name: &str,
         primary_key: serde_json::Value,
     ) -> Result<()> {
+        self.ensure_writable("DELETE")?;
         let _storage = self
             .tables
             .get(table_name)
@@ -1829,6 +2754,7 @@ impl Engine {
         column: &crate::schema::ColumnDef,
         default_value: Option<serde_json::Value>,
     ) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
         // Get the table storage
         let storage = self
             .tables
@@ -1854,8 +2780,7 @@ impl Engine {
         schema.columns.push(column.clone());
 
         // Save updated schema
-        let updated_schema = serde_yaml::to_string(&schema)?;
-        fs::write(&schema_path, updated_schema)?;
+        storage.update_schema(schema)?;
 
         // If there's a default value, backfill existing records
         if let Some(default) = default_value {
@@ -1882,11 +2807,19 @@ impl Engine {
             }
         }
 
+        self.catalog_changed();
         Ok(())
     }
 
     /// Apply a schema migration to drop a column
     pub fn migrate_drop_column(&mut self, table: &str, column: &str) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+
         // Update the schema file
         let table_path = self.base_path.join("tables").join(table);
         let schema_path = table_path.join("schema.yaml");
@@ -1897,12 +2830,12 @@ impl Engine {
         schema.columns.retain(|c| c.name != column);
 
         // Save updated schema
-        let updated_schema = serde_yaml::to_string(&schema)?;
-        fs::write(&schema_path, updated_schema)?;
+        storage.update_schema(schema)?;
 
         // Note: We don't remove the data from existing events (append-only)
         // The column will just be ignored in future queries
 
+        self.catalog_changed();
         Ok(())
     }
 
@@ -1913,6 +2846,7 @@ impl Engine {
         old_name: &str,
         new_name: &str,
     ) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
         let storage = self
             .tables
             .get(table)
@@ -1940,8 +2874,7 @@ impl Engine {
         }
 
         // Save updated schema
-        let updated_schema = serde_yaml::to_string(&schema)?;
-        fs::write(&schema_path, updated_schema)?;
+        storage.update_schema(schema)?;
 
         // Create patch events to rename the field in existing records
         let current_state = storage.reconstruct_state_at(None)?;
@@ -1966,6 +2899,458 @@ impl Engine {
             }
         }
 
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `ALTER TABLE ... ADD COLUMN`. Stored events aren't rewritten: rows
+    /// written before the column existed read back with `default` (or
+    /// NULL), and time-travel reads from before the change don't show it.
+    pub fn alter_add_column(
+        &mut self,
+        table: &str,
+        column: crate::schema::ColumnDef,
+        default: Option<serde_json::Value>,
+    ) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+
+        if schema.has_column(&column.name) || column.name == schema.primary_key {
+            return Err(DriftError::Schema(format!(
+                "column \"{}\" of relation \"{}\" already exists",
+                column.name, table
+            )));
+        }
+        if schema.is_retired_column(&column.name) {
+            return Err(DriftError::Schema(format!(
+                "column \"{}\" of relation \"{}\" was dropped or renamed and its history is retained; choose another name",
+                column.name, table
+            )));
+        }
+
+        schema.changes.push(crate::schema::ColumnChange::Add {
+            column: column.name.clone(),
+            default,
+            sequence: storage.last_sequence() + 1,
+        });
+        schema.columns.push(column);
+        storage.update_schema(schema)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Record the `DEFAULT` expressions (SQL text) declared for a table's
+    /// columns. INSERTs that leave a column out evaluate its default.
+    pub fn set_column_defaults(
+        &mut self,
+        table: &str,
+        defaults: std::collections::BTreeMap<String, String>,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+        schema.defaults = defaults;
+        storage.update_schema(schema)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Record the columns a table declares `UNIQUE`
+    pub fn set_unique_columns(&mut self, table: &str, unique: BTreeSet<String>) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+        schema.unique = unique;
+        storage.update_schema(schema)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Record a table's `CHECK` constraints and outgoing foreign keys.
+    /// Both are kept on the schema so they survive a restart.
+    pub fn set_table_constraints(
+        &mut self,
+        table: &str,
+        checks: Vec<crate::schema::CheckConstraint>,
+        foreign_keys: Vec<crate::fk::ForeignKey>,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+        schema.checks = checks;
+        schema.foreign_keys = foreign_keys.clone();
+        storage.update_schema(schema)?;
+        crate::fk::register(table, foreign_keys);
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `ALTER TABLE t SET (compression = ...)`: compress the table's
+    /// segments from now on, and its history once compacted
+    pub fn set_table_compression(&mut self, table: &str, compression: Compression) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        storage.set_compression(compression)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `ALTER TABLE t SET (bloom_filter = ...)`: keep a bloom filter of
+    /// each segment's primary keys at false-positive rate `fpp`, so lookups
+    /// by primary key skip the segments that can't hold the key, or stop
+    /// with `None`
+    pub fn set_table_bloom_filter(&mut self, table: &str, fpp: Option<f64>) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        if let Some(fpp) = fpp {
+            if !(fpp > 0.0 && fpp < 1.0) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "bloom_filter_fpp must be between 0 and 1, got {}",
+                    fpp
+                )));
+            }
+        }
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        storage.set_key_filter(fpp)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `ALTER TABLE t SET (retain_history_for = ..., retain_versions = ...)`:
+    /// how much of a table's history `VACUUM` keeps for time travel
+    pub fn set_table_retention(&mut self, table: &str, policy: RetentionPolicy) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        storage.set_retention(policy)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `WITH (storage = ...)`: scan a table's current rows from the event
+    /// log (`row`) or from a column-oriented projection of them
+    /// (`columnar`). Writes go to the event log either way.
+    pub fn set_table_storage(&mut self, table: &str, format: StorageFormat) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        storage.set_storage_format(format)?;
+        self.query_optimizer
+            .set_columnar(table, format == StorageFormat::Columnar);
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Segments a table's primary key lookups have skipped and read
+    pub fn key_filter_stats(&self, table: &str) -> Result<KeyFilterStats> {
+        self.tables
+            .get(table)
+            .map(|storage| storage.key_filter_stats())
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))
+    }
+
+    /// `ALTER TABLE t SET (bloom_filter_columns = '...')`: keep a bloom
+    /// filter of each of `columns` per chunk of a columnar table's
+    /// projection, so an equality on one tests only the chunks that might
+    /// hold the value. The rate is the table's `bloom_filter_fpp`, or the
+    /// default when that is off.
+    pub fn set_table_bloom_filter_columns(
+        &mut self,
+        table: &str,
+        columns: BTreeSet<String>,
+    ) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let missing = {
+            let schema = storage.schema();
+            columns
+                .iter()
+                .find(|column| !schema.columns.iter().any(|c| c.name == **column))
+                .cloned()
+        };
+        if let Some(column) = missing {
+            return Err(DriftError::InvalidQuery(format!(
+                "column '{}' does not exist in table '{}'",
+                column, table
+            )));
+        }
+        storage.set_bloom_filter_columns(columns)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// Chunks of a columnar table's projection that equality scans have
+    /// skipped and tested
+    pub fn chunk_filter_stats(&self, table: &str) -> Result<ChunkFilterStats> {
+        self.tables
+            .get(table)
+            .map(|storage| storage.chunk_filter_stats())
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))
+    }
+
+    /// Record which of a table's columns are enum-typed, so storage writes
+    /// them as label positions.
+    pub fn set_enum_columns(
+        &mut self,
+        table: &str,
+        enums: BTreeMap<String, EnumType>,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+        schema.enums = enums;
+        storage.update_schema(schema)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `CREATE TYPE name AS ENUM (...)`
+    pub fn create_enum_type(&mut self, enum_type: EnumType) -> Result<()> {
+        self.ensure_writable("CREATE TYPE")?;
+        {
+            let mut enum_types = self.enum_types.write();
+            if enum_types.contains_key(&enum_type.name) {
+                return Err(DriftError::Other(format!(
+                    "type \"{}\" already exists",
+                    enum_type.name
+                )));
+            }
+            enum_types.insert(enum_type.name.clone(), enum_type);
+        }
+        self.catalog_changed();
+        self.save_enum_types()
+    }
+
+    /// `DROP TYPE name`. Fails while a table still has a column of the type.
+    pub fn drop_enum_type(&mut self, name: &str) -> Result<()> {
+        self.ensure_writable("DROP TYPE")?;
+        if !self.enum_types.read().contains_key(name) {
+            return Err(DriftError::Other(format!(
+                "type \"{}\" does not exist",
+                name
+            )));
+        }
+        for (table, storage) in &self.tables {
+            if storage
+                .schema()
+                .enums
+                .values()
+                .any(|enum_type| enum_type.name == name)
+            {
+                return Err(DriftError::Other(format!(
+                    "cannot drop type {} because other objects depend on it (a column of table {})",
+                    name, table
+                )));
+            }
+        }
+        self.enum_types.write().remove(name);
+        self.catalog_changed();
+        self.save_enum_types()
+    }
+
+    /// Look up an enum type by name
+    pub fn get_enum_type(&self, name: &str) -> Option<EnumType> {
+        self.enum_types.read().get(name).cloned()
+    }
+
+    /// `CREATE SCHEMA name`. Returns false when the schema already exists
+    /// and `if_not_exists` is set.
+    pub fn create_schema(&mut self, name: &str, if_not_exists: bool) -> Result<bool> {
+        self.ensure_writable("CREATE SCHEMA")?;
+        if self.schema_exists(name) {
+            if if_not_exists {
+                return Ok(false);
+            }
+            return Err(DriftError::Other(format!(
+                "schema \"{}\" already exists",
+                name
+            )));
+        }
+        self.schemas.write().insert(name.to_string());
+        self.save_schemas()?;
+        self.catalog_changed();
+        Ok(true)
+    }
+
+    /// `DROP SCHEMA name`. Fails while the schema has tables unless
+    /// `cascade` is set, which drops them too.
+    pub fn drop_schema(&mut self, name: &str, cascade: bool) -> Result<()> {
+        self.ensure_writable("DROP SCHEMA")?;
+        if name == crate::search_path::DEFAULT_SCHEMA {
+            return Err(DriftError::Other(format!("cannot drop schema {}", name)));
+        }
+        if !self.schemas.read().contains(name) {
+            return Err(DriftError::Other(format!(
+                "schema \"{}\" does not exist",
+                name
+            )));
+        }
+
+        let mut tables: Vec<String> = self
+            .tables
+            .keys()
+            .filter(|table| crate::search_path::split_storage_name(table).0 == name)
+            .cloned()
+            .collect();
+        tables.sort();
+        if !tables.is_empty() && !cascade {
+            return Err(DriftError::Other(format!(
+                "cannot drop schema {} because other objects depend on it (tables {})",
+                name,
+                tables.join(", ")
+            )));
+        }
+        for table in tables {
+            self.drop_table(&table)?;
+        }
+
+        self.schemas.write().remove(name);
+        self.catalog_changed();
+        self.save_schemas()
+    }
+
+    /// Whether a schema exists. `public` always does.
+    pub fn schema_exists(&self, name: &str) -> bool {
+        name == crate::search_path::DEFAULT_SCHEMA || self.schemas.read().contains(name)
+    }
+
+    /// All schemas, `public` first
+    pub fn list_schemas(&self) -> Vec<String> {
+        std::iter::once(crate::search_path::DEFAULT_SCHEMA.to_string())
+            .chain(self.schemas.read().iter().cloned())
+            .collect()
+    }
+
+    /// Whether a table exists under its engine name (`schema.table`
+    /// outside `public`)
+    pub fn table_exists(&self, name: &str) -> bool {
+        self.tables.contains_key(name)
+    }
+
+    /// `ALTER TABLE ... DROP COLUMN`. The column disappears from reads;
+    /// its values stay in the event log, so time-travel reads from before
+    /// the drop still show them.
+    pub fn alter_drop_column(&mut self, table: &str, column: &str) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+
+        if column == schema.primary_key {
+            return Err(DriftError::Schema(format!(
+                "cannot drop primary key column \"{}\" of relation \"{}\"",
+                column, table
+            )));
+        }
+        match schema.columns.iter().find(|c| c.name == column) {
+            None => {
+                return Err(DriftError::Schema(format!(
+                    "column \"{}\" of relation \"{}\" does not exist",
+                    column, table
+                )))
+            }
+            Some(def) if def.index => {
+                return Err(DriftError::Schema(format!(
+                    "cannot drop indexed column \"{}\" of relation \"{}\"",
+                    column, table
+                )))
+            }
+            Some(_) => {}
+        }
+
+        schema.changes.push(crate::schema::ColumnChange::Drop {
+            column: column.to_string(),
+            sequence: storage.last_sequence() + 1,
+        });
+        schema.columns.retain(|c| c.name != column);
+        storage.update_schema(schema)?;
+        self.catalog_changed();
+        Ok(())
+    }
+
+    /// `ALTER TABLE ... RENAME COLUMN`. Reads present the column under its
+    /// new name, including values from events written before the rename.
+    pub fn alter_rename_column(&mut self, table: &str, from: &str, to: &str) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .clone();
+        let mut schema = storage.schema().clone();
+
+        if from == schema.primary_key {
+            return Err(DriftError::Schema(format!(
+                "cannot rename primary key column \"{}\" of relation \"{}\"",
+                from, table
+            )));
+        }
+        if !schema.has_column(from) {
+            return Err(DriftError::Schema(format!(
+                "column \"{}\" of relation \"{}\" does not exist",
+                from, table
+            )));
+        }
+        if schema.has_column(to) || to == schema.primary_key || schema.is_retired_column(to) {
+            return Err(DriftError::Schema(format!(
+                "column \"{}\" of relation \"{}\" already exists",
+                to, table
+            )));
+        }
+
+        schema.changes.push(crate::schema::ColumnChange::Rename {
+            from: from.to_string(),
+            to: to.to_string(),
+            sequence: storage.last_sequence() + 1,
+        });
+        for column in &mut schema.columns {
+            if column.name == from {
+                column.name = to.to_string();
+            }
+        }
+        storage.update_schema(schema)?;
+        self.catalog_changed();
         Ok(())
     }
 
@@ -2006,6 +3391,205 @@ impl Engine {
         max_seq
     }
 
+    /// Write a snapshot of every table to `writer` as a single stream (see
+    /// [`crate::snapshot_stream`]).
+    ///
+    /// Writers need `&mut Engine`, so none can run while the export holds
+    /// `&self`. Each table's sequence is also captured before anything is
+    /// written and later events are skipped, so the stream is one
+    /// consistent cut across tables.
+    pub fn export_snapshot<W: Write>(&self, writer: W) -> Result<SnapshotInfo> {
+        let mut names: Vec<String> = self.tables.keys().cloned().collect();
+        names.sort();
+        let sequences: BTreeMap<String, u64> = names
+            .iter()
+            .map(|name| (name.clone(), self.tables[name].last_sequence()))
+            .collect();
+
+        let mut out = SnapshotWriter::new(writer);
+        out.write(&SnapshotRecord::Header {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            sequences: sequences.clone(),
+        })?;
+
+        let mut events = 0u64;
+        for name in &names {
+            let storage = &self.tables[name];
+            let schema = storage.schema().clone();
+            out.write(&SnapshotRecord::Table { schema })?;
+
+            let cut = sequences[name];
+            for event in storage.read_events_with_limit(Some(usize::MAX))? {
+                if event.sequence > cut {
+                    continue;
+                }
+                out.write(&SnapshotRecord::Event { event })?;
+                events += 1;
+            }
+        }
+        out.write(&SnapshotRecord::Views {
+            views: self.list_views(),
+        })?;
+        out.write(&SnapshotRecord::End { events })?;
+        out.finish()?;
+
+        info!(
+            "Exported snapshot of {} tables ({} events)",
+            names.len(),
+            events
+        );
+        Ok(SnapshotInfo { sequences, events })
+    }
+
+    /// Load a snapshot written by [`Engine::export_snapshot`] into a new
+    /// database at `base_path`, which must be missing or empty. Events keep
+    /// their sequences, so the copy can resume replication from
+    /// [`SnapshotInfo::sequences`]. A stream that ends before its `end`
+    /// record is rejected and the partial copy removed.
+    pub fn import_snapshot<P: AsRef<Path>, R: Read>(
+        base_path: P,
+        reader: R,
+    ) -> Result<SnapshotInfo> {
+        let base_path = base_path.as_ref();
+        if base_path.exists() && fs::read_dir(base_path)?.next().is_some() {
+            return Err(DriftError::Other(format!(
+                "Snapshot target is not empty: {}",
+                base_path.display()
+            )));
+        }
+
+        let result = Self::init(base_path).and_then(|mut engine| {
+            engine.load_snapshot(SnapshotReader::new(BufReader::new(reader)))
+        });
+        if result.is_err() {
+            let _ = fs::remove_dir_all(base_path);
+        }
+        result
+    }
+
+    fn load_snapshot<R: BufRead>(&mut self, mut reader: SnapshotReader<R>) -> Result<SnapshotInfo> {
+        let sequences = match reader.next_record()? {
+            Some(SnapshotRecord::Header {
+                format_version,
+                sequences,
+            }) => {
+                if format_version != SNAPSHOT_FORMAT_VERSION {
+                    return Err(DriftError::Other(format!(
+                        "Unsupported snapshot format version {}",
+                        format_version
+                    )));
+                }
+                sequences
+            }
+            _ => {
+                return Err(DriftError::Other(
+                    "Snapshot stream does not start with a header".into(),
+                ))
+            }
+        };
+
+        let mut events = 0u64;
+        loop {
+            match reader.next_record()? {
+                Some(SnapshotRecord::Table { schema }) => self.install_table(schema)?,
+                Some(SnapshotRecord::Event { event }) => {
+                    let storage = self
+                        .tables
+                        .get(&event.table_name)
+                        .ok_or_else(|| DriftError::TableNotFound(event.table_name.clone()))?
+                        .clone();
+                    if let Some(index_mgr) = self.indexes.get(&event.table_name) {
+                        let mut index_mgr = index_mgr.write();
+                        let active = index_mgr.indexed_column_names();
+                        index_mgr.update_indexes(&event, &active)?;
+                    }
+                    storage.append_existing_event(event)?;
+                    events += 1;
+                }
+                Some(SnapshotRecord::Views { views }) => {
+                    for view in views {
+                        self.view_manager.create_view(view)?;
+                    }
+                    self.save_views()?;
+                }
+                Some(SnapshotRecord::End { events: expected }) => {
+                    if expected != events {
+                        return Err(DriftError::Other(format!(
+                            "Snapshot stream lists {} events but contained {}",
+                            expected, events
+                        )));
+                    }
+                    break;
+                }
+                Some(SnapshotRecord::Header { .. }) => {
+                    return Err(DriftError::Other(
+                        "Unexpected header in the middle of a snapshot stream".into(),
+                    ))
+                }
+                None => {
+                    return Err(DriftError::Other(
+                        "Snapshot stream ended early; it may have been truncated".into(),
+                    ))
+                }
+            }
+        }
+
+        for (name, sequence) in &sequences {
+            let storage = self
+                .tables
+                .get(name)
+                .ok_or_else(|| DriftError::TableNotFound(name.clone()))?;
+            storage.advance_sequence_to(*sequence)?;
+        }
+        for index_mgr in self.indexes.values() {
+            index_mgr.read().save_all()?;
+        }
+        for name in sequences.keys() {
+            self.register_indexes_with_optimizer(name);
+        }
+
+        info!(
+            "Imported snapshot of {} tables ({} events)",
+            sequences.len(),
+            events
+        );
+        Ok(SnapshotInfo { sequences, events })
+    }
+
+    /// Create storage for a table with an existing schema, as copied from
+    /// another database.
+    fn install_table(&mut self, schema: Schema) -> Result<()> {
+        if self.tables.contains_key(&schema.name) {
+            return Err(DriftError::Other(format!(
+                "Table '{}' already exists",
+                schema.name
+            )));
+        }
+        schema.validate()?;
+
+        let name = schema.name.clone();
+        let storage = Arc::new(
+            TableStorage::create_with_backend(
+                &self.base_path,
+                schema.clone(),
+                self.encryption_service.clone(),
+                self.storage_backend.clone(),
+            )?
+            .with_durability(self.durability.clone()),
+        );
+
+        let mut index_mgr = IndexManager::new(storage.path());
+        index_mgr.load_indexes(&schema.indexed_columns())?;
+
+        let snapshot_mgr = SnapshotManager::new(storage.path());
+
+        self.tables.insert(name.clone(), storage);
+        self.indexes
+            .insert(name.clone(), Arc::new(RwLock::new(index_mgr)));
+        self.snapshots.insert(name, Arc::new(snapshot_mgr));
+        Ok(())
+    }
+
     /// Find the sequence number for a given timestamp
     pub fn find_sequence_for_timestamp(&self, timestamp: time::OffsetDateTime) -> Result<u64> {
         let mut closest_sequence = 0u64;
@@ -2095,6 +3679,102 @@ impl Engine {
         Ok(())
     }
 
+    /// Save enum types to disk
+    fn save_enum_types(&self) -> Result<()> {
+        let types: Vec<EnumType> = self.enum_types.read().values().cloned().collect();
+        let json_data = serde_json::to_string_pretty(&types)?;
+        std::fs::write(self.base_path.join("types.json"), json_data)?;
+        Ok(())
+    }
+
+    /// Save trigger functions and the triggers that call them to disk
+    fn save_triggers(&self) -> Result<()> {
+        let json_data = serde_json::to_string_pretty(&self.trigger_manager.saved())?;
+        std::fs::write(self.base_path.join("triggers.json"), json_data)?;
+        Ok(())
+    }
+
+    /// Load trigger functions, then the triggers that call them, from disk
+    fn load_triggers(&self) -> Result<()> {
+        let triggers_file = self.base_path.join("triggers.json");
+        if !triggers_file.exists() {
+            return Ok(());
+        }
+
+        let json_data = std::fs::read_to_string(triggers_file)?;
+        let saved: SavedTriggers = serde_json::from_str(&json_data)?;
+        for function in saved.functions {
+            self.trigger_manager.create_function(function, true)?;
+        }
+        for trigger in saved.triggers {
+            self.trigger_manager.create_trigger(trigger)?;
+        }
+        Ok(())
+    }
+
+    /// Save schema names to disk
+    fn save_schemas(&self) -> Result<()> {
+        let schemas: Vec<String> = self.schemas.read().iter().cloned().collect();
+        let json_data = serde_json::to_string_pretty(&schemas)?;
+        std::fs::write(self.base_path.join("schemas.json"), json_data)?;
+        Ok(())
+    }
+
+    /// Load schema names from disk
+    fn load_schemas(&self) -> Result<()> {
+        let schemas_file = self.base_path.join("schemas.json");
+        if !schemas_file.exists() {
+            return Ok(());
+        }
+
+        let json_data = std::fs::read_to_string(schemas_file)?;
+        let schemas: Vec<String> = serde_json::from_str(&json_data)?;
+        self.schemas.write().extend(schemas);
+        Ok(())
+    }
+
+    /// Save the `ALTER ROLE ... SET` values to disk
+    pub(crate) fn save_role_settings(&self) -> Result<()> {
+        let json_data = serde_json::to_string_pretty(&self.settings.role_values())?;
+        std::fs::write(self.base_path.join("role_settings.json"), json_data)?;
+        Ok(())
+    }
+
+    /// Load the `ALTER ROLE ... SET` values from disk
+    fn load_role_settings(&self) -> Result<()> {
+        let settings_file = self.base_path.join("role_settings.json");
+        if !settings_file.exists() {
+            return Ok(());
+        }
+
+        let json_data = std::fs::read_to_string(settings_file)?;
+        let roles: BTreeMap<String, BTreeMap<String, String>> = serde_json::from_str(&json_data)?;
+        for (role, values) in roles {
+            for (name, value) in values {
+                let parameter = crate::settings::lookup(&name)?;
+                self.settings
+                    .set_for_role(&role, parameter, Some(parameter.parse(&value)?));
+            }
+        }
+        Ok(())
+    }
+
+    /// Load enum types from disk
+    fn load_enum_types(&self) -> Result<()> {
+        let types_file = self.base_path.join("types.json");
+        if !types_file.exists() {
+            return Ok(());
+        }
+
+        let json_data = std::fs::read_to_string(types_file)?;
+        let types: Vec<EnumType> = serde_json::from_str(&json_data)?;
+        let mut enum_types = self.enum_types.write();
+        for enum_type in types {
+            enum_types.insert(enum_type.name.clone(), enum_type);
+        }
+        Ok(())
+    }
+
     // === Error Recovery Methods ===
 
     /// Perform manual crash recovery
@@ -0,0 +1,200 @@
+//! Enum types
+//!
+//! `CREATE TYPE status AS ENUM ('pending', 'paid', 'void')` declares an
+//! ordered set of labels. A column of that type only accepts those labels.
+//! Table storage writes each value as the label's position, so events carry
+//! a small integer rather than the text, and turns positions back into
+//! labels when events are read. Comparisons and `ORDER BY` on the column
+//! follow declaration order, not alphabetical order.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// A declared enum type: its name and labels in declaration order
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumType {
+    pub name: String,
+    pub labels: Vec<String>,
+}
+
+impl EnumType {
+    /// Position of `label` in declaration order
+    pub fn position(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|l| l == label)
+    }
+
+    /// Validate a value written into a column of this type. NULL passes
+    /// through.
+    pub fn check(&self, value: &Value) -> Result<()> {
+        match value {
+            Value::Null => Ok(()),
+            Value::String(label) if self.position(label).is_some() => Ok(()),
+            Value::String(label) => Err(self.invalid(label)),
+            other => Err(self.invalid(&other.to_string())),
+        }
+    }
+
+    /// Stored form of a label: its position
+    pub fn encode(&self, value: &Value) -> Result<Value> {
+        match value {
+            Value::String(label) => self
+                .position(label)
+                .map(|i| Value::from(i as u64))
+                .ok_or_else(|| self.invalid(label)),
+            other => Ok(other.clone()),
+        }
+    }
+
+    /// A stored position back to its label. Anything else is returned as is.
+    pub fn decode(&self, value: &Value) -> Value {
+        match value.as_u64().and_then(|i| self.labels.get(i as usize)) {
+            Some(label) => Value::String(label.clone()),
+            None => value.clone(),
+        }
+    }
+
+    fn invalid(&self, label: &str) -> DriftError {
+        DriftError::InvalidQuery(format!(
+            "invalid input value for enum {}: \"{}\"",
+            self.name, label
+        ))
+    }
+}
+
+/// Parse `CREATE TYPE name AS ENUM ('label', ...)`
+pub fn parse_create_type(sql: &str) -> Result<EnumType> {
+    let syntax =
+        || DriftError::Parse("expected CREATE TYPE name AS ENUM ('label', ...)".to_string());
+    let rest = sql.trim().trim_end_matches(';').trim();
+    let rest = strip_keyword(rest, "CREATE").ok_or_else(syntax)?;
+    let rest = strip_keyword(rest, "TYPE").ok_or_else(syntax)?;
+    let (name, rest) = rest.split_once(char::is_whitespace).ok_or_else(syntax)?;
+    let rest = strip_keyword(rest.trim_start(), "AS").ok_or_else(syntax)?;
+    let rest = strip_keyword(rest, "ENUM").ok_or_else(syntax)?;
+    let body = rest
+        .strip_prefix('(')
+        .and_then(|r| r.strip_suffix(')'))
+        .ok_or_else(syntax)?;
+
+    let labels = parse_labels(body)?;
+    let mut seen = std::collections::HashSet::new();
+    for label in &labels {
+        if !seen.insert(label) {
+            return Err(DriftError::InvalidQuery(format!(
+                "enum label \"{}\" used more than once",
+                label
+            )));
+        }
+    }
+    Ok(EnumType {
+        name: name.to_string(),
+        labels,
+    })
+}
+
+/// `rest` without a leading case-insensitive `keyword` and the whitespace
+/// after it
+fn strip_keyword<'a>(rest: &'a str, keyword: &str) -> Option<&'a str> {
+    let head = rest.get(..keyword.len())?;
+    if !head.eq_ignore_ascii_case(keyword) {
+        return None;
+    }
+    let tail = &rest[keyword.len()..];
+    if tail.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(tail.trim_start())
+}
+
+/// Comma-separated single-quoted labels; `''` inside a label is a quote
+fn parse_labels(body: &str) -> Result<Vec<String>> {
+    let mut labels = Vec::new();
+    let mut chars = body.trim().chars().peekable();
+    if chars.peek().is_none() {
+        return Ok(labels);
+    }
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next() != Some('\'') {
+            return Err(DriftError::Parse(
+                "enum labels must be quoted strings".to_string(),
+            ));
+        }
+        let mut label = String::new();
+        loop {
+            match chars.next() {
+                Some('\'') if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    label.push('\'');
+                }
+                Some('\'') => break,
+                Some(c) => label.push(c),
+                None => return Err(DriftError::Parse("unterminated enum label".to_string())),
+            }
+        }
+        labels.push(label);
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => continue,
+            None => return Ok(labels),
+            Some(c) => {
+                return Err(DriftError::Parse(format!(
+                    "unexpected '{}' in enum label list",
+                    c
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn status() -> EnumType {
+        parse_create_type("CREATE TYPE status AS ENUM ('pending', 'paid', 'void');").unwrap()
+    }
+
+    #[test]
+    fn test_parse_create_type() {
+        assert_eq!(
+            status(),
+            EnumType {
+                name: "status".to_string(),
+                labels: vec!["pending".into(), "paid".into(), "void".into()],
+            }
+        );
+        let quoted = parse_create_type("create type mood as enum ('it''s ok', 'sad')").unwrap();
+        assert_eq!(quoted.labels, vec!["it's ok", "sad"]);
+        assert!(parse_create_type("CREATE TYPE empty AS ENUM ()")
+            .unwrap()
+            .labels
+            .is_empty());
+
+        assert!(parse_create_type("CREATE TYPE s AS ENUM ('a', 'a')").is_err());
+        assert!(parse_create_type("CREATE TYPE s AS ENUM (a, b)").is_err());
+        assert!(parse_create_type("CREATE TYPE s AS ENUM ('a'").is_err());
+        assert!(parse_create_type("CREATE TYPE s AS ENUMS ('a')").is_err());
+    }
+
+    #[test]
+    fn test_check_encode_decode() {
+        let status = status();
+        assert!(status.check(&json!("paid")).is_ok());
+        assert!(status.check(&Value::Null).is_ok());
+        let err = status.check(&json!("refunded")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid input value for enum status: \"refunded\""));
+        assert!(status.check(&json!(1)).is_err());
+
+        assert_eq!(status.encode(&json!("void")).unwrap(), json!(2));
+        assert_eq!(status.decode(&json!(2)), json!("void"));
+        assert_eq!(status.encode(&Value::Null).unwrap(), Value::Null);
+        assert_eq!(status.decode(&Value::Null), Value::Null);
+        assert!(status.encode(&json!("refunded")).is_err());
+    }
+}
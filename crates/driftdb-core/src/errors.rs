@@ -23,6 +23,10 @@ pub enum DriftError {
     #[error("Lock error: {0}")]
     Lock(String),
 
+    /// SQLSTATE 40P01: the transaction was aborted to break a deadlock
+    #[error("Deadlock detected: {0}")]
+    Deadlock(crate::mvcc::DeadlockReport),
+
     #[error("Corrupt segment: {0}")]
     CorruptSegment(String),
 
@@ -47,6 +51,27 @@ pub enum DriftError {
     #[error("Not leader")]
     NotLeader,
 
+    #[error("cannot execute {0} in read-only mode")]
+    ReadOnly(String),
+
+    /// `read_your_writes`: this node hasn't applied the session's own
+    /// latest write yet
+    #[error("read-your-writes: {0}")]
+    WriteNotApplied(String),
+
+    /// A query's result is over `max_result_rows` or `max_result_bytes`
+    #[error("result too large: {0}")]
+    ResultTooLarge(String),
+
+    /// A statement would take its session over `max_session_memory`
+    #[error("out of memory: {0}")]
+    OutOfMemory(String),
+
+    /// A read as of a point before the history a table's retention
+    /// policy keeps
+    #[error("history pruned before {0}")]
+    HistoryPruned(String),
+
     #[error("Timeout")]
     Timeout,
 
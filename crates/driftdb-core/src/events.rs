@@ -53,3 +53,28 @@ impl Event {
         }
     }
 }
+
+impl Event {
+    /// The event as a change record for consumers outside the database:
+    /// `type` is `insert`, `update` or `delete`, and the timestamp is
+    /// RFC 3339.
+    pub fn change_record(&self) -> Value {
+        let timestamp = self
+            .timestamp
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_else(|_| self.timestamp.to_string());
+        let kind = match self.event_type {
+            EventType::Insert => "insert",
+            EventType::Patch => "update",
+            EventType::SoftDelete => "delete",
+        };
+        serde_json::json!({
+            "sequence": self.sequence,
+            "timestamp": timestamp,
+            "type": kind,
+            "table": self.table_name,
+            "primary_key": self.primary_key,
+            "payload": self.payload,
+        })
+    }
+}
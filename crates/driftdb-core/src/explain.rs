@@ -36,7 +36,8 @@ pub enum ExplainFormat {
 /// There is intentionally no `buffers` field — PostgreSQL's `BUFFERS`
 /// reports buffer-pool hits / reads / writes, statistics DriftDB
 /// doesn't track yet. Adding the flag here would be performative;
-/// it'll come back when buffer-pool metrics exist.
+/// it'll come back when buffer-pool metrics exist. [`Self::parse_list`]
+/// still accepts it, since GUI clients send it with every plan request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExplainOptions {
     /// Output format.
@@ -66,6 +67,132 @@ impl Default for ExplainOptions {
     }
 }
 
+impl ExplainOptions {
+    /// Options from the parenthesised list of `EXPLAIN (option [value],
+    /// ...)`, without the parentheses. Boolean options take `TRUE`,
+    /// `FALSE`, `ON`, `OFF`, `1` or `0`, and are on when given alone.
+    /// `BUFFERS`, `SETTINGS`, `SUMMARY` and `WAL` are accepted and have
+    /// no effect.
+    pub fn parse_list(list: &str) -> Result<Self> {
+        let mut options = Self::default();
+        let mut timing = None;
+        for item in list.split(',') {
+            let mut words = item.split_whitespace();
+            let Some(name) = words.next() else {
+                return Err(DriftError::Parse(
+                    "syntax error in EXPLAIN option list".to_string(),
+                ));
+            };
+            let value = words.next();
+            if let Some(extra) = words.next() {
+                return Err(DriftError::Parse(format!(
+                    "syntax error at or near \"{}\" in EXPLAIN option list",
+                    extra
+                )));
+            }
+            let name = name.to_lowercase();
+            let flag = || -> Result<bool> {
+                match value.map(|v| v.to_lowercase()).as_deref() {
+                    None | Some("true") | Some("on") | Some("1") => Ok(true),
+                    Some("false") | Some("off") | Some("0") => Ok(false),
+                    Some(other) => Err(DriftError::InvalidQuery(format!(
+                        "{} requires a Boolean value, got \"{}\"",
+                        name.to_uppercase(),
+                        other
+                    ))),
+                }
+            };
+            match name.as_str() {
+                "analyze" => options.analyze = flag()?,
+                "verbose" => options.verbose = flag()?,
+                "costs" => options.costs = flag()?,
+                "timing" => timing = Some(flag()?),
+                "buffers" | "settings" | "summary" | "wal" => {
+                    flag()?;
+                }
+                "format" => {
+                    options.format = match value.map(str::to_lowercase).as_deref() {
+                        Some("text") => ExplainFormat::Text,
+                        Some("json") => ExplainFormat::Json,
+                        Some("yaml") => ExplainFormat::Yaml,
+                        Some(other) => {
+                            return Err(DriftError::InvalidQuery(format!(
+                                "unrecognized value for EXPLAIN option \"format\": \"{}\"",
+                                other
+                            )))
+                        }
+                        None => {
+                            return Err(DriftError::InvalidQuery(
+                                "EXPLAIN option FORMAT requires a value".to_string(),
+                            ))
+                        }
+                    }
+                }
+                _ => {
+                    return Err(DriftError::InvalidQuery(format!(
+                        "unrecognized EXPLAIN option \"{}\"",
+                        name
+                    )))
+                }
+            }
+        }
+        match timing {
+            Some(true) if !options.analyze => Err(DriftError::InvalidQuery(
+                "EXPLAIN option TIMING requires ANALYZE".to_string(),
+            )),
+            Some(timing) => Ok(Self { timing, ..options }),
+            None => Ok(Self {
+                timing: options.analyze,
+                ..options
+            }),
+        }
+    }
+}
+
+/// Split `EXPLAIN (option, ...) statement` into the statement without the
+/// option list and the list's options. `None` when `sql` isn't an EXPLAIN
+/// with an option list. A leading comment, such as a planner hint, stays
+/// in front of the statement.
+pub fn split_option_list(sql: &str) -> Option<Result<(String, ExplainOptions)>> {
+    let sql = sql.trim_start();
+    let comment_end = if sql.starts_with("/*") {
+        sql.find("*/")? + 2
+    } else {
+        0
+    };
+    let (comment, rest) = sql.split_at(comment_end);
+    let rest = rest.trim_start();
+    if !rest.get(..7)?.eq_ignore_ascii_case("EXPLAIN") {
+        return None;
+    }
+    let list = rest[7..].trim_start().strip_prefix('(')?;
+    // `EXPLAIN (SELECT ...)` is a parenthesised query, not options
+    let first = list
+        .trim_start()
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()?;
+    if ["select", "with", "values", "table"]
+        .iter()
+        .any(|keyword| first.eq_ignore_ascii_case(keyword))
+    {
+        return None;
+    }
+    let Some((list, statement)) = list.split_once(')') else {
+        return Some(Err(DriftError::Parse(
+            "unterminated EXPLAIN option list".to_string(),
+        )));
+    };
+    Some(ExplainOptions::parse_list(list).map(|options| {
+        let mut sql = comment.to_string();
+        if !sql.is_empty() {
+            sql.push(' ');
+        }
+        sql.push_str("EXPLAIN ");
+        sql.push_str(statement.trim_start());
+        (sql, options)
+    }))
+}
+
 /// Query execution plan with cost estimates
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExplainPlan {
@@ -88,15 +215,14 @@ pub struct ExplainPlan {
 impl ExplainPlan {
     /// Create a new explain plan from a plan node
     pub fn new(plan: PlanNode, planning_time_ms: f64) -> Self {
-        let cost = Self::extract_cost(&plan);
-        let estimated_rows = cost.rows;
+        let estimate = estimate(&plan);
 
         Self {
             plan,
             planning_time_ms,
             execution_time_ms: None,
-            total_cost: cost.total(),
-            estimated_rows,
+            total_cost: estimate.total,
+            estimated_rows: estimate.rows,
             actual_rows: None,
             metadata: HashMap::new(),
         }
@@ -118,6 +244,8 @@ impl ExplainPlan {
             PlanNode::Materialize { cost, .. } => *cost,
             PlanNode::Distinct { cost, .. } => *cost,
             PlanNode::SetOperation { cost, .. } => *cost,
+            PlanNode::Append { cost, .. } => *cost,
+            PlanNode::Gather { cost, .. } => *cost,
         }
     }
 
@@ -136,18 +264,20 @@ impl ExplainPlan {
     /// own line; children render indented with `->` connectors:
     ///
     /// ```text
-    /// Limit  (rows=5)
-    ///   ->  Sort  (rows=5)
+    /// Limit  (cost=2.02..2.07 rows=5 width=36)
+    ///   ->  Sort  (cost=2.02..3.02 rows=100 width=36)
     ///         Sort Key: name DESC
-    ///         ->  Seq Scan on users  (rows=100)
+    ///         ->  Seq Scan on users  (cost=0.00..1.01 rows=100 width=36)
     ///               Filter: id = 1
     /// ```
     ///
     /// This format is what psql / JDBC / psycopg2 clients expect when they
-    /// run plain `EXPLAIN`. Verbose mode (`EXPLAIN (VERBOSE)`) adds per-node
-    /// detail — Output column lists, full Filter / Join Cond predicate
-    /// text, Hash build-side, and so on. ANALYZE appends actual timing and
-    /// row counts as a trailing block.
+    /// run plain `EXPLAIN`, and what pgAdmin and DBeaver parse. Costs are
+    /// PostgreSQL's `startup..total` pair, rows and average row width in
+    /// bytes; `EXPLAIN (COSTS OFF)` leaves them out. Verbose mode
+    /// (`EXPLAIN (VERBOSE)`) adds per-node detail — Output column lists,
+    /// full Filter / Join Cond predicate text, Hash build-side, and so on.
+    /// ANALYZE appends actual timing and row counts as a trailing block.
     pub fn format_text(&self, options: &ExplainOptions) -> String {
         let mut output = String::new();
         self.format_node_text(&self.plan, 0, true, &mut output, options);
@@ -156,7 +286,7 @@ impl ExplainPlan {
             // ANALYZE block — matches what previous sessions documented for
             // EXPLAIN ANALYZE output.
             if let Some(exec_time) = self.execution_time_ms {
-                output.push_str(&format!("\nPlanning Time: {:.3} ms\n", self.planning_time_ms));
+                output.push_str(&format!("Planning Time: {:.3} ms\n", self.planning_time_ms));
                 output.push_str(&format!("Execution Time: {:.3} ms\n", exec_time));
                 if let Some(actual) = self.actual_rows {
                     let accuracy = if self.estimated_rows > 0.0 {
@@ -177,9 +307,9 @@ impl ExplainPlan {
 
     /// Format a single plan node and its children. PostgreSQL convention:
     /// the root has no connector, every other node is prefixed with `->`
-    /// at two-space indent per level. Verbose detail (predicate text,
-    /// sort keys, hash build-side, etc.) renders as additional indented
-    /// lines beneath the node.
+    /// and each level indents six more columns. Verbose detail (predicate
+    /// text, sort keys, hash build-side, etc.) renders as additional
+    /// indented lines beneath the node.
     #[allow(clippy::only_used_in_recursion)]
     fn format_node_text(
         &self,
@@ -189,28 +319,19 @@ impl ExplainPlan {
         output: &mut String,
         options: &ExplainOptions,
     ) {
-        let indent = "  ".repeat(depth);
         let prefix = if is_root {
             String::new()
         } else {
-            format!("{}->  ", indent)
-        };
-        let detail_indent = if is_root {
-            "  ".to_string()
-        } else {
-            format!("{}    ", indent)
+            format!("{}->  ", " ".repeat(6 * depth - 4))
         };
+        let detail_indent = " ".repeat(6 * depth + 2);
 
         match node {
             PlanNode::TableScan {
-                table,
-                predicates,
-                cost,
+                table, predicates, ..
             } => {
                 output.push_str(&format!("{}Seq Scan on {}", prefix, table));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if !predicates.is_empty() {
                     let text = render_predicates(predicates);
@@ -222,12 +343,10 @@ impl ExplainPlan {
                 table,
                 index,
                 predicates,
-                cost,
+                ..
             } => {
                 output.push_str(&format!("{}Index Scan using {} on {}", prefix, index, table));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if !predicates.is_empty() {
                     let text = render_predicates(predicates);
@@ -240,7 +359,7 @@ impl ExplainPlan {
                 right,
                 condition,
                 join_type,
-                cost,
+                ..
             } => {
                 let label = match join_type {
                     crate::optimizer::JoinType::Inner => "Nested Loop",
@@ -248,9 +367,7 @@ impl ExplainPlan {
                     crate::optimizer::JoinType::FullOuter => "Nested Loop Full Join",
                 };
                 output.push_str(&format!("{}{}", prefix, label));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 output.push_str(&format!(
                     "{}Join Cond: {}\n",
@@ -267,7 +384,7 @@ impl ExplainPlan {
                 condition,
                 build_side,
                 join_type,
-                cost,
+                ..
             } => {
                 let label = match join_type {
                     crate::optimizer::JoinType::Inner => "Hash Join",
@@ -275,9 +392,7 @@ impl ExplainPlan {
                     crate::optimizer::JoinType::FullOuter => "Hash Full Join",
                 };
                 output.push_str(&format!("{}{}", prefix, label));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 output.push_str(&format!(
                     "{}Hash Cond: {}\n",
@@ -295,12 +410,10 @@ impl ExplainPlan {
                 left,
                 right,
                 condition,
-                cost,
+                ..
             } => {
                 output.push_str(&format!("{}Merge Join", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 output.push_str(&format!(
                     "{}Merge Cond: {}\n",
@@ -311,17 +424,28 @@ impl ExplainPlan {
                 self.format_node_text(right, depth + 1, false, output, options);
             }
 
-            PlanNode::Sort { input, keys, cost } => {
+            PlanNode::Sort { input, keys, .. } => {
                 output.push_str(&format!("{}Sort", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if !keys.is_empty() {
                     let key_strs: Vec<String> = keys
                         .iter()
                         .map(|k| {
-                            format!("{} {}", k.column, if k.ascending { "ASC" } else { "DESC" })
+                            let mut key = k.column.clone();
+                            if k.collation != Collation::default() {
+                                key.push_str(&format!(" COLLATE \"{}\"", k.collation.name()));
+                            }
+                            key.push_str(if k.ascending { " ASC" } else { " DESC" });
+                            // Like PostgreSQL, only a non-default NULLs placement is shown
+                            if k.nulls_first == k.ascending {
+                                key.push_str(if k.nulls_first {
+                                    " NULLS FIRST"
+                                } else {
+                                    " NULLS LAST"
+                                });
+                            }
+                            key
                         })
                         .collect();
                     output.push_str(&format!(
@@ -337,7 +461,7 @@ impl ExplainPlan {
                 input,
                 group_by,
                 aggregates,
-                cost,
+                ..
             } => {
                 let label = if group_by.is_empty() {
                     "Aggregate"
@@ -345,9 +469,7 @@ impl ExplainPlan {
                     "GroupAggregate"
                 };
                 output.push_str(&format!("{}{}", prefix, label));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if !group_by.is_empty() {
                     output.push_str(&format!(
@@ -369,14 +491,10 @@ impl ExplainPlan {
             }
 
             PlanNode::Filter {
-                input,
-                predicates,
-                cost,
+                input, predicates, ..
             } => {
                 output.push_str(&format!("{}Filter", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if !predicates.is_empty() {
                     output.push_str(&format!(
@@ -388,36 +506,28 @@ impl ExplainPlan {
                 self.format_node_text(input, depth + 1, false, output, options);
             }
 
-            PlanNode::Project {
-                input,
-                columns,
-                cost,
-            } => {
-                output.push_str(&format!("{}Project", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
+            // PostgreSQL has no projection node: the columns a node returns
+            // show as its `Output:` line in verbose mode
+            PlanNode::Project { input, columns, .. } => {
+                let mut rendered = String::new();
+                self.format_node_text(input, depth, is_root, &mut rendered, options);
+                match rendered.split_once('\n') {
+                    Some((first, rest)) if options.verbose && !columns.is_empty() => {
+                        output.push_str(first);
+                        output.push_str(&format!(
+                            "\n{}Output: {}\n",
+                            detail_indent,
+                            columns.join(", ")
+                        ));
+                        output.push_str(rest);
+                    }
+                    _ => output.push_str(&rendered),
                 }
-                output.push('\n');
-                if options.verbose && !columns.is_empty() {
-                    output.push_str(&format!(
-                        "{}Output: {}\n",
-                        detail_indent,
-                        columns.join(", ")
-                    ));
-                }
-                self.format_node_text(input, depth + 1, false, output, options);
             }
 
-            PlanNode::Limit {
-                input,
-                limit,
-                offset,
-                cost,
-            } => {
+            PlanNode::Limit { input, offset, .. } => {
                 output.push_str(&format!("{}Limit", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (count={}, rows={:.0})", limit, cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if *offset > 0 {
                     output.push_str(&format!("{}Offset: {}\n", detail_indent, offset));
@@ -425,31 +535,41 @@ impl ExplainPlan {
                 self.format_node_text(input, depth + 1, false, output, options);
             }
 
-            PlanNode::Materialize { input, cost } => {
-                output.push_str(&format!("{}Materialize", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
+            PlanNode::Append { inputs, .. } => {
+                output.push_str(&format!("{}Append", prefix));
+                push_estimate(node, output, options);
+                output.push('\n');
+                for input in inputs {
+                    self.format_node_text(input, depth + 1, false, output, options);
                 }
+            }
+
+            PlanNode::Gather { input, workers, .. } => {
+                output.push_str(&format!("{}Gather", prefix));
+                push_estimate(node, output, options);
+                output.push('\n');
+                output.push_str(&format!("{}Workers Planned: {}\n", detail_indent, workers));
+                let mut scan = String::new();
+                self.format_node_text(input, depth + 1, false, &mut scan, options);
+                if matches!(**input, PlanNode::TableScan { .. }) {
+                    scan = scan.replacen("Seq Scan", "Parallel Seq Scan", 1);
+                }
+                output.push_str(&scan);
+            }
+
+            PlanNode::Materialize { input, .. } => {
+                output.push_str(&format!("{}Materialize", prefix));
+                push_estimate(node, output, options);
                 output.push('\n');
                 self.format_node_text(input, depth + 1, false, output, options);
             }
 
-            PlanNode::Distinct {
-                input,
-                columns,
-                cost,
-            } => {
+            PlanNode::Distinct { input, columns, .. } => {
                 output.push_str(&format!("{}Unique", prefix));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 if options.verbose && !columns.is_empty() {
-                    output.push_str(&format!(
-                        "{}Keys: {}\n",
-                        detail_indent,
-                        columns.join(", ")
-                    ));
+                    output.push_str(&format!("{}Keys: {}\n", detail_indent, columns.join(", ")));
                 }
                 self.format_node_text(input, depth + 1, false, output, options);
             }
@@ -458,12 +578,10 @@ impl ExplainPlan {
                 left,
                 right,
                 operation,
-                cost,
+                ..
             } => {
                 output.push_str(&format!("{}{}", prefix, operation));
-                if options.costs {
-                    output.push_str(&format!("  (rows={:.0})", cost.rows));
-                }
+                push_estimate(node, output, options);
                 output.push('\n');
                 self.format_node_text(left, depth + 1, false, output, options);
                 self.format_node_text(right, depth + 1, false, output, options);
@@ -515,6 +633,15 @@ fn render_predicate_value(value: &crate::optimizer::PredicateValue) -> String {
     match value {
         PredicateValue::Constant(v) => match v {
             serde_json::Value::String(s) => format!("'{}'", s),
+            // An IN list
+            serde_json::Value::Array(items) => format!(
+                "({})",
+                items
+                    .iter()
+                    .map(|item| render_predicate_value(&PredicateValue::Constant(item.clone())))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             other => other.to_string(),
         },
         PredicateValue::Column(c) => c.clone(),
@@ -553,6 +680,130 @@ fn render_join_condition(c: &crate::optimizer::JoinCondition) -> String {
     format!("{} {} {}", c.left_col, render_op(&c.op), c.right_col)
 }
 
+/// A node's estimates as PostgreSQL shows them
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    /// Cost before the first row comes out
+    startup: f64,
+    /// Cost of returning every row, including the node's inputs
+    total: f64,
+    rows: f64,
+    /// Average row width in bytes
+    width: f64,
+}
+
+impl fmt::Display for Estimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  (cost={:.2}..{:.2} rows={:.0} width={:.0})",
+            self.startup, self.total, self.rows, self.width
+        )
+    }
+}
+
+/// Per-row CPU cost, as the cost model charges it
+const CPU_TUPLE_COST: f64 = 0.01;
+
+/// `node`'s estimates. Each node's own cost only covers its own work, so
+/// totals add up the inputs' totals; a node that reads all its input
+/// before returning a row (a sort, an aggregate, a hash join's hashed
+/// side) starts where that input finishes.
+fn estimate(node: &PlanNode) -> Estimate {
+    let cost = ExplainPlan::extract_cost(node);
+    let own = cost.total();
+    let rows = cost.rows;
+    match node {
+        PlanNode::TableScan { .. } | PlanNode::IndexScan { .. } => Estimate {
+            startup: 0.0,
+            total: own,
+            rows,
+            width: cost.size / rows.max(1.0),
+        },
+        PlanNode::Sort { input, .. } | PlanNode::Aggregate { input, .. } => {
+            let input = estimate(input);
+            let startup = input.total + own;
+            Estimate {
+                startup,
+                total: startup + rows * CPU_TUPLE_COST,
+                rows,
+                width: input.width,
+            }
+        }
+        PlanNode::Limit { input, limit, .. } => {
+            let input = estimate(input);
+            let fraction = if input.rows > 0.0 {
+                (*limit as f64 / input.rows).min(1.0)
+            } else {
+                1.0
+            };
+            Estimate {
+                startup: input.startup,
+                total: input.startup + (input.total - input.startup) * fraction,
+                rows,
+                width: input.width,
+            }
+        }
+        PlanNode::HashJoin { left, right, .. } => {
+            let (left, right) = (estimate(left), estimate(right));
+            Estimate {
+                startup: right.total + left.startup,
+                total: left.total + right.total + own,
+                rows,
+                width: left.width + right.width,
+            }
+        }
+        PlanNode::NestedLoopJoin { left, right, .. }
+        | PlanNode::SortMergeJoin { left, right, .. } => {
+            let (left, right) = (estimate(left), estimate(right));
+            Estimate {
+                startup: left.startup + right.startup,
+                total: left.total + right.total + own,
+                rows,
+                width: left.width + right.width,
+            }
+        }
+        PlanNode::SetOperation { left, right, .. } => {
+            let (left, right) = (estimate(left), estimate(right));
+            Estimate {
+                startup: left.startup,
+                total: left.total + right.total + own,
+                rows,
+                width: left.width.max(right.width),
+            }
+        }
+        PlanNode::Append { inputs, .. } => {
+            let inputs: Vec<Estimate> = inputs.iter().map(estimate).collect();
+            Estimate {
+                startup: inputs.first().map_or(0.0, |input| input.startup),
+                total: inputs.iter().map(|input| input.total).sum::<f64>() + own,
+                rows,
+                width: inputs.iter().map(|input| input.width).fold(0.0, f64::max),
+            }
+        }
+        PlanNode::Filter { input, .. }
+        | PlanNode::Project { input, .. }
+        | PlanNode::Materialize { input, .. }
+        | PlanNode::Distinct { input, .. }
+        | PlanNode::Gather { input, .. } => {
+            let input = estimate(input);
+            Estimate {
+                startup: input.startup,
+                total: input.total + own,
+                rows,
+                width: input.width,
+            }
+        }
+    }
+}
+
+/// Append `node`'s estimates to its line, unless `COSTS OFF`
+fn push_estimate(node: &PlanNode, output: &mut String, options: &ExplainOptions) {
+    if options.costs {
+        output.push_str(&estimate(node).to_string());
+    }
+}
+
 /// EXPLAIN executor
 pub struct ExplainExecutor;
 
@@ -610,8 +861,10 @@ use sqlparser::ast::{
 };
 
 use crate::optimizer::{
-    AggregateFunc, ComparisonOp, JoinCondition, Predicate, PredicateValue, SortKey,
+    AggregateFunc, Collation, ComparisonOp, JoinCondition, PlanStep, Predicate, PredicateValue,
+    SortKey,
 };
+use crate::query::{Query, WhereCondition};
 use crate::engine::Engine;
 use crate::errors::DriftError;
 
@@ -672,7 +925,11 @@ fn build_query_plan(engine: &Engine, query: &SqlQuery) -> Result<PlanNode> {
 
     if let Some(order_by) = &query.order_by {
         if !order_by.exprs.is_empty() {
-            let keys: Vec<SortKey> = order_by.exprs.iter().map(sort_key_from_expr).collect();
+            let keys = order_by
+                .exprs
+                .iter()
+                .map(sort_key_from_expr)
+                .collect::<Result<Vec<_>>>()?;
             let rows = plan_rows(&root);
             root = PlanNode::Sort {
                 input: Box::new(root),
@@ -797,6 +1054,7 @@ fn build_select_plan(engine: &Engine, select: &Select) -> Result<PlanNode> {
         } = root
         {
             predicates.extend(preds);
+            root = choose_access_path(engine, root);
         } else {
             let rows = plan_rows(&root);
             root = PlanNode::Filter {
@@ -805,6 +1063,11 @@ fn build_select_plan(engine: &Engine, select: &Select) -> Result<PlanNode> {
                 cost: scan_cost(rows),
             };
         }
+    } else if matches!(&root, PlanNode::TableScan { table, .. }
+        if engine.partition_scheme(table.split_whitespace().next().unwrap_or(table)).is_some())
+    {
+        // Without a WHERE a partitioned table still scans each partition
+        root = choose_access_path(engine, root);
     }
 
     // GROUP BY / aggregates.
@@ -859,7 +1122,87 @@ fn build_select_plan(engine: &Engine, select: &Select) -> Result<PlanNode> {
         };
     }
 
-    Ok(root)
+    // The select list, for `EXPLAIN (VERBOSE)`'s Output line. Projecting
+    // costs nothing on top of its input.
+    let columns = output_columns(engine, select);
+    let rows = plan_rows(&root);
+    Ok(PlanNode::Project {
+        input: Box::new(root),
+        columns,
+        cost: Cost {
+            rows: rows as f64,
+            ..Default::default()
+        },
+    })
+}
+
+/// The columns `select` returns, with `*` spelled out from the tables'
+/// schemas. Columns are qualified by relation when there's more than one.
+fn output_columns(engine: &Engine, select: &Select) -> Vec<String> {
+    let relations: Vec<(String, Option<String>)> = select
+        .from
+        .iter()
+        .flat_map(|from| {
+            std::iter::once(&from.relation).chain(from.joins.iter().map(|j| &j.relation))
+        })
+        .filter_map(|relation| match relation {
+            TableFactor::Table { name, alias, .. } => Some((
+                name.to_string(),
+                alias.as_ref().map(|a| a.name.value.clone()),
+            )),
+            _ => None,
+        })
+        .collect();
+    let qualify = relations.len() > 1;
+    let columns_of = |table: &str, alias: &Option<String>| -> Option<Vec<String>> {
+        let schema = engine.table_schema(table).ok()?;
+        let label = alias.as_deref().unwrap_or(table);
+        Some(
+            schema
+                .columns
+                .iter()
+                .map(|column| {
+                    if qualify {
+                        format!("{}.{}", label, column.name)
+                    } else {
+                        column.name.clone()
+                    }
+                })
+                .collect(),
+        )
+    };
+
+    let mut columns = Vec::new();
+    for item in &select.projection {
+        match item {
+            SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                columns.push(format_sql_expr(expr))
+            }
+            SelectItem::Wildcard(_) => {
+                let expanded: Option<Vec<Vec<String>>> = relations
+                    .iter()
+                    .map(|(table, alias)| columns_of(table.as_str(), alias))
+                    .collect();
+                match expanded {
+                    Some(expanded) if !relations.is_empty() => {
+                        columns.extend(expanded.into_iter().flatten())
+                    }
+                    _ => columns.push("*".to_string()),
+                }
+            }
+            SelectItem::QualifiedWildcard(name, _) => {
+                let name = name.to_string();
+                let relation = relations
+                    .iter()
+                    .find(|(table, alias)| alias.as_deref().unwrap_or(table) == name);
+                match relation.and_then(|(table, alias)| columns_of(table.as_str(), alias)) {
+                    Some(expanded) => columns.extend(expanded),
+                    None => columns.push(format!("{}.*", name)),
+                }
+            }
+        }
+    }
+    columns
 }
 
 fn build_table_factor_plan(engine: &Engine, tf: &TableFactor) -> Result<PlanNode> {
@@ -868,14 +1211,159 @@ fn build_table_factor_plan(engine: &Engine, tf: &TableFactor) -> Result<PlanNode
         TableFactor::Table { name, .. } => name.to_string(),
         _ => label.clone(),
     };
-    let rows = engine.get_table_data(&lookup_name).map(|d| d.len()).unwrap_or(0);
     Ok(PlanNode::TableScan {
         table: label,
         predicates: vec![],
-        cost: scan_cost(rows),
+        cost: table_scan_cost(engine, &lookup_name),
     })
 }
 
+fn table_scan_cost(engine: &Engine, table: &str) -> Cost {
+    let rows = engine.get_table_data(table).map(|d| d.len()).unwrap_or(0);
+    let mut cost = scan_cost(rows);
+    if let Ok(schema) = engine.table_schema(table) {
+        cost.size = rows.max(1) as f64 * row_width(&schema);
+    }
+    cost
+}
+
+/// Average width in bytes of a row of `schema`, from its column types the
+/// way PostgreSQL estimates it for a table without statistics
+fn row_width(schema: &crate::schema::Schema) -> f64 {
+    schema
+        .columns
+        .iter()
+        .map(|column| {
+            let ty = column.col_type.to_uppercase();
+            match ty.split(['(', ' ']).next().unwrap_or_default() {
+                "BOOL" | "BOOLEAN" => 1.0,
+                "SMALLINT" | "INT2" => 2.0,
+                "INT" | "INTEGER" | "INT4" | "SERIAL" | "REAL" | "FLOAT4" | "DATE" => 4.0,
+                "BIGINT" | "INT8" | "BIGSERIAL" | "DOUBLE" | "FLOAT" | "FLOAT8" | "TIME"
+                | "TIMESTAMP" | "TIMESTAMPTZ" => 8.0,
+                "UUID" => 16.0,
+                // Variable-length types: PostgreSQL's default guess
+                _ => 32.0,
+            }
+        })
+        .sum()
+}
+
+/// Turn a single-table scan into an index scan when the optimizer would
+/// use an index for its predicates, under a `Gather` when it would split
+/// the scan across workers, or into an `Append` of the partitions left
+/// after pruning, so EXPLAIN shows the access path the query actually
+/// takes. Goes through the optimizer's plan cache, which
+/// re-plans after any schema or statistics change.
+fn choose_access_path(engine: &Engine, scan: PlanNode) -> PlanNode {
+    let PlanNode::TableScan {
+        table,
+        predicates,
+        cost,
+    } = scan
+    else {
+        return scan;
+    };
+
+    let conditions: Vec<WhereCondition> = predicates
+        .iter()
+        .filter_map(|p| {
+            let operator = match p.op {
+                ComparisonOp::Eq => "=",
+                ComparisonOp::Lt => "<",
+                ComparisonOp::Le => "<=",
+                ComparisonOp::Gt => ">",
+                ComparisonOp::Ge => ">=",
+                ComparisonOp::In => "IN",
+                ComparisonOp::Like => "LIKE",
+                _ => return None,
+            };
+            let PredicateValue::Constant(value) = &p.value else {
+                return None;
+            };
+            Some(WhereCondition {
+                column: p.column.clone(),
+                operator: operator.to_string(),
+                value: value.clone(),
+            })
+        })
+        .collect();
+    // The scan label carries the alias, if any, after the table name
+    let name = table.split_whitespace().next().unwrap_or(&table);
+    if let Some(partitions) = engine.partitions_to_scan(name, &conditions) {
+        let alias = &table[name.len()..];
+        let mut inputs: Vec<PlanNode> = partitions
+            .iter()
+            .map(|partition| {
+                let scan = PlanNode::TableScan {
+                    table: format!("{}{}", partition, alias),
+                    predicates: predicates.clone(),
+                    cost: table_scan_cost(engine, partition),
+                };
+                choose_access_path(engine, scan)
+            })
+            .collect();
+        if inputs.len() == 1 {
+            return inputs.remove(0);
+        }
+        let cost = inputs
+            .iter()
+            .map(ExplainPlan::extract_cost)
+            // The inputs' costs are added up in the estimate; the node
+            // itself only carries their combined output
+            .fold(Cost::default(), |total, cost| Cost {
+                rows: total.rows + cost.rows,
+                size: total.size + cost.size,
+                ..Default::default()
+            });
+        return PlanNode::Append { inputs, cost };
+    }
+    let query = Query::Select {
+        table: name.to_string(),
+        conditions,
+        as_of: None,
+        limit: None,
+    };
+    let steps = engine
+        .query_optimizer()
+        .optimize(&query)
+        .map(|plan| plan.steps)
+        .unwrap_or_default();
+    let index = steps.iter().find_map(|step| match step {
+        PlanStep::IndexLookup { index, .. } | PlanStep::IndexScan { index, .. } => {
+            Some(index.clone())
+        }
+        _ => None,
+    });
+    let workers = steps.iter().find_map(|step| match step {
+        PlanStep::ParallelScan { workers, .. } => Some(*workers),
+        _ => None,
+    });
+
+    match (index, workers) {
+        (Some(index), _) => PlanNode::IndexScan {
+            table,
+            index,
+            predicates,
+            cost,
+        },
+        (None, Some(workers)) => PlanNode::Gather {
+            input: Box::new(PlanNode::TableScan {
+                table,
+                predicates,
+                cost,
+            }),
+            workers,
+            cost,
+        },
+        (None, None) => PlanNode::TableScan {
+            table,
+            predicates,
+            cost,
+        },
+    }
+}
+
 fn build_join_condition(op: &JoinOperator) -> JoinCondition {
     let constraint = match op {
         JoinOperator::Inner(c)
@@ -984,10 +1472,74 @@ fn flatten_and(expr: &SqlExpr, out: &mut Vec<Predicate>) {
         flatten_and(right, out);
         return;
     }
+    // `x BETWEEN a AND b` is `x >= a AND x <= b`
+    if let SqlExpr::Between {
+        expr: inner,
+        negated: false,
+        low,
+        high,
+    } = expr
+    {
+        if let (Some(col), SqlExpr::Value(low), SqlExpr::Value(high)) =
+            (binop_column_name(inner), low.as_ref(), high.as_ref())
+        {
+            for (op, bound) in [(ComparisonOp::Ge, low), (ComparisonOp::Le, high)] {
+                out.push(Predicate {
+                    column: col.clone(),
+                    op,
+                    value: PredicateValue::Constant(sql_value_to_json(bound)),
+                    selectivity: 0.5,
+                });
+            }
+            return;
+        }
+    }
     out.push(predicate_from_expr(expr));
 }
 
 fn predicate_from_expr(expr: &SqlExpr) -> Predicate {
+    if let SqlExpr::Like {
+        negated: false,
+        expr: inner,
+        pattern,
+        escape_char: None,
+        ..
+    } = expr
+    {
+        if let (Some(col), SqlExpr::Value(v)) = (binop_column_name(inner), pattern.as_ref()) {
+            return Predicate {
+                column: col,
+                op: ComparisonOp::Like,
+                value: PredicateValue::Constant(sql_value_to_json(v)),
+                selectivity: 0.5,
+            };
+        }
+    }
+    // `x IN (...)` over literals; a NULL in the list never matches
+    if let SqlExpr::InList {
+        expr: inner,
+        list,
+        negated: false,
+    } = expr
+    {
+        let values: Option<Vec<serde_json::Value>> = list
+            .iter()
+            .map(|item| match item {
+                SqlExpr::Value(v) => Some(sql_value_to_json(v)),
+                _ => None,
+            })
+            .collect();
+        if let (Some(col), Some(values)) = (binop_column_name(inner), values) {
+            return Predicate {
+                column: col,
+                op: ComparisonOp::In,
+                value: PredicateValue::Constant(serde_json::Value::Array(
+                    values.into_iter().filter(|v| !v.is_null()).collect(),
+                )),
+                selectivity: 0.5,
+            };
+        }
+    }
     if let SqlExpr::BinaryOp { left, op, right } = expr {
         if let (Some(col), Some(cop)) = (binop_column_name(left), binary_op_to_comparison(op)) {
             // Right-hand side: try to fit as a constant or column reference.
@@ -1022,9 +1574,11 @@ fn predicate_from_expr(expr: &SqlExpr) -> Predicate {
 fn sql_value_to_json(v: &sqlparser::ast::Value) -> serde_json::Value {
     use sqlparser::ast::Value;
     match v {
-        Value::Number(n, _) => n.parse::<f64>().map(|f| serde_json::json!(f)).unwrap_or_else(
-            |_| serde_json::Value::String(n.clone()),
-        ),
+        Value::Number(n, _) => n
+            .parse::<i64>()
+            .map(|i| serde_json::json!(i))
+            .or_else(|_| n.parse::<f64>().map(|f| serde_json::json!(f)))
+            .unwrap_or_else(|_| serde_json::Value::String(n.clone())),
         Value::SingleQuotedString(s) | Value::DoubleQuotedString(s) => {
             serde_json::Value::String(s.clone())
         }
@@ -1034,11 +1588,14 @@ fn sql_value_to_json(v: &sqlparser::ast::Value) -> serde_json::Value {
     }
 }
 
-fn sort_key_from_expr(o: &OrderByExpr) -> SortKey {
-    SortKey {
-        column: format_sql_expr(&o.expr),
-        ascending: o.asc.unwrap_or(true),
-    }
+fn sort_key_from_expr(o: &OrderByExpr) -> Result<SortKey> {
+    let (expr, collation) = crate::sql_bridge::strip_collation(&o.expr)?;
+    Ok(SortKey::new(
+        format_sql_expr(expr),
+        o.asc.unwrap_or(true),
+        o.nulls_first,
+        collation,
+    ))
 }
 
 fn table_factor_label(tf: &TableFactor) -> String {
@@ -1077,6 +1634,32 @@ fn format_sql_expr(expr: &SqlExpr) -> String {
         SqlExpr::Function(f) => f.to_string(),
         SqlExpr::IsNull(inner) => format!("{} IS NULL", format_sql_expr(inner)),
         SqlExpr::IsNotNull(inner) => format!("{} IS NOT NULL", format_sql_expr(inner)),
+        SqlExpr::Like { .. } | SqlExpr::ILike { .. } => expr.to_string(),
+        SqlExpr::InList {
+            expr,
+            list,
+            negated,
+        } => format!(
+            "{} {}IN ({})",
+            format_sql_expr(expr),
+            if *negated { "NOT " } else { "" },
+            list.iter()
+                .map(format_sql_expr)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        SqlExpr::Between {
+            expr,
+            negated,
+            low,
+            high,
+        } => format!(
+            "{} {}BETWEEN {} AND {}",
+            format_sql_expr(expr),
+            if *negated { "NOT " } else { "" },
+            format_sql_expr(low),
+            format_sql_expr(high)
+        ),
         _ => format!("{:?}", expr),
     }
 }
@@ -1095,7 +1678,9 @@ fn plan_rows(node: &PlanNode) -> usize {
         | PlanNode::Limit { cost, .. }
         | PlanNode::Materialize { cost, .. }
         | PlanNode::Distinct { cost, .. }
-        | PlanNode::SetOperation { cost, .. } => cost.rows as usize,
+        | PlanNode::SetOperation { cost, .. }
+        | PlanNode::Append { cost, .. }
+        | PlanNode::Gather { cost, .. } => cost.rows as usize,
     }
 }
 
@@ -1128,7 +1713,11 @@ mod tests {
         let explain = ExplainExecutor::explain(plan, Duration::from_millis(5));
         let output = explain.format_text(&ExplainOptions::default());
         assert!(output.contains("Seq Scan on users"), "got: {}", output);
-        assert!(output.contains("rows=10000"));
+        assert!(
+            output.contains("  (cost=0.00..101.00 rows=10000 width=100)"),
+            "got: {}",
+            output
+        );
     }
 
     #[test]
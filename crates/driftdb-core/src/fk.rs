@@ -25,13 +25,14 @@
 use std::collections::HashMap;
 
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::engine::Engine;
 use crate::errors::{DriftError, Result};
 
 /// A single foreign-key column reference.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ForeignKey {
     /// Column in the *child* table that holds the FK value.
     pub column: String,
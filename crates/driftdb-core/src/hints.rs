@@ -0,0 +1,378 @@
+//! Planner hints
+//!
+//! A statement may start with a hint comment in the style of PostgreSQL's
+//! pg_hint_plan, forcing the planner's hand for that one statement:
+//!
+//! ```sql
+//! /*+ IndexScan(orders idx_orders_created) HashJoin(o c) */
+//! SELECT * FROM orders o JOIN customers c ON o.customer_id = c.id
+//! WHERE o.created > '2024-01-01'
+//! ```
+//!
+//! - `SeqScan(t)` scans the whole table.
+//! - `IndexScan(t [index ...])` reads `t` through one of the named indexes,
+//!   or any index when none is named. Indexes are named by column or as
+//!   `idx_<table>_<column>`, the name `\d` reports.
+//! - `HashJoin(a b)` and `NestLoop(a b)` pick the algorithm for the join
+//!   between `a` and `b`.
+//!
+//! Relations are named by table or alias. A hint that can't be followed —
+//! a relation the statement doesn't read, an index that doesn't exist or
+//! that no condition can use, a join that isn't planned — is dropped with
+//! a notice, and the planner chooses as it would have without it. Notices
+//! end up in [`SessionContext::notices`](crate::sql_bridge::SessionContext).
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// One hint from a statement's hint comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    SeqScan {
+        relation: String,
+    },
+    IndexScan {
+        relation: String,
+        indexes: Vec<String>,
+    },
+    HashJoin {
+        relations: Vec<String>,
+    },
+    NestLoop {
+        relations: Vec<String>,
+    },
+}
+
+/// How a table should be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanHint {
+    Seq,
+    /// Through one of these indexes; any index when empty
+    Index(Vec<String>),
+}
+
+/// How two tables should be joined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinHint {
+    Hash,
+    NestLoop,
+}
+
+impl Hint {
+    fn relations(&self) -> &[String] {
+        match self {
+            Hint::SeqScan { relation } | Hint::IndexScan { relation, .. } => {
+                std::slice::from_ref(relation)
+            }
+            Hint::HashJoin { relations } | Hint::NestLoop { relations } => relations,
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::SeqScan { relation } => write!(f, "SeqScan({})", relation),
+            Hint::IndexScan { relation, indexes } if indexes.is_empty() => {
+                write!(f, "IndexScan({})", relation)
+            }
+            Hint::IndexScan { relation, indexes } => {
+                write!(f, "IndexScan({} {})", relation, indexes.join(" "))
+            }
+            Hint::HashJoin { relations } => write!(f, "HashJoin({})", relations.join(" ")),
+            Hint::NestLoop { relations } => write!(f, "NestLoop({})", relations.join(" ")),
+        }
+    }
+}
+
+/// The hints in `sql`'s leading `/*+ ... */` comment, and a notice for
+/// each one that couldn't be parsed
+pub fn parse(sql: &str) -> (Vec<Hint>, Vec<String>) {
+    let mut hints = Vec::new();
+    let mut problems = Vec::new();
+    let Some(body) = sql
+        .trim_start()
+        .strip_prefix("/*+")
+        .and_then(|rest| rest.split_once("*/"))
+        .map(|(body, _)| body)
+    else {
+        return (hints, problems);
+    };
+
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let Some((name, after)) = rest.split_once('(') else {
+            problems.push(format!("hint syntax error at \"{}\"", rest));
+            break;
+        };
+        let Some((args, after)) = after.split_once(')') else {
+            problems.push(format!("hint syntax error at \"{}\"", rest));
+            break;
+        };
+        rest = after.trim_start();
+
+        let name = name.trim();
+        let args: Vec<String> = args
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|arg| !arg.is_empty())
+            .map(identifier)
+            .collect();
+        let hint = match name.to_ascii_lowercase().as_str() {
+            "seqscan" if args.len() == 1 => Hint::SeqScan {
+                relation: args[0].clone(),
+            },
+            "indexscan" if !args.is_empty() => Hint::IndexScan {
+                relation: args[0].clone(),
+                indexes: args[1..].to_vec(),
+            },
+            "hashjoin" if args.len() == 2 => Hint::HashJoin { relations: args },
+            "nestloop" | "nestedloop" if args.len() == 2 => Hint::NestLoop { relations: args },
+            "seqscan" | "indexscan" | "hashjoin" | "nestloop" | "nestedloop" => {
+                problems.push(format!(
+                    "hint {}({}) ignored: wrong number of arguments",
+                    name,
+                    args.join(" ")
+                ));
+                continue;
+            }
+            _ => {
+                problems.push(format!("unrecognized hint \"{}\" ignored", name));
+                continue;
+            }
+        };
+        hints.push(hint);
+    }
+    (hints, problems)
+}
+
+/// `"Name"` → `Name`, `Name` → `name`, as for SQL identifiers
+fn identifier(arg: &str) -> String {
+    arg.strip_prefix('"')
+        .and_then(|a| a.strip_suffix('"'))
+        .map(str::to_string)
+        .unwrap_or_else(|| arg.to_lowercase())
+}
+
+/// The hints of the statement being executed
+struct ActiveHints {
+    hints: Vec<Hint>,
+    /// Whether each hint was followed or dropped with a notice
+    settled: Vec<bool>,
+    /// Table for each name a relation can be hinted by: its alias, its
+    /// name as written and its engine name
+    relations: HashMap<String, String>,
+}
+
+impl ActiveHints {
+    fn table_of(&self, name: &str) -> Option<&str> {
+        self.relations.get(name).map(String::as_str)
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<ActiveHints>> = const { RefCell::new(None) };
+    static NOTICES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Installs a statement's hints while it executes. Statements without a
+/// hint comment, such as view bodies run for a hinted query, keep the
+/// hints already in force.
+pub(crate) struct HintScope {
+    previous: Option<Option<ActiveHints>>,
+}
+
+impl HintScope {
+    /// `relations` pairs each name the statement reads a table by with
+    /// the table's engine name; `indexed_columns` gives the columns a
+    /// table has indexes on
+    pub(crate) fn enter(
+        sql: &str,
+        relations: impl FnOnce() -> Vec<(String, String)>,
+        indexed_columns: impl Fn(&str) -> HashSet<String>,
+    ) -> Self {
+        let (hints, problems) = parse(sql);
+        problems.into_iter().for_each(notice);
+        if hints.is_empty() {
+            return Self { previous: None };
+        }
+
+        let relations: HashMap<String, String> = relations().into_iter().collect();
+        let hints: Vec<Hint> = hints
+            .into_iter()
+            .filter(|hint| {
+                let missing = hint
+                    .relations()
+                    .iter()
+                    .find(|name| !relations.contains_key(name.as_str()));
+                if let Some(name) = missing {
+                    notice(format!(
+                        "hint {} ignored: relation \"{}\" is not in the query",
+                        hint, name
+                    ));
+                    return false;
+                }
+                // Named indexes are checked now, as the planner only looks
+                // at them if it gets as far as choosing how to scan
+                if let Hint::IndexScan { relation, indexes } = hint {
+                    let table = &relations[relation];
+                    let columns = indexed_columns(table);
+                    let (_, bare_table) = crate::search_path::split_storage_name(table);
+                    let prefix = format!("idx_{}_", bare_table);
+                    let unknown = indexes
+                        .iter()
+                        .find(|name| !columns.contains(name.strip_prefix(&prefix).unwrap_or(name)));
+                    if let Some(unknown) = unknown {
+                        notice(format!(
+                            "hint {} ignored: table \"{}\" has no index \"{}\"",
+                            hint, table, unknown
+                        ));
+                        return false;
+                    }
+                }
+                true
+            })
+            .collect();
+        let active = ActiveHints {
+            settled: vec![false; hints.len()],
+            hints,
+            relations,
+        };
+        Self {
+            previous: Some(ACTIVE.with(|a| a.replace(Some(active)))),
+        }
+    }
+}
+
+impl Drop for HintScope {
+    fn drop(&mut self) {
+        let Some(previous) = self.previous.take() else {
+            return;
+        };
+        if let Some(active) = ACTIVE.with(|a| a.replace(previous)) {
+            for (hint, settled) in active.hints.iter().zip(active.settled) {
+                if !settled {
+                    notice(format!("hint {} was not used", hint));
+                }
+            }
+        }
+    }
+}
+
+/// The scan hint for `table`, if any, and its position for [`settle`]
+pub(crate) fn scan_hint(table: &str) -> Option<(usize, ScanHint)> {
+    ACTIVE.with(|a| {
+        let active = a.borrow();
+        let active = active.as_ref()?;
+        active
+            .hints
+            .iter()
+            .enumerate()
+            .find_map(|(i, hint)| match hint {
+                Hint::SeqScan { relation } if active.table_of(relation) == Some(table) => {
+                    Some((i, ScanHint::Seq))
+                }
+                Hint::IndexScan { relation, indexes }
+                    if active.table_of(relation) == Some(table) =>
+                {
+                    Some((i, ScanHint::Index(indexes.clone())))
+                }
+                _ => None,
+            })
+    })
+}
+
+/// The join hint for joining `left` and `right`, if any, and its
+/// position for [`settle`]
+pub(crate) fn join_hint(left: &str, right: &str) -> Option<(usize, JoinHint)> {
+    ACTIVE.with(|a| {
+        let active = a.borrow();
+        let active = active.as_ref()?;
+        active.hints.iter().enumerate().find_map(|(i, hint)| {
+            let (relations, method) = match hint {
+                Hint::HashJoin { relations } => (relations, JoinHint::Hash),
+                Hint::NestLoop { relations } => (relations, JoinHint::NestLoop),
+                _ => return None,
+            };
+            let a = active.table_of(&relations[0])?;
+            let b = active.table_of(&relations[1])?;
+            ((a, b) == (left, right) || (a, b) == (right, left)).then_some((i, method))
+        })
+    })
+}
+
+/// Mark hint `index` as handled: followed, or dropped with a notice
+pub(crate) fn settle(index: usize) {
+    ACTIVE.with(|a| {
+        if let Some(active) = a.borrow_mut().as_mut() {
+            if let Some(settled) = active.settled.get_mut(index) {
+                *settled = true;
+            }
+        }
+    });
+}
+
+/// The hint at `index`, for notices
+pub(crate) fn describe(index: usize) -> String {
+    ACTIVE.with(|a| {
+        a.borrow()
+            .as_ref()
+            .and_then(|active| active.hints.get(index))
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    })
+}
+
+/// Tell the client something about the statement that didn't stop it
+pub(crate) fn notice(message: String) {
+    NOTICES.with(|n| {
+        let mut notices = n.borrow_mut();
+        if !notices.contains(&message) {
+            notices.push(message);
+        }
+    });
+}
+
+/// Notices raised on this thread since the last call
+pub(crate) fn take_notices() -> Vec<String> {
+    NOTICES.with(|n| std::mem::take(&mut *n.borrow_mut()))
+}
+
+/// Put back notices taken by [`take_notices`], ahead of any raised since
+pub(crate) fn restore_notices(earlier: Vec<String>) {
+    NOTICES.with(|n| {
+        let mut notices = n.borrow_mut();
+        let later = std::mem::replace(&mut *notices, earlier);
+        notices.extend(later);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_leading_hint_comment() {
+        let (hints, problems) = parse(
+            "/*+ IndexScan(orders idx_orders_created) hashjoin(a, B) Leading(a b) SeqScan() */ \
+             SELECT 1",
+        );
+        assert_eq!(
+            hints,
+            vec![
+                Hint::IndexScan {
+                    relation: "orders".to_string(),
+                    indexes: vec!["idx_orders_created".to_string()],
+                },
+                Hint::HashJoin {
+                    relations: vec!["a".to_string(), "b".to_string()],
+                },
+            ]
+        );
+        assert_eq!(problems.len(), 2);
+
+        // Only a comment at the very start holds hints
+        assert!(parse("SELECT /*+ SeqScan(t) */ 1").0.is_empty());
+        assert!(parse("/* SeqScan(t) */ SELECT 1").0.is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+//! Identifier case folding
+//!
+//! PostgreSQL folds unquoted identifiers to lowercase and keeps quoted
+//! ones as written, so `SELECT Email` and `SELECT email` name the same
+//! column while `SELECT "Email"` names another. The `identifier_case`
+//! parameter picks the policy: `lower` (the default, as in PostgreSQL),
+//! `upper`, or `preserve` to match names exactly as written.
+//!
+//! Statements are folded before anything reads them, so the parser, the
+//! commands handled ahead of it and every catalog lookup see the same
+//! names. Each unquoted word is folded; quoted identifiers, string
+//! literals, dollar-quoted bodies and comments are left alone. Keywords
+//! fold along with identifiers, which is harmless as they match in any
+//! case. Only ASCII letters change, as in PostgreSQL, so the folded
+//! statement has the same length as the original.
+
+use std::borrow::Cow;
+
+use sqlparser::dialect::GenericDialect;
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+/// How unquoted identifiers are folded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdentifierCase {
+    #[default]
+    Lower,
+    Upper,
+    /// Match identifiers exactly as written
+    Preserve,
+}
+
+impl IdentifierCase {
+    /// The policy an `identifier_case` setting names
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "lower" => Some(IdentifierCase::Lower),
+            "upper" => Some(IdentifierCase::Upper),
+            "preserve" => Some(IdentifierCase::Preserve),
+            _ => None,
+        }
+    }
+}
+
+/// `sql` with its unquoted identifiers folded per `case`. SQL that can't
+/// be tokenized comes back unchanged, for the parser to report.
+pub fn fold_identifiers(sql: &str, case: IdentifierCase) -> Cow<'_, str> {
+    let needs_folding = match case {
+        IdentifierCase::Lower => sql.bytes().any(|b| b.is_ascii_uppercase()),
+        IdentifierCase::Upper => sql.bytes().any(|b| b.is_ascii_lowercase()),
+        IdentifierCase::Preserve => false,
+    };
+    if !needs_folding {
+        return Cow::Borrowed(sql);
+    }
+    let Ok(tokens) = Tokenizer::new(&GenericDialect {}, sql).tokenize_with_location() else {
+        return Cow::Borrowed(sql);
+    };
+
+    // Byte offset of the start of each line; token locations count lines
+    // and characters from 1
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(sql.match_indices('\n').map(|(at, _)| at + 1))
+        .collect();
+    let mut folded = sql.as_bytes().to_vec();
+    let mut previous: Option<&Token> = None;
+    for token in &tokens {
+        let after_at = matches!(previous, Some(&Token::AtSign));
+        previous = Some(&token.token);
+        let Token::Word(word) = &token.token else {
+            continue;
+        };
+        // The `@SEQ:n` of `FOR SYSTEM_TIME AS OF` isn't an identifier
+        if word.quote_style.is_some() || word.value.starts_with('@') || after_at {
+            continue;
+        }
+        let Some(line_start) = line_starts.get((token.location.line as usize).saturating_sub(1))
+        else {
+            continue;
+        };
+        let Some((offset, _)) = sql[*line_start..]
+            .char_indices()
+            .nth((token.location.column as usize).saturating_sub(1))
+        else {
+            continue;
+        };
+        let start = line_start + offset;
+        let Some(span) = folded.get_mut(start..start + word.value.len()) else {
+            continue;
+        };
+        if span != word.value.as_bytes() {
+            continue;
+        }
+        match case {
+            IdentifierCase::Lower => span.make_ascii_lowercase(),
+            IdentifierCase::Upper => span.make_ascii_uppercase(),
+            IdentifierCase::Preserve => {}
+        }
+    }
+    // Changing the case of ASCII letters leaves valid UTF-8 valid
+    Cow::Owned(String::from_utf8(folded).expect("ASCII case changes keep UTF-8 valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_unquoted_identifiers_only() {
+        let sql = "SELECT Email, \"Email\" FROM Users WHERE Name = 'Ada' -- Comment";
+        assert_eq!(
+            fold_identifiers(sql, IdentifierCase::Lower),
+            "select email, \"Email\" from users where name = 'Ada' -- Comment"
+        );
+        assert_eq!(
+            fold_identifiers(sql, IdentifierCase::Upper),
+            "SELECT EMAIL, \"Email\" FROM USERS WHERE NAME = 'Ada' -- Comment"
+        );
+        assert_eq!(fold_identifiers(sql, IdentifierCase::Preserve), sql);
+    }
+
+    #[test]
+    fn leaves_literals_bodies_and_sequence_markers_alone() {
+        let sql = "SELECT * FROM Events FOR SYSTEM_TIME AS OF @SEQ:3";
+        assert_eq!(
+            fold_identifiers(sql, IdentifierCase::Lower),
+            "select * from events for system_time as of @SEQ:3"
+        );
+
+        let sql = "CREATE FUNCTION F() RETURNS TRIGGER AS $$ BEGIN NEW.Total := 1; END $$";
+        assert!(fold_identifiers(sql, IdentifierCase::Lower)
+            .ends_with("$$ BEGIN NEW.Total := 1; END $$"));
+
+        // Non-ASCII text keeps its place across lines
+        let sql = "SELECT 'é' AS Café,\n  Größe FROM T";
+        assert_eq!(
+            fold_identifiers(sql, IdentifierCase::Lower),
+            "select 'é' as café,\n  größe from t"
+        );
+        assert!(matches!(
+            fold_identifiers("select id from t", IdentifierCase::Lower),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn reads_the_setting() {
+        assert_eq!(
+            IdentifierCase::from_setting("Preserve"),
+            Some(IdentifierCase::Preserve)
+        );
+        assert_eq!(IdentifierCase::from_setting("mixed"), None);
+    }
+}
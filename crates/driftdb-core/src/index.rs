@@ -141,38 +141,54 @@ impl Index {
         start: Option<(&serde_json::Value, /*inclusive:*/ bool)>,
         end: Option<(&serde_json::Value, /*inclusive:*/ bool)>,
     ) -> HashSet<String> {
-        use std::cmp::Ordering;
         let mut result: HashSet<String> = HashSet::new();
         for (key_str, key_pks) in &self.entries {
-            // Reconstruct a JSON value from the stored string key. Numeric
-            // values round-trip through serde_json; strings (which were
-            // stored without quotes) fall back to Value::String.
-            let key_value = serde_json::from_str::<serde_json::Value>(key_str)
-                .unwrap_or_else(|_| serde_json::Value::String(key_str.clone()));
-
-            if let Some((lo, lo_incl)) = start {
-                let ord = crate::query::predicate::compare_json_values(&key_value, lo);
-                let inside = match ord {
-                    Ordering::Greater => true,
-                    Ordering::Equal => lo_incl,
-                    Ordering::Less => false,
-                };
-                if !inside {
-                    continue;
-                }
+            if within_bounds(&key_value(key_str), start, end) {
+                result.extend(key_pks.iter().cloned());
             }
-            if let Some((hi, hi_incl)) = end {
-                let ord = crate::query::predicate::compare_json_values(&key_value, hi);
-                let inside = match ord {
-                    Ordering::Less => true,
-                    Ordering::Equal => hi_incl,
-                    Ordering::Greater => false,
+        }
+        result
+    }
+
+    /// Primary keys whose indexed value falls within the given bounds,
+    /// in value order (reversed when `descending`), and in key order
+    /// among rows sharing a value. Used to read `ORDER BY column LIMIT n`
+    /// pages without sorting the table; the walk is O(distinct_keys) for
+    /// the same reason as [`Index::range`]. `text` columns compare their
+    /// keys as strings, so `'10'` sorts before `'9'` as it does in a sort.
+    pub fn ordered_range(
+        &self,
+        start: Option<(&serde_json::Value, /*inclusive:*/ bool)>,
+        end: Option<(&serde_json::Value, /*inclusive:*/ bool)>,
+        descending: bool,
+        text: bool,
+    ) -> Vec<String> {
+        let mut keys: Vec<(serde_json::Value, &HashSet<String>)> = self
+            .entries
+            .iter()
+            .map(|(key_str, key_pks)| {
+                let value = if text {
+                    serde_json::Value::String(key_str.clone())
+                } else {
+                    key_value(key_str)
                 };
-                if !inside {
-                    continue;
-                }
+                (value, key_pks)
+            })
+            .filter(|(value, _)| within_bounds(value, start, end))
+            .collect();
+        keys.sort_by(|(a, _), (b, _)| crate::query::predicate::compare_json_values(a, b));
+        if descending {
+            keys.reverse();
+        }
+
+        let mut result = Vec::new();
+        for (_, key_pks) in keys {
+            let mut pks: Vec<&String> = key_pks.iter().collect();
+            pks.sort();
+            if descending {
+                pks.reverse();
             }
-            result.extend(key_pks.iter().cloned());
+            result.extend(pks.into_iter().cloned());
         }
         result
     }
@@ -201,9 +217,44 @@ impl Index {
     }
 }
 
+/// Reconstruct a JSON value from a stored index key. Numeric values
+/// round-trip through serde_json; strings (which were stored without
+/// quotes) fall back to Value::String.
+fn key_value(key_str: &str) -> serde_json::Value {
+    serde_json::from_str::<serde_json::Value>(key_str)
+        .unwrap_or_else(|_| serde_json::Value::String(key_str.to_string()))
+}
+
+fn within_bounds(
+    value: &serde_json::Value,
+    start: Option<(&serde_json::Value, bool)>,
+    end: Option<(&serde_json::Value, bool)>,
+) -> bool {
+    use std::cmp::Ordering;
+    if let Some((lo, lo_incl)) = start {
+        match crate::query::predicate::compare_json_values(value, lo) {
+            Ordering::Greater => {}
+            Ordering::Equal if lo_incl => {}
+            _ => return false,
+        }
+    }
+    if let Some((hi, hi_incl)) = end {
+        match crate::query::predicate::compare_json_values(value, hi) {
+            Ordering::Less => {}
+            Ordering::Equal if hi_incl => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
 pub struct IndexManager {
     indexes_dir: PathBuf,
     indexes: BTreeMap<String, Index>,
+    /// GIN indexes on JSONB columns, keyed by column. Entries map each
+    /// document term (see [`crate::jsonb::gin_terms`]) to the rows holding
+    /// it. Stored as `<column>.gin`.
+    gin_indexes: BTreeMap<String, Index>,
 }
 
 impl IndexManager {
@@ -211,6 +262,7 @@ impl IndexManager {
         Self {
             indexes_dir: table_path.join("indexes"),
             indexes: BTreeMap::new(),
+            gin_indexes: BTreeMap::new(),
         }
     }
 
@@ -225,6 +277,23 @@ impl IndexManager {
                     .insert(column.clone(), Index::new(column.clone()));
             }
         }
+        self.load_gin_indexes()
+    }
+
+    /// GIN indexes aren't recorded in the schema; whatever `.gin` files
+    /// exist are loaded.
+    fn load_gin_indexes(&mut self) -> Result<()> {
+        if !self.indexes_dir.exists() {
+            return Ok(());
+        }
+        for entry in fs::read_dir(&self.indexes_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("gin") {
+                continue;
+            }
+            let index = Index::load_from_file(&path)?;
+            self.gin_indexes.insert(index.column_name.clone(), index);
+        }
         Ok(())
     }
 
@@ -240,6 +309,7 @@ impl IndexManager {
         match event.event_type {
             EventType::Insert => {
                 if let serde_json::Value::Object(map) = &event.payload {
+                    self.add_gin_terms(map, &pk_str);
                     for column in indexed_columns {
                         if let Some(value) = map.get(column) {
                             if let Some(index) = self.indexes.get_mut(column) {
@@ -251,6 +321,7 @@ impl IndexManager {
             }
             EventType::Patch => {
                 if let serde_json::Value::Object(map) = &event.payload {
+                    self.add_gin_terms(map, &pk_str);
                     for column in indexed_columns {
                         if let Some(value) = map.get(column) {
                             if let Some(index) = self.indexes.get_mut(column) {
@@ -261,6 +332,12 @@ impl IndexManager {
                 }
             }
             EventType::SoftDelete => {
+                for index in self.gin_indexes.values_mut() {
+                    index.entries.retain(|_, keys| {
+                        keys.remove(&pk_str);
+                        !keys.is_empty()
+                    });
+                }
                 for index in self.indexes.values_mut() {
                     let keys_to_remove: Vec<String> = index
                         .entries
@@ -284,12 +361,32 @@ impl IndexManager {
         Ok(())
     }
 
+    /// Add a row's document terms to each GIN index. Terms a patch made
+    /// stale stay behind; they only widen the candidate set, which callers
+    /// recheck.
+    fn add_gin_terms(&mut self, row: &serde_json::Map<String, serde_json::Value>, pk: &str) {
+        for (column, index) in &mut self.gin_indexes {
+            if let Some(doc) = row.get(column) {
+                for term in crate::jsonb::gin_terms(doc) {
+                    index
+                        .entries
+                        .entry(term)
+                        .or_default()
+                        .insert(pk.to_string());
+                }
+            }
+        }
+    }
+
     pub fn save_all(&self) -> Result<()> {
         fs::create_dir_all(&self.indexes_dir)?;
         for (column, index) in &self.indexes {
             let path = self.indexes_dir.join(format!("{}.idx", column));
             index.save_to_file(path)?;
         }
+        for (column, index) in &self.gin_indexes {
+            index.save_to_file(self.indexes_dir.join(format!("{}.gin", column)))?;
+        }
         Ok(())
     }
 
@@ -305,6 +402,71 @@ impl IndexManager {
         self.indexes.keys().cloned().collect()
     }
 
+    /// Columns with a GIN index
+    pub fn gin_column_names(&self) -> HashSet<String> {
+        self.gin_indexes.keys().cloned().collect()
+    }
+
+    pub fn get_gin_index(&self, column: &str) -> Option<&Index> {
+        self.gin_indexes.get(column)
+    }
+
+    /// Build a GIN index over a JSONB column from existing data
+    pub fn build_gin_index_from_data(
+        &mut self,
+        column: &str,
+        data: &HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        if self.gin_indexes.contains_key(column) {
+            return Err(DriftError::Other(format!(
+                "GIN index already exists for column '{}'",
+                column
+            )));
+        }
+
+        let mut index = Index::new(column.to_string());
+        for (pk, row) in data {
+            if let Some(doc) = row.get(column) {
+                for term in crate::jsonb::gin_terms(doc) {
+                    index.entries.entry(term).or_default().insert(pk.clone());
+                }
+            }
+        }
+        fs::create_dir_all(&self.indexes_dir)?;
+        index.save_to_file(self.indexes_dir.join(format!("{}.gin", column)))?;
+        self.gin_indexes.insert(column.to_string(), index);
+        Ok(())
+    }
+
+    /// Rows that may contain `query` according to the column's GIN index:
+    /// those holding every one of its terms. `None` when there is no GIN
+    /// index or the query has no terms to narrow by, in which case every
+    /// row is a candidate.
+    pub fn gin_candidates(
+        &self,
+        column: &str,
+        query: &serde_json::Value,
+    ) -> Option<HashSet<String>> {
+        let index = self.gin_indexes.get(column)?;
+        // A top-level scalar can match an array element, whose terms carry
+        // an array marker the query's don't
+        if !query.is_object() && !query.is_array() {
+            return None;
+        }
+        let terms = crate::jsonb::gin_terms(query);
+        let mut candidates: Option<HashSet<String>> = None;
+        for term in &terms {
+            let Some(keys) = index.find(term) else {
+                return Some(HashSet::new());
+            };
+            candidates = Some(match candidates {
+                None => keys.clone(),
+                Some(found) => found.intersection(keys).cloned().collect(),
+            });
+        }
+        candidates
+    }
+
     /// Add a new index for a column
     pub fn add_index(&mut self, column: &str) -> Result<()> {
         if self.indexes.contains_key(column) {
@@ -353,6 +515,9 @@ impl IndexManager {
         indexed_columns: &HashSet<String>,
     ) -> Result<()> {
         self.indexes.clear();
+        for index in self.gin_indexes.values_mut() {
+            index.entries.clear();
+        }
 
         for column in indexed_columns {
             self.indexes
@@ -361,6 +526,7 @@ impl IndexManager {
 
         for (pk, row) in state {
             if let serde_json::Value::Object(map) = row {
+                self.add_gin_terms(map, pk);
                 for column in indexed_columns {
                     if let Some(value) = map.get(column) {
                         if let Some(index) = self.indexes.get_mut(column) {
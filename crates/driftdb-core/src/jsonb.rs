@@ -0,0 +1,194 @@
+//! JSONB operators
+//!
+//! Rows are stored as `serde_json::Value`, so a `JSONB` column holds its
+//! document as-is. This module implements the PostgreSQL operators over
+//! those values:
+//!
+//! - `doc -> key` returns the field (string key) or array element (integer
+//!   index, negative counts from the end) as JSON, or NULL if missing.
+//! - `doc ->> key` does the same but returns text.
+//! - `a @> b` is true when `a` contains `b`; `a <@ b` is the reverse.
+//!
+//! GIN indexes on a JSONB column store one term per leaf of each document
+//! (see [`gin_terms`]). Every leaf of a contained document is a leaf of the
+//! container, so the rows holding all of a query's terms are a superset of
+//! the rows that contain it.
+
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Column types stored as JSON documents
+pub fn is_json_type(col_type: &str) -> bool {
+    matches!(col_type.to_ascii_uppercase().as_str(), "JSON" | "JSONB")
+}
+
+/// Parse text written into a JSONB column. Non-string values are already
+/// JSON and pass through.
+pub fn parse_document(value: Value) -> Result<Value> {
+    match value {
+        Value::String(text) => serde_json::from_str(&text).map_err(|e| {
+            DriftError::InvalidQuery(format!(
+                "invalid input syntax for type jsonb: \"{}\" ({})",
+                text, e
+            ))
+        }),
+        other => Ok(other),
+    }
+}
+
+/// Operand of a JSON operator: a string literal such as `'{"a": 1}'` is
+/// read as the document it spells, anything else is used as-is.
+pub fn operand(value: &Value) -> Value {
+    match value {
+        Value::String(text) => serde_json::from_str(text).unwrap_or_else(|_| value.clone()),
+        other => other.clone(),
+    }
+}
+
+/// `doc -> key`
+pub fn get(doc: &Value, key: &Value) -> Value {
+    let doc = operand(doc);
+    let found = match (&doc, key) {
+        (Value::Object(map), Value::String(field)) => map.get(field),
+        (Value::Array(items), Value::Number(n)) => n.as_i64().and_then(|i| {
+            let index = if i < 0 { items.len() as i64 + i } else { i };
+            usize::try_from(index).ok().and_then(|i| items.get(i))
+        }),
+        _ => None,
+    };
+    found.cloned().unwrap_or(Value::Null)
+}
+
+/// `doc ->> key`
+pub fn get_text(doc: &Value, key: &Value) -> Value {
+    match get(doc, key) {
+        Value::Null => Value::Null,
+        Value::String(s) => Value::String(s),
+        other => Value::String(other.to_string()),
+    }
+}
+
+/// `doc @> query`, with PostgreSQL's rules: objects contain objects whose
+/// pairs they all contain, arrays contain arrays whose elements each match
+/// some element (in any order), and, at the top level only, an array
+/// contains a scalar it holds.
+pub fn contains(doc: &Value, query: &Value) -> bool {
+    match (doc, query) {
+        (Value::Array(items), q) if !q.is_array() && !q.is_object() => {
+            items.iter().any(|d| contains_value(d, q))
+        }
+        _ => contains_value(doc, query),
+    }
+}
+
+fn contains_value(doc: &Value, query: &Value) -> bool {
+    match (doc, query) {
+        (Value::Object(doc), Value::Object(query)) => query
+            .iter()
+            .all(|(key, q)| doc.get(key).is_some_and(|d| contains_value(d, q))),
+        (Value::Array(doc), Value::Array(query)) => query
+            .iter()
+            .all(|q| doc.iter().any(|d| contains_value(d, q))),
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (d, q) => d == q,
+    }
+}
+
+/// Index terms for a document: one `path=value` string per leaf, with
+/// array positions dropped since containment ignores them. Documents
+/// stored as text are read the way [`operand`] reads them.
+pub fn gin_terms(doc: &Value) -> Vec<String> {
+    let mut terms = Vec::new();
+    collect_terms(&operand(doc), &mut String::new(), &mut terms);
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+fn collect_terms(value: &Value, path: &mut String, terms: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let len = path.len();
+                path.push('.');
+                path.push_str(&serde_json::to_string(key).unwrap_or_default());
+                collect_terms(child, path, terms);
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            let len = path.len();
+            path.push_str("[]");
+            for item in items {
+                collect_terms(item, path, terms);
+            }
+            path.truncate(len);
+        }
+        Value::Number(n) => {
+            // 1 and 1.0 must produce the same term
+            let n = n
+                .as_f64()
+                .map(Value::from)
+                .unwrap_or(Value::Number(n.clone()));
+            terms.push(format!("{}={}", path, n));
+        }
+        scalar => terms.push(format!("{}={}", path, scalar)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn path_access() {
+        let doc = json!({"status": "active", "items": [{"sku": "A1"}, {"sku": "B2"}]});
+        assert_eq!(get(&doc, &json!("status")), json!("active"));
+        assert_eq!(get_text(&doc, &json!("status")), json!("active"));
+        let items = get(&doc, &json!("items"));
+        assert_eq!(
+            get_text(&get(&items, &json!(0)), &json!("sku")),
+            json!("A1")
+        );
+        assert_eq!(get(&items, &json!(-1)), json!({"sku": "B2"}));
+        assert_eq!(get(&items, &json!(5)), Value::Null);
+        assert_eq!(get_text(&json!({"n": 3}), &json!("n")), json!("3"));
+        assert_eq!(get(&json!(r#"{"a": 1}"#), &json!("a")), json!(1));
+    }
+
+    #[test]
+    fn containment() {
+        let doc = json!({"a": 1, "tags": ["x", "y"], "nested": {"b": true, "c": 2}});
+        assert!(contains(&doc, &json!({"a": 1})));
+        assert!(contains(&doc, &json!({"tags": ["y"]})));
+        assert!(contains(&doc, &json!({"nested": {"c": 2.0}})));
+        assert!(contains(&doc, &json!({})));
+        assert!(!contains(&doc, &json!({"a": 2})));
+        assert!(!contains(&doc, &json!({"tags": ["z"]})));
+        assert!(!contains(&doc, &json!({"missing": null})));
+        assert!(contains(&json!(["x", "y"]), &json!("x")));
+        assert!(contains(&json!([[1, 2], 3]), &json!([[1]])));
+        // The scalar-in-array rule only applies at the top level
+        assert!(!contains(&json!({"tags": ["x"]}), &json!({"tags": "x"})));
+    }
+
+    #[test]
+    fn contained_documents_have_a_subset_of_terms() {
+        let doc = json!({"a": 1, "tags": ["x", "y"], "nested": {"b": true}});
+        let query = json!({"tags": ["y"], "a": 1.0});
+        let doc_terms = gin_terms(&doc);
+        assert!(gin_terms(&query).iter().all(|t| doc_terms.contains(t)));
+    }
+
+    #[test]
+    fn invalid_document_is_rejected() {
+        assert!(parse_document(json!("{not json")).is_err());
+        assert_eq!(
+            parse_document(json!(r#"{"a": 1}"#)).unwrap(),
+            json!({"a": 1})
+        );
+        assert_eq!(parse_document(json!(5)).unwrap(), json!(5));
+    }
+}
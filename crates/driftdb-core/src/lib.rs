@@ -1,15 +1,26 @@
+pub mod arrays;
 pub mod audit;
 pub mod auth;
+pub mod auto_analyze;
 pub mod backup;
 pub mod backup_enhanced;
 pub mod bloom_filter;
+pub mod bulk_load;
+pub mod bytea;
 pub mod cache;
+pub mod changefeed;
+pub mod compaction_progress;
+pub mod compaction_scheduler;
 pub mod connection;
 pub mod consensus;
 pub mod constraints;
+pub mod decimal;
 pub mod distributed_coordinator;
+pub mod doctor;
+pub mod durability;
 pub mod encryption;
 pub mod engine;
+pub mod enums;
 pub mod error_recovery;
 pub mod errors;
 pub mod events;
@@ -17,14 +28,19 @@ pub mod explain;
 pub mod failover;
 pub mod fk;
 pub mod fulltext;
+pub mod hints;
+pub mod identifier_case;
 pub mod index;
 pub mod index_strategies;
+pub mod jsonb;
 pub mod migration;
+pub mod migration_runner;
 pub mod monitoring;
 pub mod mvcc;
 pub mod observability;
 pub mod optimizer;
 pub mod parallel;
+pub mod partitioning;
 pub mod procedures;
 pub mod query;
 pub mod query_cancellation;
@@ -32,19 +48,31 @@ pub mod query_performance;
 pub mod raft;
 pub mod rate_limit;
 pub mod replication;
+pub mod row_check;
 pub mod row_level_security;
+pub mod scalar_functions;
 pub mod schema;
+pub mod search_path;
 pub mod security_monitor;
 pub mod sequences;
+pub mod session_memory;
+pub mod settings;
 pub mod snapshot;
+pub mod snapshot_stream;
+pub mod spill;
 pub mod sql;
 pub mod sql_bridge;
+pub mod sql_lint;
 pub mod sql_views;
 pub mod stats;
 pub mod storage;
+pub mod subscription;
+pub mod system_catalog;
 pub mod transaction;
 pub mod transaction_coordinator;
 pub mod triggers;
+pub mod uuids;
+pub mod verify;
 pub mod views;
 pub mod wal;
 pub mod window;
@@ -57,9 +85,12 @@ mod storage_test;
 
 pub use audit::{AuditAction, AuditConfig, AuditEvent, AuditEventType, AuditSystem};
 pub use auth::{AuthConfig, AuthContext, AuthManager, Permission, Role, Session, User};
+pub use auto_analyze::{AutoAnalyzeConfig, AutoAnalyzer};
 pub use bloom_filter::{BloomConfig, BloomFilter, BloomStatistics, ScalableBloomFilter};
+pub use compaction_progress::{CompactionPhase, CompactionProgress, CompactionTracker};
+pub use compaction_scheduler::{CompactionConfig, CompactionPolicy, CompactionScheduler};
 pub use connection::{EngineGuard, EnginePool, EnginePoolStats, PoolConfig, PoolStats};
-pub use engine::Engine;
+pub use engine::{Engine, IndexInfo, TableStorageInfo};
 pub use errors::{DriftError, Result};
 pub use events::{Event, EventType};
 pub use explain::{ExplainExecutor, ExplainFormat, ExplainOptions, ExplainPlan};
@@ -80,3 +111,5 @@ pub use security_monitor::{
 pub use snapshot::{
     AdaptiveSnapshotManager, Snapshot, SnapshotManager, SnapshotPolicy, SnapshotStatistics,
 };
+pub use snapshot_stream::SnapshotInfo;
+pub use subscription::{EventReceiver, EventSubscription};
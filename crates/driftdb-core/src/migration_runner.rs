@@ -0,0 +1,713 @@
+//! Versioned SQL migrations
+//!
+//! [`MigrationRunner`] applies numbered `.sql` files in version order and
+//! records each one in the `_driftdb_migrations` table with a checksum of
+//! its SQL. Before applying anything it checks that every recorded
+//! migration still matches its file, so an edited or deleted migration is
+//! reported instead of leaving databases that disagree about their schema.
+//!
+//! Migration files are named `V<version>__<name>.sql`, with an optional
+//! `V<version>__<name>.down.sql` that reverts it. Each migration runs
+//! inside a transaction together with its `_driftdb_migrations` record.
+//! Schema changes take effect immediately in DriftDB, so a migration that
+//! fails part-way rolls back its data changes but not DDL that ran before
+//! the failure.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::info;
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::query::QueryResult;
+use crate::sql_bridge::{execute_sql_in_session, SessionContext};
+
+/// Table the applied migrations are recorded in
+pub const MIGRATIONS_TABLE: &str = "_driftdb_migrations";
+
+/// One migration: SQL to apply and, optionally, SQL to revert it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqlMigration {
+    pub version: u64,
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+}
+
+impl SqlMigration {
+    pub fn new(version: u64, name: impl Into<String>, up: impl Into<String>) -> Self {
+        Self {
+            version,
+            name: name.into(),
+            up: up.into(),
+            down: None,
+        }
+    }
+
+    pub fn with_down(mut self, down: impl Into<String>) -> Self {
+        self.down = Some(down.into());
+        self
+    }
+
+    /// SHA-256 of the `up` SQL, ignoring line-ending differences
+    pub fn checksum(&self) -> String {
+        use sha2::{Digest, Sha256};
+        let mut hasher = Sha256::new();
+        hasher.update(self.up.replace("\r\n", "\n").trim());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// A row of `_driftdb_migrations`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedSqlMigration {
+    pub version: u64,
+    pub name: String,
+    pub checksum: String,
+    /// RFC 3339 UTC timestamp
+    pub applied_at: String,
+}
+
+/// Applied and pending migrations, as reported by [`MigrationRunner::status`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationRunnerStatus {
+    pub applied: Vec<AppliedSqlMigration>,
+    pub pending: Vec<u64>,
+}
+
+/// Applies and reverts a set of [`SqlMigration`]s
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRunner {
+    migrations: BTreeMap<u64, SqlMigration>,
+}
+
+impl MigrationRunner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load `V<version>__<name>.sql` and `V<version>__<name>.down.sql` files
+    /// from `dir`. Other files are ignored; a `.sql` file that doesn't follow
+    /// the naming scheme is an error, so a typo can't silently skip one.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let mut ups = BTreeMap::new();
+        let mut downs = BTreeMap::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let Some(stem) = file_name.strip_suffix(".sql") else {
+                continue;
+            };
+            let (stem, is_down) = match stem.strip_suffix(".down") {
+                Some(stem) => (stem, true),
+                None => (stem, false),
+            };
+            let (version, name) = parse_migration_name(stem).ok_or_else(|| {
+                DriftError::Validation(format!(
+                    "Migration file {} is not named V<version>__<name>.sql",
+                    path.display()
+                ))
+            })?;
+
+            let target = if is_down { &mut downs } else { &mut ups };
+            let sql = fs::read_to_string(&path)?;
+            if target.insert(version, (name, sql)).is_some() {
+                return Err(DriftError::Validation(format!(
+                    "More than one migration file for version {} in {}",
+                    version,
+                    dir.display()
+                )));
+            }
+        }
+
+        let mut runner = Self::new();
+        for (version, (name, up)) in ups {
+            let mut migration = SqlMigration::new(version, name, up);
+            if let Some((_, down)) = downs.remove(&version) {
+                migration = migration.with_down(down);
+            }
+            runner.add(migration)?;
+        }
+        if let Some(version) = downs.keys().next() {
+            return Err(DriftError::Validation(format!(
+                "Down migration for version {} has no matching up migration",
+                version
+            )));
+        }
+        Ok(runner)
+    }
+
+    pub fn add(&mut self, migration: SqlMigration) -> Result<()> {
+        if self.migrations.contains_key(&migration.version) {
+            return Err(DriftError::Validation(format!(
+                "Duplicate migration version {}",
+                migration.version
+            )));
+        }
+        self.migrations.insert(migration.version, migration);
+        Ok(())
+    }
+
+    pub fn migrations(&self) -> impl Iterator<Item = &SqlMigration> {
+        self.migrations.values()
+    }
+
+    /// Migrations recorded in `_driftdb_migrations`, oldest first
+    pub fn applied(&self, engine: &mut Engine) -> Result<Vec<AppliedSqlMigration>> {
+        if !engine.table_exists(MIGRATIONS_TABLE) {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT version, name, checksum, applied_at FROM {} ORDER BY version",
+            MIGRATIONS_TABLE
+        );
+        let rows = match execute_sql_in_session(engine, &sql, &mut SessionContext::new())? {
+            QueryResult::Rows { data } => data,
+            other => {
+                return Err(DriftError::Internal(format!(
+                    "Unexpected result reading {}: {:?}",
+                    MIGRATIONS_TABLE, other
+                )))
+            }
+        };
+        rows.iter().map(applied_from_row).collect()
+    }
+
+    /// Check every applied migration against the known ones: each must
+    /// still exist and have the checksum it was applied with
+    pub fn verify(&self, engine: &mut Engine) -> Result<()> {
+        self.verify_applied(&self.applied(engine)?)
+    }
+
+    fn verify_applied(&self, applied: &[AppliedSqlMigration]) -> Result<()> {
+        for record in applied {
+            let migration = self.migrations.get(&record.version).ok_or_else(|| {
+                DriftError::Validation(format!(
+                    "Migration {} ({}) was applied but is missing",
+                    record.version, record.name
+                ))
+            })?;
+            if migration.checksum() != record.checksum {
+                return Err(DriftError::Validation(format!(
+                    "Migration {} ({}) has changed since it was applied: checksum {} was recorded, the file now has {}",
+                    record.version,
+                    record.name,
+                    record.checksum,
+                    migration.checksum()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn status(&self, engine: &mut Engine) -> Result<MigrationRunnerStatus> {
+        let applied = self.applied(engine)?;
+        let pending = self
+            .migrations
+            .keys()
+            .filter(|v| !applied.iter().any(|a| a.version == **v))
+            .copied()
+            .collect();
+        Ok(MigrationRunnerStatus { applied, pending })
+    }
+
+    /// Apply every pending migration in version order, after verifying the
+    /// applied ones. Stops at the first failure. Returns the versions applied.
+    pub fn up(&self, engine: &mut Engine) -> Result<Vec<u64>> {
+        self.ensure_table(engine)?;
+        let applied = self.applied(engine)?;
+        self.verify_applied(&applied)?;
+
+        let mut done = Vec::new();
+        for migration in self.migrations.values() {
+            if applied.iter().any(|a| a.version == migration.version) {
+                continue;
+            }
+            info!(
+                "Applying migration {}: {}",
+                migration.version, migration.name
+            );
+            let record = format!(
+                "INSERT INTO {} (version, name, checksum, applied_at) VALUES ({}, '{}', '{}', '{}')",
+                MIGRATIONS_TABLE,
+                migration.version,
+                migration.name.replace('\'', "''"),
+                migration.checksum(),
+                chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+            );
+            run_in_transaction(engine, migration, &migration.up, &record)?;
+            done.push(migration.version);
+        }
+        Ok(done)
+    }
+
+    /// Revert the most recently applied migration with its down SQL.
+    /// Returns its version, or `None` if nothing is applied.
+    pub fn down(&self, engine: &mut Engine) -> Result<Option<u64>> {
+        let applied = self.applied(engine)?;
+        self.verify_applied(&applied)?;
+        let Some(last) = applied.last() else {
+            return Ok(None);
+        };
+
+        let migration = &self.migrations[&last.version];
+        let down = migration.down.as_ref().ok_or_else(|| {
+            DriftError::Validation(format!(
+                "Migration {} ({}) has no down migration",
+                migration.version, migration.name
+            ))
+        })?;
+        info!(
+            "Reverting migration {}: {}",
+            migration.version, migration.name
+        );
+        let record = format!(
+            "DELETE FROM {} WHERE version = {}",
+            MIGRATIONS_TABLE, migration.version
+        );
+        run_in_transaction(engine, migration, down, &record)?;
+        Ok(Some(migration.version))
+    }
+
+    /// The statements that take `engine` from its current schema to the
+    /// one declared by `target_schema`, a file of `CREATE TABLE` and
+    /// `CREATE INDEX` statements. Tables and columns that aren't declared
+    /// are dropped; differences no statement can fix, such as a changed
+    /// column type, are listed in [`SchemaPlan::unsupported`].
+    pub fn plan(engine: &Engine, target_schema: &str) -> Result<SchemaPlan> {
+        let declared = parse_declared_schema(target_schema)?;
+        let mut plan = SchemaPlan::default();
+        let mut drops = Vec::new();
+
+        for table in &declared {
+            if !engine.table_exists(&table.name) {
+                plan.push(table.create_sql.clone(), false);
+                for index in table.indexes.iter().filter(|i| !i.unique) {
+                    plan.push(index.sql.clone(), false);
+                }
+                continue;
+            }
+
+            let schema = engine.table_schema(&table.name)?;
+            let indexed: BTreeSet<String> = engine
+                .list_indexes(&table.name)?
+                .into_iter()
+                .filter(|i| !i.primary)
+                .map(|i| i.column)
+                .collect();
+
+            if let Some(pk) = &table.primary_key {
+                if *pk != schema.primary_key {
+                    plan.unsupported.push(format!(
+                        "{}: primary key is {}, declared as {}",
+                        table.name, schema.primary_key, pk
+                    ));
+                }
+            }
+            for column in &table.columns {
+                match schema.columns.iter().find(|c| c.name == column.name) {
+                    None => plan.push(
+                        format!("ALTER TABLE {} ADD COLUMN {}", table.name, column.sql),
+                        false,
+                    ),
+                    Some(existing)
+                        if !existing.col_type.eq_ignore_ascii_case(&column.data_type) =>
+                    {
+                        plan.unsupported.push(format!(
+                            "{}.{}: type is {}, declared as {}",
+                            table.name, column.name, existing.col_type, column.data_type
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+            for index in &table.indexes {
+                if !indexed.contains(&index.column) {
+                    plan.push(index.sql.clone(), false);
+                }
+            }
+            for column in &indexed {
+                if !table.indexes.iter().any(|i| i.column == *column) {
+                    plan.unsupported.push(format!(
+                        "{}.{}: index isn't declared, and indexes can't be dropped",
+                        table.name, column
+                    ));
+                }
+            }
+            for existing in &schema.columns {
+                if existing.name == schema.primary_key
+                    || table.columns.iter().any(|c| c.name == existing.name)
+                {
+                    continue;
+                }
+                if indexed.contains(&existing.name) {
+                    plan.unsupported.push(format!(
+                        "{}.{}: column isn't declared, but indexed columns can't be dropped",
+                        table.name, existing.name
+                    ));
+                } else {
+                    drops.push(format!(
+                        "ALTER TABLE {} DROP COLUMN {}",
+                        table.name, existing.name
+                    ));
+                }
+            }
+        }
+
+        let mut tables = engine.list_tables();
+        tables.sort();
+        for table in tables {
+            if !table.starts_with("_driftdb") && !declared.iter().any(|t| t.name == table) {
+                drops.push(format!("DROP TABLE {}", table));
+            }
+        }
+        for sql in drops {
+            plan.push(sql, true);
+        }
+        Ok(plan)
+    }
+
+    fn ensure_table(&self, engine: &mut Engine) -> Result<()> {
+        if engine.table_exists(MIGRATIONS_TABLE) {
+            return Ok(());
+        }
+        let sql = format!(
+            "CREATE TABLE {} (version BIGINT PRIMARY KEY, name VARCHAR, checksum VARCHAR, applied_at VARCHAR)",
+            MIGRATIONS_TABLE
+        );
+        execute_sql_in_session(engine, &sql, &mut SessionContext::new())?;
+        Ok(())
+    }
+}
+
+/// Run `sql` and then `record` in one transaction, rolling back on failure.
+/// The migration's rows and its record may share a primary key; the
+/// transaction keeps them apart by table.
+fn run_in_transaction(
+    engine: &mut Engine,
+    migration: &SqlMigration,
+    sql: &str,
+    record: &str,
+) -> Result<()> {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, "BEGIN", &mut ctx)?;
+
+    let result = split_migration_sql(sql)
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(record))
+        .try_for_each(|statement| execute_sql_in_session(engine, statement, &mut ctx).map(drop));
+
+    match result {
+        Ok(()) => execute_sql_in_session(engine, "COMMIT", &mut ctx).map(drop),
+        Err(e) => {
+            let _ = execute_sql_in_session(engine, "ROLLBACK", &mut ctx);
+            Err(DriftError::Other(format!(
+                "Migration {} ({}) failed: {}",
+                migration.version, migration.name, e
+            )))
+        }
+    }
+}
+
+/// One statement of a [`SchemaPlan`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub sql: String,
+    /// Drops a table or column
+    pub destructive: bool,
+}
+
+/// Statements that converge a database on a declared schema, from
+/// [`MigrationRunner::plan`]. Additions come first and drops last.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaPlan {
+    pub changes: Vec<PlannedChange>,
+    /// Differences the plan can't fix
+    pub unsupported: Vec<String>,
+}
+
+impl SchemaPlan {
+    fn push(&mut self, sql: String, destructive: bool) {
+        self.changes.push(PlannedChange { sql, destructive });
+    }
+
+    /// Whether the database already matches the declared schema
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.unsupported.is_empty()
+    }
+
+    pub fn destructive(&self) -> impl Iterator<Item = &PlannedChange> {
+        self.changes.iter().filter(|c| c.destructive)
+    }
+
+    /// Run the planned statements in order, returning how many ran.
+    /// Refuses to run anything if the plan drops a table or column and
+    /// `allow_destructive` isn't set.
+    pub fn apply(&self, engine: &mut Engine, allow_destructive: bool) -> Result<usize> {
+        if !allow_destructive {
+            let destructive: Vec<&str> = self.destructive().map(|c| c.sql.as_str()).collect();
+            if !destructive.is_empty() {
+                return Err(DriftError::Validation(format!(
+                    "Plan includes destructive changes: {}",
+                    destructive.join("; ")
+                )));
+            }
+        }
+        let mut ctx = SessionContext::new();
+        for change in &self.changes {
+            info!("Applying schema change: {}", change.sql);
+            execute_sql_in_session(engine, &change.sql, &mut ctx).map_err(|e| {
+                DriftError::Other(format!("Schema change `{}` failed: {}", change.sql, e))
+            })?;
+        }
+        Ok(self.changes.len())
+    }
+}
+
+/// A `CREATE TABLE` in a declared schema, with its `CREATE INDEX`es
+struct DeclaredTable {
+    name: String,
+    create_sql: String,
+    primary_key: Option<String>,
+    columns: Vec<DeclaredColumn>,
+    indexes: Vec<DeclaredIndex>,
+}
+
+struct DeclaredColumn {
+    name: String,
+    data_type: String,
+    /// Name, type and default, as `ADD COLUMN` takes them
+    sql: String,
+}
+
+struct DeclaredIndex {
+    column: String,
+    sql: String,
+    /// From a `UNIQUE` column, which the table's `CREATE TABLE` indexes
+    unique: bool,
+}
+
+fn parse_declared_schema(sql: &str) -> Result<Vec<DeclaredTable>> {
+    use sqlparser::ast::{ColumnOption, Statement, TableConstraint};
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    let table_name = |name: &sqlparser::ast::ObjectName| {
+        let parts: Vec<&str> = name.0.iter().map(|i| i.value.as_str()).collect();
+        match parts.as_slice() {
+            [schema, table] => crate::search_path::storage_name(schema, table),
+            _ => parts.join("."),
+        }
+    };
+
+    let mut tables: Vec<DeclaredTable> = Vec::new();
+    for statement in split_migration_sql(sql) {
+        let ast = Parser::parse_sql(&GenericDialect {}, &statement)
+            .map_err(|e| DriftError::Parse(format!("{}: {}", statement, e)))?;
+        match ast.first() {
+            Some(Statement::CreateTable(create)) => {
+                let name = table_name(&create.name);
+                let mut table = DeclaredTable {
+                    name: name.clone(),
+                    create_sql: statement.clone(),
+                    primary_key: None,
+                    columns: Vec::new(),
+                    indexes: Vec::new(),
+                };
+                for column in &create.columns {
+                    let mut sql = format!("{} {}", column.name, column.data_type);
+                    for option in &column.options {
+                        match &option.option {
+                            ColumnOption::Default(expr) => sql += &format!(" DEFAULT {}", expr),
+                            ColumnOption::Unique {
+                                is_primary: true, ..
+                            } => table.primary_key = Some(column.name.value.clone()),
+                            ColumnOption::Unique { .. } => table.indexes.push(DeclaredIndex {
+                                column: column.name.value.clone(),
+                                sql: format!("CREATE INDEX ON {} ({})", name, column.name),
+                                unique: true,
+                            }),
+                            _ => {}
+                        }
+                    }
+                    table.columns.push(DeclaredColumn {
+                        name: column.name.value.clone(),
+                        data_type: column.data_type.to_string(),
+                        sql,
+                    });
+                }
+                for constraint in &create.constraints {
+                    if let TableConstraint::PrimaryKey { columns, .. } = constraint {
+                        table.primary_key = columns.first().map(|c| c.value.clone());
+                    }
+                }
+                tables.push(table);
+            }
+            Some(Statement::CreateIndex(create)) => {
+                let name = table_name(&create.table_name);
+                let column = create
+                    .columns
+                    .first()
+                    .map(|c| c.expr.to_string())
+                    .ok_or_else(|| DriftError::Parse(format!("{}: no column", statement)))?;
+                let table = tables.iter_mut().find(|t| t.name == name).ok_or_else(|| {
+                    DriftError::Validation(format!(
+                        "{}: table {} isn't declared before its index",
+                        statement, name
+                    ))
+                })?;
+                table.indexes.push(DeclaredIndex {
+                    column,
+                    sql: statement.clone(),
+                    unique: false,
+                });
+            }
+            _ => {
+                return Err(DriftError::Validation(format!(
+                    "A declared schema may only contain CREATE TABLE and CREATE INDEX: {}",
+                    statement
+                )))
+            }
+        }
+    }
+    Ok(tables)
+}
+
+/// `(3, "add_email")` from `V3__add_email`
+fn parse_migration_name(stem: &str) -> Option<(u64, String)> {
+    let rest = stem.strip_prefix(['V', 'v'])?;
+    let (version, name) = rest.split_once("__")?;
+    let version = version.parse().ok()?;
+    (!name.is_empty()).then(|| (version, name.to_string()))
+}
+
+fn applied_from_row(row: &Value) -> Result<AppliedSqlMigration> {
+    let text = |column: &str| -> String {
+        match &row[column] {
+            Value::String(s) => s.clone(),
+            Value::Null => String::new(),
+            other => other.to_string(),
+        }
+    };
+    let version = match &row["version"] {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| {
+        DriftError::Corruption(format!("Bad version in {}: {}", MIGRATIONS_TABLE, row))
+    })?;
+    Ok(AppliedSqlMigration {
+        version,
+        name: text("name"),
+        checksum: text("checksum"),
+        applied_at: text("applied_at"),
+    })
+}
+
+/// Split a migration file into statements at top-level semicolons,
+/// keeping quoted strings and `$tag$ ... $tag$` bodies whole and dropping
+/// `--` comments
+pub fn split_migration_sql(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut rest = sql;
+
+    while let Some(c) = rest.chars().next() {
+        let len = if rest.starts_with("--") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            rest = &rest[end..];
+            continue;
+        } else if c == ';' {
+            if !current.trim().is_empty() {
+                statements.push(current.trim().to_string());
+            }
+            current.clear();
+            rest = &rest[1..];
+            continue;
+        } else if c == '\'' || c == '"' {
+            // A doubled quote closes and reopens, which comes out the same
+            rest[1..].find(c).map_or(rest.len(), |i| i + 2)
+        } else if c == '$' {
+            dollar_quoted_len(rest).unwrap_or(1)
+        } else {
+            c.len_utf8()
+        };
+        current.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+    if !current.trim().is_empty() {
+        statements.push(current.trim().to_string());
+    }
+    statements
+}
+
+/// Length of the `$tag$ ... $tag$` string at the start of `s`, if one starts there
+fn dollar_quoted_len(s: &str) -> Option<usize> {
+    let tag_end = s[1..].find('$')? + 2;
+    let tag = &s[..tag_end];
+    if !tag[1..tag_end - 1]
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some(
+        s[tag_end..]
+            .find(tag)
+            .map_or(s.len(), |i| tag_end + i + tag.len()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_migration_name() {
+        assert_eq!(
+            parse_migration_name("V3__add_email"),
+            Some((3, "add_email".to_string()))
+        );
+        assert_eq!(
+            parse_migration_name("v010__init"),
+            Some((10, "init".to_string()))
+        );
+        assert_eq!(parse_migration_name("3__add_email"), None);
+        assert_eq!(parse_migration_name("V3_add_email"), None);
+        assert_eq!(parse_migration_name("V3__"), None);
+    }
+
+    #[test]
+    fn test_split_migration_sql() {
+        let sql = "-- users; table\nCREATE TABLE users (id INT PRIMARY KEY, name VARCHAR);\nINSERT INTO users (id, name) VALUES (1, 'a;''b');\n\
+                   CREATE FUNCTION f() RETURNS TRIGGER AS $$ UPDATE t SET x = 1; $$ LANGUAGE SQL;\n";
+        assert_eq!(
+            split_migration_sql(sql),
+            [
+                "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR)",
+                "INSERT INTO users (id, name) VALUES (1, 'a;''b')",
+                "CREATE FUNCTION f() RETURNS TRIGGER AS $$ UPDATE t SET x = 1; $$ LANGUAGE SQL",
+            ]
+        );
+        assert_eq!(split_migration_sql("SELECT $1"), ["SELECT $1"]);
+    }
+
+    #[test]
+    fn test_checksum_ignores_line_endings() {
+        let unix = SqlMigration::new(1, "init", "CREATE TABLE t (id INT);\n");
+        let windows = SqlMigration::new(1, "init", "CREATE TABLE t (id INT);\r\n");
+        let edited = SqlMigration::new(1, "init", "CREATE TABLE t (id BIGINT);\n");
+        assert_eq!(unix.checksum(), windows.checksum());
+        assert_ne!(unix.checksum(), edited.checksum());
+    }
+}
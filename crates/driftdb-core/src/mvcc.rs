@@ -14,7 +14,7 @@ use std::sync::Arc;
 use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::errors::{DriftError, Result};
 
@@ -186,6 +186,9 @@ pub struct MVCCManager {
     lock_manager: Arc<LockManager>,
     /// Deadlock detector
     deadlock_detector: Arc<DeadlockDetector>,
+    /// Why each transaction aborted to break a deadlock was chosen, until
+    /// its next operation reports it
+    deadlock_victims: Arc<Mutex<HashMap<TxnId, DeadlockReport>>>,
     /// Garbage collector
     gc_queue: Arc<Mutex<VecDeque<(RecordId, VersionTimestamp)>>>,
 }
@@ -200,6 +203,7 @@ impl MVCCManager {
             versions: Arc::new(RwLock::new(HashMap::new())),
             lock_manager: Arc::new(LockManager::new()),
             deadlock_detector: Arc::new(DeadlockDetector::new(config.deadlock_detection)),
+            deadlock_victims: Arc::new(Mutex::new(HashMap::new())),
             gc_queue: Arc::new(Mutex::new(VecDeque::new())),
         }
     }
@@ -275,7 +279,7 @@ impl MVCCManager {
 
         // Check transaction state
         if *txn.state.read() != TransactionState::Active {
-            return Err(DriftError::Other("Transaction is not active".to_string()));
+            return Err(self.inactive_error(txn));
         }
 
         // Acquire lock for serializable isolation
@@ -317,7 +321,7 @@ impl MVCCManager {
 
         // Check transaction state
         if *txn.state.read() != TransactionState::Active {
-            return Err(DriftError::Other("Transaction is not active".to_string()));
+            return Err(self.inactive_error(txn));
         }
 
         // Deletion is a write with None data
@@ -337,6 +341,9 @@ impl MVCCManager {
     pub fn commit(&self, txn: Arc<MVCCTransaction>) -> Result<()> {
         // Enforce transaction timeout
         self.enforce_timeout(&txn)?;
+        if *txn.state.read() == TransactionState::Aborted {
+            return Err(self.inactive_error(&txn));
+        }
 
         // Change state to preparing
         *txn.state.write() = TransactionState::Preparing;
@@ -838,6 +845,9 @@ impl MVCCManager {
 
     /// Run deadlock detection and abort victim transactions
     /// Returns the IDs of aborted transactions
+    ///
+    /// Each victim's next operation fails with [`DriftError::Deadlock`],
+    /// describing the cycle it was part of.
     pub fn resolve_deadlocks(&self) -> Vec<TxnId> {
         if !self.config.deadlock_detection {
             return Vec::new();
@@ -847,11 +857,20 @@ impl MVCCManager {
         let mut aborted = Vec::new();
 
         for cycle in cycles {
-            if let Some(victim_id) = self.deadlock_detector.select_victim(&cycle) {
-                if let Some(victim_txn) = self.active_txns.read().get(&victim_id).cloned() {
-                    if self.abort(victim_txn).is_ok() {
-                        aborted.push(victim_id);
-                    }
+            let report = self.deadlock_detector.report(&cycle, |txn_id, holder| {
+                self.lock_manager.waiting_for(txn_id, holder)
+            });
+            let Some(report) = report else {
+                continue;
+            };
+            let victim_id = report.victim;
+            // Bound first so the read guard is gone before abort() writes
+            let victim_txn = self.active_txns.read().get(&victim_id).cloned();
+            if let Some(victim_txn) = victim_txn {
+                warn!("Deadlock detected: {}", report);
+                self.deadlock_victims.lock().insert(victim_id, report);
+                if self.abort(victim_txn).is_ok() {
+                    aborted.push(victim_id);
                 }
             }
         }
@@ -859,6 +878,15 @@ impl MVCCManager {
         aborted
     }
 
+    /// The error for an operation on a transaction that is no longer
+    /// active: the deadlock it was aborted for, the first time it's asked
+    fn inactive_error(&self, txn: &MVCCTransaction) -> DriftError {
+        match self.deadlock_victims.lock().remove(&txn.id) {
+            Some(report) => DriftError::Deadlock(report),
+            None => DriftError::Other("Transaction is not active".to_string()),
+        }
+    }
+
     /// Get configuration
     pub fn config(&self) -> &MVCCConfig {
         &self.config
@@ -943,6 +971,21 @@ impl LockManager {
         }
     }
 
+    /// The record `txn_id` is queued for while `holder` holds it
+    fn waiting_for(&self, txn_id: TxnId, holder: TxnId) -> Option<String> {
+        let locks = self.locks.read();
+        let mut records: Vec<String> = locks
+            .iter()
+            .filter(|(_, info)| {
+                info.holders.contains(&holder)
+                    && info.waiters.iter().any(|(waiter, _)| *waiter == txn_id)
+            })
+            .map(|(record_id, _)| format!("{}:{}", record_id.table, record_id.key))
+            .collect();
+        records.sort();
+        records.into_iter().next()
+    }
+
     fn release_lock(&self, txn_id: TxnId, record_id: &RecordId) {
         let mut locks = self.locks.write();
 
@@ -1050,6 +1093,79 @@ impl DeadlockDetector {
     pub fn select_victim(&self, cycle: &[TxnId]) -> Option<TxnId> {
         cycle.iter().max().copied()
     }
+
+    /// Describe `cycle`, as returned by [`Self::detect_deadlocks`]: who
+    /// waits for whom, on what, and which transaction is aborted.
+    /// `resource(txn, holder)` names what `txn` waits for `holder` to
+    /// release, if known.
+    pub fn report(
+        &self,
+        cycle: &[TxnId],
+        resource: impl Fn(TxnId, TxnId) -> Option<String>,
+    ) -> Option<DeadlockReport> {
+        // Cycles come closed, ending with the transaction they start with
+        let txns = match cycle {
+            [first, rest @ .., last] if first == last && !rest.is_empty() => {
+                &cycle[..cycle.len() - 1]
+            }
+            _ => cycle,
+        };
+        let victim = self.select_victim(txns)?;
+        let waits = txns
+            .iter()
+            .enumerate()
+            .map(|(i, &txn_id)| {
+                let blocked_by = txns[(i + 1) % txns.len()];
+                DeadlockWait {
+                    txn_id,
+                    resource: resource(txn_id, blocked_by),
+                    blocked_by,
+                }
+            })
+            .collect();
+        Some(DeadlockReport { waits, victim })
+    }
+}
+
+/// A cycle of transactions, each waiting for a lock the next one holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadlockReport {
+    /// The cycle in wait order; the last transaction waits for the first
+    pub waits: Vec<DeadlockWait>,
+    /// The transaction aborted to break the cycle
+    pub victim: TxnId,
+}
+
+/// One transaction of a deadlock and the lock it was waiting for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeadlockWait {
+    pub txn_id: TxnId,
+    /// What it was waiting to lock, as `table:key` for a record
+    pub resource: Option<String>,
+    /// The transaction holding that lock
+    pub blocked_by: TxnId,
+}
+
+impl std::fmt::Display for DeadlockReport {
+    /// `transaction 2 waits for a lock on users:1 held by transaction 1;
+    /// transaction 1 waits for ...; transaction 2 was aborted`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for wait in &self.waits {
+            match &wait.resource {
+                Some(resource) => write!(
+                    f,
+                    "transaction {} waits for a lock on {} held by transaction {}; ",
+                    wait.txn_id, resource, wait.blocked_by
+                )?,
+                None => write!(
+                    f,
+                    "transaction {} waits for transaction {}; ",
+                    wait.txn_id, wait.blocked_by
+                )?,
+            }
+        }
+        write!(f, "transaction {} was aborted", self.victim)
+    }
 }
 
 /// MVCC statistics
@@ -1379,6 +1495,77 @@ mod tests {
         assert!(victim.is_none());
     }
 
+    #[test]
+    fn test_deadlock_report_names_the_cycle() {
+        let detector = DeadlockDetector::new(true);
+
+        let report = detector
+            .report(&[1, 2, 1], |txn, _| Some(format!("users:{}", txn)))
+            .unwrap();
+        assert_eq!(report.victim, 2);
+        assert_eq!(
+            report.waits,
+            vec![
+                DeadlockWait {
+                    txn_id: 1,
+                    resource: Some("users:1".to_string()),
+                    blocked_by: 2,
+                },
+                DeadlockWait {
+                    txn_id: 2,
+                    resource: Some("users:2".to_string()),
+                    blocked_by: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "transaction 1 waits for a lock on users:1 held by transaction 2; \
+             transaction 2 waits for a lock on users:2 held by transaction 1; \
+             transaction 2 was aborted"
+        );
+        assert!(detector.report(&[], |_, _| None).is_none());
+    }
+
+    #[test]
+    fn test_deadlock_victim_gets_deadlock_error() {
+        let mvcc = MVCCManager::new(MVCCConfig {
+            deadlock_detection: true,
+            ..Default::default()
+        });
+        let t1 = mvcc
+            .begin_transaction(IsolationLevel::Serializable)
+            .unwrap();
+        let t2 = mvcc
+            .begin_transaction(IsolationLevel::Serializable)
+            .unwrap();
+        let a = RecordId {
+            table: "users".to_string(),
+            key: "a".to_string(),
+        };
+        let b = RecordId {
+            table: "users".to_string(),
+            key: "b".to_string(),
+        };
+
+        mvcc.write(&t1, a.clone(), serde_json::json!(1)).unwrap();
+        mvcc.write(&t2, b.clone(), serde_json::json!(2)).unwrap();
+        assert!(mvcc.write(&t1, b.clone(), serde_json::json!(1)).is_err());
+        assert!(mvcc.write(&t2, a.clone(), serde_json::json!(2)).is_err());
+
+        assert_eq!(mvcc.resolve_deadlocks(), vec![t2.id]);
+        match mvcc.write(&t2, b, serde_json::json!(3)) {
+            Err(DriftError::Deadlock(report)) => {
+                assert_eq!(report.victim, t2.id);
+                assert_eq!(report.waits.len(), 2);
+                let t2_wait = report.waits.iter().find(|w| w.txn_id == t2.id).unwrap();
+                assert_eq!(t2_wait.resource.as_deref(), Some("users:a"));
+                assert_eq!(t2_wait.blocked_by, t1.id);
+            }
+            other => panic!("expected a deadlock error, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_mvcc_manager_detect_deadlocks() {
         let config = MVCCConfig {
@@ -8,6 +8,7 @@
 //! - Query plan caching
 
 use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use parking_lot::RwLock;
@@ -15,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
 use crate::errors::{DriftError, Result};
+use crate::hints::{JoinHint, ScanHint};
 use crate::index_strategies::IndexType;
 use crate::query::{AsOf, Query, WhereCondition};
 
@@ -111,6 +113,21 @@ pub enum PlanStep {
         estimated_rows: usize,
         cost: f64,
     },
+    /// Full table scan split across worker threads, each replaying and
+    /// filtering a disjoint share of the rows
+    ParallelScan {
+        table: String,
+        workers: usize,
+        estimated_rows: usize,
+        cost: f64,
+    },
+    /// Scan of a columnar table's in-memory projection, filtering one
+    /// column at a time (see [`crate::storage::columnar`])
+    ColumnarScan {
+        table: String,
+        estimated_rows: usize,
+        cost: f64,
+    },
     /// Index scan with range bounds. Either bound may be `None` for a
     /// half-open range (`age > 30` with no upper limit). Bounds carry the
     /// JSON value AND inclusivity so the executor knows whether `> 30`
@@ -159,6 +176,41 @@ pub enum PlanStep {
     },
 }
 
+/// When a full table scan is split across worker threads
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParallelScanConfig {
+    /// Most workers a single scan may use; 0 disables parallel scans
+    pub max_parallel_workers: usize,
+    /// Estimated sequential scan cost above which a scan goes parallel
+    pub min_scan_cost: f64,
+    /// Fewest estimated rows worth handing to one worker
+    pub min_rows_per_worker: usize,
+}
+
+impl Default for ParallelScanConfig {
+    fn default() -> Self {
+        Self {
+            max_parallel_workers: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4),
+            min_scan_cost: 1000.0, // ~50k rows at default cost settings
+            min_rows_per_worker: 10_000,
+        }
+    }
+}
+
+impl ParallelScanConfig {
+    /// Workers to plan for a scan of `rows` rows costing `cost` when run
+    /// sequentially; `None` when it should stay sequential
+    pub fn workers_for(&self, rows: usize, cost: f64) -> Option<usize> {
+        if self.max_parallel_workers == 0 || cost < self.min_scan_cost {
+            return None;
+        }
+        let workers = (rows / self.min_rows_per_worker.max(1)).min(self.max_parallel_workers);
+        (workers > 1).then_some(workers)
+    }
+}
+
 /// Table statistics for cost estimation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableStatistics {
@@ -176,14 +228,55 @@ pub struct TableStatistics {
     pub collection_duration_ms: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ColumnStatistics {
     pub column_name: String,
     pub distinct_values: usize,
     pub null_count: usize,
+    /// Share of rows where the column is null or missing
+    #[serde(default)]
+    pub null_fraction: f64,
     pub min_value: Option<serde_json::Value>,
     pub max_value: Option<serde_json::Value>,
     pub histogram: Option<Histogram>,
+    /// Values that make up a large share of the column, most common first
+    #[serde(default)]
+    pub most_common_values: Vec<MostCommonValue>,
+}
+
+/// A value that makes up a large share of a column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MostCommonValue {
+    pub value: serde_json::Value,
+    /// Share of the column's non-null values equal to `value`
+    pub frequency: f64,
+}
+
+/// How `ANALYZE` builds column statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalyzeConfig {
+    /// Rows read per table; larger tables are sampled at random
+    pub sample_rows: usize,
+    /// Buckets per histogram
+    pub histogram_buckets: usize,
+    /// Histogram kind for numeric columns; other columns are always
+    /// equi-depth
+    pub numeric_histogram: HistogramKind,
+    /// Most common values kept per column
+    pub most_common_values: usize,
+}
+
+impl Default for AnalyzeConfig {
+    fn default() -> Self {
+        // PostgreSQL's defaults: 300 rows per histogram bucket at a
+        // statistics target of 100
+        Self {
+            sample_rows: 30_000,
+            histogram_buckets: 100,
+            numeric_histogram: HistogramKind::EquiDepth,
+            most_common_values: 100,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -198,6 +291,18 @@ pub struct IndexStatistics {
 pub struct Histogram {
     pub buckets: Vec<HistogramBucket>,
     pub bucket_count: usize,
+    #[serde(default)]
+    pub kind: HistogramKind,
+}
+
+/// How a histogram's bucket bounds are chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistogramKind {
+    /// Each bucket holds about the same number of values
+    #[default]
+    EquiDepth,
+    /// Each bucket covers an equal share of a numeric column's range
+    EquiWidth,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -210,12 +315,188 @@ pub struct HistogramBucket {
     pub distinct_count: usize,
 }
 
+impl ColumnStatistics {
+    /// Statistics for a column from a sample of a table's rows: `values`
+    /// are the sample's non-null values, `nulls` the sampled rows where
+    /// the column was null or missing, and `total_rows` the table size
+    pub fn from_sample(
+        column_name: &str,
+        mut values: Vec<serde_json::Value>,
+        nulls: usize,
+        total_rows: usize,
+        config: &AnalyzeConfig,
+    ) -> Self {
+        let sampled = values.len() + nulls;
+        let null_fraction = if sampled == 0 {
+            0.0
+        } else {
+            nulls as f64 / sampled as f64
+        };
+
+        let mut counts: HashMap<String, (usize, &serde_json::Value)> = HashMap::new();
+        for value in &values {
+            counts.entry(value.to_string()).or_insert((0, value)).0 += 1;
+        }
+        let non_null_rows = ((total_rows as f64) * (1.0 - null_fraction)).round() as usize;
+        let singletons = counts.values().filter(|(count, _)| *count == 1).count();
+        let distinct_values =
+            estimate_distinct(values.len(), counts.len(), singletons, non_null_rows);
+
+        // Keep every value when the sample saw them all; otherwise only
+        // the ones clearly more common than average, as PostgreSQL does
+        let mut common: Vec<(usize, &serde_json::Value)> = counts.into_values().collect();
+        common.sort_by_key(|c| std::cmp::Reverse(c.0));
+        if distinct_values > common.len() || common.len() > config.most_common_values {
+            let average = values.len() as f64 / common.len().max(1) as f64;
+            common.retain(|(count, _)| *count > 1 && *count as f64 > 1.25 * average);
+        }
+        common.truncate(config.most_common_values);
+        let most_common_values = common
+            .into_iter()
+            .map(|(count, value)| MostCommonValue {
+                value: value.clone(),
+                frequency: count as f64 / values.len() as f64,
+            })
+            .collect();
+
+        values.sort_by(crate::query::predicate::compare_json_values);
+        let histogram = if values.len() < config.histogram_buckets.max(1) {
+            None
+        } else if config.numeric_histogram == HistogramKind::EquiWidth
+            && values.iter().all(|v| v.is_number())
+        {
+            let numbers: Vec<f64> = values.iter().filter_map(|v| v.as_f64()).collect();
+            Histogram::equi_width(&numbers, config.histogram_buckets)
+        } else {
+            Histogram::equi_depth(&values, config.histogram_buckets)
+        };
+
+        Self {
+            column_name: column_name.to_string(),
+            distinct_values,
+            null_count: (null_fraction * total_rows as f64).round() as usize,
+            null_fraction,
+            min_value: values.first().cloned(),
+            max_value: values.last().cloned(),
+            histogram,
+            most_common_values,
+        }
+    }
+}
+
+/// Distinct values in a column of `total` non-null values, from a sample
+/// of `sampled` with `distinct` distinct values, `singletons` of which
+/// were seen once: the Haas-Stokes estimator PostgreSQL uses
+fn estimate_distinct(sampled: usize, distinct: usize, singletons: usize, total: usize) -> usize {
+    if sampled == 0 || sampled >= total {
+        return distinct;
+    }
+    let (n, d, f1, big_n) = (
+        sampled as f64,
+        distinct as f64,
+        singletons as f64,
+        total as f64,
+    );
+    let estimate = n * d / (n - f1 + f1 * n / big_n);
+    (estimate.round() as usize).clamp(distinct, total)
+}
+
+impl Histogram {
+    /// Buckets holding about the same number of values each, from values
+    /// sorted with `compare_json_values`
+    pub fn equi_depth(sorted: &[serde_json::Value], buckets: usize) -> Option<Self> {
+        let bucket_count = buckets.min(sorted.len());
+        if bucket_count == 0 {
+            return None;
+        }
+        let buckets: Vec<HistogramBucket> = (0..bucket_count)
+            .map(|i| {
+                let slice =
+                    &sorted[i * sorted.len() / bucket_count..(i + 1) * sorted.len() / bucket_count];
+                let distinct: HashSet<String> = slice.iter().map(|v| v.to_string()).collect();
+                HistogramBucket {
+                    lower_bound: slice[0].clone(),
+                    upper_bound: slice[slice.len() - 1].clone(),
+                    frequency: slice.len(),
+                    min_value: slice[0].clone(),
+                    max_value: slice[slice.len() - 1].clone(),
+                    distinct_count: distinct.len(),
+                }
+            })
+            .collect();
+        Some(Self {
+            bucket_count: buckets.len(),
+            buckets,
+            kind: HistogramKind::EquiDepth,
+        })
+    }
+
+    /// Buckets covering equal shares of the range of `values`
+    pub fn equi_width(values: &[f64], buckets: usize) -> Option<Self> {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        if values.is_empty() || buckets == 0 || !min.is_finite() || !max.is_finite() {
+            return None;
+        }
+        let bucket_count = if max > min { buckets } else { 1 };
+        let width = (max - min) / bucket_count as f64;
+
+        let mut members: Vec<Vec<f64>> = vec![Vec::new(); bucket_count];
+        for &value in values {
+            let i = if width > 0.0 {
+                (((value - min) / width) as usize).min(bucket_count - 1)
+            } else {
+                0
+            };
+            members[i].push(value);
+        }
+
+        let buckets = members
+            .into_iter()
+            .enumerate()
+            .map(|(i, members)| {
+                let lower = min + i as f64 * width;
+                let upper = if i + 1 == bucket_count {
+                    max
+                } else {
+                    lower + width
+                };
+                let distinct: HashSet<u64> = members.iter().map(|v| v.to_bits()).collect();
+                let low = members.iter().copied().fold(f64::INFINITY, f64::min);
+                let high = members.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                HistogramBucket {
+                    lower_bound: serde_json::json!(lower),
+                    upper_bound: serde_json::json!(upper),
+                    frequency: members.len(),
+                    min_value: serde_json::json!(if members.is_empty() { lower } else { low }),
+                    max_value: serde_json::json!(if members.is_empty() { upper } else { high }),
+                    distinct_count: distinct.len(),
+                }
+            })
+            .collect();
+        Some(Self {
+            buckets,
+            bucket_count,
+            kind: HistogramKind::EquiWidth,
+        })
+    }
+}
+
 /// Query optimizer
 pub struct QueryOptimizer {
     statistics: Arc<RwLock<HashMap<String, TableStatistics>>>,
-    plan_cache: Arc<RwLock<HashMap<String, QueryPlan>>>,
+    /// Cached plans, tagged with the catalog version they were planned at
+    plan_cache: Arc<RwLock<HashMap<String, (u64, QueryPlan)>>>,
+    /// Bumped on every schema or statistics change; see
+    /// [`Self::invalidate_plans`]
+    catalog_version: AtomicU64,
     cost_model: CostModel,
+    parallel_scan: RwLock<ParallelScanConfig>,
+    analyze: RwLock<AnalyzeConfig>,
     snapshot_registry: Arc<RwLock<HashMap<String, Vec<SnapshotInfo>>>>,
+    /// Tables stored `columnar`, whose scans are costed as
+    /// [`PlanStep::ColumnarScan`]
+    columnar_tables: RwLock<HashSet<String>>,
 }
 
 /// Information about available snapshots
@@ -237,19 +518,111 @@ impl QueryOptimizer {
         Self {
             statistics: Arc::new(RwLock::new(HashMap::new())),
             plan_cache: Arc::new(RwLock::new(HashMap::new())),
+            catalog_version: AtomicU64::new(0),
             cost_model: CostModel::default(),
+            parallel_scan: RwLock::new(ParallelScanConfig::default()),
+            analyze: RwLock::new(AnalyzeConfig::default()),
             snapshot_registry: Arc::new(RwLock::new(HashMap::new())),
+            columnar_tables: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Current parallel scan settings
+    pub fn parallel_scan_config(&self) -> ParallelScanConfig {
+        self.parallel_scan.read().clone()
+    }
+
+    /// Change when scans run in parallel. Cached plans were made under the
+    /// old settings, so they're re-planned.
+    pub fn set_parallel_scan_config(&self, config: ParallelScanConfig) {
+        *self.parallel_scan.write() = config;
+        self.invalidate_plans();
+    }
+
+    /// Current `ANALYZE` settings
+    pub fn analyze_config(&self) -> AnalyzeConfig {
+        self.analyze.read().clone()
+    }
+
+    /// Change how `ANALYZE` samples tables and builds statistics. Takes
+    /// effect at the next `ANALYZE`.
+    pub fn set_analyze_config(&self, config: AnalyzeConfig) {
+        *self.analyze.write() = config;
+    }
+
+    /// The current catalog version. Plans cached at an older version are
+    /// stale and get re-planned.
+    pub fn catalog_version(&self) -> u64 {
+        self.catalog_version.load(Ordering::Acquire)
+    }
+
+    /// Mark every cached plan stale after a schema or statistics change
+    pub fn invalidate_plans(&self) {
+        self.catalog_version.fetch_add(1, Ordering::AcqRel);
+        self.plan_cache.write().clear();
+    }
+
+    /// Forget everything known about a dropped table, so a new table of
+    /// the same name isn't planned with the old one's indexes and stats
+    pub fn forget_table(&self, table: &str) {
+        self.statistics.write().remove(table);
+        self.columnar_tables.write().remove(table);
+        self.invalidate_plans();
+    }
+
+    /// Record whether `table` is stored columnar, re-planning its scans
+    pub fn set_columnar(&self, table: &str, columnar: bool) {
+        let changed = if columnar {
+            self.columnar_tables.write().insert(table.to_string())
+        } else {
+            self.columnar_tables.write().remove(table)
+        };
+        if changed {
+            self.invalidate_plans();
+        }
+    }
+
+    fn is_columnar(&self, table: &str) -> bool {
+        self.columnar_tables.read().contains(table)
+    }
+
+    /// The full scan of `table`: its columnar projection if it has one,
+    /// otherwise a replay of its rows
+    fn full_scan_step(&self, table: &str, estimated_rows: usize) -> PlanStep {
+        if self.is_columnar(table) {
+            PlanStep::ColumnarScan {
+                table: table.to_string(),
+                estimated_rows,
+                cost: self.cost_model.columnar_scan_cost(estimated_rows),
+            }
+        } else {
+            PlanStep::TableScan {
+                table: table.to_string(),
+                estimated_rows,
+                cost: self.cost_model.table_scan_cost(estimated_rows),
+            }
         }
     }
 
     /// Optimize a query and produce execution plan
     #[instrument(skip(self))]
     pub fn optimize(&self, query: &Query) -> Result<QueryPlan> {
-        // Check plan cache
+        // Check plan cache. A plan made before the last schema or
+        // statistics change may no longer be the best one, or valid at all.
+        // Hinted plans are made for one statement and never cached.
         let cache_key = self.query_cache_key(query);
-        if let Some(cached_plan) = self.plan_cache.read().get(&cache_key) {
-            debug!("Using cached query plan");
-            return Ok(cached_plan.clone());
+        let version = self.catalog_version();
+        let hinted = matches!(
+            query,
+            Query::Select { table, .. } if crate::hints::scan_hint(table).is_some()
+        );
+        if !hinted {
+            if let Some((planned_at, cached_plan)) = self.plan_cache.read().get(&cache_key) {
+                if *planned_at == version {
+                    debug!("Using cached query plan");
+                    return Ok(cached_plan.clone());
+                }
+            }
         }
 
         let plan = match query {
@@ -272,8 +645,10 @@ impl QueryOptimizer {
         }?;
 
         // Cache the plan if it's cacheable
-        if plan.cacheable {
-            self.plan_cache.write().insert(cache_key, plan.clone());
+        if plan.cacheable && !hinted {
+            self.plan_cache
+                .write()
+                .insert(cache_key, (version, plan.clone()));
         }
 
         Ok(plan)
@@ -308,7 +683,12 @@ impl QueryOptimizer {
 
         // Step 2: Choose access method (index vs table scan)
         let access_plans = self.generate_access_plans(table, conditions);
-        let best_access = self.choose_best_plan(&access_plans);
+        let best_access = match crate::hints::scan_hint(table) {
+            Some((hint, scan)) => self
+                .choose_hinted_plan(table, &access_plans, hint, &scan)
+                .or_else(|| self.choose_best_plan(&access_plans)),
+            None => self.choose_best_plan(&access_plans),
+        };
 
         if let Some(plan) = best_access {
             uses_index = matches!(
@@ -321,13 +701,9 @@ impl QueryOptimizer {
             steps.push(plan);
         } else {
             // Fallback to table scan
-            let scan_cost = self.cost_model.table_scan_cost(estimated_rows);
-            steps.push(PlanStep::TableScan {
-                table: table.to_string(),
-                estimated_rows,
-                cost: scan_cost,
-            });
-            estimated_cost += scan_cost;
+            let scan = self.full_scan_step(table, estimated_rows);
+            estimated_cost += self.cost_of_step(&scan);
+            steps.push(scan);
         }
 
         // Step 3: Apply remaining filters in the order the optimizer chose.
@@ -472,13 +848,25 @@ impl QueryOptimizer {
             }
         }
 
-        // Always consider table scan as fallback
+        // Always consider table scan as fallback, and splitting it across
+        // workers when it's expensive enough to be worth the coordination.
+        // A columnar table's projection is scanned in place, never split.
         let scan_rows = self.estimate_table_rows(table);
-        plans.push(PlanStep::TableScan {
-            table: table.to_string(),
-            estimated_rows: scan_rows,
-            cost: self.cost_model.table_scan_cost(scan_rows),
-        });
+        let scan = self.full_scan_step(table, scan_rows);
+        let scan_cost = self.cost_of_step(&scan);
+        let columnar = matches!(scan, PlanStep::ColumnarScan { .. });
+        plans.push(scan);
+        if columnar {
+            return plans;
+        }
+        if let Some(workers) = self.parallel_scan.read().workers_for(scan_rows, scan_cost) {
+            plans.push(PlanStep::ParallelScan {
+                table: table.to_string(),
+                workers,
+                estimated_rows: scan_rows,
+                cost: self.cost_model.parallel_scan_cost(scan_rows, workers),
+            });
+        }
 
         plans
     }
@@ -497,6 +885,87 @@ impl QueryOptimizer {
             .cloned()
     }
 
+    /// The cheapest plan a scan hint allows, or `None` with a notice when
+    /// it allows none of them
+    fn choose_hinted_plan(
+        &self,
+        table: &str,
+        plans: &[PlanStep],
+        hint: usize,
+        scan: &ScanHint,
+    ) -> Option<PlanStep> {
+        crate::hints::settle(hint);
+        let allowed: Vec<PlanStep> = match scan {
+            ScanHint::Seq => plans
+                .iter()
+                .filter(|p| {
+                    matches!(
+                        p,
+                        PlanStep::TableScan { .. }
+                            | PlanStep::ParallelScan { .. }
+                            | PlanStep::ColumnarScan { .. }
+                    )
+                })
+                .cloned()
+                .collect(),
+            ScanHint::Index(names) => {
+                let columns = match self.hinted_index_columns(table, names) {
+                    Ok(columns) => columns,
+                    Err(unknown) => {
+                        crate::hints::notice(format!(
+                            "hint {} ignored: table \"{}\" has no index \"{}\"",
+                            crate::hints::describe(hint),
+                            table,
+                            unknown
+                        ));
+                        return None;
+                    }
+                };
+                plans
+                    .iter()
+                    .filter(|p| match p {
+                        PlanStep::IndexScan { index, .. } | PlanStep::IndexLookup { index, .. } => {
+                            columns.is_empty() || columns.contains(index)
+                        }
+                        _ => false,
+                    })
+                    .cloned()
+                    .collect()
+            }
+        };
+        if allowed.is_empty() {
+            crate::hints::notice(format!(
+                "hint {} ignored: no condition in the query can use the index",
+                crate::hints::describe(hint)
+            ));
+        }
+        self.choose_best_plan(&allowed)
+    }
+
+    /// The indexed columns a scan hint names, by column or as
+    /// `idx_<table>_<column>`. The first name that isn't an index of
+    /// `table` is the error.
+    fn hinted_index_columns(
+        &self,
+        table: &str,
+        names: &[String],
+    ) -> std::result::Result<Vec<String>, String> {
+        let stats = self.statistics.read();
+        let indexed = stats.get(table).map(|s| &s.index_stats);
+        let (_, bare_table) = crate::search_path::split_storage_name(table);
+        let prefix = format!("idx_{}_", bare_table);
+        names
+            .iter()
+            .map(|name| {
+                let column = name.strip_prefix(&prefix).unwrap_or(name);
+                indexed
+                    .filter(|indexed| indexed.contains_key(column))
+                    .map(|_| column.to_string())
+                    .ok_or_else(|| name.clone())
+            })
+            .collect()
+    }
+
     /// Plan time travel operations
     fn plan_time_travel(&self, table: &str, as_of: &AsOf) -> (Option<PlanStep>, Option<PlanStep>) {
         match as_of {
@@ -550,34 +1019,15 @@ impl QueryOptimizer {
 
         if let Some(table_stats) = stats.get(table) {
             if let Some(col_stats) = table_stats.column_stats.get(&condition.column) {
-                // Account for nulls
-                let null_fraction = if table_stats.row_count > 0 {
-                    col_stats.null_count as f64 / table_stats.row_count as f64
-                } else {
-                    0.0
-                };
+                let null_fraction = col_stats.null_fraction;
 
                 // Use statistics to estimate selectivity
                 let non_null_selectivity = match condition.operator.as_str() {
-                    "=" => {
-                        // Point-query selectivity: uniform-distribution
-                        // estimate, 1 / distinct_values. Histograms
-                        // are NOT consulted here even when present —
-                        // equi-depth histograms record bucket frequency
-                        // (≈ total_rows / bucket_count), which doesn't
-                        // depend on cardinality, so they can't
-                        // distinguish a 2-distinct-value column from a
-                        // 1000-distinct one. Use the column's
-                        // distinct_values directly. PostgreSQL uses
-                        // `most_common_values` for the same purpose;
-                        // we don't track MCVs yet, so falling back to
-                        // uniform-distribution is the right move.
-                        if col_stats.distinct_values > 0 {
-                            1.0 / col_stats.distinct_values as f64
-                        } else {
-                            0.1 // Default
-                        }
-                    }
+                    "=" => self.estimate_equality_selectivity(
+                        col_stats,
+                        &condition.value,
+                        table_stats.row_count,
+                    ),
                     "<" | ">" | "<=" | ">=" => {
                         // Range query selectivity using min/max or histogram
                         if let Some(histogram) = &col_stats.histogram {
@@ -585,7 +1035,6 @@ impl QueryOptimizer {
                                 &condition.value,
                                 &condition.operator,
                                 histogram,
-                                table_stats.row_count,
                             )
                         } else if col_stats.min_value.is_some() && col_stats.max_value.is_some() {
                             self.estimate_range_selectivity_with_bounds(
@@ -598,8 +1047,48 @@ impl QueryOptimizer {
                             0.3 // Default 30% selectivity for range
                         }
                     }
+                    "!=" | "<>" => {
+                        1.0 - self.estimate_equality_selectivity(
+                            col_stats,
+                            &condition.value,
+                            table_stats.row_count,
+                        )
+                    }
+                    "IN" | "NOT IN" => {
+                        // Each listed value as an equality, NULLs excepted
+                        let listed: f64 = condition
+                            .value
+                            .as_array()
+                            .map(|values| {
+                                values
+                                    .iter()
+                                    .filter(|v| !v.is_null())
+                                    .map(|v| {
+                                        self.estimate_equality_selectivity(
+                                            col_stats,
+                                            v,
+                                            table_stats.row_count,
+                                        )
+                                    })
+                                    .sum()
+                            })
+                            .unwrap_or(0.0);
+                        let has_null = condition
+                            .value
+                            .as_array()
+                            .is_some_and(|values| values.iter().any(|v| v.is_null()));
+                        match condition.operator.as_str() {
+                            "IN" => listed.min(1.0),
+                            // A NULL in the list matches nothing
+                            _ if has_null => 0.0,
+                            _ => 1.0 - listed.min(1.0),
+                        }
+                    }
                     "IS NULL" => null_fraction,
                     "IS NOT NULL" => 1.0 - null_fraction,
+                    op if crate::query::predicate::PatternKind::from_operator(op).is_some() => {
+                        Self::default_selectivity(op)
+                    }
                     _ => 0.5, // Default 50% for unknown operators
                 };
 
@@ -610,21 +1099,53 @@ impl QueryOptimizer {
                     non_null_selectivity
                 }
             } else {
-                0.3 // No statistics, use default
+                Self::default_selectivity(&condition.operator) // No statistics
             }
         } else {
-            0.3 // No table statistics
+            Self::default_selectivity(&condition.operator) // No table statistics
         }
     }
 
-    // `estimate_equality_selectivity_with_histogram` lived here until
-    // slice 9. Slice 8's analysis showed equi-depth histograms can't
-    // distinguish cardinality for equality queries — every bucket
-    // holds ~total_rows / bucket_count rows by construction, so the
-    // estimate didn't depend on the column's actual cardinality.
-    // Equality now uses `1 / distinct_values` directly. PostgreSQL
-    // solves the same problem with most_common_values (MCV) lists;
-    // adding MCV tracking is a future slice.
+    /// Selectivity assumed without statistics to go on. Patterns are
+    /// usually written to pick out a few rows, so a match is assumed rarer
+    /// than an equality or range.
+    fn default_selectivity(operator: &str) -> f64 {
+        match crate::query::predicate::PatternKind::from_operator(operator) {
+            Some((_, false)) => 0.05,
+            Some((_, true)) => 0.95,
+            None => 0.3,
+        }
+    }
+
+    /// Selectivity of `column = value` among non-null values. A most
+    /// common value's frequency is known; the other values share what the
+    /// most common ones leave evenly. Histograms can't help here: an
+    /// equi-depth bucket holds about the same number of rows whatever the
+    /// column's cardinality.
+    fn estimate_equality_selectivity(
+        &self,
+        col_stats: &ColumnStatistics,
+        value: &serde_json::Value,
+        row_count: usize,
+    ) -> f64 {
+        let mcvs = &col_stats.most_common_values;
+        if let Some(mcv) = mcvs
+            .iter()
+            .find(|m| self.compare_values(&m.value, value) == 0)
+        {
+            return mcv.frequency;
+        }
+        if col_stats.distinct_values == 0 {
+            return 0.1; // Default
+        }
+        if mcvs.is_empty() {
+            return 1.0 / col_stats.distinct_values as f64;
+        }
+        let remaining: f64 = 1.0 - mcvs.iter().map(|m| m.frequency).sum::<f64>();
+        let others = col_stats.distinct_values.saturating_sub(mcvs.len()).max(1);
+        // Never estimate no rows at all; the value may have arrived since
+        (remaining.max(0.0) / others as f64).max(1.0 / row_count.max(1) as f64)
+    }
 
     /// Estimate selectivity for range queries using histogram
     fn estimate_range_selectivity_with_histogram(
@@ -632,7 +1153,6 @@ impl QueryOptimizer {
         value: &serde_json::Value,
         operator: &str,
         histogram: &Histogram,
-        total_rows: usize,
     ) -> f64 {
         let mut matching_frequency = 0;
 
@@ -674,7 +1194,13 @@ impl QueryOptimizer {
             }
         }
 
-        matching_frequency as f64 / total_rows as f64
+        // Frequencies count sampled values, so compare them to the
+        // histogram's total rather than the table's row count
+        let total: usize = histogram.buckets.iter().map(|b| b.frequency).sum();
+        if total == 0 {
+            return 0.3;
+        }
+        matching_frequency as f64 / total as f64
     }
 
     /// Estimate range selectivity using min/max bounds
@@ -820,6 +1346,39 @@ impl QueryOptimizer {
 
         const NL_THRESHOLD: usize = 1000;
 
+        let hash_build_side = match join_type {
+            JoinType::LeftOuter | JoinType::FullOuter => JoinSide::Right,
+            JoinType::Inner => {
+                if left_rows < right_rows {
+                    JoinSide::Left
+                } else {
+                    JoinSide::Right
+                }
+            }
+        };
+
+        // A `HashJoin(a b)` or `NestLoop(a b)` hint overrides the rules
+        if let Some((hint, method)) = crate::hints::join_hint(left_table, right_table) {
+            crate::hints::settle(hint);
+            return match method {
+                JoinHint::Hash => PlanNode::HashJoin {
+                    left,
+                    right,
+                    condition,
+                    build_side: hash_build_side,
+                    join_type,
+                    cost: Cost::seq_scan(1.0, (left_rows + right_rows) as f64),
+                },
+                JoinHint::NestLoop => PlanNode::NestedLoopJoin {
+                    left,
+                    right,
+                    condition,
+                    join_type,
+                    cost: Cost::seq_scan(1.0, (left_rows * right_rows.max(1)) as f64),
+                },
+            };
+        }
+
         // OUTER joins constrain the hash-build side: LEFT and FULL must
         // build on the RIGHT (preserving side probes; build side is the
         // one we may need to mark "matched" for unmatched-row emission).
@@ -850,21 +1409,11 @@ impl QueryOptimizer {
         // joins force build_side = Right (need to probe with the
         // preserving side and mark matches for FULL OUTER).
         if left_rows > NL_THRESHOLD || right_rows > NL_THRESHOLD {
-            let build_side = match join_type {
-                JoinType::LeftOuter | JoinType::FullOuter => JoinSide::Right,
-                JoinType::Inner => {
-                    if left_rows < right_rows {
-                        JoinSide::Left
-                    } else {
-                        JoinSide::Right
-                    }
-                }
-            };
             return PlanNode::HashJoin {
                 left,
                 right,
                 condition,
-                build_side,
+                build_side: hash_build_side,
                 join_type,
                 cost: Cost::seq_scan(1.0, (left_rows + right_rows) as f64),
             };
@@ -900,7 +1449,7 @@ impl QueryOptimizer {
             "=" | "==" | "!=" | "<>" => 2,
             "<" | "<=" | ">" | ">=" => 3,
             "IN" | "NOT IN" => 4,
-            "LIKE" => 5,
+            "LIKE" | "NOT LIKE" | "ILIKE" | "NOT ILIKE" => 5,
             _ => 6,
         }
     }
@@ -945,6 +1494,8 @@ impl QueryOptimizer {
     fn cost_of_step(&self, step: &PlanStep) -> f64 {
         match step {
             PlanStep::TableScan { cost, .. } => *cost,
+            PlanStep::ParallelScan { cost, .. } => *cost,
+            PlanStep::ColumnarScan { cost, .. } => *cost,
             PlanStep::IndexScan { cost, .. } => *cost,
             PlanStep::IndexLookup { cost, .. } => *cost,
             PlanStep::Filter { cost, .. } => *cost,
@@ -960,6 +1511,8 @@ impl QueryOptimizer {
     fn rows_after_step(&self, step: &PlanStep, input_rows: usize) -> usize {
         match step {
             PlanStep::TableScan { estimated_rows, .. } => *estimated_rows,
+            PlanStep::ParallelScan { estimated_rows, .. } => *estimated_rows,
+            PlanStep::ColumnarScan { estimated_rows, .. } => *estimated_rows,
             PlanStep::IndexScan { estimated_rows, .. } => *estimated_rows,
             PlanStep::IndexLookup { estimated_rows, .. } => *estimated_rows,
             PlanStep::Filter { selectivity, .. } => (input_rows as f64 * selectivity) as usize,
@@ -995,15 +1548,72 @@ impl QueryOptimizer {
         format!("{:?}", query) // Simple serialization
     }
 
-    /// Update table statistics. Also clears the plan cache: cached
-    /// plans were computed against the prior `column_stats` /
-    /// `row_count`, so post-ANALYZE the same query may want a
-    /// different access method or filter order. `register_table_indexes`
-    /// already invalidates here for the same reason; this is the
-    /// missing counterpart for the ANALYZE-driven entry point.
+    /// Update table statistics. Also invalidates cached plans: they
+    /// were computed against the prior `column_stats` / `row_count`, so
+    /// post-ANALYZE the same query may want a different access method or
+    /// filter order. `register_table_indexes` invalidates for the same
+    /// reason.
     pub fn update_statistics(&self, table: &str, stats: TableStatistics) {
         self.statistics.write().insert(table.to_string(), stats);
-        self.plan_cache.write().clear();
+        self.invalidate_plans();
+    }
+
+    /// A copy of the statistics held for a table
+    pub fn table_statistics(&self, table: &str) -> Option<TableStatistics> {
+        self.statistics.read().get(table).cloned()
+    }
+
+    /// Widen the analyzed columns' min/max to take in `row`, just written
+    /// to `table`, so values appended since `ANALYZE` aren't estimated as
+    /// out of range. Cached plans keep their estimates until the next
+    /// `ANALYZE`.
+    pub fn widen_column_ranges(&self, table: &str, row: &serde_json::Value) {
+        use crate::query::predicate::compare_json_values;
+        use std::cmp::Ordering;
+
+        let Some(fields) = row.as_object() else {
+            return;
+        };
+        let below = |stats: &ColumnStatistics, value: &serde_json::Value| {
+            stats
+                .min_value
+                .as_ref()
+                .is_none_or(|min| compare_json_values(value, min) == Ordering::Less)
+        };
+        let above = |stats: &ColumnStatistics, value: &serde_json::Value| {
+            stats
+                .max_value
+                .as_ref()
+                .is_none_or(|max| compare_json_values(value, max) == Ordering::Greater)
+        };
+        let widened = |table_stats: &TableStatistics| {
+            fields.iter().any(|(column, value)| {
+                !value.is_null()
+                    && table_stats
+                        .column_stats
+                        .get(column)
+                        .is_some_and(|stats| below(stats, value) || above(stats, value))
+            })
+        };
+        if !self.statistics.read().get(table).is_some_and(widened) {
+            return;
+        }
+
+        let mut statistics = self.statistics.write();
+        let Some(table_stats) = statistics.get_mut(table) else {
+            return;
+        };
+        for (column, value) in fields.iter().filter(|(_, value)| !value.is_null()) {
+            let Some(stats) = table_stats.column_stats.get_mut(column) else {
+                continue;
+            };
+            if below(stats, value) {
+                stats.min_value = Some(value.clone());
+            }
+            if above(stats, value) {
+                stats.max_value = Some(value.clone());
+            }
+        }
     }
 
     /// Register the set of indexed columns for a table so the planner can
@@ -1063,7 +1673,8 @@ impl QueryOptimizer {
                 });
         }
         // A registration change invalidates cached plans for this table.
-        self.plan_cache.write().clear();
+        drop(stats_map);
+        self.invalidate_plans();
     }
 
     /// Clear plan cache
@@ -1107,6 +1718,8 @@ impl QueryOptimizer {
         for step in &plan.steps {
             match step {
                 PlanStep::TableScan { estimated_rows, .. }
+                | PlanStep::ParallelScan { estimated_rows, .. }
+                | PlanStep::ColumnarScan { estimated_rows, .. }
                 | PlanStep::IndexScan { estimated_rows, .. } => {
                     // Assume average row size of 1KB
                     memory = memory.max(estimated_rows * 1024);
@@ -1153,6 +1766,21 @@ impl CostModel {
         self.seq_page_cost * pages as f64 + self.cpu_tuple_cost * rows as f64
     }
 
+    /// A scan of a columnar projection: nothing is read from segments, and
+    /// each row costs a comparison on the columns filtered rather than a
+    /// whole row rebuilt from events
+    pub fn columnar_scan_cost(&self, rows: usize) -> f64 {
+        self.cpu_operator_cost * rows as f64
+    }
+
+    /// A table scan shared by `workers`, plus gathering their rows back.
+    /// Whether it's worth starting workers at all is decided by
+    /// [`ParallelScanConfig::min_scan_cost`] before this is compared.
+    pub fn parallel_scan_cost(&self, rows: usize, workers: usize) -> f64 {
+        let workers = workers.max(1) as f64;
+        self.table_scan_cost(rows) / workers + self.cpu_tuple_cost * 0.1 * rows as f64
+    }
+
     pub fn index_scan_cost(&self, rows: usize) -> f64 {
         let pages = (rows / 200).max(1); // More rows per index page
         self.random_page_cost * pages as f64 + self.cpu_tuple_cost * rows as f64
@@ -1276,6 +1904,14 @@ pub enum PlanNode {
     },
     /// Materialize (force materialization point)
     Materialize { input: Box<PlanNode>, cost: Cost },
+    /// Concatenate the scans of a partitioned table's partitions
+    Append { inputs: Vec<PlanNode>, cost: Cost },
+    /// Collect the rows of a scan split across parallel workers
+    Gather {
+        input: Box<PlanNode>,
+        workers: usize,
+        cost: Cost,
+    },
     /// DISTINCT — duplicate elimination on the input's projection.
     Distinct {
         input: Box<PlanNode>,
@@ -1419,6 +2055,102 @@ fn default_join_type() -> JoinType {
 pub struct SortKey {
     pub column: String,
     pub ascending: bool,
+    /// NULLs (and missing columns) sort before every value
+    #[serde(default)]
+    pub nulls_first: bool,
+    #[serde(default)]
+    pub collation: Collation,
+}
+
+impl SortKey {
+    /// Without `NULLS FIRST`/`NULLS LAST`, NULLs sort as larger than any
+    /// value, as in PostgreSQL: last ascending, first descending.
+    pub fn new(
+        column: impl Into<String>,
+        ascending: bool,
+        nulls_first: Option<bool>,
+        collation: Collation,
+    ) -> Self {
+        Self {
+            column: column.into(),
+            ascending,
+            nulls_first: nulls_first.unwrap_or(!ascending),
+            collation,
+        }
+    }
+
+    /// Order two rows' values for this key; `None` is a missing column
+    pub fn compare(
+        &self,
+        a: Option<&serde_json::Value>,
+        b: Option<&serde_json::Value>,
+    ) -> std::cmp::Ordering {
+        self.compare_by(a, b, |a, b| self.collation.compare(a, b))
+    }
+
+    /// [`SortKey::compare`] with `values` ordering two non-NULL values
+    /// ascending, for columns whose order isn't their values' (enums).
+    pub fn compare_by(
+        &self,
+        a: Option<&serde_json::Value>,
+        b: Option<&serde_json::Value>,
+        values: impl FnOnce(&serde_json::Value, &serde_json::Value) -> std::cmp::Ordering,
+    ) -> std::cmp::Ordering {
+        use std::cmp::Ordering as Order;
+        let a = a.filter(|v| !v.is_null());
+        let b = b.filter(|v| !v.is_null());
+        match (a, b) {
+            (None, None) => Order::Equal,
+            (None, Some(_)) if self.nulls_first => Order::Less,
+            (None, Some(_)) => Order::Greater,
+            (Some(_), None) if self.nulls_first => Order::Greater,
+            (Some(_), None) => Order::Less,
+            (Some(a), Some(b)) if self.ascending => values(a, b),
+            (Some(a), Some(b)) => values(a, b).reverse(),
+        }
+    }
+}
+
+/// How a sort key compares text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Collation {
+    /// Code point order, like PostgreSQL's "C" collation
+    #[default]
+    Binary,
+    /// Letters compare without regard to case
+    CaseInsensitive,
+}
+
+impl Collation {
+    /// The collation `COLLATE "name"` asks for
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "c" | "posix" | "default" => Ok(Collation::Binary),
+            "case_insensitive" | "nocase" => Ok(Collation::CaseInsensitive),
+            _ => Err(DriftError::InvalidQuery(format!(
+                "collation \"{}\" does not exist",
+                name
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Collation::Binary => "C",
+            Collation::CaseInsensitive => "case_insensitive",
+        }
+    }
+
+    /// Order two non-NULL values; only text is affected by the collation
+    pub fn compare(&self, a: &serde_json::Value, b: &serde_json::Value) -> std::cmp::Ordering {
+        match (self, a.as_str(), b.as_str()) {
+            (Collation::CaseInsensitive, Some(a), Some(b)) => a
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b.chars().flat_map(char::to_lowercase)),
+            _ => crate::query::predicate::compare_json_values(a, b),
+        }
+    }
 }
 
 /// Aggregate function
@@ -1477,8 +2209,7 @@ struct CostParameters {
     #[allow(dead_code)]
     cpu_tuple_cost: f64,
     cpu_operator_cost: f64,
-    #[allow(dead_code)]
-    parallel_workers: usize,
+    parallel_scan: ParallelScanConfig,
     work_mem: usize, // KB
 }
 
@@ -1489,8 +2220,8 @@ impl Default for CostParameters {
             random_page_cost: 4.0,
             cpu_tuple_cost: 0.01,
             cpu_operator_cost: 0.0025,
-            parallel_workers: 4,
-            work_mem: 4096, // 4MB
+            parallel_scan: ParallelScanConfig::default(),
+            work_mem: crate::spill::DEFAULT_WORK_MEM / 1024,
         }
     }
 }
@@ -1506,6 +2237,7 @@ struct OptimizerStats {
     #[allow(dead_code)]
     joins_reordered: u64,
     indexes_used: u64,
+    parallel_scans_planned: u64,
 }
 
 impl Default for CostOptimizer {
@@ -1941,10 +2673,103 @@ impl CostOptimizer {
         Ok(plan)
     }
 
-    /// Plan parallel execution
+    /// Split table scans that are expensive enough across parallel
+    /// workers, under a `Gather` that merges their rows
     fn plan_parallel_execution(&self, plan: PlanNode) -> Result<PlanNode> {
-        // Add parallel scan/join nodes where beneficial
-        Ok(plan)
+        let parallel = |input: Box<PlanNode>| self.plan_parallel_execution(*input).map(Box::new);
+        Ok(match plan {
+            PlanNode::TableScan {
+                table,
+                predicates,
+                cost,
+            } => {
+                let workers = self
+                    .params
+                    .parallel_scan
+                    .workers_for(cost.rows as usize, cost.total());
+                let scan = PlanNode::TableScan {
+                    table,
+                    predicates,
+                    cost,
+                };
+                match workers {
+                    Some(workers) => {
+                        self.stats.write().parallel_scans_planned += 1;
+                        PlanNode::Gather {
+                            input: Box::new(scan),
+                            workers,
+                            cost: Cost {
+                                io_cost: cost.io_cost / workers as f64,
+                                cpu_cost: cost.cpu_cost / workers as f64,
+                                ..cost
+                            },
+                        }
+                    }
+                    None => scan,
+                }
+            }
+            PlanNode::Filter {
+                input,
+                predicates,
+                cost,
+            } => PlanNode::Filter {
+                input: parallel(input)?,
+                predicates,
+                cost,
+            },
+            PlanNode::Project {
+                input,
+                columns,
+                cost,
+            } => PlanNode::Project {
+                input: parallel(input)?,
+                columns,
+                cost,
+            },
+            PlanNode::Sort { input, keys, cost } => PlanNode::Sort {
+                input: parallel(input)?,
+                keys,
+                cost,
+            },
+            PlanNode::Aggregate {
+                input,
+                group_by,
+                aggregates,
+                cost,
+            } => PlanNode::Aggregate {
+                input: parallel(input)?,
+                group_by,
+                aggregates,
+                cost,
+            },
+            PlanNode::Limit {
+                input,
+                limit,
+                offset,
+                cost,
+            } => PlanNode::Limit {
+                input: parallel(input)?,
+                limit,
+                offset,
+                cost,
+            },
+            PlanNode::HashJoin {
+                left,
+                right,
+                condition,
+                build_side,
+                join_type,
+                cost,
+            } => PlanNode::HashJoin {
+                left: parallel(left)?,
+                right: parallel(right)?,
+                condition,
+                build_side,
+                join_type,
+                cost,
+            },
+            other => other,
+        })
     }
 
     /// Estimate cost of a plan node
@@ -1954,7 +2779,8 @@ impl CostOptimizer {
             | PlanNode::IndexScan { cost, .. }
             | PlanNode::HashJoin { cost, .. }
             | PlanNode::NestedLoopJoin { cost, .. }
-            | PlanNode::SortMergeJoin { cost, .. } => Ok(*cost),
+            | PlanNode::SortMergeJoin { cost, .. }
+            | PlanNode::Gather { cost, .. } => Ok(*cost),
             _ => Ok(Cost::default()),
         }
     }
@@ -2033,6 +2859,7 @@ impl CostOptimizer {
             | PlanNode::Aggregate { input, .. }
             | PlanNode::Limit { input, .. }
             | PlanNode::Materialize { input, .. }
+            | PlanNode::Gather { input, .. }
             | PlanNode::Distinct { input, .. } => {
                 self.extract_joins_recursive(input, tables, joins);
             }
@@ -2040,6 +2867,11 @@ impl CostOptimizer {
                 self.extract_joins_recursive(left, tables, joins);
                 self.extract_joins_recursive(right, tables, joins);
             }
+            PlanNode::Append { inputs, .. } => {
+                for input in inputs {
+                    self.extract_joins_recursive(input, tables, joins);
+                }
+            }
         }
     }
 
@@ -2070,9 +2902,15 @@ impl CostOptimizer {
             | PlanNode::Aggregate { input, .. }
             | PlanNode::Limit { input, .. }
             | PlanNode::Materialize { input, .. }
+            | PlanNode::Gather { input, .. }
             | PlanNode::Distinct { input, .. } => {
                 self.collect_tables_recursive(input, tables);
             }
+            PlanNode::Append { inputs, .. } => {
+                for input in inputs {
+                    self.collect_tables_recursive(input, tables);
+                }
+            }
         }
     }
 
@@ -2437,9 +3275,11 @@ mod tests {
                 column_name: "status".to_string(),
                 distinct_values: 3,
                 null_count: 0,
+                null_fraction: 0.0,
                 min_value: None,
                 max_value: None,
                 histogram: None,
+                most_common_values: Vec::new(),
             },
         );
 
@@ -2472,14 +3312,232 @@ mod tests {
         assert!(plan.estimated_cost > 0.0);
     }
 
+    #[test]
+    fn test_histograms() {
+        let values: Vec<serde_json::Value> = (0..1000).map(|i| serde_json::json!(i)).collect();
+        let depth = Histogram::equi_depth(&values, 100).unwrap();
+        assert_eq!(depth.kind, HistogramKind::EquiDepth);
+        assert_eq!(depth.bucket_count, 100);
+        assert!(depth.buckets.iter().all(|b| b.frequency == 10));
+        assert_eq!(depth.buckets[99].upper_bound, serde_json::json!(999));
+
+        // Skewed: most values in the first tenth of the range
+        let numbers: Vec<f64> = (0..1000)
+            .map(|i| if i < 900 { (i % 10) as f64 } else { i as f64 })
+            .collect();
+        let width = Histogram::equi_width(&numbers, 10).unwrap();
+        assert_eq!(width.kind, HistogramKind::EquiWidth);
+        assert_eq!(width.buckets[0].frequency, 900);
+        assert_eq!(width.buckets[0].distinct_count, 10);
+        let total: usize = width.buckets.iter().map(|b| b.frequency).sum();
+        assert_eq!(total, 1000);
+
+        let constant = Histogram::equi_width(&[5.0, 5.0], 10).unwrap();
+        assert_eq!(constant.bucket_count, 1);
+    }
+
+    #[test]
+    fn test_column_statistics_from_sample() {
+        let config = AnalyzeConfig {
+            histogram_buckets: 10,
+            most_common_values: 200,
+            ..AnalyzeConfig::default()
+        };
+        // 600 'paid', 300 'open', then 100 unique refunds, and 100 nulls
+        let mut values: Vec<serde_json::Value> = Vec::new();
+        values.extend((0..600).map(|_| serde_json::json!("paid")));
+        values.extend((0..300).map(|_| serde_json::json!("open")));
+        values.extend((0..100).map(|i| serde_json::json!(format!("refund-{}", i))));
+
+        let stats = ColumnStatistics::from_sample("status", values, 100, 1100, &config);
+        assert_eq!(stats.distinct_values, 102);
+        assert!((stats.null_fraction - 1.0 / 11.0).abs() < 1e-9);
+        assert_eq!(stats.null_count, 100);
+        // All values were seen, so every one is listed
+        assert_eq!(stats.most_common_values.len(), 102);
+        assert_eq!(stats.most_common_values[0].value, serde_json::json!("paid"));
+        assert!((stats.most_common_values[0].frequency - 0.6).abs() < 1e-9);
+        assert_eq!(stats.histogram.unwrap().bucket_count, 10);
+
+        // The same sample out of a much larger table: the unique values
+        // suggest many more unseen ones, so only the common ones are kept
+        let values: Vec<serde_json::Value> = (0..1000)
+            .map(|i| match i {
+                0..=599 => serde_json::json!("paid"),
+                600..=899 => serde_json::json!("open"),
+                _ => serde_json::json!(format!("refund-{}", i)),
+            })
+            .collect();
+        let stats = ColumnStatistics::from_sample("status", values, 0, 100_000, &config);
+        assert!(stats.distinct_values > 102, "{}", stats.distinct_values);
+        assert_eq!(stats.most_common_values.len(), 2);
+    }
+
+    #[test]
+    fn test_equality_selectivity_uses_most_common_values() {
+        let optimizer = QueryOptimizer::new();
+        let stats = ColumnStatistics {
+            column_name: "status".to_string(),
+            distinct_values: 12,
+            most_common_values: vec![
+                MostCommonValue {
+                    value: serde_json::json!("paid"),
+                    frequency: 0.7,
+                },
+                MostCommonValue {
+                    value: serde_json::json!("open"),
+                    frequency: 0.2,
+                },
+            ],
+            ..ColumnStatistics::default()
+        };
+        let estimate = |v| optimizer.estimate_equality_selectivity(&stats, &v, 10_000);
+        assert!((estimate(serde_json::json!("paid")) - 0.7).abs() < 1e-9);
+        // The other 10 values share the remaining 10%
+        assert!((estimate(serde_json::json!("void")) - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pattern_selectivity_defaults() {
+        let optimizer = QueryOptimizer::new();
+        let estimate = |operator: &str| {
+            optimizer.estimate_selectivity(
+                "users",
+                &WhereCondition {
+                    column: "name".to_string(),
+                    operator: operator.to_string(),
+                    value: serde_json::json!("ada%"),
+                },
+            )
+        };
+        for pattern in ["LIKE", "ILIKE", "~", "~*"] {
+            assert!(estimate(pattern) < estimate("="), "{}", pattern);
+        }
+        assert!(estimate("NOT ILIKE") > estimate("="));
+    }
+
+    #[test]
+    fn test_in_list_selectivity() {
+        let optimizer = QueryOptimizer::new();
+        let column = ColumnStatistics {
+            column_name: "status".to_string(),
+            distinct_values: 12,
+            most_common_values: vec![MostCommonValue {
+                value: serde_json::json!("paid"),
+                frequency: 0.7,
+            }],
+            ..ColumnStatistics::default()
+        };
+        optimizer.update_statistics(
+            "orders",
+            TableStatistics {
+                table_name: "orders".to_string(),
+                row_count: 10_000,
+                column_count: 1,
+                avg_row_size: 0,
+                total_size_bytes: 0,
+                data_size_bytes: 0,
+                column_stats: HashMap::from([("status".to_string(), column)]),
+                column_statistics: HashMap::new(),
+                index_stats: HashMap::new(),
+                last_updated: 0,
+                collection_method: String::new(),
+                collection_duration_ms: 0,
+            },
+        );
+        let estimate = |operator: &str, value| {
+            optimizer.estimate_selectivity(
+                "orders",
+                &WhereCondition {
+                    column: "status".to_string(),
+                    operator: operator.to_string(),
+                    value,
+                },
+            )
+        };
+
+        // 0.7 for "paid", and the remaining 30% split over 11 values
+        let listed = 0.7 + 0.3 / 11.0;
+        let values = serde_json::json!(["paid", "void"]);
+        assert!((estimate("IN", values.clone()) - listed).abs() < 1e-9);
+        assert!((estimate("NOT IN", values) - (1.0 - listed)).abs() < 1e-9);
+        assert!((estimate("IN", serde_json::json!(["paid", null])) - 0.7).abs() < 1e-9);
+        assert_eq!(estimate("NOT IN", serde_json::json!(["paid", null])), 0.0);
+    }
+
     #[test]
     fn test_cost_model() {
         let cost_model = CostModel::default();
 
         assert!(cost_model.table_scan_cost(1000) > cost_model.table_scan_cost(100));
+        assert!(cost_model.columnar_scan_cost(1000) < cost_model.table_scan_cost(1000));
         assert!(cost_model.index_lookup_cost() < cost_model.table_scan_cost(1000));
         assert!(cost_model.sort_cost(1000) > cost_model.filter_cost(1000));
     }
+
+    #[test]
+    fn test_parallel_scan_workers() {
+        let config = ParallelScanConfig {
+            max_parallel_workers: 4,
+            min_scan_cost: 1000.0,
+            min_rows_per_worker: 10_000,
+        };
+        assert_eq!(config.workers_for(1_000_000, 20_000.0), Some(4));
+        assert_eq!(config.workers_for(30_000, 2_000.0), Some(3));
+        // Too cheap, or too few rows for more than one worker
+        assert_eq!(config.workers_for(1_000_000, 999.0), None);
+        assert_eq!(config.workers_for(15_000, 2_000.0), None);
+
+        let disabled = ParallelScanConfig {
+            max_parallel_workers: 0,
+            ..config
+        };
+        assert_eq!(disabled.workers_for(1_000_000, 20_000.0), None);
+    }
+
+    #[test]
+    fn test_plan_parallel_execution_gathers_large_scans() {
+        let mut optimizer = CostOptimizer::new();
+        optimizer.params.parallel_scan = ParallelScanConfig {
+            max_parallel_workers: 4,
+            min_scan_cost: 1000.0,
+            min_rows_per_worker: 10_000,
+        };
+        let scan = |table: &str, rows: f64| PlanNode::TableScan {
+            table: table.to_string(),
+            predicates: vec![],
+            cost: Cost::seq_scan(rows / 100.0, rows),
+        };
+        let plan = PlanNode::Limit {
+            input: Box::new(PlanNode::HashJoin {
+                left: Box::new(scan("events", 1_000_000.0)),
+                right: Box::new(scan("users", 500.0)),
+                condition: JoinCondition {
+                    left_col: "user_id".to_string(),
+                    right_col: "id".to_string(),
+                    op: ComparisonOp::Eq,
+                    raw_text: None,
+                },
+                build_side: JoinSide::Right,
+                join_type: JoinType::Inner,
+                cost: Cost::default(),
+            }),
+            limit: 10,
+            offset: 0,
+            cost: Cost::default(),
+        };
+
+        let plan = optimizer.plan_parallel_execution(plan).unwrap();
+        let PlanNode::Limit { input, .. } = plan else {
+            panic!("expected Limit");
+        };
+        let PlanNode::HashJoin { left, right, .. } = *input else {
+            panic!("expected HashJoin");
+        };
+        assert!(matches!(*left, PlanNode::Gather { workers: 4, .. }));
+        assert!(matches!(*right, PlanNode::TableScan { .. }));
+        assert_eq!(optimizer.stats.read().parallel_scans_planned, 1);
+    }
 }
 #[cfg(test)]
 mod cost_tests {
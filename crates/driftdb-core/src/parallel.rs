@@ -7,11 +7,12 @@
 //! - Thread pool management
 //! - Result aggregation
 //! - Adaptive parallelism based on data size
+//! - A process-wide worker pool shared by parallel table scans
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use rayon::prelude::*;
 use serde_json::Value;
 use tracing::{debug, trace};
@@ -452,6 +453,102 @@ impl ParallelExecutor {
     }
 }
 
+/// Process-wide pool that parallel table scans run on. It's sized to the
+/// machine's cores once, and every scan leases its workers from the same
+/// budget, so concurrent queries share the cores instead of each starting
+/// threads of their own.
+pub struct ScanWorkerPool {
+    pool: rayon::ThreadPool,
+    size: usize,
+    available: Mutex<usize>,
+}
+
+impl ScanWorkerPool {
+    /// Create a pool of `size` workers
+    pub fn new(size: usize) -> Result<Self> {
+        let size = size.max(1);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(size)
+            .thread_name(|i| format!("driftdb-scan-{}", i))
+            .build()
+            .map_err(|e| DriftError::Internal(format!("Failed to create scan pool: {}", e)))?;
+        Ok(Self {
+            pool,
+            size,
+            available: Mutex::new(size),
+        })
+    }
+
+    /// The pool shared by every engine in the process, or `None` if its
+    /// threads couldn't be started
+    pub fn global() -> Option<&'static ScanWorkerPool> {
+        static POOL: OnceLock<Option<ScanWorkerPool>> = OnceLock::new();
+        POOL.get_or_init(|| {
+            let size = std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(4);
+            ScanWorkerPool::new(size)
+                .map_err(|e| tracing::warn!("Parallel scans disabled: {}", e))
+                .ok()
+        })
+        .as_ref()
+    }
+
+    /// Total workers in the pool
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Workers not currently leased to a scan
+    pub fn available(&self) -> usize {
+        *self.available.lock()
+    }
+
+    /// Lease up to `wanted` workers. Never waits: when other scans hold
+    /// most of the pool the lease gets fewer, possibly none, and the
+    /// caller should scan with what it was given or fall back to a
+    /// sequential scan.
+    pub fn lease(&self, wanted: usize) -> WorkerLease<'_> {
+        let mut available = self.available.lock();
+        let workers = wanted.min(*available);
+        *available -= workers;
+        trace!("Leased {} of {} wanted scan workers", workers, wanted);
+        WorkerLease {
+            pool: self,
+            workers,
+        }
+    }
+}
+
+/// Workers leased from a [`ScanWorkerPool`], returned when dropped
+pub struct WorkerLease<'a> {
+    pool: &'a ScanWorkerPool,
+    workers: usize,
+}
+
+impl WorkerLease<'_> {
+    /// How many workers this lease may keep busy at once
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Run `op` on the pool. Work inside should be split into at most
+    /// [`Self::workers`] tasks to stay within the lease.
+    pub fn install<R, F>(&self, op: F) -> R
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        self.pool.pool.install(op)
+    }
+}
+
+impl Drop for WorkerLease<'_> {
+    fn drop(&mut self) {
+        *self.pool.available.lock() += self.workers;
+    }
+}
+
 /// Aggregate function definition
 #[derive(Debug, Clone)]
 pub struct AggregateFunction {
@@ -600,4 +697,25 @@ mod tests {
             }]
         ));
     }
+
+    #[test]
+    fn test_scan_pool_leases_share_the_budget() {
+        let pool = ScanWorkerPool::new(4).unwrap();
+
+        let first = pool.lease(3);
+        assert_eq!(first.workers(), 3);
+        let second = pool.lease(3);
+        assert_eq!(second.workers(), 1);
+        assert_eq!(pool.lease(2).workers(), 0);
+        assert_eq!(pool.available(), 0);
+
+        drop(first);
+        assert_eq!(pool.available(), 3);
+        drop(second);
+        assert_eq!(pool.available(), pool.size());
+
+        let lease = pool.lease(2);
+        let sum: i32 = lease.install(|| (1..=10).into_par_iter().sum());
+        assert_eq!(sum, 55);
+    }
 }
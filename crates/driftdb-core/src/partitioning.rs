@@ -0,0 +1,1167 @@
+//! Declarative table partitioning
+//!
+//! `CREATE TABLE events (...) PARTITION BY RANGE (created_at)` makes
+//! `events` a partitioned table. It holds no rows itself: they live in its
+//! partitions, each an ordinary table with the parent's columns, created
+//! with
+//!
+//! - `CREATE TABLE events_2025 PARTITION OF events FOR VALUES FROM
+//!   ('2025-01-01') TO ('2026-01-01')` for range partitioning. The lower
+//!   bound is inclusive and the upper exclusive; `MINVALUE` or `MAXVALUE`
+//!   leaves a side open. Range partitions can't overlap.
+//! - `CREATE TABLE events_p0 PARTITION OF events FOR VALUES WITH (MODULUS
+//!   4, REMAINDER 0)` for `PARTITION BY HASH (col)`. Each modulus must
+//!   divide the next larger one, as in PostgreSQL.
+//!
+//! A row written to the parent goes to the partition whose bound holds its
+//! partition key, and is refused when there is none; a row written to a
+//! partition directly must fall within its bound. An UPDATE that changes
+//! the key moves the row to its new partition. Reading the parent reads
+//! only the partitions [`PartitionScheme::prune`] keeps for the query's
+//! conditions on the key, so `WHERE created_at >= '2025-03-01'` skips
+//! every partition that ends before March. Dropping the parent drops its
+//! partitions.
+//!
+//! Old data expires a partition at a time rather than row by row. `DROP
+//! TABLE events_2023` removes a partition with all its history.
+//! `ALTER TABLE events DETACH PARTITION events_2023` takes it out of
+//! `events` at once but keeps it as a table of its own, still open to
+//! time travel; with `ARCHIVE` its segments also move to the cold tier of
+//! a tiered storage backend.
+//!
+//! Partitioning is by a single column, with no default partition and no
+//! sub-partitions. Primary keys are unique within each partition. The
+//! schemes are persisted to `partitions.json`.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::query::WhereCondition;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartitionStrategy {
+    Range,
+    Hash,
+}
+
+impl PartitionStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartitionStrategy::Range => "range",
+            PartitionStrategy::Hash => "hash",
+        }
+    }
+}
+
+/// The rows a partition takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PartitionBound {
+    /// `FROM (from) TO (to)`; `None` is `MINVALUE` or `MAXVALUE`
+    Range {
+        from: Option<Value>,
+        to: Option<Value>,
+    },
+    /// `WITH (MODULUS modulus, REMAINDER remainder)`
+    Hash { modulus: u64, remainder: u64 },
+}
+
+impl PartitionBound {
+    fn strategy(&self) -> PartitionStrategy {
+        match self {
+            PartitionBound::Range { .. } => PartitionStrategy::Range,
+            PartitionBound::Hash { .. } => PartitionStrategy::Hash,
+        }
+    }
+
+    /// Whether a row with partition key `key` belongs here
+    pub fn contains(&self, key: &Value) -> bool {
+        match self {
+            PartitionBound::Range { .. } if key.is_null() => false,
+            PartitionBound::Range { from, to } => {
+                let after_from = from
+                    .as_ref()
+                    .is_none_or(|from| matches!(compare_keys(key, from), Some(o) if o.is_ge()));
+                let before_to = to
+                    .as_ref()
+                    .is_none_or(|to| compare_keys(key, to) == Some(Ordering::Less));
+                after_from && before_to
+            }
+            PartitionBound::Hash { modulus, remainder } => hash_key(key) % modulus == *remainder,
+        }
+    }
+
+    /// Whether a row here could satisfy `key <operator> value`. Only
+    /// answers `false` when it certainly can't.
+    fn may_match(&self, operator: &str, value: &Value) -> bool {
+        match operator {
+            "IN" => {
+                return value
+                    .as_array()
+                    .is_none_or(|items| items.iter().any(|item| self.may_match("=", item)))
+            }
+            "IS NULL" => return self.contains(&Value::Null),
+            _ => {}
+        }
+        if value.is_null() {
+            // `key = NULL` and ordered comparisons with NULL match nothing
+            return !matches!(operator, "=" | "==" | "<" | "<=" | ">" | ">=");
+        }
+
+        match self {
+            PartitionBound::Hash { .. } => match operator {
+                "=" | "==" => self.contains(value),
+                _ => true,
+            },
+            PartitionBound::Range { from, to } => {
+                // `None` from a comparison means the types differ: keep
+                let from_cmp = from.as_ref().map(|from| compare_keys(from, value));
+                let to_cmp = to.as_ref().map(|to| compare_keys(to, value));
+                let from_is =
+                    |test: fn(Ordering) -> bool| matches!(from_cmp, Some(Some(o)) if test(o));
+                let to_is = |test: fn(Ordering) -> bool| matches!(to_cmp, Some(Some(o)) if test(o));
+                match operator {
+                    "=" | "==" => !from_is(Ordering::is_gt) && !to_is(Ordering::is_le),
+                    "<" => !from_is(Ordering::is_ge),
+                    "<=" => !from_is(Ordering::is_gt),
+                    ">" | ">=" => !to_is(Ordering::is_le),
+                    _ => true,
+                }
+            }
+        }
+    }
+
+    /// `FOR VALUES ...` as written in `CREATE TABLE ... PARTITION OF`
+    pub fn to_sql(&self) -> String {
+        match self {
+            PartitionBound::Range { from, to } => format!(
+                "FROM ({}) TO ({})",
+                bound_sql(from, "MINVALUE"),
+                bound_sql(to, "MAXVALUE")
+            ),
+            PartitionBound::Hash { modulus, remainder } => {
+                format!("WITH (MODULUS {}, REMAINDER {})", modulus, remainder)
+            }
+        }
+    }
+}
+
+/// `conditions` with strict comparisons on the integer key `column` made
+/// inclusive, `> 2022` as `>= 2023`, so a range partition ending at 2023
+/// is seen to hold no match
+fn inclusive_integer_bounds(conditions: &[WhereCondition], column: &str) -> Vec<WhereCondition> {
+    conditions
+        .iter()
+        .map(|condition| {
+            let step = match condition.operator.as_str() {
+                ">" => 1,
+                "<" => -1,
+                _ => return condition.clone(),
+            };
+            match condition.value.as_i64().and_then(|v| v.checked_add(step)) {
+                Some(value) if condition.column == column => WhereCondition {
+                    column: condition.column.clone(),
+                    operator: format!("{}=", condition.operator),
+                    value: value.into(),
+                },
+                _ => condition.clone(),
+            }
+        })
+        .collect()
+}
+
+fn bound_sql(value: &Option<Value>, open: &str) -> String {
+    match value {
+        None => open.to_string(),
+        Some(Value::String(text)) => format!("'{}'", text.replace('\'', "''")),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// One partition of a partitioned table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Partition {
+    pub name: String,
+    pub bound: PartitionBound,
+}
+
+/// How a partitioned table splits its rows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartitionScheme {
+    pub table: String,
+    pub strategy: PartitionStrategy,
+    pub column: String,
+    pub partitions: Vec<Partition>,
+}
+
+impl PartitionScheme {
+    pub fn new(table: &str, strategy: PartitionStrategy, column: &str) -> Self {
+        Self {
+            table: table.to_string(),
+            strategy,
+            column: column.to_string(),
+            partitions: Vec::new(),
+        }
+    }
+
+    /// The partition key of `row`; a missing column is NULL
+    pub fn key<'a>(&self, row: &'a Value) -> &'a Value {
+        static NULL: Value = Value::Null;
+        row.get(&self.column).unwrap_or(&NULL)
+    }
+
+    /// The partition `row` belongs in
+    pub fn partition_for(&self, row: &Value) -> Result<&Partition> {
+        let key = self.key(row);
+        self.partitions
+            .iter()
+            .find(|partition| partition.bound.contains(key))
+            .ok_or_else(|| {
+                DriftError::InvalidQuery(format!(
+                    "no partition of relation \"{}\" found for row: partition key ({}) = ({})",
+                    self.table,
+                    self.column,
+                    display_key(key)
+                ))
+            })
+    }
+
+    /// The partitions that can hold rows matching every condition in
+    /// `conditions`, in creation order
+    pub fn prune(&self, conditions: &[WhereCondition]) -> Vec<&Partition> {
+        let on_key: Vec<&WhereCondition> = conditions
+            .iter()
+            .filter(|condition| condition.column == self.column)
+            .collect();
+        self.partitions
+            .iter()
+            .filter(|partition| {
+                on_key.iter().all(|condition| {
+                    partition
+                        .bound
+                        .may_match(&condition.operator, &condition.value)
+                })
+            })
+            .collect()
+    }
+
+    /// Add a partition, checking its bound against the strategy and the
+    /// existing partitions
+    pub fn add(&mut self, partition: Partition) -> Result<()> {
+        if partition.bound.strategy() != self.strategy {
+            return Err(DriftError::InvalidQuery(format!(
+                "invalid bound specification for a {} partition",
+                self.strategy.as_str()
+            )));
+        }
+
+        match &partition.bound {
+            PartitionBound::Range { from, to } => {
+                if range_before(from, to) != Some(true) {
+                    return Err(DriftError::InvalidQuery(format!(
+                        "empty range bound specified for partition \"{}\"",
+                        partition.name
+                    )));
+                }
+                for existing in &self.partitions {
+                    let PartitionBound::Range {
+                        from: other_from,
+                        to: other_to,
+                    } = &existing.bound
+                    else {
+                        continue;
+                    };
+                    let overlaps = match (
+                        range_before(from, other_to),
+                        range_before(other_from, to),
+                    ) {
+                        (Some(a), Some(b)) => a && b,
+                        _ => return Err(DriftError::InvalidQuery(format!(
+                            "bounds of partition \"{}\" are not comparable with partition \"{}\"",
+                            partition.name, existing.name
+                        ))),
+                    };
+                    if overlaps {
+                        return Err(overlap_error(&partition, existing));
+                    }
+                }
+            }
+            PartitionBound::Hash { modulus, remainder } => {
+                if *modulus == 0 {
+                    return Err(DriftError::InvalidQuery(
+                        "modulus for hash partition must be an integer value greater than zero"
+                            .to_string(),
+                    ));
+                }
+                if remainder >= modulus {
+                    return Err(DriftError::InvalidQuery(
+                        "remainder for hash partition must be less than modulus".to_string(),
+                    ));
+                }
+                for existing in &self.partitions {
+                    let PartitionBound::Hash {
+                        modulus: other_modulus,
+                        remainder: other_remainder,
+                    } = existing.bound
+                    else {
+                        continue;
+                    };
+                    let (small, large) = if other_modulus <= *modulus {
+                        ((other_modulus, other_remainder), (*modulus, *remainder))
+                    } else {
+                        ((*modulus, *remainder), (other_modulus, other_remainder))
+                    };
+                    if large.0 % small.0 != 0 {
+                        return Err(DriftError::InvalidQuery(
+                            "every hash partition modulus must be a factor of the next larger modulus"
+                                .to_string(),
+                        ));
+                    }
+                    if large.1 % small.0 == small.1 {
+                        return Err(overlap_error(&partition, existing));
+                    }
+                }
+            }
+        }
+
+        self.partitions.push(partition);
+        Ok(())
+    }
+
+    /// Forget the partition called `name`
+    pub fn remove(&mut self, name: &str) {
+        self.partitions.retain(|partition| partition.name != name);
+    }
+}
+
+fn overlap_error(partition: &Partition, existing: &Partition) -> DriftError {
+    DriftError::InvalidQuery(format!(
+        "partition \"{}\" would overlap partition \"{}\"",
+        partition.name, existing.name
+    ))
+}
+
+/// Whether lower bound `from` comes before upper bound `to`, with `None`
+/// as minus and plus infinity. `None` when they can't be compared.
+fn range_before(from: &Option<Value>, to: &Option<Value>) -> Option<bool> {
+    match (from, to) {
+        (Some(from), Some(to)) => compare_keys(from, to).map(Ordering::is_lt),
+        _ => Some(true),
+    }
+}
+
+/// Order of two partition keys of the same type: numbers by value, text
+/// (and the dates and timestamps stored as text) by its characters.
+/// `None` for different types.
+fn compare_keys(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(_), Value::Number(_)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+/// Hash of a partition key. It decides where rows are stored, so it must
+/// not change between builds: FNV-1a over the key's text, with whole
+/// numbers hashed as integers so `5` and `5.0` agree. NULL hashes to 0.
+fn hash_key(key: &Value) -> u64 {
+    let text = match key {
+        Value::Null => return 0,
+        Value::String(text) => text.clone(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => (f as i64).to_string(),
+            _ => n.to_string(),
+        },
+        other => other.to_string(),
+    };
+    text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn display_key(key: &Value) -> String {
+    match key {
+        Value::Null => "NULL".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl Engine {
+    /// `PARTITION BY`: make `table`, just created, a partitioned table
+    pub fn partition_table(
+        &mut self,
+        table: &str,
+        strategy: PartitionStrategy,
+        column: &str,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let schema = self.table_schema(table)?;
+        if !schema.columns.iter().any(|c| c.name == column) {
+            return Err(DriftError::InvalidQuery(format!(
+                "column \"{}\" named in partition key does not exist",
+                column
+            )));
+        }
+        if self.partition_parent(table).is_some() {
+            return Err(DriftError::InvalidQuery(
+                "sub-partitioning is not supported".to_string(),
+            ));
+        }
+        self.partitions.write().insert(
+            table.to_string(),
+            PartitionScheme::new(table, strategy, column),
+        );
+        self.save_partitions()
+    }
+
+    /// `PARTITION OF`: create `name` with the columns and constraints of
+    /// the partitioned table `parent`, holding its rows within `bound`
+    pub fn create_partition(
+        &mut self,
+        name: &str,
+        parent: &str,
+        bound: PartitionBound,
+    ) -> Result<()> {
+        self.ensure_writable("CREATE TABLE")?;
+        let schema = self.table_schema(parent)?;
+        let mut scheme = self.partition_scheme(parent).ok_or_else(|| {
+            DriftError::InvalidQuery(format!("\"{}\" is not partitioned", parent))
+        })?;
+        scheme.add(Partition {
+            name: name.to_string(),
+            bound,
+        })?;
+
+        self.create_table_with_columns(name, &schema.primary_key, schema.columns.clone())?;
+        if !schema.defaults.is_empty() {
+            self.set_column_defaults(name, schema.defaults.clone())?;
+        }
+        if !schema.enums.is_empty() {
+            self.set_enum_columns(name, schema.enums.clone())?;
+        }
+        if !schema.checks.is_empty() || !schema.foreign_keys.is_empty() {
+            self.set_table_constraints(name, schema.checks.clone(), schema.foreign_keys.clone())?;
+        }
+
+        self.partitions.write().insert(parent.to_string(), scheme);
+        self.save_partitions()
+    }
+
+    /// How `table` is partitioned, if it is
+    pub fn partition_scheme(&self, table: &str) -> Option<PartitionScheme> {
+        self.partitions.read().get(table).cloned()
+    }
+
+    /// The partitioned table `table` is a partition of, if any
+    pub fn partition_parent(&self, table: &str) -> Option<String> {
+        self.partitions
+            .read()
+            .values()
+            .find(|scheme| scheme.partitions.iter().any(|p| p.name == table))
+            .map(|scheme| scheme.table.clone())
+    }
+
+    /// The table a row written to `table` is stored in: its partition when
+    /// `table` is partitioned, otherwise `table` itself, after checking
+    /// that a partition's row is within its bound
+    pub fn partition_for_row(&self, table: &str, row: &Value) -> Result<String> {
+        let partitions = self.partitions.read();
+        if let Some(scheme) = partitions.get(table) {
+            return scheme.partition_for(row).map(|p| p.name.clone());
+        }
+        let owner = partitions.values().find_map(|scheme| {
+            scheme
+                .partitions
+                .iter()
+                .find(|p| p.name == table)
+                .map(|p| (scheme, p))
+        });
+        if let Some((scheme, partition)) = owner {
+            if !partition.bound.contains(scheme.key(row)) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "new row for relation \"{}\" violates partition constraint",
+                    table
+                )));
+            }
+        }
+        Ok(table.to_string())
+    }
+
+    /// `DETACH PARTITION`: make `partition` a table of its own. It keeps
+    /// its rows and history, but reads of `parent` no longer include them.
+    pub fn detach_partition(&mut self, parent: &str, partition: &str) -> Result<()> {
+        self.ensure_writable("ALTER TABLE")?;
+        let mut partitions = self.partitions.write();
+        let scheme = partitions.get_mut(parent).ok_or_else(|| {
+            DriftError::InvalidQuery(format!("\"{}\" is not partitioned", parent))
+        })?;
+        if !scheme.partitions.iter().any(|p| p.name == partition) {
+            return Err(DriftError::InvalidQuery(format!(
+                "relation \"{}\" is not a partition of relation \"{}\"",
+                partition, parent
+            )));
+        }
+        scheme.remove(partition);
+        drop(partitions);
+        self.save_partitions()
+    }
+
+    /// The partitions of `table` a read with `conditions` has to scan, or
+    /// `None` when `table` isn't partitioned
+    pub fn partitions_to_scan(
+        &self,
+        table: &str,
+        conditions: &[WhereCondition],
+    ) -> Option<Vec<String>> {
+        let partitions = self.partitions.read();
+        let scheme = partitions.get(table)?;
+        let integer_key = self.table_schema(table).is_ok_and(|schema| {
+            schema.columns.iter().any(|c| {
+                c.name == scheme.column
+                    && crate::scalar_functions::cast_type(&c.col_type) == Some("integer")
+            })
+        });
+        let conditions = if integer_key {
+            inclusive_integer_bounds(conditions, &scheme.column)
+        } else {
+            conditions.to_vec()
+        };
+        Some(
+            scheme
+                .prune(&conditions)
+                .into_iter()
+                .map(|p| p.name.clone())
+                .collect(),
+        )
+    }
+
+    /// Drop `table`'s partitioning: the partitions it had if it was
+    /// partitioned, for the caller to drop, or its entry in its parent's
+    /// scheme if it was a partition
+    pub(crate) fn forget_partitioning(&mut self, table: &str) -> Result<Vec<String>> {
+        let mut partitions = self.partitions.write();
+        let dropped = match partitions.remove(table) {
+            Some(scheme) => scheme.partitions.into_iter().map(|p| p.name).collect(),
+            None => {
+                let Some(scheme) = partitions
+                    .values_mut()
+                    .find(|scheme| scheme.partitions.iter().any(|p| p.name == table))
+                else {
+                    return Ok(Vec::new());
+                };
+                scheme.remove(table);
+                Vec::new()
+            }
+        };
+        drop(partitions);
+        self.save_partitions()?;
+        Ok(dropped)
+    }
+
+    fn save_partitions(&self) -> Result<()> {
+        let schemes: Vec<PartitionScheme> = self.partitions.read().values().cloned().collect();
+        let json_data = serde_json::to_string_pretty(&schemes)?;
+        fs::write(self.base_path().join("partitions.json"), json_data)?;
+        Ok(())
+    }
+
+    /// Load `partitions.json`
+    pub(crate) fn load_partitions(&self) -> Result<()> {
+        let file = self.base_path().join("partitions.json");
+        if !file.exists() {
+            return Ok(());
+        }
+        let schemes: Vec<PartitionScheme> = serde_json::from_str(&fs::read_to_string(file)?)?;
+        let schemes: BTreeMap<String, PartitionScheme> = schemes
+            .into_iter()
+            .map(|scheme| (scheme.table.clone(), scheme))
+            .collect();
+        *self.partitions.write() = schemes;
+        Ok(())
+    }
+}
+
+/// A parsed `CREATE TABLE name PARTITION OF parent FOR VALUES ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionOf {
+    /// As written, possibly schema-qualified and quoted
+    pub name: String,
+    pub parent: String,
+    pub bound: PartitionBound,
+    pub if_not_exists: bool,
+}
+
+/// A parsed `ALTER TABLE parent DETACH PARTITION name [ARCHIVE]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetachPartition {
+    /// As written, possibly schema-qualified and quoted
+    pub parent: String,
+    pub partition: String,
+    /// Move the detached partition's segments to the cold tier
+    pub archive: bool,
+}
+
+/// Split `CREATE TABLE ... PARTITION BY {RANGE | HASH} (col)` into the
+/// plain `CREATE TABLE` and the partitioning. `None` for other statements.
+pub fn split_partition_by(sql: &str) -> Option<Result<(String, PartitionStrategy, String)>> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if !starts_with_words(sql, &["CREATE", "TABLE"]) || find_keyword(sql, "PARTITION OF").is_some()
+    {
+        return None;
+    }
+    let at = find_keyword(sql, "PARTITION BY")?;
+    let create = sql[..at].trim_end().to_string();
+    Some(
+        parse_partition_spec(sql[at + "PARTITION BY".len()..].trim())
+            .map(|(strategy, column)| (create, strategy, column)),
+    )
+}
+
+fn parse_partition_spec(spec: &str) -> Result<(PartitionStrategy, String)> {
+    let word_end = spec
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(spec.len());
+    let strategy = match spec[..word_end].to_uppercase().as_str() {
+        "RANGE" => PartitionStrategy::Range,
+        "HASH" => PartitionStrategy::Hash,
+        "LIST" => {
+            return Err(DriftError::InvalidQuery(
+                "PARTITION BY LIST is not supported; use RANGE or HASH".to_string(),
+            ))
+        }
+        _ => {
+            return Err(DriftError::Parse(format!(
+                "expected RANGE or HASH after PARTITION BY, found \"{}\"",
+                spec
+            )))
+        }
+    };
+    let (columns, rest) = paren_group(&spec[word_end..])?;
+    if !rest.trim().is_empty() {
+        return Err(DriftError::Parse(format!(
+            "unexpected \"{}\" after the partition key",
+            rest.trim()
+        )));
+    }
+    let columns: Vec<&str> = columns.split(',').map(str::trim).collect();
+    match columns.as_slice() {
+        [column] if !column.is_empty() => Ok((strategy, unquote(column))),
+        [_, _, ..] => Err(DriftError::InvalidQuery(
+            "partitioning by more than one column is not supported".to_string(),
+        )),
+        _ => Err(DriftError::Parse(
+            "missing partition key column".to_string(),
+        )),
+    }
+}
+
+/// Parse `CREATE TABLE [IF NOT EXISTS] name PARTITION OF parent FOR VALUES
+/// ...`. `None` for other statements.
+pub fn parse_partition_of(sql: &str) -> Option<Result<PartitionOf>> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if !starts_with_words(sql, &["CREATE", "TABLE"]) {
+        return None;
+    }
+    let at = find_keyword(sql, "PARTITION OF")?;
+    Some(parse_partition_of_at(sql, at))
+}
+
+fn parse_partition_of_at(sql: &str, at: usize) -> Result<PartitionOf> {
+    let head: Vec<&str> = sql[..at].split_whitespace().skip(2).collect();
+    let (if_not_exists, name) = match head.as_slice() {
+        [name] => (false, *name),
+        [i, n, e, name]
+            if i.eq_ignore_ascii_case("IF")
+                && n.eq_ignore_ascii_case("NOT")
+                && e.eq_ignore_ascii_case("EXISTS") =>
+        {
+            (true, *name)
+        }
+        _ => {
+            return Err(DriftError::Parse(
+                "expected CREATE TABLE name PARTITION OF parent".to_string(),
+            ))
+        }
+    };
+
+    let rest = sql[at + "PARTITION OF".len()..].trim_start();
+    let parent_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let parent = &rest[..parent_end];
+    let rest = rest[parent_end..].trim_start();
+    let Some(values) = strip_words(rest, &["FOR", "VALUES"]) else {
+        if starts_with_words(rest, &["DEFAULT"]) {
+            return Err(DriftError::InvalidQuery(
+                "default partitions are not supported".to_string(),
+            ));
+        }
+        return Err(DriftError::Parse(format!(
+            "expected FOR VALUES after PARTITION OF {}",
+            parent
+        )));
+    };
+
+    let (bound, rest) = if let Some(range) = strip_words(values, &["FROM"]) {
+        let (from, rest) = paren_group(range)?;
+        let to = strip_words(rest, &["TO"])
+            .ok_or_else(|| DriftError::Parse("expected TO after FROM (...)".to_string()))?;
+        let (to, rest) = paren_group(to)?;
+        let bound = PartitionBound::Range {
+            from: parse_bound_value(from, "MINVALUE")?,
+            to: parse_bound_value(to, "MAXVALUE")?,
+        };
+        (bound, rest)
+    } else if let Some(with) = strip_words(values, &["WITH"]) {
+        let (options, rest) = paren_group(with)?;
+        (parse_hash_bound(options)?, rest)
+    } else if starts_with_words(values, &["IN"]) {
+        return Err(DriftError::InvalidQuery(
+            "list partitions (FOR VALUES IN) are not supported".to_string(),
+        ));
+    } else {
+        return Err(DriftError::Parse(
+            "expected FROM (...) TO (...) or WITH (MODULUS n, REMAINDER r)".to_string(),
+        ));
+    };
+
+    if find_keyword(rest, "PARTITION BY").is_some() {
+        return Err(DriftError::InvalidQuery(
+            "sub-partitioning is not supported".to_string(),
+        ));
+    }
+    if !rest.trim().is_empty() {
+        return Err(DriftError::Parse(format!(
+            "unexpected \"{}\" after the partition bound",
+            rest.trim()
+        )));
+    }
+
+    Ok(PartitionOf {
+        name: name.to_string(),
+        parent: parent.to_string(),
+        bound,
+        if_not_exists,
+    })
+}
+
+/// Parse `ALTER TABLE parent DETACH PARTITION name [ARCHIVE]`. `None` for
+/// other statements.
+pub fn parse_detach_partition(sql: &str) -> Option<Result<DetachPartition>> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    if !starts_with_words(sql, &["ALTER", "TABLE"]) {
+        return None;
+    }
+    let at = find_keyword(sql, "DETACH PARTITION")?;
+    let head: Vec<&str> = sql[..at].split_whitespace().skip(2).collect();
+    let tail: Vec<&str> = sql[at + "DETACH PARTITION".len()..]
+        .split_whitespace()
+        .collect();
+    let ([parent], [partition, rest @ ..]) = (head.as_slice(), tail.as_slice()) else {
+        return Some(Err(DriftError::Parse(
+            "expected ALTER TABLE parent DETACH PARTITION name".to_string(),
+        )));
+    };
+    let archive = match rest {
+        [] => false,
+        [word] if word.eq_ignore_ascii_case("ARCHIVE") => true,
+        [word, ..] if word.eq_ignore_ascii_case("CONCURRENTLY") => {
+            return Some(Err(DriftError::InvalidQuery(
+                "DETACH PARTITION CONCURRENTLY is not supported".to_string(),
+            )))
+        }
+        _ => {
+            return Some(Err(DriftError::Parse(format!(
+                "unexpected \"{}\" after DETACH PARTITION {}",
+                rest.join(" "),
+                partition
+            ))))
+        }
+    };
+    Some(Ok(DetachPartition {
+        parent: parent.to_string(),
+        partition: partition.to_string(),
+        archive,
+    }))
+}
+
+fn parse_hash_bound(options: &str) -> Result<PartitionBound> {
+    let mut modulus = None;
+    let mut remainder = None;
+    for option in options.split(',') {
+        let mut words = option.split_whitespace();
+        let (Some(name), Some(value), None) = (words.next(), words.next(), words.next()) else {
+            return Err(DriftError::Parse(format!(
+                "invalid hash partition option \"{}\"",
+                option.trim()
+            )));
+        };
+        let value: u64 = value.parse().map_err(|_| {
+            DriftError::InvalidQuery(format!(
+                "{} for hash partition must be a non-negative integer",
+                name.to_lowercase()
+            ))
+        })?;
+        match name.to_uppercase().as_str() {
+            "MODULUS" => modulus = Some(value),
+            "REMAINDER" => remainder = Some(value),
+            _ => {
+                return Err(DriftError::Parse(format!(
+                    "unrecognized hash partition bound specification \"{}\"",
+                    name
+                )))
+            }
+        }
+    }
+    match (modulus, remainder) {
+        (Some(modulus), Some(remainder)) => Ok(PartitionBound::Hash { modulus, remainder }),
+        (None, _) => Err(DriftError::Parse(
+            "modulus for hash partition must be specified".to_string(),
+        )),
+        (_, None) => Err(DriftError::Parse(
+            "remainder for hash partition must be specified".to_string(),
+        )),
+    }
+}
+
+/// One range bound: a literal, optionally typed (`DATE '2025-01-01'`,
+/// `'2025-01-01'::date`), or `open` (`MINVALUE` / `MAXVALUE`)
+fn parse_bound_value(text: &str, open: &str) -> Result<Option<Value>> {
+    let text = text.trim();
+    if text.contains(',') && !text.starts_with('\'') {
+        return Err(DriftError::InvalidQuery(
+            "partitioning by more than one column is not supported".to_string(),
+        ));
+    }
+    if text.eq_ignore_ascii_case("MINVALUE") || text.eq_ignore_ascii_case("MAXVALUE") {
+        if !text.eq_ignore_ascii_case(open) {
+            return Err(DriftError::InvalidQuery(format!(
+                "{} is not allowed here; use {}",
+                text.to_uppercase(),
+                open
+            )));
+        }
+        return Ok(None);
+    }
+
+    // Drop a `::type` cast or a leading type name
+    let text = match text.rfind("::") {
+        Some(cast) if !text[cast..].contains('\'') => text[..cast].trim(),
+        _ => text,
+    };
+    let text = match text.find('\'') {
+        Some(quote) if quote > 0 && text[..quote].trim().chars().all(|c| c.is_alphabetic()) => {
+            &text[quote..]
+        }
+        _ => text,
+    };
+
+    if let Some(inner) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        return Ok(Some(Value::String(inner.replace("''", "'"))));
+    }
+    if text.eq_ignore_ascii_case("TRUE") || text.eq_ignore_ascii_case("FALSE") {
+        return Ok(Some(Value::Bool(text.eq_ignore_ascii_case("TRUE"))));
+    }
+    if let Ok(n) = text.parse::<i64>() {
+        return Ok(Some(Value::from(n)));
+    }
+    if let Some(n) = text
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        return Ok(Some(Value::Number(n)));
+    }
+    Err(DriftError::Parse(format!(
+        "invalid partition bound value \"{}\"",
+        text
+    )))
+}
+
+/// The contents of the parenthesized group `text` starts with, and what
+/// follows it
+pub(crate) fn paren_group(text: &str) -> Result<(&str, &str)> {
+    let text = text.trim_start();
+    if !text.starts_with('(') {
+        return Err(DriftError::Parse(format!("expected \"(\" at \"{}\"", text)));
+    }
+    let mut depth = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok((&text[1..i], &text[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(DriftError::Parse(format!(
+        "unbalanced parentheses in \"{}\"",
+        text
+    )))
+}
+
+/// Byte offset of the words `keyword` (ignoring case) in `sql`, outside
+/// quotes and parentheses
+pub(crate) fn find_keyword(sql: &str, keyword: &str) -> Option<usize> {
+    let bytes = sql.as_bytes();
+    let keyword = keyword.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_' || b == b'"';
+    let mut depth = 0i32;
+    let mut quoted = false;
+    for i in 0..bytes.len() {
+        match bytes[i] {
+            b'\'' => quoted = !quoted,
+            b'(' if !quoted => depth += 1,
+            b')' if !quoted => depth -= 1,
+            _ if quoted || depth != 0 => {}
+            _ => {
+                let end = i + keyword.len();
+                if end <= bytes.len()
+                    && bytes[i..end].eq_ignore_ascii_case(keyword)
+                    && (i == 0 || !is_word(bytes[i - 1]))
+                    && (end == bytes.len() || !is_word(bytes[end]))
+                {
+                    return Some(i);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// `text` after the leading `words`, ignoring case, or `None` if it
+/// doesn't start with them
+fn strip_words<'a>(text: &'a str, words: &[&str]) -> Option<&'a str> {
+    let mut rest = text.trim_start();
+    for word in words {
+        let head = rest.get(..word.len())?;
+        let boundary = rest[word.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| !c.is_ascii_alphanumeric() && c != '_');
+        if !head.eq_ignore_ascii_case(word) || !boundary {
+            return None;
+        }
+        rest = rest[word.len()..].trim_start();
+    }
+    Some(rest)
+}
+
+fn starts_with_words(text: &str, words: &[&str]) -> bool {
+    strip_words(text, words).is_some()
+}
+
+fn unquote(ident: &str) -> String {
+    ident
+        .strip_prefix('"')
+        .and_then(|i| i.strip_suffix('"'))
+        .map(|i| i.replace("\"\"", "\""))
+        .unwrap_or_else(|| ident.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn range(from: Value, to: Value) -> PartitionBound {
+        PartitionBound::Range {
+            from: Some(from),
+            to: Some(to),
+        }
+    }
+
+    fn condition(operator: &str, value: Value) -> WhereCondition {
+        WhereCondition {
+            column: "created_at".to_string(),
+            operator: operator.to_string(),
+            value,
+        }
+    }
+
+    fn yearly() -> PartitionScheme {
+        let mut scheme = PartitionScheme::new("events", PartitionStrategy::Range, "created_at");
+        for year in 2023..=2025 {
+            scheme
+                .add(Partition {
+                    name: format!("events_{}", year),
+                    bound: range(
+                        json!(format!("{}-01-01", year)),
+                        json!(format!("{}-01-01", year + 1)),
+                    ),
+                })
+                .unwrap();
+        }
+        scheme
+    }
+
+    fn names(partitions: Vec<&Partition>) -> Vec<&str> {
+        partitions.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_partition_ddl() {
+        let (create, strategy, column) = split_partition_by(
+            "CREATE TABLE events (id INT PRIMARY KEY, created_at DATE) PARTITION BY RANGE (created_at);",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            create,
+            "CREATE TABLE events (id INT PRIMARY KEY, created_at DATE)"
+        );
+        assert_eq!(strategy, PartitionStrategy::Range);
+        assert_eq!(column, "created_at");
+        assert!(split_partition_by("CREATE TABLE t (id INT)").is_none());
+        assert!(
+            split_partition_by("CREATE TABLE t (id INT) PARTITION BY LIST (id)")
+                .unwrap()
+                .is_err()
+        );
+
+        let of = parse_partition_of(
+            "create table events_2025 partition of events for values from (DATE '2025-01-01') to (MAXVALUE)",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(of.name, "events_2025");
+        assert_eq!(of.parent, "events");
+        assert_eq!(
+            of.bound,
+            PartitionBound::Range {
+                from: Some(json!("2025-01-01")),
+                to: None
+            }
+        );
+
+        let of = parse_partition_of(
+            "CREATE TABLE IF NOT EXISTS t_p1 PARTITION OF t FOR VALUES WITH (MODULUS 4, REMAINDER 1)",
+        )
+        .unwrap()
+        .unwrap();
+        assert!(of.if_not_exists);
+        assert_eq!(
+            of.bound,
+            PartitionBound::Hash {
+                modulus: 4,
+                remainder: 1
+            }
+        );
+
+        let detach = parse_detach_partition("ALTER TABLE t DETACH PARTITION t_2023 archive;")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            detach,
+            DetachPartition {
+                parent: "t".to_string(),
+                partition: "t_2023".to_string(),
+                archive: true,
+            }
+        );
+        assert!(
+            parse_detach_partition("ALTER TABLE t DETACH PARTITION t_2023 FINALIZE")
+                .unwrap()
+                .is_err()
+        );
+        assert!(parse_detach_partition("ALTER TABLE t ADD COLUMN c INT").is_none());
+    }
+
+    #[test]
+    fn routes_rows_and_rejects_overlaps() {
+        let mut scheme = yearly();
+        let row = json!({"id": 1, "created_at": "2024-06-30"});
+        assert_eq!(scheme.partition_for(&row).unwrap().name, "events_2024");
+        let row = json!({"id": 2, "created_at": "2025-01-01"});
+        assert_eq!(scheme.partition_for(&row).unwrap().name, "events_2025");
+        assert!(scheme
+            .partition_for(&json!({"id": 3, "created_at": "2030-01-01"}))
+            .is_err());
+        assert!(scheme.partition_for(&json!({"id": 4})).is_err());
+
+        let overlap = scheme.add(Partition {
+            name: "events_mid".to_string(),
+            bound: range(json!("2024-06-01"), json!("2024-07-01")),
+        });
+        assert!(overlap.unwrap_err().to_string().contains("would overlap"));
+        let empty = scheme.add(Partition {
+            name: "events_empty".to_string(),
+            bound: range(json!("2027-01-01"), json!("2026-01-01")),
+        });
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn prunes_partitions_by_conditions() {
+        let scheme = yearly();
+        assert_eq!(scheme.prune(&[]).len(), 3);
+        assert_eq!(
+            names(scheme.prune(&[condition("=", json!("2024-02-29"))])),
+            ["events_2024"]
+        );
+        assert_eq!(
+            names(scheme.prune(&[condition(">=", json!("2024-03-01"))])),
+            ["events_2024", "events_2025"]
+        );
+        assert_eq!(
+            names(scheme.prune(&[
+                condition(">=", json!("2024-01-01")),
+                condition("<", json!("2024-01-01"))
+            ])),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            names(scheme.prune(&[condition("<", json!("2024-01-01"))])),
+            ["events_2023"]
+        );
+        // A value of another type can't be compared, so nothing is skipped
+        assert_eq!(scheme.prune(&[condition("=", json!(2024))]).len(), 3);
+    }
+
+    #[test]
+    fn hash_partitions_cover_every_key_once() {
+        let mut scheme = PartitionScheme::new("t", PartitionStrategy::Hash, "id");
+        for remainder in 0..4 {
+            scheme
+                .add(Partition {
+                    name: format!("t_p{}", remainder),
+                    bound: PartitionBound::Hash {
+                        modulus: 4,
+                        remainder,
+                    },
+                })
+                .unwrap();
+        }
+        for id in 0..100 {
+            let row = json!({ "id": id });
+            let partition = scheme.partition_for(&row).unwrap();
+            let by_equality = scheme.prune(&[WhereCondition {
+                column: "id".to_string(),
+                operator: "=".to_string(),
+                value: json!(id),
+            }]);
+            assert_eq!(names(by_equality), [partition.name.as_str()]);
+        }
+        let bad_modulus = scheme.add(Partition {
+            name: "t_p9".to_string(),
+            bound: PartitionBound::Hash {
+                modulus: 6,
+                remainder: 5,
+            },
+        });
+        assert!(bad_modulus.is_err());
+    }
+}
@@ -1,3 +1,7 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use serde_json::json;
 
 use super::{AsOf, Query, QueryResult, WhereCondition};
@@ -5,7 +9,31 @@ use crate::engine::Engine;
 use crate::errors::Result;
 use crate::events::Event;
 use crate::optimizer::PlanStep;
-use crate::parallel::{ParallelConfig, ParallelExecutor};
+use crate::parallel::ScanWorkerPool;
+
+thread_local! {
+    /// Rows `select` has read from each table on this thread
+    static ROWS_SCANNED: RefCell<BTreeMap<String, u64>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+fn count_scanned(table: &str, rows: usize) {
+    ROWS_SCANNED.with(|scanned| {
+        *scanned.borrow_mut().entry(table.to_string()).or_default() += rows as u64;
+    });
+}
+
+/// Rows read from each table on this thread since the last call, before
+/// the WHERE clause filtered them
+pub(crate) fn take_rows_scanned() -> BTreeMap<String, u64> {
+    ROWS_SCANNED.with(|scanned| std::mem::take(&mut *scanned.borrow_mut()))
+}
+
+/// Add back counts taken by [`take_rows_scanned`]
+pub(crate) fn restore_rows_scanned(earlier: BTreeMap<String, u64>) {
+    for (table, rows) in earlier {
+        count_scanned(&table, rows as usize);
+    }
+}
 
 impl Engine {
     pub fn execute_query(&mut self, query: Query) -> Result<QueryResult> {
@@ -113,6 +141,25 @@ impl Engine {
         as_of: Option<AsOf>,
         limit: Option<usize>,
     ) -> Result<Vec<serde_json::Value>> {
+        // A partitioned table's rows are in the partitions its conditions
+        // don't rule out
+        if let Some(partitions) = self.partitions_to_scan(table, &conditions) {
+            let mut rows = Vec::new();
+            for partition in partitions {
+                let remaining = limit.map(|limit| limit.saturating_sub(rows.len()));
+                if remaining == Some(0) {
+                    break;
+                }
+                rows.extend(self.select(
+                    &partition,
+                    conditions.clone(),
+                    as_of.clone(),
+                    remaining,
+                )?);
+            }
+            return Ok(rows);
+        }
+
         // Ask the optimizer for a plan. The plan tells us:
         //   (a) which access method to use (index lookup vs. table scan);
         //   (b) the order in which residual `Filter` predicates should
@@ -191,47 +238,76 @@ impl Engine {
             .get(table)
             .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
 
-        let sequence = match as_of {
-            Some(AsOf::Sequence(seq)) => Some(seq),
-            Some(AsOf::Timestamp(ts)) => {
-                let events = storage.read_all_events()?;
-                events
-                    .iter()
-                    .filter(|e| e.timestamp <= ts)
-                    .map(|e| e.sequence)
-                    .max()
-            }
-            Some(AsOf::Now) | None => None,
-        };
+        let sequence = storage.as_of_sequence(as_of.as_ref())?;
 
-        let state = storage.reconstruct_state_at(sequence)?;
-
-        // Use parallel execution for large datasets
-        if state.len() > 1000 {
-            // Create parallel executor with default config
-            let parallel_executor = ParallelExecutor::new(ParallelConfig::default())?;
+        // A columnar table's current rows are scanned from its projection
+        if sequence.is_none() {
+            if let Some(rows) = storage.scan_columnar(&ordered_conditions, limit)? {
+                count_scanned(table, rows.len());
+                return Ok(rows);
+            }
+        }
 
-            // Convert state to format expected by parallel executor
-            let data: Vec<(serde_json::Value, serde_json::Value)> = state
-                .into_iter()
-                .map(|(pk, row)| (serde_json::Value::String(pk), row))
-                .collect();
+        // An equality on the primary key of a table with key filters reads
+        // only the segments that might hold the key
+        let primary_key = storage.schema().primary_key.clone();
+        if let Some(key) = conditions
+            .iter()
+            .find(|c| c.column == primary_key && (c.operator == "=" || c.operator == "=="))
+        {
+            if let Some(rows) = storage.rows_with_key_at(&key.value, sequence)? {
+                count_scanned(table, rows.len());
+                let mut results: Vec<serde_json::Value> = rows
+                    .into_iter()
+                    .filter(|row| super::predicate::matches_conditions(row, &ordered_conditions))
+                    .collect();
+                if let Some(limit) = limit {
+                    results.truncate(limit);
+                }
+                return Ok(results);
+            }
+        }
 
-            // Execute query in parallel
-            parallel_executor.parallel_select(data, &ordered_conditions, limit)
-        } else {
-            // Use sequential execution for small datasets
-            let mut results: Vec<serde_json::Value> = state
-                .into_values()
-                .filter(|row| super::predicate::matches_conditions(row, &ordered_conditions))
-                .collect();
-
-            if let Some(limit) = limit {
-                results.truncate(limit);
+        // The optimizer splits scans it costs high enough across workers.
+        // Workers come from the shared pool; if other scans hold it, run
+        // sequentially rather than wait.
+        let parallel_workers = plan.as_ref().and_then(|p| {
+            p.steps.iter().find_map(|step| match step {
+                PlanStep::ParallelScan { workers, .. } => Some(*workers),
+                _ => None,
+            })
+        });
+        if let (Some(wanted), Some(pool)) = (parallel_workers, ScanWorkerPool::global()) {
+            let lease = pool.lease(wanted);
+            if lease.workers() > 1 {
+                let scanned = AtomicUsize::new(0);
+                let mut results = storage.parallel_scan(sequence, &lease, |row| {
+                    scanned.fetch_add(1, Ordering::Relaxed);
+                    super::predicate::matches_conditions(row, &ordered_conditions)
+                })?;
+                count_scanned(table, scanned.into_inner());
+                if let Some(limit) = limit {
+                    results.truncate(limit);
+                }
+                if let Some(perf) = &self.query_performance {
+                    perf.record_parallel_execution();
+                }
+                return Ok(results);
             }
+        }
+
+        let state = storage.reconstruct_state_at(sequence)?;
+        count_scanned(table, state.len());
+        let mut results: Vec<serde_json::Value> = state
+            .into_values()
+            .filter(|row| super::predicate::matches_conditions(row, &ordered_conditions))
+            .collect();
 
-            Ok(results)
+        if let Some(limit) = limit {
+            results.truncate(limit);
         }
+
+        Ok(results)
     }
 
     /// If the optimizer chose an `IndexLookup` or `IndexScan`, fetch the
@@ -310,8 +386,10 @@ impl Engine {
         let residual = predicates_from_filter_steps(plan);
 
         let mut results = Vec::new();
+        let mut scanned = 0;
         for pk in pks {
             let Some(row) = state.get(&pk) else { continue };
+            scanned += 1;
             if super::predicate::matches_conditions(row, &residual) {
                 results.push(row.clone());
                 if let Some(lim) = limit {
@@ -321,9 +399,116 @@ impl Engine {
                 }
             }
         }
+        count_scanned(table, scanned);
+        Ok(Some(results))
+    }
+
+    /// Read one `ORDER BY column LIMIT limit OFFSET offset` page through
+    /// the column's index, touching only the rows up to the end of the
+    /// page instead of sorting the whole table. Range conditions on the
+    /// column bound the walk, which is what makes keyset pagination
+    /// (`WHERE id > $last_seen ORDER BY id LIMIT n`) cheap; every
+    /// condition is still re-checked against the row.
+    ///
+    /// NULLs are not indexed, so returns `Ok(None)` — fall back to a
+    /// sort — whenever they could belong on the page: NULLs sorting
+    /// first, or the index running out before the page fills. Neither
+    /// applies to the primary key or to a column the WHERE clause
+    /// compares, since those rows can't be NULL.
+    pub fn select_ordered_by_index(
+        &self,
+        table: &str,
+        conditions: &[WhereCondition],
+        key: &crate::optimizer::SortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Option<Vec<serde_json::Value>>> {
+        if key.collation != crate::optimizer::Collation::Binary {
+            return Ok(None);
+        }
+        let Some(storage) = self.tables.get(table) else {
+            return Ok(None);
+        };
+        let schema = storage.schema();
+        let Some(column) = schema.columns.iter().find(|c| c.name == key.column) else {
+            return Ok(None);
+        };
+        let col_type = column.col_type.to_uppercase();
+        let text = col_type.contains("CHAR") || col_type.contains("TEXT");
+
+        let mut start: Option<(&serde_json::Value, bool)> = None;
+        let mut end: Option<(&serde_json::Value, bool)> = None;
+        let mut compared = false;
+        for c in conditions.iter().filter(|c| c.column == key.column) {
+            match c.operator.as_str() {
+                ">" | ">=" => start = Some((&c.value, c.operator == ">=")),
+                "<" | "<=" => end = Some((&c.value, c.operator == "<=")),
+                "=" | "==" => {
+                    start = Some((&c.value, true));
+                    end = Some((&c.value, true));
+                }
+                _ => continue,
+            }
+            compared = true;
+        }
+        let never_null = schema.primary_key == key.column || compared;
+        if key.nulls_first && !never_null {
+            return Ok(None);
+        }
+
+        let pks = {
+            let Some(index_mgr) = self.indexes.get(table) else {
+                return Ok(None);
+            };
+            let mgr_guard = index_mgr.read();
+            let Some(index) = mgr_guard.get_index(&key.column) else {
+                return Ok(None);
+            };
+            index.ordered_range(start, end, !key.ascending, text)
+        };
+
+        let state = storage.reconstruct_state_at(None)?;
+        let mut skipped = 0;
+        let mut results = Vec::new();
+        for pk in pks {
+            if results.len() >= limit {
+                return Ok(Some(results));
+            }
+            let Some(row) = state.get(&pk) else { continue };
+            if !super::predicate::matches_conditions(row, conditions) {
+                continue;
+            }
+            if skipped < offset {
+                skipped += 1;
+                continue;
+            }
+            results.push(row.clone());
+        }
+        if results.len() < limit && !never_null {
+            return Ok(None);
+        }
         Ok(Some(results))
     }
 
+    /// `SELECT COUNT(*)` without a WHERE clause, from the table's
+    /// maintained row count. Time travel replays only primary keys up to
+    /// the target sequence, starting from the latest snapshot before it.
+    pub fn count_rows(&self, table: &str, as_of: Option<AsOf>) -> Result<usize> {
+        if let Some(partitions) = self.partitions_to_scan(table, &[]) {
+            return partitions.iter().try_fold(0, |count, partition| {
+                Ok(count + self.count_rows(partition, as_of.clone())?)
+            });
+        }
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+        match storage.as_of_sequence(as_of.as_ref())? {
+            Some(sequence) => storage.row_count_at(Some(sequence)),
+            None => storage.row_count(),
+        }
+    }
+
     fn get_drift_history(
         &self,
         table: &str,
@@ -374,6 +559,130 @@ impl Engine {
         Ok(schema.columns.iter().map(|c| c.name.clone()).collect())
     }
 
+    /// Columns declared `JSON` or `JSONB`
+    pub fn get_json_columns(&self, table: &str) -> Result<Vec<String>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage
+            .schema()
+            .columns
+            .iter()
+            .filter(|c| crate::jsonb::is_json_type(&c.col_type))
+            .map(|c| c.name.clone())
+            .collect())
+    }
+
+    /// DECIMAL/NUMERIC columns with their declared precision and scale
+    pub fn get_decimal_columns(
+        &self,
+        table: &str,
+    ) -> Result<Vec<(String, crate::decimal::DecimalSpec)>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage
+            .schema()
+            .columns
+            .iter()
+            .filter_map(|c| {
+                crate::decimal::DecimalSpec::from_col_type(&c.col_type)
+                    .map(|spec| (c.name.clone(), spec))
+            })
+            .collect())
+    }
+
+    /// UUID columns
+    pub fn get_uuid_columns(&self, table: &str) -> Result<Vec<String>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage
+            .schema()
+            .columns
+            .iter()
+            .filter(|c| crate::uuids::is_uuid_type(&c.col_type))
+            .map(|c| c.name.clone())
+            .collect())
+    }
+
+    /// BYTEA columns
+    pub fn get_bytea_columns(&self, table: &str) -> Result<Vec<String>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage
+            .schema()
+            .columns
+            .iter()
+            .filter(|c| crate::bytea::is_bytea_type(&c.col_type))
+            .map(|c| c.name.clone())
+            .collect())
+    }
+
+    /// Enum-typed columns with their types
+    pub fn get_enum_columns(
+        &self,
+        table: &str,
+    ) -> Result<std::collections::BTreeMap<String, crate::enums::EnumType>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage.schema().enums.clone())
+    }
+
+    /// Column `DEFAULT` expressions declared at CREATE TABLE, as SQL text
+    pub fn get_column_defaults(
+        &self,
+        table: &str,
+    ) -> Result<std::collections::BTreeMap<String, String>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage.schema().defaults.clone())
+    }
+
+    /// `CHECK` constraints declared at CREATE TABLE
+    pub fn get_check_constraints(
+        &self,
+        table: &str,
+    ) -> Result<Vec<crate::schema::CheckConstraint>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage.schema().checks.clone())
+    }
+
+    /// Array columns with their declared element types
+    pub fn get_array_columns(&self, table: &str) -> Result<Vec<(String, String)>> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| crate::errors::DriftError::TableNotFound(table.to_string()))?;
+
+        Ok(storage
+            .schema()
+            .columns
+            .iter()
+            .filter_map(|c| {
+                crate::arrays::element_type(&c.col_type).map(|elem| (c.name.clone(), elem))
+            })
+            .collect())
+    }
 }
 
 /// Extract residual predicates from a plan in the order the optimizer
@@ -19,15 +19,21 @@
 //! Operator coverage (canonical):
 //! - `=`, `==`, `!=`, `<>`
 //! - `<`, `<=`, `>`, `>=` (numeric-first, with [`compare_json_values`] fallback)
-//! - `LIKE` (SQL `%` and `_` wildcards via regex)
+//! - `LIKE`, `ILIKE` (SQL `%` and `_` wildcards via regex) and their
+//!   `NOT` forms
+//! - `~`, `~*`, `!~`, `!~*` (POSIX-style regex match, case-insensitive
+//!   with `*`)
 //! - `IN`, `NOT IN` (RHS is a JSON array)
 //! - `IS NULL`, `IS NOT NULL`
 //!
 //! Missing columns are treated as NULL — i.e., they match `IS NULL` and
 //! nothing else.
 
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 
+use regex::{Regex, RegexBuilder};
 use serde_json::Value;
 use tracing::warn;
 
@@ -40,9 +46,16 @@ pub fn matches_conditions(row: &Value, conditions: &[WhereCondition]) -> bool {
 }
 
 fn matches_one(row: &Value, cond: &WhereCondition) -> bool {
+    matches_value(row.get(&cond.column), cond)
+}
+
+/// True if a row whose `cond.column` holds `value` (`None` when the row
+/// lacks the column) matches `cond`, for readers that hold a column
+/// rather than whole rows.
+pub fn matches_value(value: Option<&Value>, cond: &WhereCondition) -> bool {
     // Missing column behaves as a NULL value — matches only `IS NULL`,
     // mirroring SQL three-valued logic at the WHERE-clause boundary.
-    let Some(field_value) = row.get(&cond.column) else {
+    let Some(field_value) = value else {
         return cond.operator == "IS NULL";
     };
     compare_values(field_value, &cond.value, &cond.operator)
@@ -66,8 +79,8 @@ pub fn compare_values(left: &Value, right: &Value, operator: &str) -> bool {
     }
 
     match operator {
-        "=" | "==" => left == right,
-        "!=" | "<>" => left != right,
+        "=" | "==" => left == right || decimal_cmp(left, right) == Some(Ordering::Equal),
+        "!=" | "<>" => left != right && decimal_cmp(left, right) != Some(Ordering::Equal),
         "<" => ordered_cmp(left, right) == Ordering::Less,
         "<=" => matches!(ordered_cmp(left, right), Ordering::Less | Ordering::Equal),
         ">" => ordered_cmp(left, right) == Ordering::Greater,
@@ -75,19 +88,20 @@ pub fn compare_values(left: &Value, right: &Value, operator: &str) -> bool {
             ordered_cmp(left, right),
             Ordering::Greater | Ordering::Equal
         ),
-        "LIKE" => like_matches(left, right),
         "IN" => match right.as_array() {
             Some(array) => array.contains(left),
             None => false,
         },
         "NOT IN" => match right.as_array() {
-            Some(array) => !array.contains(left),
+            // A NULL in the list makes every non-member unknown, not true
+            Some(array) => !array.contains(left) && !array.iter().any(Value::is_null),
             // SQL `x NOT IN <non-list>` is structurally ill-formed; treat
             // as unmatched rather than silently matching everything.
             None => false,
         },
         "IS NULL" => left.is_null(),
         "IS NOT NULL" => !left.is_null(),
+        op if PatternKind::from_operator(op).is_some() => pattern_matches(left, right, op),
         other => {
             warn!("predicate: unknown operator '{}'", other);
             false
@@ -115,6 +129,9 @@ pub fn compare_json_values(a: &Value, b: &Value) -> Ordering {
             _ => Ordering::Equal,
         },
         (V::String(a), V::String(b)) => a.cmp(b),
+        (V::Number(_), V::String(_)) | (V::String(_), V::Number(_)) => {
+            decimal_cmp(a, b).unwrap_or_else(|| a.to_string().cmp(&b.to_string()))
+        }
         (V::Array(a), V::Array(b)) => a.len().cmp(&b.len()),
         (V::Object(a), V::Object(b)) => a.len().cmp(&b.len()),
         // Heterogeneous types — fall back to string representation for
@@ -123,6 +140,18 @@ pub fn compare_json_values(a: &Value, b: &Value) -> Ordering {
     }
 }
 
+/// Order a number against a DECIMAL value stored as exact text (see
+/// [`crate::decimal`]). `None` unless exactly one side is a string and
+/// both read as decimals.
+fn decimal_cmp(left: &Value, right: &Value) -> Option<Ordering> {
+    if left.is_string() == right.is_string() {
+        return None;
+    }
+    let left = crate::decimal::Decimal::from_value(left)?;
+    let right = crate::decimal::Decimal::from_value(right)?;
+    Some(left.cmp(&right))
+}
+
 /// Numeric-first ordering used by predicate evaluation. Tries `as_f64`
 /// for both sides; if either isn't numeric, falls back to
 /// [`compare_json_values`].
@@ -133,14 +162,97 @@ fn ordered_cmp(left: &Value, right: &Value) -> Ordering {
     }
 }
 
-/// Apply SQL `LIKE`: `%` matches any sequence, `_` matches a single
-/// character. Both sides must be strings; non-string sides are not a
-/// match. Pattern characters are escaped before substitution to avoid
-/// regex injection through user-controlled patterns.
-fn like_matches(left: &Value, right: &Value) -> bool {
-    let (Some(text), Some(pattern)) = (left.as_str(), right.as_str()) else {
+/// How a text-matching operator reads its pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternKind {
+    /// `LIKE`: `%` matches any sequence, `_` any single character
+    Like,
+    /// `ILIKE`: `LIKE`, ignoring case
+    ILike,
+    /// `~`: a regular expression found anywhere in the text
+    Regex,
+    /// `~*`: `~`, ignoring case
+    IRegex,
+}
+
+impl PatternKind {
+    /// The kind of a pattern operator and whether it is negated, e.g.
+    /// `NOT ILIKE` → `(ILike, true)`. `None` for other operators.
+    pub fn from_operator(operator: &str) -> Option<(Self, bool)> {
+        Some(match operator {
+            "LIKE" => (PatternKind::Like, false),
+            "NOT LIKE" => (PatternKind::Like, true),
+            "ILIKE" => (PatternKind::ILike, false),
+            "NOT ILIKE" => (PatternKind::ILike, true),
+            "~" => (PatternKind::Regex, false),
+            "!~" => (PatternKind::Regex, true),
+            "~*" => (PatternKind::IRegex, false),
+            "!~*" => (PatternKind::IRegex, true),
+            _ => return None,
+        })
+    }
+}
+
+/// Compiled patterns are kept per thread, so a query compiles each pattern
+/// once however many rows it tests; the cache is emptied when it outgrows
+/// this many entries
+const PATTERN_CACHE_ENTRIES: usize = 256;
+
+thread_local! {
+    static PATTERNS: RefCell<HashMap<(String, PatternKind), Regex>> =
+        RefCell::new(HashMap::new());
+}
+
+/// The regex `pattern` compiles to under `kind`. An error is only
+/// possible for the regex kinds; LIKE patterns are escaped.
+pub fn pattern_regex(pattern: &str, kind: PatternKind) -> Result<Regex, regex::Error> {
+    let key = (pattern.to_string(), kind);
+    if let Some(regex) = PATTERNS.with(|p| p.borrow().get(&key).cloned()) {
+        return Ok(regex);
+    }
+    let regex = match kind {
+        PatternKind::Like | PatternKind::ILike => RegexBuilder::new(&like_to_regex(pattern))
+            .case_insensitive(kind == PatternKind::ILike)
+            .dot_matches_new_line(true)
+            .build()?,
+        PatternKind::Regex | PatternKind::IRegex => RegexBuilder::new(pattern)
+            .case_insensitive(kind == PatternKind::IRegex)
+            .build()?,
+    };
+    PATTERNS.with(|p| {
+        let mut patterns = p.borrow_mut();
+        if patterns.len() >= PATTERN_CACHE_ENTRIES {
+            patterns.clear();
+        }
+        patterns.insert(key, regex.clone());
+    });
+    Ok(regex)
+}
+
+/// Apply a pattern operator. Both sides must be strings; non-string
+/// sides are not a match, and neither is an invalid regex (the SQL layer
+/// rejects those before any row is read).
+fn pattern_matches(left: &Value, right: &Value, operator: &str) -> bool {
+    let (Some((kind, negated)), Some(text), Some(pattern)) = (
+        PatternKind::from_operator(operator),
+        left.as_str(),
+        right.as_str(),
+    ) else {
         return false;
     };
+    match pattern_regex(pattern, kind) {
+        Ok(regex) => regex.is_match(text) != negated,
+        Err(e) => {
+            warn!("predicate: invalid pattern '{}': {}", pattern, e);
+            false
+        }
+    }
+}
+
+/// The anchored regex for a SQL `LIKE` pattern. Pattern characters are
+/// escaped before substitution to avoid regex injection through
+/// user-controlled patterns.
+fn like_to_regex(pattern: &str) -> String {
     // Regex metacharacters that need escaping when they appear literally
     // in a LIKE pattern. Note '%' and '_' are *not* listed — they're SQL
     // wildcards translated below.
@@ -161,9 +273,7 @@ fn like_matches(left: &Value, right: &Value) -> bool {
         }
     }
     regex_pattern.push('$');
-    regex::Regex::new(&regex_pattern)
-        .map(|re| re.is_match(text))
-        .unwrap_or(false)
+    regex_pattern
 }
 
 #[cfg(test)]
@@ -233,6 +343,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn ilike_and_regex() {
+        let row = json!({"name": "Ada Lovelace"});
+        let matches =
+            |op: &str, pattern: &str| matches_conditions(&row, &[cond("name", op, json!(pattern))]);
+        assert!(matches("ILIKE", "ada%"));
+        assert!(!matches("LIKE", "ada%"));
+        assert!(matches("NOT LIKE", "ada%"));
+        assert!(matches("~", "Love"));
+        assert!(!matches("~", "^love"));
+        assert!(matches("~*", "LOVE"));
+        assert!(matches("!~", "^Bob"));
+        assert!(!matches("!~*", "ADA"));
+        // An invalid regex matches nothing, negated or not
+        assert!(!matches("~", "("));
+        assert!(!matches("!~", "("));
+        assert!(pattern_regex("(", PatternKind::Regex).is_err());
+    }
+
     #[test]
     fn in_and_not_in() {
         let row = json!({"city": "Boston"});
@@ -254,6 +383,11 @@ mod tests {
             &row,
             &[cond("city", "NOT IN", json!("Boston"))]
         ));
+        // SQL: `'Boston' NOT IN ('NYC', NULL)` is NULL, not true
+        assert!(!matches_conditions(
+            &row,
+            &[cond("city", "NOT IN", json!(["NYC", null]))]
+        ));
     }
 
     #[test]
@@ -292,4 +426,17 @@ mod tests {
         let b = json!("hello");
         assert_ne!(compare_json_values(&a, &b), Ordering::Equal);
     }
+
+    #[test]
+    fn numbers_compare_with_decimal_text() {
+        // Wide DECIMAL values are stored as their exact text
+        let wide = json!("12345678.1234567891");
+        assert!(compare_values(&wide, &json!(12345678.12), ">"));
+        assert!(compare_values(&json!(2.5), &json!("2.50"), "="));
+        assert!(!compare_values(&json!(2.5), &json!("2.50"), "!="));
+        assert_eq!(
+            compare_json_values(&json!(99), &json!("100.000000000000000001")),
+            Ordering::Less
+        );
+    }
 }
@@ -84,6 +84,11 @@ impl QueryPerformanceOptimizer {
         })
     }
 
+    /// Count a query whose scan was split across parallel workers
+    pub fn record_parallel_execution(&self) {
+        self.stats.write().parallel_executions += 1;
+    }
+
     pub fn get_statistics(&self) -> Result<OptimizationStats> {
         Ok(self.stats.read().clone())
     }
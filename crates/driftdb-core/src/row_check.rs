@@ -0,0 +1,120 @@
+//! Checking a row before it is inserted
+//!
+//! [`Engine::check_insert_row`] runs the checks an insert of a JSON row
+//! goes through without writing anything: the row is an object, values
+//! fit their columns' declared types, the primary key is set and not
+//! taken, and the table's CHECK, foreign key and registered constraints
+//! hold. Batched inserts check every row this way before writing the
+//! first, and `driftdb ingest --dry-run` uses it to report the lines a
+//! load would reject.
+//!
+//! Columns the schema doesn't declare are let through, as they are for
+//! any insert into a table created without a column list.
+
+use serde_json::Value;
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::schema::ColumnDef;
+
+impl Engine {
+    /// Check `record` as a new row of `table`, returning its primary key.
+    /// Typed columns are normalized in place (JSON documents parsed,
+    /// decimals scaled, ...) and constraint defaults filled in, as they
+    /// would be on insert.
+    pub fn check_insert_row(&self, table: &str, record: &mut Value) -> Result<Value> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        if !record.is_object() {
+            return Err(DriftError::InvalidQuery(
+                "row is not a JSON object".to_string(),
+            ));
+        }
+        let schema = storage.schema().clone();
+
+        *record = crate::sql_bridge::coerce_typed_columns(self, table, record.take(), None)?;
+        for column in &schema.columns {
+            if let Some(value) = record.get(&column.name) {
+                check_column_type(table, column, value)?;
+            }
+        }
+
+        self.constraint_manager
+            .read()
+            .validate_insert(&schema, record, self)
+            .map_err(|e| DriftError::Other(format!("Constraint violation: {}", e)))?;
+        crate::sql_bridge::validate_checks(self, table, record)?;
+        crate::fk::validate_insert(self, table, record)?;
+
+        let primary_key = record
+            .get(&schema.primary_key)
+            .filter(|key| !key.is_null())
+            .cloned()
+            .ok_or_else(|| {
+                DriftError::InvalidQuery(format!(
+                    "Missing primary key field '{}'",
+                    schema.primary_key
+                ))
+            })?;
+        if storage.has_live_key(&primary_key)? {
+            return Err(DriftError::InvalidQuery(format!(
+                "Primary key violation: {} already exists",
+                primary_key
+            )));
+        }
+        Ok(primary_key)
+    }
+}
+
+/// Reject a value that can't be stored in `column`. Types without a
+/// fixed JSON form (dates, times, ...) take whatever they're given.
+fn check_column_type(table: &str, column: &ColumnDef, value: &Value) -> Result<()> {
+    if value.is_null() {
+        return Ok(());
+    }
+    let upper = column.col_type.trim().to_uppercase();
+    let (base, args) = match upper.split_once('(') {
+        Some((base, args)) => (base.trim(), args.trim_end_matches(')')),
+        None => (upper.as_str(), ""),
+    };
+    let fits = match base {
+        "SMALLINT" | "INT2" | "INT" | "INTEGER" | "INT4" | "SERIAL" | "BIGINT" | "INT8"
+        | "BIGSERIAL" => value.is_i64() || value.is_u64(),
+        "REAL" | "FLOAT4" | "FLOAT" | "FLOAT8" | "DOUBLE" | "DOUBLE PRECISION" => value.is_number(),
+        "BOOL" | "BOOLEAN" => value.is_boolean(),
+        "VARCHAR" | "CHARACTER VARYING" | "CHAR" | "CHARACTER" | "TEXT" => {
+            let Some(text) = value.as_str() else {
+                return Err(type_mismatch(table, column, value));
+            };
+            match args.trim().parse::<usize>() {
+                Ok(limit) if text.chars().count() > limit => {
+                    return Err(DriftError::Validation(format!(
+                        "value too long for column \"{}\" of relation \"{}\" ({})",
+                        column.name,
+                        table,
+                        column.col_type.to_lowercase()
+                    )));
+                }
+                _ => true,
+            }
+        }
+        _ => true,
+    };
+    if fits {
+        Ok(())
+    } else {
+        Err(type_mismatch(table, column, value))
+    }
+}
+
+fn type_mismatch(table: &str, column: &ColumnDef, value: &Value) -> DriftError {
+    DriftError::Validation(format!(
+        "column \"{}\" of relation \"{}\" is of type {} but the value is {}",
+        column.name,
+        table,
+        column.col_type.to_lowercase(),
+        value
+    ))
+}
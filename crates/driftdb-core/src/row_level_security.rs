@@ -170,6 +170,29 @@ pub enum PolicyResult {
     Filter(String),
 }
 
+/// The policies a session's writes are checked against, and who is
+/// writing. Set on `sql_bridge::SessionContext`, it makes inserted rows
+/// pass the table's INSERT `WITH CHECK` policies.
+#[derive(Clone)]
+pub struct RowSecurity {
+    pub manager: Arc<RlsManager>,
+    pub context: SecurityContext,
+}
+
+impl RowSecurity {
+    pub fn new(manager: Arc<RlsManager>, context: SecurityContext) -> Self {
+        Self { manager, context }
+    }
+}
+
+impl std::fmt::Debug for RowSecurity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RowSecurity")
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
 /// Row-level security manager
 pub struct RlsManager {
     /// Table policies: table_name -> policies
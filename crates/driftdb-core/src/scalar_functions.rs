@@ -0,0 +1,536 @@
+//! Scalar SQL functions
+//!
+//! String, date and conversion functions usable anywhere a row value is:
+//! in the select list, in WHERE, in UPDATE ... SET and in column defaults.
+//!
+//! - Strings: `LOWER`, `UPPER`, `LENGTH`, `TRIM` (and `LTRIM`/`RTRIM`),
+//!   `SUBSTRING`, `CONCAT`
+//! - NULL handling: `COALESCE`, `NULLIF`
+//! - Dates: `NOW()`, `DATE_TRUNC`, `EXTRACT` (and `DATE_PART`)
+//! - `CAST(x AS type)` and `x::type`
+//!
+//! A NULL argument makes the result NULL, except in `COALESCE` and
+//! `NULLIF`, and in `CONCAT`, which skips NULLs. An argument of the wrong
+//! type is an error, as PostgreSQL reports it: `function lower(integer)
+//! does not exist`.
+//!
+//! Dates and timestamps are stored as text, so the date functions read
+//! `2025-01-01`, `2025-01-01 10:30:00[.fff]` or RFC 3339 text (converted to
+//! UTC) and return text in the same forms. `DATE_TRUNC` to a day or longer
+//! returns a date, `2025-01-01`, so it compares equal to a date literal;
+//! to an hour or shorter it returns a timestamp.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Functions this module evaluates, by lowercase name
+const FUNCTIONS: &[&str] = &[
+    "char_length",
+    "character_length",
+    "coalesce",
+    "concat",
+    "current_timestamp",
+    "date_part",
+    "date_trunc",
+    "gen_random_uuid",
+    "length",
+    "lower",
+    "ltrim",
+    "now",
+    "nullif",
+    "rtrim",
+    "substr",
+    "substring",
+    "trim",
+    "upper",
+];
+
+/// Whether `name` is a scalar function, as opposed to an aggregate or
+/// window function
+pub fn is_scalar(name: &str) -> bool {
+    FUNCTIONS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// Whether calls to `name` with the same arguments give the same result
+/// within a statement, so a call on literals can be evaluated once
+pub fn is_stable(name: &str) -> bool {
+    is_scalar(name) && !name.eq_ignore_ascii_case("gen_random_uuid")
+}
+
+/// Which end(s) of a string `TRIM` strips
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimSide {
+    Both,
+    Leading,
+    Trailing,
+}
+
+/// Call the scalar function `name` on `args`. `None` when there is no
+/// such function.
+pub fn call(name: &str, args: &[Value]) -> Result<Option<Value>> {
+    let name = name.to_ascii_lowercase();
+    let value = match (name.as_str(), args) {
+        ("gen_random_uuid", []) => crate::uuids::generate(),
+        ("now" | "current_timestamp", []) => Value::String(
+            chrono::Utc::now()
+                .naive_utc()
+                .format("%Y-%m-%d %H:%M:%S%.6f")
+                .to_string(),
+        ),
+        ("coalesce", [_, ..]) => args
+            .iter()
+            .find(|arg| !arg.is_null())
+            .cloned()
+            .unwrap_or(Value::Null),
+        ("nullif", [a, b]) => {
+            let equal = !b.is_null()
+                && crate::query::predicate::compare_json_values(a, b) == std::cmp::Ordering::Equal;
+            if equal {
+                Value::Null
+            } else {
+                a.clone()
+            }
+        }
+        ("concat", _) => Value::String(
+            args.iter()
+                .filter(|arg| !arg.is_null())
+                .map(to_text)
+                .collect(),
+        ),
+        (_, _) if args.iter().any(Value::is_null) && is_scalar(&name) => Value::Null,
+        ("lower", [Value::String(s)]) => Value::String(s.to_lowercase()),
+        ("upper", [Value::String(s)]) => Value::String(s.to_uppercase()),
+        ("length" | "char_length" | "character_length", [Value::String(s)]) => {
+            Value::from(s.chars().count())
+        }
+        ("trim", [value]) => trim(value, None, TrimSide::Both)?,
+        ("ltrim", [value]) => trim(value, None, TrimSide::Leading)?,
+        ("rtrim", [value]) => trim(value, None, TrimSide::Trailing)?,
+        ("trim", [value, chars]) => trim(value, Some(chars), TrimSide::Both)?,
+        ("ltrim", [value, chars]) => trim(value, Some(chars), TrimSide::Leading)?,
+        ("rtrim", [value, chars]) => trim(value, Some(chars), TrimSide::Trailing)?,
+        ("substring" | "substr", [value, from]) => substring(value, Some(from), None)?,
+        ("substring" | "substr", [value, from, count]) => {
+            substring(value, Some(from), Some(count))?
+        }
+        ("date_trunc", [Value::String(unit), value]) => date_trunc(unit, value)?,
+        ("date_part", [Value::String(field), value]) => extract(field, value)?,
+        _ if is_scalar(&name) => return Err(no_such_function(&name, args)),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// `TRIM([side] [chars FROM] value)`: strip `chars` (spaces by default)
+/// from one or both ends
+pub fn trim(value: &Value, chars: Option<&Value>, side: TrimSide) -> Result<Value> {
+    let name = match side {
+        TrimSide::Both => "trim",
+        TrimSide::Leading => "ltrim",
+        TrimSide::Trailing => "rtrim",
+    };
+    let chars: Vec<char> = match chars {
+        None => vec![' '],
+        Some(Value::Null) => return Ok(Value::Null),
+        Some(Value::String(chars)) => chars.chars().collect(),
+        Some(other) => return Err(no_such_function(name, &[value.clone(), other.clone()])),
+    };
+    let text = match value {
+        Value::Null => return Ok(Value::Null),
+        Value::String(text) => text.as_str(),
+        other => return Err(no_such_function(name, std::slice::from_ref(other))),
+    };
+    let stripped = match side {
+        TrimSide::Both => text.trim_matches(chars.as_slice()),
+        TrimSide::Leading => text.trim_start_matches(chars.as_slice()),
+        TrimSide::Trailing => text.trim_end_matches(chars.as_slice()),
+    };
+    Ok(Value::String(stripped.to_string()))
+}
+
+/// `SUBSTRING(value FROM from FOR count)`: `count` characters from the
+/// 1-based position `from`, which may be before the start
+pub fn substring(value: &Value, from: Option<&Value>, count: Option<&Value>) -> Result<Value> {
+    let signature = || {
+        let mut args = vec![value.clone()];
+        args.extend(from.cloned());
+        args.extend(count.cloned());
+        no_such_function("substring", &args)
+    };
+    if value.is_null() || from.is_some_and(Value::is_null) || count.is_some_and(Value::is_null) {
+        return Ok(Value::Null);
+    }
+    let text = value.as_str().ok_or_else(signature)?;
+    let start = match from {
+        Some(from) => from.as_i64().ok_or_else(signature)?,
+        None => 1,
+    };
+    let end = match count {
+        Some(count) => {
+            let count = count.as_i64().ok_or_else(signature)?;
+            if count < 0 {
+                return Err(DriftError::InvalidQuery(
+                    "negative substring length not allowed".to_string(),
+                ));
+            }
+            start.saturating_add(count)
+        }
+        None => i64::MAX,
+    };
+    let skip = usize::try_from(start.max(1) - 1).unwrap_or(usize::MAX);
+    let take = usize::try_from(end.saturating_sub(start.max(1)).max(0)).unwrap_or(usize::MAX);
+    Ok(Value::String(text.chars().skip(skip).take(take).collect()))
+}
+
+/// `DATE_TRUNC(unit, value)`: the timestamp `value` rounded down to the
+/// start of its `unit`
+pub fn date_trunc(unit: &str, value: &Value) -> Result<Value> {
+    let Some(timestamp) = timestamp_arg("timestamp", value)? else {
+        return Ok(Value::Null);
+    };
+    let date = timestamp.date();
+    let truncated = match unit.to_ascii_lowercase().as_str() {
+        "second" => timestamp.with_nanosecond(0),
+        "minute" => date.and_hms_opt(timestamp.hour(), timestamp.minute(), 0),
+        "hour" => date.and_hms_opt(timestamp.hour(), 0, 0),
+        "day" => return Ok(format_date(date)),
+        "week" => {
+            let monday =
+                date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+            return Ok(format_date(monday));
+        }
+        "month" => return Ok(format_date(first_of(date.year(), date.month()))),
+        "quarter" => {
+            let month = (date.month() - 1) / 3 * 3 + 1;
+            return Ok(format_date(first_of(date.year(), month)));
+        }
+        "year" => return Ok(format_date(first_of(date.year(), 1))),
+        other => return Err(unknown_unit(other)),
+    };
+    Ok(truncated.map_or(Value::Null, format_timestamp))
+}
+
+/// `EXTRACT(field FROM value)`: one field of the timestamp `value`
+pub fn extract(field: &str, value: &Value) -> Result<Value> {
+    let Some(timestamp) = timestamp_arg("timestamp", value)? else {
+        return Ok(Value::Null);
+    };
+    let fraction = timestamp.nanosecond() as f64 / 1e9;
+    let field = field.trim_matches('\'').to_ascii_lowercase();
+    let value = match field.as_str() {
+        "year" => Value::from(timestamp.year()),
+        "quarter" => Value::from((timestamp.month() - 1) / 3 + 1),
+        "month" => Value::from(timestamp.month()),
+        "week" => Value::from(timestamp.iso_week().week()),
+        "day" => Value::from(timestamp.day()),
+        "dow" | "dayofweek" => Value::from(timestamp.weekday().num_days_from_sunday()),
+        "isodow" => Value::from(timestamp.weekday().number_from_monday()),
+        "doy" | "dayofyear" => Value::from(timestamp.ordinal()),
+        "hour" => Value::from(timestamp.hour()),
+        "minute" => Value::from(timestamp.minute()),
+        "second" => number(timestamp.second() as f64 + fraction),
+        "epoch" => number(timestamp.and_utc().timestamp() as f64 + fraction),
+        other => return Err(unknown_unit(other)),
+    };
+    Ok(value)
+}
+
+/// `CAST(value AS type_name)`, `type_name` as written, e.g. `INTEGER`,
+/// `VARCHAR(20)` or `NUMERIC(10,2)`
+pub fn cast(value: Value, type_name: &str) -> Result<Value> {
+    if value.is_null() {
+        return Ok(Value::Null);
+    }
+    let upper = type_name.trim().to_ascii_uppercase();
+    let base = base_type(&upper);
+    let invalid = |type_name: &str, value: &Value| {
+        DriftError::InvalidQuery(format!(
+            "invalid input syntax for type {}: \"{}\"",
+            type_name,
+            to_text(value)
+        ))
+    };
+    match base {
+        "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "INT2" | "INT4" | "INT8" => match &value {
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::from(i)),
+                None => n
+                    .as_f64()
+                    .map(f64::round)
+                    .filter(|f| f.abs() < i64::MAX as f64)
+                    .map(|f| Value::from(f as i64))
+                    .ok_or_else(|| DriftError::InvalidQuery("integer out of range".to_string())),
+            },
+            Value::String(text) => text
+                .trim()
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| invalid("integer", &value)),
+            Value::Bool(b) => Ok(Value::from(i64::from(*b))),
+            _ => Err(cannot_cast(&value, "integer")),
+        },
+        "NUMERIC" | "DECIMAL" | "DEC" => {
+            let spec = crate::decimal::DecimalSpec::from_col_type(&upper)
+                .ok_or_else(|| cannot_cast(&value, "numeric"))?;
+            match &value {
+                Value::Number(_) | Value::String(_) => spec.coerce(value),
+                _ => Err(cannot_cast(&value, "numeric")),
+            }
+        }
+        "REAL" | "FLOAT" | "FLOAT4" | "FLOAT8" | "DOUBLE" => match &value {
+            Value::Number(n) => Ok(number(n.as_f64().unwrap_or_default())),
+            Value::String(text) => text
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(number)
+                .ok_or_else(|| invalid("double precision", &value)),
+            _ => Err(cannot_cast(&value, "double precision")),
+        },
+        "TEXT" | "VARCHAR" | "CHAR" | "CHARACTER" | "STRING" => Ok(Value::String(to_text(&value))),
+        "BOOL" | "BOOLEAN" => match &value {
+            Value::Bool(_) => Ok(value),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => Ok(Value::Bool(i != 0)),
+                None => Err(cannot_cast(&value, "boolean")),
+            },
+            Value::String(text) => match text.trim().to_ascii_lowercase().as_str() {
+                "t" | "true" | "y" | "yes" | "on" | "1" => Ok(Value::Bool(true)),
+                "f" | "false" | "n" | "no" | "off" | "0" => Ok(Value::Bool(false)),
+                _ => Err(invalid("boolean", &value)),
+            },
+            _ => Err(cannot_cast(&value, "boolean")),
+        },
+        "DATE" => match timestamp_arg("date", &value)? {
+            Some(timestamp) => Ok(format_date(timestamp.date())),
+            None => Ok(Value::Null),
+        },
+        "TIMESTAMP" | "TIMESTAMPTZ" | "DATETIME" => match timestamp_arg("timestamp", &value)? {
+            Some(timestamp) => Ok(format_timestamp(timestamp)),
+            None => Ok(Value::Null),
+        },
+        "UUID" => crate::uuids::parse(value),
+        "JSON" | "JSONB" => match value {
+            Value::String(text) => serde_json::from_str(&text).map_err(|_| {
+                DriftError::InvalidQuery(format!(
+                    "invalid input syntax for type json: \"{}\"",
+                    text
+                ))
+            }),
+            other => Ok(other),
+        },
+        _ => Err(DriftError::InvalidQuery(format!(
+            "type \"{}\" does not exist",
+            type_name.trim().to_lowercase()
+        ))),
+    }
+}
+
+/// The type of the values `CAST(x AS type_name)` gives, named as
+/// [`type_name`] names it. `None` for JSON, which may hold any value.
+pub fn cast_type(type_name: &str) -> Option<&'static str> {
+    match base_type(&type_name.trim().to_ascii_uppercase()) {
+        "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "INT2" | "INT4" | "INT8" => Some("integer"),
+        "NUMERIC" | "DECIMAL" | "DEC" | "REAL" | "FLOAT" | "FLOAT4" | "FLOAT8" | "DOUBLE" => {
+            Some("numeric")
+        }
+        "BOOL" | "BOOLEAN" => Some("boolean"),
+        "JSON" | "JSONB" => None,
+        // Dates, timestamps and UUIDs are stored as text
+        _ => Some("text"),
+    }
+}
+
+/// The type of the values the function `name` returns, named as
+/// [`type_name`] names it. `None` when it depends on the arguments.
+pub fn result_type(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "char_length" | "character_length" | "length" => Some("integer"),
+        "date_part" => Some("numeric"),
+        "coalesce" | "nullif" => None,
+        _ if is_scalar(name) => Some("text"),
+        _ => None,
+    }
+}
+
+/// `VARCHAR(20)` and `TIMESTAMP WITH TIME ZONE` convert like their base type
+fn base_type(upper: &str) -> &str {
+    upper
+        .split(|c: char| c == '(' || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+}
+
+/// A value as text, the way CONCAT and casts to text print it
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `value` read as a `target` (date or timestamp). NULL gives `None`.
+fn timestamp_arg(target: &str, value: &Value) -> Result<Option<NaiveDateTime>> {
+    let text = match value {
+        Value::Null => return Ok(None),
+        Value::String(text) => text.trim(),
+        other => return Err(cannot_cast(other, target)),
+    };
+    let parsed = [
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        chrono::DateTime::parse_from_rfc3339(text)
+            .ok()
+            .map(|timestamp| timestamp.naive_utc())
+    })
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    });
+    match parsed {
+        Some(timestamp) => Ok(Some(timestamp)),
+        None => Err(DriftError::InvalidQuery(format!(
+            "invalid input syntax for type {}: \"{}\"",
+            target, text
+        ))),
+    }
+}
+
+fn first_of(year: i32, month: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, 1).unwrap_or_default()
+}
+
+fn format_date(date: NaiveDate) -> Value {
+    Value::String(date.format("%Y-%m-%d").to_string())
+}
+
+fn format_timestamp(timestamp: NaiveDateTime) -> Value {
+    Value::String(timestamp.format("%Y-%m-%d %H:%M:%S%.f").to_string())
+}
+
+/// A number, as an integer when it has no fraction
+fn number(f: f64) -> Value {
+    if f.fract() == 0.0 && f.abs() < i64::MAX as f64 {
+        Value::from(f as i64)
+    } else {
+        serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// The SQL type name PostgreSQL would give `value` in an error
+pub fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "unknown",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "numeric",
+        Value::Number(_) => "integer",
+        Value::String(_) => "text",
+        Value::Array(_) => "array",
+        Value::Object(_) => "jsonb",
+    }
+}
+
+fn no_such_function(name: &str, args: &[Value]) -> DriftError {
+    let types: Vec<&str> = args.iter().map(type_name).collect();
+    DriftError::InvalidQuery(format!(
+        "function {}({}) does not exist",
+        name,
+        types.join(", ")
+    ))
+}
+
+fn cannot_cast(value: &Value, target: &str) -> DriftError {
+    DriftError::InvalidQuery(format!(
+        "cannot cast type {} to {}",
+        type_name(value),
+        target
+    ))
+}
+
+fn unknown_unit(unit: &str) -> DriftError {
+    DriftError::InvalidQuery(format!(
+        "unit \"{}\" not recognized for type timestamp",
+        unit
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn call_ok(name: &str, args: &[Value]) -> Value {
+        call(name, args).unwrap().unwrap()
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(call_ok("LOWER", &[json!("MiXeD")]), json!("mixed"));
+        assert_eq!(call_ok("length", &[json!("héllo")]), json!(5));
+        assert_eq!(
+            call_ok("concat", &[json!("a"), Value::Null, json!(1)]),
+            json!("a1")
+        );
+        assert_eq!(
+            trim(&json!("xxhixx"), Some(&json!("x")), TrimSide::Leading).unwrap(),
+            json!("hixx")
+        );
+        assert_eq!(
+            substring(&json!("abcdef"), Some(&json!(0)), Some(&json!(3))).unwrap(),
+            json!("ab")
+        );
+        assert_eq!(
+            substring(&json!("abcdef"), Some(&json!(3)), None).unwrap(),
+            json!("cdef")
+        );
+        assert_eq!(
+            substring(&json!("abcdef"), Some(&json!(-5)), Some(&json!(2))).unwrap(),
+            json!("")
+        );
+        assert!(call("no_such", &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn dates() {
+        let ts = json!("2025-05-14 13:45:30.25");
+        assert_eq!(date_trunc("month", &ts).unwrap(), json!("2025-05-01"));
+        assert_eq!(date_trunc("quarter", &ts).unwrap(), json!("2025-04-01"));
+        // 2025-05-14 is a Wednesday
+        assert_eq!(date_trunc("week", &ts).unwrap(), json!("2025-05-12"));
+        assert_eq!(
+            date_trunc("hour", &ts).unwrap(),
+            json!("2025-05-14 13:00:00")
+        );
+        assert_eq!(extract("dow", &ts).unwrap(), json!(3));
+        assert_eq!(extract("second", &ts).unwrap(), json!(30.25));
+        assert_eq!(
+            extract("epoch", &json!("1970-01-02T00:00:00+01:00")).unwrap(),
+            json!(82800)
+        );
+        assert!(date_trunc("fortnight", &ts).is_err());
+    }
+
+    #[test]
+    fn casts() {
+        assert_eq!(cast(json!(" 42 "), "INT").unwrap(), json!(42));
+        assert_eq!(cast(json!(2.7), "BIGINT").unwrap(), json!(3));
+        assert_eq!(cast(json!("1.005"), "NUMERIC(5,2)").unwrap(), json!(1.01));
+        assert_eq!(cast(json!(7), "VARCHAR(10)").unwrap(), json!("7"));
+        assert_eq!(cast(json!("yes"), "BOOLEAN").unwrap(), json!(true));
+        assert_eq!(
+            cast(json!("2025-01-01T08:00:00Z"), "DATE").unwrap(),
+            json!("2025-01-01")
+        );
+        assert!(cast(json!(true), "DATE").is_err());
+        assert!(cast(json!(1), "GEOMETRY").is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
 use std::path::Path;
 
 use crate::errors::{DriftError, Result};
+use crate::storage::{Compression, RetentionPolicy, StorageFormat};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ColumnDef {
@@ -14,11 +15,99 @@ pub struct ColumnDef {
     pub index: bool,
 }
 
+/// A column change made by `ALTER TABLE`. Events are never rewritten, so
+/// rows are presented through these changes on read. `sequence` is the first
+/// table sequence the change applies to; time-travel reads at an earlier
+/// sequence see the table as it was before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ColumnChange {
+    Add {
+        column: String,
+        #[serde(default)]
+        default: Option<serde_json::Value>,
+        sequence: u64,
+    },
+    Drop {
+        column: String,
+        sequence: u64,
+    },
+    Rename {
+        from: String,
+        to: String,
+        sequence: u64,
+    },
+}
+
+impl ColumnChange {
+    pub fn sequence(&self) -> u64 {
+        match self {
+            ColumnChange::Add { sequence, .. }
+            | ColumnChange::Drop { sequence, .. }
+            | ColumnChange::Rename { sequence, .. } => *sequence,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
     pub name: String,
     pub primary_key: String,
     pub columns: Vec<ColumnDef>,
+    /// Column changes in the order they were made.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<ColumnChange>,
+    /// Column `DEFAULT` expressions from `CREATE TABLE`, as SQL text.
+    /// They are evaluated per row, so volatile defaults such as
+    /// `gen_random_uuid()` produce a fresh value each time.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub defaults: BTreeMap<String, String>,
+    /// Enum-typed columns with their type. Storage writes these columns
+    /// as label positions (see [`crate::enums`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub enums: BTreeMap<String, crate::enums::EnumType>,
+    /// `CHECK` constraints from `CREATE TABLE`, in declaration order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<CheckConstraint>,
+    /// Single columns declared `UNIQUE` at `CREATE TABLE`. They are
+    /// indexed like any other column; being declared unique is what
+    /// makes them valid `ON CONFLICT` targets.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub unique: BTreeSet<String>,
+    /// Outgoing foreign keys, registered with [`crate::fk`] when the
+    /// table is loaded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub foreign_keys: Vec<crate::fk::ForeignKey>,
+    /// Codec for segments written from now on, set with
+    /// `ALTER TABLE t SET (compression = ...)`
+    #[serde(default, skip_serializing_if = "Compression::is_none")]
+    pub compression: Compression,
+    /// False-positive rate of the per-segment primary key filters, set
+    /// with `ALTER TABLE t SET (bloom_filter = on)`; `None` when off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bloom_filter_fpp: Option<f64>,
+    /// Columns a columnar table keeps per-chunk bloom filters of, for
+    /// equality lookups, set with `ALTER TABLE t SET (bloom_filter_columns
+    /// = '...')` (see [`crate::storage::columnar`])
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub bloom_filter_columns: BTreeSet<String>,
+    /// How scans read the table's current rows, set with
+    /// `WITH (storage = columnar)` (see [`crate::storage::columnar`])
+    #[serde(default, skip_serializing_if = "StorageFormat::is_row")]
+    pub storage: StorageFormat,
+    /// How much history `VACUUM` keeps for time travel, set with
+    /// `ALTER TABLE t SET (retain_history_for = ...)` (see
+    /// [`crate::storage::retention`])
+    #[serde(default, skip_serializing_if = "RetentionPolicy::is_unbounded")]
+    pub retention: RetentionPolicy,
+}
+
+/// A named `CHECK` constraint. The expression is kept as SQL text, like
+/// column defaults, and evaluated against each row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    pub name: String,
+    pub expression: String,
 }
 
 impl Schema {
@@ -27,6 +116,17 @@ impl Schema {
             name,
             primary_key,
             columns,
+            changes: Vec::new(),
+            defaults: BTreeMap::new(),
+            enums: BTreeMap::new(),
+            checks: Vec::new(),
+            unique: BTreeSet::new(),
+            foreign_keys: Vec::new(),
+            compression: Compression::None,
+            bloom_filter_fpp: None,
+            bloom_filter_columns: BTreeSet::new(),
+            storage: StorageFormat::Row,
+            retention: RetentionPolicy::default(),
         }
     }
 
@@ -42,6 +142,73 @@ impl Schema {
         self.columns.iter().any(|c| c.name == name)
     }
 
+    /// Present a stored row as of `sequence` (`None` = current): columns
+    /// added by then appear with their default, dropped ones disappear and
+    /// renamed ones move to their new name.
+    pub fn evolve_row(&self, row: &mut serde_json::Value, sequence: Option<u64>) {
+        let Some(row) = row.as_object_mut() else {
+            return;
+        };
+        for change in &self.changes {
+            if sequence.is_some_and(|seq| change.sequence() > seq) {
+                break;
+            }
+            match change {
+                ColumnChange::Add {
+                    column, default, ..
+                } => {
+                    if !row.contains_key(column) {
+                        row.insert(
+                            column.clone(),
+                            default.clone().unwrap_or(serde_json::Value::Null),
+                        );
+                    }
+                }
+                ColumnChange::Drop { column, .. } => {
+                    row.remove(column);
+                }
+                ColumnChange::Rename { from, to, .. } => {
+                    if let Some(value) = row.remove(from) {
+                        row.entry(to.clone()).or_insert(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Replace enum labels in an event payload with their positions
+    pub fn encode_enums(&self, payload: &mut serde_json::Value) -> Result<()> {
+        if let Some(row) = payload.as_object_mut() {
+            for (column, enum_type) in &self.enums {
+                if let Some(value) = row.get_mut(column) {
+                    *value = enum_type.encode(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn stored enum positions in an event payload back into labels
+    pub fn decode_enums(&self, payload: &mut serde_json::Value) {
+        if let Some(row) = payload.as_object_mut() {
+            for (column, enum_type) in &self.enums {
+                if let Some(value) = row.get_mut(column) {
+                    *value = enum_type.decode(value);
+                }
+            }
+        }
+    }
+
+    /// Whether `name` was dropped or renamed away earlier. Old events still
+    /// carry values under such names, so they can't be reused.
+    pub fn is_retired_column(&self, name: &str) -> bool {
+        self.changes.iter().any(|change| match change {
+            ColumnChange::Drop { column, .. } => column == name,
+            ColumnChange::Rename { from, .. } => from == name,
+            ColumnChange::Add { .. } => false,
+        })
+    }
+
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = fs::read_to_string(path)?;
         Ok(serde_yaml::from_str(&content)?)
@@ -0,0 +1,215 @@
+//! Schema namespaces and table name resolution
+//!
+//! Tables live in named schemas (`CREATE SCHEMA app`). A table in the
+//! default `public` schema is stored under its bare name, so databases
+//! created before schemas existed keep working unchanged; a table in any
+//! other schema is stored as `schema.table`.
+//!
+//! Each session has a search path (`SET search_path TO app, public`).
+//! An unqualified table name is looked up in every schema on the path. If
+//! exactly one of them has the table, that's the one used; if several do,
+//! the reference is ambiguous and the statement fails rather than silently
+//! picking one. New unqualified tables are created in the first schema on
+//! the path that exists.
+
+use crate::errors::{DriftError, Result};
+
+/// The schema every database starts with
+pub const DEFAULT_SCHEMA: &str = "public";
+
+/// The engine's name for `table` in `schema`
+pub fn storage_name(schema: &str, table: &str) -> String {
+    if schema == DEFAULT_SCHEMA {
+        table.to_string()
+    } else {
+        format!("{}.{}", schema, table)
+    }
+}
+
+/// Split an engine table name into its schema and table
+pub fn split_storage_name(name: &str) -> (&str, &str) {
+    name.split_once('.').unwrap_or((DEFAULT_SCHEMA, name))
+}
+
+/// The search path new sessions start with
+pub fn default_search_path() -> Vec<String> {
+    vec![DEFAULT_SCHEMA.to_string()]
+}
+
+/// Parse the value of `SET search_path TO ...`: a comma-separated list of
+/// schema names, optionally quoted. `DEFAULT` restores the default path.
+pub fn parse_search_path(value: &str) -> Result<Vec<String>> {
+    let value = value.trim().trim_end_matches(';').trim();
+    if value.eq_ignore_ascii_case("DEFAULT") {
+        return Ok(default_search_path());
+    }
+
+    let mut path = Vec::new();
+    for item in value.split(',').map(str::trim) {
+        let schema = if let Some(quoted) = item
+            .strip_prefix('"')
+            .and_then(|i| i.strip_suffix('"'))
+            .or_else(|| item.strip_prefix('\'').and_then(|i| i.strip_suffix('\'')))
+        {
+            quoted.to_string()
+        } else {
+            item.to_lowercase()
+        };
+        if schema.is_empty() {
+            return Err(DriftError::Parse(format!(
+                "invalid value for parameter \"search_path\": \"{}\"",
+                value
+            )));
+        }
+        path.push(schema);
+    }
+    Ok(path)
+}
+
+/// Resolve a table reference written as `parts` (`[table]` or
+/// `[schema, table]`) to the engine's table name. `table_exists` is asked
+/// about engine names. An unqualified name found in no schema on the path
+/// is returned as written, so callers report it as a missing table or
+/// treat it as a view or CTE name.
+pub fn resolve(
+    search_path: &[String],
+    parts: &[&str],
+    table_exists: impl Fn(&str) -> bool,
+) -> Result<String> {
+    match parts {
+        [schema, table] => Ok(storage_name(schema, table)),
+        [table] => {
+            let found: Vec<&String> = search_path
+                .iter()
+                .filter(|schema| table_exists(&storage_name(schema, table)))
+                .collect();
+            match found.as_slice() {
+                [] => Ok(table.to_string()),
+                [schema] => Ok(storage_name(schema, table)),
+                _ => Err(DriftError::InvalidQuery(format!(
+                    "table reference \"{}\" is ambiguous: it exists in schemas {}",
+                    table,
+                    found
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))),
+            }
+        }
+        _ => Err(improper_name(parts)),
+    }
+}
+
+/// The engine name for a table being created as `parts`. An unqualified
+/// table goes into the first schema on the path that exists.
+pub fn creation_name(
+    search_path: &[String],
+    parts: &[&str],
+    schema_exists: impl Fn(&str) -> bool,
+) -> Result<String> {
+    match parts {
+        [schema, table] => {
+            if !schema_exists(schema) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "schema \"{}\" does not exist",
+                    schema
+                )));
+            }
+            Ok(storage_name(schema, table))
+        }
+        [table] => search_path
+            .iter()
+            .find(|schema| schema_exists(schema))
+            .map(|schema| storage_name(schema, table))
+            .ok_or_else(|| {
+                DriftError::InvalidQuery("no schema has been selected to create in".to_string())
+            }),
+        _ => Err(improper_name(parts)),
+    }
+}
+
+fn improper_name(parts: &[&str]) -> DriftError {
+    DriftError::InvalidQuery(format!(
+        "improper qualified name (too many dotted names): {}",
+        parts.join(".")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(schemas: &[&str]) -> Vec<String> {
+        schemas.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_storage_names() {
+        assert_eq!(storage_name("public", "users"), "users");
+        assert_eq!(storage_name("app", "users"), "app.users");
+        assert_eq!(split_storage_name("app.users"), ("app", "users"));
+        assert_eq!(split_storage_name("users"), ("public", "users"));
+    }
+
+    #[test]
+    fn test_parse_search_path() {
+        assert_eq!(
+            parse_search_path("App, \"Audit\", 'public'").unwrap(),
+            path(&["app", "Audit", "public"])
+        );
+        assert_eq!(parse_search_path("DEFAULT").unwrap(), path(&["public"]));
+        assert!(parse_search_path("app,,public").is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let tables = ["users", "app.orders", "app.users", "audit.events"];
+        let exists = |name: &str| tables.contains(&name);
+
+        let app_first = path(&["app", "audit"]);
+        assert_eq!(
+            resolve(&app_first, &["orders"], exists).unwrap(),
+            "app.orders"
+        );
+        assert_eq!(
+            resolve(&app_first, &["events"], exists).unwrap(),
+            "audit.events"
+        );
+        assert_eq!(
+            resolve(&app_first, &["users"], exists).unwrap(),
+            "app.users"
+        );
+        assert_eq!(
+            resolve(&app_first, &["missing"], exists).unwrap(),
+            "missing"
+        );
+        assert_eq!(
+            resolve(&app_first, &["public", "users"], exists).unwrap(),
+            "users"
+        );
+
+        let err = resolve(&path(&["app", "public"]), &["users"], exists).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("\"users\" is ambiguous: it exists in schemas app, public"));
+        assert!(resolve(&app_first, &["a", "b", "c"], exists).is_err());
+    }
+
+    #[test]
+    fn test_creation_name() {
+        let schemas = ["public", "app"];
+        let exists = |name: &str| schemas.contains(&name);
+
+        assert_eq!(
+            creation_name(&path(&["missing", "app"]), &["t"], exists).unwrap(),
+            "app.t"
+        );
+        assert_eq!(
+            creation_name(&path(&["app"]), &["public", "t"], exists).unwrap(),
+            "t"
+        );
+        assert!(creation_name(&path(&["app"]), &["nope", "t"], exists).is_err());
+        assert!(creation_name(&path(&["missing"]), &["t"], exists).is_err());
+    }
+}
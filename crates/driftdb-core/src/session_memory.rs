@@ -0,0 +1,157 @@
+//! Per-session memory accounting
+//!
+//! Sorts, hash tables, grouped rows and result buffers reserve the memory
+//! they hold against the session whose statement builds them. With
+//! `max_session_memory` set, a reservation that would take the session
+//! over it fails the statement with an out-of-memory error, so one
+//! runaway query fails on its own instead of exhausting the server's
+//! memory and taking every other connection down with it.
+//!
+//! Sizes are the same estimates `work_mem` uses. What a statement
+//! reserved is released when it finishes; statements it runs along the
+//! way (views, triggers, procedures) count against the same session.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::errors::{DriftError, Result};
+
+/// Memory a session's running statement holds, and the most it has held
+#[derive(Debug, Default)]
+pub struct SessionMemory {
+    current: AtomicU64,
+    peak: AtomicU64,
+}
+
+impl SessionMemory {
+    /// Bytes the running statement holds; 0 between statements
+    pub fn current(&self) -> u64 {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// The most bytes any of the session's statements held at once
+    pub fn peak(&self) -> u64 {
+        self.peak.load(Ordering::Relaxed)
+    }
+
+    fn add(&self, bytes: u64) {
+        let current = self.current.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn release(&self, bytes: u64) {
+        let _ = self
+            .current
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(bytes))
+            });
+    }
+}
+
+/// The session the running statement reserves memory against
+struct Account {
+    memory: Arc<SessionMemory>,
+    /// `max_session_memory` in bytes; 0 for no limit
+    limit: u64,
+    /// Bytes reserved since the statement started
+    reserved: u64,
+}
+
+thread_local! {
+    static ACCOUNT: RefCell<Option<Account>> = const { RefCell::new(None) };
+}
+
+/// RAII guard for a statement's reservations, released on drop
+pub(crate) struct StatementMemory {
+    entered: bool,
+}
+
+/// Account what the statement about to run reserves to `memory`, up to
+/// `limit` bytes (0 for no limit). A statement run by another one keeps
+/// the outer statement's account.
+pub(crate) fn enter(memory: &Arc<SessionMemory>, limit: u64) -> StatementMemory {
+    let entered = ACCOUNT.with(|a| {
+        let mut account = a.borrow_mut();
+        if account.is_some() {
+            return false;
+        }
+        *account = Some(Account {
+            memory: memory.clone(),
+            limit,
+            reserved: 0,
+        });
+        true
+    });
+    StatementMemory { entered }
+}
+
+impl Drop for StatementMemory {
+    fn drop(&mut self) {
+        if !self.entered {
+            return;
+        }
+        if let Some(account) = ACCOUNT.with(|a| a.borrow_mut().take()) {
+            account.memory.release(account.reserved);
+        }
+    }
+}
+
+/// Reserve `bytes` for the rest of the statement. Fails, reserving
+/// nothing, if that would take the session over `max_session_memory`.
+/// Outside a session statement nothing is accounted.
+pub(crate) fn reserve(bytes: usize) -> Result<()> {
+    ACCOUNT.with(|a| {
+        let mut account = a.borrow_mut();
+        let Some(account) = account.as_mut() else {
+            return Ok(());
+        };
+        let bytes = bytes as u64;
+        if account.limit > 0 && account.memory.current().saturating_add(bytes) > account.limit {
+            return Err(DriftError::OutOfMemory(format!(
+                "statement needs more memory than max_session_memory = {} allows; \
+                 narrow the query, add a LIMIT or raise max_session_memory",
+                crate::settings::Value::Memory(account.limit)
+            )));
+        }
+        account.memory.add(bytes);
+        account.reserved += bytes;
+        Ok(())
+    })
+}
+
+/// Reserve the estimated size of `rows`
+pub(crate) fn reserve_rows(rows: &[Value]) -> Result<()> {
+    reserve(rows.iter().map(crate::spill::estimated_size).sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservations_last_until_the_statement_ends() {
+        let memory = Arc::new(SessionMemory::default());
+        {
+            let _statement = enter(&memory, 1000);
+            reserve(600).unwrap();
+            {
+                // A nested statement counts against the same session
+                let _nested = enter(&Arc::new(SessionMemory::default()), 0);
+                reserve(300).unwrap();
+            }
+            assert_eq!(memory.current(), 900);
+            let err = reserve(200).unwrap_err();
+            assert!(err.to_string().contains("max_session_memory = 1000B"));
+            assert_eq!(memory.current(), 900);
+        }
+        assert_eq!(memory.current(), 0);
+        assert_eq!(memory.peak(), 900);
+
+        // Nothing is accounted outside a statement
+        reserve(usize::MAX).unwrap();
+        assert_eq!(memory.current(), 0);
+    }
+}
@@ -0,0 +1,611 @@
+//! Runtime parameters
+//!
+//! `SHOW name`, `SHOW ALL`, `SET name {TO | =} value` and `RESET name`
+//! read and change the parameters in [`PARAMETERS`]. Every value is
+//! checked against its parameter's [`Kind`] before it is stored and is
+//! shown in canonical form: after `SET work_mem = 65536` (bare numbers are
+//! kilobytes, as in PostgreSQL) `SHOW work_mem` says `64MB`.
+//!
+//! A session sees the first of:
+//!
+//! 1. its own `SET`, kept in its
+//!    [`SessionContext`](crate::sql_bridge::SessionContext) until `RESET`
+//!    or the session ends;
+//! 2. `ALTER ROLE name SET name = value`, for sessions of that user or
+//!    of a user with that role, persisted to `role_settings.json`;
+//! 3. `SET GLOBAL name = value`, kept by the engine for every session
+//!    until it closes;
+//! 4. the parameter's default.
+//!
+//! `SET GLOBAL`, `RESET GLOBAL` and `ALTER ROLE ... SET` need a superuser
+//! whenever the session has a user at all. Parameters marked [`Context::Internal`] describe the
+//! server and can't be set.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use parking_lot::RwLock;
+
+use crate::errors::{DriftError, Result};
+
+/// What values a parameter takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Bool,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    /// An amount of memory; bare numbers are kilobytes
+    Memory,
+    /// An amount of memory as for `Memory`, or 0 for no limit
+    MemoryLimit,
+    /// A length of time; bare numbers are milliseconds
+    Duration,
+    /// One of `values`, or an alias for one of them
+    Enum {
+        values: &'static [&'static str],
+        aliases: &'static [(&'static str, &'static str)],
+    },
+    Text,
+    /// Comma-separated names
+    List,
+}
+
+/// Where a parameter can be set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Context {
+    /// Describes the server; can't be set
+    Internal,
+    /// Per session, or for every session with `SET GLOBAL`
+    User,
+}
+
+/// A runtime parameter
+#[derive(Debug)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub context: Context,
+    /// The value before anything sets it, as `SET` would accept it
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+/// Every parameter, in the order `SHOW ALL` lists them
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter {
+        name: "application_name",
+        kind: Kind::Text,
+        context: Context::User,
+        default: "",
+        description: "Name of the client application, for logs and monitoring",
+    },
+    Parameter {
+        name: "client_encoding",
+        kind: Kind::Enum {
+            values: &["UTF8"],
+            aliases: &[("utf8", "UTF8"), ("utf-8", "UTF8"), ("unicode", "UTF8")],
+        },
+        context: Context::User,
+        default: "UTF8",
+        description: "Character set of text sent to and from the client",
+    },
+    Parameter {
+        name: "DateStyle",
+        kind: Kind::Text,
+        context: Context::User,
+        default: "ISO, MDY",
+        description: "Display format for dates and times",
+    },
+    Parameter {
+        name: "default_transaction_isolation",
+        kind: Kind::Enum {
+            values: &[
+                "read uncommitted",
+                "read committed",
+                "repeatable read",
+                "serializable",
+            ],
+            aliases: &[],
+        },
+        context: Context::User,
+        default: "read committed",
+        description: "Isolation level of each new transaction",
+    },
+    Parameter {
+        name: "default_transaction_read_only",
+        kind: Kind::Bool,
+        context: Context::User,
+        default: "off",
+        description: "Whether each new transaction is read-only",
+    },
+    Parameter {
+        name: "extra_float_digits",
+        kind: Kind::Integer { min: -15, max: 3 },
+        context: Context::User,
+        default: "1",
+        description: "Extra digits shown for floating-point values",
+    },
+    Parameter {
+        name: "identifier_case",
+        kind: Kind::Enum {
+            values: &["lower", "upper", "preserve"],
+            aliases: &[],
+        },
+        context: Context::User,
+        default: "lower",
+        description: "How unquoted identifiers are folded before names are looked up",
+    },
+    Parameter {
+        name: "integer_datetimes",
+        kind: Kind::Bool,
+        context: Context::Internal,
+        default: "on",
+        description: "Whether timestamps are stored as integers",
+    },
+    Parameter {
+        name: "IntervalStyle",
+        kind: Kind::Enum {
+            values: &["postgres"],
+            aliases: &[],
+        },
+        context: Context::User,
+        default: "postgres",
+        description: "Display format for intervals",
+    },
+    Parameter {
+        name: "max_identifier_length",
+        kind: Kind::Integer { min: 63, max: 63 },
+        context: Context::Internal,
+        default: "63",
+        description: "Longest identifier, in bytes",
+    },
+    Parameter {
+        name: "max_result_bytes",
+        kind: Kind::MemoryLimit,
+        context: Context::User,
+        default: "0",
+        description: "Largest result a query may return, by estimated size; 0 for no limit",
+    },
+    Parameter {
+        name: "max_result_rows",
+        kind: Kind::Integer {
+            min: 0,
+            max: i64::MAX,
+        },
+        context: Context::User,
+        default: "0",
+        description: "Most rows a query may return; 0 for no limit",
+    },
+    Parameter {
+        name: "max_session_memory",
+        kind: Kind::MemoryLimit,
+        context: Context::User,
+        default: "0",
+        description: "Memory a session's sorts, hash tables and results may hold at once; 0 for no limit",
+    },
+    Parameter {
+        name: "read_your_writes",
+        kind: Kind::Bool,
+        context: Context::User,
+        default: "off",
+        description: "Whether reads wait until this node has applied the session's own writes",
+    },
+    Parameter {
+        name: "read_your_writes_timeout",
+        kind: Kind::Duration,
+        context: Context::User,
+        default: "5s",
+        description: "Longest a read waits for the session's writes to be applied",
+    },
+    Parameter {
+        name: "search_path",
+        kind: Kind::List,
+        context: Context::User,
+        default: "public",
+        description: "Schemas unqualified table names are looked up in",
+    },
+    Parameter {
+        name: "server_encoding",
+        kind: Kind::Text,
+        context: Context::Internal,
+        default: "UTF8",
+        description: "Character set text is stored in",
+    },
+    Parameter {
+        name: "server_version",
+        kind: Kind::Text,
+        context: Context::Internal,
+        default: concat!("14.0 (DriftDB ", env!("CARGO_PKG_VERSION"), ")"),
+        description: "PostgreSQL version clients should assume, with the DriftDB release",
+    },
+    Parameter {
+        name: "standard_conforming_strings",
+        kind: Kind::Bool,
+        context: Context::Internal,
+        default: "on",
+        description: "Whether backslashes in string literals are taken literally",
+    },
+    Parameter {
+        name: "statement_timeout",
+        kind: Kind::Duration,
+        context: Context::User,
+        default: "0",
+        description: "Longest a query may run before it is canceled; 0 for no limit",
+    },
+    Parameter {
+        name: "synchronous_commit",
+        kind: Kind::Enum {
+            values: &["full", "async", "fsync_off"],
+            aliases: &[
+                ("on", "full"),
+                ("true", "full"),
+                ("local", "full"),
+                ("remote_write", "full"),
+                ("remote_apply", "full"),
+                ("off", "async"),
+                ("false", "async"),
+            ],
+        },
+        context: Context::User,
+        default: "full",
+        description: "When writes are fsynced",
+    },
+    Parameter {
+        name: "TimeZone",
+        kind: Kind::Text,
+        context: Context::User,
+        default: "UTC",
+        description: "Time zone for displaying timestamps",
+    },
+    Parameter {
+        name: "transaction_error_behavior",
+        kind: Kind::Enum {
+            values: &["abort", "continue"],
+            aliases: &[],
+        },
+        context: Context::User,
+        default: "abort",
+        description: "Whether an error inside a transaction aborts it or rolls back only the failing statement",
+    },
+    Parameter {
+        name: "work_mem",
+        kind: Kind::Memory,
+        context: Context::User,
+        default: "4MB",
+        description: "Memory a sort or hash join may use before spilling to disk",
+    },
+];
+
+/// The parameter called `name`, ignoring case
+pub fn lookup(name: &str) -> Result<&'static Parameter> {
+    let name = name.trim().trim_matches('"');
+    PARAMETERS
+        .iter()
+        .find(|parameter| parameter.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            DriftError::InvalidQuery(format!("unrecognized configuration parameter \"{}\"", name))
+        })
+}
+
+impl Parameter {
+    /// Check `raw` against the parameter's type
+    pub fn parse(&self, raw: &str) -> Result<Value> {
+        let raw = raw.trim();
+        let unquoted = raw
+            .strip_prefix('\'')
+            .and_then(|r| r.strip_suffix('\''))
+            .unwrap_or(raw);
+        let invalid = || {
+            DriftError::InvalidQuery(format!(
+                "invalid value for parameter \"{}\": \"{}\"",
+                self.name, unquoted
+            ))
+        };
+
+        match self.kind {
+            Kind::Bool => match unquoted.to_lowercase().as_str() {
+                "on" | "true" | "yes" | "1" => Ok(Value::Bool(true)),
+                "off" | "false" | "no" | "0" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            Kind::Integer { min, max } => match unquoted.parse::<i64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(Value::Integer(n)),
+                Ok(n) => Err(DriftError::InvalidQuery(format!(
+                    "{} is outside the valid range for parameter \"{}\" ({} .. {})",
+                    n, self.name, min, max
+                ))),
+                Err(_) => Err(invalid()),
+            },
+            Kind::MemoryLimit if unquoted == "0" => Ok(Value::Memory(0)),
+            Kind::Memory | Kind::MemoryLimit => {
+                let (n, unit) = split_unit(unquoted).ok_or_else(invalid)?;
+                let scale = match unit {
+                    "B" => 1,
+                    "" | "kB" => 1 << 10,
+                    "MB" => 1 << 20,
+                    "GB" => 1 << 30,
+                    "TB" => 1 << 40,
+                    _ => return Err(invalid()),
+                };
+                match n.checked_mul(scale) {
+                    Some(bytes) if bytes > 0 => Ok(Value::Memory(bytes)),
+                    _ => Err(invalid()),
+                }
+            }
+            Kind::Duration => {
+                let (n, unit) = split_unit(unquoted).ok_or_else(invalid)?;
+                let scale = match unit {
+                    "" | "ms" => 1,
+                    "s" => 1000,
+                    "min" => 60 * 1000,
+                    "h" => 60 * 60 * 1000,
+                    "d" => 24 * 60 * 60 * 1000,
+                    _ => return Err(invalid()),
+                };
+                n.checked_mul(scale)
+                    .map(Value::Duration)
+                    .ok_or_else(invalid)
+            }
+            Kind::Enum { values, aliases } => {
+                let lower = unquoted.to_lowercase();
+                values
+                    .iter()
+                    .find(|value| value.eq_ignore_ascii_case(&lower))
+                    .or_else(|| {
+                        aliases
+                            .iter()
+                            .find(|(alias, _)| *alias == lower)
+                            .map(|(_, value)| value)
+                    })
+                    .map(|value| Value::Text(value.to_string()))
+                    .ok_or_else(|| {
+                        DriftError::InvalidQuery(format!(
+                            "invalid value for parameter \"{}\": \"{}\" (expected {})",
+                            self.name,
+                            unquoted,
+                            values.join(", ")
+                        ))
+                    })
+            }
+            Kind::Text => Ok(Value::Text(unquoted.to_string())),
+            Kind::List => crate::search_path::parse_search_path(raw).map(Value::List),
+        }
+    }
+
+    /// The value before anything sets it
+    pub fn default_value(&self) -> Value {
+        self.parse(self.default)
+            .expect("parameter defaults are valid values")
+    }
+}
+
+/// `64MB` → `(64, "MB")`; units are case-sensitive, as in PostgreSQL
+fn split_unit(value: &str) -> Option<(u64, &str)> {
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let n = value[..digits].parse().ok()?;
+    Some((n, value[digits..].trim()))
+}
+
+/// A parameter's value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Integer(i64),
+    /// Bytes
+    Memory(u64),
+    /// Milliseconds
+    Duration(u64),
+    Text(String),
+    List(Vec<String>),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => f.write_str(if *b { "on" } else { "off" }),
+            Value::Integer(n) => write!(f, "{}", n),
+            Value::Memory(0) => f.write_str("0"),
+            Value::Memory(bytes) => write_scaled(
+                f,
+                *bytes,
+                &[
+                    (1 << 40, "TB"),
+                    (1 << 30, "GB"),
+                    (1 << 20, "MB"),
+                    (1 << 10, "kB"),
+                    (1, "B"),
+                ],
+            ),
+            Value::Duration(0) => f.write_str("0"),
+            Value::Duration(ms) => write_scaled(
+                f,
+                *ms,
+                &[
+                    (24 * 60 * 60 * 1000, "d"),
+                    (60 * 60 * 1000, "h"),
+                    (60 * 1000, "min"),
+                    (1000, "s"),
+                    (1, "ms"),
+                ],
+            ),
+            Value::Text(text) => f.write_str(text),
+            Value::List(items) => f.write_str(&items.join(", ")),
+        }
+    }
+}
+
+/// `n` in the largest of `units` that divides it exactly
+fn write_scaled(f: &mut fmt::Formatter<'_>, n: u64, units: &[(u64, &str)]) -> fmt::Result {
+    let (scale, unit) = units
+        .iter()
+        .find(|(scale, _)| n.is_multiple_of(*scale))
+        .unwrap_or(&units[units.len() - 1]);
+    write!(f, "{}{}", n / scale, unit)
+}
+
+/// Values set with `SET GLOBAL`, shared by every session of an engine,
+/// and with `ALTER ROLE ... SET` for the sessions of a role
+#[derive(Debug, Default)]
+pub struct GlobalSettings {
+    values: RwLock<HashMap<&'static str, Value>>,
+    roles: RwLock<BTreeMap<String, BTreeMap<&'static str, Value>>>,
+}
+
+impl GlobalSettings {
+    pub fn get(&self, parameter: &Parameter) -> Option<Value> {
+        self.values.read().get(parameter.name).cloned()
+    }
+
+    /// Set `parameter` for every session, or go back to its default
+    pub fn set(&self, parameter: &'static Parameter, value: Option<Value>) {
+        let mut values = self.values.write();
+        match value {
+            Some(value) => values.insert(parameter.name, value),
+            None => values.remove(parameter.name),
+        };
+    }
+
+    /// The value `role` has for `parameter`, if it was given one
+    pub fn get_for_role(&self, role: &str, parameter: &Parameter) -> Option<Value> {
+        self.roles.read().get(role)?.get(parameter.name).cloned()
+    }
+
+    /// The value for a session whose user and roles are `roles`, most
+    /// specific first: that of the first role given one, else the global
+    /// value
+    pub fn get_for_roles(&self, roles: &[String], parameter: &Parameter) -> Option<Value> {
+        roles
+            .iter()
+            .find_map(|role| self.get_for_role(role, parameter))
+            .or_else(|| self.get(parameter))
+    }
+
+    /// Set `parameter` for `role`'s sessions, or go back to the global
+    /// value
+    pub fn set_for_role(&self, role: &str, parameter: &'static Parameter, value: Option<Value>) {
+        let mut roles = self.roles.write();
+        match value {
+            Some(value) => {
+                roles
+                    .entry(role.to_string())
+                    .or_default()
+                    .insert(parameter.name, value);
+            }
+            None => {
+                if let Some(values) = roles.get_mut(role) {
+                    values.remove(parameter.name);
+                    if values.is_empty() {
+                        roles.remove(role);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Forget every value set for `role`
+    pub fn reset_role(&self, role: &str) {
+        self.roles.write().remove(role);
+    }
+
+    /// Every role's values, as `SET` would accept them
+    pub fn role_values(&self) -> BTreeMap<String, BTreeMap<String, String>> {
+        self.roles
+            .read()
+            .iter()
+            .map(|(role, values)| {
+                let values = values
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+                (role.clone(), values)
+            })
+            .collect()
+    }
+}
+
+thread_local! {
+    /// The session's own values, mirrored from `SessionContext.settings`
+    /// by sql_bridge
+    static SESSION: RefCell<HashMap<String, Value>> = RefCell::new(HashMap::new());
+}
+
+/// The session's value for `parameter`, if it set one
+pub(crate) fn session_value(parameter: &Parameter) -> Option<Value> {
+    SESSION.with(|s| s.borrow().get(parameter.name).cloned())
+}
+
+/// Set `parameter` for the session, or go back to the engine's value
+pub(crate) fn set_session_value(parameter: &Parameter, value: Option<Value>) {
+    SESSION.with(|s| {
+        let mut values = s.borrow_mut();
+        match value {
+            Some(value) => values.insert(parameter.name.to_string(), value),
+            None => values.remove(parameter.name),
+        };
+    });
+}
+
+/// Replace the session's values, returning the previous ones
+pub(crate) fn replace_session_values(values: HashMap<String, Value>) -> HashMap<String, Value> {
+    SESSION.with(|s| s.replace(values))
+}
+
+/// The session's `work_mem` in bytes, if it set one
+pub(crate) fn session_work_mem() -> Option<usize> {
+    match lookup("work_mem").ok().and_then(session_value) {
+        Some(Value::Memory(bytes)) => Some(bytes as usize),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_are_checked_and_shown_canonically() {
+        let work_mem = lookup("WORK_MEM").unwrap();
+        assert_eq!(work_mem.parse("65536").unwrap().to_string(), "64MB");
+        assert_eq!(work_mem.parse("'1536kB'").unwrap().to_string(), "1536kB");
+        assert_eq!(
+            work_mem.default_value(),
+            Value::Memory(crate::spill::DEFAULT_WORK_MEM as u64)
+        );
+        assert!(work_mem.parse("64mb").is_err());
+        assert!(work_mem.parse("0").is_err());
+
+        let timeout = lookup("statement_timeout").unwrap();
+        assert_eq!(timeout.parse("90s").unwrap().to_string(), "90s");
+        assert_eq!(timeout.parse("120000").unwrap().to_string(), "2min");
+        assert_eq!(timeout.default_value().to_string(), "0");
+        assert!(timeout.parse("-1").is_err());
+
+        let max_bytes = lookup("max_result_bytes").unwrap();
+        assert_eq!(max_bytes.default_value(), Value::Memory(0));
+        assert_eq!(max_bytes.default_value().to_string(), "0");
+        assert_eq!(max_bytes.parse("1GB").unwrap().to_string(), "1GB");
+        assert!(lookup("max_result_rows").unwrap().parse("-1").is_err());
+
+        let sync = lookup("synchronous_commit").unwrap();
+        assert_eq!(sync.parse("ON").unwrap(), Value::Text("full".to_string()));
+        assert!(sync.parse("maybe").is_err());
+
+        let isolation = lookup("default_transaction_isolation").unwrap();
+        assert_eq!(
+            isolation.parse("'REPEATABLE READ'").unwrap().to_string(),
+            "repeatable read"
+        );
+        assert!(isolation.parse("snapshot").is_err());
+
+        assert!(lookup("extra_float_digits").unwrap().parse("4").is_err());
+        assert!(lookup("no_such_parameter").is_err());
+        for parameter in PARAMETERS {
+            parameter.default_value();
+        }
+    }
+}
@@ -0,0 +1,507 @@
+//! Spilling sorts and hash joins to disk
+//!
+//! Sorts and hash joins whose input outgrows `work_mem` work through
+//! temporary files instead of holding everything in memory:
+//! - Sorts write sorted runs of at most `work_mem` and merge them back
+//! - Hash joins partition both inputs on the join key and join one pair
+//!   of partitions at a time
+//!
+//! Each operation writes into its own directory under the spill directory,
+//! which is removed when the operation finishes, fails or unwinds. Files
+//! left behind by a crash are removed the next time the database opens.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::errors::{DriftError, Result};
+
+/// Memory a single sort or hash join may use before spilling, in bytes
+pub const DEFAULT_WORK_MEM: usize = 4 * 1024 * 1024;
+
+/// Most runs merged at once; more runs are first merged into longer ones,
+/// which bounds the number of open files
+const MERGE_FAN_IN: usize = 64;
+
+/// Most partitions a hash join is split into. A join key so common that
+/// its partition alone outgrows `work_mem` is still joined in memory.
+const MAX_PARTITIONS: usize = 256;
+
+/// Prefix of every per-operation directory, so startup cleanup only
+/// removes what spilling created
+const WORKSPACE_PREFIX: &str = "spill-";
+
+/// Numbers workspaces uniquely within the process, even across engines
+/// sharing a spill directory
+static NEXT_WORKSPACE: AtomicU64 = AtomicU64::new(0);
+
+/// Spill counters since the engine opened
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpillStats {
+    pub sorts_spilled: u64,
+    pub joins_spilled: u64,
+    pub files_written: u64,
+    pub bytes_written: u64,
+}
+
+/// `work_mem` and the spill directory, shared by every query on an engine
+pub struct SpillManager {
+    work_mem: AtomicUsize,
+    temp_dir: RwLock<PathBuf>,
+    sorts_spilled: AtomicU64,
+    joins_spilled: AtomicU64,
+    files_written: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl SpillManager {
+    pub fn new(temp_dir: PathBuf) -> Self {
+        Self {
+            work_mem: AtomicUsize::new(DEFAULT_WORK_MEM),
+            temp_dir: RwLock::new(temp_dir),
+            sorts_spilled: AtomicU64::new(0),
+            joins_spilled: AtomicU64::new(0),
+            files_written: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+        }
+    }
+
+    /// Memory a single sort or hash join may use, in bytes: the
+    /// session's `work_mem` if it set one, else the engine's
+    pub fn work_mem(&self) -> usize {
+        crate::settings::session_work_mem()
+            .unwrap_or_else(|| self.work_mem.load(AtomicOrdering::Relaxed))
+    }
+
+    pub fn set_work_mem(&self, bytes: usize) {
+        self.work_mem.store(bytes.max(1), AtomicOrdering::Relaxed);
+    }
+
+    /// Directory temporary files are written under
+    pub fn temp_dir(&self) -> PathBuf {
+        self.temp_dir.read().clone()
+    }
+
+    /// Spill under `dir` from now on. Operations already spilling finish
+    /// in the old directory.
+    pub fn set_temp_dir(&self, dir: impl Into<PathBuf>) {
+        *self.temp_dir.write() = dir.into();
+    }
+
+    pub fn stats(&self) -> SpillStats {
+        SpillStats {
+            sorts_spilled: self.sorts_spilled.load(AtomicOrdering::Relaxed),
+            joins_spilled: self.joins_spilled.load(AtomicOrdering::Relaxed),
+            files_written: self.files_written.load(AtomicOrdering::Relaxed),
+            bytes_written: self.bytes_written.load(AtomicOrdering::Relaxed),
+        }
+    }
+
+    /// Remove spill directories left behind by a process that stopped
+    /// mid-operation. Returns how many were removed.
+    pub fn remove_stale(&self) -> Result<usize> {
+        let dir = self.temp_dir();
+        if !dir.exists() {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with(WORKSPACE_PREFIX)
+            {
+                fs::remove_dir_all(entry.path())?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Whether `rows` take more than `work_mem`
+    pub fn exceeds_work_mem(&self, rows: &[Value]) -> bool {
+        let work_mem = self.work_mem();
+        let mut total = 0;
+        rows.iter().any(|row| {
+            total += estimated_size(row);
+            total > work_mem
+        })
+    }
+
+    /// Sort `rows` by `compare`, spilling sorted runs to disk when they
+    /// don't fit in `work_mem`. The sort is stable either way. What the
+    /// sort holds in memory counts against the session's
+    /// `max_session_memory`.
+    pub fn sort<F>(&self, mut rows: Vec<Value>, compare: F) -> Result<Vec<Value>>
+    where
+        F: Fn(&Value, &Value) -> Ordering,
+    {
+        if !self.exceeds_work_mem(&rows) {
+            crate::session_memory::reserve_rows(&rows)?;
+            rows.sort_by(&compare);
+            return Ok(rows);
+        }
+
+        let total = rows.len();
+        let work_mem = self.work_mem();
+        crate::session_memory::reserve(work_mem)?;
+        let mut workspace = self.workspace()?;
+        let mut runs = Vec::new();
+        let mut run = Vec::new();
+        let mut run_size = 0;
+        for row in rows {
+            run_size += estimated_size(&row);
+            run.push(row);
+            if run_size >= work_mem {
+                run.sort_by(&compare);
+                runs.push(workspace.write(run.drain(..))?);
+                run_size = 0;
+            }
+        }
+        if !run.is_empty() {
+            run.sort_by(&compare);
+            runs.push(workspace.write(run.drain(..))?);
+        }
+        debug!("Sorting {} rows through {} spilled runs", total, runs.len());
+
+        while runs.len() > MERGE_FAN_IN {
+            let mut merged = Vec::with_capacity(runs.len().div_ceil(MERGE_FAN_IN));
+            for group in runs.chunks(MERGE_FAN_IN) {
+                let rows = merge(workspace.open_all(group)?, &compare);
+                merged.push(workspace.write_results(rows)?);
+            }
+            for run in &runs {
+                fs::remove_file(run)?;
+            }
+            runs = merged;
+        }
+
+        let mut sorted = Vec::with_capacity(total);
+        for row in merge(workspace.open_all(&runs)?, &compare) {
+            sorted.push(row?);
+        }
+        self.sorts_spilled.fetch_add(1, AtomicOrdering::Relaxed);
+        self.record(&workspace);
+        Ok(sorted)
+    }
+
+    /// Number of partitions a hash join should split into so each build
+    /// partition fits in `work_mem`
+    pub fn partitions_for(&self, build_rows: &[Value]) -> usize {
+        let size: usize = build_rows.iter().map(estimated_size).sum();
+        size.div_ceil(self.work_mem()).clamp(2, MAX_PARTITIONS)
+    }
+
+    /// A fresh directory for one operation's files
+    pub fn workspace(&self) -> Result<SpillWorkspace> {
+        let dir = self.temp_dir();
+        fs::create_dir_all(&dir)?;
+        let id = NEXT_WORKSPACE.fetch_add(1, AtomicOrdering::Relaxed);
+        let path = dir.join(format!("{}{}-{}", WORKSPACE_PREFIX, std::process::id(), id));
+        fs::create_dir(&path)?;
+        Ok(SpillWorkspace {
+            path,
+            files: 0,
+            bytes: 0,
+        })
+    }
+
+    /// Count a hash join that went through `workspace`
+    pub fn record_join(&self, workspace: &SpillWorkspace) {
+        self.joins_spilled.fetch_add(1, AtomicOrdering::Relaxed);
+        self.record(workspace);
+    }
+
+    fn record(&self, workspace: &SpillWorkspace) {
+        self.files_written
+            .fetch_add(workspace.files, AtomicOrdering::Relaxed);
+        self.bytes_written
+            .fetch_add(workspace.bytes, AtomicOrdering::Relaxed);
+    }
+}
+
+/// The temporary files of one sort or join, deleted on drop
+pub struct SpillWorkspace {
+    path: PathBuf,
+    files: u64,
+    bytes: u64,
+}
+
+impl SpillWorkspace {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write `rows` to a new file, one JSON document per line
+    pub fn write(&mut self, rows: impl IntoIterator<Item = Value>) -> Result<PathBuf> {
+        self.write_results(rows.into_iter().map(Ok))
+    }
+
+    fn write_results(&mut self, rows: impl IntoIterator<Item = Result<Value>>) -> Result<PathBuf> {
+        let path = self.path.join(format!("{:08}.json", self.files));
+        let mut writer = SpillWriter::create(&path)?;
+        for row in rows {
+            writer.push(&row?)?;
+        }
+        self.bytes += writer.finish()?;
+        self.files += 1;
+        Ok(path)
+    }
+
+    /// Split `rows` into `partitions` files by the hash of `key`. Rows
+    /// without a key go to the first partition.
+    pub fn partition<'a, K>(
+        &mut self,
+        rows: impl IntoIterator<Item = &'a Value>,
+        partitions: usize,
+        key: K,
+    ) -> Result<Vec<PathBuf>>
+    where
+        K: Fn(&Value) -> Option<String>,
+    {
+        let paths: Vec<PathBuf> = (0..partitions)
+            .map(|i| self.path.join(format!("{:08}.json", self.files + i as u64)))
+            .collect();
+        let mut writers = paths
+            .iter()
+            .map(|path| SpillWriter::create(path))
+            .collect::<Result<Vec<_>>>()?;
+        for row in rows {
+            let partition = key(row).map_or(0, |key| {
+                let mut hasher = DefaultHasher::new();
+                key.hash(&mut hasher);
+                (hasher.finish() % partitions as u64) as usize
+            });
+            writers[partition].push(row)?;
+        }
+        for writer in writers {
+            self.bytes += writer.finish()?;
+        }
+        self.files += partitions as u64;
+        Ok(paths)
+    }
+
+    /// Read back every row of a file written by this workspace
+    pub fn read(&self, path: &Path) -> Result<Vec<Value>> {
+        SpillReader::open(path)?.collect()
+    }
+
+    fn open_all(&self, paths: &[PathBuf]) -> Result<Vec<SpillReader>> {
+        paths.iter().map(|path| SpillReader::open(path)).collect()
+    }
+}
+
+impl Drop for SpillWorkspace {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            debug!("Failed to remove spill directory {:?}: {}", self.path, e);
+        }
+    }
+}
+
+struct SpillWriter {
+    writer: BufWriter<File>,
+    bytes: u64,
+}
+
+impl SpillWriter {
+    fn create(path: &Path) -> Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            bytes: 0,
+        })
+    }
+
+    fn push(&mut self, row: &Value) -> Result<()> {
+        let line = serde_json::to_vec(row)?;
+        self.writer.write_all(&line)?;
+        self.writer.write_all(b"\n")?;
+        self.bytes += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// Flush the file and return how many bytes went into it
+    fn finish(mut self) -> Result<u64> {
+        self.writer.flush()?;
+        Ok(self.bytes)
+    }
+}
+
+struct SpillReader {
+    lines: Lines<BufReader<File>>,
+}
+
+impl SpillReader {
+    fn open(path: &Path) -> Result<Self> {
+        Ok(Self {
+            lines: BufReader::new(File::open(path)?).lines(),
+        })
+    }
+}
+
+impl Iterator for SpillReader {
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.lines.next()?;
+        Some(
+            line.map_err(DriftError::from)
+                .and_then(|line| serde_json::from_str(&line).map_err(DriftError::from)),
+        )
+    }
+}
+
+/// Merge sorted runs. Among equal rows the earlier run's comes first, so
+/// merging runs of a stable sort in order keeps the sort stable.
+fn merge<'a, F>(
+    mut runs: Vec<SpillReader>,
+    compare: &'a F,
+) -> impl Iterator<Item = Result<Value>> + 'a
+where
+    F: Fn(&Value, &Value) -> Ordering + 'a,
+{
+    let mut heads: Vec<Option<Result<Value>>> = runs.iter_mut().map(Iterator::next).collect();
+    std::iter::from_fn(move || {
+        // An unreadable run ends the merge with its error
+        if let Some(i) = heads.iter().position(|head| matches!(head, Some(Err(_)))) {
+            let error = heads[i].take();
+            heads.clear();
+            return error;
+        }
+        let mut smallest: Option<usize> = None;
+        for (i, head) in heads.iter().enumerate() {
+            let Some(Ok(row)) = head else {
+                continue;
+            };
+            let better = match smallest {
+                Some(j) => match &heads[j] {
+                    Some(Ok(best)) => compare(row, best) == Ordering::Less,
+                    _ => true,
+                },
+                None => true,
+            };
+            if better {
+                smallest = Some(i);
+            }
+        }
+        let i = smallest?;
+        let next = runs[i].next();
+        std::mem::replace(&mut heads[i], next)
+    })
+}
+
+/// Rough in-memory size of a row, in bytes
+pub fn estimated_size(value: &Value) -> usize {
+    const SLOT: usize = std::mem::size_of::<Value>();
+    match value {
+        Value::String(s) => SLOT + s.len(),
+        Value::Array(items) => SLOT + items.iter().map(estimated_size).sum::<usize>(),
+        Value::Object(map) => {
+            SLOT + map
+                .iter()
+                .map(|(key, value)| SLOT + key.len() + estimated_size(value))
+                .sum::<usize>()
+        }
+        _ => SLOT,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn rows(n: usize) -> Vec<Value> {
+        (0..n)
+            .map(|i| json!({"id": i, "key": (i * 7919) % 101, "pad": "x".repeat(20)}))
+            .collect()
+    }
+
+    fn by_key(a: &Value, b: &Value) -> Ordering {
+        a["key"].as_u64().cmp(&b["key"].as_u64())
+    }
+
+    #[test]
+    fn test_sort_within_work_mem_stays_in_memory() {
+        let temp = TempDir::new().unwrap();
+        let spill = SpillManager::new(temp.path().join("tmp"));
+        let mut expected = rows(100);
+        expected.sort_by(by_key);
+
+        assert_eq!(spill.sort(rows(100), by_key).unwrap(), expected);
+        assert_eq!(spill.stats(), SpillStats::default());
+        assert!(!temp.path().join("tmp").exists());
+    }
+
+    #[test]
+    fn test_spilled_sort_is_stable_and_cleans_up() {
+        let temp = TempDir::new().unwrap();
+        let spill = SpillManager::new(temp.path().join("tmp"));
+        // Small enough for a few rows per run and a multi-pass merge
+        spill.set_work_mem(512);
+        let mut expected = rows(2000);
+        expected.sort_by(by_key);
+
+        assert_eq!(spill.sort(rows(2000), by_key).unwrap(), expected);
+        let stats = spill.stats();
+        assert_eq!(stats.sorts_spilled, 1);
+        assert!(stats.files_written as usize > MERGE_FAN_IN);
+        assert_eq!(fs::read_dir(temp.path().join("tmp")).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_partitions_keep_equal_keys_together() {
+        let temp = TempDir::new().unwrap();
+        let spill = SpillManager::new(temp.path().join("tmp"));
+        spill.set_work_mem(1024);
+        let input = rows(500);
+        let partitions = spill.partitions_for(&input);
+        assert!(partitions > 2);
+
+        let mut workspace = spill.workspace().unwrap();
+        let key = |row: &Value| (row["key"] != 0).then(|| row["key"].to_string());
+        let paths = workspace.partition(&input, partitions, key).unwrap();
+        let mut seen = Vec::new();
+        for (i, path) in paths.iter().enumerate() {
+            for row in workspace.read(path).unwrap() {
+                if row["key"] == 0 {
+                    assert_eq!(i, 0, "keyless rows go to the first partition");
+                }
+                seen.push((row["key"].as_u64().unwrap(), i));
+            }
+        }
+        assert_eq!(seen.len(), input.len());
+        seen.sort();
+        seen.dedup();
+        let keys: std::collections::HashSet<_> = seen.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys.len(), seen.len(), "a key landed in two partitions");
+
+        let dir = workspace.path().to_path_buf();
+        drop(workspace);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_remove_stale_only_touches_spill_directories() {
+        let temp = TempDir::new().unwrap();
+        let spill = SpillManager::new(temp.path().to_path_buf());
+        fs::create_dir(temp.path().join("spill-1-0")).unwrap();
+        fs::write(temp.path().join("spill-1-0/00000000.json"), "{}\n").unwrap();
+        fs::create_dir(temp.path().join("tables")).unwrap();
+
+        assert_eq!(spill.remove_stale().unwrap(), 1);
+        assert!(!temp.path().join("spill-1-0").exists());
+        assert!(temp.path().join("tables").exists());
+    }
+}
@@ -300,18 +300,7 @@ impl JoinExecutor {
             .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
 
         // Determine sequence for temporal query
-        let sequence = match as_of {
-            Some(AsOf::Sequence(seq)) => Some(seq),
-            Some(AsOf::Timestamp(ts)) => {
-                let events = storage.read_all_events()?;
-                events
-                    .iter()
-                    .filter(|e| e.timestamp <= ts)
-                    .map(|e| e.sequence)
-                    .max()
-            }
-            Some(AsOf::Now) | None => None,
-        };
+        let sequence = storage.as_of_sequence(as_of.as_ref())?;
 
         // Reconstruct state at sequence
         let state = storage.reconstruct_state_at(sequence)?;
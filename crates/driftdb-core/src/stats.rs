@@ -19,7 +19,7 @@ use tracing::{debug, info, trace};
 
 use crate::errors::Result;
 use crate::optimizer::{
-    ColumnStatistics, Histogram, HistogramBucket, IndexStatistics, TableStatistics,
+    ColumnStatistics, Histogram, HistogramBucket, HistogramKind, IndexStatistics, TableStatistics,
 };
 
 /// Statistics collection configuration
@@ -528,9 +528,15 @@ impl StatisticsManager {
             column_name: column_name.to_string(),
             distinct_values: distinct_count,
             null_count,
+            null_fraction: if data.is_empty() {
+                0.0
+            } else {
+                null_count as f64 / data.len() as f64
+            },
             min_value: min_value.map(|v| serde_json::json!(v)),
             max_value: max_value.map(|v| serde_json::json!(v)),
             histogram,
+            most_common_values: Vec::new(),
         })
     }
 
@@ -540,6 +546,7 @@ impl StatisticsManager {
             return Histogram {
                 buckets: Vec::new(),
                 bucket_count: 0,
+                kind: HistogramKind::EquiWidth,
             };
         }
 
@@ -559,6 +566,7 @@ impl StatisticsManager {
                     distinct_count: 1,
                 }],
                 bucket_count: 1,
+                kind: HistogramKind::EquiWidth,
             };
         }
 
@@ -607,6 +615,7 @@ impl StatisticsManager {
         Histogram {
             buckets,
             bucket_count: self.config.histogram_buckets,
+            kind: HistogramKind::EquiWidth,
         }
     }
 
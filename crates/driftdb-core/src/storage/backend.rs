@@ -0,0 +1,328 @@
+//! Where segment bytes live.
+//!
+//! Segments are opened, appended to, read, listed and deleted through a
+//! [`StorageBackend`], so the event log doesn't have to sit in local files.
+//! [`LocalFileBackend`] is the default and keeps segments as files under the
+//! table directory. [`MemoryBackend`] keeps them in process memory, which
+//! makes engine tests fast and leaves nothing behind; the same instance can
+//! be handed to a reopened engine to simulate a restart.
+//!
+//! [`TieredBackend`](super::TieredBackend) combines two backends, moving
+//! segments that have gone cold from the first to the second.
+//!
+//! Only segments go through the backend. A table's schema, meta, snapshots
+//! and indexes are small and are still written to the table directory.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+
+use crate::errors::Result;
+use crate::storage::tiered::TierStats;
+
+/// Storage for segment files, addressed by path
+pub trait StorageBackend: Send + Sync {
+    /// Create an empty segment, replacing any existing one
+    fn create(&self, path: &Path) -> Result<Box<dyn SegmentSink>>;
+
+    /// Open a segment for appending, creating it if missing
+    fn open_append(&self, path: &Path) -> Result<Box<dyn SegmentSink>>;
+
+    /// Open a segment for reading from the start
+    fn open_read(&self, path: &Path) -> Result<Box<dyn SegmentSource>>;
+
+    /// Size of a segment in bytes
+    fn size(&self, path: &Path) -> Result<u64>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    /// Segments directly inside `dir`, sorted by path
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>>;
+
+    fn delete(&self, path: &Path) -> Result<()>;
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()>;
+
+    /// Cut a segment back to its first `len` bytes
+    fn truncate(&self, path: &Path, len: u64) -> Result<()>;
+
+    /// A whole segment's bytes
+    fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.open_read(path)?.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// When a segment was last written, if the backend keeps track
+    fn modified(&self, _path: &Path) -> Option<SystemTime> {
+        None
+    }
+
+    /// Move the segments in `dir` that have gone cold to a slower tier,
+    /// returning how many moved. Backends without tiers move nothing.
+    fn relocate_cold(&self, _dir: &Path) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Move every segment in `dir` that isn't open for writing to the
+    /// slower tier now, whatever its age, returning how many moved
+    fn archive(&self, _dir: &Path) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Per-tier read and relocation counts, for tiered backends
+    fn tier_stats(&self) -> Option<TierStats> {
+        None
+    }
+}
+
+/// Append side of an open segment
+pub trait SegmentSink: Write + Send + Sync {
+    /// Make everything written so far durable
+    fn sync(&mut self) -> Result<()>;
+}
+
+/// Read side of an open segment
+pub trait SegmentSource: Read + Seek + Send + Sync {}
+
+impl<T: Read + Seek + Send + Sync> SegmentSource for T {}
+
+impl SegmentSink for File {
+    fn sync(&mut self) -> Result<()> {
+        self.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Segments as files on the local filesystem
+#[derive(Debug, Default)]
+pub struct LocalFileBackend;
+
+impl LocalFileBackend {
+    /// The process-wide instance used when no backend is given
+    pub fn shared() -> Arc<dyn StorageBackend> {
+        static SHARED: OnceLock<Arc<LocalFileBackend>> = OnceLock::new();
+        SHARED.get_or_init(|| Arc::new(LocalFileBackend)).clone()
+    }
+}
+
+impl StorageBackend for LocalFileBackend {
+    fn create(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Box::new(file))
+    }
+
+    fn open_append(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Box::new(file))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn SegmentSource>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
+    fn size(&self, path: &Path) -> Result<u64> {
+        Ok(fs::metadata(path)?.len())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        if !dir.exists() {
+            return Ok(paths);
+        }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        fs::rename(from, to)?;
+        Ok(())
+    }
+
+    fn truncate(&self, path: &Path, len: u64) -> Result<()> {
+        OpenOptions::new().write(true).open(path)?.set_len(len)?;
+        Ok(())
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+type SharedBytes = Arc<Mutex<Vec<u8>>>;
+
+/// Segments held in memory. Clones share the same segments.
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    files: Arc<Mutex<BTreeMap<PathBuf, SharedBytes>>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total bytes held across all segments
+    pub fn bytes_used(&self) -> u64 {
+        self.files
+            .lock()
+            .values()
+            .map(|bytes| bytes.lock().len() as u64)
+            .sum()
+    }
+
+    fn file(&self, path: &Path) -> Result<SharedBytes> {
+        self.files
+            .lock()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| not_found(path).into())
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no segment at {}", path.display()),
+    )
+}
+
+/// Appends to a [`MemoryBackend`] segment
+struct MemorySink(SharedBytes);
+
+impl Write for MemorySink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SegmentSink for MemorySink {
+    fn sync(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    fn create(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let bytes = SharedBytes::default();
+        self.files.lock().insert(path.to_path_buf(), bytes.clone());
+        Ok(Box::new(MemorySink(bytes)))
+    }
+
+    fn open_append(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let bytes = self
+            .files
+            .lock()
+            .entry(path.to_path_buf())
+            .or_default()
+            .clone();
+        Ok(Box::new(MemorySink(bytes)))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn SegmentSource>> {
+        // Readers see the segment as it was when opened
+        let bytes = self.file(path)?.lock().clone();
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+
+    fn size(&self, path: &Path) -> Result<u64> {
+        Ok(self.file(path)?.lock().len() as u64)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.lock().contains_key(path)
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .files
+            .lock()
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect())
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        match self.files.lock().remove(path) {
+            Some(_) => Ok(()),
+            None => Err(not_found(path).into()),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let mut files = self.files.lock();
+        let bytes = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), bytes);
+        Ok(())
+    }
+
+    fn truncate(&self, path: &Path, len: u64) -> Result<()> {
+        self.file(path)?.lock().truncate(len as usize);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_backend_round_trips_segments() {
+        let backend = MemoryBackend::new();
+        let dir = Path::new("/db/tables/t/segments");
+        let first = dir.join("00000001.seg");
+
+        let mut sink = backend.create(&first).unwrap();
+        sink.write_all(b"hello").unwrap();
+        sink.sync().unwrap();
+        let mut sink = backend.open_append(&first).unwrap();
+        sink.write_all(b" world").unwrap();
+
+        assert_eq!(backend.read(&first).unwrap(), b"hello world");
+        assert_eq!(backend.size(&first).unwrap(), 11);
+
+        backend.truncate(&first, 5).unwrap();
+        let second = dir.join("00000002.seg");
+        backend.rename(&first, &second).unwrap();
+        assert!(!backend.exists(&first));
+        assert_eq!(backend.list(dir).unwrap(), vec![second.clone()]);
+        assert!(backend.list(Path::new("/db")).unwrap().is_empty());
+
+        backend.delete(&second).unwrap();
+        assert!(backend.open_read(&second).is_err());
+        assert_eq!(backend.bytes_used(), 0);
+    }
+}
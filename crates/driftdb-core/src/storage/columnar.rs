@@ -0,0 +1,408 @@
+//! Per-table storage format
+//!
+//! Every table writes the same event log, one row-shaped event at a time,
+//! so appends, time travel, replication and compaction don't depend on
+//! the format. A table with `storage = columnar` also keeps a projection
+//! of its current rows one column at a time: a scan evaluates each
+//! condition down a single column and builds only the rows that match,
+//! instead of replaying the log into rows and testing each one.
+//!
+//! The projection is kept in memory. It is built from the log the first
+//! time the table is scanned and kept current by every append after
+//! that. Reads as of an earlier point replay the log as for a row table.
+//!
+//! Slots are grouped into chunks of [`CHUNK_ROWS`]. For each column in
+//! `bloom_filter_columns`, every chunk keeps a bloom filter of the values
+//! the column has held in its slots, and a scan with an equality on the
+//! column tests only the chunks whose filter might hold the value. Values
+//! overwritten or deleted stay in the filter, which only costs a test.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::bloom_filter::{BloomConfig, ScalableBloomFilter};
+use crate::decimal::Decimal;
+use crate::errors::{DriftError, Result};
+use crate::events::{Event, EventType};
+use crate::query::predicate::matches_value;
+use crate::query::WhereCondition;
+
+/// How a table's current rows are laid out for scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// Scans replay the event log into rows
+    #[default]
+    Row,
+    /// Scans read a column-oriented projection of the current rows
+    Columnar,
+}
+
+impl StorageFormat {
+    pub fn is_row(&self) -> bool {
+        *self == StorageFormat::Row
+    }
+}
+
+impl fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageFormat::Row => f.write_str("row"),
+            StorageFormat::Columnar => f.write_str("columnar"),
+        }
+    }
+}
+
+impl FromStr for StorageFormat {
+    type Err = DriftError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "row" => Ok(StorageFormat::Row),
+            "columnar" => Ok(StorageFormat::Columnar),
+            other => Err(DriftError::InvalidQuery(format!(
+                "unknown storage format '{}', expected row or columnar",
+                other
+            ))),
+        }
+    }
+}
+
+/// Slots per chunk
+pub const CHUNK_ROWS: usize = 1024;
+
+/// Chunks equality scans on filtered columns have skipped and tested
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkFilterStats {
+    /// Chunks a filter ruled out
+    pub chunks_skipped: u64,
+    /// Chunks tested because their filter might hold the value
+    pub chunks_read: u64,
+}
+
+impl ChunkFilterStats {
+    pub(crate) fn add(&mut self, other: ChunkFilterStats) {
+        self.chunks_skipped += other.chunks_skipped;
+        self.chunks_read += other.chunks_read;
+    }
+}
+
+/// What a filtered column's chunk records about its values
+#[derive(Debug)]
+pub(crate) struct ColumnChunkMetadata {
+    /// Every value the column has held in the chunk, by [`filter_key`]
+    filter: ScalableBloomFilter,
+}
+
+impl ColumnChunkMetadata {
+    fn new(fpp: f64) -> Self {
+        Self {
+            filter: ScalableBloomFilter::new(BloomConfig {
+                expected_elements: CHUNK_ROWS,
+                false_positive_rate: fpp,
+            }),
+        }
+    }
+}
+
+/// A table's current rows, one vector per column. A row is a slot across
+/// the vectors; slots freed by deletes are reused.
+#[derive(Debug, Default)]
+pub(crate) struct ColumnarProjection {
+    /// Slot of each live row, by primary key
+    slots: HashMap<String, usize>,
+    /// Whether each slot holds a live row
+    live: Vec<bool>,
+    free: Vec<usize>,
+    /// Each column's value in every slot, `None` where the row lacks it
+    columns: BTreeMap<String, Vec<Option<Value>>>,
+    /// False-positive rate of the chunk filters
+    filter_fpp: f64,
+    /// Each filtered column's chunks; a chunk past the end has never
+    /// held a value in the column
+    chunks: BTreeMap<String, Vec<ColumnChunkMetadata>>,
+}
+
+impl ColumnarProjection {
+    /// Project `rows`, keyed by primary key as replay keys them, filtering
+    /// the chunks of `filtered` columns at false-positive rate `fpp`
+    pub(crate) fn from_rows(
+        rows: HashMap<String, Value>,
+        fpp: f64,
+        filtered: &BTreeSet<String>,
+    ) -> Self {
+        let mut projection = Self {
+            filter_fpp: fpp,
+            chunks: filtered
+                .iter()
+                .map(|column| (column.clone(), Vec::new()))
+                .collect(),
+            ..Self::default()
+        };
+        for (key, row) in rows {
+            projection.insert(key, row);
+        }
+        projection
+    }
+
+    /// Fold an appended event into the projection
+    pub(crate) fn apply(&mut self, event: &Event) {
+        let key = event.primary_key.to_string();
+        match event.event_type {
+            EventType::Insert => self.insert(key, event.payload.clone()),
+            EventType::Patch => {
+                let (Some(&slot), Value::Object(patch)) = (self.slots.get(&key), &event.payload)
+                else {
+                    return;
+                };
+                for (column, value) in patch {
+                    self.set(slot, column, value.clone());
+                }
+            }
+            EventType::SoftDelete => {
+                if let Some(slot) = self.slots.remove(&key) {
+                    self.clear(slot);
+                    self.live[slot] = false;
+                    self.free.push(slot);
+                }
+            }
+        }
+    }
+
+    /// Rows matching every condition, at most `limit` of them. An
+    /// equality on a filtered column tests only the chunks that might
+    /// hold the value, counted in `stats`.
+    pub(crate) fn scan(
+        &self,
+        conditions: &[WhereCondition],
+        limit: Option<usize>,
+        stats: &mut ChunkFilterStats,
+    ) -> Vec<Value> {
+        let mut matching = self.live.clone();
+        let filtered = conditions.iter().find_map(|cond| {
+            let chunks = self.chunks.get(&cond.column)?;
+            matches!(cond.operator.as_str(), "=" | "==").then(|| (chunks, filter_key(&cond.value)))
+        });
+        if let Some((chunks, key)) = filtered {
+            for (chunk, slots) in matching.chunks_mut(CHUNK_ROWS).enumerate() {
+                if chunks
+                    .get(chunk)
+                    .is_some_and(|meta| meta.filter.contains(&key))
+                {
+                    stats.chunks_read += 1;
+                } else {
+                    stats.chunks_skipped += 1;
+                    slots.fill(false);
+                }
+            }
+        }
+        for cond in conditions {
+            let column = self.columns.get(&cond.column);
+            for (slot, matches) in matching.iter_mut().enumerate() {
+                if *matches {
+                    let value = column.and_then(|values| values[slot].as_ref());
+                    *matches = matches_value(value, cond);
+                }
+            }
+        }
+
+        matching
+            .iter()
+            .enumerate()
+            .filter(|(_, matches)| **matches)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|(slot, _)| self.row(slot))
+            .collect()
+    }
+
+    fn insert(&mut self, key: String, row: Value) {
+        let slot = match self.slots.get(&key) {
+            Some(&slot) => {
+                self.clear(slot);
+                slot
+            }
+            None => {
+                let slot = self.free.pop().unwrap_or_else(|| {
+                    self.live.push(false);
+                    for values in self.columns.values_mut() {
+                        values.push(None);
+                    }
+                    self.live.len() - 1
+                });
+                self.live[slot] = true;
+                self.slots.insert(key, slot);
+                slot
+            }
+        };
+        if let Value::Object(fields) = row {
+            for (column, value) in fields {
+                self.set(slot, &column, value);
+            }
+        }
+    }
+
+    fn set(&mut self, slot: usize, column: &str, value: Value) {
+        if let Some(chunks) = self.chunks.get_mut(column) {
+            let chunk = slot / CHUNK_ROWS;
+            while chunks.len() <= chunk {
+                chunks.push(ColumnChunkMetadata::new(self.filter_fpp));
+            }
+            chunks[chunk].filter.add(&filter_key(&value));
+        }
+        let slots = self.live.len();
+        let values = self
+            .columns
+            .entry(column.to_string())
+            .or_insert_with(|| vec![None; slots]);
+        values[slot] = Some(value);
+    }
+
+    fn clear(&mut self, slot: usize) {
+        for values in self.columns.values_mut() {
+            values[slot] = None;
+        }
+    }
+
+    fn row(&self, slot: usize) -> Value {
+        Value::Object(
+            self.columns
+                .iter()
+                .filter_map(|(column, values)| Some((column.clone(), values[slot].clone()?)))
+                .collect(),
+        )
+    }
+}
+
+/// How a chunk filter holds `value`: numbers, and text that reads as
+/// one, by decimal value, since `=` compares them that way
+fn filter_key(value: &Value) -> String {
+    match Decimal::from_value(value) {
+        Some(decimal) => decimal.normalize().to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::key_filter::DEFAULT_FALSE_POSITIVE_RATE;
+    use serde_json::json;
+
+    fn condition(column: &str, operator: &str, value: Value) -> WhereCondition {
+        WhereCondition {
+            column: column.to_string(),
+            operator: operator.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn appends_keep_the_projection_current() {
+        let mut projection = ColumnarProjection::from_rows(
+            HashMap::from([
+                ("1".to_string(), json!({"id": 1, "score": 10})),
+                ("2".to_string(), json!({"id": 2, "score": 20})),
+            ]),
+            DEFAULT_FALSE_POSITIVE_RATE,
+            &BTreeSet::new(),
+        );
+        projection.apply(&Event::new_soft_delete("t".to_string(), json!(1)));
+        projection.apply(&Event::new_insert(
+            "t".to_string(),
+            json!(3),
+            json!({"id": 3, "score": 30, "note": "new"}),
+        ));
+        projection.apply(&Event::new_patch(
+            "t".to_string(),
+            json!(2),
+            json!({"score": 25}),
+        ));
+
+        // The deleted row's slot went to row 3
+        assert_eq!(projection.live.len(), 2);
+        let mut rows = projection.scan(
+            &[condition("score", ">", json!(20))],
+            None,
+            &mut ChunkFilterStats::default(),
+        );
+        rows.sort_by_key(|row| row["id"].as_i64());
+        assert_eq!(
+            rows,
+            vec![
+                json!({"id": 2, "score": 25}),
+                json!({"id": 3, "score": 30, "note": "new"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_missing_column_only_matches_is_null() {
+        let projection = ColumnarProjection::from_rows(
+            HashMap::from([
+                ("1".to_string(), json!({"id": 1})),
+                ("2".to_string(), json!({"id": 2, "note": "x"})),
+            ]),
+            DEFAULT_FALSE_POSITIVE_RATE,
+            &BTreeSet::new(),
+        );
+        assert_eq!(
+            projection.scan(
+                &[condition("note", "IS NULL", Value::Null)],
+                None,
+                &mut ChunkFilterStats::default()
+            ),
+            vec![json!({"id": 1})]
+        );
+        assert_eq!(
+            "COLUMNAR".parse::<StorageFormat>().unwrap(),
+            StorageFormat::Columnar
+        );
+        assert!("rows".parse::<StorageFormat>().is_err());
+    }
+
+    #[test]
+    fn equalities_test_only_chunks_that_might_hold_the_value() {
+        let mut projection = ColumnarProjection::from_rows(
+            HashMap::new(),
+            DEFAULT_FALSE_POSITIVE_RATE,
+            &BTreeSet::from(["user_id".to_string()]),
+        );
+        for id in 0..CHUNK_ROWS * 2 {
+            projection.apply(&Event::new_insert(
+                "t".to_string(),
+                json!(id),
+                json!({"id": id, "user_id": id / 10}),
+            ));
+        }
+        let mut stats = ChunkFilterStats::default();
+        let user = |value| [condition("user_id", "=", value)];
+
+        // No chunk has user 5000
+        assert!(projection
+            .scan(&user(json!(5000)), None, &mut stats)
+            .is_empty());
+        assert_eq!(
+            stats,
+            ChunkFilterStats {
+                chunks_skipped: 2,
+                chunks_read: 0,
+            }
+        );
+
+        // Text compares with numbers by value, so it finds them too
+        projection.apply(&Event::new_patch(
+            "t".to_string(),
+            json!(7),
+            json!({"user_id": 5000}),
+        ));
+        assert_eq!(
+            projection.scan(&user(json!("5000.0")), None, &mut stats),
+            vec![json!({"id": 7, "user_id": 5000})]
+        );
+        assert_eq!(stats.chunks_read, 1);
+    }
+}
@@ -0,0 +1,197 @@
+//! Segment compression.
+//!
+//! A table's [`Compression`] applies to the segments written while it is
+//! set. Small JSON events compress poorly one at a time, so a compressed
+//! segment gathers the frames appended between flushes (up to 64 KiB of
+//! them) into a block, compressed together before it is (optionally)
+//! encrypted and framed. A torn write still loses at most the last block,
+//! which no flush had completed.
+//!
+//! A compressed segment starts with an 8-byte header naming its codec, so
+//! segments written under different settings can be read side by side,
+//! and a segment whose codec this build doesn't know fails to open rather
+//! than being read as garbage.
+//! Segments without a header are uncompressed; that is every segment
+//! written before compression existed. The header's magic read as a frame
+//! length would exceed the largest frame allowed, so the two can't be
+//! confused.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{DriftError, Result};
+
+const MAGIC: [u8; 4] = *b"DSEG";
+const HEADER_VERSION: u8 = 1;
+
+/// Length of the header at the start of a compressed segment
+pub const HEADER_LEN: u64 = 8;
+
+/// Zstd level used for segments; favors write speed over ratio
+const ZSTD_LEVEL: i32 = 3;
+
+/// Brotli quality used when `brotli` is named without one
+pub const DEFAULT_BROTLI_QUALITY: u32 = 9;
+
+/// Highest Brotli quality
+const MAX_BROTLI_QUALITY: u32 = 11;
+
+/// Brotli window size, as a power of two
+const BROTLI_WINDOW: u32 = 22;
+
+/// Codec applied to segment frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+    Lz4,
+    /// Brotli at a quality from 0 to 11: better ratios than Zstd on text,
+    /// for archival tables where write speed matters less. The header
+    /// records the quality so appends after a reopen keep it; reading
+    /// doesn't need it.
+    Brotli(u32),
+}
+
+impl Compression {
+    pub fn is_none(&self) -> bool {
+        *self == Compression::None
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => data.to_vec(),
+            Compression::Zstd => zstd::encode_all(data, ZSTD_LEVEL)?,
+            Compression::Lz4 => lz4::block::compress(data, None, true)?,
+            Compression::Brotli(quality) => {
+                let mut writer = brotli::CompressorWriter::new(
+                    Vec::new(),
+                    4096,
+                    quality.min(MAX_BROTLI_QUALITY),
+                    BROTLI_WINDOW,
+                );
+                writer.write_all(data)?;
+                writer.into_inner()
+            }
+        })
+    }
+
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => data.to_vec(),
+            Compression::Zstd => zstd::decode_all(data)?,
+            Compression::Lz4 => lz4::block::decompress(data, None)?,
+            Compression::Brotli(_) => {
+                let mut decoded = Vec::new();
+                brotli::Decompressor::new(data, 4096).read_to_end(&mut decoded)?;
+                decoded
+            }
+        })
+    }
+
+    /// The codec's setting stored in the header next to its id
+    fn parameter(self) -> u8 {
+        match self {
+            Compression::Brotli(quality) => quality.min(MAX_BROTLI_QUALITY) as u8,
+            _ => 0,
+        }
+    }
+
+    fn id(self) -> u8 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+            Compression::Lz4 => 2,
+            Compression::Brotli(_) => 3,
+        }
+    }
+
+    fn from_header(id: u8, parameter: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Compression::None),
+            1 => Ok(Compression::Zstd),
+            2 => Ok(Compression::Lz4),
+            3 => Ok(Compression::Brotli(u32::from(parameter))),
+            other => Err(DriftError::CorruptSegment(format!(
+                "unknown segment codec {}; the segment was written by a build with a codec this one doesn't support",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::None => f.write_str("none"),
+            Compression::Zstd => f.write_str("zstd"),
+            Compression::Lz4 => f.write_str("lz4"),
+            Compression::Brotli(DEFAULT_BROTLI_QUALITY) => f.write_str("brotli"),
+            Compression::Brotli(quality) => write!(f, "brotli:{}", quality),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = DriftError;
+
+    /// `none`, `zstd`, `lz4`, `brotli`, or `brotli:quality` as in
+    /// `brotli:11`
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.to_ascii_lowercase();
+        if let Some(quality) = s.strip_prefix("brotli:") {
+            return match quality.parse() {
+                Ok(quality @ 0..=MAX_BROTLI_QUALITY) => Ok(Compression::Brotli(quality)),
+                _ => Err(DriftError::InvalidQuery(format!(
+                    "invalid brotli quality '{}', expected 0 to {}",
+                    quality, MAX_BROTLI_QUALITY
+                ))),
+            };
+        }
+        match s.as_str() {
+            "none" => Ok(Compression::None),
+            "zstd" => Ok(Compression::Zstd),
+            "lz4" => Ok(Compression::Lz4),
+            "brotli" => Ok(Compression::Brotli(DEFAULT_BROTLI_QUALITY)),
+            other => Err(DriftError::InvalidQuery(format!(
+                "unknown compression '{}', expected none, zstd, lz4 or brotli[:quality]",
+                other
+            ))),
+        }
+    }
+}
+
+/// Start a segment compressed with `compression`
+pub fn write_header<W: Write>(writer: &mut W, compression: Compression) -> Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&[HEADER_VERSION, compression.id(), compression.parameter(), 0])?;
+    Ok(())
+}
+
+/// The codec named by a segment's header, or `None` when the segment has
+/// no header, in which case the caller rewinds to read frames from the
+/// start.
+pub fn read_header<R: Read>(reader: &mut R) -> Result<Option<Compression>> {
+    let mut header = [0u8; HEADER_LEN as usize];
+    let mut filled = 0;
+    while filled < header.len() {
+        match reader.read(&mut header[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    if filled < header.len() || header[..4] != MAGIC {
+        return Ok(None);
+    }
+    if header[4] != HEADER_VERSION {
+        return Err(DriftError::CorruptSegment(format!(
+            "unsupported segment header version {}",
+            header[4]
+        )));
+    }
+    Compression::from_header(header[5], header[6]).map(Some)
+}
@@ -0,0 +1,169 @@
+//! Per-segment primary key bloom filters
+//!
+//! With `bloom_filter` set on a table, each segment has a bloom filter of
+//! the primary keys its events name. A lookup by primary key reads only
+//! the segments whose filter might hold the key, so a key that was never
+//! written is answered without reading any. Only primary keys are
+//! filtered: a patch names its row's key but not the columns it leaves
+//! alone, so a filter on any other column could rule out a segment that
+//! changes the row. Other columns of a columnar table are filtered per
+//! chunk of its projection instead (see [`crate::storage::columnar`]).
+//!
+//! Filters are kept in memory. A segment started while filtering is on
+//! has its filter built as events are appended; any other closed segment
+//! has its filter built from the segment the first time a lookup reads
+//! it. A segment that already had events when the table was opened, or
+//! when filtering was turned on, is read by every lookup until it is
+//! closed.
+
+use std::collections::HashMap;
+
+use crate::bloom_filter::{BloomConfig, ScalableBloomFilter};
+use crate::events::Event;
+
+/// False-positive rate used by `bloom_filter = on`
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.01;
+
+/// Keys a filter is sized for before it grows
+const INITIAL_KEYS: usize = 1024;
+
+/// Segments primary key lookups skipped and read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyFilterStats {
+    /// Segments a filter ruled out
+    pub segments_skipped: u64,
+    /// Segments read because their filter might hold the key, or they
+    /// had none yet
+    pub segments_read: u64,
+}
+
+/// A table's segment filters
+#[derive(Debug)]
+pub(crate) struct KeyFilters {
+    /// False-positive rate, `None` when the table isn't filtered
+    fpp: Option<f64>,
+    /// Bumped whenever the filters are thrown away, so a filter built
+    /// from a segment read before then isn't kept
+    generation: u64,
+    segments: HashMap<u64, ScalableBloomFilter>,
+    stats: KeyFilterStats,
+}
+
+impl KeyFilters {
+    pub(crate) fn new(fpp: Option<f64>) -> Self {
+        Self {
+            fpp,
+            generation: 0,
+            segments: HashMap::new(),
+            stats: KeyFilterStats::default(),
+        }
+    }
+
+    pub(crate) fn enabled(&self) -> bool {
+        self.fpp.is_some()
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(crate) fn stats(&self) -> KeyFilterStats {
+        self.stats
+    }
+
+    /// Throw every filter away, filtering at `fpp` from now on
+    pub(crate) fn reset(&mut self, fpp: Option<f64>) {
+        self.fpp = fpp;
+        self.generation += 1;
+        self.segments.clear();
+    }
+
+    /// Segment `id` was just created empty; its filter is built as it is
+    /// written
+    pub(crate) fn start(&mut self, id: u64) {
+        if let Some(fpp) = self.fpp {
+            self.segments.insert(id, empty_filter(fpp));
+        }
+    }
+
+    /// An event for primary key `key` was appended to segment `id`
+    pub(crate) fn record(&mut self, id: u64, key: &serde_json::Value) {
+        if let Some(filter) = self.segments.get_mut(&id) {
+            filter.add(&key.to_string());
+        }
+    }
+
+    /// Whether segment `id` might have events for `key`; `None` when it
+    /// has no filter
+    pub(crate) fn might_contain(&mut self, id: u64, key: &str) -> Option<bool> {
+        let found = self.segments.get(&id)?.contains(&key);
+        if found {
+            self.stats.segments_read += 1;
+        } else {
+            self.stats.segments_skipped += 1;
+        }
+        Some(found)
+    }
+
+    /// A segment without a filter was read in full
+    pub(crate) fn read_unfiltered(&mut self) {
+        self.stats.segments_read += 1;
+    }
+
+    /// Keep the filter built from closed segment `id`, unless the filters
+    /// were thrown away since `generation`
+    pub(crate) fn insert_built(&mut self, generation: u64, id: u64, filter: ScalableBloomFilter) {
+        if generation == self.generation && self.enabled() {
+            self.segments.insert(id, filter);
+        }
+    }
+
+    /// A filter of the keys `events` name, at the current rate
+    pub(crate) fn build(&self, events: &[Event]) -> Option<ScalableBloomFilter> {
+        let mut filter = empty_filter(self.fpp?);
+        for event in events {
+            filter.add(&event.primary_key.to_string());
+        }
+        Some(filter)
+    }
+}
+
+fn empty_filter(fpp: f64) -> ScalableBloomFilter {
+    ScalableBloomFilter::new(BloomConfig {
+        expected_elements: INITIAL_KEYS,
+        false_positive_rate: fpp,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_segments_started_or_built_are_filtered() {
+        let mut filters = KeyFilters::new(Some(DEFAULT_FALSE_POSITIVE_RATE));
+        filters.start(2);
+        filters.record(2, &serde_json::json!(1));
+        filters.record(1, &serde_json::json!(1));
+
+        assert_eq!(filters.might_contain(2, "1"), Some(true));
+        assert_eq!(filters.might_contain(2, "2"), Some(false));
+        assert_eq!(filters.might_contain(1, "1"), None);
+
+        // A filter built before a reset is dropped
+        let generation = filters.generation();
+        let built = filters.build(&[]).unwrap();
+        filters.reset(Some(DEFAULT_FALSE_POSITIVE_RATE));
+        filters.insert_built(generation, 1, built);
+        assert_eq!(filters.might_contain(1, "1"), None);
+        assert_eq!(filters.might_contain(2, "1"), None);
+
+        assert_eq!(
+            filters.stats(),
+            KeyFilterStats {
+                segments_skipped: 1,
+                segments_read: 1,
+            }
+        );
+    }
+}
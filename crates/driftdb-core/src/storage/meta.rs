@@ -4,6 +4,7 @@ use std::fs;
 use std::path::Path;
 
 use crate::errors::Result;
+use crate::storage::HistoryFloor;
 
 /// Metadata about a single segment's sequence range
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -120,6 +121,12 @@ pub struct TableMeta {
     pub segment_count: u64,
     pub snapshot_interval: u64,
     pub compact_threshold: u64,
+    /// Snapshot sequence the table was last compacted up to (0 if never)
+    #[serde(default)]
+    pub last_compaction_sequence: u64,
+    /// How far back history reaches once retention has pruned it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_floor: Option<HistoryFloor>,
     /// Index of segment sequence ranges for optimized reads
     #[serde(default)]
     pub segment_index: SegmentIndex,
@@ -133,6 +140,8 @@ impl Default for TableMeta {
             segment_count: 1,
             snapshot_interval: 100_000,
             compact_threshold: 128 * 1024 * 1024,
+            last_compaction_sequence: 0,
+            history_floor: None,
             segment_index: SegmentIndex::new(),
         }
     }
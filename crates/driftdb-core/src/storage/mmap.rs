@@ -0,0 +1,269 @@
+//! Memory-mapped segment reads.
+//!
+//! [`MappedFileBackend`] stores segments as local files exactly like
+//! [`LocalFileBackend`], but serves reads from a memory map of the file.
+//! Maps are kept per segment and handed to every reader, so repeated scans
+//! of a hot table cost no syscalls and are served by the OS page cache.
+//!
+//! A map only ever covers what was in the file when it was made. Appending
+//! through the backend drops the segment's map and the next read maps the
+//! longer file; deleting or renaming a segment, as compaction does, drops
+//! it for good, so no reader sees a segment that has been compacted away.
+//!
+//! Reading a page of a map whose file was cut shorter raises SIGBUS, so
+//! truncation never shrinks a file that a reader still has mapped: the
+//! kept prefix is written to a new file that replaces the old one, and
+//! open readers finish on the old contents. Segments must not be truncated
+//! by anything outside the backend. If a file can't be mapped, it is read
+//! with ordinary file I/O instead.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use memmap2::Mmap;
+use parking_lot::Mutex;
+
+use crate::errors::Result;
+use crate::storage::backend::{LocalFileBackend, SegmentSink, SegmentSource, StorageBackend};
+
+/// Local segment files, read through memory maps
+#[derive(Default)]
+pub struct MappedFileBackend {
+    files: LocalFileBackend,
+    maps: Arc<MapCache>,
+}
+
+impl MappedFileBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Segments currently mapped
+    pub fn mapped_segments(&self) -> usize {
+        self.maps
+            .entries
+            .lock()
+            .values()
+            .filter(|entry| entry.map.is_some())
+            .count()
+    }
+
+    /// The segment's map, mapping it first if it has none
+    fn map(&self, path: &Path) -> Option<MappedSegment> {
+        let mut entries = self.maps.entries.lock();
+        let entry = entries.entry(path.to_path_buf()).or_default();
+        if entry.map.is_none() {
+            match map_file(path) {
+                Ok(Some(map)) => entry.map = Some(Arc::new(map)),
+                // Nothing to map
+                Ok(None) => return None,
+                Err(e) => {
+                    tracing::debug!("Reading {} without mmap: {}", path.display(), e);
+                    return None;
+                }
+            }
+        }
+        Some(MappedSegment {
+            map: entry.map.clone()?,
+            _reader: entry.readers.clone(),
+        })
+    }
+
+    /// Cut a file back to `len` bytes without shrinking it under a map
+    fn replace_with_prefix(&self, path: &Path, len: u64) -> Result<()> {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".truncating");
+        let replacement = PathBuf::from(name);
+        {
+            let mut source = File::open(path)?;
+            let mut target = File::create(&replacement)?;
+            io::copy(&mut (&mut source).take(len), &mut target)?;
+            target.sync_all()?;
+        }
+        fs::rename(&replacement, path)?;
+        Ok(())
+    }
+}
+
+fn map_file(path: &Path) -> io::Result<Option<Mmap>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: segment files are only changed through this backend, which
+    // appends past the end of existing maps and never shrinks a file that
+    // is still mapped.
+    unsafe { Mmap::map(&file) }.map(Some)
+}
+
+#[derive(Default)]
+struct MapCache {
+    entries: Mutex<HashMap<PathBuf, MapEntry>>,
+}
+
+#[derive(Default)]
+struct MapEntry {
+    map: Option<Arc<Mmap>>,
+    /// Held by every reader of the segment, whichever map it reads from,
+    /// so truncation can tell when a map may still be in use
+    readers: Arc<()>,
+}
+
+impl MapCache {
+    /// Forget a segment's map; readers keep theirs
+    fn drop_map(&self, path: &Path) {
+        if let Some(entry) = self.entries.lock().get_mut(path) {
+            entry.map = None;
+        }
+    }
+
+    /// Forget a segment altogether, returning whether readers are open
+    fn remove(&self, path: &Path) -> bool {
+        self.entries
+            .lock()
+            .remove(path)
+            .is_some_and(|entry| Arc::strong_count(&entry.readers) > 1)
+    }
+}
+
+/// A reader's view of a mapped segment
+struct MappedSegment {
+    map: Arc<Mmap>,
+    _reader: Arc<()>,
+}
+
+impl AsRef<[u8]> for MappedSegment {
+    fn as_ref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+/// Appends to a segment, dropping its map so readers see the new bytes
+struct MappedSink {
+    file: Box<dyn SegmentSink>,
+    path: PathBuf,
+    maps: Arc<MapCache>,
+}
+
+impl Write for MappedSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.maps.drop_map(&self.path);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl SegmentSink for MappedSink {
+    fn sync(&mut self) -> Result<()> {
+        self.file.sync()
+    }
+}
+
+impl StorageBackend for MappedFileBackend {
+    fn create(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        // Replaced, not truncated in place, if a reader still maps it
+        if self.maps.remove(path) && path.exists() {
+            fs::remove_file(path)?;
+        }
+        let file = self.files.create(path)?;
+        Ok(Box::new(MappedSink {
+            file,
+            path: path.to_path_buf(),
+            maps: self.maps.clone(),
+        }))
+    }
+
+    fn open_append(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let file = self.files.open_append(path)?;
+        Ok(Box::new(MappedSink {
+            file,
+            path: path.to_path_buf(),
+            maps: self.maps.clone(),
+        }))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn SegmentSource>> {
+        match self.map(path) {
+            Some(segment) => Ok(Box::new(Cursor::new(segment))),
+            None => self.files.open_read(path),
+        }
+    }
+
+    fn size(&self, path: &Path) -> Result<u64> {
+        self.files.size(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.exists(path)
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        self.files.list(dir)
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        // Unlinking leaves open maps readable
+        self.maps.remove(path);
+        self.files.delete(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        self.maps.remove(from);
+        self.maps.remove(to);
+        self.files.rename(from, to)
+    }
+
+    fn truncate(&self, path: &Path, len: u64) -> Result<()> {
+        if self.maps.remove(path) {
+            self.replace_with_prefix(path, len)
+        } else {
+            self.files.truncate(path, len)
+        }
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        self.files.modified(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_follow_appends_and_truncation() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let backend = MappedFileBackend::new();
+        let path = temp.path().join("00000001.seg");
+
+        let mut sink = backend.create(&path).unwrap();
+        sink.write_all(b"hello").unwrap();
+        sink.sync().unwrap();
+        assert_eq!(backend.read(&path).unwrap(), b"hello");
+        assert_eq!(backend.mapped_segments(), 1);
+
+        sink.write_all(b" world").unwrap();
+        sink.sync().unwrap();
+        let mut open = backend.open_read(&path).unwrap();
+        assert_eq!(backend.read(&path).unwrap(), b"hello world");
+
+        // A reader keeps its map while the file is cut back under it
+        backend.truncate(&path, 5).unwrap();
+        let mut before = Vec::new();
+        open.read_to_end(&mut before).unwrap();
+        assert_eq!(before, b"hello world");
+        assert_eq!(backend.read(&path).unwrap(), b"hello");
+
+        backend.delete(&path).unwrap();
+        assert_eq!(backend.mapped_segments(), 0);
+        assert!(backend.open_read(&path).is_err());
+    }
+}
@@ -1,11 +1,25 @@
+pub mod backend;
+pub mod columnar;
+pub mod compression;
 pub mod frame;
+pub mod key_filter;
 pub mod meta;
+pub mod mmap;
+pub mod retention;
 pub mod segment;
 pub mod streaming;
 pub mod table_storage;
+pub mod tiered;
 
+pub use backend::{LocalFileBackend, MemoryBackend, SegmentSink, SegmentSource, StorageBackend};
+pub use columnar::{ChunkFilterStats, StorageFormat};
+pub use compression::Compression;
 pub use frame::{Frame, FramedRecord};
+pub use key_filter::KeyFilterStats;
 pub use meta::{SegmentBounds, SegmentIndex, TableMeta};
+pub use mmap::MappedFileBackend;
+pub use retention::{HistoryFloor, RetentionPolicy};
 pub use segment::{Segment, SegmentReader, SegmentWriter};
 pub use streaming::{reconstruct_state_streaming, EventStreamIterator, StreamConfig};
-pub use table_storage::{TableStats, TableStorage};
+pub use table_storage::{TableStats, TableStorage, VacuumStats};
+pub use tiered::{TierAge, TierPolicy, TierStats, TieredBackend};
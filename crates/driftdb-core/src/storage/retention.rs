@@ -0,0 +1,227 @@
+//! Per-table retention of drift history
+//!
+//! A table keeps every change it has seen for time travel. A retention
+//! policy bounds that: `retain_history_for` keeps every version from a
+//! window back from now, and `retain_versions` keeps the table's last N
+//! versions, each event being one. With both set, history either of them
+//! keeps is kept.
+//!
+//! `VACUUM` and `VACUUM FULL` fold the history outside the policy into
+//! the table's state at the oldest point it keeps, and record that point
+//! as the table's history floor. Reads as of before the floor fail rather
+//! than return the folded state.
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::errors::{DriftError, Result};
+use crate::events::Event;
+
+/// How much of a table's history is kept for time travel, set with
+/// `ALTER TABLE t SET (retain_history_for = '90 days')` or
+/// `SET (retain_versions = 50)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Seconds of history kept back from now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history_for_secs: Option<u64>,
+    /// Versions kept back from the latest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Whether all history is kept
+    pub fn is_unbounded(&self) -> bool {
+        self.history_for_secs.is_none() && self.versions.is_none()
+    }
+
+    /// Set the table option `option` (`retain_history_for` or
+    /// `retain_versions`) to `value`; `none` or `off` clears it
+    pub fn set(&mut self, option: &str, value: &str) -> Result<()> {
+        let cleared = matches!(value.to_lowercase().as_str(), "none" | "off");
+        match option {
+            "retain_history_for" => {
+                self.history_for_secs = if cleared {
+                    None
+                } else {
+                    Some(parse_period(value)?)
+                };
+            }
+            "retain_versions" => {
+                self.versions = if cleared {
+                    None
+                } else {
+                    match value.parse::<u64>() {
+                        Ok(versions) if versions > 0 => Some(versions),
+                        _ => {
+                            return Err(DriftError::InvalidQuery(format!(
+                                "retain_versions must be a positive number, got '{}'",
+                                value
+                            )))
+                        }
+                    }
+                };
+            }
+            other => {
+                return Err(DriftError::InvalidQuery(format!(
+                    "unknown retention option '{}'",
+                    other
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// The latest sequence whose history the policy lets go: the state as
+    /// of it is all that must remain of everything up to it. `events` are
+    /// the table's events in sequence order, needed only for a window.
+    /// `None` when nothing falls outside the policy.
+    pub fn prune_through(
+        &self,
+        last_sequence: u64,
+        now: OffsetDateTime,
+        events: &[Event],
+    ) -> Option<u64> {
+        let by_versions = self
+            .versions
+            .map(|versions| last_sequence.saturating_sub(versions));
+        let by_window = self.history_for_secs.map(|secs| {
+            // A window reaching past the start of time keeps everything
+            let cutoff = i64::try_from(secs)
+                .ok()
+                .and_then(|secs| now.checked_sub(time::Duration::seconds(secs)));
+            cutoff.map_or(0, |cutoff| {
+                events
+                    .iter()
+                    .filter(|event| event.timestamp <= cutoff)
+                    .map(|event| event.sequence)
+                    .max()
+                    .unwrap_or(0)
+            })
+        });
+        let through = match (by_versions, by_window) {
+            (Some(a), Some(b)) => a.min(b),
+            (a, b) => a.or(b)?,
+        };
+        (through > 0).then_some(through)
+    }
+}
+
+/// `90 days`, `12h`, `1 week` → seconds
+fn parse_period(value: &str) -> Result<u64> {
+    let invalid = || {
+        DriftError::InvalidQuery(format!(
+            "invalid retention period '{}', expected e.g. '90 days'",
+            value
+        ))
+    };
+    let value = value.trim();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let n: u64 = value[..digits].parse().map_err(|_| invalid())?;
+    let scale = match value[digits..].trim().to_lowercase().as_str() {
+        "s" | "sec" | "second" | "seconds" => 1,
+        "min" | "minute" | "minutes" => 60,
+        "h" | "hour" | "hours" => 60 * 60,
+        "d" | "day" | "days" => 24 * 60 * 60,
+        "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    match n.checked_mul(scale) {
+        Some(secs) if secs > 0 => Ok(secs),
+        _ => Err(invalid()),
+    }
+}
+
+/// The oldest point a table's history reaches once it has been folded:
+/// the sequence the folded state stands at, and that event's time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryFloor {
+    pub sequence: u64,
+    pub timestamp: OffsetDateTime,
+}
+
+impl HistoryFloor {
+    /// Fails for a read of `table` as of a sequence before the floor
+    pub fn check_sequence(&self, table: &str, sequence: u64) -> Result<()> {
+        if sequence < self.sequence {
+            return Err(self.pruned(table));
+        }
+        Ok(())
+    }
+
+    /// Fails for a read of `table` as of a time before the floor
+    pub fn check_timestamp(&self, table: &str, timestamp: OffsetDateTime) -> Result<()> {
+        if timestamp < self.timestamp {
+            return Err(self.pruned(table));
+        }
+        Ok(())
+    }
+
+    fn pruned(&self, table: &str) -> DriftError {
+        let timestamp = self
+            .timestamp
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_else(|_| self.timestamp.to_string());
+        DriftError::HistoryPruned(format!(
+            "sequence {} ({}) of table '{}'",
+            self.sequence, timestamp, table
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn periods_and_versions_parse() {
+        let mut policy = RetentionPolicy::default();
+        policy.set("retain_history_for", "90 days").unwrap();
+        policy.set("retain_versions", "50").unwrap();
+        assert_eq!(policy.history_for_secs, Some(90 * 24 * 60 * 60));
+        assert_eq!(policy.versions, Some(50));
+        assert!(policy.set("retain_history_for", "90 fortnights").is_err());
+        assert!(policy.set("retain_versions", "0").is_err());
+
+        policy.set("retain_history_for", "12h").unwrap();
+        assert_eq!(policy.history_for_secs, Some(12 * 60 * 60));
+        policy.set("retain_history_for", "none").unwrap();
+        policy.set("retain_versions", "OFF").unwrap();
+        assert!(policy.is_unbounded());
+    }
+
+    #[test]
+    fn the_longer_policy_wins() {
+        let now = OffsetDateTime::now_utc();
+        let events: Vec<Event> = (1..=10)
+            .map(|sequence| {
+                let mut event = Event::new_insert("t".to_string(), json!(sequence), json!({}));
+                event.sequence = sequence;
+                event.timestamp = now - time::Duration::days(11 - sequence as i64);
+                event
+            })
+            .collect();
+
+        let window = RetentionPolicy {
+            history_for_secs: Some(3 * 24 * 60 * 60),
+            versions: None,
+        };
+        // Events 1..=8 are 3 or more days old
+        assert_eq!(window.prune_through(10, now, &events), Some(8));
+
+        let both = RetentionPolicy {
+            versions: Some(5),
+            ..window
+        };
+        assert_eq!(both.prune_through(10, now, &events), Some(5));
+        let few = RetentionPolicy {
+            versions: Some(20),
+            history_for_secs: None,
+        };
+        assert_eq!(few.prune_through(10, now, &events), None);
+    }
+}
@@ -1,17 +1,27 @@
-use std::fs::{self, File, OpenOptions};
-use std::io::{BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::collections::VecDeque;
+use std::io::{BufReader, BufWriter, Cursor, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::encryption::EncryptionService;
 use crate::errors::Result;
 use crate::events::Event;
+use crate::storage::backend::{LocalFileBackend, SegmentSink, SegmentSource, StorageBackend};
+use crate::storage::compression::{self, Compression, HEADER_LEN};
 use crate::storage::frame::{Frame, FramedRecord};
 
+/// Uncompressed bytes of frames a compressed segment gathers before it
+/// writes them out as one block, flush or not
+const BLOCK_SIZE: usize = 64 * 1024;
+
 pub struct Segment {
     path: PathBuf,
     id: u64,
     encryption_service: Option<Arc<EncryptionService>>,
+    backend: Arc<dyn StorageBackend>,
+    /// Codec for a segment this creates; existing segments keep the one
+    /// in their header
+    compression: Compression,
 }
 
 impl Segment {
@@ -20,6 +30,8 @@ impl Segment {
             path,
             id,
             encryption_service: None,
+            backend: LocalFileBackend::shared(),
+            compression: Compression::None,
         }
     }
 
@@ -32,52 +44,87 @@ impl Segment {
             path,
             id,
             encryption_service: Some(encryption_service),
+            backend: LocalFileBackend::shared(),
+            compression: Compression::None,
         }
     }
 
+    /// Keep this segment in `backend` instead of a local file
+    pub fn with_backend(mut self, backend: Arc<dyn StorageBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Compress the frames of a segment this creates with `compression`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     pub fn create(&self) -> Result<SegmentWriter> {
-        if let Some(parent) = self.path.parent() {
-            fs::create_dir_all(parent)?;
+        let sink = self.backend.create(&self.path)?;
+        self.start_writer(sink)
+    }
+
+    pub fn open_writer(&self) -> Result<SegmentWriter> {
+        let len = if self.exists() {
+            self.backend.size(&self.path)?
+        } else {
+            0
+        };
+        // Too short to hold a frame: empty, or a header torn by a crash
+        if len < HEADER_LEN {
+            if len > 0 {
+                self.backend.truncate(&self.path, 0)?;
+            }
+            let sink = self.backend.open_append(&self.path)?;
+            return self.start_writer(sink);
         }
-        let file = OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&self.path)?;
+        let compression = self.codec()?;
+        let sink = self.backend.open_append(&self.path)?;
         Ok(SegmentWriter::new(
-            file,
+            sink,
+            len,
             self.encryption_service.clone(),
             self.id,
+            compression,
         ))
     }
 
-    pub fn open_writer(&self) -> Result<SegmentWriter> {
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.path)?;
-        Ok(SegmentWriter::new(
-            file,
+    /// A writer for an empty segment, starting it with a header if it is
+    /// compressed
+    fn start_writer(&self, sink: Box<dyn SegmentSink>) -> Result<SegmentWriter> {
+        let mut writer = SegmentWriter::new(
+            sink,
+            0,
             self.encryption_service.clone(),
             self.id,
-        ))
+            self.compression,
+        );
+        if !self.compression.is_none() {
+            compression::write_header(&mut writer.writer, self.compression)?;
+            writer.bytes_written = HEADER_LEN;
+        }
+        Ok(writer)
+    }
+
+    /// The codec this segment was written with, from its header
+    pub fn codec(&self) -> Result<Compression> {
+        let mut source = self.backend.open_read(&self.path)?;
+        Ok(compression::read_header(&mut source)?.unwrap_or_default())
     }
 
     pub fn open_reader(&self) -> Result<SegmentReader> {
-        let file = File::open(&self.path)?;
-        Ok(SegmentReader::new(
-            file,
-            self.encryption_service.clone(),
-            self.id,
-        ))
+        let source = self.backend.open_read(&self.path)?;
+        SegmentReader::new(source, self.encryption_service.clone(), self.id)
     }
 
     pub fn size(&self) -> Result<u64> {
-        Ok(fs::metadata(&self.path)?.len())
+        self.backend.size(&self.path)
     }
 
     pub fn exists(&self) -> bool {
-        self.path.exists()
+        self.backend.exists(&self.path)
     }
 
     pub fn path(&self) -> &Path {
@@ -89,38 +136,70 @@ impl Segment {
     }
 
     pub fn truncate_at(&self, position: u64) -> Result<()> {
-        let file = OpenOptions::new().write(true).open(&self.path)?;
-        file.set_len(position)?;
-        Ok(())
+        self.backend.truncate(&self.path, position)
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        self.backend.delete(&self.path)
     }
 }
 
 pub struct SegmentWriter {
-    writer: BufWriter<File>,
+    writer: BufWriter<Box<dyn SegmentSink>>,
     bytes_written: u64,
     encryption_service: Option<Arc<EncryptionService>>,
     segment_id: u64,
+    compression: Compression,
+    /// Frames of a compressed segment not written yet, compressed
+    /// together as one block when it fills or on the next flush
+    pending: Vec<u8>,
 }
 
 impl SegmentWriter {
     fn new(
-        file: File,
+        sink: Box<dyn SegmentSink>,
+        len: u64,
         encryption_service: Option<Arc<EncryptionService>>,
         segment_id: u64,
+        compression: Compression,
     ) -> Self {
-        let pos = file.metadata().map(|m| m.len()).unwrap_or(0);
         Self {
-            writer: BufWriter::new(file),
-            bytes_written: pos,
+            writer: BufWriter::new(sink),
+            bytes_written: len,
             encryption_service,
             segment_id,
+            compression,
+            pending: Vec::new(),
         }
     }
 
+    /// Append an event, returning the segment's size counting frames
+    /// still waiting to be compressed at their uncompressed size
     pub fn append_event(&mut self, event: &Event) -> Result<u64> {
         let record = FramedRecord::from_event(event.clone());
-        let mut frame = record.to_frame()?;
+        let frame = record.to_frame()?;
+        if self.compression.is_none() {
+            self.write_frame(frame)?;
+        } else {
+            frame.write_to(&mut self.pending)?;
+            if self.pending.len() >= BLOCK_SIZE {
+                self.write_block()?;
+            }
+        }
+        Ok(self.bytes_written())
+    }
 
+    /// Compress the pending frames into one frame and write it
+    fn write_block(&mut self) -> Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let block = self.compression.compress(&self.pending)?;
+        self.pending.clear();
+        self.write_frame(Frame::new(block))
+    }
+
+    fn write_frame(&mut self, mut frame: Frame) -> Result<()> {
         // Encrypt frame data if encryption service is available
         if let Some(ref encryption_service) = self.encryption_service {
             let context = format!("segment_{}", self.segment_id);
@@ -135,42 +214,74 @@ impl SegmentWriter {
 
         frame.write_to(&mut self.writer)?;
         self.bytes_written += 8 + frame.data.len() as u64;
-        Ok(self.bytes_written)
+        Ok(())
     }
 
+    /// Write out the pending block, if any, and flush
     pub fn flush(&mut self) -> Result<()> {
+        self.write_block()?;
         self.writer.flush()?;
         Ok(())
     }
 
     pub fn sync(&mut self) -> Result<()> {
         self.flush()?;
-        self.writer.get_mut().sync_all()?;
-        Ok(())
+        self.writer.get_mut().sync()
     }
 
     pub fn bytes_written(&self) -> u64 {
-        self.bytes_written
+        self.bytes_written + self.pending.len() as u64
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+}
+
+impl Drop for SegmentWriter {
+    /// Write out the pending block, as the buffered writer flushes on drop
+    fn drop(&mut self) {
+        let _ = self.write_block();
     }
 }
 
 pub struct SegmentReader {
-    reader: BufReader<File>,
+    reader: BufReader<Box<dyn SegmentSource>>,
     encryption_service: Option<Arc<EncryptionService>>,
     segment_id: u64,
+    compression: Compression,
+    /// Offset of the first frame, past any header
+    data_start: u64,
+    /// Events of the last block read that haven't been returned yet
+    block: VecDeque<Event>,
 }
 
 impl SegmentReader {
     fn new(
-        file: File,
+        source: Box<dyn SegmentSource>,
         encryption_service: Option<Arc<EncryptionService>>,
         segment_id: u64,
-    ) -> Self {
-        Self {
-            reader: BufReader::new(file),
+    ) -> Result<Self> {
+        let mut reader = BufReader::new(source);
+        let (compression, data_start) = match compression::read_header(&mut reader)? {
+            Some(compression) => (compression, HEADER_LEN),
+            None => {
+                reader.seek(SeekFrom::Start(0))?;
+                (Compression::None, 0)
+            }
+        };
+        Ok(Self {
+            reader,
             encryption_service,
             segment_id,
-        }
+            compression,
+            data_start,
+            block: VecDeque::new(),
+        })
+    }
+
+    pub fn compression(&self) -> Compression {
+        self.compression
     }
 
     pub fn read_all_events(&mut self) -> Result<Vec<Event>> {
@@ -182,6 +293,9 @@ impl SegmentReader {
     }
 
     pub fn read_next_event(&mut self) -> Result<Option<Event>> {
+        if let Some(event) = self.block.pop_front() {
+            return Ok(Some(event));
+        }
         match Frame::read_from(&mut self.reader)? {
             Some(mut frame) => {
                 // Decrypt frame data if encryption service is available
@@ -189,6 +303,10 @@ impl SegmentReader {
                     let context = format!("segment_{}", self.segment_id);
                     frame.data = encryption_service.decrypt(&frame.data, &context)?;
                 }
+                if !self.compression.is_none() {
+                    self.block = self.decode_block(&frame.data)?.into();
+                    return Ok(self.block.pop_front());
+                }
 
                 let record = FramedRecord::from_frame(&frame)?;
                 Ok(Some(record.event))
@@ -197,15 +315,48 @@ impl SegmentReader {
         }
     }
 
+    /// The events of a compressed block: the frames appended between two
+    /// flushes, compressed together
+    fn decode_block(&self, data: &[u8]) -> Result<Vec<Event>> {
+        let mut frames = Cursor::new(self.compression.decompress(data)?);
+        let mut events = Vec::new();
+        while let Some(frame) = Frame::read_from(&mut frames)? {
+            events.push(FramedRecord::from_frame(&frame)?.event);
+        }
+        Ok(events)
+    }
+
     pub fn verify_and_find_corruption(&mut self) -> Result<Option<u64>> {
-        self.reader.seek(SeekFrom::Start(0))?;
+        Ok(self.read_verified_prefix()?.1)
+    }
+
+    /// Events up to the first frame that fails verification, and that
+    /// frame's byte offset if there is one
+    pub fn read_verified_prefix(&mut self) -> Result<(Vec<Event>, Option<u64>)> {
+        let (events, end, complete) = self.read_verified(self.data_start)?;
+        Ok((events, (!complete).then_some(end)))
+    }
+
+    /// Events from the frame at byte `offset` (the first frame for 0) up
+    /// to the first that fails verification or is cut short, the offset
+    /// just past the last good one, and whether that is the end of the
+    /// segment. A segment still being written can be read on from that
+    /// offset once more of it is on disk.
+    pub fn read_verified_from(&mut self, offset: u64) -> Result<(Vec<Event>, u64, bool)> {
+        self.read_verified(offset.max(self.data_start))
+    }
+
+    fn read_verified(&mut self, start: u64) -> Result<(Vec<Event>, u64, bool)> {
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.block.clear();
+        let mut events = Vec::new();
 
         loop {
             let current_pos = self.reader.stream_position()?;
             match Frame::read_from(&mut self.reader) {
                 Ok(Some(mut frame)) => {
                     if !frame.verify() {
-                        return Ok(Some(current_pos));
+                        return Ok((events, current_pos, false));
                     }
 
                     // Try to decrypt if encryption is enabled
@@ -213,22 +364,25 @@ impl SegmentReader {
                         let context = format!("segment_{}", self.segment_id);
                         match encryption_service.decrypt(&frame.data, &context) {
                             Ok(decrypted) => frame.data = decrypted,
-                            Err(_) => return Ok(Some(current_pos)), // Decryption failure indicates corruption
+                            Err(_) => return Ok((events, current_pos, false)), // Decryption failure indicates corruption
                         }
                     }
+                    if !self.compression.is_none() {
+                        match self.decode_block(&frame.data) {
+                            Ok(block) => events.extend(block),
+                            Err(_) => return Ok((events, current_pos, false)),
+                        }
+                        continue;
+                    }
 
                     match FramedRecord::from_frame(&frame) {
-                        Ok(_) => {
-                            let _ = self.reader.stream_position()?;
-                        }
-                        Err(_) => return Ok(Some(current_pos)),
+                        Ok(record) => events.push(record.event),
+                        Err(_) => return Ok((events, current_pos, false)),
                     }
                 }
-                Ok(None) => break,
-                Err(_) => return Ok(Some(current_pos)),
+                Ok(None) => return Ok((events, current_pos, true)),
+                Err(_) => return Ok((events, current_pos, false)),
             }
         }
-
-        Ok(None)
     }
 }
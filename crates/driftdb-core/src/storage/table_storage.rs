@@ -1,29 +1,87 @@
 use fs2::FileExt;
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use parking_lot::{Mutex, RwLock};
+use rayon::prelude::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::durability::{Durability, SyncMode, SyncTarget};
 use crate::encryption::EncryptionService;
 use crate::errors::{DriftError, Result};
 use crate::events::Event;
+use crate::parallel::WorkerLease;
+use crate::query::{AsOf, WhereCondition};
 use crate::schema::Schema;
-use crate::storage::{Segment, SegmentBounds, SegmentWriter, TableMeta};
+use crate::storage::columnar::{ChunkFilterStats, ColumnarProjection};
+use crate::storage::key_filter::{KeyFilterStats, KeyFilters, DEFAULT_FALSE_POSITIVE_RATE};
+use crate::storage::{
+    Compression, HistoryFloor, LocalFileBackend, RetentionPolicy, Segment, SegmentBounds,
+    SegmentIndex, SegmentWriter, StorageBackend, StorageFormat, TableMeta,
+};
+use crate::subscription::{AppendSignal, EventSubscription};
+
+/// Present while a bulk load is unfinished
+const BULK_LOAD_MARKER: &str = "bulk_load.incomplete";
+
+/// Where `VACUUM FULL` writes the table's new segment. Not a `.seg` file,
+/// so reads don't list it until it is swapped in.
+pub const VACUUM_STAGING_FILE: &str = "vacuum.tmp";
 
 #[derive(Debug, Clone)]
 pub struct TableStats {
     pub sequence_count: u64,
     pub segment_count: u64,
     pub snapshot_count: u64,
+    pub last_compaction_sequence: u64,
+}
+
+/// What [`TableStorage::vacuum_full`] did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VacuumStats {
+    /// Live rows written to the new segment
+    pub rows: u64,
+    /// Bytes the table took on disk before and after
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl VacuumStats {
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
 }
 
 pub struct TableStorage {
     path: PathBuf,
-    schema: Schema,
+    schema: RwLock<Schema>,
     meta: Arc<RwLock<TableMeta>>,
     current_writer: Arc<RwLock<Option<SegmentWriter>>>,
     encryption_service: Option<Arc<EncryptionService>>,
+    /// Where the segments live; everything else is in `path`
+    backend: Arc<dyn StorageBackend>,
+    durability: Arc<Durability>,
+    bulk_loading: AtomicBool,
+    /// Primary keys of the live rows, kept in step with every append once
+    /// [`TableStorage::row_count`] has built it from the segments
+    live_keys: RwLock<Option<HashSet<String>>>,
+    /// The schema's segment codec, kept apart so rotating a segment
+    /// doesn't take the schema lock
+    compression: RwLock<Compression>,
+    /// Bloom filters of the primary keys in each segment, with the
+    /// schema's rate likewise kept apart (see [`crate::storage::key_filter`])
+    key_filters: Mutex<KeyFilters>,
+    /// Held shared by reads from a snapshot and the segments, and
+    /// exclusively while [`TableStorage::vacuum_full`] swaps them out
+    segments_swap: RwLock<()>,
+    /// Current rows by column for a columnar table, kept in step with
+    /// every append once [`TableStorage::scan_columnar`] has built it
+    columnar: RwLock<Option<ColumnarProjection>>,
+    /// Chunks equality scans of the projection have skipped and tested
+    chunk_filter_stats: Mutex<ChunkFilterStats>,
+    /// Raised after every append, for [`TableStorage::subscribe`]
+    appended: Arc<AppendSignal>,
     _lock_file: Option<fs::File>,
 }
 
@@ -32,6 +90,21 @@ impl TableStorage {
         base_path: P,
         schema: Schema,
         encryption_service: Option<Arc<EncryptionService>>,
+    ) -> Result<Self> {
+        Self::create_with_backend(
+            base_path,
+            schema,
+            encryption_service,
+            LocalFileBackend::shared(),
+        )
+    }
+
+    /// [`TableStorage::create`] with the segments kept in `backend`
+    pub fn create_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        schema: Schema,
+        encryption_service: Option<Arc<EncryptionService>>,
+        backend: Arc<dyn StorageBackend>,
     ) -> Result<Self> {
         let path = base_path.as_ref().join("tables").join(&schema.name);
         fs::create_dir_all(&path)?;
@@ -58,23 +131,34 @@ impl TableStorage {
         fs::create_dir_all(path.join("snapshots"))?;
         fs::create_dir_all(path.join("indexes"))?;
 
-        let segment = if let Some(ref encryption_service) = encryption_service {
-            Segment::new_with_encryption(
-                path.join("segments").join("00000001.seg"),
-                1,
-                encryption_service.clone(),
-            )
-        } else {
-            Segment::new(path.join("segments").join("00000001.seg"), 1)
-        };
+        let compression = schema.compression;
+        let mut key_filters = KeyFilters::new(schema.bloom_filter_fpp);
+        key_filters.start(1);
+        let segment = open_segment(
+            path.join("segments").join("00000001.seg"),
+            1,
+            &encryption_service,
+            &backend,
+        )
+        .with_compression(compression);
         let writer = segment.create()?;
 
         Ok(Self {
             path,
-            schema,
+            schema: RwLock::new(schema),
             meta: Arc::new(RwLock::new(meta)),
             current_writer: Arc::new(RwLock::new(Some(writer))),
             encryption_service,
+            backend,
+            durability: Durability::new(SyncMode::Full),
+            bulk_loading: AtomicBool::new(false),
+            live_keys: RwLock::new(None),
+            compression: RwLock::new(compression),
+            key_filters: Mutex::new(key_filters),
+            segments_swap: RwLock::new(()),
+            columnar: RwLock::new(None),
+            chunk_filter_stats: Mutex::default(),
+            appended: Arc::default(),
             _lock_file: Some(lock_file),
         })
     }
@@ -83,6 +167,21 @@ impl TableStorage {
         base_path: P,
         table_name: &str,
         encryption_service: Option<Arc<EncryptionService>>,
+    ) -> Result<Self> {
+        Self::open_with_backend(
+            base_path,
+            table_name,
+            encryption_service,
+            LocalFileBackend::shared(),
+        )
+    }
+
+    /// [`TableStorage::open`] for a table whose segments are in `backend`
+    pub fn open_with_backend<P: AsRef<Path>>(
+        base_path: P,
+        table_name: &str,
+        encryption_service: Option<Arc<EncryptionService>>,
+        backend: Arc<dyn StorageBackend>,
     ) -> Result<Self> {
         let path = base_path.as_ref().join("tables").join(table_name);
 
@@ -106,24 +205,35 @@ impl TableStorage {
 
         let segment_id = meta.segment_count;
         let segment_path = path.join("segments").join(format!("{:08}.seg", segment_id));
-        let segment = if let Some(ref encryption_service) = encryption_service {
-            Segment::new_with_encryption(segment_path, segment_id, encryption_service.clone())
-        } else {
-            Segment::new(segment_path, segment_id)
-        };
+        let compression = schema.compression;
+        let segment = open_segment(segment_path, segment_id, &encryption_service, &backend)
+            .with_compression(compression);
 
+        // A segment that already has events isn't filtered until it closes
+        let mut key_filters = KeyFilters::new(schema.bloom_filter_fpp);
         let writer = if segment.exists() {
             segment.open_writer()?
         } else {
+            key_filters.start(segment_id);
             segment.create()?
         };
 
         let storage = Self {
             path,
-            schema,
+            schema: RwLock::new(schema),
             meta: Arc::new(RwLock::new(meta)),
             current_writer: Arc::new(RwLock::new(Some(writer))),
             encryption_service,
+            backend,
+            durability: Durability::new(SyncMode::Full),
+            bulk_loading: AtomicBool::new(false),
+            live_keys: RwLock::new(None),
+            compression: RwLock::new(compression),
+            key_filters: Mutex::new(key_filters),
+            segments_swap: RwLock::new(()),
+            columnar: RwLock::new(None),
+            chunk_filter_stats: Mutex::default(),
+            appended: Arc::default(),
             _lock_file: Some(lock_file),
         };
 
@@ -133,6 +243,12 @@ impl TableStorage {
         Ok(storage)
     }
 
+    /// Sync writes as `durability` says rather than always fsyncing
+    pub fn with_durability(mut self, durability: Arc<Durability>) -> Self {
+        self.durability = durability;
+        self
+    }
+
     /// Ensure the segment index is built and up-to-date
     fn ensure_segment_index(&self) -> Result<()> {
         let meta = self.meta.read();
@@ -145,28 +261,27 @@ impl TableStorage {
 
     /// Scan all segments and build the sequence index
     pub fn build_segment_index(&self) -> Result<()> {
-        let segments_dir = self.path.join("segments");
+        self.index_segments(false)
+    }
 
-        let mut segment_files: Vec<_> = fs::read_dir(&segments_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s == "seg")
-                    .unwrap_or(false)
-            })
-            .collect();
+    /// Throw the segment index away and build it again from the segments
+    pub fn rebuild_segment_index(&self) -> Result<()> {
+        self.index_segments(true)
+    }
 
-        segment_files.sort_by_key(|entry| entry.path());
+    /// Record the bounds of segments the index lacks, or of every segment
+    /// `from_scratch`, with appends held off throughout
+    fn index_segments(&self, from_scratch: bool) -> Result<()> {
+        let segment_files = self.segment_files()?;
 
         let mut meta = self.meta.write();
+        if from_scratch {
+            meta.segment_index = SegmentIndex::new();
+        }
 
-        for entry in segment_files {
+        for path in segment_files {
             // Extract segment ID from filename (e.g., "00000001.seg" -> 1)
-            let segment_id = entry
-                .path()
+            let segment_id = path
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .and_then(|s| s.parse::<u64>().ok())
@@ -182,17 +297,13 @@ impl TableStorage {
             }
 
             // Read segment to get min/max sequence
-            let segment = if let Some(ref encryption_service) = self.encryption_service {
-                Segment::new_with_encryption(entry.path(), segment_id, encryption_service.clone())
-            } else {
-                Segment::new(entry.path(), segment_id)
-            };
+            let segment = self.segment(path, segment_id);
 
             if let Ok(mut reader) = segment.open_reader() {
                 let events = reader.read_all_events().unwrap_or_default();
                 if !events.is_empty() {
-                    let min_seq = events.first().map(|e| e.sequence).unwrap_or(0);
-                    let max_seq = events.last().map(|e| e.sequence).unwrap_or(0);
+                    let min_seq = events.iter().map(|e| e.sequence).min().unwrap_or(0);
+                    let max_seq = events.iter().map(|e| e.sequence).max().unwrap_or(0);
                     let event_count = events.len() as u64;
 
                     meta.segment_index.update_segment(
@@ -209,55 +320,545 @@ impl TableStorage {
         Ok(())
     }
 
-    pub fn append_event(&self, mut event: Event) -> Result<u64> {
+    pub fn append_event(&self, event: Event) -> Result<u64> {
+        self.write_event(event, true)
+    }
+
+    /// Append an event copied from another database, keeping its original
+    /// sequence (which may be 0 for rows rewritten by compaction).
+    pub fn append_existing_event(&self, event: Event) -> Result<u64> {
+        self.write_event(event, false)
+    }
+
+    /// Move the table's sequence forward to `sequence` without writing an
+    /// event, so a copied table continues numbering where its source did.
+    pub fn advance_sequence_to(&self, sequence: u64) -> Result<()> {
+        let mut meta = self.meta.write();
+        if sequence > meta.last_sequence {
+            meta.last_sequence = sequence;
+            meta.save_to_file(self.path.join("meta.json"))?;
+        }
+        Ok(())
+    }
+
+    fn write_event(&self, mut event: Event, assign_sequence: bool) -> Result<u64> {
+        if self.bulk_loading.load(Ordering::Acquire) {
+            return Err(DriftError::Other(format!(
+                "table '{}' is being bulk loaded",
+                self.schema.read().name
+            )));
+        }
+        self.schema.read().encode_enums(&mut event.payload)?;
+
         let mut meta = self.meta.write();
         let mut writer_guard = self.current_writer.write();
+        let mode = self.durability.effective_mode();
+        self.append_locked(
+            &mut meta,
+            &mut writer_guard,
+            &mut event,
+            assign_sequence,
+            mode == SyncMode::Full,
+        )?;
+        flush_writer(&mut writer_guard)?;
 
-        meta.last_sequence += 1;
-        event.sequence = meta.last_sequence;
+        meta.save_to_file(self.path.join("meta.json"))?;
+        drop(writer_guard);
+        if mode != SyncMode::Full {
+            self.durability.defer(self.current_writer.clone(), mode);
+        }
+        self.appended.notify();
+        Ok(event.sequence)
+    }
+
+    /// Append `events` under a single hold of the meta and writer locks,
+    /// with one sync and one meta save for the lot. Every event's enum
+    /// values are encoded before any is written, so one that doesn't fit
+    /// rejects the batch. Sequences are assigned in place; returns the
+    /// last one.
+    pub fn append_events(&self, events: &mut [Event]) -> Result<u64> {
+        if self.bulk_loading.load(Ordering::Acquire) {
+            return Err(DriftError::Other(format!(
+                "table '{}' is being bulk loaded",
+                self.schema.read().name
+            )));
+        }
+        {
+            let schema = self.schema.read();
+            for event in events.iter_mut() {
+                schema.encode_enums(&mut event.payload)?;
+            }
+        }
+        if events.is_empty() {
+            return Ok(self.last_sequence());
+        }
+
+        let mut meta = self.meta.write();
+        let mut writer_guard = self.current_writer.write();
+        let mode = self.durability.effective_mode();
+        for event in events.iter_mut() {
+            self.append_locked(&mut meta, &mut writer_guard, event, true, false)?;
+        }
+        if mode == SyncMode::Full {
+            if let Some(writer) = writer_guard.as_mut() {
+                writer.sync()?;
+            }
+        } else {
+            flush_writer(&mut writer_guard)?;
+        }
+
+        meta.save_to_file(self.path.join("meta.json"))?;
+        drop(writer_guard);
+        if mode != SyncMode::Full {
+            self.durability.defer(self.current_writer.clone(), mode);
+        }
+        self.appended.notify();
+        Ok(meta.last_sequence)
+    }
+
+    /// Sequence bounds recorded for each segment
+    pub fn segment_bounds(&self) -> std::collections::BTreeMap<u64, SegmentBounds> {
+        self.meta.read().segment_index.segments.clone()
+    }
+
+    /// Append one event under the meta and writer locks, rotating the
+    /// segment past the threshold. The caller flushes the writer and saves
+    /// meta.
+    fn append_locked(
+        &self,
+        meta: &mut TableMeta,
+        writer_guard: &mut Option<SegmentWriter>,
+        event: &mut Event,
+        assign_sequence: bool,
+        sync: bool,
+    ) -> Result<()> {
+        if assign_sequence {
+            meta.last_sequence += 1;
+            event.sequence = meta.last_sequence;
+        } else {
+            meta.last_sequence = meta.last_sequence.max(event.sequence);
+        }
 
         let current_segment_id = meta.segment_count;
+        let Some(writer) = writer_guard.as_mut() else {
+            return Err(DriftError::Other("No writer available".into()));
+        };
+        let bytes_written = writer.append_event(event)?;
+
+        let rotating = bytes_written > self.segment_rotation_threshold();
+        // A segment being rotated away is synced now; a deferred fsync
+        // would reach its successor instead. Otherwise the caller flushes
+        // once it has appended all it will, so a compressed segment
+        // compresses a batch as one block.
+        if sync || rotating {
+            writer.sync()?;
+        }
 
-        if let Some(writer) = writer_guard.as_mut() {
-            let bytes_written = writer.append_event(&event)?;
+        if let Some(live) = self.live_keys.write().as_mut() {
+            track_live_key(live, event);
+        }
+        self.key_filters
+            .lock()
+            .record(current_segment_id, &event.primary_key);
+        if let Some(projection) = self.columnar.write().as_mut() {
+            // Events arrive with enum columns encoded, as they are written
+            let mut event = event.clone();
+            self.decode_enums(std::slice::from_mut(&mut event));
+            projection.apply(&event);
+        }
+
+        // Update segment index bounds for current segment
+        let bounds = meta
+            .segment_index
+            .segments
+            .entry(current_segment_id)
+            .or_insert_with(|| SegmentBounds::new(event.sequence, event.sequence, 0));
+        // Widen the bounds either way: a copied event can carry a sequence
+        // below the ones already in the segment, and bounds that don't
+        // cover it would let reads skip the segment
+        bounds.min_sequence = bounds.min_sequence.min(event.sequence);
+        bounds.max_sequence = bounds.max_sequence.max(event.sequence);
+        bounds.event_count += 1;
+
+        if rotating {
+            // Rotate to new segment
+            meta.segment_count += 1;
+            let new_segment_path = self
+                .path
+                .join("segments")
+                .join(format!("{:08}.seg", meta.segment_count));
+            let new_segment = self.segment(new_segment_path, meta.segment_count);
+            *writer_guard = Some(new_segment.create()?);
+            self.key_filters.lock().start(meta.segment_count);
+        }
+        Ok(())
+    }
 
-            // Always sync after writing to ensure data is persisted
+    /// Close the active segment and start an empty one, so everything
+    /// written so far is in closed segments that can change tier. Does
+    /// nothing while the active segment is empty.
+    pub fn seal_active_segment(&self) -> Result<()> {
+        let mut meta = self.meta.write();
+        let mut writer_guard = self.current_writer.write();
+        if !meta
+            .segment_index
+            .segments
+            .contains_key(&meta.segment_count)
+        {
+            return Ok(());
+        }
+        if let Some(writer) = writer_guard.as_mut() {
             writer.sync()?;
+        }
+        meta.segment_count += 1;
+        let new_segment_path = self
+            .path
+            .join("segments")
+            .join(format!("{:08}.seg", meta.segment_count));
+        let new_segment = self.segment(new_segment_path, meta.segment_count);
+        *writer_guard = Some(new_segment.create()?);
+        self.key_filters.lock().start(meta.segment_count);
+        meta.save_to_file(self.path.join("meta.json"))?;
+        Ok(())
+    }
 
-            // Update segment index bounds for current segment
-            let bounds = meta
-                .segment_index
-                .segments
-                .entry(current_segment_id)
-                .or_insert_with(|| SegmentBounds::new(event.sequence, event.sequence, 0));
-            // Update max_sequence and event_count
-            bounds.max_sequence = event.sequence;
-            bounds.event_count += 1;
-
-            if bytes_written > self.segment_rotation_threshold() {
-                // Rotate to new segment
-                meta.segment_count += 1;
-                let new_segment_path = self
-                    .path
-                    .join("segments")
-                    .join(format!("{:08}.seg", meta.segment_count));
-                let new_segment = if let Some(ref encryption_service) = self.encryption_service {
-                    Segment::new_with_encryption(
-                        new_segment_path,
-                        meta.segment_count,
-                        encryption_service.clone(),
-                    )
-                } else {
-                    Segment::new(new_segment_path, meta.segment_count)
-                };
-                *writer_guard = Some(new_segment.create()?);
+    /// Start loading an empty table straight into its segments. Until
+    /// [`TableStorage::finish_bulk_load`] the table is marked incomplete,
+    /// and a table still marked when next opened has the whole load
+    /// discarded.
+    pub fn begin_bulk_load(&self) -> Result<()> {
+        let meta = self.meta.read();
+        if meta.last_sequence > 0 {
+            return Err(DriftError::Other(format!(
+                "bulk load needs an empty table, but '{}' has {} events",
+                self.schema.read().name,
+                meta.last_sequence
+            )));
+        }
+        if self.bulk_loading.swap(true, Ordering::AcqRel) {
+            return Err(DriftError::Other(format!(
+                "table '{}' is already being bulk loaded",
+                self.schema.read().name
+            )));
+        }
+        let marker = fs::File::create(self.path.join(BULK_LOAD_MARKER))?;
+        marker.sync_all()?;
+        Ok(())
+    }
+
+    pub fn is_bulk_loading(&self) -> bool {
+        self.bulk_loading.load(Ordering::Acquire)
+    }
+
+    /// Append a batch during a bulk load. Nothing is fsynced until
+    /// [`TableStorage::finish_bulk_load`], and meta is saved once per
+    /// batch. Returns the last sequence written.
+    pub fn append_bulk(&self, events: Vec<Event>) -> Result<u64> {
+        if !self.is_bulk_loading() {
+            return Err(DriftError::Other(format!(
+                "table '{}' has no bulk load in progress",
+                self.schema.read().name
+            )));
+        }
+        let schema = self.schema.read();
+        let mut meta = self.meta.write();
+        let mut writer_guard = self.current_writer.write();
+        for mut event in events {
+            schema.encode_enums(&mut event.payload)?;
+            self.append_locked(&mut meta, &mut writer_guard, &mut event, true, false)?;
+        }
+        flush_writer(&mut writer_guard)?;
+        meta.save_to_file(self.path.join("meta.json"))?;
+        self.appended.notify();
+        Ok(meta.last_sequence)
+    }
+
+    /// Fsync everything the bulk load wrote and clear the incomplete mark
+    pub fn finish_bulk_load(&self) -> Result<()> {
+        if !self.is_bulk_loading() {
+            return Err(DriftError::Other(format!(
+                "table '{}' has no bulk load in progress",
+                self.schema.read().name
+            )));
+        }
+        self.sync()?;
+        self.meta.read().save_to_file(self.path.join("meta.json"))?;
+        fs::remove_file(self.path.join(BULK_LOAD_MARKER))?;
+        self.bulk_loading.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// Throw away a bulk load that never finished: every segment and
+    /// snapshot goes and the table is empty again. Returns whether there
+    /// was one.
+    pub fn discard_incomplete_bulk_load(&self) -> Result<bool> {
+        let marker = self.path.join(BULK_LOAD_MARKER);
+        if !marker.exists() {
+            return Ok(false);
+        }
+
+        let mut meta = self.meta.write();
+        let mut writer_guard = self.current_writer.write();
+        *writer_guard = None;
+        for path in self.backend.list(&self.path.join("segments"))? {
+            self.backend.delete(&path)?;
+        }
+        for entry in fs::read_dir(self.path.join("snapshots"))? {
+            let path = entry?.path();
+            if path.is_file() {
+                fs::remove_file(path)?;
             }
+        }
+
+        *meta = TableMeta::default();
+        meta.save_to_file(self.path.join("meta.json"))?;
+        *self.live_keys.write() = None;
+        *self.columnar.write() = None;
+        let segment_path = self.path.join("segments").join("00000001.seg");
+        *writer_guard = Some(self.segment(segment_path, 1).create()?);
+        {
+            let mut key_filters = self.key_filters.lock();
+            let fpp = self.schema.read().bloom_filter_fpp;
+            key_filters.reset(fpp);
+            key_filters.start(1);
+        }
+
+        fs::remove_file(marker)?;
+        Ok(true)
+    }
+
+    /// Pick up a segment directory rewritten by compaction up to
+    /// `compacted_through`. The writer's segment may have been replaced, so
+    /// appends move to the newest segment on disk and the segment index is
+    /// rebuilt from scratch.
+    pub fn reopen_after_compaction(&self, compacted_through: u64) -> Result<()> {
+        let segments_dir = self.path.join("segments");
+        let last_segment = self
+            .segment_files()?
+            .iter()
+            .filter_map(|path| {
+                path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<u64>().ok())
+            })
+            .max()
+            .unwrap_or(1)
+            .max(1);
+
+        {
+            let mut meta = self.meta.write();
+            let mut writer_guard = self.current_writer.write();
+
+            meta.segment_count = last_segment;
+            meta.last_compaction_sequence = compacted_through;
+            meta.segment_index = SegmentIndex::new();
+
+            let segment_path = segments_dir.join(format!("{:08}.seg", last_segment));
+            let segment = self.segment(segment_path, last_segment);
+            let mut key_filters = self.key_filters.lock();
+            key_filters.reset(self.schema.read().bloom_filter_fpp);
+            *writer_guard = Some(if segment.exists() {
+                segment.open_writer()?
+            } else {
+                key_filters.start(last_segment);
+                segment.create()?
+            });
+        }
+
+        self.build_segment_index()
+    }
+
+    /// Rewrite the table's segments as one segment of its current rows,
+    /// dropping superseded versions, deleted rows and snapshots, so the
+    /// space they took is released. History before the rewrite is gone, as
+    /// after a compaction. A table with a retention policy keeps the
+    /// history the policy does and folds only what is older.
+    pub fn vacuum_full(&self) -> Result<VacuumStats> {
+        let policy = self.schema.read().retention;
+        let fold_through = if policy.is_unbounded() {
+            self.last_sequence()
         } else {
-            return Err(DriftError::Other("No writer available".into()));
+            let floor = self.history_floor().map_or(0, |floor| floor.sequence);
+            self.retention_prune_point(&policy)?.unwrap_or(0).max(floor)
+        };
+        self.rewrite_segments(fold_through)
+    }
+
+    /// Fold the history the table's retention policy no longer keeps into
+    /// the state at the oldest point it does, rewriting the segments as
+    /// [`TableStorage::vacuum_full`] does. `None` when there is no policy
+    /// or nothing more falls outside it.
+    pub fn prune_history(&self) -> Result<Option<VacuumStats>> {
+        let policy = self.schema.read().retention;
+        if policy.is_unbounded() {
+            return Ok(None);
+        }
+        let floor = self.history_floor().map_or(0, |floor| floor.sequence);
+        match self.retention_prune_point(&policy)? {
+            Some(through) if through > floor => self.rewrite_segments(through).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Keep as much history as `policy` says from now on
+    pub fn set_retention(&self, policy: RetentionPolicy) -> Result<()> {
+        let mut schema = self.schema.read().clone();
+        schema.retention = policy;
+        self.update_schema(schema)
+    }
+
+    /// Where the table's history begins, if it has been folded
+    pub fn history_floor(&self) -> Option<HistoryFloor> {
+        self.meta.read().history_floor
+    }
+
+    /// The latest sequence whose history `policy` lets go
+    fn retention_prune_point(&self, policy: &RetentionPolicy) -> Result<Option<u64>> {
+        let last_sequence = self.last_sequence();
+        let events = match policy.history_for_secs {
+            Some(_) => {
+                let floor = self.history_floor().map_or(0, |floor| floor.sequence);
+                self.read_events_after_sequence(floor)?
+            }
+            None => Vec::new(),
+        };
+        Ok(policy.prune_through(last_sequence, time::OffsetDateTime::now_utc(), &events))
+    }
+
+    /// Rewrite the segments as the state at `fold_through` followed by
+    /// every event after it, and drop the snapshots before it.
+    ///
+    /// The new segment is written beside the old ones, which reads keep
+    /// using until the swap. Events appended meanwhile are copied over
+    /// before it, and the swap holds reads and appends off while the new
+    /// segment takes the next segment id and the old segments and
+    /// snapshots are deleted.
+    fn rewrite_segments(&self, fold_through: u64) -> Result<VacuumStats> {
+        let bytes_before = self.calculate_size_bytes()?;
+        let staging_path = self.path.join("segments").join(VACUUM_STAGING_FILE);
+        let mut staging = self.segment(staging_path.clone(), 0).create()?;
+
+        // The folded rows take the time of the event they stand at, so
+        // reads as of a time still find them
+        let folded_at = match fold_through {
+            0 => None,
+            sequence => self
+                .read_events_after_sequence(sequence - 1)?
+                .into_iter()
+                .find(|event| event.sequence == sequence)
+                .map(|event| event.timestamp),
+        };
+        let state = self.replay_events_to(Some(fold_through))?;
+        let table_name = self.schema.read().name.clone();
+        for (key, row) in state {
+            let mut event = Event::new_insert(table_name.clone(), serde_json::from_str(&key)?, row);
+            event.sequence = fold_through;
+            if let Some(timestamp) = folded_at {
+                event.timestamp = timestamp;
+            }
+            self.schema.read().encode_enums(&mut event.payload)?;
+            staging.append_event(&event)?;
         }
 
+        // Copy the events after the folded state, until nothing more was
+        // appended. Reads take the swap lock before meta, so it is taken
+        // first here too.
+        let mut copied_through = fold_through;
+        let (swap, mut meta, mut writer_guard) = loop {
+            for mut event in self.read_events_after_sequence(copied_through)? {
+                self.schema.read().encode_enums(&mut event.payload)?;
+                staging.append_event(&event)?;
+                copied_through = copied_through.max(event.sequence);
+            }
+            let swap = self.segments_swap.write();
+            let meta = self.meta.write();
+            if meta.last_sequence <= copied_through {
+                break (swap, meta, self.current_writer.write());
+            }
+        };
+        staging.sync()?;
+        drop(staging);
+
+        let old_segments = self.segment_files()?;
+        let segment_id = meta.segment_count + 1;
+        let segment_path = self
+            .path
+            .join("segments")
+            .join(format!("{:08}.seg", segment_id));
+        *writer_guard = None;
+        self.backend.rename(&staging_path, &segment_path)?;
+        for path in old_segments {
+            self.backend.delete(&path)?;
+        }
+        for entry in fs::read_dir(self.path.join("snapshots"))? {
+            let path = entry?.path();
+            let sequence = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok());
+            if path.is_file() && sequence.is_none_or(|sequence| sequence < fold_through) {
+                fs::remove_file(path)?;
+            }
+        }
+
+        meta.segment_count = segment_id;
+        meta.last_compaction_sequence = fold_through;
+        if let Some(timestamp) = folded_at {
+            if meta
+                .history_floor
+                .is_none_or(|floor| floor.sequence < fold_through)
+            {
+                meta.history_floor = Some(HistoryFloor {
+                    sequence: fold_through,
+                    timestamp,
+                });
+            }
+        }
+        meta.segment_index = SegmentIndex::new();
+        *writer_guard = Some(self.segment(segment_path, segment_id).open_writer()?);
+        // The new segment has events, so it isn't filtered until it closes
+        self.key_filters
+            .lock()
+            .reset(self.schema.read().bloom_filter_fpp);
         meta.save_to_file(self.path.join("meta.json"))?;
-        Ok(event.sequence)
+        drop(writer_guard);
+        drop(meta);
+        drop(swap);
+        self.build_segment_index()?;
+
+        Ok(VacuumStats {
+            rows: self.row_count()? as u64,
+            bytes_before,
+            bytes_after: self.calculate_size_bytes()?,
+        })
+    }
+
+    /// Fails for a read as of `sequence` before the table's history floor
+    fn check_history(&self, sequence: Option<u64>) -> Result<()> {
+        if let (Some(sequence), Some(floor)) = (sequence, self.history_floor()) {
+            floor.check_sequence(&self.schema.read().name, sequence)?;
+        }
+        Ok(())
+    }
+
+    /// The sequence a read `as_of` reads at (`None` = current): for a time,
+    /// the last event at or before it. A time before the table's history
+    /// floor fails.
+    pub fn as_of_sequence(&self, as_of: Option<&AsOf>) -> Result<Option<u64>> {
+        Ok(match as_of {
+            Some(AsOf::Sequence(seq)) => Some(*seq),
+            Some(AsOf::Timestamp(ts)) => {
+                if let Some(floor) = self.history_floor() {
+                    floor.check_timestamp(&self.schema.read().name, *ts)?;
+                }
+                self.read_all_events()?
+                    .iter()
+                    .filter(|e| e.timestamp <= *ts)
+                    .map(|e| e.sequence)
+                    .max()
+            }
+            Some(AsOf::Now) | None => None,
+        })
     }
 
     pub fn flush(&self) -> Result<()> {
@@ -281,27 +882,13 @@ impl TableStorage {
     /// Read events with an optional limit to prevent unbounded memory usage
     /// This is the safe version that should be used in production
     pub fn read_events_with_limit(&self, max_events: Option<usize>) -> Result<Vec<Event>> {
+        let _swap = self.segments_swap.read_recursive();
         const DEFAULT_MAX_EVENTS: usize = 1_000_000; // 1M events max by default
         let limit = max_events.unwrap_or(DEFAULT_MAX_EVENTS);
 
         let mut all_events = Vec::new();
-        let segments_dir = self.path.join("segments");
-
-        let mut segment_files: Vec<_> = fs::read_dir(&segments_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s == "seg")
-                    .unwrap_or(false)
-            })
-            .collect();
 
-        segment_files.sort_by_key(|entry| entry.path());
-
-        for entry in segment_files {
+        for path in self.segment_files()? {
             if all_events.len() >= limit {
                 tracing::warn!(
                     "Event limit reached ({} events). Consider using snapshots or pagination.",
@@ -313,13 +900,9 @@ impl TableStorage {
                 )));
             }
 
-            let segment = if let Some(ref encryption_service) = self.encryption_service {
-                Segment::new_with_encryption(entry.path(), 0, encryption_service.clone())
-            } else {
-                Segment::new(entry.path(), 0)
-            };
-            let mut reader = segment.open_reader()?;
-            let segment_events = reader.read_all_events()?;
+            let mut reader = self.segment_at(path).open_reader()?;
+            let mut segment_events = reader.read_all_events()?;
+            self.decode_enums(&mut segment_events);
 
             // Check if adding these events would exceed the limit
             if all_events.len() + segment_events.len() > limit {
@@ -341,10 +924,261 @@ impl TableStorage {
         Ok(all_events)
     }
 
+    /// A segment file of this table, decrypted the way reads decrypt it
+    pub fn segment_at(&self, path: PathBuf) -> Segment {
+        self.segment(path, 0)
+    }
+
+    /// Segment `id` at `path` in this table's backend, compressed with the
+    /// table's codec if it is created
+    fn segment(&self, path: PathBuf, id: u64) -> Segment {
+        open_segment(path, id, &self.encryption_service, &self.backend)
+            .with_compression(self.compression())
+    }
+
+    /// Codec for segments written from now on
+    pub fn compression(&self) -> Compression {
+        *self.compression.read()
+    }
+
+    /// Compress segments written from now on with `compression`. The
+    /// active segment is closed so new events use the codec straight
+    /// away; older segments keep theirs until a compaction rewrites them.
+    pub fn set_compression(&self, compression: Compression) -> Result<()> {
+        let mut schema = self.schema.read().clone();
+        schema.compression = compression;
+        self.update_schema(schema)?;
+
+        let mut meta = self.meta.write();
+        let mut writer_guard = self.current_writer.write();
+        let Some(writer) = writer_guard.as_mut() else {
+            return Ok(());
+        };
+        if writer.compression() == compression {
+            return Ok(());
+        }
+        writer.sync()?;
+        meta.segment_count += 1;
+        let segment_path = self
+            .path
+            .join("segments")
+            .join(format!("{:08}.seg", meta.segment_count));
+        *writer_guard = Some(self.segment(segment_path, meta.segment_count).create()?);
+        self.key_filters.lock().start(meta.segment_count);
+        meta.save_to_file(self.path.join("meta.json"))?;
+        Ok(())
+    }
+
+    /// Filter each segment's primary keys at false-positive rate `fpp`
+    /// from now on, or stop with `None`. Filters built so far are thrown
+    /// away; an active segment that already has events is read by every
+    /// lookup until it closes.
+    pub fn set_key_filter(&self, fpp: Option<f64>) -> Result<()> {
+        let mut schema = self.schema.read().clone();
+        schema.bloom_filter_fpp = fpp;
+        self.update_schema(schema)?;
+
+        // Holding meta keeps appends out until the active segment's
+        // filter is in place
+        let meta = self.meta.read();
+        let mut key_filters = self.key_filters.lock();
+        key_filters.reset(fpp);
+        if !meta
+            .segment_index
+            .segments
+            .contains_key(&meta.segment_count)
+        {
+            key_filters.start(meta.segment_count);
+        }
+        Ok(())
+    }
+
+    /// Lay the table's current rows out as `format` for scans from now on.
+    /// A columnar projection is built by the next scan.
+    pub fn set_storage_format(&self, format: StorageFormat) -> Result<()> {
+        let mut schema = self.schema.read().clone();
+        schema.storage = format;
+        self.update_schema(schema)
+    }
+
+    /// Keep per-chunk bloom filters of `columns` in the columnar
+    /// projection from its next build on
+    pub fn set_bloom_filter_columns(&self, columns: BTreeSet<String>) -> Result<()> {
+        let mut schema = self.schema.read().clone();
+        schema.bloom_filter_columns = columns;
+        self.update_schema(schema)
+    }
+
+    /// Current rows of a columnar table matching every condition, at most
+    /// `limit` of them; `None` for a row table. The first scan builds the
+    /// projection from the log.
+    pub fn scan_columnar(
+        &self,
+        conditions: &[WhereCondition],
+        limit: Option<usize>,
+    ) -> Result<Option<Vec<serde_json::Value>>> {
+        if self.schema.read().storage.is_row() {
+            return Ok(None);
+        }
+        let mut stats = ChunkFilterStats::default();
+        if let Some(projection) = self.columnar.read().as_ref() {
+            let rows = projection.scan(conditions, limit, &mut stats);
+            self.chunk_filter_stats.lock().add(stats);
+            return Ok(Some(rows));
+        }
+        let (fpp, filtered) = {
+            let schema = self.schema.read();
+            (
+                schema
+                    .bloom_filter_fpp
+                    .unwrap_or(DEFAULT_FALSE_POSITIVE_RATE),
+                schema.bloom_filter_columns.clone(),
+            )
+        };
+        loop {
+            let sequence = self.last_sequence();
+            let projection =
+                ColumnarProjection::from_rows(self.reconstruct_state_at(None)?, fpp, &filtered);
+            // As in `row_count`: appends hold the meta lock
+            let meta = self.meta.write();
+            if meta.last_sequence != sequence {
+                continue;
+            }
+            let rows = projection.scan(conditions, limit, &mut stats);
+            self.chunk_filter_stats.lock().add(stats);
+            *self.columnar.write() = Some(projection);
+            return Ok(Some(rows));
+        }
+    }
+
+    /// Segments primary key lookups have skipped and read
+    pub fn key_filter_stats(&self) -> KeyFilterStats {
+        self.key_filters.lock().stats()
+    }
+
+    /// Chunks of the columnar projection equality scans have skipped and
+    /// tested
+    pub fn chunk_filter_stats(&self) -> ChunkFilterStats {
+        *self.chunk_filter_stats.lock()
+    }
+
+    /// The backend holding this table's segments
+    pub fn backend(&self) -> Arc<dyn StorageBackend> {
+        self.backend.clone()
+    }
+
+    /// Paths of the table's segment files, in order
+    pub fn segment_files(&self) -> Result<Vec<PathBuf>> {
+        Ok(self
+            .backend
+            .list(&self.path.join("segments"))?
+            .into_iter()
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("seg"))
+            .collect())
+    }
+
+    /// Rows as of `sequence` (`None` = current), presented through the
+    /// schema's column changes as they stood at that point.
     pub fn reconstruct_state_at(
         &self,
         sequence: Option<u64>,
     ) -> Result<HashMap<String, serde_json::Value>> {
+        self.check_history(sequence)?;
+        let mut state = self.replay_events_to(sequence)?;
+        let schema = self.schema.read();
+        if !schema.changes.is_empty() {
+            for row in state.values_mut() {
+                schema.evolve_row(row, sequence);
+            }
+        }
+        Ok(state)
+    }
+
+    /// The row with primary key `key` as of `sequence` (`None` = current),
+    /// if there is one, reading only the segments whose key filter might
+    /// hold it. `None` when the table has no key filters.
+    pub fn rows_with_key_at(
+        &self,
+        key: &serde_json::Value,
+        sequence: Option<u64>,
+    ) -> Result<Option<Vec<serde_json::Value>>> {
+        if !self.key_filters.lock().enabled() {
+            return Ok(None);
+        }
+        self.check_history(sequence)?;
+        let _swap = self.segments_swap.read_recursive();
+        let key_string = key.to_string();
+        let target_seq = sequence.unwrap_or(u64::MAX);
+
+        let snapshot_manager = crate::snapshot::SnapshotManager::new(&self.path);
+        let mut state = HashMap::new();
+        let mut after_seq = 0;
+        if let Ok(Some(snapshot)) = snapshot_manager.find_latest_before(target_seq) {
+            if let Some(row) = snapshot.state.get(&key_string) {
+                if let Ok(row) = serde_json::from_str(row) {
+                    state.insert(key_string.clone(), row);
+                }
+            }
+            after_seq = snapshot.sequence;
+        }
+
+        let (bounds, active) = {
+            let meta = self.meta.read();
+            (meta.segment_index.segments.clone(), meta.segment_count)
+        };
+        for (id, path) in self.segment_paths_from(0)? {
+            if let Some(bounds) = bounds.get(&id) {
+                if !bounds.contains_events_after(after_seq)
+                    || !bounds.contains_events_at_or_before(target_seq)
+                {
+                    continue;
+                }
+            }
+            let generation = {
+                let mut key_filters = self.key_filters.lock();
+                match key_filters.might_contain(id, &key_string) {
+                    Some(false) => continue,
+                    Some(true) => None,
+                    None => {
+                        key_filters.read_unfiltered();
+                        Some(key_filters.generation())
+                    }
+                }
+            };
+
+            let mut events = self.segment(path, id).open_reader()?.read_all_events()?;
+            // Closed segments don't change, so one read builds their filter
+            if let Some(generation) = generation.filter(|_| id != 0 && id < active) {
+                let mut key_filters = self.key_filters.lock();
+                if let Some(filter) = key_filters.build(&events) {
+                    key_filters.insert_built(generation, id, filter);
+                }
+            }
+            events.retain(|event| {
+                event.sequence > after_seq
+                    && event.sequence <= target_seq
+                    && event.primary_key == *key
+            });
+            self.decode_enums(&mut events);
+            for event in events {
+                apply_event(&mut state, event);
+            }
+        }
+
+        let schema = self.schema.read();
+        if !schema.changes.is_empty() {
+            for row in state.values_mut() {
+                schema.evolve_row(row, sequence);
+            }
+        }
+        Ok(Some(state.into_values().collect()))
+    }
+
+    fn replay_events_to(
+        &self,
+        sequence: Option<u64>,
+    ) -> Result<HashMap<String, serde_json::Value>> {
+        let _swap = self.segments_swap.read_recursive();
         let target_seq = sequence.unwrap_or(u64::MAX);
 
         // OPTIMIZATION: Try to use a snapshot first
@@ -366,27 +1200,7 @@ impl TableStorage {
                     break;
                 }
 
-                match event.event_type {
-                    crate::events::EventType::Insert => {
-                        state.insert(event.primary_key.to_string(), event.payload);
-                    }
-                    crate::events::EventType::Patch => {
-                        if let Some(existing) = state.get_mut(&event.primary_key.to_string()) {
-                            if let (
-                                serde_json::Value::Object(existing_map),
-                                serde_json::Value::Object(patch_map),
-                            ) = (existing, &event.payload)
-                            {
-                                for (key, value) in patch_map {
-                                    existing_map.insert(key.clone(), value.clone());
-                                }
-                            }
-                        }
-                    }
-                    crate::events::EventType::SoftDelete => {
-                        state.remove(&event.primary_key.to_string());
-                    }
-                }
+                apply_event(&mut state, event);
             }
 
             return Ok(state);
@@ -401,35 +1215,144 @@ impl TableStorage {
                 break;
             }
 
-            match event.event_type {
-                crate::events::EventType::Insert => {
-                    state.insert(event.primary_key.to_string(), event.payload);
-                }
-                crate::events::EventType::Patch => {
-                    if let Some(existing) = state.get_mut(&event.primary_key.to_string()) {
-                        if let (
-                            serde_json::Value::Object(existing_map),
-                            serde_json::Value::Object(patch_map),
-                        ) = (existing, &event.payload)
-                        {
-                            for (key, value) in patch_map {
-                                existing_map.insert(key.clone(), value.clone());
-                            }
-                        }
+            apply_event(&mut state, event);
+        }
+
+        Ok(state)
+    }
+
+    /// Rows as of `sequence` (`None` = current) that pass `filter`, with
+    /// the replay split across the workers of `lease`. Segments are decoded
+    /// in parallel, then events are partitioned by primary key so each
+    /// worker folds every event of the rows it owns, in sequence order, and
+    /// filters them. Rows come back in no particular order.
+    pub fn parallel_scan<F>(
+        &self,
+        sequence: Option<u64>,
+        lease: &WorkerLease<'_>,
+        filter: F,
+    ) -> Result<Vec<serde_json::Value>>
+    where
+        F: Fn(&serde_json::Value) -> bool + Sync,
+    {
+        let _swap = self.segments_swap.read_recursive();
+        let target_seq = sequence.unwrap_or(u64::MAX);
+        let workers = lease.workers().max(1);
+
+        let snapshot_manager = crate::snapshot::SnapshotManager::new(&self.path);
+        let (base, after_seq) = match snapshot_manager.find_latest_before(target_seq) {
+            Ok(Some(snapshot)) => (snapshot.state, snapshot.sequence),
+            _ => (HashMap::new(), 0),
+        };
+
+        let start_segment = {
+            let meta = self.meta.read();
+            if after_seq == 0 || meta.segment_index.segments.is_empty() {
+                Some(0)
+            } else {
+                meta.segment_index.find_first_segment_after(after_seq)
+            }
+        };
+        let segments = match start_segment {
+            Some(start) => self.segment_paths_from(start)?,
+            None => Vec::new(),
+        };
+
+        // Decode segments in order-preserving chunks, one per worker
+        let chunk_size = segments.len().div_ceil(workers).max(1);
+        let decoded: Vec<Vec<Event>> = lease.install(|| {
+            segments
+                .par_chunks(chunk_size)
+                .map(|chunk| -> Result<Vec<Event>> {
+                    let mut events = Vec::new();
+                    for (segment_id, path) in chunk {
+                        let segment = self.segment(path.clone(), *segment_id);
+                        events.extend(segment.open_reader()?.read_all_events()?);
                     }
-                }
-                crate::events::EventType::SoftDelete => {
-                    state.remove(&event.primary_key.to_string());
-                }
+                    Ok(events)
+                })
+                .collect::<Result<Vec<_>>>()
+        })?;
+
+        // Every event for a key lands in the same partition, in order
+        let mut partitions: Vec<(HashMap<String, serde_json::Value>, Vec<Event>)> =
+            (0..workers).map(|_| Default::default()).collect();
+        for (pk, row) in base {
+            if let Ok(row) = serde_json::from_str(&row) {
+                partitions[partition_of(&pk, workers)].0.insert(pk, row);
+            }
+        }
+        for event in decoded.into_iter().flatten() {
+            if event.sequence <= after_seq || event.sequence > target_seq {
+                continue;
             }
+            let part = partition_of(&event.primary_key.to_string(), workers);
+            partitions[part].1.push(event);
         }
 
-        Ok(state)
+        let schema = self.schema.read();
+        let schema = &*schema;
+        let rows: Vec<Vec<serde_json::Value>> = lease.install(|| {
+            partitions
+                .into_par_iter()
+                .map(|(mut state, mut events)| {
+                    if !schema.enums.is_empty() {
+                        for event in &mut events {
+                            schema.decode_enums(&mut event.payload);
+                        }
+                    }
+                    for event in events {
+                        apply_event(&mut state, event);
+                    }
+                    state
+                        .into_values()
+                        .filter_map(|mut row| {
+                            if !schema.changes.is_empty() {
+                                schema.evolve_row(&mut row, sequence);
+                            }
+                            filter(&row).then_some(row)
+                        })
+                        .collect()
+                })
+                .collect()
+        });
+
+        Ok(rows.into_iter().flatten().collect())
+    }
+
+    /// Segment files with an id of at least `start`, in order
+    fn segment_paths_from(&self, start: u64) -> Result<Vec<(u64, PathBuf)>> {
+        let segments: Vec<(u64, PathBuf)> = self
+            .segment_files()?
+            .into_iter()
+            .map(|path| {
+                let id = path
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .unwrap_or(0);
+                (id, path)
+            })
+            .filter(|(id, _)| *id >= start)
+            .collect();
+        Ok(segments)
+    }
+
+    /// Present enum columns of events read from segments as labels
+    fn decode_enums(&self, events: &mut [Event]) {
+        let schema = self.schema.read();
+        if schema.enums.is_empty() {
+            return;
+        }
+        for event in events {
+            schema.decode_enums(&mut event.payload);
+        }
     }
 
     /// Read only events after a specific sequence number
     /// Uses the segment index to skip segments that don't contain relevant events
     pub fn read_events_after_sequence(&self, after_seq: u64) -> Result<Vec<Event>> {
+        let _swap = self.segments_swap.read_recursive();
         let meta = self.meta.read();
 
         // If segment index is available and populated, use optimized path
@@ -462,28 +1385,11 @@ impl TableStorage {
         start_segment_id: u64,
         after_seq: u64,
     ) -> Result<Vec<Event>> {
-        let segments_dir = self.path.join("segments");
         let mut all_events = Vec::new();
 
-        // Get segment files, sorted by name
-        let mut segment_files: Vec<_> = fs::read_dir(&segments_dir)?
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .and_then(|s| s.to_str())
-                    .map(|s| s == "seg")
-                    .unwrap_or(false)
-            })
-            .collect();
-
-        segment_files.sort_by_key(|entry| entry.path());
-
-        for entry in segment_files {
+        for path in self.segment_files()? {
             // Extract segment ID from filename
-            let segment_id = entry
-                .path()
+            let segment_id = path
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .and_then(|s| s.parse::<u64>().ok())
@@ -494,14 +1400,11 @@ impl TableStorage {
                 continue;
             }
 
-            let segment = if let Some(ref encryption_service) = self.encryption_service {
-                Segment::new_with_encryption(entry.path(), segment_id, encryption_service.clone())
-            } else {
-                Segment::new(entry.path(), segment_id)
-            };
+            let segment = self.segment(path, segment_id);
 
             let mut reader = segment.open_reader()?;
-            let segment_events = reader.read_all_events()?;
+            let mut segment_events = reader.read_all_events()?;
+            self.decode_enums(&mut segment_events);
 
             // Filter events to only those after after_seq
             for event in segment_events {
@@ -518,6 +1421,11 @@ impl TableStorage {
         &self,
         timestamp: chrono::DateTime<chrono::Utc>,
     ) -> Result<Option<u64>> {
+        if let Some(floor) = self.history_floor() {
+            let at = time::OffsetDateTime::from_unix_timestamp(timestamp.timestamp())
+                .map_err(|e| DriftError::InvalidQuery(format!("Invalid timestamp: {}", e)))?;
+            floor.check_timestamp(&self.schema.read().name, at)?;
+        }
         // Find the sequence number that corresponds to a given timestamp
         let events = self.read_all_events()?;
 
@@ -538,28 +1446,67 @@ impl TableStorage {
         Ok(latest_seq)
     }
 
-    pub fn schema(&self) -> &Schema {
-        &self.schema
+    pub fn schema(&self) -> parking_lot::RwLockReadGuard<'_, Schema> {
+        self.schema.read()
+    }
+
+    /// Persist `schema` as the table's schema and use it from now on.
+    pub fn update_schema(&self, schema: Schema) -> Result<()> {
+        schema.save_to_file(self.path.join("schema.yaml"))?;
+        *self.compression.write() = schema.compression;
+        *self.schema.write() = schema;
+        // Rows are presented through the schema, so a projection of them
+        // is rebuilt under the new one
+        *self.columnar.write() = None;
+        Ok(())
+    }
+
+    /// Sequence of the most recently appended event.
+    pub fn last_sequence(&self) -> u64 {
+        self.meta.read().last_sequence
+    }
+
+    /// Sequence the table's history was last folded through, by a
+    /// compaction or `VACUUM FULL`
+    pub fn last_compaction_sequence(&self) -> u64 {
+        self.meta.read().last_compaction_sequence
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Follow the table's events from its current last one, woken by
+    /// each append
+    pub fn subscribe(&self) -> Result<EventSubscription> {
+        EventSubscription::new(
+            &self.schema.read().name,
+            self.path.clone(),
+            self.backend.clone(),
+            self.encryption_service.clone(),
+            Some(self.appended.clone()),
+        )
+    }
+
     /// Calculate the total size of all table files in bytes
+    /// Bytes held by the table's segments in its backend
+    fn segments_size(&self) -> Result<u64> {
+        let segments_dir = self.path.join("segments");
+        if !segments_dir.exists() {
+            return Ok(0);
+        }
+        let mut size = 0u64;
+        for path in self.backend.list(&segments_dir)? {
+            size += self.backend.size(&path).unwrap_or(0);
+        }
+        Ok(size)
+    }
+
     pub fn calculate_size_bytes(&self) -> Result<u64> {
         let mut total_size = 0u64;
 
         // Calculate segments size
-        let segments_dir = self.path.join("segments");
-        if segments_dir.exists() {
-            for entry in fs::read_dir(&segments_dir)? {
-                let entry = entry?;
-                if let Ok(metadata) = entry.metadata() {
-                    total_size += metadata.len();
-                }
-            }
-        }
+        total_size += self.segments_size()?;
 
         // Calculate snapshots size
         let snapshots_dir = self.path.join("snapshots");
@@ -599,17 +1546,7 @@ impl TableStorage {
         let mut breakdown = HashMap::new();
 
         // Calculate segments size
-        let segments_dir = self.path.join("segments");
-        let mut segments_size = 0u64;
-        if segments_dir.exists() {
-            for entry in fs::read_dir(&segments_dir)? {
-                let entry = entry?;
-                if let Ok(metadata) = entry.metadata() {
-                    segments_size += metadata.len();
-                }
-            }
-        }
-        breakdown.insert("segments".to_string(), segments_size);
+        breakdown.insert("segments".to_string(), self.segments_size()?);
 
         // Calculate snapshots size
         let snapshots_dir = self.path.join("snapshots");
@@ -659,6 +1596,7 @@ impl TableStorage {
             sequence_count: meta.last_sequence,
             segment_count: meta.segment_count,
             snapshot_count,
+            last_compaction_sequence: meta.last_compaction_sequence,
         }
     }
 
@@ -668,14 +1606,160 @@ impl TableStorage {
 
     /// Count total number of records in the table
     pub fn count_records(&self) -> Result<usize> {
-        let _events = self.read_all_events()?;
+        self.row_count()
+    }
+
+    /// Number of live rows. The first call replays the segments once to
+    /// learn the live primary keys; every append after that keeps the set
+    /// current, so later calls don't read the table at all. Built from the
+    /// segments rather than persisted, the count can't drift from the
+    /// rows a crash leaves behind.
+    pub fn row_count(&self) -> Result<usize> {
+        if let Some(live) = self.live_keys.read().as_ref() {
+            return Ok(live.len());
+        }
+        loop {
+            let sequence = self.last_sequence();
+            let keys = self.replay_keys_to(Some(sequence))?;
+            // Appends hold the meta lock, so none can land between the
+            // check and installing the set
+            let meta = self.meta.write();
+            if meta.last_sequence != sequence {
+                continue;
+            }
+            let count = keys.len();
+            *self.live_keys.write() = Some(keys);
+            return Ok(count);
+        }
+    }
+
+    /// Whether a live row has primary key `key`, from the key set
+    /// [`TableStorage::row_count`] keeps current
+    pub fn has_live_key(&self, key: &serde_json::Value) -> Result<bool> {
+        self.row_count()?;
+        let key = key.to_string();
+        match self.live_keys.read().as_ref() {
+            Some(live) => Ok(live.contains(&key)),
+            None => Ok(self.replay_keys_to(None)?.contains(&key)),
+        }
+    }
+
+    /// Number of rows live as of `sequence` (`None` = current): the latest
+    /// snapshot's keys plus the events after it, without materializing
+    /// any row.
+    pub fn row_count_at(&self, sequence: Option<u64>) -> Result<usize> {
+        self.check_history(sequence)?;
+        match sequence {
+            Some(sequence) if sequence < self.last_sequence() => {
+                Ok(self.replay_keys_to(Some(sequence))?.len())
+            }
+            _ => self.row_count(),
+        }
+    }
+
+    /// The primary keys [`TableStorage::reconstruct_state_at`] would return
+    fn replay_keys_to(&self, sequence: Option<u64>) -> Result<HashSet<String>> {
+        let _swap = self.segments_swap.read_recursive();
+        let target_seq = sequence.unwrap_or(u64::MAX);
+
+        let snapshot_manager = crate::snapshot::SnapshotManager::new(&self.path);
+        let (mut keys, events) = match snapshot_manager.find_latest_before(target_seq) {
+            Ok(Some(snapshot)) => (
+                snapshot.state.into_keys().collect(),
+                self.read_events_after_sequence(snapshot.sequence)?,
+            ),
+            _ => (HashSet::new(), self.read_all_events()?),
+        };
+        for event in events {
+            if event.sequence > target_seq {
+                break;
+            }
+            track_live_key(&mut keys, &event);
+        }
+        Ok(keys)
+    }
+}
+
+/// Segment `id` at `path`, encrypted if the table is, stored in `backend`
+fn open_segment(
+    path: PathBuf,
+    id: u64,
+    encryption_service: &Option<Arc<EncryptionService>>,
+    backend: &Arc<dyn StorageBackend>,
+) -> Segment {
+    let segment = match encryption_service {
+        Some(encryption_service) => {
+            Segment::new_with_encryption(path, id, encryption_service.clone())
+        }
+        None => Segment::new(path, id),
+    };
+    segment.with_backend(backend.clone())
+}
 
-        // Count non-deleted records by reconstructing the current state
-        let state = self.reconstruct_state_at(None)?;
-        Ok(state.len())
+/// Flush what the active writer has been handed, so reads see it
+fn flush_writer(writer: &mut Option<SegmentWriter>) -> Result<()> {
+    match writer.as_mut() {
+        Some(writer) => writer.flush(),
+        None => Ok(()),
     }
 }
 
+/// Fold one event into a set of live primary keys, the way [`apply_event`]
+/// folds it into rows
+fn track_live_key(keys: &mut HashSet<String>, event: &Event) {
+    match event.event_type {
+        crate::events::EventType::Insert => {
+            keys.insert(event.primary_key.to_string());
+        }
+        crate::events::EventType::Patch => {}
+        crate::events::EventType::SoftDelete => {
+            keys.remove(&event.primary_key.to_string());
+        }
+    }
+}
+
+/// Fold one event into replayed state
+fn apply_event(state: &mut HashMap<String, serde_json::Value>, event: Event) {
+    match event.event_type {
+        crate::events::EventType::Insert => {
+            state.insert(event.primary_key.to_string(), event.payload);
+        }
+        crate::events::EventType::Patch => {
+            if let Some(existing) = state.get_mut(&event.primary_key.to_string()) {
+                if let (
+                    serde_json::Value::Object(existing_map),
+                    serde_json::Value::Object(patch_map),
+                ) = (existing, &event.payload)
+                {
+                    for (key, value) in patch_map {
+                        existing_map.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        crate::events::EventType::SoftDelete => {
+            state.remove(&event.primary_key.to_string());
+        }
+    }
+}
+
+impl SyncTarget for RwLock<Option<SegmentWriter>> {
+    fn sync_to_disk(&self) -> Result<()> {
+        if let Some(writer) = self.write().as_mut() {
+            writer.sync()?;
+        }
+        Ok(())
+    }
+}
+
+/// Which of `partitions` a primary key's rows are replayed in
+fn partition_of(pk: &str, partitions: usize) -> usize {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    pk.hash(&mut hasher);
+    (hasher.finish() % partitions as u64) as usize
+}
+
 impl Drop for TableStorage {
     fn drop(&mut self) {
         // The lock file will be automatically unlocked when dropped
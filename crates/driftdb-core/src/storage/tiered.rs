@@ -0,0 +1,555 @@
+//! Tiered segment storage.
+//!
+//! A [`TieredBackend`] keeps recently used segments on a fast backend (the
+//! local disk) and relocates the rest to a slow, cheap one such as a mounted
+//! object store. Segments are only ever moved whole, and only once nothing
+//! has them open for writing, so the active segment of every table stays
+//! hot. Reads find a segment wherever it is: a cold segment is fetched
+//! whole and kept in a bounded in-memory cache, so a time-travel query that
+//! walks old history pays the slow tier once rather than per read.
+//!
+//! A segment is cold once [`TierPolicy::cold_after`] has passed since it was
+//! last written, or last read with [`TierAge::Accessed`]. Appending to or
+//! truncating a cold segment brings it back to the hot tier first.
+//!
+//! Relocation copies a segment to the cold tier, syncs it and only then
+//! removes the hot copy. A crash in between leaves both copies; reads
+//! prefer the hot one and the next relocation overwrites the cold one.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::io::{self, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::errors::Result;
+use crate::storage::backend::{SegmentSink, SegmentSource, StorageBackend};
+
+/// What a segment's age is measured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TierAge {
+    /// The last write, so old history goes cold however often it's read
+    #[default]
+    Written,
+    /// The last read or write, so history that queries touch stays hot
+    Accessed,
+}
+
+/// When segments move to the cold tier and how much of it is cached
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierPolicy {
+    /// Age after which a segment is relocated
+    pub cold_after: Duration,
+    /// Bytes of cold segments kept in memory after being read
+    pub cache_size: u64,
+    pub age: TierAge,
+}
+
+impl Default for TierPolicy {
+    fn default() -> Self {
+        Self {
+            cold_after: Duration::from_secs(30 * 24 * 60 * 60),
+            cache_size: 256 * 1024 * 1024,
+            age: TierAge::Written,
+        }
+    }
+}
+
+/// Where segment reads were served from, and what relocation moved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierStats {
+    /// Reads of segments on the hot tier
+    pub hot_reads: u64,
+    /// Reads of cold segments answered from the cache
+    pub cache_hits: u64,
+    /// Reads that fetched a cold segment from the cold tier
+    pub cold_reads: u64,
+    /// Segments moved to the cold tier
+    pub relocated: u64,
+    pub relocated_bytes: u64,
+    /// Cold segments brought back to be written to
+    pub recalled: u64,
+    /// Bytes currently held by the cache
+    pub cached_bytes: u64,
+}
+
+#[derive(Default)]
+struct TierCounters {
+    hot_reads: AtomicU64,
+    cache_hits: AtomicU64,
+    cold_reads: AtomicU64,
+    relocated: AtomicU64,
+    relocated_bytes: AtomicU64,
+    recalled: AtomicU64,
+}
+
+/// Least-recently-used cache of whole cold segments, bounded in bytes
+#[derive(Default)]
+struct SegmentCache {
+    entries: HashMap<PathBuf, Arc<[u8]>>,
+    order: VecDeque<PathBuf>,
+    bytes: u64,
+}
+
+impl SegmentCache {
+    fn get(&mut self, path: &Path) -> Option<Arc<[u8]>> {
+        let bytes = self.entries.get(path)?.clone();
+        self.order.retain(|p| p != path);
+        self.order.push_back(path.to_path_buf());
+        Some(bytes)
+    }
+
+    fn insert(&mut self, path: &Path, bytes: Arc<[u8]>, capacity: u64) {
+        self.remove(path);
+        if bytes.len() as u64 > capacity {
+            return;
+        }
+        while self.bytes + bytes.len() as u64 > capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(evicted) = self.entries.remove(&oldest) {
+                self.bytes -= evicted.len() as u64;
+            }
+        }
+        self.bytes += bytes.len() as u64;
+        self.order.push_back(path.to_path_buf());
+        self.entries.insert(path.to_path_buf(), bytes);
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(bytes) = self.entries.remove(path) {
+            self.bytes -= bytes.len() as u64;
+            self.order.retain(|p| p != path);
+        }
+    }
+}
+
+/// Segments split between a hot and a cold [`StorageBackend`]. Paths are
+/// those of the hot tier; a cold segment is stored at the same path
+/// relative to `cold_root` instead of `hot_root`.
+pub struct TieredBackend {
+    hot: Arc<dyn StorageBackend>,
+    cold: Arc<dyn StorageBackend>,
+    hot_root: PathBuf,
+    cold_root: PathBuf,
+    policy: TierPolicy,
+    /// Last write, or access under [`TierAge::Accessed`], seen per segment
+    last_used: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
+    /// Segments with open writers, which are never relocated
+    writers: Arc<Mutex<HashMap<PathBuf, usize>>>,
+    cache: Mutex<SegmentCache>,
+    /// Held for writing while a segment changes tier
+    moving: RwLock<()>,
+    counters: TierCounters,
+}
+
+impl TieredBackend {
+    pub fn new(
+        hot: Arc<dyn StorageBackend>,
+        cold: Arc<dyn StorageBackend>,
+        hot_root: impl Into<PathBuf>,
+        cold_root: impl Into<PathBuf>,
+        policy: TierPolicy,
+    ) -> Self {
+        Self {
+            hot,
+            cold,
+            hot_root: hot_root.into(),
+            cold_root: cold_root.into(),
+            policy,
+            last_used: Arc::new(Mutex::new(HashMap::new())),
+            writers: Arc::new(Mutex::new(HashMap::new())),
+            cache: Mutex::new(SegmentCache::default()),
+            moving: RwLock::new(()),
+            counters: TierCounters::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &TierPolicy {
+        &self.policy
+    }
+
+    pub fn stats(&self) -> TierStats {
+        let c = &self.counters;
+        TierStats {
+            hot_reads: c.hot_reads.load(Ordering::Relaxed),
+            cache_hits: c.cache_hits.load(Ordering::Relaxed),
+            cold_reads: c.cold_reads.load(Ordering::Relaxed),
+            relocated: c.relocated.load(Ordering::Relaxed),
+            relocated_bytes: c.relocated_bytes.load(Ordering::Relaxed),
+            recalled: c.recalled.load(Ordering::Relaxed),
+            cached_bytes: self.cache.lock().bytes,
+        }
+    }
+
+    /// Whether the segment at `path` is on the cold tier
+    pub fn is_cold(&self, path: &Path) -> bool {
+        !self.hot.exists(path) && self.cold.exists(&self.cold_path(path))
+    }
+
+    fn cold_path(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.hot_root) {
+            Ok(relative) => self.cold_root.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    fn touch(&self, path: &Path) {
+        self.last_used
+            .lock()
+            .insert(path.to_path_buf(), SystemTime::now());
+    }
+
+    /// Move a cold segment back to the hot tier. Caller holds `moving`.
+    fn recall(&self, path: &Path) -> Result<()> {
+        let cold_path = self.cold_path(path);
+        if self.hot.exists(path) || !self.cold.exists(&cold_path) {
+            return Ok(());
+        }
+        let bytes = self.cold.read(&cold_path)?;
+        let mut sink = self.hot.create(path)?;
+        sink.write_all(&bytes)?;
+        sink.sync()?;
+        drop(sink);
+        self.cold.delete(&cold_path)?;
+        self.cache.lock().remove(path);
+        self.counters.recalled.fetch_add(1, Ordering::Relaxed);
+        debug!("Recalled segment {} from the cold tier", path.display());
+        Ok(())
+    }
+
+    fn track_writer(&self, path: &Path, inner: Box<dyn SegmentSink>) -> Box<dyn SegmentSink> {
+        *self.writers.lock().entry(path.to_path_buf()).or_insert(0) += 1;
+        self.touch(path);
+        Box::new(TieredSink {
+            inner,
+            path: path.to_path_buf(),
+            writers: self.writers.clone(),
+            last_used: self.last_used.clone(),
+        })
+    }
+
+    fn is_due(&self, path: &Path, now: SystemTime) -> bool {
+        let last = *self
+            .last_used
+            .lock()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| self.hot.modified(path).unwrap_or(now));
+        now.duration_since(last).unwrap_or_default() >= self.policy.cold_after
+    }
+
+    fn relocate(&self, path: &Path) -> Result<bool> {
+        let _moving = self.moving.write();
+        if self.writers.lock().contains_key(path) || !self.hot.exists(path) {
+            return Ok(false);
+        }
+        let bytes = self.hot.read(path)?;
+        let cold_path = self.cold_path(path);
+        let mut sink = self.cold.create(&cold_path)?;
+        sink.write_all(&bytes)?;
+        sink.sync()?;
+        drop(sink);
+        self.hot.delete(path)?;
+        self.counters.relocated.fetch_add(1, Ordering::Relaxed);
+        self.counters
+            .relocated_bytes
+            .fetch_add(bytes.len() as u64, Ordering::Relaxed);
+        Ok(true)
+    }
+}
+
+impl StorageBackend for TieredBackend {
+    fn create(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let _moving = self.moving.write();
+        let cold_path = self.cold_path(path);
+        if self.cold.exists(&cold_path) {
+            self.cold.delete(&cold_path)?;
+        }
+        self.cache.lock().remove(path);
+        let sink = self.hot.create(path)?;
+        Ok(self.track_writer(path, sink))
+    }
+
+    fn open_append(&self, path: &Path) -> Result<Box<dyn SegmentSink>> {
+        let _moving = self.moving.write();
+        self.recall(path)?;
+        let sink = self.hot.open_append(path)?;
+        Ok(self.track_writer(path, sink))
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn SegmentSource>> {
+        let _moving = self.moving.read();
+        if self.policy.age == TierAge::Accessed {
+            self.touch(path);
+        }
+        if self.hot.exists(path) {
+            self.counters.hot_reads.fetch_add(1, Ordering::Relaxed);
+            return self.hot.open_read(path);
+        }
+        if let Some(bytes) = self.cache.lock().get(path) {
+            self.counters.cache_hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(Box::new(Cursor::new(bytes)));
+        }
+        let bytes: Arc<[u8]> = self.cold.read(&self.cold_path(path))?.into();
+        self.counters.cold_reads.fetch_add(1, Ordering::Relaxed);
+        self.cache
+            .lock()
+            .insert(path, bytes.clone(), self.policy.cache_size);
+        Ok(Box::new(Cursor::new(bytes)))
+    }
+
+    fn size(&self, path: &Path) -> Result<u64> {
+        let _moving = self.moving.read();
+        if self.hot.exists(path) {
+            self.hot.size(path)
+        } else {
+            self.cold.size(&self.cold_path(path))
+        }
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let _moving = self.moving.read();
+        self.hot.exists(path) || self.cold.exists(&self.cold_path(path))
+    }
+
+    fn list(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let _moving = self.moving.read();
+        let mut paths: BTreeSet<PathBuf> = self.hot.list(dir)?.into_iter().collect();
+        for cold_path in self.cold.list(&self.cold_path(dir))? {
+            if let Some(name) = cold_path.file_name() {
+                paths.insert(dir.join(name));
+            }
+        }
+        Ok(paths.into_iter().collect())
+    }
+
+    fn delete(&self, path: &Path) -> Result<()> {
+        let _moving = self.moving.write();
+        let cold_path = self.cold_path(path);
+        let in_hot = self.hot.exists(path);
+        let in_cold = self.cold.exists(&cold_path);
+        if !in_hot && !in_cold {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no segment at {}", path.display()),
+            )
+            .into());
+        }
+        if in_hot {
+            self.hot.delete(path)?;
+        }
+        if in_cold {
+            self.cold.delete(&cold_path)?;
+        }
+        self.cache.lock().remove(path);
+        self.last_used.lock().remove(path);
+        Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let _moving = self.moving.write();
+        let (cold_from, cold_to) = (self.cold_path(from), self.cold_path(to));
+        // The renamed segment replaces `to` on whichever tier it was on
+        if self.hot.exists(from) {
+            self.hot.rename(from, to)?;
+            if self.cold.exists(&cold_to) {
+                self.cold.delete(&cold_to)?;
+            }
+        } else {
+            self.cold.rename(&cold_from, &cold_to)?;
+            if self.hot.exists(to) {
+                self.hot.delete(to)?;
+            }
+        }
+        let mut cache = self.cache.lock();
+        cache.remove(from);
+        cache.remove(to);
+        drop(cache);
+        let mut last_used = self.last_used.lock();
+        if let Some(time) = last_used.remove(from) {
+            last_used.insert(to.to_path_buf(), time);
+        }
+        Ok(())
+    }
+
+    fn truncate(&self, path: &Path, len: u64) -> Result<()> {
+        let _moving = self.moving.write();
+        self.recall(path)?;
+        self.hot.truncate(path, len)
+    }
+
+    fn modified(&self, path: &Path) -> Option<SystemTime> {
+        self.last_used
+            .lock()
+            .get(path)
+            .copied()
+            .or_else(|| self.hot.modified(path))
+    }
+
+    fn relocate_cold(&self, dir: &Path) -> Result<usize> {
+        let now = SystemTime::now();
+        let mut moved = 0;
+        for path in self.hot.list(dir)? {
+            if self.writers.lock().contains_key(&path) || !self.is_due(&path, now) {
+                continue;
+            }
+            if self.relocate(&path)? {
+                moved += 1;
+            }
+        }
+        if moved > 0 {
+            info!(
+                "Moved {} segments in {} to the cold tier",
+                moved,
+                dir.display()
+            );
+        }
+        Ok(moved)
+    }
+
+    fn archive(&self, dir: &Path) -> Result<usize> {
+        let mut moved = 0;
+        for path in self.hot.list(dir)? {
+            if self.relocate(&path)? {
+                moved += 1;
+            }
+        }
+        if moved > 0 {
+            info!(
+                "Archived {} segments in {} to the cold tier",
+                moved,
+                dir.display()
+            );
+        }
+        Ok(moved)
+    }
+
+    fn tier_stats(&self) -> Option<TierStats> {
+        Some(self.stats())
+    }
+}
+
+/// A hot-tier sink that keeps its segment from being relocated while open
+struct TieredSink {
+    inner: Box<dyn SegmentSink>,
+    path: PathBuf,
+    writers: Arc<Mutex<HashMap<PathBuf, usize>>>,
+    last_used: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
+}
+
+impl Write for TieredSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl SegmentSink for TieredSink {
+    fn sync(&mut self) -> Result<()> {
+        self.inner.sync()
+    }
+}
+
+impl Drop for TieredSink {
+    fn drop(&mut self) {
+        // The segment's age counts from when its writer closed
+        self.last_used
+            .lock()
+            .insert(self.path.clone(), SystemTime::now());
+        let mut writers = self.writers.lock();
+        if let Some(count) = writers.get_mut(&self.path) {
+            *count -= 1;
+            if *count == 0 {
+                writers.remove(&self.path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::MemoryBackend;
+
+    fn tiered(cache_size: u64) -> (TieredBackend, MemoryBackend, MemoryBackend) {
+        let (hot, cold) = (MemoryBackend::new(), MemoryBackend::new());
+        let backend = TieredBackend::new(
+            Arc::new(hot.clone()),
+            Arc::new(cold.clone()),
+            "/data",
+            "/archive",
+            TierPolicy {
+                cold_after: Duration::ZERO,
+                cache_size,
+                age: TierAge::Written,
+            },
+        );
+        (backend, hot, cold)
+    }
+
+    fn write(backend: &TieredBackend, path: &Path, bytes: &[u8]) {
+        let mut sink = backend.create(path).unwrap();
+        sink.write_all(bytes).unwrap();
+    }
+
+    #[test]
+    fn relocates_closed_segments_and_caches_reads() {
+        let (backend, hot, cold) = tiered(8);
+        let dir = Path::new("/data/tables/t/segments");
+        let (first, second) = (dir.join("00000001.seg"), dir.join("00000002.seg"));
+        write(&backend, &first, b"first");
+        let mut open = backend.create(&second).unwrap();
+        open.write_all(b"second").unwrap();
+
+        // The open segment stays put
+        assert_eq!(backend.relocate_cold(dir).unwrap(), 1);
+        assert!(backend.is_cold(&first) && !backend.is_cold(&second));
+        assert!(cold.exists(Path::new("/archive/tables/t/segments/00000001.seg")));
+        assert_eq!(hot.list(dir).unwrap(), vec![second.clone()]);
+        assert_eq!(
+            backend.list(dir).unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        assert_eq!(backend.read(&first).unwrap(), b"first");
+        assert_eq!(backend.read(&first).unwrap(), b"first");
+        assert_eq!(backend.read(&second).unwrap(), b"second");
+        let stats = backend.stats();
+        assert_eq!(
+            (stats.cold_reads, stats.cache_hits, stats.hot_reads),
+            (1, 1, 1)
+        );
+        assert_eq!(stats.cached_bytes, 5);
+
+        // Closing the writer lets its segment go; it doesn't fit the cache
+        // next to the first, which is evicted
+        drop(open);
+        assert_eq!(backend.relocate_cold(dir).unwrap(), 1);
+        assert_eq!(backend.read(&second).unwrap(), b"second");
+        assert_eq!(backend.stats().cached_bytes, 6);
+        assert_eq!(backend.read(&first).unwrap(), b"first");
+        assert_eq!(backend.stats().cold_reads, 3);
+
+        // Appending brings a segment back
+        let mut sink = backend.open_append(&first).unwrap();
+        sink.write_all(b"!").unwrap();
+        drop(sink);
+        assert!(!backend.is_cold(&first));
+        assert_eq!(backend.read(&first).unwrap(), b"first!");
+        assert_eq!(backend.stats().recalled, 1);
+
+        backend.delete(&second).unwrap();
+        assert_eq!(backend.list(dir).unwrap(), vec![first]);
+        assert_eq!(cold.bytes_used(), 0);
+    }
+}
@@ -0,0 +1,327 @@
+//! Following a table's events as they are appended
+//!
+//! An [`EventSubscription`] reads a table's segments from a sequence on
+//! and then waits for more, the way `tail -f` follows a file. It keeps its
+//! place as a segment and byte offset, so each wait only reads what was
+//! appended since, and a frame still being written is picked up on the
+//! next read once it is complete.
+//!
+//! [`Engine::subscribe`] follows a table of an open engine and is woken
+//! by each append. [`EventSubscription::open`] follows a table from
+//! another process: it takes no table lock, so it works while a server or
+//! application has the database open, and polls the segments instead.
+//! Encrypted tables can only be followed through the engine that holds
+//! the key.
+//!
+//! [`EventSubscription::into_receiver`] hands a subscription to a thread
+//! of its own that sends the events over a bounded channel, for consumers
+//! that would rather block on an [`EventReceiver`] than poll. Writers
+//! never wait on subscribers: they only raise the table's append signal,
+//! and a subscriber that falls behind reads the events it missed from the
+//! segments when it catches up, a segment at a time. Only committed events
+//! are seen, as a transaction's writes reach the segments at COMMIT.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+use tracing::warn;
+
+use crate::encryption::EncryptionService;
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::events::Event;
+use crate::schema::Schema;
+use crate::storage::{LocalFileBackend, Segment, StorageBackend, TableMeta};
+
+/// How often a subscription without an append signal looks for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How often a receiver's thread with nothing to send checks whether the
+/// receiver was dropped
+const RECEIVER_WAKE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Raised by a table after every append, for subscriptions to wait on
+#[derive(Default)]
+pub struct AppendSignal {
+    appends: Mutex<u64>,
+    appended: Condvar,
+}
+
+impl AppendSignal {
+    /// Number of appends signalled so far
+    pub fn appends(&self) -> u64 {
+        *self.appends.lock()
+    }
+
+    pub(crate) fn notify(&self) {
+        *self.appends.lock() += 1;
+        self.appended.notify_all();
+    }
+
+    /// Wait up to `timeout` for an append after the first `seen`.
+    /// Returns whether there was one.
+    pub fn wait_past(&self, seen: u64, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut appends = self.appends.lock();
+        while *appends <= seen {
+            if self.appended.wait_until(&mut appends, deadline).timed_out() {
+                return *appends > seen;
+            }
+        }
+        true
+    }
+}
+
+/// A reader that delivers a table's events in sequence order, each once
+pub struct EventSubscription {
+    table: String,
+    path: PathBuf,
+    backend: Arc<dyn StorageBackend>,
+    encryption_service: Option<Arc<EncryptionService>>,
+    signal: Option<Arc<AppendSignal>>,
+    /// Sequence of the last event delivered
+    position: u64,
+    /// Segment to read on from, and the offset of its next frame
+    segment: u64,
+    offset: u64,
+}
+
+impl EventSubscription {
+    /// Follow `table` in the database at `data_dir` from its current last
+    /// event, without opening an engine
+    pub fn open<P: AsRef<Path>>(data_dir: P, table: &str) -> Result<Self> {
+        let path = data_dir.as_ref().join("tables").join(table);
+        if !path.join("schema.yaml").exists() {
+            return Err(DriftError::TableNotFound(table.to_string()));
+        }
+        Self::new(table, path, LocalFileBackend::shared(), None, None)
+    }
+
+    pub(crate) fn new(
+        table: &str,
+        path: PathBuf,
+        backend: Arc<dyn StorageBackend>,
+        encryption_service: Option<Arc<EncryptionService>>,
+        signal: Option<Arc<AppendSignal>>,
+    ) -> Result<Self> {
+        let mut subscription = Self {
+            table: table.to_string(),
+            path,
+            backend,
+            encryption_service,
+            signal,
+            position: 0,
+            segment: 0,
+            offset: 0,
+        };
+        let last_sequence = subscription.meta()?.last_sequence;
+        subscription.seek(last_sequence)?;
+        Ok(subscription)
+    }
+
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Sequence of the last event delivered, or the one the subscription
+    /// started after
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Deliver events after `sequence` from now on; 0 replays the whole
+    /// table
+    pub fn seek(&mut self, sequence: u64) -> Result<()> {
+        let index = self.meta()?.segment_index;
+        self.position = sequence;
+        self.segment = index
+            .find_first_segment_after(sequence)
+            .or_else(|| index.segments.keys().next_back().copied())
+            .unwrap_or(0);
+        self.offset = 0;
+        Ok(())
+    }
+
+    /// The next events, without waiting: those appended since the last
+    /// call, or while catching up, the rest of the next segment that has
+    /// any. Empty once the subscription is at the end of the table.
+    pub fn poll(&mut self) -> Result<Vec<Event>> {
+        let segments = self.segment_paths()?;
+        let last = segments.keys().next_back().copied();
+        let mut events = Vec::new();
+        for (id, path) in segments {
+            let start = if id == self.segment { self.offset } else { 0 };
+            let segment = match &self.encryption_service {
+                Some(encryption_service) => {
+                    Segment::new_with_encryption(path, id, encryption_service.clone())
+                }
+                None => Segment::new(path, id),
+            }
+            .with_backend(self.backend.clone());
+            let (read, end, complete) = segment.open_reader()?.read_verified_from(start)?;
+            // Only the segment being written may end in a partial frame
+            if !complete && Some(id) != last {
+                return Err(DriftError::CorruptSegment(format!(
+                    "segment {} of table '{}' is unreadable from byte {}",
+                    id, self.table, end
+                )));
+            }
+            self.segment = id;
+            self.offset = end;
+            events.extend(read.into_iter().filter(|e| e.sequence > self.position));
+            if !events.is_empty() {
+                break;
+            }
+        }
+
+        if let Some(event) = events.last() {
+            self.position = event.sequence;
+            // Read the schema afresh: enum types may have gained values
+            let schema = Schema::load_from_file(self.path.join("schema.yaml"))?;
+            if !schema.enums.is_empty() {
+                for event in &mut events {
+                    schema.decode_enums(&mut event.payload);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// [`EventSubscription::poll`], waiting up to `timeout` for the first
+    /// event if there are none yet. An empty result means the wait ran
+    /// out.
+    pub fn next_events(&mut self, timeout: Duration) -> Result<Vec<Event>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let seen = self.signal.as_ref().map(|signal| signal.appends());
+            let events = self.poll()?;
+            let now = Instant::now();
+            if !events.is_empty() || now >= deadline {
+                return Ok(events);
+            }
+            match (&self.signal, seen) {
+                (Some(signal), Some(seen)) => {
+                    signal.wait_past(seen, deadline - now);
+                }
+                _ => std::thread::sleep(POLL_INTERVAL.min(deadline - now)),
+            }
+        }
+    }
+
+    /// Deliver the events over a channel holding up to `capacity` of
+    /// them, from a thread that follows the table until the receiver is
+    /// dropped. While the channel is full the thread waits, and the
+    /// events behind it stay in the segments.
+    pub fn into_receiver(mut self, capacity: usize) -> Result<EventReceiver> {
+        let (sender, events) = mpsc::sync_channel(capacity.max(1));
+        let closed = Arc::new(AtomicBool::new(false));
+        let stop = closed.clone();
+        std::thread::Builder::new()
+            .name(format!("driftdb-subscription-{}", self.table))
+            .spawn(move || {
+                while !stop.load(Ordering::Acquire) {
+                    match self.next_events(RECEIVER_WAKE_INTERVAL) {
+                        Ok(events) => {
+                            for event in events {
+                                if sender.send(Ok(event)).is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            warn!("Subscription to table '{}' failed: {}", self.table, e);
+                            let _ = sender.send(Err(e));
+                            return;
+                        }
+                    }
+                }
+            })
+            .map_err(|e| {
+                DriftError::Other(format!("failed to start subscription thread: {}", e))
+            })?;
+        Ok(EventReceiver { events, closed })
+    }
+
+    fn meta(&self) -> Result<TableMeta> {
+        TableMeta::load_from_file(self.path.join("meta.json"))
+    }
+
+    /// Segment files from the current one on, by id
+    fn segment_paths(&self) -> Result<BTreeMap<u64, PathBuf>> {
+        Ok(self
+            .backend
+            .list(&self.path.join("segments"))?
+            .into_iter()
+            .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("seg"))
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_str()?.parse::<u64>().ok()?;
+                (id >= self.segment).then_some((id, path))
+            })
+            .collect())
+    }
+}
+
+/// The receiving end of [`EventSubscription::into_receiver`]. Iterating
+/// blocks for each event; a failed read of the table is delivered as an
+/// error and ends the subscription. Dropping it stops the thread.
+pub struct EventReceiver {
+    events: mpsc::Receiver<Result<Event>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl EventReceiver {
+    /// The next event if one is waiting
+    pub fn try_recv(&self) -> Option<Result<Event>> {
+        self.events.try_recv().ok()
+    }
+
+    /// The next event, waiting up to `timeout` for it
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Result<Event>> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+impl Iterator for EventReceiver {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events.recv().ok()
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+    }
+}
+
+impl Engine {
+    /// Follow `table` from its current last event, woken by each append.
+    /// Use [`EventSubscription::seek`] to start further back.
+    pub fn subscribe(&self, table: &str) -> Result<EventSubscription> {
+        self.tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?
+            .subscribe()
+    }
+
+    /// `table`'s events after sequence `after` (or from now, for `None`)
+    /// and then as they are committed, over a channel of `capacity`
+    /// events. See [`EventSubscription::into_receiver`].
+    pub fn subscribe_events(
+        &self,
+        table: &str,
+        after: Option<u64>,
+        capacity: usize,
+    ) -> Result<EventReceiver> {
+        let mut subscription = self.subscribe(table)?;
+        if let Some(after) = after {
+            subscription.seek(after)?;
+        }
+        subscription.into_receiver(capacity)
+    }
+}
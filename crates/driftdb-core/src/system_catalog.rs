@@ -0,0 +1,431 @@
+//! System catalog
+//!
+//! Read-only relations describing the database, for drivers and BI tools
+//! that look for tables through the catalog on connect:
+//!
+//! - `information_schema.schemata`, `information_schema.tables` and
+//!   `information_schema.columns`
+//! - `pg_catalog.pg_namespace`, `pg_catalog.pg_class` and
+//!   `pg_catalog.pg_attribute`
+//!
+//! Rows are built from the engine's schemas each time a statement reads
+//! them, so they're never stale. As in PostgreSQL, `pg_catalog` relations
+//! can also be named without their schema unless a table of the same name
+//! is on the search path. The catalog describes itself too: its relations
+//! are listed in `tables` and `pg_class` alongside user tables.
+//!
+//! Object ids are derived from names, so they stay the same across
+//! restarts but change when a table is renamed.
+
+use serde_json::{json, Value};
+
+use crate::engine::Engine;
+use crate::search_path::{split_storage_name, DEFAULT_SCHEMA};
+
+/// Schema of the SQL-standard catalog views
+pub const INFORMATION_SCHEMA: &str = "information_schema";
+
+/// Schema of the PostgreSQL catalog tables
+pub const PG_CATALOG: &str = "pg_catalog";
+
+/// The database name catalog rows report
+const CATALOG_NAME: &str = "driftdb";
+
+/// A catalog relation and its columns with their types
+struct Relation {
+    schema: &'static str,
+    name: &'static str,
+    columns: &'static [(&'static str, &'static str)],
+}
+
+const RELATIONS: &[Relation] = &[
+    Relation {
+        schema: INFORMATION_SCHEMA,
+        name: "schemata",
+        columns: &[("catalog_name", "TEXT"), ("schema_name", "TEXT")],
+    },
+    Relation {
+        schema: INFORMATION_SCHEMA,
+        name: "tables",
+        columns: &[
+            ("table_catalog", "TEXT"),
+            ("table_schema", "TEXT"),
+            ("table_name", "TEXT"),
+            ("table_type", "TEXT"),
+            ("is_insertable_into", "TEXT"),
+        ],
+    },
+    Relation {
+        schema: INFORMATION_SCHEMA,
+        name: "columns",
+        columns: &[
+            ("table_catalog", "TEXT"),
+            ("table_schema", "TEXT"),
+            ("table_name", "TEXT"),
+            ("column_name", "TEXT"),
+            ("ordinal_position", "INTEGER"),
+            ("column_default", "TEXT"),
+            ("is_nullable", "TEXT"),
+            ("data_type", "TEXT"),
+            ("character_maximum_length", "INTEGER"),
+            ("udt_name", "TEXT"),
+        ],
+    },
+    Relation {
+        schema: PG_CATALOG,
+        name: "pg_namespace",
+        columns: &[("oid", "OID"), ("nspname", "NAME")],
+    },
+    Relation {
+        schema: PG_CATALOG,
+        name: "pg_class",
+        columns: &[
+            ("oid", "OID"),
+            ("relname", "NAME"),
+            ("relnamespace", "OID"),
+            ("relkind", "CHAR"),
+            ("relnatts", "SMALLINT"),
+            ("relhasindex", "BOOLEAN"),
+            ("relpersistence", "CHAR"),
+        ],
+    },
+    Relation {
+        schema: PG_CATALOG,
+        name: "pg_attribute",
+        columns: &[
+            ("attrelid", "OID"),
+            ("attname", "NAME"),
+            ("atttypid", "OID"),
+            ("attlen", "SMALLINT"),
+            ("attnum", "SMALLINT"),
+            ("atttypmod", "INTEGER"),
+            ("attnotnull", "BOOLEAN"),
+            ("atthasdef", "BOOLEAN"),
+            ("attisdropped", "BOOLEAN"),
+        ],
+    },
+];
+
+/// The catalog relation an engine table name refers to, if any
+fn relation(name: &str) -> Option<&'static Relation> {
+    let (schema, table) = split_storage_name(name);
+    RELATIONS.iter().find(|relation| {
+        relation.name == table
+            && (relation.schema == schema || (relation.schema == PG_CATALOG && !name.contains('.')))
+    })
+}
+
+/// Whether `name`, as resolved to an engine table name, is a catalog
+/// relation. A bare `pg_class` counts; shadowing by a user table of the
+/// same name is for the caller to check.
+pub fn is_catalog_relation(name: &str) -> bool {
+    relation(name).is_some()
+}
+
+/// The current rows of catalog relation `name`, or `None` when it isn't one
+pub fn relation_rows(engine: &Engine, name: &str) -> Option<Vec<Value>> {
+    let relation = relation(name)?;
+    let tables = Table::all(engine);
+    let rows = match relation.name {
+        "schemata" => schemas(engine)
+            .into_iter()
+            .map(|schema| json!({"catalog_name": CATALOG_NAME, "schema_name": schema}))
+            .collect(),
+        "tables" => tables
+            .iter()
+            .filter(|table| table.kind != Kind::MaterializedView)
+            .map(|table| {
+                json!({
+                    "table_catalog": CATALOG_NAME,
+                    "table_schema": table.schema,
+                    "table_name": table.name,
+                    "table_type": match table.kind {
+                        Kind::Table | Kind::Catalog if table.schema != INFORMATION_SCHEMA => {
+                            "BASE TABLE"
+                        }
+                        _ => "VIEW",
+                    },
+                    "is_insertable_into": if table.kind == Kind::Table { "YES" } else { "NO" },
+                })
+            })
+            .collect(),
+        "columns" => tables
+            .iter()
+            .filter(|table| table.kind != Kind::MaterializedView)
+            .flat_map(|table| {
+                table.columns.iter().enumerate().map(|(i, column)| {
+                    json!({
+                        "table_catalog": CATALOG_NAME,
+                        "table_schema": table.schema,
+                        "table_name": table.name,
+                        "column_name": column.name,
+                        "ordinal_position": i + 1,
+                        "column_default": column.default,
+                        "is_nullable": if column.not_null { "NO" } else { "YES" },
+                        "data_type": column.pg_type.data_type,
+                        "character_maximum_length": column.pg_type.max_length,
+                        "udt_name": column.pg_type.udt_name,
+                    })
+                })
+            })
+            .collect(),
+        "pg_namespace" => schemas(engine)
+            .into_iter()
+            .map(|schema| json!({"oid": namespace_oid(&schema), "nspname": schema}))
+            .collect(),
+        "pg_class" => tables
+            .iter()
+            .map(|table| {
+                json!({
+                    "oid": table.oid,
+                    "relname": table.name,
+                    "relnamespace": namespace_oid(&table.schema),
+                    "relkind": match table.kind {
+                        Kind::Table | Kind::Catalog => "r",
+                        Kind::View => "v",
+                        Kind::MaterializedView => "m",
+                    },
+                    "relnatts": table.columns.len(),
+                    "relhasindex": table.has_index,
+                    "relpersistence": "p",
+                })
+            })
+            .collect(),
+        "pg_attribute" => tables
+            .iter()
+            .flat_map(|table| {
+                table.columns.iter().enumerate().map(|(i, column)| {
+                    json!({
+                        "attrelid": table.oid,
+                        "attname": column.name,
+                        "atttypid": column.pg_type.oid,
+                        "attlen": column.pg_type.len,
+                        "attnum": i + 1,
+                        "atttypmod": column.pg_type.max_length.map_or(-1, |n| n as i64 + 4),
+                        "attnotnull": column.not_null,
+                        "atthasdef": column.default.is_some(),
+                        "attisdropped": false,
+                    })
+                })
+            })
+            .collect(),
+        _ => unreachable!("every catalog relation has rows"),
+    };
+    Some(rows)
+}
+
+/// Every schema, `public` first, then the catalog's own
+fn schemas(engine: &Engine) -> Vec<String> {
+    let mut schemas = engine.list_schemas();
+    schemas.push(INFORMATION_SCHEMA.to_string());
+    schemas.push(PG_CATALOG.to_string());
+    schemas
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Table,
+    View,
+    MaterializedView,
+    Catalog,
+}
+
+/// A relation as the catalog describes it
+struct Table {
+    oid: u32,
+    schema: String,
+    name: String,
+    kind: Kind,
+    has_index: bool,
+    columns: Vec<Column>,
+}
+
+struct Column {
+    name: String,
+    pg_type: PgType,
+    not_null: bool,
+    default: Option<String>,
+}
+
+impl Table {
+    /// User tables and views by schema and name, then the catalog's own
+    /// relations
+    fn all(engine: &Engine) -> Vec<Table> {
+        let mut tables: Vec<Table> = engine
+            .list_tables()
+            .into_iter()
+            .filter_map(|name| engine.table_schema(&name).ok().map(|schema| (name, schema)))
+            .map(|(engine_name, schema)| {
+                let (schema_name, name) = split_storage_name(&engine_name);
+                let columns = schema
+                    .columns
+                    .iter()
+                    .map(|column| Column {
+                        name: column.name.clone(),
+                        pg_type: match schema.enums.get(&column.name) {
+                            Some(enum_type) => PgType::user_defined(&enum_type.name),
+                            None => PgType::of(&column.col_type),
+                        },
+                        not_null: column.name == schema.primary_key,
+                        default: schema.defaults.get(&column.name).cloned(),
+                    })
+                    .collect();
+                Table {
+                    oid: object_oid(&engine_name),
+                    schema: schema_name.to_string(),
+                    name: name.to_string(),
+                    kind: Kind::Table,
+                    // The primary key is always indexed
+                    has_index: true,
+                    columns,
+                }
+            })
+            .collect();
+
+        tables.extend(engine.list_views().into_iter().map(|view| {
+            let (schema, name) = split_storage_name(&view.name);
+            Table {
+                oid: object_oid(&view.name),
+                schema: schema.to_string(),
+                name: name.to_string(),
+                kind: if view.is_materialized {
+                    Kind::MaterializedView
+                } else {
+                    Kind::View
+                },
+                has_index: false,
+                columns: view
+                    .columns
+                    .iter()
+                    .map(|column| Column {
+                        name: column.name.clone(),
+                        pg_type: PgType::of(&column.data_type),
+                        not_null: !column.nullable,
+                        default: None,
+                    })
+                    .collect(),
+            }
+        }));
+        tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+
+        tables.extend(RELATIONS.iter().map(|relation| {
+            Table {
+                oid: object_oid(&format!("{}.{}", relation.schema, relation.name)),
+                schema: relation.schema.to_string(),
+                name: relation.name.to_string(),
+                kind: Kind::Catalog,
+                has_index: false,
+                columns: relation
+                    .columns
+                    .iter()
+                    .map(|(name, ty)| Column {
+                        name: name.to_string(),
+                        pg_type: PgType::of(ty),
+                        not_null: false,
+                        default: None,
+                    })
+                    .collect(),
+            }
+        }));
+        tables
+    }
+}
+
+/// How PostgreSQL names and sizes a column type
+struct PgType {
+    /// `information_schema.columns.data_type`
+    data_type: String,
+    /// The type's `pg_type` name
+    udt_name: String,
+    oid: u32,
+    /// Fixed size in bytes, or -1 for variable-length types
+    len: i16,
+    /// Declared length of a `VARCHAR(n)` or `CHAR(n)`
+    max_length: Option<u32>,
+}
+
+impl PgType {
+    /// The PostgreSQL type for a column type as written in `CREATE TABLE`
+    fn of(col_type: &str) -> Self {
+        let upper = col_type.trim().to_uppercase();
+        if let Some(element) = upper.strip_suffix("[]") {
+            let element = Self::of(element);
+            return Self {
+                data_type: "ARRAY".to_string(),
+                udt_name: format!("_{}", element.udt_name),
+                oid: 0,
+                len: -1,
+                max_length: None,
+            };
+        }
+        let (base, args) = match upper.split_once('(') {
+            Some((base, args)) => (base.trim(), args.trim_end_matches(')')),
+            None => (upper.as_str(), ""),
+        };
+        let max_length = args.split(',').next().and_then(|n| n.trim().parse().ok());
+        let (data_type, udt_name, oid, len) = match base {
+            "BOOL" | "BOOLEAN" => ("boolean", "bool", 16, 1),
+            "SMALLINT" | "INT2" => ("smallint", "int2", 21, 2),
+            "INT" | "INTEGER" | "INT4" | "SERIAL" => ("integer", "int4", 23, 4),
+            "BIGINT" | "INT8" | "BIGSERIAL" => ("bigint", "int8", 20, 8),
+            "REAL" | "FLOAT4" => ("real", "float4", 700, 4),
+            "FLOAT" | "FLOAT8" | "DOUBLE" | "DOUBLE PRECISION" => {
+                ("double precision", "float8", 701, 8)
+            }
+            "NUMERIC" | "DECIMAL" => ("numeric", "numeric", 1700, -1),
+            "VARCHAR" | "CHARACTER VARYING" => ("character varying", "varchar", 1043, -1),
+            "CHAR" | "CHARACTER" | "BPCHAR" => ("character", "bpchar", 1042, -1),
+            "NAME" => ("name", "name", 19, 64),
+            "OID" => ("oid", "oid", 26, 4),
+            "DATE" => ("date", "date", 1082, 4),
+            "TIME" => ("time without time zone", "time", 1083, 8),
+            "TIMESTAMP" => ("timestamp without time zone", "timestamp", 1114, 8),
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => {
+                ("timestamp with time zone", "timestamptz", 1184, 8)
+            }
+            "UUID" => ("uuid", "uuid", 2950, 16),
+            "JSON" => ("json", "json", 114, -1),
+            "JSONB" => ("jsonb", "jsonb", 3802, -1),
+            "BYTEA" => ("bytea", "bytea", 17, -1),
+            _ => ("text", "text", 25, -1),
+        };
+        Self {
+            data_type: data_type.to_string(),
+            udt_name: udt_name.to_string(),
+            oid,
+            len,
+            max_length: max_length.filter(|_| matches!(udt_name, "varchar" | "bpchar")),
+        }
+    }
+
+    /// An enum type created with `CREATE TYPE ... AS ENUM`
+    fn user_defined(name: &str) -> Self {
+        Self {
+            data_type: "USER-DEFINED".to_string(),
+            udt_name: name.to_string(),
+            oid: object_oid(name),
+            len: 4,
+            max_length: None,
+        }
+    }
+}
+
+/// PostgreSQL's fixed ids for its own schemas; others are derived from
+/// the name
+fn namespace_oid(schema: &str) -> u32 {
+    match schema {
+        PG_CATALOG => 11,
+        DEFAULT_SCHEMA => 2200,
+        INFORMATION_SCHEMA => 13_000,
+        _ => object_oid(schema),
+    }
+}
+
+/// A stable id for a named object, above PostgreSQL's reserved range
+fn object_oid(name: &str) -> u32 {
+    // FNV-1a
+    let hash = name.bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+    });
+    16_384 + hash % (u32::MAX / 2)
+}
@@ -15,6 +15,7 @@ use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, instrument, warn};
 
+use crate::durability::{Durability, SyncMode};
 use crate::errors::{DriftError, Result};
 use crate::events::Event;
 use crate::observability::Metrics;
@@ -56,7 +57,16 @@ pub enum TransactionState {
 #[derive(Debug, Clone)]
 pub struct Savepoint {
     pub name: String,
-    pub write_set_snapshot: HashMap<String, Event>,
+    pub write_set_snapshot: HashMap<RowKey, Event>,
+}
+
+/// Identifies a row in the read and write sets: its table and its
+/// JSON-serialized primary key. Rows in different tables may share a
+/// primary key within one transaction.
+pub type RowKey = (String, String);
+
+fn row_key(table: &str, primary_key: &str) -> RowKey {
+    (table.to_string(), primary_key.to_string())
 }
 
 pub struct Transaction {
@@ -65,8 +75,8 @@ pub struct Transaction {
     pub state: TransactionState,
     pub start_time: Instant,
     pub snapshot_version: u64,
-    pub read_set: HashSet<String>,         // Keys read
-    pub write_set: HashMap<String, Event>, // Pending writes
+    pub read_set: HashSet<RowKey>,         // Rows read
+    pub write_set: HashMap<RowKey, Event>, // Pending writes
     pub locked_keys: HashSet<String>,      // Keys locked for this transaction
     pub timeout: Duration,
     /// SAVEPOINT stack. Innermost (most-recent) savepoint last.
@@ -218,12 +228,9 @@ impl LockManager {
         blocking_txn: u64,
     ) -> Result<()> {
         // Simple deadlock detection: check if blocking_txn is waiting for txn_id
-        if self.would_cause_deadlock(txn_id, blocking_txn) {
-            error!(
-                "Deadlock detected: txn {} waiting for txn {}",
-                txn_id, blocking_txn
-            );
-            return Err(DriftError::Lock("Deadlock detected".to_string()));
+        if let Some(report) = self.deadlock(txn_id, key, blocking_txn) {
+            error!("Deadlock detected: {}", report);
+            return Err(DriftError::Deadlock(report));
         }
 
         // Add to wait queue
@@ -243,26 +250,69 @@ impl LockManager {
         )))
     }
 
-    /// Check if acquiring lock would cause deadlock
-    fn would_cause_deadlock(&self, waiting_txn: u64, blocking_txn: u64) -> bool {
+    /// The deadlock `waiting_txn` would enter by waiting for `key`, held
+    /// by `blocking_txn`: a chain of waits from `blocking_txn` back to
+    /// `waiting_txn`. The requester is the victim, as it hasn't started
+    /// waiting.
+    fn deadlock(
+        &self,
+        waiting_txn: u64,
+        key: &str,
+        blocking_txn: u64,
+    ) -> Option<crate::mvcc::DeadlockReport> {
         let waits_for = self.waits_for.lock();
 
-        // DFS to check if blocking_txn can reach waiting_txn
-        let mut visited = HashSet::new();
+        // DFS from blocking_txn, remembering how each txn was reached
+        let mut reached_from = HashMap::new();
         let mut stack = vec![blocking_txn];
-
         while let Some(txn) = stack.pop() {
             if txn == waiting_txn {
-                return true; // Cycle detected
+                break;
             }
-            if visited.insert(txn) {
-                if let Some(waiting_for) = waits_for.get(&txn) {
-                    stack.extend(waiting_for.iter());
+            for &next in waits_for.get(&txn).into_iter().flatten() {
+                if next != blocking_txn && !reached_from.contains_key(&next) {
+                    reached_from.insert(next, txn);
+                    stack.push(next);
                 }
             }
         }
+        drop(waits_for);
+        let mut last = *reached_from.get(&waiting_txn)?;
+
+        // blocking_txn -> ... -> last -> waiting_txn, walked backwards
+        let mut chain = vec![last];
+        while last != blocking_txn {
+            last = reached_from[&last];
+            chain.push(last);
+        }
+        chain.reverse();
 
-        false
+        let wait_queue = self.wait_queue.lock();
+        let queued_key = |txn: u64| {
+            let mut keys: Vec<&String> = wait_queue
+                .iter()
+                .filter(|(_, waiters)| waiters.iter().any(|(id, _)| *id == txn))
+                .map(|(key, _)| key)
+                .collect();
+            keys.sort();
+            keys.first().map(|key| key.to_string())
+        };
+        let mut waits = vec![crate::mvcc::DeadlockWait {
+            txn_id: waiting_txn,
+            resource: Some(key.to_string()),
+            blocked_by: blocking_txn,
+        }];
+        for (i, &txn) in chain.iter().enumerate() {
+            waits.push(crate::mvcc::DeadlockWait {
+                txn_id: txn,
+                resource: queued_key(txn),
+                blocked_by: chain.get(i + 1).copied().unwrap_or(waiting_txn),
+            });
+        }
+        Some(crate::mvcc::DeadlockReport {
+            waits,
+            victim: waiting_txn,
+        })
     }
 
     /// Release all locks held by a transaction
@@ -344,6 +394,15 @@ impl TransactionManager {
 
     /// Create a new TransactionManager with specified base path
     pub fn new_with_path<P: AsRef<std::path::Path>>(base_path: P) -> Result<Self> {
+        Self::new_with_durability(base_path, Durability::new(SyncMode::Full))
+    }
+
+    /// Like [`TransactionManager::new_with_path`], with WAL fsyncs
+    /// following `durability`
+    pub fn new_with_durability<P: AsRef<std::path::Path>>(
+        base_path: P,
+        durability: Arc<Durability>,
+    ) -> Result<Self> {
         let base_path = base_path.as_ref();
         let wal_dir = base_path.join("wal");
         let wal_path = wal_dir.join("wal.log");
@@ -353,10 +412,10 @@ impl TransactionManager {
             .map_err(|e| DriftError::Other(format!("Failed to create WAL directory: {}", e)))?;
 
         // Create WAL
-        let wal = Arc::new(WalManager::new(
-            &wal_path,
-            crate::wal::WalConfig::default(),
-        )?);
+        let wal = Arc::new(
+            WalManager::new(&wal_path, crate::wal::WalConfig::default())?
+                .with_durability(durability),
+        );
 
         Ok(Self {
             next_txn_id: Arc::new(AtomicU64::new(1)),
@@ -412,6 +471,7 @@ impl TransactionManager {
     pub fn read(
         &self,
         txn: &Arc<Mutex<Transaction>>,
+        table: &str,
         key: &str,
     ) -> Result<Option<serde_json::Value>> {
         let mut txn_guard = txn.lock();
@@ -426,7 +486,7 @@ impl TransactionManager {
         }
 
         // Check write set first (read-your-writes)
-        if let Some(event) = txn_guard.write_set.get(key) {
+        if let Some(event) = txn_guard.write_set.get(&row_key(table, key)) {
             return Ok(Some(event.payload.clone()));
         }
 
@@ -440,7 +500,7 @@ impl TransactionManager {
         }
 
         // Record read for conflict detection
-        txn_guard.read_set.insert(key.to_string());
+        txn_guard.read_set.insert(row_key(table, key));
 
         // In production, would read from storage at snapshot_version
         // For now, return None (key not found)
@@ -467,7 +527,9 @@ impl TransactionManager {
         txn_guard.locked_keys.insert(key.clone());
 
         // Add to write set
-        txn_guard.write_set.insert(key, event);
+        txn_guard
+            .write_set
+            .insert(row_key(&event.table_name, &key), event);
 
         Ok(())
     }
@@ -595,7 +657,7 @@ impl TransactionManager {
                 if other.write_set.contains_key(read_key)
                     && other.snapshot_version < txn.snapshot_version
                 {
-                    debug!("Read-write conflict detected on key {}", read_key);
+                    debug!("Read-write conflict detected on key {:?}", read_key);
                     return Ok(false);
                 }
             }
@@ -603,7 +665,7 @@ impl TransactionManager {
             // Check for write-write conflicts
             for write_key in txn.write_set.keys() {
                 if other.write_set.contains_key(write_key) {
-                    debug!("Write-write conflict detected on key {}", write_key);
+                    debug!("Write-write conflict detected on key {:?}", write_key);
                     return Ok(false);
                 }
             }
@@ -649,18 +711,67 @@ impl TransactionManager {
         Ok(txn_id)
     }
 
+    /// Buffer `event` in the transaction's write set, unless another
+    /// open transaction already has a pending write to the same row.
+    /// A patch of a row the transaction already inserted or patched is
+    /// folded into that event, so the row is still inserted at commit.
     pub fn add_write(&mut self, txn_id: u64, event: Event) -> Result<()> {
+        self.check_write_conflict(Some(txn_id), &event.table_name, &event.primary_key)?;
+
         let active_txns = self.active_transactions.read();
         let txn = active_txns
             .get(&txn_id)
             .ok_or_else(|| DriftError::Other(format!("Transaction {} not found", txn_id)))?;
 
         let mut txn_guard = txn.lock();
-        let key = event.primary_key.to_string();
+        let key = row_key(&event.table_name, &event.primary_key.to_string());
+        if let Some(pending) = txn_guard.write_set.get_mut(&key) {
+            let foldable = matches!(
+                pending.event_type,
+                crate::events::EventType::Insert | crate::events::EventType::Patch
+            );
+            if foldable && event.event_type == crate::events::EventType::Patch {
+                if let (Some(row), Some(patch)) =
+                    (pending.payload.as_object_mut(), event.payload.as_object())
+                {
+                    row.extend(patch.iter().map(|(k, v)| (k.clone(), v.clone())));
+                    pending.timestamp = event.timestamp;
+                    return Ok(());
+                }
+            }
+        }
         txn_guard.write_set.insert(key, event);
         Ok(())
     }
 
+    /// Refuse a write to a row that an open transaction other than
+    /// `txn_id` has a pending write to. The first writer wins: letting
+    /// both through would have the later commit overwrite an update it
+    /// never saw. Auto-commit writes pass `None`.
+    pub fn check_write_conflict(
+        &self,
+        txn_id: Option<u64>,
+        table: &str,
+        primary_key: &serde_json::Value,
+    ) -> Result<()> {
+        let key = row_key(table, &primary_key.to_string());
+        let active_txns = self.active_transactions.read();
+        for (other_id, other) in active_txns.iter() {
+            if Some(*other_id) == txn_id {
+                continue;
+            }
+            let other = other.lock();
+            let pending = other.write_set.contains_key(&key);
+            if pending && other.is_active() && !other.is_timeout() {
+                return Err(DriftError::Conflict(format!(
+                    "could not serialize access due to concurrent update of row {} in table \"{}\"",
+                    key.1, table
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Push a SAVEPOINT marker. PostgreSQL allows duplicate names —
     /// the new savepoint shadows the older same-named one; a later
     /// `ROLLBACK TO name` resolves to the most recent (innermost)
@@ -735,12 +846,13 @@ impl TransactionManager {
         Ok(())
     }
 
-    /// Classify the latest buffered event (if any) for a PK in this
-    /// transaction. Returns `None` when the buffer has no event for
-    /// the PK — the caller then falls back to committed-state lookup.
+    /// Classify the latest buffered event (if any) for a PK of `table`
+    /// in this transaction. Returns `None` when the buffer has no event
+    /// for the row — the caller then falls back to committed-state lookup.
     pub fn write_set_event_kind(
         &self,
         txn_id: u64,
+        table: &str,
         pk_str: &str,
     ) -> Result<Option<crate::engine::BufferEventKind>> {
         let active_txns = self.active_transactions.read();
@@ -748,14 +860,13 @@ impl TransactionManager {
             .get(&txn_id)
             .ok_or_else(|| DriftError::Other(format!("Transaction {} not found", txn_id)))?;
         let txn_guard = txn.lock();
-        Ok(txn_guard.write_set.get(pk_str).map(|event| {
-            match event.event_type {
-                crate::events::EventType::SoftDelete => {
-                    crate::engine::BufferEventKind::Deleted
-                }
+        Ok(txn_guard
+            .write_set
+            .get(&row_key(table, pk_str))
+            .map(|event| match event.event_type {
+                crate::events::EventType::SoftDelete => crate::engine::BufferEventKind::Deleted,
                 _ => crate::engine::BufferEventKind::Active,
-            }
-        }))
+            }))
     }
 
     pub fn simple_commit(&mut self, txn_id: u64) -> Result<Vec<Event>> {
@@ -873,6 +984,13 @@ mod tests {
         // Txn 2 tries to get key1 (would cause deadlock)
         let result = lock_mgr.acquire_write_lock(2, "key1");
         assert!(result.is_err());
-        assert!(result.unwrap_err().to_string().contains("Deadlock"));
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Deadlock"));
+        assert_eq!(
+            err.to_string(),
+            "Deadlock detected: transaction 2 waits for a lock on key1 held by transaction 1; \
+             transaction 1 waits for a lock on key2 held by transaction 2; \
+             transaction 2 was aborted"
+        );
     }
 }
@@ -266,6 +266,7 @@ impl TransactionCoordinator {
     fn is_retryable_error(&self, error: &DriftError) -> bool {
         match error {
             DriftError::Lock(_) => true, // Lock conflicts are retryable
+            DriftError::Deadlock(_) => true,
             DriftError::Other(msg) if msg.contains("conflict") => true,
             DriftError::Other(msg) if msg.contains("timeout") => true,
             DriftError::Other(msg) if msg.contains("validation failed") => true,
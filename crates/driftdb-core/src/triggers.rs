@@ -76,6 +76,29 @@ pub enum TriggerAction {
     },
     /// Send notification
     Notify { channel: String, payload: Value },
+    /// Run a trigger function created with `CREATE FUNCTION ... RETURNS TRIGGER`.
+    /// The body is executed by the SQL bridge so its statements join the
+    /// transaction of the statement that fired the trigger.
+    ExecuteFunction(String),
+}
+
+/// A SQL trigger function, registered via
+/// `CREATE FUNCTION name() RETURNS TRIGGER AS $$ ... $$`.
+///
+/// The body is a `;`-separated list of statements. Besides ordinary SQL
+/// (which may reference `NEW.col` / `OLD.col`), the body understands:
+/// - `NEW.col := expr` — rewrite a column of the incoming row (BEFORE only)
+/// - `RAISE EXCEPTION 'message'` — reject the row and fail the statement
+/// - `RETURN NULL` — silently skip the row (BEFORE only)
+/// - `RETURN NEW` / `RETURN OLD` — stop executing the body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerFunction {
+    /// Function name
+    pub name: String,
+    /// Raw function body (the text between the `$$` delimiters)
+    pub body: String,
+    /// Creation timestamp
+    pub created_at: SystemTime,
 }
 
 /// Trigger definition
@@ -107,6 +130,15 @@ pub struct TriggerDefinition {
     pub description: Option<String>,
 }
 
+/// The on-disk form of the trigger catalog (`triggers.json`). Functions
+/// come first so loading can recreate them before the triggers that
+/// call them.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SavedTriggers {
+    pub functions: Vec<TriggerFunction>,
+    pub triggers: Vec<TriggerDefinition>,
+}
+
 /// Trigger execution context
 #[derive(Debug, Clone)]
 pub struct TriggerContext {
@@ -141,12 +173,30 @@ pub enum TriggerResult {
     ModifyRow(Value),
 }
 
+/// Engine-side hooks the trigger manager calls back into while firing
+/// triggers. The manager itself has no engine handle; the SQL bridge
+/// implements this so trigger work runs in the firing statement's session.
+pub trait TriggerRuntime {
+    /// Run the named trigger function for one row
+    fn run_function(
+        &mut self,
+        function: &str,
+        context: &TriggerContext,
+        timing: TriggerTiming,
+    ) -> Result<TriggerResult>;
+
+    /// Evaluate a trigger's WHEN condition against the OLD/NEW rows
+    fn evaluate_condition(&mut self, condition: &str, context: &TriggerContext) -> Result<bool>;
+}
+
 /// Trigger manager for handling all triggers in the database
 pub struct TriggerManager {
     /// All trigger definitions by table
     triggers_by_table: Arc<RwLock<HashMap<String, Vec<TriggerDefinition>>>>,
     /// All triggers by name
     triggers_by_name: Arc<RwLock<HashMap<String, TriggerDefinition>>>,
+    /// Trigger functions by name
+    functions: Arc<RwLock<HashMap<String, TriggerFunction>>>,
     /// Trigger execution statistics
     stats: Arc<RwLock<TriggerStatistics>>,
     /// Maximum recursion depth for cascading triggers
@@ -182,6 +232,7 @@ impl TriggerManager {
         Self {
             triggers_by_table: Arc::new(RwLock::new(HashMap::new())),
             triggers_by_name: Arc::new(RwLock::new(HashMap::new())),
+            functions: Arc::new(RwLock::new(HashMap::new())),
             stats: Arc::new(RwLock::new(TriggerStatistics::default())),
             max_recursion_depth: 16,
             recursion_depth: Arc::new(RwLock::new(HashMap::new())),
@@ -205,6 +256,16 @@ impl TriggerManager {
             trigger_name, table_name
         );
 
+        // Trigger functions must exist before a trigger can reference them
+        if let TriggerAction::ExecuteFunction(function) = &definition.action {
+            if !self.functions.read().contains_key(function) {
+                return Err(DriftError::InvalidQuery(format!(
+                    "function {}() does not exist",
+                    function
+                )));
+            }
+        }
+
         // Check if trigger already exists
         {
             let triggers = self.triggers_by_name.read();
@@ -333,6 +394,19 @@ impl TriggerManager {
         &self,
         context: &TriggerContext,
         timing: TriggerTiming,
+    ) -> Result<TriggerResult> {
+        self.execute_triggers_with(context, timing, None)
+    }
+
+    /// Execute triggers for an event, delegating WHEN conditions and
+    /// `ExecuteFunction` actions to `runtime` when one is supplied. The SQL
+    /// bridge passes a runtime that evaluates against the live engine within
+    /// the caller's transaction.
+    pub fn execute_triggers_with(
+        &self,
+        context: &TriggerContext,
+        timing: TriggerTiming,
+        runtime: Option<&mut dyn TriggerRuntime>,
     ) -> Result<TriggerResult> {
         let triggers = {
             let by_table = self.triggers_by_table.read();
@@ -342,12 +416,7 @@ impl TriggerManager {
         // Filter triggers that should fire
         let applicable_triggers: Vec<_> = triggers
             .iter()
-            .filter(|t| {
-                t.enabled
-                    && t.timing == timing
-                    && t.events.contains(&context.event)
-                    && self.evaluate_when_condition(t, context)
-            })
+            .filter(|t| t.enabled && t.timing == timing && t.events.contains(&context.event))
             .collect();
 
         if applicable_triggers.is_empty() {
@@ -375,25 +444,72 @@ impl TriggerManager {
             *depth += 1;
         }
 
+        let result = self.run_applicable_triggers(&applicable_triggers, context, timing, runtime);
+
+        // Clean up recursion tracking. This runs on every exit path so an
+        // aborted trigger doesn't leave the transaction's depth inflated.
+        if let Some(txn_id) = context.transaction_id {
+            let mut depths = self.recursion_depth.write();
+            if let Some(depth) = depths.get_mut(&txn_id) {
+                *depth = depth.saturating_sub(1);
+                if *depth == 0 {
+                    depths.remove(&txn_id);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Run each applicable trigger in order, folding their results
+    fn run_applicable_triggers(
+        &self,
+        triggers: &[&TriggerDefinition],
+        context: &TriggerContext,
+        timing: TriggerTiming,
+        mut runtime: Option<&mut dyn TriggerRuntime>,
+    ) -> Result<TriggerResult> {
         let mut result = TriggerResult::Continue;
+        // Each BEFORE trigger sees the row as modified by the triggers that
+        // ran ahead of it, so chained rewrites compose.
+        let mut current = context.clone();
+
+        for trigger in triggers {
+            let fires = match (&trigger.when_condition, runtime.as_mut()) {
+                (Some(condition), Some(rt)) => rt.evaluate_condition(condition, &current)?,
+                _ => self.evaluate_when_condition(trigger, &current),
+            };
+            if !fires {
+                continue;
+            }
 
-        for trigger in applicable_triggers {
             let start = std::time::Instant::now();
 
-            match self.execute_single_trigger(trigger, context) {
+            let outcome = match (&trigger.action, runtime.as_mut()) {
+                (TriggerAction::ExecuteFunction(function), Some(rt)) => {
+                    rt.run_function(function, &current, timing)
+                }
+                _ => self.execute_single_trigger(trigger, &current),
+            };
+
+            match outcome {
                 Ok(TriggerResult::Continue) => {}
                 Ok(TriggerResult::Skip) => {
                     if trigger.level == TriggerLevel::Row {
+                        self.stats.write().skipped_rows += 1;
                         result = TriggerResult::Skip;
+                        self.update_stats(true, start.elapsed().as_millis() as f64);
                         break;
                     }
                 }
                 Ok(TriggerResult::Abort(msg)) => {
+                    self.stats.write().aborted_operations += 1;
                     self.update_stats(false, start.elapsed().as_millis() as f64);
                     return Ok(TriggerResult::Abort(msg));
                 }
                 Ok(TriggerResult::ModifyRow(new_row)) => {
                     if timing == TriggerTiming::Before {
+                        current.new_row = Some(new_row.clone());
                         result = TriggerResult::ModifyRow(new_row);
                     }
                 }
@@ -407,17 +523,6 @@ impl TriggerManager {
             self.update_stats(true, start.elapsed().as_millis() as f64);
         }
 
-        // Clean up recursion tracking
-        if let Some(txn_id) = context.transaction_id {
-            let mut depths = self.recursion_depth.write();
-            if let Some(depth) = depths.get_mut(&txn_id) {
-                *depth = depth.saturating_sub(1);
-                if *depth == 0 {
-                    depths.remove(&txn_id);
-                }
-            }
-        }
-
         Ok(result)
     }
 
@@ -434,36 +539,11 @@ impl TriggerManager {
                 // Execute SQL statement
                 if let Some(ref engine_arc) = self.engine {
                     // Replace placeholders in SQL with trigger context values
-                    let mut bound_sql = sql.clone();
-
-                    // Replace OLD and NEW row references
-                    if let Some(ref old_row) = context.old_row {
-                        for (key, value) in old_row.as_object().unwrap_or(&serde_json::Map::new()) {
-                            let placeholder = format!("OLD.{}", key);
-                            let value_str = match value {
-                                Value::String(s) => format!("'{}'", s.replace('\'', "''")),
-                                Value::Number(n) => n.to_string(),
-                                Value::Bool(b) => b.to_string(),
-                                Value::Null => "NULL".to_string(),
-                                _ => format!("'{}'", value.to_string().replace('\'', "''")),
-                            };
-                            bound_sql = bound_sql.replace(&placeholder, &value_str);
-                        }
-                    }
-
-                    if let Some(ref new_row) = context.new_row {
-                        for (key, value) in new_row.as_object().unwrap_or(&serde_json::Map::new()) {
-                            let placeholder = format!("NEW.{}", key);
-                            let value_str = match value {
-                                Value::String(s) => format!("'{}'", s.replace('\'', "''")),
-                                Value::Number(n) => n.to_string(),
-                                Value::Bool(b) => b.to_string(),
-                                Value::Null => "NULL".to_string(),
-                                _ => format!("'{}'", value.to_string().replace('\'', "''")),
-                            };
-                            bound_sql = bound_sql.replace(&placeholder, &value_str);
-                        }
-                    }
+                    let bound_sql = bind_row_references(
+                        sql,
+                        context.old_row.as_ref(),
+                        context.new_row.as_ref(),
+                    );
 
                     debug!("Executing trigger SQL: {}", bound_sql);
 
@@ -558,6 +638,12 @@ impl TriggerManager {
                 debug!("Would notify channel '{}' with payload", channel);
                 Ok(TriggerResult::Continue)
             }
+            TriggerAction::ExecuteFunction(func_name) => {
+                // Dispatched through the `TriggerRuntime` passed to
+                // `execute_triggers_with`; nothing to do without one.
+                debug!("Trigger function '{}' has no runner", func_name);
+                Ok(TriggerResult::Continue)
+            }
         }
     }
 
@@ -740,6 +826,66 @@ impl TriggerManager {
             (total_time + execution_time_ms) / stats.total_executions as f64;
     }
 
+    /// Create (or replace) a trigger function
+    pub fn create_function(&self, function: TriggerFunction, or_replace: bool) -> Result<()> {
+        let mut functions = self.functions.write();
+        if functions.contains_key(&function.name) && !or_replace {
+            return Err(DriftError::InvalidQuery(format!(
+                "function {}() already exists",
+                function.name
+            )));
+        }
+        info!("Trigger function '{}' created", function.name);
+        functions.insert(function.name.clone(), function);
+        Ok(())
+    }
+
+    /// Drop a trigger function. Fails while any trigger still references it.
+    pub fn drop_function(&self, name: &str) -> Result<()> {
+        let dependents: Vec<String> = self
+            .triggers_by_name
+            .read()
+            .values()
+            .filter(|t| matches!(&t.action, TriggerAction::ExecuteFunction(f) if f == name))
+            .map(|t| t.name.clone())
+            .collect();
+        if !dependents.is_empty() {
+            return Err(DriftError::InvalidQuery(format!(
+                "cannot drop function {}() because trigger(s) {} depend on it",
+                name,
+                dependents.join(", ")
+            )));
+        }
+
+        self.functions.write().remove(name).ok_or_else(|| {
+            DriftError::InvalidQuery(format!("function {}() does not exist", name))
+        })?;
+        info!("Trigger function '{}' dropped", name);
+        Ok(())
+    }
+
+    /// Get trigger function by name
+    pub fn get_function(&self, name: &str) -> Option<TriggerFunction> {
+        self.functions.read().get(name).cloned()
+    }
+
+    /// The trigger catalog as written to disk. Triggers keep their
+    /// per-table creation order, which is the order they fire in.
+    pub(crate) fn saved(&self) -> SavedTriggers {
+        let mut functions: Vec<TriggerFunction> = self.functions.read().values().cloned().collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        let triggers = self
+            .triggers_by_table
+            .read()
+            .values()
+            .flat_map(|triggers| triggers.iter().cloned())
+            .collect();
+        SavedTriggers {
+            functions,
+            triggers,
+        }
+    }
+
     /// Get trigger by name
     pub fn get_trigger(&self, trigger_name: &str) -> Option<TriggerDefinition> {
         self.triggers_by_name.read().get(trigger_name).cloned()
@@ -861,6 +1007,253 @@ impl TriggerBuilder {
     }
 }
 
+/// Render a JSON value as a SQL literal for substitution into trigger SQL
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Number(n) => n.to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Null => "NULL".to_string(),
+        _ => format!("'{}'", value.to_string().replace('\'', "''")),
+    }
+}
+
+/// Replace `OLD.col` / `NEW.col` references in `sql` with literals taken from
+/// the trigger's rows. Longer column names are substituted first so `NEW.id`
+/// can't clobber the prefix of `NEW.id_hash`.
+pub fn bind_row_references(sql: &str, old_row: Option<&Value>, new_row: Option<&Value>) -> String {
+    let mut bound = sql.to_string();
+    for (prefix, row) in [("OLD", old_row), ("NEW", new_row)] {
+        let Some(obj) = row.and_then(|r| r.as_object()) else {
+            continue;
+        };
+        let mut keys: Vec<&String> = obj.keys().collect();
+        keys.sort_by_key(|k| std::cmp::Reverse(k.len()));
+        for key in keys {
+            let literal = sql_literal(&obj[key]);
+            bound = bound.replace(&format!("{}.{}", prefix, key), &literal);
+            bound = bound.replace(&format!("{}.{}", prefix.to_lowercase(), key), &literal);
+        }
+    }
+    bound
+}
+
+/// Split a trigger function body into statements on `;`, ignoring
+/// semicolons inside single-quoted strings.
+pub fn split_function_body(body: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for ch in body.chars() {
+        match ch {
+            '\'' => {
+                in_quotes = !in_quotes;
+                current.push(ch);
+            }
+            ';' if !in_quotes => {
+                if !current.trim().is_empty() {
+                    statements.push(current.trim().to_string());
+                }
+                current.clear();
+            }
+            _ => current.push(ch),
+        }
+    }
+    if !current.trim().is_empty() {
+        statements.push(current.trim().to_string());
+    }
+    statements
+}
+
+/// Parse `CREATE [OR REPLACE] FUNCTION name() RETURNS TRIGGER [LANGUAGE SQL]
+/// AS $$ body $$ [LANGUAGE SQL]`. Returns the function and whether
+/// `OR REPLACE` was given.
+pub fn parse_create_function(sql: &str) -> Result<(TriggerFunction, bool)> {
+    let invalid = |msg: &str| DriftError::InvalidQuery(format!("CREATE FUNCTION: {}", msg));
+
+    let open = sql
+        .find("$$")
+        .ok_or_else(|| invalid("body must be enclosed in $$ ... $$"))?;
+    let close = sql[open + 2..]
+        .find("$$")
+        .map(|i| open + 2 + i)
+        .ok_or_else(|| invalid("unterminated $$ body"))?;
+    let body = sql[open + 2..close].trim().to_string();
+
+    let header = sql[..open].trim();
+    let upper = header.to_uppercase();
+    let tokens: Vec<&str> = upper.split_whitespace().collect();
+    let or_replace = tokens.get(1) == Some(&"OR") && tokens.get(2) == Some(&"REPLACE");
+
+    let after_keyword = upper
+        .find("FUNCTION")
+        .map(|i| i + "FUNCTION".len())
+        .ok_or_else(|| invalid("expected FUNCTION keyword"))?;
+    let paren = header[after_keyword..]
+        .find('(')
+        .map(|i| after_keyword + i)
+        .ok_or_else(|| invalid("expected argument list"))?;
+    let name = header[after_keyword..paren].trim().to_string();
+    if name.is_empty() {
+        return Err(invalid("missing function name"));
+    }
+
+    let rest = &upper[paren..];
+    if !rest.starts_with("()") {
+        return Err(invalid("trigger functions take no arguments"));
+    }
+    if !rest.contains("RETURNS TRIGGER") {
+        return Err(invalid("only RETURNS TRIGGER functions are supported"));
+    }
+    let trailer = sql[close + 2..].trim().trim_end_matches(';').to_uppercase();
+    let language = [rest, trailer.as_str()]
+        .iter()
+        .find_map(|part| part.split("LANGUAGE").nth(1))
+        .map(|l| l.split_whitespace().next().unwrap_or("").to_string());
+    if let Some(lang) = language {
+        if lang != "SQL" && lang != "PLPGSQL" {
+            return Err(invalid(&format!("unsupported language {}", lang)));
+        }
+    }
+
+    Ok((
+        TriggerFunction {
+            name,
+            body,
+            created_at: SystemTime::now(),
+        },
+        or_replace,
+    ))
+}
+
+/// Parse `CREATE [OR REPLACE] TRIGGER name {BEFORE | AFTER} event [OR event ...]
+/// ON table [FOR [EACH] ROW] [WHEN (condition)] EXECUTE {FUNCTION | PROCEDURE} f()`.
+/// Returns the definition and whether `OR REPLACE` was given.
+pub fn parse_create_trigger(sql: &str) -> Result<(TriggerDefinition, bool)> {
+    let invalid = |msg: &str| DriftError::InvalidQuery(format!("CREATE TRIGGER: {}", msg));
+
+    let sql = sql.trim().trim_end_matches(';');
+    let upper = sql.to_uppercase();
+
+    let exec_pos = upper
+        .find(" EXECUTE FUNCTION ")
+        .map(|i| (i, " EXECUTE FUNCTION ".len()))
+        .or_else(|| {
+            upper
+                .find(" EXECUTE PROCEDURE ")
+                .map(|i| (i, " EXECUTE PROCEDURE ".len()))
+        })
+        .ok_or_else(|| invalid("expected EXECUTE FUNCTION name()"))?;
+    let call = sql[exec_pos.0 + exec_pos.1..].trim();
+    let function = call
+        .split('(')
+        .next()
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .ok_or_else(|| invalid("missing function name"))?;
+
+    let mut head = &sql[..exec_pos.0];
+    let mut when_condition = None;
+    if let Some(when_pos) = head.to_uppercase().find(" WHEN ") {
+        let condition = head[when_pos + " WHEN ".len()..].trim();
+        let condition = condition
+            .strip_prefix('(')
+            .and_then(|c| c.strip_suffix(')'))
+            .unwrap_or(condition);
+        when_condition = Some(condition.trim().to_string());
+        head = &head[..when_pos];
+    }
+
+    let tokens: Vec<&str> = head.split_whitespace().collect();
+    let keyword = |i: usize| tokens.get(i).map(|t| t.to_uppercase()).unwrap_or_default();
+
+    let mut pos = 1;
+    let or_replace = keyword(1) == "OR" && keyword(2) == "REPLACE";
+    if or_replace {
+        pos += 2;
+    }
+    if keyword(pos) != "TRIGGER" {
+        return Err(invalid("expected TRIGGER keyword"));
+    }
+    pos += 1;
+    let name = tokens
+        .get(pos)
+        .ok_or_else(|| invalid("missing trigger name"))?
+        .to_string();
+    pos += 1;
+
+    let timing = match keyword(pos).as_str() {
+        "BEFORE" => TriggerTiming::Before,
+        "AFTER" => TriggerTiming::After,
+        "INSTEAD" => {
+            return Err(invalid("INSTEAD OF triggers are not supported on tables"));
+        }
+        other => {
+            return Err(invalid(&format!(
+                "expected BEFORE or AFTER, found '{}'",
+                other
+            )))
+        }
+    };
+    pos += 1;
+
+    let mut events = Vec::new();
+    loop {
+        events.push(match keyword(pos).as_str() {
+            "INSERT" => TriggerEvent::Insert,
+            "UPDATE" => TriggerEvent::Update,
+            "DELETE" => TriggerEvent::Delete,
+            other => return Err(invalid(&format!("unsupported trigger event '{}'", other))),
+        });
+        pos += 1;
+        if keyword(pos) == "OR" {
+            pos += 1;
+        } else {
+            break;
+        }
+    }
+
+    if keyword(pos) != "ON" {
+        return Err(invalid("expected ON table"));
+    }
+    pos += 1;
+    let table = tokens
+        .get(pos)
+        .ok_or_else(|| invalid("missing table name"))?
+        .to_string();
+    pos += 1;
+
+    if keyword(pos) == "FOR" {
+        pos += 1;
+        if keyword(pos) == "EACH" {
+            pos += 1;
+        }
+        match keyword(pos).as_str() {
+            "ROW" => {}
+            "STATEMENT" => {
+                return Err(invalid("FOR EACH STATEMENT triggers are not yet supported"));
+            }
+            other => return Err(invalid(&format!("expected ROW, found '{}'", other))),
+        }
+        pos += 1;
+    }
+    if pos < tokens.len() {
+        return Err(invalid(&format!("unexpected '{}'", tokens[pos])));
+    }
+
+    let mut builder = TriggerBuilder::new(name, table)
+        .timing(timing)
+        .level(TriggerLevel::Row)
+        .action(TriggerAction::ExecuteFunction(function));
+    for event in events {
+        builder = builder.on_event(event);
+    }
+    if let Some(condition) = when_condition {
+        builder = builder.when_condition(condition);
+    }
+    Ok((builder.build()?, or_replace))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
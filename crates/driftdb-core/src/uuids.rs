@@ -0,0 +1,93 @@
+//! UUID columns
+//!
+//! A `UUID` column stores each value as its canonical text: 36 characters,
+//! lowercase hex, hyphenated. Literals are accepted in any of the forms
+//! PostgreSQL reads (upper case, braces, no hyphens) and normalized on the
+//! way in, so equal UUIDs are always equal strings. Lowercase fixed-width
+//! hex sorts the same way as the 128-bit value, which keeps `ORDER BY`,
+//! range comparisons and the B-tree indexes correct without a separate
+//! binary encoding.
+
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::errors::{DriftError, Result};
+
+/// Column types stored as UUIDs
+pub fn is_uuid_type(col_type: &str) -> bool {
+    col_type.eq_ignore_ascii_case("UUID")
+}
+
+/// Validate a value written into a UUID column and return its canonical
+/// form. NULL passes through.
+pub fn parse(value: Value) -> Result<Value> {
+    match value {
+        Value::Null => Ok(Value::Null),
+        Value::String(text) => match Uuid::parse_str(text.trim()) {
+            Ok(uuid) if !text.trim_start().starts_with("urn:") => {
+                Ok(Value::String(uuid.hyphenated().to_string()))
+            }
+            _ => Err(invalid(&text)),
+        },
+        other => Err(invalid(&other.to_string())),
+    }
+}
+
+/// `gen_random_uuid()`: a random (version 4) UUID
+pub fn generate() -> Value {
+    Value::String(Uuid::new_v4().hyphenated().to_string())
+}
+
+fn invalid(text: &str) -> DriftError {
+    DriftError::InvalidQuery(format!("invalid input syntax for type uuid: \"{}\"", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_accepts_postgres_input_forms() {
+        let canonical = json!("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11");
+        for text in [
+            "a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11",
+            "A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11",
+            "{a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}",
+            "a0eebc999c0b4ef8bb6d6bb9bd380a11",
+        ] {
+            assert_eq!(parse(json!(text)).unwrap(), canonical, "{}", text);
+        }
+        assert_eq!(parse(Value::Null).unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_rejects_malformed_values() {
+        for value in [
+            json!("not-a-uuid"),
+            json!("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1"),
+            json!("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a1g"),
+            json!("urn:uuid:a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11"),
+            json!(42),
+        ] {
+            let err = parse(value).unwrap_err();
+            assert!(err.to_string().contains("type uuid"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_generated_uuids_are_canonical_and_distinct() {
+        let a = generate();
+        let b = generate();
+        assert_ne!(a, b);
+        assert_eq!(parse(a.clone()).unwrap(), a);
+        assert_eq!(a.as_str().unwrap().chars().nth(14), Some('4'));
+    }
+
+    #[test]
+    fn test_text_order_matches_value_order() {
+        let low = parse(json!("0FFFFFFF-FFFF-FFFF-FFFF-FFFFFFFFFFFF")).unwrap();
+        let high = parse(json!("a0000000-0000-0000-0000-000000000000")).unwrap();
+        assert!(low.as_str().unwrap() < high.as_str().unwrap());
+    }
+}
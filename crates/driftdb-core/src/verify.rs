@@ -0,0 +1,263 @@
+//! Re-checking declared constraints against current data
+//!
+//! Writes through SQL enforce primary keys, foreign keys and `CHECK`
+//! constraints as they happen, but rows can arrive without those checks:
+//! events applied directly, a restore, or a bulk import. [`Engine::verify_constraints`]
+//! re-checks every constraint on a table against its current rows and
+//! reports each violating row by its key.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::engine::Engine;
+use crate::errors::{DriftError, Result};
+use crate::fk::ForeignKey;
+use crate::schema::CheckConstraint;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConstraintKind {
+    PrimaryKey,
+    ForeignKey,
+    Check,
+}
+
+/// One row breaking one constraint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Violation {
+    pub table: String,
+    pub constraint: String,
+    pub kind: ConstraintKind,
+    /// The row's primary key, or the key it is stored under when the row
+    /// has none
+    pub key: Value,
+    pub row: Value,
+    pub detail: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}: key {}: {}",
+            self.table, self.constraint, self.key, self.detail
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConstraintReport {
+    pub tables_checked: usize,
+    pub constraints_checked: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl ConstraintReport {
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// The report as text, one violation per line
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![format!(
+            "Checked {} constraints on {} tables",
+            self.constraints_checked, self.tables_checked
+        )];
+        if self.is_clean() {
+            lines.push("No violations found".to_string());
+        }
+        lines.extend(self.violations.iter().map(|v| v.to_string()));
+        lines
+    }
+
+    fn merge(&mut self, other: ConstraintReport) {
+        self.tables_checked += other.tables_checked;
+        self.constraints_checked += other.constraints_checked;
+        self.violations.extend(other.violations);
+    }
+}
+
+impl Engine {
+    /// Re-check `table`'s primary key, foreign keys and `CHECK`
+    /// constraints against its current rows
+    pub fn verify_constraints(&self, table: &str) -> Result<ConstraintReport> {
+        let storage = self
+            .tables
+            .get(table)
+            .ok_or_else(|| DriftError::TableNotFound(table.to_string()))?;
+        let schema = storage.schema().clone();
+        // Rows in key order, so reports are stable from run to run
+        let rows: BTreeMap<String, Value> =
+            storage.reconstruct_state_at(None)?.into_iter().collect();
+
+        let mut report = ConstraintReport {
+            tables_checked: 1,
+            constraints_checked: 1 + schema.foreign_keys.len() + schema.checks.len(),
+            violations: Vec::new(),
+        };
+        report
+            .violations
+            .extend(check_primary_key(table, &schema.primary_key, &rows));
+        for fk in &schema.foreign_keys {
+            report.violations.extend(self.check_foreign_key(
+                table,
+                &schema.primary_key,
+                fk,
+                &rows,
+            )?);
+        }
+        for check in &schema.checks {
+            report
+                .violations
+                .extend(check_constraint(table, &schema.primary_key, check, &rows)?);
+        }
+        Ok(report)
+    }
+
+    /// [`Engine::verify_constraints`] for every table
+    pub fn verify_all_constraints(&self) -> Result<ConstraintReport> {
+        let mut tables: Vec<&String> = self.tables.keys().collect();
+        tables.sort();
+        let mut report = ConstraintReport::default();
+        for table in tables {
+            report.merge(self.verify_constraints(table)?);
+        }
+        Ok(report)
+    }
+
+    fn check_foreign_key(
+        &self,
+        table: &str,
+        primary_key: &str,
+        fk: &ForeignKey,
+        rows: &BTreeMap<String, Value>,
+    ) -> Result<Vec<Violation>> {
+        // A parent table that no longer exists matches nothing
+        let parents: HashSet<String> = match self.get_table_data(&fk.ref_table) {
+            Ok(parent_rows) => parent_rows
+                .iter()
+                .filter_map(|row| row.get(&fk.ref_column))
+                .filter(|value| !value.is_null())
+                .map(|value| value.to_string())
+                .collect(),
+            Err(DriftError::TableNotFound(_)) => HashSet::new(),
+            Err(e) => return Err(e),
+        };
+        let constraint = format!("{}_{}_fkey", table, fk.column);
+
+        Ok(rows
+            .iter()
+            .filter_map(|(stored_key, row)| {
+                // NULL references nothing, as with MATCH SIMPLE on insert
+                let value = row.get(&fk.column).filter(|v| !v.is_null())?;
+                if parents.contains(&value.to_string()) {
+                    return None;
+                }
+                Some(Violation {
+                    table: table.to_string(),
+                    constraint: constraint.clone(),
+                    kind: ConstraintKind::ForeignKey,
+                    key: row_key(primary_key, stored_key, row),
+                    row: row.clone(),
+                    detail: format!(
+                        "{} = {} has no match in {}.{}",
+                        fk.column, value, fk.ref_table, fk.ref_column
+                    ),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Every row needs a non-NULL primary key that no other row shares and
+/// that matches the key the row is stored under
+fn check_primary_key(
+    table: &str,
+    primary_key: &str,
+    rows: &BTreeMap<String, Value>,
+) -> Vec<Violation> {
+    let constraint = format!("{}_pkey", table);
+    let violation = |stored_key: &String, row: &Value, detail: String| Violation {
+        table: table.to_string(),
+        constraint: constraint.clone(),
+        kind: ConstraintKind::PrimaryKey,
+        key: row_key(primary_key, stored_key, row),
+        row: row.clone(),
+        detail,
+    };
+
+    let mut by_key: BTreeMap<String, Vec<(&String, &Value)>> = BTreeMap::new();
+    let mut violations = Vec::new();
+    for (stored_key, row) in rows {
+        match row.get(primary_key).filter(|v| !v.is_null()) {
+            Some(value) => by_key
+                .entry(value.to_string())
+                .or_default()
+                .push((stored_key, row)),
+            None => violations.push(violation(
+                stored_key,
+                row,
+                format!("{} is NULL", primary_key),
+            )),
+        }
+    }
+    for (key, holders) in by_key {
+        if holders.len() > 1 {
+            for &(stored_key, row) in &holders {
+                violations.push(violation(
+                    stored_key,
+                    row,
+                    format!(
+                        "{} = {} is shared by {} rows",
+                        primary_key,
+                        key,
+                        holders.len()
+                    ),
+                ));
+            }
+        } else if let Some(&(stored_key, row)) = holders.first() {
+            if *stored_key != key {
+                violations.push(violation(
+                    stored_key,
+                    row,
+                    format!(
+                        "stored under key {} but {} = {}",
+                        stored_key, primary_key, key
+                    ),
+                ));
+            }
+        }
+    }
+    violations
+}
+
+fn check_constraint(
+    table: &str,
+    primary_key: &str,
+    check: &CheckConstraint,
+    rows: &BTreeMap<String, Value>,
+) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+    for (stored_key, row) in rows {
+        if !crate::sql_bridge::check_constraint_holds(check, row)? {
+            violations.push(Violation {
+                table: table.to_string(),
+                constraint: check.name.clone(),
+                kind: ConstraintKind::Check,
+                key: row_key(primary_key, stored_key, row),
+                row: row.clone(),
+                detail: format!("fails CHECK ({})", check.expression),
+            });
+        }
+    }
+    Ok(violations)
+}
+
+fn row_key(primary_key: &str, stored_key: &str, row: &Value) -> Value {
+    match row.get(primary_key) {
+        Some(value) if !value.is_null() => value.clone(),
+        _ => Value::String(stored_key.to_string()),
+    }
+}
@@ -113,6 +113,65 @@ pub struct MaterializedViewData {
     pub row_count: usize,
     /// Approximate size in bytes
     pub size_bytes: usize,
+    /// Last event sequence of each source table the data reflects
+    pub source_sequences: HashMap<String, u64>,
+    /// Primary key of the source row behind each entry of `data`, for views
+    /// that can be refreshed incrementally
+    pub row_keys: Option<Vec<String>>,
+    /// Engine catalog version at refresh time
+    pub catalog_version: u64,
+}
+
+impl MaterializedViewData {
+    /// Contents computed now from sources at `source_sequences`
+    pub fn new(
+        data: Vec<Value>,
+        row_keys: Option<Vec<String>>,
+        source_sequences: HashMap<String, u64>,
+        catalog_version: u64,
+    ) -> Self {
+        Self {
+            row_count: data.len(),
+            size_bytes: data.iter().map(|v| v.to_string().len()).sum(),
+            data,
+            refreshed_at: SystemTime::now(),
+            source_sequences,
+            row_keys,
+            catalog_version,
+        }
+    }
+}
+
+/// How a materialized view refresh computes the new contents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshMode {
+    /// Recompute the view from scratch
+    Full,
+    /// Re-evaluate only the source rows changed since the last refresh
+    Incremental,
+}
+
+/// How far a materialized view lags behind its source tables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterializedViewStatus {
+    pub name: String,
+    /// Whether the view has been refreshed since it was created or loaded
+    pub populated: bool,
+    /// Highest source sequence the contents reflect
+    pub last_refresh_sequence: u64,
+    /// Highest sequence currently in the source tables
+    pub current_sequence: u64,
+    /// Source events appended since the last refresh
+    pub pending_events: u64,
+    /// Whether `REFRESH ... INCREMENTAL` can apply deltas for this view
+    pub incremental: bool,
+}
+
+impl MaterializedViewStatus {
+    /// Whether the sources changed since the last refresh
+    pub fn is_stale(&self) -> bool {
+        !self.populated || self.pending_events > 0
+    }
 }
 
 /// View manager for handling all views in the database
@@ -569,14 +628,7 @@ impl ViewManager {
             vec![]
         };
         let row_count = data.len();
-        let size_bytes = data.len() * 100; // Rough estimate
-
-        let materialized_data = MaterializedViewData {
-            data,
-            refreshed_at: SystemTime::now(),
-            row_count,
-            size_bytes,
-        };
+        let materialized_data = MaterializedViewData::new(data, None, HashMap::new(), 0);
 
         // Store materialized data
         let mut materialized = self.materialized_data.write();
@@ -717,14 +769,7 @@ impl ViewManager {
 
     /// Cache materialized view data
     pub fn cache_materialized_data(&self, view_name: &str, data: Vec<Value>) -> Result<()> {
-        let size_bytes = data.iter().map(|v| v.to_string().len()).sum();
-
-        let materialized = MaterializedViewData {
-            row_count: data.len(),
-            data,
-            refreshed_at: SystemTime::now(),
-            size_bytes,
-        };
+        let materialized = MaterializedViewData::new(data, None, HashMap::new(), 0);
 
         self.materialized_data
             .write()
@@ -744,6 +789,24 @@ impl ViewManager {
             .get(view_name)
             .map(|data| data.data.clone())
     }
+
+    /// Current contents of a materialized view, if it has been populated
+    pub fn materialized_contents(&self, view_name: &str) -> Option<MaterializedViewData> {
+        self.materialized_data.read().get(view_name).cloned()
+    }
+
+    /// Replace a materialized view's contents. The new contents are built
+    /// beforehand, so readers only wait for the swap and never see a
+    /// partially refreshed view.
+    pub fn replace_materialized_contents(&self, view_name: &str, data: MaterializedViewData) {
+        self.materialized_data
+            .write()
+            .insert(view_name.to_string(), data);
+
+        let mut stats = self.stats.write();
+        stats.refresh_count += 1;
+        stats.materialized_views_refreshed += 1;
+    }
 }
 
 /// Builder for creating view definitions
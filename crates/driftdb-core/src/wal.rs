@@ -11,6 +11,7 @@ use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+use crate::durability::{Durability, SyncMode, SyncTarget};
 use crate::errors::{DriftError, Result};
 // use crate::events::Event;
 
@@ -29,6 +30,26 @@ pub struct WalEntry {
     pub checksum: u32,
 }
 
+impl WalEntry {
+    /// Whether the stored checksum matches the entry's contents
+    pub fn has_valid_checksum(&self) -> bool {
+        checksum_of(self).is_ok_and(|checksum| checksum == self.checksum)
+    }
+}
+
+/// CRC32 of an entry serialized with its checksum field zeroed
+fn checksum_of(entry: &WalEntry) -> Result<u32> {
+    let entry_for_checksum = WalEntry {
+        checksum: 0,
+        ..entry.clone()
+    };
+
+    let serialized = serde_json::to_string(&entry_for_checksum)?;
+    let mut hasher = Hasher::new();
+    hasher.update(serialized.as_bytes());
+    Ok(hasher.finalize())
+}
+
 /// Types of operations that can be logged to WAL
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum WalOperation {
@@ -83,6 +104,8 @@ pub struct WalManager {
     sequence: Arc<Mutex<u64>>,
     /// WAL configuration
     config: WalConfig,
+    /// When `sync_on_write` fsyncs actually happen
+    durability: Arc<Durability>,
 }
 
 /// WAL configuration
@@ -121,6 +144,7 @@ impl WalManager {
             writer: Arc::new(Mutex::new(None)),
             sequence: Arc::new(Mutex::new(0)),
             config,
+            durability: Durability::new(SyncMode::Full),
         };
 
         // Initialize WAL file
@@ -129,6 +153,12 @@ impl WalManager {
         Ok(manager)
     }
 
+    /// Sync writes as `durability` says rather than on every write
+    pub fn with_durability(mut self, durability: Arc<Durability>) -> Self {
+        self.durability = durability;
+        self
+    }
+
     /// Initialize WAL file and recover sequence number
     fn init_wal(&self) -> Result<()> {
         // If WAL file exists, read it to find the latest sequence number
@@ -205,6 +235,7 @@ impl WalManager {
         let serialized = serde_json::to_string(&entry_with_checksum)?;
 
         // Write to WAL
+        let mode = self.durability.effective_mode();
         {
             let mut writer_guard = self.writer.lock().unwrap();
             if let Some(ref mut writer) = *writer_guard {
@@ -212,7 +243,9 @@ impl WalManager {
 
                 if self.config.sync_on_write {
                     writer.flush()?;
-                    writer.get_ref().sync_all()?; // Force to disk
+                    if mode == SyncMode::Full {
+                        writer.get_ref().sync_all()?; // Force to disk
+                    }
                 }
             } else {
                 return Err(DriftError::Internal(
@@ -220,22 +253,16 @@ impl WalManager {
                 ));
             }
         }
+        if self.config.sync_on_write && mode != SyncMode::Full {
+            self.durability.defer(self.writer.clone(), mode);
+        }
 
         Ok(sequence)
     }
 
     /// Calculate checksum for WAL entry
     fn calculate_checksum(&self, entry: &WalEntry) -> Result<u32> {
-        // Create entry without checksum for calculation
-        let entry_for_checksum = WalEntry {
-            checksum: 0,
-            ..entry.clone()
-        };
-
-        let serialized = serde_json::to_string(&entry_for_checksum)?;
-        let mut hasher = Hasher::new();
-        hasher.update(serialized.as_bytes());
-        Ok(hasher.finalize())
+        checksum_of(entry)
     }
 
     /// Verify checksum of a WAL entry
@@ -285,6 +312,11 @@ impl WalManager {
         Ok(entries)
     }
 
+    /// Path of the file a checkpoint moves the old log to
+    pub fn rotated_path(wal_path: &Path) -> PathBuf {
+        wal_path.with_extension("wal.old")
+    }
+
     /// Create a checkpoint (truncate WAL up to this point)
     pub fn checkpoint(&self, up_to_sequence: u64) -> Result<()> {
         // Log the checkpoint operation first
@@ -296,7 +328,7 @@ impl WalManager {
         let entries_to_keep = self.replay_from_sequence(up_to_sequence + 1)?;
 
         // Rotate WAL file
-        let backup_path = self.wal_path.with_extension("wal.old");
+        let backup_path = Self::rotated_path(&self.wal_path);
         std::fs::rename(&self.wal_path, backup_path)?;
 
         // Recreate WAL with only the entries after checkpoint
@@ -329,6 +361,17 @@ impl WalManager {
     }
 }
 
+impl SyncTarget for Mutex<Option<BufWriter<File>>> {
+    fn sync_to_disk(&self) -> Result<()> {
+        let mut writer_guard = self.lock().unwrap();
+        if let Some(ref mut writer) = *writer_guard {
+            writer.flush()?;
+            writer.get_ref().sync_all()?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,121 @@
+//! `ALTER TABLE ADD/DROP/RENAME COLUMN`: stored events are never rewritten,
+//! so older rows pick up defaults on read and time-travel queries see the
+//! columns the table had at that point.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+/// Each insert is its own event, so `items` sits at sequence 2 afterwards.
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE items (id VARCHAR, name VARCHAR, PRIMARY KEY (id))",
+        "INSERT INTO items (id, name) VALUES ('a', 'apple')",
+        "INSERT INTO items (id, name) VALUES ('b', 'banana')",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn add_column_fills_default_and_respects_as_of() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE items ADD COLUMN qty INT DEFAULT 0",
+        &mut ctx,
+    )
+    .unwrap();
+    execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO items (id, name, qty) VALUES ('c', 'cherry', 7)",
+        &mut ctx,
+    )
+    .unwrap();
+
+    let current = rows(&mut engine, &mut ctx, "SELECT * FROM items ORDER BY id");
+    assert_eq!(current[0]["qty"], 0);
+    assert_eq!(current[2]["qty"], 7);
+
+    let before = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM items FOR SYSTEM_TIME AS OF @SEQ:2",
+    );
+    assert_eq!(before.len(), 2);
+    assert!(before.iter().all(|r| r.get("qty").is_none()));
+
+    let after = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM items FOR SYSTEM_TIME AS OF @SEQ:3",
+    );
+    assert_eq!(after.len(), 3);
+    assert!(after.iter().all(|r| r.get("qty").is_some()));
+}
+
+#[test]
+fn drop_column_hides_it_but_keeps_history() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    execute_sql_in_session(&mut engine, "ALTER TABLE items DROP COLUMN name", &mut ctx).unwrap();
+
+    let current = rows(&mut engine, &mut ctx, "SELECT * FROM items");
+    assert!(current.iter().all(|r| r.get("name").is_none()));
+
+    let before = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM items FOR SYSTEM_TIME AS OF @SEQ:2 ORDER BY id",
+    );
+    assert_eq!(before[0]["name"], "apple");
+
+    // A dropped name can't come back: old events still carry its values.
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE items ADD COLUMN name VARCHAR",
+        &mut ctx
+    )
+    .is_err());
+}
+
+#[test]
+fn rename_column_moves_existing_values() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE items RENAME COLUMN name TO label",
+        &mut ctx,
+    )
+    .unwrap();
+
+    let current = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, label FROM items ORDER BY id",
+    );
+    assert_eq!(current[0]["label"], "apple");
+    assert_eq!(current[1]["label"], "banana");
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE items RENAME COLUMN id TO key",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("primary key"), "{}", err);
+}
@@ -0,0 +1,177 @@
+//! ARRAY columns: literals, subscripts, ANY/ALL, containment and overlap.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn ids(engine: &mut Engine, sql: &str) -> Vec<String> {
+    let mut ids: Vec<String> = rows(engine, sql)
+        .iter()
+        .map(|row| row["id"].as_str().unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE users (id VARCHAR, roles TEXT[], scores INT[], PRIMARY KEY (id))",
+        "INSERT INTO users (id, roles, scores) VALUES ('ann', ARRAY['admin', 'dev'], ARRAY[1, 2, 3])",
+        "INSERT INTO users (id, roles, scores) VALUES ('bob', '{dev,ops}', '{4,5}')",
+        "INSERT INTO users (id, roles, scores) VALUES ('cat', '{}', '{7}')",
+    ] {
+        run(&mut engine, sql);
+    }
+    engine
+}
+
+#[test]
+fn literals_are_stored_as_typed_arrays() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let bob = rows(&mut engine, "SELECT * FROM users WHERE id = 'bob'");
+    assert_eq!(bob[0]["roles"], json!(["dev", "ops"]));
+    assert_eq!(bob[0]["scores"], json!([4, 5]));
+
+    let mut ctx = SessionContext::new();
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO users (id, scores) VALUES ('bad', '{1,two}')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("invalid input syntax"), "{}", err);
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO users (id, scores) VALUES ('bad', '{1,2')",
+        &mut ctx,
+    )
+    .is_err());
+}
+
+#[test]
+fn subscripts_are_one_based() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let first = rows(
+        &mut engine,
+        "SELECT roles[1] AS first_role, scores[3] AS third FROM users WHERE id = 'ann'",
+    );
+    assert_eq!(first[0]["first_role"], json!("admin"));
+    assert_eq!(first[0]["third"], json!(3));
+
+    let out_of_range = rows(
+        &mut engine,
+        "SELECT roles[5] AS r FROM users WHERE id = 'ann'",
+    );
+    assert_eq!(out_of_range[0]["r"], serde_json::Value::Null);
+
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE roles[1] = 'dev'"),
+        vec!["bob"]
+    );
+}
+
+#[test]
+fn any_and_all() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM users WHERE 'admin' = ANY(roles)"
+        ),
+        vec!["ann"]
+    );
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE 'dev' = ANY(roles)"),
+        vec!["ann", "bob"]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM users WHERE 'dev' = ANY(roles) AND id <> 'ann'"
+        ),
+        vec!["bob"]
+    );
+    // ALL over an empty array is true
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE 4 <= ALL(scores)"),
+        vec!["bob", "cat"]
+    );
+}
+
+#[test]
+fn containment_and_overlap() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM users WHERE roles @> ARRAY['dev', 'admin']"
+        ),
+        vec!["ann"]
+    );
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE roles @> '{dev}'"),
+        vec!["ann", "bob"]
+    );
+    // Every array contains the empty array
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE roles @> '{}'"),
+        vec!["ann", "bob", "cat"]
+    );
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE '{3,1}' <@ scores"),
+        vec!["ann"]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM users WHERE scores && ARRAY[3, 4]"
+        ),
+        vec!["ann", "bob"]
+    );
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM users WHERE roles && '{qa}'"),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn update_replaces_an_array() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    run(
+        &mut engine,
+        "UPDATE users SET roles = '{admin}' WHERE id = 'cat'",
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM users WHERE 'admin' = ANY(roles)"
+        ),
+        vec!["ann", "cat"]
+    );
+}
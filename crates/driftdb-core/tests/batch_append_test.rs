@@ -0,0 +1,182 @@
+//! `Engine::append_events_batch`: many inserts checked together and
+//! written with one append, all or nothing.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql, execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn count(engine: &mut Engine, sql: &str) -> Value {
+    rows(engine, sql)[0]["count(*)"].clone()
+}
+
+fn items(range: std::ops::Range<i64>) -> Vec<Value> {
+    range
+        .map(|id| json!({"id": id, "category": format!("c{}", id % 3)}))
+        .collect()
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE items (id INT, category VARCHAR, PRIMARY KEY (id))",
+    )
+    .unwrap();
+    execute_sql(&mut engine, "CREATE INDEX idx_category ON items (category)").unwrap();
+    engine
+}
+
+#[test]
+fn batch_is_queryable_indexed_and_durable() {
+    let temp = TempDir::new().unwrap();
+    {
+        let mut engine = setup(&temp);
+        let before = engine.table_sequence("items").unwrap();
+        let last = engine.append_events_batch("items", items(0..100)).unwrap();
+        assert_eq!(last, before + 100);
+        assert_eq!(engine.table_sequence("items"), Some(last));
+
+        // A second batch continues after the first
+        engine
+            .append_events_batch("items", items(100..120))
+            .unwrap();
+        assert_eq!(
+            engine
+                .lookup_by_index("items", "category", &json!("c1"))
+                .unwrap()
+                .len(),
+            40
+        );
+    }
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(count(&mut engine, "SELECT COUNT(*) FROM items"), json!(120));
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT id FROM items WHERE category = 'c2' AND id < 9 ORDER BY id"
+        ),
+        vec![json!({"id": 2}), json!({"id": 5}), json!({"id": 8})]
+    );
+}
+
+#[test]
+fn batch_is_all_or_nothing() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    engine.append_events_batch("items", items(0..10)).unwrap();
+    let sequence = engine.table_sequence("items");
+
+    let mut duplicate_in_batch = items(10..20);
+    duplicate_in_batch.push(json!({"id": 12, "category": "again"}));
+    let mut taken_key = items(20..30);
+    taken_key.push(json!({"id": 3, "category": "again"}));
+    let mut missing_key = items(30..40);
+    missing_key.insert(5, json!({"category": "no key"}));
+    let mut not_an_object = items(40..50);
+    not_an_object.push(json!([1, 2]));
+
+    for (batch, message) in [
+        (duplicate_in_batch, "Primary key violation"),
+        (taken_key, "Primary key violation"),
+        (missing_key, "Missing primary key field 'id'"),
+        (not_an_object, "row is not a JSON object"),
+    ] {
+        let err = engine
+            .append_events_batch("items", batch)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(message), "{}", err);
+
+        // Nothing from the failed batch was written
+        assert_eq!(engine.table_sequence("items"), sequence);
+        assert_eq!(count(&mut engine, "SELECT COUNT(*) FROM items"), json!(10));
+    }
+
+    assert!(engine
+        .append_events_batch("missing", items(0..1))
+        .unwrap_err()
+        .to_string()
+        .contains("missing"));
+}
+
+#[test]
+fn committed_transaction_is_written_as_a_batch() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let before = engine.table_sequence("items").unwrap();
+
+    let values: Vec<String> = (0..50)
+        .map(|id| format!("({}, 'c{}')", id, id % 3))
+        .collect();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "BEGIN".to_string(),
+        format!(
+            "INSERT INTO items (id, category) VALUES {}",
+            values.join(", ")
+        ),
+        "COMMIT".to_string(),
+    ] {
+        execute_sql_in_session(&mut engine, &sql, &mut ctx).unwrap();
+    }
+
+    assert_eq!(engine.table_sequence("items"), Some(before + 50));
+    assert_eq!(
+        count(
+            &mut engine,
+            "SELECT COUNT(*) FROM items WHERE category = 'c0'"
+        ),
+        json!(17)
+    );
+}
+
+#[test]
+fn rows_are_checked_against_column_types() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE people (id INTEGER PRIMARY KEY, name VARCHAR(5), active BOOLEAN, \
+         score DOUBLE PRECISION)",
+    )
+    .unwrap();
+
+    let mut good = json!({"id": 1, "name": "Ann", "active": true, "score": 1.5});
+    assert_eq!(
+        engine.check_insert_row("people", &mut good).unwrap(),
+        json!(1)
+    );
+    // Checking writes nothing
+    assert_eq!(count(&mut engine, "SELECT COUNT(*) FROM people"), json!(0));
+
+    for (row, message) in [
+        (
+            json!({"id": "one"}),
+            "column \"id\" of relation \"people\" is of type integer",
+        ),
+        (json!({"id": 2, "name": "Bartholomew"}), "value too long"),
+        (json!({"id": 3, "active": "yes"}), "is of type boolean"),
+        (
+            json!({"id": 4, "score": "high"}),
+            "is of type double precision",
+        ),
+        (json!({"name": "Cy"}), "Missing primary key field 'id'"),
+    ] {
+        let mut row = row;
+        let err = engine
+            .check_insert_row("people", &mut row)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(message), "{}", err);
+    }
+}
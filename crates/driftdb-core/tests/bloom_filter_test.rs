@@ -0,0 +1,254 @@
+//! `ALTER TABLE ... SET (bloom_filter = on)`: each segment keeps a bloom
+//! filter of its primary keys, and a lookup by primary key reads only the
+//! segments that might hold the key. `bloom_filter_columns` does the same
+//! for other columns of a columnar table, per chunk of its projection.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::storage::columnar::CHUNK_ROWS;
+use driftdb_core::storage::{ChunkFilterStats, KeyFilterStats};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn insert(engine: &mut Engine, ctx: &mut SessionContext, ids: std::ops::RangeInclusive<u32>) {
+    for id in ids {
+        run(
+            engine,
+            ctx,
+            &format!(
+                "INSERT INTO events (id, note) VALUES ({}, 'event {}')",
+                id, id
+            ),
+        );
+    }
+}
+
+/// Three segments of 20 events, rotated by changing codec, then an
+/// update of row 5 (sequence 61) and a delete of row 30 (sequence 62)
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE events (id INT PRIMARY KEY, note VARCHAR)",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE events SET (bloom_filter = on)",
+    );
+    insert(&mut engine, &mut ctx, 1..=20);
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE events SET (compression = lz4)",
+    );
+    insert(&mut engine, &mut ctx, 21..=40);
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE events SET (compression = none)",
+    );
+    insert(&mut engine, &mut ctx, 41..=60);
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE events SET note = 'changed' WHERE id = 5",
+    );
+    run(&mut engine, &mut ctx, "DELETE FROM events WHERE id = 30");
+    engine
+}
+
+fn lookup(engine: &mut Engine, id: u32, seq: u64) -> Vec<serde_json::Value> {
+    run(
+        engine,
+        &mut SessionContext::new(),
+        &format!(
+            "SELECT id, note FROM events FOR SYSTEM_TIME AS OF @SEQ:{} WHERE id = {}",
+            seq, id
+        ),
+    )
+}
+
+/// What `f` adds to the table's lookup counts
+fn counted(engine: &mut Engine, f: impl FnOnce(&mut Engine)) -> KeyFilterStats {
+    let before = engine.key_filter_stats("events").unwrap();
+    f(engine);
+    let after = engine.key_filter_stats("events").unwrap();
+    KeyFilterStats {
+        segments_skipped: after.segments_skipped - before.segments_skipped,
+        segments_read: after.segments_read - before.segments_read,
+    }
+}
+
+#[test]
+fn a_key_no_segment_holds_reads_none() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    assert_eq!(
+        engine.table_storage_info("events").unwrap().segment_count,
+        3
+    );
+
+    let missing = counted(&mut engine, |engine| {
+        assert!(lookup(engine, 1000, 62).is_empty());
+    });
+    assert_eq!(
+        missing,
+        KeyFilterStats {
+            segments_skipped: 3,
+            segments_read: 0,
+        }
+    );
+
+    // Row 5 is in the first segment and patched in the last
+    let found = counted(&mut engine, |engine| {
+        assert_eq!(
+            lookup(engine, 5, 62),
+            vec![json!({"id": 5, "note": "changed"})]
+        );
+    });
+    assert_eq!(found.segments_read, 2);
+    assert_eq!(
+        lookup(&mut engine, 5, 60),
+        vec![json!({"id": 5, "note": "event 5"})]
+    );
+
+    // Deletes are seen too
+    assert!(lookup(&mut engine, 30, 62).is_empty());
+    assert_eq!(
+        lookup(&mut engine, 30, 61),
+        vec![json!({"id": 30, "note": "event 30"})]
+    );
+}
+
+#[test]
+fn filters_are_rebuilt_after_reopening_and_can_be_turned_off() {
+    let temp = TempDir::new().unwrap();
+    drop(setup(&temp));
+    let mut engine = Engine::open(temp.path()).unwrap();
+
+    // The first lookup builds the closed segments' filters; the active
+    // segment had events when opened, so it is read until it closes
+    let first = counted(&mut engine, |engine| {
+        assert!(lookup(engine, 1000, 62).is_empty());
+    });
+    assert_eq!(first.segments_read, 3);
+    let second = counted(&mut engine, |engine| {
+        assert!(lookup(engine, 1000, 62).is_empty());
+    });
+    assert_eq!(
+        second,
+        KeyFilterStats {
+            segments_skipped: 2,
+            segments_read: 1,
+        }
+    );
+    assert_eq!(
+        lookup(&mut engine, 42, 62),
+        vec![json!({"id": 42, "note": "event 42"})]
+    );
+
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE events SET (bloom_filter = off)",
+    );
+    let off = counted(&mut engine, |engine| {
+        assert_eq!(
+            lookup(engine, 5, 62),
+            vec![json!({"id": 5, "note": "changed"})]
+        );
+    });
+    assert_eq!(off, KeyFilterStats::default());
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE events SET (bloom_filter_fpp = 2)",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("between 0 and 1"), "{}", err);
+}
+
+#[test]
+fn a_value_no_chunk_holds_tests_none() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE visits (id INT PRIMARY KEY, user_id INT, page VARCHAR) \
+         WITH (storage = 'columnar', bloom_filter_columns = 'user_id', bloom_filter_fpp = 0.001)",
+    );
+    // Build the projection first, so rows take slots in insert order:
+    // three chunks, each user's visits in one of them
+    run(&mut engine, &mut ctx, "SELECT id FROM visits");
+    let visits = (0..CHUNK_ROWS * 2 + 100)
+        .map(|id| json!({"id": id, "user_id": id / 100, "page": format!("/p{}", id % 7)}))
+        .collect();
+    engine.append_events_batch("visits", visits).unwrap();
+
+    let by_user = |engine: &mut Engine, user: u32| {
+        let before = engine.chunk_filter_stats("visits").unwrap();
+        let rows = run(
+            engine,
+            &mut SessionContext::new(),
+            &format!("SELECT id FROM visits WHERE user_id = {}", user),
+        );
+        let after = engine.chunk_filter_stats("visits").unwrap();
+        let stats = ChunkFilterStats {
+            chunks_skipped: after.chunks_skipped - before.chunks_skipped,
+            chunks_read: after.chunks_read - before.chunks_read,
+        };
+        (rows.len(), stats)
+    };
+
+    assert_eq!(
+        by_user(&mut engine, 5000),
+        (
+            0,
+            ChunkFilterStats {
+                chunks_skipped: 3,
+                chunks_read: 0,
+            }
+        )
+    );
+    assert_eq!(
+        by_user(&mut engine, 3),
+        (
+            100,
+            ChunkFilterStats {
+                chunks_skipped: 2,
+                chunks_read: 1,
+            }
+        )
+    );
+
+    // Values written after the projection is built are filtered too
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE visits SET user_id = 5000 WHERE id = 7",
+    );
+    assert_eq!(by_user(&mut engine, 5000).0, 1);
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE visits SET (bloom_filter_columns = 'user_id, referrer')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("'referrer'"), "{}", err);
+}
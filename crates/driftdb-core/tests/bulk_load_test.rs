@@ -0,0 +1,103 @@
+//! Bulk loads: rows straight to segments, visible once finished and
+//! discarded as a whole when the load never finishes.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn items(range: std::ops::Range<i64>) -> Vec<Value> {
+    range
+        .map(|id| json!({"id": id, "category": format!("c{}", id % 3)}))
+        .collect()
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE items (id INT, category VARCHAR, PRIMARY KEY (id))",
+    )
+    .unwrap();
+    execute_sql(&mut engine, "CREATE INDEX idx_category ON items (category)").unwrap();
+    engine
+}
+
+#[test]
+fn finished_load_is_queryable_and_durable() {
+    let temp = TempDir::new().unwrap();
+    {
+        let engine = setup(&temp);
+        engine.begin_bulk_load("items").unwrap();
+        assert_eq!(engine.bulk_load_rows("items", items(0..50)).unwrap(), 50);
+        assert_eq!(engine.bulk_load_rows("items", items(50..90)).unwrap(), 40);
+        let summary = engine.finish_bulk_load("items").unwrap();
+        assert_eq!((summary.rows, summary.keys), (90, 90));
+    }
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(rows(&mut engine, "SELECT * FROM items").len(), 90);
+    // Served by the rebuilt index
+    assert_eq!(
+        rows(&mut engine, "SELECT * FROM items WHERE category = 'c1'").len(),
+        30
+    );
+    // Ordinary writes work again once the load is done
+    execute_sql(
+        &mut engine,
+        "INSERT INTO items (id, category) VALUES (90, 'c0')",
+    )
+    .unwrap();
+}
+
+#[test]
+fn unfinished_load_is_discarded_on_open() {
+    let temp = TempDir::new().unwrap();
+    {
+        let engine = setup(&temp);
+        engine.begin_bulk_load("items").unwrap();
+        engine.bulk_load_rows("items", items(0..20)).unwrap();
+        // Dropped without finishing, as a crash would
+    }
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert!(rows(&mut engine, "SELECT * FROM items").is_empty());
+    assert!(rows(&mut engine, "SELECT * FROM items WHERE category = 'c1'").is_empty());
+
+    // The table can be loaded again from scratch
+    engine.begin_bulk_load("items").unwrap();
+    engine.bulk_load_rows("items", items(0..5)).unwrap();
+    assert_eq!(engine.finish_bulk_load("items").unwrap().rows, 5);
+}
+
+#[test]
+fn load_needs_an_empty_table_and_blocks_other_writes() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    engine.begin_bulk_load("items").unwrap();
+    assert!(engine.begin_bulk_load("items").is_err());
+    let err = execute_sql(
+        &mut engine,
+        "INSERT INTO items (id, category) VALUES (1, 'c1')",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("bulk loaded"), "{}", err);
+    assert!(engine
+        .bulk_load_rows("items", vec![json!({"category": "c1"})])
+        .is_err());
+    engine.bulk_load_rows("items", items(0..3)).unwrap();
+    engine.finish_bulk_load("items").unwrap();
+    assert!(engine.finish_bulk_load("items").is_err());
+
+    let err = engine.begin_bulk_load("items").unwrap_err();
+    assert!(err.to_string().contains("empty table"), "{}", err);
+}
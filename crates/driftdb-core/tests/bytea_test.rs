@@ -0,0 +1,95 @@
+//! BYTEA columns: hex and escape literals, NULLs, validation and values
+//! surviving a reopen.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE blobs (id INTEGER PRIMARY KEY, data BYTEA)",
+    );
+    engine
+}
+
+#[test]
+fn literals_are_stored_in_hex_format() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        r"INSERT INTO blobs (id, data) VALUES (1, '\xDEADBEEF')",
+    );
+    run(
+        &mut engine,
+        r"INSERT INTO blobs (id, data) VALUES (2, '\000\377ab')",
+    );
+    run(&mut engine, "INSERT INTO blobs (id, data) VALUES (3, NULL)");
+    run(&mut engine, "INSERT INTO blobs (id) VALUES (4)");
+
+    let all = rows(&mut engine, "SELECT * FROM blobs ORDER BY id");
+    assert_eq!(all[0]["data"], json!(r"\xdeadbeef"));
+    assert_eq!(all[1]["data"], json!(r"\x00ff6162"));
+    assert_eq!(all[2]["data"], serde_json::Value::Null);
+    assert!(all[3].get("data").is_none_or(|v| v.is_null()));
+
+    run(
+        &mut engine,
+        r"UPDATE blobs SET data = '\x0102' WHERE id = 3",
+    );
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let row = rows(&mut engine, "SELECT data FROM blobs WHERE id = 3");
+    assert_eq!(row[0]["data"], json!(r"\x0102"));
+    let row = rows(
+        &mut engine,
+        r"SELECT id FROM blobs WHERE data = '\xdeadbeef'",
+    );
+    assert_eq!(row.len(), 1);
+}
+
+#[test]
+fn malformed_values_are_rejected() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        r"INSERT INTO blobs (id, data) VALUES (1, '\x00')",
+    );
+
+    let mut ctx = SessionContext::new();
+    for sql in [
+        r"INSERT INTO blobs (id, data) VALUES (2, '\xabc')",
+        r"INSERT INTO blobs (id, data) VALUES (3, '\xgg')",
+        r"INSERT INTO blobs (id, data) VALUES (4, 'bad\escape')",
+        r"UPDATE blobs SET data = 42 WHERE id = 1",
+    ] {
+        let err = execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid input syntax for type bytea"),
+            "{}: {}",
+            sql,
+            err
+        );
+    }
+    assert_eq!(rows(&mut engine, "SELECT * FROM blobs").len(), 1);
+}
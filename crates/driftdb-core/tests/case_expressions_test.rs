@@ -0,0 +1,225 @@
+//! `CASE` in its searched and simple forms, in the select list, WHERE and
+//! ORDER BY: NULL conditions match nothing, only the chosen branch is
+//! evaluated, and branches of different types are an error.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn error(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> String {
+    execute_sql_in_session(engine, sql, ctx)
+        .unwrap_err()
+        .to_string()
+}
+
+fn setup(temp: &TempDir) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE orders (id INT PRIMARY KEY, amount INT, status VARCHAR, code VARCHAR)",
+    );
+    for values in [
+        "1, 500, 'paid', '17'",
+        "2, 1500, 'shipped', 'x9'",
+        "3, NULL, 'paid', NULL",
+        "4, 2500, NULL, '4'",
+    ] {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "INSERT INTO orders (id, amount, status, code) VALUES ({})",
+                values
+            ),
+        );
+    }
+    (engine, ctx)
+}
+
+/// `column` of each row, by id
+fn by_id(mut rows: Vec<Value>, column: &str) -> Vec<Value> {
+    rows.sort_by_key(|row| row["id"].as_i64());
+    rows.iter().map(|row| row[column].clone()).collect()
+}
+
+fn ids(rows: Vec<Value>) -> Vec<i64> {
+    rows.iter().map(|row| row["id"].as_i64().unwrap()).collect()
+}
+
+#[test]
+fn searched_case() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    // A NULL amount makes the condition NULL, which falls through to ELSE
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE WHEN amount > 1000 THEN 'large' ELSE 'small' END AS bucket \
+         FROM orders",
+    );
+    assert_eq!(
+        by_id(rows, "bucket"),
+        [
+            json!("small"),
+            json!("large"),
+            json!("small"),
+            json!("large")
+        ]
+    );
+
+    // Without ELSE an unmatched row is NULL; unaliased, the column is `case`
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE WHEN amount < 1000 THEN 'cheap' WHEN amount < 2000 THEN 'fair' END \
+         FROM orders",
+    );
+    assert_eq!(
+        by_id(rows, "case"),
+        [json!("cheap"), json!("fair"), json!(null), json!(null)]
+    );
+
+    // Only the chosen branch runs, so codes that aren't numbers are never cast
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE WHEN code ~ '^[0-9]+$' THEN CAST(code AS INT) ELSE 0 END AS n \
+         FROM orders",
+    );
+    assert_eq!(by_id(rows, "n"), [json!(17), json!(0), json!(0), json!(4)]);
+
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT CASE WHEN 1 > 2 THEN 'a' WHEN NULL THEN 'b' ELSE 'c' END AS x"
+        ),
+        vec![json!({"x": "c"})]
+    );
+}
+
+#[test]
+fn simple_case() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE status WHEN 'paid' THEN 1 WHEN 'shipped' THEN 2 ELSE 0 END AS stage \
+         FROM orders",
+    );
+    assert_eq!(
+        by_id(rows, "stage"),
+        [json!(1), json!(2), json!(1), json!(0)]
+    );
+
+    // NULL equals nothing, not even NULL
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE status WHEN NULL THEN 'missing' ELSE 'known' END AS s FROM orders",
+    );
+    assert_eq!(by_id(rows, "s"), vec![json!("known"); 4]);
+}
+
+#[test]
+fn case_in_where_and_order_by() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let mut rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders \
+         WHERE CASE WHEN amount > 1000 THEN status = 'shipped' ELSE true END",
+    );
+    rows.sort_by_key(|row| row["id"].as_i64());
+    assert_eq!(ids(rows), [1, 2, 3]);
+
+    let mut rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders \
+         WHERE (CASE WHEN amount > 1000 THEN 'large' ELSE 'small' END) = 'large'",
+    );
+    rows.sort_by_key(|row| row["id"].as_i64());
+    assert_eq!(ids(rows), [2, 4]);
+
+    // The sort value is computed for each row and not returned
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM orders ORDER BY \
+             CASE status WHEN 'shipped' THEN 0 WHEN 'paid' THEN 1 ELSE 2 END, id DESC"
+        ),
+        vec![
+            json!({"id": 2}),
+            json!({"id": 3}),
+            json!({"id": 1}),
+            json!({"id": 4})
+        ]
+    );
+}
+
+#[test]
+fn branches_must_have_one_type() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT CASE WHEN amount > 1000 THEN 'large' ELSE 0 END AS bucket FROM orders",
+    );
+    assert!(
+        err.contains("CASE types text and integer cannot be matched"),
+        "{}",
+        err
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders WHERE id = 1 ORDER BY CASE WHEN id > 0 THEN amount ELSE status END",
+    );
+    assert!(
+        err.contains("CASE types integer and text cannot be matched"),
+        "{}",
+        err
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT CASE WHEN status THEN 1 END FROM orders",
+    );
+    assert!(
+        err.contains("argument of CASE/WHEN must be type boolean, not type text"),
+        "{}",
+        err
+    );
+
+    // Integers and numerics mix, and NULL goes with anything
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, CASE WHEN amount > 1000 THEN 1.5 WHEN amount > 0 THEN 1 END AS rate \
+         FROM orders",
+    );
+    assert_eq!(
+        by_id(rows, "rate"),
+        [json!(1), json!(1.5), json!(null), json!(1.5)]
+    );
+}
@@ -0,0 +1,258 @@
+//! `CREATE CHANGEFEED`: a table's changes delivered to a webhook or a
+//! file, at least once, resuming after the last acknowledged event when
+//! the database is opened again.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path().join("db")).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, status VARCHAR)",
+    )
+    .unwrap();
+    engine
+}
+
+fn insert(engine: &mut Engine, id: i64) {
+    execute_sql(
+        engine,
+        &format!("INSERT INTO orders (id, status) VALUES ({}, 'new')", id),
+    )
+    .unwrap();
+}
+
+fn show(engine: &mut Engine) -> Vec<Value> {
+    match execute_sql(engine, "SHOW CHANGEFEEDS").unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+/// Wait for `check` to pass, for up to 10 seconds
+fn eventually<T>(mut check: impl FnMut() -> Option<T>) -> T {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Some(value) = check() {
+            return value;
+        }
+        assert!(Instant::now() < deadline, "timed out");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn delivered_ids(path: &Path) -> Vec<Value> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["primary_key"].clone())
+        .collect()
+}
+
+#[test]
+fn file_changefeed_resumes_after_restart() {
+    let temp = TempDir::new().unwrap();
+    let sink = temp.path().join("orders.jsonl");
+    let mut engine = setup(&temp);
+    insert(&mut engine, 1);
+
+    // Starts after the current last event
+    let result = execute_sql(
+        &mut engine,
+        &format!(
+            "CREATE CHANGEFEED FOR TABLE orders INTO 'file://{}'",
+            sink.display()
+        ),
+    )
+    .unwrap();
+    assert!(
+        matches!(result, QueryResult::Success { ref message } if message.contains("orders_changefeed")),
+        "{:?}",
+        result
+    );
+    insert(&mut engine, 2);
+    insert(&mut engine, 3);
+    eventually(|| (delivered_ids(&sink) == vec![json!(2), json!(3)]).then_some(()));
+    let sequence = engine.table_sequence("orders").unwrap();
+    eventually(|| (show(&mut engine)[0]["acknowledged_sequence"] == json!(sequence)).then_some(()));
+
+    let line: Value = serde_json::from_str(
+        std::fs::read_to_string(&sink)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(line["type"], "insert");
+    assert_eq!(line["table"], "orders");
+    assert_eq!(line["payload"]["status"], "new");
+
+    // Reopening resumes after the acknowledged events
+    drop(engine);
+    let mut engine = Engine::open(temp.path().join("db")).unwrap();
+    insert(&mut engine, 4);
+    eventually(|| (delivered_ids(&sink).len() == 3).then_some(()));
+    assert_eq!(delivered_ids(&sink), vec![json!(2), json!(3), json!(4)]);
+
+    execute_sql(&mut engine, "DROP CHANGEFEED orders_changefeed").unwrap();
+    assert!(show(&mut engine).is_empty());
+    insert(&mut engine, 5);
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(delivered_ids(&sink).len(), 3);
+    execute_sql(&mut engine, "DROP CHANGEFEED IF EXISTS orders_changefeed").unwrap();
+}
+
+/// A webhook that answers the first request with 503 and the rest with
+/// 200, keeping the bodies it accepted
+fn flaky_webhook() -> (u16, Arc<Mutex<Vec<Value>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let accepted = Arc::new(Mutex::new(Vec::new()));
+    let bodies = accepted.clone();
+    std::thread::spawn(move || {
+        for (n, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let status = if n == 0 {
+                "503 Service Unavailable"
+            } else {
+                bodies
+                    .lock()
+                    .unwrap()
+                    .push(serde_json::from_slice(&body).unwrap());
+                "200 OK"
+            };
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+        }
+    });
+    (port, accepted)
+}
+
+#[test]
+fn webhook_changefeed_retries_until_acknowledged() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    for id in 1..=3 {
+        insert(&mut engine, id);
+    }
+    let (port, accepted) = flaky_webhook();
+
+    execute_sql(
+        &mut engine,
+        &format!(
+            "CREATE CHANGEFEED feed FOR TABLE orders INTO 'webhook://127.0.0.1:{}/hook' \
+             WITH (cursor = 'beginning', batch_size = 2)",
+            port
+        ),
+    )
+    .unwrap();
+    let batches = eventually(|| {
+        let batches = accepted.lock().unwrap().clone();
+        (batches.len() == 2).then_some(batches)
+    });
+
+    // The rejected first batch came again, and batches stay in order
+    let ids: Vec<Vec<Value>> = batches
+        .iter()
+        .map(|batch| {
+            assert_eq!(batch["changefeed"], "feed");
+            assert_eq!(batch["table"], "orders");
+            batch["events"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|event| event["primary_key"].clone())
+                .collect()
+        })
+        .collect();
+    assert_eq!(ids, vec![vec![json!(1), json!(2)], vec![json!(3)]]);
+    eventually(|| {
+        let row = show(&mut engine)[0].clone();
+        (row["acknowledged_sequence"] == json!(3) && row["last_error"].is_null()).then_some(())
+    });
+}
+
+#[test]
+fn changefeed_statements_are_checked() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let sink = temp.path().join("out.jsonl");
+
+    for (sql, message) in [
+        (
+            "CREATE CHANGEFEED FOR TABLE orders INTO 'kafka://broker/orders'".to_string(),
+            "unsupported changefeed sink",
+        ),
+        (
+            format!(
+                "CREATE CHANGEFEED FOR TABLE missing INTO 'file://{}'",
+                sink.display()
+            ),
+            "missing",
+        ),
+        (
+            format!(
+                "CREATE CHANGEFEED FOR TABLE orders INTO 'file://{}' WITH (batch_size = 0)",
+                sink.display()
+            ),
+            "batch_size",
+        ),
+        (
+            format!(
+                "CREATE CHANGEFEED FOR TABLE orders INTO 'file://{}' WITH (format = 'avro')",
+                sink.display()
+            ),
+            "only json",
+        ),
+        ("DROP CHANGEFEED nope".to_string(), "does not exist"),
+    ] {
+        let err = execute_sql(&mut engine, &sql).unwrap_err().to_string();
+        assert!(err.contains(message), "{}: {}", sql, err);
+    }
+
+    let create = format!(
+        "CREATE CHANGEFEED feed FOR TABLE orders INTO 'file://{}'",
+        sink.display()
+    );
+    execute_sql(&mut engine, &create).unwrap();
+    assert!(execute_sql(&mut engine, &create)
+        .unwrap_err()
+        .to_string()
+        .contains("already exists"));
+
+    // A read-only engine keeps its changefeeds but doesn't run them
+    engine.set_read_only(true);
+    assert_eq!(show(&mut engine)[0]["running"], json!(false));
+    assert!(execute_sql(&mut engine, "DROP CHANGEFEED feed").is_err());
+    engine.set_read_only(false);
+    assert_eq!(show(&mut engine)[0]["running"], json!(true));
+}
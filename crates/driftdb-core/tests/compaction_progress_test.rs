@@ -0,0 +1,86 @@
+//! Compaction progress: phases are reported in order through the callback
+//! and the tracker, and `SHOW COMPACTION PROGRESS` lists the outcome.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{CompactionPhase, Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+#[test]
+fn compaction_reports_each_phase() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER)",
+    );
+    for i in 0..20 {
+        run(
+            &mut engine,
+            &format!("INSERT INTO t (id, n) VALUES ({}, 0)", i),
+        );
+    }
+    run(&mut engine, "UPDATE t SET n = 1");
+    engine.create_snapshot("t").unwrap();
+
+    let mut phases = Vec::new();
+    let mut last_percent = 0.0;
+    engine
+        .compact_table_with_progress("t", |progress| {
+            assert!(progress.percent() >= last_percent);
+            last_percent = progress.percent();
+            if phases.last() != Some(&progress.phase) {
+                phases.push(progress.phase);
+            }
+        })
+        .unwrap();
+    assert_eq!(
+        phases,
+        [
+            CompactionPhase::WritingSnapshot,
+            CompactionPhase::ReadingSegments,
+            CompactionPhase::Cleaning,
+            CompactionPhase::Swapping,
+            CompactionPhase::Done,
+        ]
+    );
+    assert_eq!(last_percent, 100.0);
+    assert_eq!(rows(&mut engine, "SELECT * FROM t WHERE n = 1").len(), 20);
+
+    let progress = rows(&mut engine, "SHOW COMPACTION PROGRESS");
+    assert_eq!(progress.len(), 1);
+    assert_eq!(progress[0]["table"], "t");
+    assert_eq!(progress[0]["status"], "finished");
+    assert_eq!(progress[0]["percent"], 100.0);
+}
+
+#[test]
+fn failed_compaction_is_reported() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(&mut engine, "CREATE TABLE t (id INTEGER PRIMARY KEY)");
+
+    // No snapshot to compact from
+    assert!(engine.compact_table("t").is_err());
+    let tracker = engine.compaction_tracker();
+    let progress = tracker.get("t").unwrap();
+    assert!(progress.is_finished());
+    assert!(progress.error.as_deref().unwrap().contains("No snapshots"));
+    assert!(tracker.running().is_empty());
+
+    let shown = rows(&mut engine, "SHOW COMPACTION PROGRESS;");
+    assert_eq!(shown[0]["status"], "failed");
+}
@@ -0,0 +1,305 @@
+//! Segment compression: each codec round-trips, a table can switch codec
+//! with `ALTER TABLE ... SET (compression = ...)` and keep reading the
+//! segments written before, compaction rewrites history compressed, and
+//! a segment with a codec this build doesn't know fails to read.
+
+use std::path::Path;
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::storage::compression::DEFAULT_BROTLI_QUALITY;
+use driftdb_core::storage::{Compression, Segment};
+use driftdb_core::{Engine, Event, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn segment_codecs(dir: &Path) -> Vec<Compression> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir.join("tables/events/segments"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("seg"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|path| Segment::new(path, 0).codec().unwrap())
+        .collect()
+}
+
+#[test]
+fn every_codec_round_trips_a_segment() {
+    let temp = TempDir::new().unwrap();
+    let events: Vec<Event> = (0..50)
+        .map(|i| {
+            Event::new_insert(
+                "events".to_string(),
+                json!(i),
+                json!({"id": i, "kind": "click", "note": "the same words again and again"}),
+            )
+        })
+        .collect();
+
+    let mut sizes = Vec::new();
+    for (i, codec) in [
+        Compression::None,
+        Compression::Zstd,
+        Compression::Lz4,
+        Compression::Brotli(DEFAULT_BROTLI_QUALITY),
+        Compression::Brotli(11),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let path = temp.path().join(format!("{}.seg", i));
+        let segment = Segment::new(path.clone(), 1).with_compression(codec);
+        let mut writer = segment.create().unwrap();
+        for event in &events[..25] {
+            writer.append_event(event).unwrap();
+        }
+        writer.sync().unwrap();
+        drop(writer);
+
+        // Reopening for append keeps the codec the segment started with,
+        // whatever the caller asks for
+        let mut writer = Segment::new(path.clone(), 1).open_writer().unwrap();
+        assert_eq!(writer.compression(), codec);
+        for event in &events[25..] {
+            writer.append_event(event).unwrap();
+        }
+        writer.sync().unwrap();
+
+        let segment = Segment::new(path, 1);
+        assert_eq!(segment.codec().unwrap(), codec);
+        let read = segment.open_reader().unwrap().read_all_events().unwrap();
+        assert_eq!(read.len(), events.len());
+        for (read, written) in read.iter().zip(&events) {
+            assert_eq!(read.primary_key, written.primary_key);
+            assert_eq!(read.payload, written.payload);
+        }
+        sizes.push(segment.size().unwrap());
+    }
+
+    assert!(
+        sizes[1] < sizes[0],
+        "zstd {} vs none {}",
+        sizes[1],
+        sizes[0]
+    );
+    assert!(sizes[2] < sizes[0], "lz4 {} vs none {}", sizes[2], sizes[0]);
+    assert!(
+        sizes[3] < sizes[2],
+        "brotli {} vs lz4 {}",
+        sizes[3],
+        sizes[2]
+    );
+    assert!(
+        sizes[4] < sizes[0],
+        "brotli:11 {} vs none {}",
+        sizes[4],
+        sizes[0]
+    );
+}
+
+#[test]
+fn alter_table_switches_codec_and_keeps_old_segments_readable() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    {
+        let mut engine = Engine::init(temp.path()).unwrap();
+        run(
+            &mut engine,
+            &mut ctx,
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, kind VARCHAR)",
+        );
+        let mut id = 0;
+        for codec in ["zstd", "'lz4'", "none"] {
+            run(
+                &mut engine,
+                &mut ctx,
+                &format!("ALTER TABLE events SET (compression = {})", codec),
+            );
+            for _ in 0..3 {
+                id += 1;
+                run(
+                    &mut engine,
+                    &mut ctx,
+                    &format!("INSERT INTO events (id, kind) VALUES ({}, 'click')", id),
+                );
+            }
+        }
+        run(&mut engine, &mut ctx, "DELETE FROM events WHERE id = 4");
+        assert!(execute_sql_in_session(
+            &mut engine,
+            "ALTER TABLE events SET (compression = 'gzip')",
+            &mut ctx,
+        )
+        .is_err());
+        assert!(execute_sql_in_session(
+            &mut engine,
+            "ALTER TABLE events SET (fillfactor = 70)",
+            &mut ctx,
+        )
+        .is_err());
+    }
+
+    assert_eq!(
+        segment_codecs(temp.path()),
+        [
+            Compression::None,
+            Compression::Zstd,
+            Compression::Lz4,
+            Compression::None
+        ]
+    );
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT COUNT(*) FROM events WHERE id > 0"
+        ),
+        vec![json!({"count(*)": 8})]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT COUNT(*) FROM events FOR SYSTEM_TIME AS OF @SEQ:5 WHERE id > 0"
+        ),
+        vec![json!({"count(*)": 5})]
+    );
+}
+
+#[test]
+fn compaction_compresses_history() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE events (id INTEGER PRIMARY KEY, kind VARCHAR)",
+    );
+    for id in 1..=20 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!("INSERT INTO events (id, kind) VALUES ({}, 'click')", id),
+        );
+    }
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE events SET (compression = 'zstd')",
+    );
+    assert_eq!(segment_codecs(temp.path())[0], Compression::None);
+
+    engine.create_snapshot("events").unwrap();
+    engine.compact_table("events").unwrap();
+    assert!(segment_codecs(temp.path())
+        .iter()
+        .all(|codec| *codec == Compression::Zstd));
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE events SET kind = 'view' WHERE id = 7",
+    );
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT kind FROM events WHERE id = 7"
+        ),
+        vec![json!({"kind": "view"})]
+    );
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT COUNT(*) FROM events"),
+        vec![json!({"count(*)": 20})]
+    );
+}
+
+#[test]
+fn brotli_quality_is_kept_and_unknown_codecs_fail_to_read() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    {
+        let mut engine = Engine::init(temp.path()).unwrap();
+        run(
+            &mut engine,
+            &mut ctx,
+            "CREATE TABLE events (id INTEGER PRIMARY KEY, note VARCHAR)",
+        );
+        run(
+            &mut engine,
+            &mut ctx,
+            "ALTER TABLE events SET (compression = 'brotli:11')",
+        );
+        run(
+            &mut engine,
+            &mut ctx,
+            "INSERT INTO events (id, note) VALUES (1, 'archived text')",
+        );
+        let err = execute_sql_in_session(
+            &mut engine,
+            "ALTER TABLE events SET (compression = 'brotli:12')",
+            &mut ctx,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("brotli quality"), "{}", err);
+    }
+    assert_eq!(
+        segment_codecs(temp.path()),
+        [Compression::None, Compression::Brotli(11)]
+    );
+    assert_eq!(Compression::Brotli(11).to_string(), "brotli:11");
+    assert_eq!(
+        "BROTLI".parse::<Compression>().unwrap(),
+        Compression::Brotli(DEFAULT_BROTLI_QUALITY)
+    );
+
+    // Appending after a reopen keeps the quality
+    {
+        let mut engine = Engine::open(temp.path()).unwrap();
+        run(
+            &mut engine,
+            &mut ctx,
+            "INSERT INTO events (id, note) VALUES (2, 'more archived text')",
+        );
+        assert_eq!(
+            run(
+                &mut engine,
+                &mut ctx,
+                "SELECT note FROM events WHERE id = 2"
+            ),
+            vec![json!({"note": "more archived text"})]
+        );
+    }
+    assert_eq!(
+        segment_codecs(temp.path()),
+        [Compression::None, Compression::Brotli(11)]
+    );
+
+    // A codec id no build has written is an error, not garbage
+    let path = temp.path().join("tables/events/segments/00000002.seg");
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[5] = 200;
+    std::fs::write(&path, bytes).unwrap();
+    let err = Segment::new(path, 0).codec().unwrap_err();
+    assert!(
+        err.to_string().contains("unknown segment codec 200"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,61 @@
+//! Non-recursive `WITH` queries: several CTEs in one statement, later ones
+//! reading earlier ones, and a staged aggregation joined back to a table.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE customers (id VARCHAR, region VARCHAR, PRIMARY KEY (id))",
+        "CREATE TABLE orders (id VARCHAR, customer_id VARCHAR, amount INT, PRIMARY KEY (id))",
+        "INSERT INTO customers (id, region) VALUES ('alice', 'eu'), ('bob', 'us')",
+        "INSERT INTO orders (id, customer_id, amount) VALUES ('o1', 'alice', 10), ('o2', 'alice', 20), ('o3', 'bob', 5)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn later_cte_reads_an_earlier_one() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let result = rows(
+        &mut engine,
+        &mut ctx,
+        "WITH big AS (SELECT * FROM orders WHERE amount > 5),
+              big_alice AS (SELECT * FROM big WHERE customer_id = 'alice')
+         SELECT id FROM big_alice ORDER BY id",
+    );
+    let ids: Vec<_> = result.iter().map(|r| r["id"].clone()).collect();
+    assert_eq!(ids, vec!["o1", "o2"]);
+}
+
+#[test]
+fn staged_aggregation_joins_back_to_a_table() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let result = rows(
+        &mut engine,
+        &mut ctx,
+        "WITH totals AS (SELECT customer_id, SUM(amount) AS total FROM orders GROUP BY customer_id)
+         SELECT c.id, c.region, t.total FROM customers c JOIN totals t ON c.id = t.customer_id
+         ORDER BY c.id",
+    );
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0]["id"], "alice");
+    assert_eq!(result[0]["total"], 30);
+    assert_eq!(result[1]["region"], "us");
+}
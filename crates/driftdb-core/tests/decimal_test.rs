@@ -0,0 +1,153 @@
+//! DECIMAL/NUMERIC columns: values are rounded to their scale, and sums,
+//! averages and arithmetic over them are exact.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn ledger(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE ledger (id INT, amount NUMERIC(12,2), PRIMARY KEY (id))",
+    );
+    engine
+}
+
+#[test]
+fn point_one_plus_point_two_is_point_three() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = ledger(&temp);
+    run(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (1, 0.1)",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (2, 0.2)",
+    );
+
+    let sum = rows(&mut engine, "SELECT SUM(amount) FROM ledger");
+    assert_eq!(sum[0]["sum(amount)"], json!(0.3));
+
+    let added = rows(
+        &mut engine,
+        "SELECT amount + 0.2 AS total FROM ledger WHERE id = 1",
+    );
+    assert_eq!(added[0]["total"], json!(0.3));
+
+    let matched = rows(&mut engine, "SELECT * FROM ledger WHERE amount + 0.2 = 0.3");
+    assert_eq!(matched.len(), 1);
+}
+
+#[test]
+fn sums_are_exact_to_the_cent() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = ledger(&temp);
+    for id in 0..1000 {
+        run(
+            &mut engine,
+            &format!("INSERT INTO ledger (id, amount) VALUES ({}, 0.01)", id),
+        );
+    }
+    run(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (1000, 19.99)",
+    );
+
+    let totals = rows(
+        &mut engine,
+        "SELECT SUM(amount) AS total, AVG(amount) AS mean FROM ledger",
+    );
+    assert_eq!(totals[0]["total"], json!(29.99));
+    assert_eq!(totals[0]["mean"], json!(0.0299600399600400));
+}
+
+#[test]
+fn values_are_rounded_to_scale_and_checked_against_precision() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = ledger(&temp);
+    run(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (1, '10.005')",
+    );
+    run(&mut engine, "INSERT INTO ledger (id, amount) VALUES (2, 7)");
+    run(&mut engine, "UPDATE ledger SET amount = 2.499 WHERE id = 2");
+
+    let stored = rows(&mut engine, "SELECT * FROM ledger ORDER BY id");
+    assert_eq!(stored[0]["amount"], json!(10.01));
+    assert_eq!(stored[1]["amount"], json!(2.5));
+
+    let mut ctx = SessionContext::new();
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (3, 12345678901.5)",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("numeric field overflow"),
+        "{}",
+        err
+    );
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO ledger (id, amount) VALUES (3, 'ten')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("type numeric"), "{}", err);
+}
+
+#[test]
+fn values_wider_than_a_float_are_kept_exactly() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE balances (id INT, amount NUMERIC(30,10), PRIMARY KEY (id))",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO balances (id, amount) VALUES (1, 12345678.1234567891)",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO balances (id, amount) VALUES (2, '0.0000000001')",
+    );
+
+    let stored = rows(&mut engine, "SELECT * FROM balances ORDER BY id");
+    assert_eq!(stored[0]["amount"], json!("12345678.1234567891"));
+
+    let totals = rows(&mut engine, "SELECT SUM(amount) AS total FROM balances");
+    assert_eq!(totals[0]["total"], json!("12345678.1234567892"));
+
+    let bumped = rows(
+        &mut engine,
+        "SELECT amount + 1 AS next FROM balances WHERE id = 1",
+    );
+    assert_eq!(bumped[0]["next"], json!("12345679.1234567891"));
+
+    let matched = rows(
+        &mut engine,
+        "SELECT id FROM balances WHERE amount > 12345678.12345678",
+    );
+    assert_eq!(matched.len(), 1);
+    assert_eq!(matched[0]["id"], json!(1));
+}
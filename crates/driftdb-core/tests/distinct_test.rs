@@ -0,0 +1,79 @@
+//! `SELECT DISTINCT` deduplicates the projected rows; `DISTINCT ON (...)`
+//! keeps the first row per key under the query's ORDER BY.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE events (id INT, user_id VARCHAR, kind VARCHAR, created_at INT, PRIMARY KEY (id))",
+        "INSERT INTO events (id, user_id, kind, created_at) VALUES
+            (1, 'alice', 'login', 100),
+            (2, 'bob', 'login', 110),
+            (3, 'alice', 'click', 120),
+            (4, 'bob', 'logout', 105),
+            (5, 'alice', 'login', 130)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn distinct_dedupes_selected_columns() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let kinds = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT DISTINCT kind FROM events ORDER BY kind",
+    );
+    let kinds: Vec<_> = kinds.iter().map(|r| r["kind"].clone()).collect();
+    assert_eq!(kinds, vec!["click", "login", "logout"]);
+
+    // LIMIT applies after deduplication.
+    let limited = rows(&mut engine, &mut ctx, "SELECT DISTINCT user_id FROM events LIMIT 2");
+    assert_eq!(limited.len(), 2);
+}
+
+#[test]
+fn distinct_on_returns_newest_row_per_user() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let latest = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT DISTINCT ON (user_id) user_id, kind, created_at FROM events
+         ORDER BY user_id, created_at DESC",
+    );
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest[0]["user_id"], "alice");
+    assert_eq!(latest[0]["created_at"], 130);
+    assert_eq!(latest[1]["user_id"], "bob");
+    assert_eq!(latest[1]["kind"], "login");
+}
+
+#[test]
+fn distinct_on_must_lead_the_order_by() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "SELECT DISTINCT ON (user_id) * FROM events ORDER BY created_at",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("DISTINCT ON"), "{}", err);
+}
@@ -0,0 +1,283 @@
+//! Doctor: WAL and snapshot consistency checks, and a repair that only
+//! runs when it can show nothing recoverable is lost.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::doctor::{Fix, Severity};
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::wal::{WalConfig, WalManager, WalOperation};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    execute_sql(engine, sql).unwrap();
+}
+
+/// Five rows in `items`, closed so the files can be tampered with
+fn setup(temp: &TempDir, checkpoint: bool) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE items (id INT, name VARCHAR, PRIMARY KEY (id))",
+    );
+    for i in 1..=5 {
+        run(
+            &mut engine,
+            &format!("INSERT INTO items (id, name) VALUES ({}, 'item {}')", i, i),
+        );
+    }
+    if checkpoint {
+        run(&mut engine, "CHECKPOINT TABLE items");
+    }
+}
+
+fn table_dir(temp: &TempDir) -> PathBuf {
+    temp.path().join("tables").join("items")
+}
+
+fn segment(temp: &TempDir) -> PathBuf {
+    table_dir(temp).join("segments").join("00000001.seg")
+}
+
+fn append(path: &Path, bytes: &[u8]) {
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .unwrap()
+        .write_all(bytes)
+        .unwrap();
+}
+
+/// Flip a byte inside the first frame's payload, so its checksum fails
+fn corrupt_first_frame(path: &Path) {
+    let mut bytes = fs::read(path).unwrap();
+    bytes[10] ^= 0xff;
+    fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn healthy_database_has_no_findings() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, true);
+    let engine = Engine::open(temp.path()).unwrap();
+
+    let report = engine.diagnose().unwrap();
+    assert!(report.is_healthy(), "{:#?}", report.findings);
+    assert_eq!(report.tables_checked, 1);
+    assert_eq!(report.lines()[1], "No problems found");
+}
+
+#[test]
+fn torn_segment_tail_is_truncated() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, false);
+    let intact = fs::metadata(segment(&temp)).unwrap().len();
+    // A frame header promising more bytes than the disk had room for
+    append(&segment(&temp), &[64, 0, 0, 0, 1, 2, 3, 4, 9, 9]);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let report = engine.diagnose().unwrap();
+    assert_eq!(report.findings.len(), 1, "{:#?}", report.findings);
+    assert_eq!(report.findings[0].severity, Severity::Error);
+    assert_eq!(report.findings[0].fix, Fix::Truncate(intact));
+    // Diagnosing alone changes nothing
+    assert!(fs::metadata(segment(&temp)).unwrap().len() > intact);
+
+    let repaired = engine.repair().unwrap();
+    assert_eq!(repaired.repaired.len(), 1);
+    assert!(repaired.refused.is_none());
+    assert_eq!(fs::metadata(segment(&temp)).unwrap().len(), intact);
+    assert!(engine.diagnose().unwrap().is_healthy());
+    assert_eq!(rows(&mut engine, "SELECT * FROM items").len(), 5);
+}
+
+#[test]
+fn dangling_and_half_written_snapshots_are_removed() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, true);
+    let snapshots = table_dir(&temp).join("snapshots");
+    let good = snapshots.join("0000000005.snap");
+    let dangling = snapshots.join("0000000099.snap");
+    let unreadable = snapshots.join("0000000003.snap");
+    let half_written = snapshots.join("0000000007.snap.tmp");
+    fs::copy(&good, &dangling).unwrap();
+    fs::write(&unreadable, b"not a snapshot").unwrap();
+    fs::write(&half_written, b"partial").unwrap();
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let report = engine.diagnose().unwrap();
+    let flagged: Vec<&PathBuf> = report.findings.iter().map(|f| &f.path).collect();
+    assert_eq!(flagged.len(), 3, "{:#?}", report.findings);
+    for path in [&dangling, &unreadable, &half_written] {
+        assert!(flagged.contains(&path), "{} not flagged", path.display());
+    }
+    assert!(report.findings.iter().all(|f| f.fix == Fix::Remove));
+    assert!(report
+        .lines()
+        .iter()
+        .any(|line| line.contains("last event is 5")));
+
+    engine.repair().unwrap();
+    assert!(good.exists());
+    assert!(!dangling.exists() && !unreadable.exists() && !half_written.exists());
+    assert!(engine.diagnose().unwrap().is_healthy());
+    assert_eq!(rows(&mut engine, "SELECT * FROM items").len(), 5);
+}
+
+#[test]
+fn repair_refuses_when_events_would_be_lost() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, false);
+    corrupt_first_frame(&segment(&temp));
+    fs::write(
+        table_dir(&temp)
+            .join("snapshots")
+            .join("0000000004.snap.tmp"),
+        b"partial",
+    )
+    .unwrap();
+    let before = fs::read(segment(&temp)).unwrap();
+
+    let engine = Engine::open(temp.path()).unwrap();
+    let report = engine.repair().unwrap();
+    assert!(!report.can_repair());
+    assert!(report.repaired.is_empty());
+    let refused = report.refused.expect("repair should refuse");
+    assert!(refused.contains("events 1-5"), "{}", refused);
+
+    // Not even the safe fixes were applied
+    assert_eq!(fs::read(segment(&temp)).unwrap(), before);
+    assert!(table_dir(&temp)
+        .join("snapshots")
+        .join("0000000004.snap.tmp")
+        .exists());
+}
+
+#[test]
+fn snapshot_makes_damaged_history_repairable() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, true);
+    corrupt_first_frame(&segment(&temp));
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let report = engine.diagnose().unwrap();
+    let gap = report
+        .findings
+        .iter()
+        .find(|f| f.problem.contains("gap"))
+        .expect("gap reported");
+    assert_eq!(gap.severity, Severity::Warning);
+    assert_eq!(gap.fix, Fix::None);
+    assert!(report.findings.iter().any(|f| f.fix == Fix::Truncate(0)));
+
+    let repaired = engine.repair().unwrap();
+    assert!(repaired.refused.is_none());
+    assert_eq!(fs::metadata(segment(&temp)).unwrap().len(), 0);
+    assert_eq!(rows(&mut engine, "SELECT * FROM items").len(), 5);
+}
+
+#[test]
+fn wal_torn_tail_and_checkpoint_leftovers() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, false);
+    let wal = temp.path().join("wal.log");
+    append(&wal, b"{\"sequence\":1,\"transaction_id\":null,\"oper");
+    // A rotated log with no checkpoint in the current log to vouch for it
+    fs::write(temp.path().join("wal.wal.old"), b"").unwrap();
+
+    let engine = Engine::open(temp.path()).unwrap();
+    let report = engine.diagnose().unwrap();
+    let torn = report
+        .findings
+        .iter()
+        .find(|f| f.path == wal)
+        .expect("torn WAL reported");
+    assert!(torn.table.is_none());
+    assert!(matches!(torn.fix, Fix::Truncate(_)));
+    let rotated = report
+        .findings
+        .iter()
+        .find(|f| f.path == temp.path().join("wal.wal.old"))
+        .expect("rotated WAL reported");
+    assert!(matches!(rotated.fix, Fix::Unproven(_)));
+    assert!(engine.repair().unwrap().refused.is_some());
+
+    fs::remove_file(temp.path().join("wal.wal.old")).unwrap();
+    let repaired = engine.repair().unwrap();
+    assert!(repaired.refused.is_none());
+    assert!(engine.diagnose().unwrap().is_healthy());
+}
+
+#[test]
+fn wal_damage_before_valid_entries_is_not_truncated() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, false);
+    let wal = temp.path().join("wal").join("wal.log");
+    {
+        let log = WalManager::new(&wal, WalConfig::default()).unwrap();
+        for id in ["1", "2"] {
+            log.log_operation(WalOperation::Insert {
+                table: "items".to_string(),
+                row_id: id.to_string(),
+                data: json!({"id": id}),
+            })
+            .unwrap();
+        }
+    }
+    let lines = fs::read_to_string(&wal).unwrap();
+    fs::write(&wal, format!("garbage\n{}", lines)).unwrap();
+
+    let engine = Engine::open(temp.path()).unwrap();
+    let report = engine.repair().unwrap();
+    let finding = report
+        .findings
+        .iter()
+        .find(|f| f.path == wal)
+        .expect("damaged WAL reported");
+    assert!(matches!(&finding.fix, Fix::Unproven(reason) if reason.contains("follow")));
+    assert!(report.refused.is_some());
+    assert!(fs::read_to_string(&wal).unwrap().starts_with("garbage\n"));
+}
+
+#[test]
+fn stale_segment_bounds_are_rebuilt() {
+    let temp = TempDir::new().unwrap();
+    setup(&temp, false);
+    // Record the segment as starting past its first events, as an append
+    // that didn't widen the bounds would have left it
+    let meta_path = table_dir(&temp).join("meta.json");
+    let mut meta: Value = serde_json::from_str(&fs::read_to_string(&meta_path).unwrap()).unwrap();
+    meta["segment_index"]["segments"]["1"]["min_sequence"] = json!(3);
+    fs::write(&meta_path, meta.to_string()).unwrap();
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let report = engine.diagnose().unwrap();
+    assert_eq!(report.findings.len(), 1, "{:#?}", report.findings);
+    let stale = &report.findings[0];
+    assert_eq!(stale.severity, Severity::Error);
+    assert_eq!(stale.fix, Fix::RebuildSegmentIndex);
+    assert!(
+        stale.problem.contains("records sequences 3-5"),
+        "{}",
+        stale.problem
+    );
+
+    let repaired = engine.repair().unwrap();
+    assert_eq!(
+        repaired.repaired,
+        vec!["rebuilt the segment index of items"]
+    );
+    assert!(engine.diagnose().unwrap().is_healthy());
+    assert_eq!(rows(&mut engine, "SELECT * FROM items").len(), 5);
+}
@@ -0,0 +1,96 @@
+//! Sync modes: `SET synchronous_commit` per session, deferred fsyncs
+//! under the weaker modes and catching up on return to `full`.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::durability::SyncMode;
+use driftdb_core::sql_bridge::{execute_sql, execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) {
+    execute_sql_in_session(engine, sql, ctx).unwrap();
+}
+
+fn shown_mode(engine: &mut Engine, ctx: &mut SessionContext) -> String {
+    match execute_sql_in_session(engine, "SHOW synchronous_commit", ctx).unwrap() {
+        QueryResult::Rows { data } => data[0]["synchronous_commit"].as_str().unwrap().to_string(),
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn session_setting_overrides_engine_mode() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    assert_eq!(shown_mode(&mut engine, &mut ctx), "full");
+    run(&mut engine, &mut ctx, "SET synchronous_commit TO fsync_off");
+    assert_eq!(ctx.synchronous_commit, Some(SyncMode::FsyncOff));
+    assert_eq!(shown_mode(&mut engine, &mut ctx), "fsync_off");
+
+    // Other sessions keep the engine's mode
+    let mut other = SessionContext::new();
+    assert_eq!(shown_mode(&mut engine, &mut other), "full");
+
+    run(&mut engine, &mut ctx, "SET synchronous_commit = off");
+    assert_eq!(shown_mode(&mut engine, &mut ctx), "async");
+    run(&mut engine, &mut ctx, "RESET synchronous_commit");
+    assert_eq!(ctx.synchronous_commit, None);
+    assert_eq!(shown_mode(&mut engine, &mut ctx), "full");
+
+    let err = execute_sql_in_session(&mut engine, "SET synchronous_commit TO maybe", &mut ctx)
+        .unwrap_err();
+    assert!(err.to_string().contains("synchronous_commit"), "{}", err);
+}
+
+#[test]
+fn fsync_off_defers_until_back_to_full() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE items (id INT, name VARCHAR, PRIMARY KEY (id))",
+    );
+    assert_eq!(engine.durability().stats().deferred_writes, 0);
+
+    run(&mut engine, &mut ctx, "SET synchronous_commit TO fsync_off");
+    for id in 0..5 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!("INSERT INTO items (id, name) VALUES ({}, 'item')", id),
+        );
+    }
+    let stats = engine.durability().stats();
+    assert!(stats.deferred_writes >= 5, "{:?}", stats);
+    assert_eq!(stats.deferred_syncs, 0);
+
+    run(&mut engine, &mut ctx, "SET synchronous_commit TO full");
+    assert!(engine.durability().stats().deferred_syncs > 0);
+    assert_eq!(engine.durability().sync_pending().unwrap(), 0);
+}
+
+#[test]
+fn rows_written_without_fsync_are_readable_after_reopen() {
+    let temp = TempDir::new().unwrap();
+    {
+        let mut engine = Engine::init(temp.path()).unwrap();
+        engine.durability().set_mode(SyncMode::FsyncOff).unwrap();
+        execute_sql(
+            &mut engine,
+            "CREATE TABLE items (id INT, name VARCHAR, PRIMARY KEY (id))",
+        )
+        .unwrap();
+        execute_sql(&mut engine, "INSERT INTO items (id, name) VALUES (1, 'a')").unwrap();
+    }
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    match execute_sql(&mut engine, "SELECT name FROM items WHERE id = 1").unwrap() {
+        QueryResult::Rows { data } => assert_eq!(data, vec![json!({"name": "a"})]),
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
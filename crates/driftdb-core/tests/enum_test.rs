@@ -0,0 +1,161 @@
+//! Enum types: membership is enforced, values are stored as label
+//! positions, and comparisons and ORDER BY follow declaration order.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn statuses(engine: &mut Engine, sql: &str) -> Vec<String> {
+    rows(engine, sql)
+        .iter()
+        .map(|row| row["status"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TYPE status AS ENUM ('pending', 'paid', 'void')",
+        "CREATE TABLE invoices (id INT, status status, PRIMARY KEY (id))",
+        "INSERT INTO invoices (id, status) VALUES (1, 'void')",
+        "INSERT INTO invoices (id, status) VALUES (2, 'pending')",
+        "INSERT INTO invoices (id, status) VALUES (3, 'paid')",
+        "INSERT INTO invoices (id, status) VALUES (4, 'pending')",
+    ] {
+        run(&mut engine, sql);
+    }
+    engine
+}
+
+#[test]
+fn undeclared_labels_are_rejected() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO invoices (id, status) VALUES (5, 'refunded')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid input value for enum status: \"refunded\""),
+        "{}",
+        err
+    );
+
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "UPDATE invoices SET status = 'Paid' WHERE id = 2",
+        &mut ctx,
+    )
+    .is_err());
+
+    run(
+        &mut engine,
+        "UPDATE invoices SET status = 'paid' WHERE id = 2",
+    );
+    let row = rows(&mut engine, "SELECT * FROM invoices WHERE id = 2");
+    assert_eq!(row[0]["status"], json!("paid"));
+    assert_eq!(rows(&mut engine, "SELECT * FROM invoices").len(), 4);
+}
+
+#[test]
+fn order_by_follows_declaration_order() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        statuses(&mut engine, "SELECT * FROM invoices ORDER BY status, id"),
+        vec!["pending", "pending", "paid", "void"]
+    );
+    assert_eq!(
+        statuses(
+            &mut engine,
+            "SELECT status FROM invoices ORDER BY status DESC"
+        ),
+        vec!["void", "paid", "pending", "pending"]
+    );
+}
+
+#[test]
+fn comparisons_follow_declaration_order() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // Alphabetically 'paid' < 'pending'; in declaration order it's after
+    assert_eq!(
+        statuses(
+            &mut engine,
+            "SELECT * FROM invoices WHERE status > 'pending' ORDER BY status"
+        ),
+        vec!["paid", "void"]
+    );
+    assert_eq!(
+        statuses(
+            &mut engine,
+            "SELECT * FROM invoices WHERE 'paid' >= status AND id > 2 ORDER BY status"
+        ),
+        vec!["pending", "paid"]
+    );
+    assert_eq!(
+        statuses(&mut engine, "SELECT * FROM invoices WHERE status = 'void'"),
+        vec!["void"]
+    );
+
+    let mut ctx = SessionContext::new();
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "SELECT * FROM invoices WHERE status < 'unknown'",
+        &mut ctx,
+    )
+    .is_err());
+}
+
+#[test]
+fn types_and_values_survive_reopen() {
+    let temp = TempDir::new().unwrap();
+    let engine = setup(&temp);
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(
+        statuses(&mut engine, "SELECT * FROM invoices ORDER BY status, id"),
+        vec!["pending", "pending", "paid", "void"]
+    );
+    run(
+        &mut engine,
+        "CREATE TABLE refunds (id INT, status status, PRIMARY KEY (id))",
+    );
+    let mut ctx = SessionContext::new();
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO refunds (id, status) VALUES (1, 'refunded')",
+        &mut ctx,
+    )
+    .is_err());
+
+    // A type can't be dropped while columns use it
+    assert!(execute_sql_in_session(&mut engine, "DROP TYPE status", &mut ctx).is_err());
+    run(&mut engine, "DROP TABLE refunds");
+    run(&mut engine, "DROP TABLE invoices");
+    run(&mut engine, "DROP TYPE status");
+    run(&mut engine, "DROP TYPE IF EXISTS status");
+}
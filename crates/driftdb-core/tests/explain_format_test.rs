@@ -0,0 +1,163 @@
+//! EXPLAIN's text output follows PostgreSQL's layout — `cost=startup..total
+//! rows=n width=w` on each node, six columns of indent per level — and
+//! `EXPLAIN (option, ...)` picks the format, verbosity and whether costs
+//! are shown.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn plan(engine: &mut Engine, sql: &str) -> Vec<String> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data
+            .iter()
+            .map(|row| row["QUERY PLAN"].as_str().unwrap().to_string())
+            .collect(),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, category VARCHAR, price INTEGER)",
+        "INSERT INTO items (id, category, price) VALUES (1, 'a', 10)",
+        "INSERT INTO items (id, category, price) VALUES (2, 'b', 20)",
+        "INSERT INTO items (id, category, price) VALUES (3, 'a', 30)",
+    ] {
+        execute_sql(&mut engine, sql).unwrap();
+    }
+    engine
+}
+
+#[test]
+fn text_format_matches_postgres_layout() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        plan(&mut engine, "EXPLAIN SELECT * FROM items"),
+        vec!["Seq Scan on items  (cost=0.00..1.00 rows=3 width=40)"]
+    );
+
+    assert_eq!(
+        plan(
+            &mut engine,
+            "EXPLAIN (COSTS OFF) SELECT id FROM items WHERE category = 'a' \
+             ORDER BY price DESC LIMIT 2",
+        ),
+        vec![
+            "Limit",
+            "  ->  Sort",
+            "        Sort Key: price DESC",
+            "        ->  Seq Scan on items",
+            "              Filter: category = 'a'",
+        ]
+    );
+
+    // Every node's cost covers its inputs, and a sort starts only once
+    // its input is done
+    let lines = plan(
+        &mut engine,
+        "EXPLAIN SELECT id FROM items ORDER BY price LIMIT 2",
+    );
+    assert!(
+        lines[0].starts_with("Limit  (cost=2.00..")
+            && lines[1].starts_with("  ->  Sort  (cost=2.00..2.03 rows=3 width=40)"),
+        "{:?}",
+        lines
+    );
+}
+
+#[test]
+fn verbose_shows_output_columns() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        plan(
+            &mut engine,
+            "EXPLAIN (VERBOSE, COSTS OFF) SELECT * FROM items WHERE category = 'a'",
+        ),
+        vec![
+            "Seq Scan on items",
+            "  Output: id, category, price",
+            "  Filter: category = 'a'",
+        ]
+    );
+    assert_eq!(
+        plan(
+            &mut engine,
+            "EXPLAIN (VERBOSE TRUE, COSTS FALSE) SELECT price, id AS item FROM items",
+        ),
+        vec!["Seq Scan on items", "  Output: price, id"]
+    );
+    // The option list can follow a hint comment
+    assert_eq!(
+        plan(
+            &mut engine,
+            "/*+ SeqScan(items) */ EXPLAIN (COSTS OFF) SELECT * FROM items WHERE id = 2",
+        ),
+        vec!["Seq Scan on items", "  Filter: id = 2"]
+    );
+}
+
+#[test]
+fn format_and_analyze_options() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let json = plan(&mut engine, "EXPLAIN (FORMAT JSON) SELECT * FROM items");
+    assert_eq!(json.len(), 1);
+    let document: serde_json::Value = serde_json::from_str(&json[0]).unwrap();
+    assert_eq!(document["estimated_rows"], 3.0);
+    assert!(document["plan"].is_object(), "{}", document);
+
+    let text = plan(&mut engine, "EXPLAIN (FORMAT TEXT) SELECT * FROM items");
+    assert_eq!(text, plan(&mut engine, "EXPLAIN SELECT * FROM items"));
+
+    let analyzed = plan(
+        &mut engine,
+        "EXPLAIN (ANALYZE, COSTS OFF, BUFFERS) SELECT * FROM items",
+    );
+    assert_eq!(analyzed[0], "Seq Scan on items");
+    assert!(
+        analyzed
+            .iter()
+            .any(|line| line.starts_with("Execution Time:")),
+        "{:?}",
+        analyzed
+    );
+    assert!(analyzed
+        .iter()
+        .any(|line| line.starts_with("Actual Rows: 3")));
+}
+
+#[test]
+fn bad_options_are_rejected() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    for (sql, message) in [
+        (
+            "EXPLAIN (FORMAT XML) SELECT * FROM items",
+            "unrecognized value for EXPLAIN option \"format\"",
+        ),
+        (
+            "EXPLAIN (COLOURS) SELECT * FROM items",
+            "unrecognized EXPLAIN option \"colours\"",
+        ),
+        (
+            "EXPLAIN (COSTS maybe) SELECT * FROM items",
+            "requires a Boolean value",
+        ),
+        (
+            "EXPLAIN (TIMING) SELECT * FROM items",
+            "TIMING requires ANALYZE",
+        ),
+    ] {
+        let err = execute_sql(&mut engine, sql).unwrap_err().to_string();
+        assert!(err.contains(message), "{}: {}", sql, err);
+    }
+}
@@ -0,0 +1,159 @@
+//! `FETCH FIRST n ROWS { ONLY | WITH TIES }`: WITH TIES also returns every
+//! row tying with the nth on the ORDER BY values, whether or not they are
+//! selected, and requires an ORDER BY.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn ids(engine: &mut Engine, sql: &str) -> Vec<i64> {
+    rows(engine, sql)
+        .iter()
+        .map(|row| row["id"].as_i64().unwrap())
+        .collect()
+}
+
+fn leaderboard(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE scores (id INTEGER PRIMARY KEY, player VARCHAR, points INTEGER)",
+    )
+    .unwrap();
+    for (id, points) in (1..).zip([100, 90, 90, 80, 80, 80, 70]) {
+        execute_sql(
+            &mut engine,
+            &format!(
+                "INSERT INTO scores (id, player, points) VALUES ({}, 'p{}', {})",
+                id, id, points
+            ),
+        )
+        .unwrap();
+    }
+    engine
+}
+
+#[test]
+fn with_ties_returns_rows_tying_at_the_boundary() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = leaderboard(&temp);
+
+    // 4th place is shared by three players
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT id FROM scores ORDER BY points DESC, id FETCH FIRST 4 ROWS ONLY"
+        ),
+        vec![1, 2, 3, 4]
+    );
+    let mut top = ids(
+        &mut engine,
+        "SELECT id FROM scores ORDER BY points DESC FETCH FIRST 4 ROWS WITH TIES",
+    );
+    top.sort();
+    assert_eq!(top, vec![1, 2, 3, 4, 5, 6]);
+
+    // No tie at the boundary: exactly n rows
+    let mut top = ids(
+        &mut engine,
+        "SELECT id FROM scores ORDER BY points DESC FETCH FIRST 3 ROWS WITH TIES",
+    );
+    top.sort();
+    assert_eq!(top, vec![1, 2, 3]);
+
+    // Ties are on every ORDER BY key, selected or not
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT player FROM scores ORDER BY points DESC, id FETCH FIRST 2 ROWS WITH TIES"
+        ),
+        vec![json!({"player": "p1"}), json!({"player": "p2"})]
+    );
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT player FROM scores ORDER BY points FETCH FIRST ROW WITH TIES"
+        ),
+        vec![json!({"player": "p7"})]
+    );
+}
+
+#[test]
+fn with_ties_counts_from_the_offset() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = leaderboard(&temp);
+
+    // The offset ends between the 90s and the 80s, so which rows it skips
+    // doesn't depend on how ties are ordered
+    let mut page = ids(
+        &mut engine,
+        "SELECT id FROM scores ORDER BY points DESC OFFSET 3 ROWS FETCH NEXT 2 ROWS WITH TIES",
+    );
+    page.sort();
+    assert_eq!(page, vec![4, 5, 6]);
+
+    // An offset ending inside a tie group needs a key that orders the
+    // group; ties are then on both keys, so none extend the page
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT id FROM scores ORDER BY points DESC, id OFFSET 2 ROWS FETCH NEXT 2 ROWS WITH TIES"
+        ),
+        vec![3, 4]
+    );
+    // Ties still count from the offset when it splits their group
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT points FROM scores ORDER BY points DESC OFFSET 4 ROWS FETCH NEXT 1 ROW WITH TIES"
+        ),
+        vec![json!({"points": 80}), json!({"points": 80})]
+    );
+
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT DISTINCT points FROM scores ORDER BY points DESC \
+             FETCH FIRST 2 ROWS WITH TIES"
+        ),
+        vec![json!({"points": 100}), json!({"points": 90})]
+    );
+    assert!(rows(
+        &mut engine,
+        "SELECT id FROM scores ORDER BY points FETCH FIRST 0 ROWS WITH TIES"
+    )
+    .is_empty());
+}
+
+#[test]
+fn with_ties_requires_order_by() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = leaderboard(&temp);
+
+    let err = execute_sql(
+        &mut engine,
+        "SELECT id FROM scores FETCH FIRST 3 ROWS WITH TIES",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("ORDER BY"), "{}", err);
+
+    // ONLY needs none
+    assert_eq!(
+        rows(&mut engine, "SELECT id FROM scores FETCH FIRST 3 ROWS ONLY").len(),
+        3
+    );
+    assert!(execute_sql(
+        &mut engine,
+        "SELECT id FROM scores ORDER BY id LIMIT 2 FETCH FIRST 3 ROWS ONLY",
+    )
+    .is_err());
+}
@@ -11,6 +11,17 @@ fn test_exclusive_table_lock() {
         name: "test_table".to_string(),
         primary_key: "id".to_string(),
         columns: vec![],
+        changes: vec![],
+        defaults: Default::default(),
+        enums: Default::default(),
+        checks: vec![],
+        unique: Default::default(),
+        foreign_keys: vec![],
+        compression: Default::default(),
+        bloom_filter_fpp: None,
+        bloom_filter_columns: Default::default(),
+        storage: Default::default(),
+        retention: Default::default(),
     };
 
     // First TableStorage should acquire the lock successfully
@@ -32,6 +43,17 @@ fn test_lock_released_on_drop() {
         name: "test_table".to_string(),
         primary_key: "id".to_string(),
         columns: vec![],
+        changes: vec![],
+        defaults: Default::default(),
+        enums: Default::default(),
+        checks: vec![],
+        unique: Default::default(),
+        foreign_keys: vec![],
+        compression: Default::default(),
+        bloom_filter_fpp: None,
+        bloom_filter_columns: Default::default(),
+        storage: Default::default(),
+        retention: Default::default(),
     };
 
     // Create and drop first TableStorage
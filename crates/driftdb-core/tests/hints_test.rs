@@ -0,0 +1,152 @@
+//! Planner hints: a leading `/*+ ... */` comment forces the scan or join
+//! algorithm for its statement without changing the result, and hints that
+//! can't be followed are ignored with a notice.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn plan(engine: &mut Engine, ctx: &mut SessionContext, hint: &str, sql: &str) -> String {
+    rows(engine, ctx, &format!("{} EXPLAIN {}", hint, sql))
+        .iter()
+        .filter_map(|row| row["QUERY PLAN"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setup(temp: &TempDir, ctx: &mut SessionContext) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE customers (id INTEGER PRIMARY KEY, name VARCHAR)",
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer_id INTEGER, total INTEGER)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, ctx).unwrap();
+    }
+    for id in 1..=3 {
+        execute_sql_in_session(
+            &mut engine,
+            &format!(
+                "INSERT INTO customers (id, name) VALUES ({}, 'c{}')",
+                id, id
+            ),
+            ctx,
+        )
+        .unwrap();
+    }
+    for id in 1..=30 {
+        execute_sql_in_session(
+            &mut engine,
+            &format!(
+                "INSERT INTO orders (id, customer_id, total) VALUES ({}, {}, {})",
+                id,
+                id % 3 + 1,
+                id * 10
+            ),
+            ctx,
+        )
+        .unwrap();
+    }
+    execute_sql_in_session(&mut engine, "CREATE INDEX idx_total ON orders (total)", ctx).unwrap();
+    engine
+}
+
+const LOOKUP: &str = "SELECT id FROM orders WHERE total = 70";
+
+#[test]
+fn scan_hints_choose_the_access_path() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    let mut engine = setup(&temp, &mut ctx);
+
+    assert!(plan(&mut engine, &mut ctx, "", LOOKUP).contains("Index Scan"));
+    let text = plan(&mut engine, &mut ctx, "/*+ SeqScan(orders) */", LOOKUP);
+    assert!(text.contains("Seq Scan on orders"), "{}", text);
+    assert!(ctx.notices.is_empty(), "{:?}", ctx.notices);
+
+    // The hinted plan isn't cached for the unhinted statement
+    assert!(plan(&mut engine, &mut ctx, "", LOOKUP).contains("Index Scan"));
+
+    for hint in [
+        "",
+        "/*+ SeqScan(orders) */",
+        "/*+ IndexScan(orders) */",
+        "/*+ IndexScan(o idx_orders_total) */",
+        "/*+ indexscan(O total) */",
+    ] {
+        let sql = format!("{} SELECT o.id FROM orders o WHERE o.total = 70", hint);
+        assert_eq!(rows(&mut engine, &mut ctx, &sql), vec![json!({"id": 7})]);
+        assert!(ctx.notices.is_empty(), "{}: {:?}", hint, ctx.notices);
+    }
+}
+
+#[test]
+fn join_hints_keep_the_result() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    let mut engine = setup(&temp, &mut ctx);
+
+    let sql = "SELECT o.id, c.name FROM orders o JOIN customers c ON o.customer_id = c.id \
+               WHERE o.total <= 50 ORDER BY o.id";
+    let expected = rows(&mut engine, &mut ctx, sql);
+    assert_eq!(expected.len(), 5);
+    for hint in [
+        "/*+ HashJoin(o c) */",
+        "/*+ NestLoop(c o) */",
+        "/*+ NestedLoop(orders customers) */",
+    ] {
+        assert_eq!(
+            rows(&mut engine, &mut ctx, &format!("{} {}", hint, sql)),
+            expected
+        );
+        assert!(ctx.notices.is_empty(), "{}: {:?}", hint, ctx.notices);
+    }
+}
+
+#[test]
+fn hints_that_cannot_be_followed_raise_notices() {
+    let temp = TempDir::new().unwrap();
+    let mut ctx = SessionContext::new();
+    let mut engine = setup(&temp, &mut ctx);
+
+    let cases = [
+        ("/*+ IndexScan(orders idx_missing) */", "has no index"),
+        ("/*+ IndexScan(orders) */", "no condition in the query"),
+        ("/*+ SeqScan(customers) */", "is not in the query"),
+        ("/*+ HashJoin(orders customers) */", "is not in the query"),
+        ("/*+ Leading(orders) */", "unrecognized hint"),
+        (
+            "/*+ SeqScan(orders customers) */",
+            "wrong number of arguments",
+        ),
+    ];
+    for (hint, notice) in cases {
+        let sql = format!(
+            "{} SELECT id FROM orders WHERE customer_id = 1 ORDER BY id LIMIT 2",
+            hint
+        );
+        // The statement still runs, planned as without the hint
+        assert_eq!(
+            rows(&mut engine, &mut ctx, &sql),
+            vec![json!({"id": 3}), json!({"id": 6})]
+        );
+        assert_eq!(ctx.notices.len(), 1, "{}: {:?}", hint, ctx.notices);
+        assert!(ctx.notices[0].contains(notice), "{}", ctx.notices[0]);
+    }
+
+    // The next statement starts without them
+    rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders WHERE total = 10",
+    );
+    assert!(ctx.notices.is_empty());
+}
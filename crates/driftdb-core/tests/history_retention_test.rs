@@ -0,0 +1,183 @@
+//! `ALTER TABLE ... SET (retain_versions = n)` and
+//! `SET (retain_history_for = '90 days')`: `VACUUM` folds the history
+//! outside the policy into the state at its oldest kept point, time travel
+//! inside the policy reads as before, and reads from before it fail.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use time::{Duration, OffsetDateTime};
+
+use driftdb_core::query::AsOf;
+use driftdb_core::schema::{ColumnDef, Schema};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::storage::{RetentionPolicy, TableStorage};
+use driftdb_core::{Engine, Event, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+/// Balances by id as of `seq`
+fn balances_at(engine: &mut Engine, seq: u64) -> Vec<Value> {
+    let mut rows = run(
+        engine,
+        &mut SessionContext::new(),
+        &format!(
+            "SELECT id, balance FROM accounts FOR SYSTEM_TIME AS OF @SEQ:{}",
+            seq
+        ),
+    );
+    rows.sort_by_key(|row| row["id"].as_i64());
+    rows
+}
+
+fn pruned_error(engine: &mut Engine, seq: u64) -> String {
+    execute_sql_in_session(
+        engine,
+        &format!("SELECT id FROM accounts FOR SYSTEM_TIME AS OF @SEQ:{}", seq),
+        &mut SessionContext::new(),
+    )
+    .unwrap_err()
+    .to_string()
+}
+
+#[test]
+fn vacuum_keeps_the_last_versions_and_refuses_reads_before_them() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE accounts (id INT PRIMARY KEY, balance INT)",
+    );
+    // Sequences 1-5 insert, 6-10 set 110, 11-15 set 120
+    for id in 1..=5 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!("INSERT INTO accounts (id, balance) VALUES ({}, 100)", id),
+        );
+    }
+    for balance in [110, 120] {
+        for id in 1..=5 {
+            run(
+                &mut engine,
+                &mut ctx,
+                &format!(
+                    "UPDATE accounts SET balance = {} WHERE id = {}",
+                    balance, id
+                ),
+            );
+        }
+    }
+    let at_12 = balances_at(&mut engine, 12);
+    assert_eq!(at_12[1], json!({"id": 2, "balance": 120}));
+    assert_eq!(at_12[2], json!({"id": 3, "balance": 110}));
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "ALTER TABLE accounts SET (retain_versions = 5)",
+    );
+    run(&mut engine, &mut ctx, "VACUUM accounts");
+
+    // The five versions after sequence 10 are kept, on top of its state
+    assert_eq!(balances_at(&mut engine, 12), at_12);
+    assert!(balances_at(&mut engine, 10)
+        .iter()
+        .all(|row| row["balance"] == json!(110)));
+    let err = pruned_error(&mut engine, 9);
+    assert!(err.contains("history pruned before sequence 10"), "{}", err);
+    assert!(balances_at(&mut engine, 15)
+        .iter()
+        .all(|row| row["balance"] == json!(120)));
+
+    // VACUUM FULL keeps the same window
+    run(&mut engine, &mut ctx, "VACUUM FULL accounts");
+    assert_eq!(balances_at(&mut engine, 12), at_12);
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(balances_at(&mut engine, 12), at_12);
+    assert!(pruned_error(&mut engine, 9).contains("history pruned before"));
+    assert_eq!(
+        engine.table_schema("accounts").unwrap().retention.versions,
+        Some(5)
+    );
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "ALTER TABLE accounts SET (retain_history_for = 'forever')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid retention period"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn history_older_than_the_window_is_pruned() {
+    let temp = TempDir::new().unwrap();
+    let schema = Schema::new(
+        "prices".to_string(),
+        "id".to_string(),
+        vec![ColumnDef {
+            name: "id".to_string(),
+            col_type: "INT".to_string(),
+            index: false,
+        }],
+    );
+    let storage = TableStorage::create(temp.path(), schema, None).unwrap();
+    // Ten prices for three items, one every ten days from 115 days ago
+    let now = OffsetDateTime::now_utc();
+    for sequence in 1..=10u64 {
+        let mut event = Event::new_insert(
+            "prices".to_string(),
+            json!(sequence % 3),
+            json!({"id": sequence % 3, "price": sequence}),
+        );
+        event.sequence = sequence;
+        event.timestamp = now - Duration::days(125 - 10 * sequence as i64);
+        storage.append_existing_event(event).unwrap();
+    }
+    let before: Vec<_> = (0..=10)
+        .map(|seq| storage.reconstruct_state_at(Some(seq)).unwrap())
+        .collect();
+
+    storage
+        .set_retention(RetentionPolicy {
+            history_for_secs: Some(90 * 24 * 60 * 60),
+            versions: None,
+        })
+        .unwrap();
+    storage.prune_history().unwrap().unwrap();
+    // Sequence 3, 95 days ago, is the last point outside the window
+    assert_eq!(storage.history_floor().unwrap().sequence, 3);
+    assert!(storage.prune_history().unwrap().is_none());
+
+    for days in [92, 60, 30] {
+        let as_of = AsOf::Timestamp(now - Duration::days(days));
+        let seq = storage.as_of_sequence(Some(&as_of)).unwrap().unwrap();
+        assert_eq!(
+            storage.reconstruct_state_at(Some(seq)).unwrap(),
+            before[seq as usize]
+        );
+    }
+    assert_eq!(storage.reconstruct_state_at(None).unwrap(), before[10]);
+
+    let as_of = AsOf::Timestamp(now - Duration::days(100));
+    let err = storage.as_of_sequence(Some(&as_of)).unwrap_err();
+    assert!(
+        err.to_string().contains("history pruned before sequence 3"),
+        "{}",
+        err
+    );
+    assert!(storage.reconstruct_state_at(Some(2)).is_err());
+}
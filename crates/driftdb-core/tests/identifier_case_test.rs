@@ -0,0 +1,120 @@
+//! `identifier_case`: unquoted identifiers fold to lowercase by default,
+//! as in PostgreSQL, so `Email` and `email` name the same column while
+//! the quoted `"Email"` names another. Sessions can fold to uppercase or
+//! match names exactly as written instead.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+#[test]
+fn unquoted_identifiers_fold_to_lowercase() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SHOW identifier_case"),
+        vec![json!({"identifier_case": "lower"})]
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE Contacts (Id INTEGER PRIMARY KEY, Email VARCHAR, \"Email\" VARCHAR)",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO CONTACTS (ID, EMAIL, \"Email\") VALUES (1, 'Plain@Example.com', 'Quoted@Example.com')",
+    );
+    assert!(engine.table_exists("contacts"));
+
+    let plain = vec![json!({"email": "Plain@Example.com"})];
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT Email FROM contacts"),
+        plain
+    );
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT email FROM Contacts"),
+        plain
+    );
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT \"email\" FROM contacts"),
+        plain
+    );
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT \"Email\" FROM contacts"),
+        vec![json!({"Email": "Quoted@Example.com"})]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM contacts WHERE EMAIL = 'Plain@Example.com'"
+        ),
+        vec![json!({"id": 1})]
+    );
+
+    // Quoting keeps the case, so this names a table that doesn't exist
+    assert!(execute_sql_in_session(&mut engine, "SELECT * FROM \"Contacts\"", &mut ctx).is_err());
+}
+
+#[test]
+fn sessions_can_fold_to_uppercase_or_preserve_case() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut upper = SessionContext::new();
+    let mut preserve = SessionContext::new();
+
+    run(&mut engine, &mut upper, "SET identifier_case = upper");
+    run(
+        &mut engine,
+        &mut upper,
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, label VARCHAR)",
+    );
+    run(
+        &mut engine,
+        &mut upper,
+        "INSERT INTO Items (Id, Label) VALUES (1, 'first')",
+    );
+    assert!(engine.table_exists("ITEMS"));
+    assert_eq!(
+        run(&mut engine, &mut upper, "SELECT label FROM items"),
+        vec![json!({"LABEL": "first"})]
+    );
+
+    run(&mut engine, &mut preserve, "SET identifier_case = preserve");
+    run(
+        &mut engine,
+        &mut preserve,
+        "CREATE TABLE Orders (Id INTEGER PRIMARY KEY, Total INTEGER)",
+    );
+    run(
+        &mut engine,
+        &mut preserve,
+        "INSERT INTO Orders (Id, Total) VALUES (1, 42)",
+    );
+    assert!(engine.table_exists("Orders"));
+    assert_eq!(
+        run(&mut engine, &mut preserve, "SELECT Total FROM Orders"),
+        vec![json!({"Total": 42})]
+    );
+    assert!(execute_sql_in_session(&mut engine, "SELECT * FROM orders", &mut preserve).is_err());
+
+    // The default session folds to lowercase, so it needs quotes for both
+    let mut default = SessionContext::new();
+    assert_eq!(
+        run(&mut engine, &mut default, "SELECT \"LABEL\" FROM \"ITEMS\""),
+        vec![json!({"LABEL": "first"})]
+    );
+    assert!(execute_sql_in_session(&mut engine, "SELECT * FROM Orders", &mut default).is_err());
+}
@@ -0,0 +1,214 @@
+//! INSERT ... SELECT: filling a table from a query through the regular
+//! insert path, in batches, atomically, inside transactions and under row
+//! security.
+
+use std::sync::Arc;
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::row_level_security::{
+    Policy, PolicyAction, PolicyCheck, RlsManager, RowSecurity, SecurityContext,
+};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> QueryResult {
+    execute_sql_in_session(engine, sql, ctx).unwrap()
+}
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    match run(engine, &mut SessionContext::new(), sql) {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let ctx = &mut SessionContext::new();
+    for sql in [
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, created_at VARCHAR, total INTEGER)",
+        "CREATE TABLE orders_archive (id INTEGER PRIMARY KEY, created_at VARCHAR, total INTEGER)",
+        "INSERT INTO orders (id, created_at, total) VALUES (1, '2023-05-01', 10), (2, '2023-11-30', 20), (3, '2024-02-01', 30)",
+    ] {
+        run(&mut engine, ctx, sql);
+    }
+    engine
+}
+
+#[test]
+fn archives_matching_rows() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let result = run(
+        &mut engine,
+        &mut SessionContext::new(),
+        "INSERT INTO orders_archive SELECT * FROM orders WHERE created_at < '2024-01-01'",
+    );
+    match result {
+        QueryResult::Success { message } => assert_eq!(message, "Inserted 2 row(s)"),
+        other => panic!("expected Success, got {:?}", other),
+    }
+    let archived = rows(&mut engine, "SELECT * FROM orders_archive ORDER BY id");
+    assert_eq!(archived.len(), 2);
+    assert_eq!(archived[1]["created_at"], "2023-11-30");
+    assert_eq!(archived[1]["total"], 20);
+}
+
+#[test]
+fn listed_columns_map_by_position_and_take_defaults() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let ctx = &mut SessionContext::new();
+    run(
+        &mut engine,
+        ctx,
+        "CREATE TABLE totals (order_id INTEGER PRIMARY KEY, amount INTEGER, source VARCHAR DEFAULT 'orders')",
+    );
+
+    let result = run(
+        &mut engine,
+        ctx,
+        "INSERT INTO totals (order_id, amount) SELECT id, total FROM orders ORDER BY total DESC LIMIT 2 RETURNING order_id",
+    );
+    match result {
+        QueryResult::Rows { data } => {
+            assert_eq!(data, [json!({"order_id": 3}), json!({"order_id": 2})])
+        }
+        other => panic!("expected Rows, got {:?}", other),
+    }
+    let stored = rows(&mut engine, "SELECT * FROM totals WHERE order_id = 3");
+    assert_eq!(stored[0]["amount"], 30);
+    assert_eq!(stored[0]["source"], "orders");
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO totals (order_id) SELECT id, total FROM orders",
+        ctx,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("more expressions than target columns"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn sources_larger_than_a_batch_are_read_in_key_order() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let ctx = &mut SessionContext::new();
+    let older = (10..2_510)
+        .map(|id| json!({"id": id, "created_at": "2022-01-01", "total": id % 7}))
+        .collect();
+    engine.append_events_batch("orders", older).unwrap();
+
+    run(
+        &mut engine,
+        ctx,
+        "INSERT INTO orders_archive SELECT * FROM orders WHERE created_at < '2023-01-01'",
+    );
+    let archived = rows(&mut engine, "SELECT * FROM orders_archive");
+    assert_eq!(archived.len(), 2_500);
+
+    // The key isn't in the select list, so it is carried alongside
+    run(
+        &mut engine,
+        ctx,
+        "CREATE TABLE totals (amount INTEGER, order_id INTEGER PRIMARY KEY)",
+    );
+    let result = run(
+        &mut engine,
+        ctx,
+        "INSERT INTO totals (amount, order_id) SELECT total, id FROM orders WHERE total > 0",
+    );
+    let expected = 3 + (10..2_510).filter(|id| id % 7 != 0).count();
+    match result {
+        QueryResult::Success { message } => {
+            assert_eq!(message, format!("Inserted {} row(s)", expected))
+        }
+        other => panic!("expected Success, got {:?}", other),
+    }
+    let stored = rows(&mut engine, "SELECT * FROM totals WHERE order_id = 2509");
+    assert_eq!(stored, [json!({"amount": 3, "order_id": 2509})]);
+}
+
+#[test]
+fn runs_inside_a_transaction_and_is_atomic() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let ctx = &mut SessionContext::new();
+
+    run(&mut engine, ctx, "BEGIN");
+    run(
+        &mut engine,
+        ctx,
+        "INSERT INTO orders_archive SELECT * FROM orders",
+    );
+    run(&mut engine, ctx, "ROLLBACK");
+    assert!(rows(&mut engine, "SELECT * FROM orders_archive").is_empty());
+
+    // A duplicate key part-way through leaves nothing behind
+    run(
+        &mut engine,
+        ctx,
+        "INSERT INTO orders_archive (id, created_at, total) VALUES (3, 'kept', 0)",
+    );
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO orders_archive SELECT * FROM orders ORDER BY id",
+        ctx,
+    )
+    .is_err());
+    let archived = rows(&mut engine, "SELECT * FROM orders_archive");
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0]["created_at"], "kept");
+}
+
+#[test]
+fn inserted_rows_must_pass_row_security() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let ctx = &mut SessionContext::new();
+    for sql in [
+        "CREATE TABLE drafts (id INTEGER PRIMARY KEY, owner VARCHAR)",
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, owner VARCHAR)",
+        "INSERT INTO drafts (id, owner) VALUES (1, 'alice'), (2, 'bob')",
+    ] {
+        run(&mut engine, ctx, sql);
+    }
+
+    let manager = Arc::new(RlsManager::new());
+    manager.enable_rls("posts").unwrap();
+    manager
+        .create_policy(
+            Policy::new(
+                "own_posts".to_string(),
+                "posts".to_string(),
+                PolicyAction::Insert,
+                PolicyCheck::Permissive,
+            )
+            .with_check("owner = $user".to_string()),
+        )
+        .unwrap();
+    ctx.row_security = Some(RowSecurity::new(
+        manager,
+        SecurityContext::new("alice".to_string(), vec![], false),
+    ));
+
+    let err = execute_sql_in_session(&mut engine, "INSERT INTO posts SELECT * FROM drafts", ctx)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("row-level security policy"), "{}", err);
+    assert!(rows(&mut engine, "SELECT * FROM posts").is_empty());
+
+    run(
+        &mut engine,
+        ctx,
+        "INSERT INTO posts SELECT * FROM drafts WHERE owner = 'alice'",
+    );
+    assert_eq!(rows(&mut engine, "SELECT * FROM posts").len(), 1);
+}
@@ -305,8 +305,8 @@ async fn test_isolation_levels() {
     let txn2 = tx_mgr.begin(IsolationLevel::ReadCommitted).unwrap();
 
     // Both can read
-    let _ = tx_mgr.read(&txn1, "key1");
-    let _ = tx_mgr.read(&txn2, "key1");
+    let _ = tx_mgr.read(&txn1, "test", "key1");
+    let _ = tx_mgr.read(&txn2, "test", "key1");
 
     // Test SERIALIZABLE with conflict
     let txn3 = tx_mgr.begin(IsolationLevel::Serializable).unwrap();
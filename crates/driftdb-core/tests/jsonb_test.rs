@@ -0,0 +1,179 @@
+//! JSONB columns: path operators, containment, and GIN indexes returning
+//! the same rows as a full scan.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn ids(engine: &mut Engine, sql: &str) -> Vec<String> {
+    let mut ids: Vec<String> = rows(engine, sql)
+        .iter()
+        .map(|row| row["id"].as_str().unwrap().to_string())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE orders (id VARCHAR, payload JSONB, PRIMARY KEY (id))",
+    );
+    for (id, doc) in [
+        (
+            "o1",
+            r#"{"status": "active", "items": [{"sku": "A1"}, {"sku": "B2"}], "tags": ["rush"]}"#,
+        ),
+        (
+            "o2",
+            r#"{"status": "shipped", "items": [{"sku": "C3"}], "tags": ["rush", "gift"]}"#,
+        ),
+        ("o3", r#"{"status": "active", "items": [], "tags": []}"#),
+    ] {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO orders (id, payload) VALUES ('{}', '{}')",
+                id, doc
+            ),
+        );
+    }
+    engine
+}
+
+#[test]
+fn path_operators() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // Text written to a JSONB column is stored as a document
+    let all = rows(&mut engine, "SELECT * FROM orders WHERE id = 'o1'");
+    assert_eq!(all[0]["payload"]["status"], json!("active"));
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM orders WHERE payload->>'status' = 'active'"
+        ),
+        vec!["o1", "o3"]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM orders WHERE payload->>'status' IN ('shipped') OR payload->>'note' IS NOT NULL"
+        ),
+        vec!["o2"]
+    );
+
+    let skus = rows(
+        &mut engine,
+        "SELECT payload->'items'->0->>'sku' AS sku FROM orders WHERE id = 'o1'",
+    );
+    assert_eq!(skus[0]["sku"], json!("A1"));
+
+    let missing = rows(
+        &mut engine,
+        "SELECT payload->'items'->0->>'sku' AS sku FROM orders WHERE id = 'o3'",
+    );
+    assert_eq!(missing[0]["sku"], serde_json::Value::Null);
+}
+
+#[test]
+fn containment_with_and_without_gin_index() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let queries = [
+        r#"SELECT * FROM orders WHERE payload @> '{"status": "active"}'"#,
+        r#"SELECT * FROM orders WHERE payload @> '{"tags": ["rush"]}'"#,
+        r#"SELECT * FROM orders WHERE payload @> '{"items": [{"sku": "C3"}]}'"#,
+        r#"SELECT * FROM orders WHERE '{"status": "shipped"}' <@ payload"#,
+        r#"SELECT * FROM orders WHERE payload @> '{"status": "gone"}'"#,
+        r#"SELECT * FROM orders WHERE payload @> '{"tags": ["rush"]}' AND id <> 'o2'"#,
+    ];
+    let scanned: Vec<Vec<String>> = queries.iter().map(|q| ids(&mut engine, q)).collect();
+    assert_eq!(scanned[0], vec!["o1", "o3"]);
+    assert_eq!(scanned[1], vec!["o1", "o2"]);
+    assert_eq!(scanned[2], vec!["o2"]);
+    assert_eq!(scanned[3], vec!["o2"]);
+    assert!(scanned[4].is_empty());
+    assert_eq!(scanned[5], vec!["o1"]);
+
+    run(
+        &mut engine,
+        "CREATE INDEX idx_payload ON orders USING GIN (payload)",
+    );
+    assert!(engine
+        .lookup_by_gin_index("orders", "payload", &json!({"status": "active"}))
+        .is_some());
+    let indexed: Vec<Vec<String>> = queries.iter().map(|q| ids(&mut engine, q)).collect();
+    assert_eq!(indexed, scanned);
+
+    // The index follows later writes
+    run(
+        &mut engine,
+        r#"UPDATE orders SET payload = '{"status": "shipped", "tags": []}' WHERE id = 'o1'"#,
+    );
+    run(&mut engine, "DELETE FROM orders WHERE id = 'o3'");
+    run(
+        &mut engine,
+        r#"INSERT INTO orders (id, payload) VALUES ('o4', '{"status": "active"}')"#,
+    );
+    assert_eq!(ids(&mut engine, queries[0]), vec!["o4"]);
+    assert_eq!(ids(&mut engine, queries[1]), vec!["o2"]);
+    assert_eq!(ids(&mut engine, queries[3]), vec!["o1", "o2"]);
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert!(engine
+        .lookup_by_gin_index("orders", "payload", &json!({"status": "active"}))
+        .is_some());
+    assert_eq!(ids(&mut engine, queries[0]), vec!["o4"]);
+    assert_eq!(ids(&mut engine, queries[3]), vec!["o1", "o2"]);
+}
+
+#[test]
+fn invalid_json_is_rejected() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO orders (id, payload) VALUES ('bad', '{not json')",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("invalid input syntax for type jsonb"));
+    assert!(rows(&mut engine, "SELECT * FROM orders WHERE id = 'bad'").is_empty());
+}
+
+#[test]
+fn gin_index_requires_a_json_column() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+    let err = execute_sql_in_session(
+        &mut engine,
+        "CREATE INDEX idx_id ON orders USING GIN (id)",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("gin"), "{}", err);
+}
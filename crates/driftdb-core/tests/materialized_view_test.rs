@@ -0,0 +1,282 @@
+//! Materialized views: contents are stored at refresh time, go stale as the
+//! sources change, and catch up with a full or an incremental refresh that
+//! must match recomputing the view from scratch.
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::views::RefreshMode;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) -> String {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Success { message } => message,
+        other => format!("{:?}", other),
+    }
+}
+
+/// Rows in a stable order, for comparing results as sets
+fn sorted(mut data: Vec<Value>) -> Vec<Value> {
+    data.sort_by_key(|row| row.to_string());
+    data
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE orders (id INT, region VARCHAR, amount INT, PRIMARY KEY (id))",
+    );
+    for i in 1..=12 {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO orders (id, region, amount) VALUES ({}, '{}', {})",
+                i,
+                if i % 2 == 0 { "east" } else { "west" },
+                i * 5
+            ),
+        );
+    }
+    engine
+}
+
+const BIG_ORDERS: &str = "SELECT id, region, amount FROM orders WHERE amount > 20";
+
+#[test]
+fn view_is_stale_until_refreshed() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        &format!("CREATE MATERIALIZED VIEW big_orders AS {}", BIG_ORDERS),
+    );
+    assert_eq!(
+        sorted(rows(&mut engine, "SELECT * FROM big_orders")),
+        sorted(rows(&mut engine, BIG_ORDERS))
+    );
+    assert!(!engine
+        .materialized_view_status("big_orders")
+        .unwrap()
+        .is_stale());
+
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, region, amount) VALUES (13, 'east', 100)",
+    );
+    run(&mut engine, "DELETE FROM orders WHERE id = 12");
+
+    // Reads keep returning the stored contents
+    assert_eq!(rows(&mut engine, "SELECT * FROM big_orders").len(), 8);
+    let status = engine.materialized_view_status("big_orders").unwrap();
+    assert!(status.is_stale());
+    assert_eq!(status.pending_events, 2);
+    assert!(status.current_sequence > status.last_refresh_sequence);
+
+    assert_eq!(
+        run(&mut engine, "REFRESH MATERIALIZED VIEW big_orders"),
+        "Materialized view 'big_orders' refreshed"
+    );
+    assert_eq!(
+        sorted(rows(&mut engine, "SELECT * FROM big_orders")),
+        sorted(rows(&mut engine, BIG_ORDERS))
+    );
+    let status = engine.materialized_view_status("big_orders").unwrap();
+    assert!(!status.is_stale());
+    assert_eq!(status.last_refresh_sequence, status.current_sequence);
+
+    // The outer query's WHERE applies to the stored rows
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT * FROM big_orders WHERE region = 'east'"
+        )
+        .len(),
+        4
+    );
+}
+
+#[test]
+fn incremental_refresh_matches_recompute() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        &format!("CREATE MATERIALIZED VIEW big_orders AS {}", BIG_ORDERS),
+    );
+    assert!(
+        engine
+            .materialized_view_status("big_orders")
+            .unwrap()
+            .incremental
+    );
+
+    let rounds: [&[&str]; 3] = [
+        &[
+            "INSERT INTO orders (id, region, amount) VALUES (20, 'north', 500)",
+            "INSERT INTO orders (id, region, amount) VALUES (21, 'north', 1)",
+        ],
+        &[
+            // Moves rows into and out of the view, and changes one in place
+            "UPDATE orders SET amount = 99 WHERE id = 2",
+            "UPDATE orders SET amount = 3 WHERE id = 10",
+            "UPDATE orders SET region = 'south' WHERE id = 11",
+            "DELETE FROM orders WHERE id = 12",
+        ],
+        &[
+            "DELETE FROM orders WHERE id = 20",
+            "INSERT INTO orders (id, region, amount) VALUES (12, 'east', 60)",
+            "UPDATE orders SET amount = 1000 WHERE region = 'west'",
+        ],
+    ];
+    for statements in rounds {
+        for sql in statements {
+            run(&mut engine, sql);
+        }
+        assert_eq!(
+            run(
+                &mut engine,
+                "REFRESH MATERIALIZED VIEW big_orders INCREMENTAL"
+            ),
+            "Materialized view 'big_orders' refreshed incrementally"
+        );
+        assert_eq!(
+            sorted(rows(&mut engine, "SELECT * FROM big_orders")),
+            sorted(rows(&mut engine, BIG_ORDERS))
+        );
+        assert!(!engine
+            .materialized_view_status("big_orders")
+            .unwrap()
+            .is_stale());
+    }
+}
+
+#[test]
+fn incremental_refresh_falls_back_to_full_when_it_cannot_apply_deltas() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // Aggregates can't be maintained row by row
+    let totals = "SELECT region, COUNT(*) AS n FROM orders GROUP BY region";
+    run(
+        &mut engine,
+        &format!("CREATE MATERIALIZED VIEW region_totals AS {}", totals),
+    );
+    assert!(
+        !engine
+            .materialized_view_status("region_totals")
+            .unwrap()
+            .incremental
+    );
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, region, amount) VALUES (13, 'north', 7)",
+    );
+    assert_eq!(
+        engine
+            .refresh_materialized_view("region_totals", RefreshMode::Incremental)
+            .unwrap(),
+        RefreshMode::Full
+    );
+    assert_eq!(
+        sorted(rows(&mut engine, "SELECT * FROM region_totals")),
+        sorted(rows(&mut engine, totals))
+    );
+
+    // A schema change since the last refresh forces a recompute
+    run(
+        &mut engine,
+        "CREATE MATERIALIZED VIEW all_orders AS SELECT * FROM orders",
+    );
+    run(
+        &mut engine,
+        "ALTER TABLE orders ADD COLUMN note VARCHAR DEFAULT 'none'",
+    );
+    assert_eq!(
+        engine
+            .refresh_materialized_view("all_orders", RefreshMode::Incremental)
+            .unwrap(),
+        RefreshMode::Full
+    );
+    assert_eq!(
+        sorted(rows(&mut engine, "SELECT * FROM all_orders")),
+        sorted(rows(&mut engine, "SELECT * FROM orders"))
+    );
+}
+
+#[test]
+fn concurrently_show_and_drop() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        &format!("CREATE MATERIALIZED VIEW big_orders AS {}", BIG_ORDERS),
+    );
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, region, amount) VALUES (13, 'east', 100)",
+    );
+
+    let listed = rows(&mut engine, "SHOW MATERIALIZED VIEWS");
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["name"], "big_orders");
+    assert_eq!(listed[0]["stale"], true);
+    assert_eq!(listed[0]["pending_events"], 1);
+
+    run(
+        &mut engine,
+        "REFRESH MATERIALIZED VIEW CONCURRENTLY big_orders INCREMENTAL",
+    );
+    assert_eq!(rows(&mut engine, "SELECT * FROM big_orders").len(), 9);
+    assert_eq!(
+        rows(&mut engine, "SHOW MATERIALIZED VIEWS")[0]["stale"],
+        false
+    );
+
+    assert!(execute_sql(&mut engine, "REFRESH MATERIALIZED VIEW big_orders FAST").is_err());
+    run(&mut engine, "CREATE VIEW plain AS SELECT id FROM orders");
+    assert!(execute_sql(&mut engine, "REFRESH MATERIALIZED VIEW plain").is_err());
+    assert!(execute_sql(&mut engine, "DROP MATERIALIZED VIEW plain").is_err());
+
+    run(&mut engine, "DROP MATERIALIZED VIEW big_orders");
+    assert!(rows(&mut engine, "SHOW MATERIALIZED VIEWS").is_empty());
+    run(&mut engine, "DROP MATERIALIZED VIEW IF EXISTS big_orders");
+}
+
+#[test]
+fn contents_are_repopulated_after_reopen() {
+    let temp = TempDir::new().unwrap();
+    {
+        let mut engine = setup(&temp);
+        run(
+            &mut engine,
+            &format!("CREATE MATERIALIZED VIEW big_orders AS {}", BIG_ORDERS),
+        );
+    }
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert!(
+        !engine
+            .materialized_view_status("big_orders")
+            .unwrap()
+            .populated
+    );
+    assert_eq!(
+        sorted(rows(&mut engine, "SELECT * FROM big_orders")),
+        sorted(rows(&mut engine, BIG_ORDERS))
+    );
+    assert!(
+        engine
+            .materialized_view_status("big_orders")
+            .unwrap()
+            .populated
+    );
+}
@@ -0,0 +1,237 @@
+//! Versioned SQL migrations: applying in order, recording checksums,
+//! refusing edited migrations and reverting with down SQL.
+
+use std::fs;
+
+use tempfile::TempDir;
+
+use driftdb_core::migration_runner::{MigrationRunner, SqlMigration};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn write_migrations(dir: &std::path::Path) {
+    fs::write(
+        dir.join("V1__create_users.sql"),
+        "-- users\nCREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR);\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("V2__seed_users.sql"),
+        "INSERT INTO users (id, name) VALUES (1, 'ada');\nINSERT INTO users (id, name) VALUES (2, 'grace');\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.join("V2__seed_users.down.sql"),
+        "DELETE FROM users WHERE id IN (1, 2);",
+    )
+    .unwrap();
+    fs::write(dir.join("README.md"), "not a migration").unwrap();
+}
+
+#[test]
+fn applies_pending_migrations_in_order_once() {
+    let data = TempDir::new().unwrap();
+    let migrations = TempDir::new().unwrap();
+    write_migrations(migrations.path());
+    let mut engine = Engine::init(data.path()).unwrap();
+
+    let runner = MigrationRunner::from_dir(migrations.path()).unwrap();
+    assert_eq!(runner.up(&mut engine).unwrap(), [1, 2]);
+    assert_eq!(rows(&mut engine, "SELECT * FROM users").len(), 2);
+    // User 2 was written in the same transaction as the record for
+    // version 2; the two rows share a key but not a table
+    assert_eq!(
+        rows(&mut engine, "SELECT name FROM users WHERE id = 2")[0]["name"],
+        "grace"
+    );
+
+    let applied = runner.applied(&mut engine).unwrap();
+    assert_eq!(applied.len(), 2);
+    assert_eq!(applied[0].name, "create_users");
+    assert_eq!(
+        applied[1].checksum,
+        runner.migrations().nth(1).unwrap().checksum()
+    );
+
+    // Nothing left to do
+    assert!(runner.up(&mut engine).unwrap().is_empty());
+    assert!(runner.status(&mut engine).unwrap().pending.is_empty());
+}
+
+#[test]
+fn edited_migration_is_rejected() {
+    let data = TempDir::new().unwrap();
+    let migrations = TempDir::new().unwrap();
+    write_migrations(migrations.path());
+    let mut engine = Engine::init(data.path()).unwrap();
+    MigrationRunner::from_dir(migrations.path())
+        .unwrap()
+        .up(&mut engine)
+        .unwrap();
+
+    fs::write(
+        migrations.path().join("V1__create_users.sql"),
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);",
+    )
+    .unwrap();
+    fs::write(
+        migrations.path().join("V3__more.sql"),
+        "INSERT INTO users (id, name) VALUES (3, 'linus');",
+    )
+    .unwrap();
+
+    let runner = MigrationRunner::from_dir(migrations.path()).unwrap();
+    let err = runner.up(&mut engine).unwrap_err().to_string();
+    assert!(err.contains("has changed since it was applied"), "{}", err);
+    // Nothing after the check ran
+    assert_eq!(rows(&mut engine, "SELECT * FROM users").len(), 2);
+}
+
+#[test]
+fn failed_migration_rolls_back_its_rows_and_record() {
+    let data = TempDir::new().unwrap();
+    let mut engine = Engine::init(data.path()).unwrap();
+    let mut runner = MigrationRunner::new();
+    runner
+        .add(SqlMigration::new(
+            1,
+            "create",
+            "CREATE TABLE t (id INTEGER PRIMARY KEY)",
+        ))
+        .unwrap();
+    runner
+        .add(SqlMigration::new(
+            2,
+            "broken",
+            "INSERT INTO t (id) VALUES (1); INSERT INTO missing (id) VALUES (1);",
+        ))
+        .unwrap();
+
+    let err = runner.up(&mut engine).unwrap_err().to_string();
+    assert!(err.contains("Migration 2 (broken) failed"), "{}", err);
+    assert!(rows(&mut engine, "SELECT * FROM t").is_empty());
+
+    let status = runner.status(&mut engine).unwrap();
+    assert_eq!(status.applied.len(), 1);
+    assert_eq!(status.pending, [2]);
+}
+
+#[test]
+fn down_reverts_the_latest_migration() {
+    let data = TempDir::new().unwrap();
+    let migrations = TempDir::new().unwrap();
+    write_migrations(migrations.path());
+    let mut engine = Engine::init(data.path()).unwrap();
+    let runner = MigrationRunner::from_dir(migrations.path()).unwrap();
+    runner.up(&mut engine).unwrap();
+
+    assert_eq!(runner.down(&mut engine).unwrap(), Some(2));
+    assert!(rows(&mut engine, "SELECT * FROM users").is_empty());
+    assert_eq!(runner.status(&mut engine).unwrap().pending, [2]);
+
+    // V1 has no down migration
+    let err = runner.down(&mut engine).unwrap_err().to_string();
+    assert!(err.contains("has no down migration"), "{}", err);
+
+    // Re-applying works after a revert
+    assert_eq!(runner.up(&mut engine).unwrap(), [2]);
+}
+
+#[test]
+fn badly_named_migration_file_is_an_error() {
+    let migrations = TempDir::new().unwrap();
+    fs::write(migrations.path().join("create_users.sql"), "SELECT 1").unwrap();
+    assert!(MigrationRunner::from_dir(migrations.path()).is_err());
+}
+
+const DECLARED: &str = "
+    CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE, visits INTEGER DEFAULT 0);
+    CREATE TABLE posts (id INTEGER PRIMARY KEY, author INTEGER, title VARCHAR);
+    CREATE INDEX posts_author ON posts (author);
+";
+
+#[test]
+fn plan_creates_declared_schema_then_converges() {
+    let data = TempDir::new().unwrap();
+    let mut engine = Engine::init(data.path()).unwrap();
+
+    let plan = MigrationRunner::plan(&engine, DECLARED).unwrap();
+    let sql: Vec<&str> = plan.changes.iter().map(|c| c.sql.as_str()).collect();
+    assert_eq!(sql.len(), 3, "{:?}", sql);
+    assert!(sql[0].starts_with("CREATE TABLE users"));
+    assert_eq!(sql[2], "CREATE INDEX posts_author ON posts (author)");
+    assert_eq!(plan.apply(&mut engine, false).unwrap(), 3);
+
+    let plan = MigrationRunner::plan(&engine, DECLARED).unwrap();
+    assert!(plan.is_empty(), "{:?}", plan);
+}
+
+#[test]
+fn plan_adds_columns_and_guards_drops() {
+    let data = TempDir::new().unwrap();
+    let mut engine = Engine::init(data.path()).unwrap();
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR UNIQUE, nickname VARCHAR)",
+        "CREATE TABLE legacy (id INTEGER PRIMARY KEY)",
+        "CREATE TABLE posts (id INTEGER PRIMARY KEY, author INTEGER, title TEXT)",
+        "CREATE INDEX ON posts (author)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut SessionContext::new()).unwrap();
+    }
+
+    let plan = MigrationRunner::plan(&engine, DECLARED).unwrap();
+    let changes: Vec<(&str, bool)> = plan
+        .changes
+        .iter()
+        .map(|c| (c.sql.as_str(), c.destructive))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (
+                "ALTER TABLE users ADD COLUMN visits INTEGER DEFAULT 0",
+                false
+            ),
+            ("ALTER TABLE users DROP COLUMN nickname", true),
+            ("DROP TABLE legacy", true),
+        ]
+    );
+    assert_eq!(
+        plan.unsupported,
+        ["posts.title: type is TEXT, declared as VARCHAR"]
+    );
+
+    // Nothing runs unless drops are allowed
+    let err = plan.apply(&mut engine, false).unwrap_err().to_string();
+    assert!(err.contains("DROP TABLE legacy"), "{}", err);
+    assert!(engine.table_exists("legacy"));
+
+    plan.apply(&mut engine, true).unwrap();
+    assert!(!engine.table_exists("legacy"));
+    let columns: Vec<String> = engine
+        .table_schema("users")
+        .unwrap()
+        .columns
+        .into_iter()
+        .map(|c| c.name)
+        .collect();
+    assert_eq!(columns, ["id", "email", "visits"]);
+}
+
+#[test]
+fn declared_schema_rejects_other_statements() {
+    let data = TempDir::new().unwrap();
+    let engine = Engine::init(data.path()).unwrap();
+    let err = MigrationRunner::plan(&engine, "INSERT INTO users (id) VALUES (1);")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("may only contain"), "{}", err);
+}
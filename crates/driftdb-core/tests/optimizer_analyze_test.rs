@@ -373,15 +373,12 @@ fn analyze_high_cardinality_caps_distinct_count() {
             indexed_columns: vec![],
         })
         .unwrap();
-    // 11_000 rows, all-unique values on `v`.
-    for i in 0..11_000 {
-        engine
-            .execute_query(Query::Insert {
-                table: "t".to_string(),
-                data: json!({"id": format!("r{}", i), "v": format!("unique_{}", i)}),
-            })
-            .unwrap();
-    }
+    // 11_000 rows, all-unique values on `v`, written as one batch so
+    // the load doesn't dominate the test's run time.
+    let rows = (0..11_000)
+        .map(|i| json!({"id": format!("r{}", i), "v": format!("unique_{}", i)}))
+        .collect();
+    engine.append_events_batch("t", rows).unwrap();
     run_ok(&mut engine, "ANALYZE TABLE t");
     assert_eq!(engine.query_optimizer().statistics_row_count("t"), Some(11_000));
 }
@@ -630,3 +627,140 @@ fn analyze_is_the_production_path_smoke_test() {
         "after re-ANALYZE: high_card still more selective"
     );
 }
+
+// ─── Sampling, histograms and most common values ────────────────
+
+#[test]
+fn analyze_samples_large_tables() {
+    use driftdb_core::optimizer::{AnalyzeConfig, HistogramKind};
+
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    engine
+        .execute_query(Query::CreateTable {
+            name: "orders".to_string(),
+            primary_key: "id".to_string(),
+            indexed_columns: vec![],
+        })
+        .unwrap();
+    let rows = (0..2_000)
+        .map(|i| {
+            let status = if i % 10 == 0 { "refunded" } else { "paid" };
+            json!({"id": format!("o{}", i), "amount": i, "status": status})
+        })
+        .collect();
+    engine.append_events_batch("orders", rows).unwrap();
+    engine.query_optimizer().set_analyze_config(AnalyzeConfig {
+        sample_rows: 500,
+        histogram_buckets: 20,
+        ..AnalyzeConfig::default()
+    });
+
+    let stats = engine.collect_table_statistics("orders").unwrap();
+    assert_eq!(stats.row_count, 2_000);
+    assert_eq!(stats.collection_method, "sample");
+
+    let amount = &stats.column_stats["amount"];
+    let histogram = amount.histogram.as_ref().unwrap();
+    assert_eq!(histogram.kind, HistogramKind::EquiDepth);
+    assert_eq!(histogram.bucket_count, 20);
+    assert_eq!(
+        histogram.buckets.iter().map(|b| b.frequency).sum::<usize>(),
+        500
+    );
+    // Unique per row: estimated well past the 500 sampled
+    assert!(amount.distinct_values > 1_000, "{}", amount.distinct_values);
+    assert_eq!(amount.null_fraction, 0.0);
+
+    let status = &stats.column_stats["status"];
+    assert_eq!(status.distinct_values, 2);
+    assert_eq!(status.most_common_values[0].value, json!("paid"));
+    assert_eq!(status.most_common_values.len(), 2);
+
+    run_ok(&mut engine, "ANALYZE TABLE orders");
+    assert_eq!(
+        engine.query_optimizer().statistics_row_count("orders"),
+        Some(2_000)
+    );
+}
+
+#[test]
+fn analyze_column_list_refreshes_only_those_columns() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run_ok(
+        &mut engine,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, a INTEGER, b INTEGER)",
+    );
+    for i in 0..10 {
+        run_ok(
+            &mut engine,
+            &format!("INSERT INTO t (id, a, b) VALUES ({}, {}, {})", i, i, i),
+        );
+    }
+    run_ok(&mut engine, "ANALYZE t");
+    for i in 10..30 {
+        run_ok(
+            &mut engine,
+            &format!("INSERT INTO t (id, a, b) VALUES ({}, {}, {})", i, i, i),
+        );
+    }
+
+    run_ok(&mut engine, "ANALYZE t (a)");
+    let stats = engine.query_optimizer().table_statistics("t").unwrap();
+    assert_eq!(stats.row_count, 30);
+    assert_eq!(stats.column_stats["a"].distinct_values, 30);
+    assert_eq!(stats.column_stats["b"].distinct_values, 10);
+
+    run_ok(&mut engine, "ANALYZE TABLE t (\"b\", id);");
+    let stats = engine.query_optimizer().table_statistics("t").unwrap();
+    assert_eq!(stats.column_stats["b"].distinct_values, 30);
+
+    let err = execute_sql(&mut engine, "ANALYZE t (missing)")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("column \"missing\""), "{}", err);
+    assert!(execute_sql(&mut engine, "ANALYZE nope (a)").is_err());
+}
+
+#[test]
+fn writes_count_towards_the_next_analyze() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run_ok(
+        &mut engine,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, a INTEGER)",
+    );
+    for i in 0..4 {
+        run_ok(
+            &mut engine,
+            &format!("INSERT INTO t (id, a) VALUES ({}, 0)", i),
+        );
+    }
+    run_ok(&mut engine, "UPDATE t SET a = 1 WHERE id = 0");
+    run_ok(&mut engine, "DELETE FROM t WHERE id = 1");
+    assert_eq!(engine.changes_since_analyze("t"), 6);
+    assert_eq!(engine.tables_due_for_analyze(5), ["t"]);
+    assert!(engine.tables_due_for_analyze(6).is_empty());
+
+    // A column list doesn't reset the count; the whole table does
+    run_ok(&mut engine, "ANALYZE t (a)");
+    assert_eq!(engine.changes_since_analyze("t"), 6);
+    run_ok(&mut engine, "ANALYZE");
+    assert_eq!(engine.changes_since_analyze("t"), 0);
+
+    // Bulk loads need an empty table
+    run_ok(
+        &mut engine,
+        "CREATE TABLE loaded (id INTEGER PRIMARY KEY, a INTEGER)",
+    );
+    engine.begin_bulk_load("loaded").unwrap();
+    engine
+        .bulk_load_rows(
+            "loaded",
+            vec![json!({"id": 10, "a": 2}), json!({"id": 11, "a": 3})],
+        )
+        .unwrap();
+    engine.finish_bulk_load("loaded").unwrap();
+    assert_eq!(engine.changes_since_analyze("loaded"), 2);
+}
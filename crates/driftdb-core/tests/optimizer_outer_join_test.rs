@@ -332,12 +332,15 @@ fn full_outer_includes_unmatched_from_both_sides() {
         .iter()
         .filter_map(|r| r.get("id").and_then(|v| v.as_str()))
         .collect();
-    // 'id' is the left's id (users.id) for matched + unmatched-left
-    // rows; for unmatched-right rows it's depts.id (passed through
-    // bare).
+    // 'id' is the left's id (users.id) on every row, so it is NULL for
+    // unmatched-right rows; their depts.id is under `d.id`, as on
+    // matched rows.
     assert!(user_ids.contains("u1"));
     assert!(user_ids.contains("u_lonely"));
-    assert!(user_ids.contains("d_empty"));
+    assert!(!user_ids.contains("d_empty"));
+    assert!(rs
+        .iter()
+        .any(|r| r["id"].is_null() && r["d.id"] == json!("d_empty")));
 }
 
 #[test]
@@ -0,0 +1,97 @@
+//! ORDER BY with several keys, NULLS FIRST / NULLS LAST and a
+//! case-insensitive collation.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE tasks (id INTEGER PRIMARY KEY, priority INTEGER, created_at VARCHAR, title VARCHAR)",
+        "INSERT INTO tasks (id, priority, created_at, title) VALUES (1, 2, '2024-01-02', 'banana')",
+        "INSERT INTO tasks (id, priority, created_at, title) VALUES (2, 2, NULL, 'Cherry')",
+        "INSERT INTO tasks (id, priority, created_at, title) VALUES (3, 1, '2024-01-01', 'apple')",
+        "INSERT INTO tasks (id, priority, created_at, title) VALUES (4, NULL, '2024-01-03', 'Apricot')",
+        "INSERT INTO tasks (id, priority, created_at, title) VALUES (5, 2, '2024-01-01', 'date')",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn ids(engine: &mut Engine, ctx: &mut SessionContext, order_by: &str) -> Vec<i64> {
+    let sql = format!("SELECT id FROM tasks ORDER BY {}", order_by);
+    match execute_sql_in_session(engine, &sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data.iter().map(|r| r["id"].as_i64().unwrap()).collect(),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn work_queue_order() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "priority DESC, created_at ASC NULLS LAST"),
+        [4, 5, 1, 2, 3]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "priority DESC NULLS LAST, created_at ASC NULLS LAST"
+        ),
+        [5, 1, 2, 3, 4]
+    );
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "priority ASC, created_at DESC"),
+        [3, 2, 1, 5, 4]
+    );
+}
+
+#[test]
+fn null_placement_in_both_directions() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    // By default NULLs sort as the largest value
+    assert_eq!(ids(&mut engine, &mut ctx, "created_at, id"), [3, 5, 1, 4, 2]);
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "created_at DESC, id"),
+        [2, 4, 1, 3, 5]
+    );
+
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "created_at ASC NULLS FIRST, id"),
+        [2, 3, 5, 1, 4]
+    );
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "created_at DESC NULLS LAST, id"),
+        [4, 1, 3, 5, 2]
+    );
+}
+
+#[test]
+fn case_insensitive_collation() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(ids(&mut engine, &mut ctx, "title"), [4, 2, 3, 1, 5]);
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "title COLLATE \"case_insensitive\""),
+        [3, 4, 1, 2, 5]
+    );
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "title COLLATE \"case_insensitive\" DESC"),
+        [5, 2, 1, 4, 3]
+    );
+
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "SELECT id FROM tasks ORDER BY title COLLATE \"klingon\"",
+        &mut ctx
+    )
+    .is_err());
+}
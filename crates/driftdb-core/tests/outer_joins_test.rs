@@ -0,0 +1,179 @@
+//! `LEFT`, `RIGHT` and `FULL OUTER JOIN` through SQL: the side without a
+//! match is NULL-extended, `alias.*` lists that side's columns as NULLs,
+//! NULL join keys match nothing, and the hash and nested-loop joins agree.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+/// Users 1-3, where cy has no orders; order 13 has no user and order 14
+/// a user that doesn't exist. With `force_hash` the optimizer believes
+/// both tables are large, so it picks the hash join over nested loops.
+fn setup(temp: &TempDir, force_hash: bool) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR)",
+        "CREATE TABLE orders (id INT PRIMARY KEY, user_id INT, total INT)",
+        "CREATE TABLE refunds (id INT PRIMARY KEY, user_id INT)",
+        "CREATE TABLE coupons (code VARCHAR PRIMARY KEY, user_id INT)",
+        "INSERT INTO users (id, name) VALUES (1, 'ada')",
+        "INSERT INTO users (id, name) VALUES (2, 'bob')",
+        "INSERT INTO users (id, name) VALUES (3, 'cy')",
+        "INSERT INTO orders (id, user_id, total) VALUES (10, 1, 100)",
+        "INSERT INTO orders (id, user_id, total) VALUES (11, 1, 50)",
+        "INSERT INTO orders (id, user_id, total) VALUES (12, 2, 75)",
+        "INSERT INTO orders (id, user_id, total) VALUES (13, NULL, 20)",
+        "INSERT INTO orders (id, user_id, total) VALUES (14, 9, 5)",
+        "INSERT INTO refunds (id, user_id) VALUES (1, NULL)",
+    ] {
+        run(&mut engine, &mut ctx, sql);
+    }
+    if force_hash {
+        for table in ["users", "orders", "refunds", "coupons"] {
+            engine.query_optimizer().register_table_indexes(
+                table,
+                &["user_id".to_string()],
+                100_000,
+            );
+        }
+    }
+    (engine, ctx)
+}
+
+fn sorted(mut rows: Vec<Value>) -> Vec<Value> {
+    rows.sort_by_key(|row| row.to_string());
+    rows
+}
+
+#[test]
+fn left_join_null_extends_the_right() {
+    for force_hash in [false, true] {
+        let temp = TempDir::new().unwrap();
+        let (mut engine, mut ctx) = setup(&temp, force_hash);
+
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT u.*, o.total FROM users u LEFT JOIN orders o ON o.user_id = u.id",
+        );
+        assert_eq!(
+            sorted(rows),
+            vec![
+                json!({"id": 1, "name": "ada", "total": 100}),
+                json!({"id": 1, "name": "ada", "total": 50}),
+                json!({"id": 2, "name": "bob", "total": 75}),
+                json!({"id": 3, "name": "cy", "total": null}),
+            ]
+        );
+
+        // An empty right side has no rows to take column names from, so
+        // its columns come from the table
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT u.name, c.* FROM users u LEFT JOIN coupons c ON c.user_id = u.id \
+             WHERE u.id = 3",
+        );
+        assert_eq!(
+            rows,
+            vec![json!({"name": "cy", "code": null, "user_id": null})]
+        );
+    }
+}
+
+#[test]
+fn right_join_null_extends_the_left() {
+    for force_hash in [false, true] {
+        let temp = TempDir::new().unwrap();
+        let (mut engine, mut ctx) = setup(&temp, force_hash);
+
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT o.id AS order_id, u.name FROM users u \
+             RIGHT JOIN orders o ON o.user_id = u.id",
+        );
+        assert_eq!(
+            sorted(rows),
+            vec![
+                json!({"order_id": 10, "name": "ada"}),
+                json!({"order_id": 11, "name": "ada"}),
+                json!({"order_id": 12, "name": "bob"}),
+                json!({"order_id": 13, "name": null}),
+                json!({"order_id": 14, "name": null}),
+            ]
+        );
+    }
+}
+
+#[test]
+fn full_join_keeps_unmatched_rows_from_both_sides() {
+    for force_hash in [false, true] {
+        let temp = TempDir::new().unwrap();
+        let (mut engine, mut ctx) = setup(&temp, force_hash);
+
+        // Both tables have an `id`; each side's reads NULL where that
+        // side is missing
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT u.id AS user_id, o.id AS order_id FROM users u \
+             FULL OUTER JOIN orders o ON o.user_id = u.id",
+        );
+        assert_eq!(
+            sorted(rows),
+            vec![
+                json!({"user_id": 1, "order_id": 10}),
+                json!({"user_id": 1, "order_id": 11}),
+                json!({"user_id": 2, "order_id": 12}),
+                json!({"user_id": 3, "order_id": null}),
+                json!({"user_id": null, "order_id": 13}),
+                json!({"user_id": null, "order_id": 14}),
+            ]
+        );
+    }
+}
+
+#[test]
+fn null_keys_match_nothing() {
+    for force_hash in [false, true] {
+        let temp = TempDir::new().unwrap();
+        let (mut engine, mut ctx) = setup(&temp, force_hash);
+
+        // Order 13 and the refund both have a NULL user_id
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT o.id FROM orders o JOIN refunds r ON o.user_id = r.user_id",
+        );
+        assert!(rows.is_empty(), "{:?}", rows);
+
+        let rows = run(
+            &mut engine,
+            &mut ctx,
+            "SELECT o.id AS order_id, r.id AS refund_id FROM orders o \
+             FULL JOIN refunds r ON o.user_id = r.user_id",
+        );
+        assert_eq!(
+            sorted(rows),
+            vec![
+                json!({"order_id": 10, "refund_id": null}),
+                json!({"order_id": 11, "refund_id": null}),
+                json!({"order_id": 12, "refund_id": null}),
+                json!({"order_id": 13, "refund_id": null}),
+                json!({"order_id": 14, "refund_id": null}),
+                json!({"order_id": null, "refund_id": 1}),
+            ]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! LIMIT/OFFSET paging and keyset pagination, which read ordered pages
+//! off an index when the sort column has one.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, score INTEGER, code VARCHAR)",
+        &mut ctx,
+    )
+    .unwrap();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE INDEX idx_score ON items (score)",
+        &mut ctx,
+    )
+    .unwrap();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE INDEX idx_code ON items (code)",
+        &mut ctx,
+    )
+    .unwrap();
+    // Inserted out of order; every fifth row has no score
+    for i in 0..25 {
+        let id = i * 7 % 25;
+        let score = if id % 5 == 0 {
+            "NULL".to_string()
+        } else {
+            (id * 10).to_string()
+        };
+        execute_sql_in_session(
+            &mut engine,
+            &format!(
+                "INSERT INTO items (id, score, code) VALUES ({}, {}, '{}')",
+                id, score, id
+            ),
+            &mut ctx,
+        )
+        .unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn ids(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<i64> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data.iter().map(|r| r["id"].as_i64().unwrap()).collect(),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn limit_and_offset() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY id LIMIT 3"
+        ),
+        [0, 1, 2]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY id LIMIT 10 OFFSET 20"
+        ),
+        [20, 21, 22, 23, 24]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY id DESC LIMIT 3 OFFSET 1"
+        ),
+        [23, 22, 21]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY id OFFSET 22"
+        ),
+        [22, 23, 24]
+    );
+    assert!(ids(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM items ORDER BY id LIMIT 5 OFFSET 30"
+    )
+    .is_empty());
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE id >= 10 AND code <> '12' ORDER BY id LIMIT 3 OFFSET 1"
+        ),
+        [11, 13, 14]
+    );
+}
+
+#[test]
+fn keyset_pages_match_offset_pages() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let mut last_seen = -1;
+    let mut pages = 0;
+    loop {
+        let page = ids(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "SELECT id FROM items WHERE id > {} ORDER BY id LIMIT 4",
+                last_seen
+            ),
+        );
+        let by_offset = ids(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "SELECT id FROM items ORDER BY id LIMIT 4 OFFSET {}",
+                pages * 4
+            ),
+        );
+        assert_eq!(page, by_offset);
+        let Some(&last) = page.last() else { break };
+        last_seen = last;
+        pages += 1;
+    }
+    assert_eq!(pages, 7);
+    assert_eq!(last_seen, 24);
+
+    // Signed literals are pushed down like any other
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE id BETWEEN -5 AND 1 ORDER BY id LIMIT 10"
+        ),
+        [0, 1]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE id IN (-1, 3) ORDER BY id LIMIT 10"
+        ),
+        [3]
+    );
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE id < 5 ORDER BY id DESC LIMIT 3"
+        ),
+        [4, 3, 2]
+    );
+}
+
+#[test]
+fn nulls_and_text_keep_sort_order() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    // NULL scores sort after every value ascending and before them
+    // descending, although the index doesn't hold them
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY score LIMIT 4 OFFSET 19"
+        )[..1],
+        [24]
+    );
+    assert!(ids(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM items ORDER BY score LIMIT 25"
+    )[20..]
+        .iter()
+        .all(|id| id % 5 == 0));
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items ORDER BY score DESC NULLS LAST LIMIT 2"
+        ),
+        [24, 23]
+    );
+    assert!(ids(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM items ORDER BY score DESC LIMIT 5"
+    )
+    .iter()
+    .all(|id| id % 5 == 0));
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE score > 200 ORDER BY score LIMIT 10"
+        ),
+        [21, 22, 23, 24]
+    );
+
+    // Text compares as text: '10' before '9'
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM items WHERE code >= '1' ORDER BY code LIMIT 4"
+        ),
+        [1, 10, 11, 12]
+    );
+}
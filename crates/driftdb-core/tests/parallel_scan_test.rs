@@ -0,0 +1,222 @@
+//! Parallel sequential scans: a scan split across workers returns exactly
+//! the rows a sequential replay does, including after patches, deletes,
+//! snapshots and for time-travel reads.
+
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::optimizer::{ParallelScanConfig, PlanStep};
+use driftdb_core::parallel::ScanWorkerPool;
+use driftdb_core::query::{AsOf, WhereCondition};
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, OptimizationConfig, Query, QueryResult};
+
+/// Scans lease workers from one process-wide pool, so tests that count
+/// parallel executions take turns with the others
+static POOL: Mutex<()> = Mutex::new(());
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    execute_sql(engine, sql).unwrap();
+}
+
+fn plan(engine: &mut Engine, sql: &str) -> String {
+    rows(engine, &format!("EXPLAIN {}", sql))
+        .iter()
+        .filter_map(|row| row["QUERY PLAN"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Plan every scan of a few rows across up to four workers
+fn go_parallel(engine: &Engine) {
+    engine
+        .query_optimizer()
+        .set_parallel_scan_config(ParallelScanConfig {
+            max_parallel_workers: 4,
+            min_scan_cost: 0.0,
+            min_rows_per_worker: 1,
+        });
+}
+
+fn go_sequential(engine: &Engine) {
+    engine
+        .query_optimizer()
+        .set_parallel_scan_config(ParallelScanConfig {
+            max_parallel_workers: 0,
+            ..ParallelScanConfig::default()
+        });
+}
+
+fn select(engine: &mut Engine, conditions: Vec<WhereCondition>, as_of: Option<AsOf>) -> Vec<Value> {
+    let query = Query::Select {
+        table: "orders".to_string(),
+        conditions,
+        as_of,
+        limit: None,
+    };
+    match engine.execute_query(query).unwrap() {
+        QueryResult::Rows { mut data } => {
+            data.sort_by_key(|row| row.to_string());
+            data
+        }
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+/// The same query, scanned in parallel and sequentially
+fn both_ways(
+    engine: &mut Engine,
+    conditions: Vec<WhereCondition>,
+    as_of: Option<AsOf>,
+) -> (Vec<Value>, Vec<Value>) {
+    go_parallel(engine);
+    let parallel = select(engine, conditions.clone(), as_of.clone());
+    go_sequential(engine);
+    let sequential = select(engine, conditions, as_of);
+    (parallel, sequential)
+}
+
+fn condition(column: &str, operator: &str, value: Value) -> WhereCondition {
+    WhereCondition {
+        column: column.to_string(),
+        operator: operator.to_string(),
+        value,
+    }
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE orders (id INT, region VARCHAR, amount INT, PRIMARY KEY (id))",
+    );
+    for i in 0..200 {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO orders (id, region, amount) VALUES ({}, '{}', {})",
+                i,
+                ["east", "west", "north"][i % 3],
+                i
+            ),
+        );
+    }
+    run(
+        &mut engine,
+        "UPDATE orders SET amount = 1000 WHERE region = 'north'",
+    );
+    run(&mut engine, "DELETE FROM orders WHERE amount < 20");
+    run(&mut engine, "ANALYZE TABLE orders");
+    engine
+}
+
+#[test]
+fn parallel_scan_returns_the_sequential_rows() {
+    let _turn = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    engine
+        .enable_query_optimization(OptimizationConfig::default())
+        .unwrap();
+
+    go_parallel(&engine);
+    let query = Query::Select {
+        table: "orders".to_string(),
+        conditions: vec![],
+        as_of: None,
+        limit: None,
+    };
+    let chosen = engine.query_optimizer().optimize(&query).unwrap();
+    assert!(
+        chosen
+            .steps
+            .iter()
+            .any(|step| matches!(step, PlanStep::ParallelScan { workers: 4, .. })),
+        "expected a parallel scan: {:?}",
+        chosen
+    );
+
+    for conditions in [
+        vec![],
+        vec![condition("amount", ">", json!(100))],
+        vec![
+            condition("region", "=", json!("north")),
+            condition("amount", "=", json!(1000)),
+        ],
+    ] {
+        let (parallel, sequential) = both_ways(&mut engine, conditions, None);
+        assert!(!parallel.is_empty());
+        assert_eq!(parallel, sequential);
+    }
+
+    let parallel_pool = ScanWorkerPool::global().is_some_and(|pool| pool.size() > 1);
+    let stats = engine
+        .get_query_optimizer()
+        .unwrap()
+        .get_statistics()
+        .unwrap();
+    if parallel_pool {
+        assert_eq!(stats.parallel_executions, 3);
+    } else {
+        assert_eq!(stats.parallel_executions, 0);
+    }
+}
+
+#[test]
+fn parallel_scan_replays_from_snapshots_and_into_the_past() {
+    let _turn = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(&mut engine, "CHECKPOINT TABLE orders");
+    // Sequences are dense from 1, so the event count is the last one
+    let checkpointed = engine.table_storage_info("orders").unwrap().event_count;
+
+    run(
+        &mut engine,
+        "UPDATE orders SET region = 'south' WHERE amount > 150",
+    );
+    run(&mut engine, "DELETE FROM orders WHERE region = 'east'");
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, region, amount) VALUES (500, 'east', 7)",
+    );
+
+    let (parallel, sequential) = both_ways(&mut engine, vec![], None);
+    assert_eq!(parallel, sequential);
+    assert!(parallel.iter().any(|row| row["region"] == "south"));
+
+    for as_of in [checkpointed - 50, checkpointed, checkpointed + 1] {
+        let (parallel, sequential) = both_ways(&mut engine, vec![], Some(AsOf::Sequence(as_of)));
+        assert!(!parallel.is_empty());
+        assert_eq!(parallel, sequential, "as of sequence {}", as_of);
+    }
+}
+
+#[test]
+fn explain_shows_gather_only_when_parallel_scans_are_enabled() {
+    let _turn = POOL.lock().unwrap_or_else(|e| e.into_inner());
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let sql = "SELECT * FROM orders WHERE amount > 100";
+
+    go_parallel(&engine);
+    let text = plan(&mut engine, sql);
+    assert!(text.contains("Gather"), "{}", text);
+    assert!(text.contains("Workers Planned: 4"), "{}", text);
+    assert!(text.contains("Parallel Seq Scan on orders"), "{}", text);
+
+    // Zero workers turns parallel scans off
+    go_sequential(&engine);
+    let text = plan(&mut engine, sql);
+    assert!(!text.contains("Gather"), "{}", text);
+    assert!(text.contains("Seq Scan on orders"), "{}", text);
+}
@@ -0,0 +1,327 @@
+//! `PARTITION BY RANGE` / `PARTITION BY HASH`: rows inserted into the
+//! parent are stored in the partition whose bounds hold their key, UPDATE
+//! moves a row whose key leaves its partition, reads skip partitions the
+//! WHERE clause rules out, and dropping the parent drops its partitions.
+//! Old partitions are dropped, or detached and archived, as a unit.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::storage::{MemoryBackend, TierPolicy, TieredBackend};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected rows, got {:?}", other),
+    }
+}
+
+fn ids(engine: &mut Engine, sql: &str) -> Vec<i64> {
+    let mut ids: Vec<i64> = rows(engine, sql)
+        .iter()
+        .map(|row| row["id"].as_i64().unwrap())
+        .collect();
+    ids.sort();
+    ids
+}
+
+fn plan(engine: &mut Engine, sql: &str) -> String {
+    rows(engine, sql)
+        .iter()
+        .map(|row| row["QUERY PLAN"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path().join("db")).unwrap();
+    create_sales(&mut engine);
+    engine
+}
+
+fn create_sales(engine: &mut Engine) {
+    for sql in [
+        "CREATE TABLE sales (id INTEGER PRIMARY KEY, year INTEGER, amount INTEGER) \
+         PARTITION BY RANGE (year)",
+        "CREATE TABLE sales_old PARTITION OF sales FOR VALUES FROM (MINVALUE) TO (2023)",
+        "CREATE TABLE sales_2023 PARTITION OF sales FOR VALUES FROM (2023) TO (2024)",
+        "CREATE TABLE sales_2024 PARTITION OF sales FOR VALUES FROM (2024) TO (2025)",
+        "INSERT INTO sales (id, year, amount) VALUES (1, 2020, 10)",
+        "INSERT INTO sales (id, year, amount) VALUES (2, 2023, 20)",
+        "INSERT INTO sales (id, year, amount) VALUES (3, 2023, 30)",
+        "INSERT INTO sales (id, year, amount) VALUES (4, 2024, 40)",
+    ] {
+        execute_sql(engine, sql).unwrap();
+    }
+}
+
+#[test]
+fn range_partitions_route_rows_by_key() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales"), vec![1, 2, 3, 4]);
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_old"), vec![1]);
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2023"), vec![2, 3]);
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2024"), vec![4]);
+    assert_eq!(
+        rows(&mut engine, "SELECT COUNT(*) AS n FROM sales")[0]["n"],
+        json!(4)
+    );
+
+    let err = execute_sql(
+        &mut engine,
+        "INSERT INTO sales (id, year, amount) VALUES (5, 2030, 50)",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("no partition of relation \"sales\""),
+        "{}",
+        err
+    );
+
+    // Writing a partition directly still has to respect its bounds
+    let err = execute_sql(
+        &mut engine,
+        "INSERT INTO sales_2024 (id, year, amount) VALUES (6, 2023, 60)",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("violates partition constraint"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn overlapping_partitions_are_rejected() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let err = execute_sql(
+        &mut engine,
+        "CREATE TABLE sales_mid PARTITION OF sales FOR VALUES FROM (2024) TO (2026)",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("overlap"), "{}", err);
+    assert!(!engine.table_exists("sales_mid"));
+}
+
+#[test]
+fn where_clause_prunes_partitions() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM sales WHERE year = 2023"),
+        vec![2, 3]
+    );
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM sales WHERE year >= 2023"),
+        vec![2, 3, 4]
+    );
+
+    let pruned = plan(
+        &mut engine,
+        "EXPLAIN (COSTS OFF) SELECT * FROM sales WHERE year = 2023",
+    );
+    assert!(pruned.contains("Seq Scan on sales_2023"), "{}", pruned);
+    assert!(!pruned.contains("sales_2024"), "{}", pruned);
+    assert!(!pruned.contains("sales_old"), "{}", pruned);
+    assert!(!pruned.contains("Append"), "{}", pruned);
+
+    let two = plan(
+        &mut engine,
+        "EXPLAIN (COSTS OFF) SELECT * FROM sales WHERE year > 2022",
+    );
+    assert!(two.contains("Append"), "{}", two);
+    assert!(two.contains("Seq Scan on sales_2023"), "{}", two);
+    assert!(two.contains("Seq Scan on sales_2024"), "{}", two);
+    assert!(!two.contains("sales_old"), "{}", two);
+
+    let all = plan(&mut engine, "EXPLAIN (COSTS OFF) SELECT * FROM sales");
+    assert!(all.contains("Seq Scan on sales_old"), "{}", all);
+}
+
+#[test]
+fn update_moves_rows_between_partitions() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    execute_sql(&mut engine, "UPDATE sales SET year = 2024 WHERE id = 2").unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2023"), vec![3]);
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2024"), vec![2, 4]);
+
+    execute_sql(&mut engine, "UPDATE sales SET amount = 99 WHERE id = 3").unwrap();
+    assert_eq!(
+        rows(&mut engine, "SELECT amount FROM sales_2023 WHERE id = 3")[0]["amount"],
+        json!(99)
+    );
+
+    assert!(execute_sql(&mut engine, "UPDATE sales SET year = 1999 WHERE id = 4").is_ok());
+    // A key no partition holds fails the update and leaves the row in place
+    assert!(execute_sql(&mut engine, "UPDATE sales SET year = 2031 WHERE id = 4").is_err());
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_old"), vec![1, 4]);
+
+    execute_sql(&mut engine, "DELETE FROM sales WHERE year = 2024").unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales"), vec![1, 3, 4]);
+}
+
+#[test]
+fn hash_partitions_spread_rows() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path().join("db")).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE events (id INTEGER PRIMARY KEY, kind VARCHAR) PARTITION BY HASH (id)",
+    )
+    .unwrap();
+    for remainder in 0..4 {
+        execute_sql(
+            &mut engine,
+            &format!(
+                "CREATE TABLE events_{0} PARTITION OF events \
+                 FOR VALUES WITH (MODULUS 4, REMAINDER {0})",
+                remainder
+            ),
+        )
+        .unwrap();
+    }
+    for id in 1..=40 {
+        execute_sql(
+            &mut engine,
+            &format!("INSERT INTO events (id, kind) VALUES ({}, 'click')", id),
+        )
+        .unwrap();
+    }
+
+    let mut spread = Vec::new();
+    for remainder in 0..4 {
+        spread.extend(ids(
+            &mut engine,
+            &format!("SELECT * FROM events_{}", remainder),
+        ));
+    }
+    spread.sort();
+    assert_eq!(spread, (1..=40).collect::<Vec<_>>());
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM events WHERE id = 17"),
+        vec![17]
+    );
+
+    let pruned = plan(
+        &mut engine,
+        "EXPLAIN (COSTS OFF) SELECT * FROM events WHERE id = 17",
+    );
+    assert!(!pruned.contains("Append"), "{}", pruned);
+}
+
+#[test]
+fn drop_table_drops_partitions_and_scheme_survives_reopen() {
+    let temp = TempDir::new().unwrap();
+    {
+        let _engine = setup(&temp);
+    }
+
+    let mut engine = Engine::open(temp.path().join("db")).unwrap();
+    execute_sql(
+        &mut engine,
+        "INSERT INTO sales (id, year, amount) VALUES (5, 2024, 50)",
+    )
+    .unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2024"), vec![4, 5]);
+
+    execute_sql(&mut engine, "DROP TABLE sales").unwrap();
+    for table in ["sales", "sales_old", "sales_2023", "sales_2024"] {
+        assert!(!engine.table_exists(table), "{} still exists", table);
+    }
+}
+
+#[test]
+fn dropping_a_partition_removes_it_and_its_history() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    execute_sql(&mut engine, "DROP TABLE sales_2023").unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales"), vec![1, 4]);
+    assert!(!temp
+        .path()
+        .join("db")
+        .join("tables")
+        .join("sales_2023")
+        .exists());
+    assert!(execute_sql(
+        &mut engine,
+        "INSERT INTO sales (id, year, amount) VALUES (7, 2023, 70)",
+    )
+    .is_err());
+}
+
+#[test]
+fn detached_partition_keeps_its_rows_and_history() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    execute_sql(&mut engine, "ALTER TABLE sales DETACH PARTITION sales_2023").unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales"), vec![1, 4]);
+    assert_eq!(
+        ids(&mut engine, "SELECT * FROM sales WHERE year = 2023"),
+        Vec::<i64>::new()
+    );
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2023"), vec![2, 3]);
+
+    // Still a table of its own, with its history open to time travel
+    execute_sql(&mut engine, "DELETE FROM sales_2023 WHERE id = 2").unwrap();
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2023"), vec![3]);
+    assert_eq!(
+        ids(
+            &mut engine,
+            "SELECT * FROM sales_2023 FOR SYSTEM_TIME AS OF @SEQ:2"
+        ),
+        vec![2, 3]
+    );
+
+    let err =
+        execute_sql(&mut engine, "ALTER TABLE sales DETACH PARTITION sales_2023").unwrap_err();
+    assert!(err.to_string().contains("is not a partition"), "{}", err);
+
+    // A plain storage backend has no cold tier to archive to
+    let err = execute_sql(
+        &mut engine,
+        "ALTER TABLE sales DETACH PARTITION sales_old ARCHIVE",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no cold tier"), "{}", err);
+}
+
+#[test]
+fn detach_archive_moves_the_partition_to_the_cold_tier() {
+    let temp = TempDir::new().unwrap();
+    let backend = Arc::new(TieredBackend::new(
+        Arc::new(MemoryBackend::new()),
+        Arc::new(MemoryBackend::new()),
+        temp.path(),
+        "/archive",
+        TierPolicy::default(),
+    ));
+    let mut engine = Engine::init_with_backend(temp.path(), backend.clone()).unwrap();
+    create_sales(&mut engine);
+
+    execute_sql(
+        &mut engine,
+        "ALTER TABLE sales DETACH PARTITION sales_2023 ARCHIVE",
+    )
+    .unwrap();
+    let segments = |table: &str| temp.path().join("tables").join(table).join("segments");
+    assert!(backend.is_cold(&segments("sales_2023").join("00000001.seg")));
+    assert!(!backend.is_cold(&segments("sales_2024").join("00000001.seg")));
+
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales"), vec![1, 4]);
+    assert_eq!(ids(&mut engine, "SELECT * FROM sales_2023"), vec![2, 3]);
+    assert!(engine.storage_tier_stats().unwrap().cold_reads >= 1);
+}
@@ -0,0 +1,104 @@
+//! Text matching in WHERE: `[NOT] LIKE`, `[NOT] ILIKE` and the regex
+//! operators `~`, `~*`, `!~`, `!~*`, pushed to the engine or evaluated row
+//! by row, with invalid regexes reported as errors.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn ids(engine: &mut Engine, filter: &str) -> Vec<i64> {
+    let sql = format!("SELECT id FROM people WHERE {} ORDER BY id", filter);
+    match execute_sql(engine, &sql).unwrap() {
+        QueryResult::Rows { data } => data.iter().map(|row| row["id"].as_i64().unwrap()).collect(),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE people (id INTEGER PRIMARY KEY, name VARCHAR)",
+    )
+    .unwrap();
+    for (id, name) in [
+        (1, "'Ada Lovelace'"),
+        (2, "'ada byron'"),
+        (3, "'ADAM Smith'"),
+        (4, "'Grace Hopper'"),
+        (5, "NULL"),
+    ] {
+        execute_sql(
+            &mut engine,
+            &format!("INSERT INTO people (id, name) VALUES ({}, {})", id, name),
+        )
+        .unwrap();
+    }
+    engine
+}
+
+#[test]
+fn ilike_matches_mixed_case() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "name ILIKE 'ada%'"), vec![1, 2, 3]);
+    assert_eq!(ids(&mut engine, "name LIKE 'Ada%'"), vec![1]);
+    assert_eq!(ids(&mut engine, "name ILIKE '%_O_E%'"), vec![1]);
+    // NULL names match neither way
+    assert_eq!(ids(&mut engine, "name NOT ILIKE 'ada%'"), vec![4]);
+    assert_eq!(ids(&mut engine, "name NOT LIKE 'Ada%'"), vec![2, 3, 4]);
+
+    // Evaluated row by row under OR
+    assert_eq!(
+        ids(&mut engine, "name ILIKE '%BYRON' OR id = 4"),
+        vec![2, 4]
+    );
+
+    execute_sql(
+        &mut engine,
+        "UPDATE people SET name = 'anon' WHERE name ILIKE 'adam%'",
+    )
+    .unwrap();
+    assert_eq!(ids(&mut engine, "name = 'anon'"), vec![3]);
+}
+
+#[test]
+fn regex_operators() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "name ~ '^[A-Z]+ '"), vec![3]);
+    assert_eq!(ids(&mut engine, "name ~ 'ada'"), vec![2]);
+    assert_eq!(ids(&mut engine, "name ~* 'ADA'"), vec![1, 2, 3]);
+    assert_eq!(ids(&mut engine, "name !~ '^A'"), vec![2, 4]);
+    assert_eq!(ids(&mut engine, "name !~* '^ada'"), vec![4]);
+    assert_eq!(
+        ids(
+            &mut engine,
+            "id > 1 AND (name ~* 'hopper$' OR name ~ '^ADAM')"
+        ),
+        vec![3, 4]
+    );
+}
+
+#[test]
+fn invalid_regex_is_an_error() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    for filter in ["name ~ '(unclosed'", "name ~* '[a-' OR id = 1"] {
+        let err = execute_sql(
+            &mut engine,
+            &format!("SELECT id FROM people WHERE {}", filter),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("invalid regular expression"),
+            "{}: {}",
+            filter,
+            err
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Plan invalidation: cached plans are re-planned as soon as the catalog
+//! changes, without restarting, so a new index is used by the very next
+//! query and a dropped one stops being used.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    execute_sql(engine, sql).unwrap();
+}
+
+fn plan(engine: &mut Engine, sql: &str) -> String {
+    rows(engine, &format!("EXPLAIN {}", sql))
+        .iter()
+        .filter_map(|row| row["QUERY PLAN"].as_str())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE users (id INT, email VARCHAR, PRIMARY KEY (id))",
+    );
+    for i in 0..20 {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO users (id, email) VALUES ({}, 'u{}@example.com')",
+                i, i
+            ),
+        );
+    }
+    engine
+}
+
+const LOOKUP: &str = "SELECT id FROM users WHERE email = 'u7@example.com'";
+
+#[test]
+fn create_index_switches_the_next_query_to_an_index_scan() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // Planned, and cached, as a sequential scan
+    assert!(plan(&mut engine, LOOKUP).contains("Seq Scan on users"));
+    assert!(plan(&mut engine, LOOKUP).contains("Seq Scan on users"));
+
+    let before = engine.catalog_version();
+    run(&mut engine, "CREATE INDEX idx_email ON users (email)");
+    assert!(engine.catalog_version() > before);
+
+    let text = plan(&mut engine, LOOKUP);
+    assert!(text.contains("Index Scan using email on users"), "{}", text);
+    assert_eq!(rows(&mut engine, LOOKUP), vec![json!({ "id": 7 })]);
+}
+
+#[test]
+fn recreated_table_is_not_planned_with_the_old_index() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(&mut engine, "CREATE INDEX idx_email ON users (email)");
+    assert!(plan(&mut engine, LOOKUP).contains("Index Scan"));
+
+    run(&mut engine, "DROP TABLE users");
+    run(
+        &mut engine,
+        "CREATE TABLE users (id INT, email VARCHAR, PRIMARY KEY (id))",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO users (id, email) VALUES (7, 'u7@example.com')",
+    );
+
+    let text = plan(&mut engine, LOOKUP);
+    assert!(text.contains("Seq Scan on users"), "{}", text);
+    assert_eq!(rows(&mut engine, LOOKUP), vec![json!({ "id": 7 })]);
+}
+
+#[test]
+fn ddl_and_analyze_bump_the_catalog_version() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    for sql in [
+        "ALTER TABLE users ADD COLUMN name VARCHAR",
+        "ANALYZE TABLE users",
+        "ALTER TABLE users DROP COLUMN name",
+        "CREATE SCHEMA app",
+    ] {
+        let before = engine.catalog_version();
+        run(&mut engine, sql);
+        assert!(
+            engine.catalog_version() > before,
+            "{} kept the version",
+            sql
+        );
+    }
+
+    // Reads and writes leave it alone
+    let before = engine.catalog_version();
+    run(&mut engine, "UPDATE users SET email = 'x' WHERE id = 1");
+    rows(&mut engine, "SELECT * FROM users");
+    assert_eq!(engine.catalog_version(), before);
+}
@@ -0,0 +1,96 @@
+//! Read-only (hot-standby) mode: reads and time travel keep working, every
+//! write fails with `DriftError::ReadOnly`, inside a transaction or not.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{DriftError, Engine, QueryResult};
+
+fn setup() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE items (id VARCHAR, qty INT, PRIMARY KEY (id))",
+        "INSERT INTO items (id, qty) VALUES ('a', 1)",
+        "UPDATE items SET qty = 2 WHERE id = 'a'",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    temp
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn reads_succeed_and_writes_fail() {
+    let temp = setup();
+    let mut engine = Engine::open_read_only(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SELECT * FROM items")[0]["qty"],
+        2
+    );
+    let before = rows(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM items FOR SYSTEM_TIME AS OF @SEQ:1",
+    );
+    assert_eq!(before[0]["qty"], 1);
+
+    for sql in [
+        "INSERT INTO items (id, qty) VALUES ('b', 1)",
+        "UPDATE items SET qty = 3",
+        "DELETE FROM items",
+        "CREATE TABLE other (id VARCHAR, PRIMARY KEY (id))",
+        "ALTER TABLE items ADD COLUMN note VARCHAR",
+        "DROP TABLE items",
+    ] {
+        let err = execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap_err();
+        assert!(matches!(err, DriftError::ReadOnly(_)), "{}: {}", sql, err);
+    }
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM items").len(), 1);
+}
+
+#[test]
+fn read_only_transaction_commits_and_a_write_aborts_it() {
+    let temp = setup();
+    let mut engine = Engine::open_read_only(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    execute_sql_in_session(&mut engine, "BEGIN", &mut ctx).unwrap();
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM items").len(), 1);
+    execute_sql_in_session(&mut engine, "COMMIT", &mut ctx).unwrap();
+
+    execute_sql_in_session(&mut engine, "BEGIN", &mut ctx).unwrap();
+    let err = execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO items (id, qty) VALUES ('b', 1)",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(matches!(err, DriftError::ReadOnly(_)), "{}", err);
+    let err = execute_sql_in_session(&mut engine, "SELECT * FROM items", &mut ctx).unwrap_err();
+    assert!(err.to_string().contains("aborted"), "{}", err);
+    execute_sql_in_session(&mut engine, "ROLLBACK", &mut ctx).unwrap();
+}
+
+#[test]
+fn read_only_can_be_toggled_at_runtime() {
+    let temp = setup();
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    engine.set_read_only(true);
+    assert!(execute_sql_in_session(&mut engine, "DELETE FROM items", &mut ctx).is_err());
+
+    engine.set_read_only(false);
+    execute_sql_in_session(&mut engine, "DELETE FROM items", &mut ctx).unwrap();
+    assert!(rows(&mut engine, &mut ctx, "SELECT * FROM items").is_empty());
+}
@@ -0,0 +1,146 @@
+//! `read_your_writes`: a session's statements see every write it made
+//! before, and a node that hasn't applied those writes yet refuses to
+//! answer rather than serve a stale read.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup(temp: &TempDir) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE t (id VARCHAR, name VARCHAR, PRIMARY KEY (id))",
+        "SET read_your_writes = on",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (engine, ctx)
+}
+
+fn names(engine: &mut Engine, ctx: &mut SessionContext) -> Vec<String> {
+    match execute_sql_in_session(engine, "SELECT name FROM t ORDER BY id", ctx).unwrap() {
+        QueryResult::Rows { data } => data
+            .iter()
+            .map(|row| row["name"].as_str().unwrap().to_string())
+            .collect(),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn write_then_read_observes_the_write() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    for i in 0..20 {
+        execute_sql_in_session(
+            &mut engine,
+            &format!("INSERT INTO t (id, name) VALUES ('{:02}', 'v{}')", i, i),
+            &mut ctx,
+        )
+        .unwrap();
+        assert_eq!(names(&mut engine, &mut ctx).len(), i + 1);
+
+        execute_sql_in_session(
+            &mut engine,
+            &format!("UPDATE t SET name = 'u{}' WHERE id = '{:02}'", i, i),
+            &mut ctx,
+        )
+        .unwrap();
+        assert_eq!(names(&mut engine, &mut ctx)[i], format!("u{}", i));
+    }
+    assert_eq!(
+        ctx.write_positions.get("t").copied(),
+        engine.table_sequence("t")
+    );
+    assert_eq!(ctx.unapplied_write(&engine), None);
+}
+
+#[test]
+fn committed_transaction_is_visible_to_the_next_statement() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    execute_sql_in_session(&mut engine, "BEGIN", &mut ctx).unwrap();
+    execute_sql_in_session(
+        &mut engine,
+        "INSERT INTO t (id, name) VALUES ('a', 'first'), ('b', 'second')",
+        &mut ctx,
+    )
+    .unwrap();
+    // Buffered writes haven't reached the table yet
+    assert!(!ctx.write_positions.contains_key("t"));
+
+    execute_sql_in_session(&mut engine, "COMMIT", &mut ctx).unwrap();
+    assert_eq!(
+        ctx.write_positions.get("t").copied(),
+        engine.table_sequence("t")
+    );
+    assert_eq!(names(&mut engine, &mut ctx), vec!["first", "second"]);
+}
+
+#[test]
+fn lagging_node_refuses_the_read() {
+    let primary_dir = TempDir::new().unwrap();
+    let replica_dir = TempDir::new().unwrap();
+    let (mut primary, mut ctx) = setup(&primary_dir);
+    let (mut replica, mut replication) = setup(&replica_dir);
+
+    execute_sql_in_session(
+        &mut primary,
+        "INSERT INTO t (id, name) VALUES ('a', 'n')",
+        &mut ctx,
+    )
+    .unwrap();
+
+    // The replica hasn't replayed the insert
+    let (table, written, applied) = ctx.unapplied_write(&replica).unwrap();
+    assert_eq!(table, "t");
+    assert!(applied < written);
+    let err = execute_sql_in_session(&mut replica, "SELECT * FROM t", &mut ctx)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("read-your-writes"), "{}", err);
+
+    // Replaying it, here through a session of its own, lets the read in
+    execute_sql_in_session(
+        &mut replica,
+        "INSERT INTO t (id, name) VALUES ('a', 'n')",
+        &mut replication,
+    )
+    .unwrap();
+    assert_eq!(ctx.unapplied_write(&replica), None);
+    assert_eq!(names(&mut replica, &mut ctx), vec!["n"]);
+
+    // Turning the setting off forgets the session's writes
+    execute_sql_in_session(&mut replica, "SET read_your_writes = off", &mut ctx).unwrap();
+    assert!(ctx.write_positions.is_empty());
+}
+
+#[test]
+fn settings_are_checked() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    assert!(ctx.read_your_writes(&engine));
+    assert_eq!(
+        ctx.read_your_writes_timeout(&engine),
+        std::time::Duration::from_secs(5)
+    );
+    execute_sql_in_session(
+        &mut engine,
+        "SET read_your_writes_timeout = '250ms'",
+        &mut ctx,
+    )
+    .unwrap();
+    assert_eq!(
+        ctx.read_your_writes_timeout(&engine),
+        std::time::Duration::from_millis(250)
+    );
+    assert!(execute_sql_in_session(&mut engine, "SET read_your_writes = maybe", &mut ctx).is_err());
+
+    execute_sql_in_session(&mut engine, "RESET read_your_writes", &mut ctx).unwrap();
+    assert!(!ctx.read_your_writes(&engine));
+}
@@ -0,0 +1,115 @@
+//! `RETURNING` on INSERT, UPDATE and DELETE: the affected rows come back
+//! as a normal row set — post-images for writes, the removed rows for
+//! DELETE — projected like a SELECT list.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE TABLE items (id VARCHAR, qty INT, PRIMARY KEY (id))",
+        &mut ctx,
+    )
+    .unwrap();
+    (temp, engine, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn insert_returning_star_and_columns() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let all = rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('a', 1), ('b', 2) RETURNING *",
+    );
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[1]["id"], "b");
+    assert_eq!(all[1]["qty"], 2);
+
+    let ids = rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('c', 3) RETURNING id",
+    );
+    assert_eq!(ids, vec![serde_json::json!({"id": "c"})]);
+}
+
+#[test]
+fn update_returns_post_image_and_delete_returns_removed_rows() {
+    let (_t, mut engine, mut ctx) = setup();
+    rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('a', 1), ('b', 2) RETURNING id",
+    );
+
+    let updated = rows(
+        &mut engine,
+        &mut ctx,
+        "UPDATE items SET qty = qty + 10 WHERE id = 'a' RETURNING id, qty",
+    );
+    assert_eq!(updated, vec![serde_json::json!({"id": "a", "qty": 11})]);
+
+    let deleted = rows(
+        &mut engine,
+        &mut ctx,
+        "DELETE FROM items WHERE id = 'b' RETURNING *",
+    );
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0]["qty"], 2);
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM items").len(), 1);
+}
+
+#[test]
+fn returning_inside_a_transaction() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    execute_sql_in_session(&mut engine, "BEGIN", &mut ctx).unwrap();
+    let inserted = rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('a', 5) RETURNING qty",
+    );
+    assert_eq!(inserted[0]["qty"], 5);
+    execute_sql_in_session(&mut engine, "ROLLBACK", &mut ctx).unwrap();
+
+    assert!(rows(&mut engine, &mut ctx, "SELECT * FROM items").is_empty());
+}
+
+#[test]
+fn upsert_returning_reports_inserted_and_updated_rows() {
+    let (_t, mut engine, mut ctx) = setup();
+    rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('a', 1) RETURNING id",
+    );
+
+    let result = rows(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO items (id, qty) VALUES ('a', 4), ('b', 2)
+         ON CONFLICT (id) DO UPDATE SET qty = EXCLUDED.qty RETURNING id, qty",
+    );
+    assert_eq!(
+        result,
+        vec![
+            serde_json::json!({"id": "a", "qty": 4}),
+            serde_json::json!({"id": "b", "qty": 2}),
+        ]
+    );
+}
@@ -0,0 +1,67 @@
+//! `SessionContext::rows_scanned`: the rows each statement read from each
+//! table before its WHERE clause filtered them, replaced by every
+//! statement.
+
+use std::collections::BTreeMap;
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::Engine;
+
+fn scanned(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> BTreeMap<String, u64> {
+    execute_sql_in_session(engine, sql, ctx).unwrap();
+    ctx.rows_scanned.clone()
+}
+
+fn setup(temp: &TempDir) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR)",
+        "CREATE TABLE orders (id INT PRIMARY KEY, user_id INT, total INT)",
+        "INSERT INTO users (id, name) VALUES (1, 'ada')",
+        "INSERT INTO users (id, name) VALUES (2, 'bob')",
+        "INSERT INTO orders (id, user_id, total) VALUES (10, 1, 100)",
+        "INSERT INTO orders (id, user_id, total) VALUES (11, 1, 50)",
+        "INSERT INTO orders (id, user_id, total) VALUES (12, 2, 75)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (engine, ctx)
+}
+
+#[test]
+fn counts_rows_read_per_table() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    // A filter on a column without an index still reads every row
+    let rows = scanned(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders WHERE total > 60",
+    );
+    assert_eq!(rows, BTreeMap::from([("orders".to_string(), 3)]));
+
+    let rows = scanned(
+        &mut engine,
+        &mut ctx,
+        "SELECT u.name, o.total FROM users u JOIN orders o ON o.user_id = u.id",
+    );
+    assert_eq!(rows.get("users"), Some(&2));
+    assert_eq!(rows.get("orders"), Some(&3));
+}
+
+#[test]
+fn each_statement_replaces_the_counts() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    scanned(&mut engine, &mut ctx, "SELECT * FROM orders");
+    let rows = scanned(&mut engine, &mut ctx, "SELECT * FROM users");
+    assert_eq!(rows, BTreeMap::from([("users".to_string(), 2)]));
+
+    // A statement that reads no table leaves none
+    assert!(scanned(&mut engine, &mut ctx, "SELECT 1 + 1 AS two").is_empty());
+}
@@ -0,0 +1,310 @@
+//! String, date and cast functions in the select list and in WHERE:
+//! results, NULL propagation, type errors, and WHERE clauses whose
+//! function calls on literals are evaluated once.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn error(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> String {
+    execute_sql_in_session(engine, sql, ctx)
+        .unwrap_err()
+        .to_string()
+}
+
+fn setup(temp: &TempDir) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE users (id INT PRIMARY KEY, name VARCHAR, nickname VARCHAR, \
+         age INT, created_at TIMESTAMP)",
+    );
+    for (id, name, nickname, created_at) in [
+        (1, "'  Ada Lovelace '", "'ada'", "'2025-01-01 09:30:00'"),
+        (2, "'Grace Hopper'", "NULL", "'2025-01-01 23:59:59.5'"),
+        (3, "'Alan Turing'", "'alan'", "'2025-01-02 00:00:00'"),
+        (4, "'Edsger Dijkstra'", "NULL", "NULL"),
+    ] {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "INSERT INTO users (id, name, nickname, age, created_at) \
+                 VALUES ({}, {}, {}, {}, {})",
+                id,
+                name,
+                nickname,
+                30 + id,
+                created_at
+            ),
+        );
+    }
+    (engine, ctx)
+}
+
+fn ids(mut rows: Vec<Value>) -> Vec<i64> {
+    rows.sort_by_key(|row| row["id"].as_i64());
+    rows.iter().map(|row| row["id"].as_i64().unwrap()).collect()
+}
+
+#[test]
+fn string_functions() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT UPPER(TRIM(name)) AS shout, LENGTH(name), \
+             SUBSTRING(TRIM(name) FROM 1 FOR 3) AS short, \
+             CONCAT(nickname, '#', id) AS tag \
+             FROM users WHERE id = 1"
+        ),
+        vec![json!({
+            "shout": "ADA LOVELACE",
+            "length": 15,
+            "short": "Ada",
+            "tag": "ada#1"
+        })]
+    );
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE LOWER(name) LIKE '%turing%' OR TRIM(name) = 'Ada Lovelace'"
+        )),
+        vec![1, 3]
+    );
+
+    // NULL in, NULL out; CONCAT skips NULLs
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT LOWER(nickname) AS l, LENGTH(nickname) AS n, \
+             TRIM(nickname) AS t, CONCAT('x', nickname, 'y') AS c \
+             FROM users WHERE id = 2"
+        ),
+        vec![json!({"l": null, "n": null, "t": null, "c": "xy"})]
+    );
+
+    let err = error(&mut engine, &mut ctx, "SELECT LOWER(age) FROM users");
+    assert!(
+        err.contains("function lower(integer) does not exist"),
+        "{}",
+        err
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM users WHERE SUBSTRING(name FROM 'a') = 'x'",
+    );
+    assert!(err.contains("function substring(text, text)"), "{}", err);
+}
+
+#[test]
+fn null_handling_functions() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let mut rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, COALESCE(nickname, name) FROM users",
+    );
+    rows.sort_by_key(|row| row["id"].as_i64());
+    let names: Vec<&Value> = rows.iter().map(|row| &row["coalesce"]).collect();
+    assert_eq!(
+        names,
+        [
+            &json!("ada"),
+            &json!("Grace Hopper"),
+            &json!("alan"),
+            &json!("Edsger Dijkstra")
+        ]
+    );
+
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT NULLIF(nickname, 'ada') AS a, NULLIF(age, 0) AS b, \
+             COALESCE(nickname, created_at) AS c FROM users WHERE id = 4"
+        ),
+        vec![json!({"a": null, "b": 34, "c": null})]
+    );
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE NULLIF(nickname, 'ada') IS NULL"
+        )),
+        vec![1, 2, 4]
+    );
+
+    let err = error(&mut engine, &mut ctx, "SELECT NULLIF(name) FROM users");
+    assert!(err.contains("function nullif(text)"), "{}", err);
+}
+
+#[test]
+fn date_functions() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE DATE_TRUNC('day', created_at) = '2025-01-01'"
+        )),
+        vec![1, 2]
+    );
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE EXTRACT(HOUR FROM created_at) >= 9"
+        )),
+        vec![1, 2]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT DATE_TRUNC('hour', created_at) AS h, EXTRACT(DOY FROM created_at) AS d, \
+             EXTRACT(SECOND FROM created_at) AS s FROM users WHERE id = 2"
+        ),
+        vec![json!({"h": "2025-01-01 23:00:00", "d": 1, "s": 59.5})]
+    );
+    // Nothing was created in the future; NOW() is read once
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE created_at < NOW()"
+        )),
+        vec![1, 2, 3]
+    );
+
+    // NULL in, NULL out
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT DATE_TRUNC('month', created_at) AS m, \
+             EXTRACT(YEAR FROM created_at) AS y FROM users WHERE id = 4"
+        ),
+        vec![json!({"m": null, "y": null})]
+    );
+
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT DATE_TRUNC('fortnight', created_at) FROM users",
+    );
+    assert!(err.contains("unit \"fortnight\" not recognized"), "{}", err);
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT EXTRACT(YEAR FROM age) FROM users",
+    );
+    assert!(
+        err.contains("cannot cast type integer to timestamp"),
+        "{}",
+        err
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT DATE_TRUNC('day', name) FROM users",
+    );
+    assert!(
+        err.contains("invalid input syntax for type timestamp"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn casts_and_constant_folding() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT CAST(age AS VARCHAR), CAST(created_at AS DATE) AS day, \
+             age::text AS t FROM users WHERE id = 1"
+        ),
+        vec![json!({"age": "31", "day": "2025-01-01", "t": "31"})]
+    );
+    // Literal-only calls fold before the WHERE clause is planned
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE age > CAST('32' AS INT) AND age < 30 + LENGTH('abcd')"
+        )),
+        vec![3]
+    );
+    assert_eq!(
+        ids(run(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM users WHERE nickname = LOWER('ALAN')"
+        )),
+        vec![3]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT CAST(nickname AS INT) AS n FROM users WHERE id = 2"
+        ),
+        vec![json!({"n": null})]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT UPPER('abc'), CAST('7' AS INT) AS n"
+        ),
+        vec![json!({"upper": "ABC", "n": 7})]
+    );
+
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM users WHERE age = CAST('old' AS INT)",
+    );
+    assert!(
+        err.contains("invalid input syntax for type integer: \"old\""),
+        "{}",
+        err
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT CAST(name AS BOOLEAN) FROM users",
+    );
+    assert!(
+        err.contains("invalid input syntax for type boolean"),
+        "{}",
+        err
+    );
+    let err = error(&mut engine, &mut ctx, "SELECT CAST(age AS DATE) FROM users");
+    assert!(err.contains("cannot cast type integer to date"), "{}", err);
+}
@@ -0,0 +1,62 @@
+//! Catalog introspection: a table's schema and its indexes, including
+//! ones created after the table.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, IndexInfo};
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+#[test]
+fn schema_reports_columns_and_defaults() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, visits INTEGER DEFAULT 0)",
+    );
+
+    let schema = engine.table_schema("users").unwrap();
+    assert_eq!(schema.primary_key, "id");
+    let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["id", "email", "visits"]);
+    assert_eq!(schema.defaults.get("visits").map(String::as_str), Some("0"));
+
+    assert!(engine.table_schema("missing").is_err());
+}
+
+#[test]
+fn indexes_include_primary_key_and_later_indexes() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE docs (id INTEGER PRIMARY KEY, title TEXT, body JSONB)",
+    );
+    // Index names aren't kept; secondary indexes read back as idx_<table>_<column>
+    run(&mut engine, "CREATE INDEX ON docs (title)");
+    run(
+        &mut engine,
+        "CREATE INDEX body_gin ON docs USING GIN (body)",
+    );
+
+    let indexes = engine.list_indexes("docs").unwrap();
+    let summary: Vec<(&str, &str, &str, bool)> = indexes
+        .iter()
+        .map(|i: &IndexInfo| (i.name.as_str(), i.column.as_str(), i.method, i.unique))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("docs_pkey", "id", "btree", true),
+            ("idx_docs_title", "title", "btree", false),
+            ("idx_docs_body", "body", "gin", false),
+        ]
+    );
+    assert!(indexes[0].primary);
+    assert!(engine.list_indexes("missing").is_err());
+}
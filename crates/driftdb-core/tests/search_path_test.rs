@@ -0,0 +1,158 @@
+//! Schemas and the session search path: qualified names, resolution of
+//! unqualified names in search-path order, and ambiguity errors.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) {
+    execute_sql_in_session(engine, sql, ctx).unwrap();
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE SCHEMA app",
+        "CREATE SCHEMA audit",
+        "CREATE TABLE app.users (id INT, name VARCHAR, PRIMARY KEY (id))",
+        "CREATE TABLE audit.events (id INT, kind VARCHAR, PRIMARY KEY (id))",
+        "INSERT INTO app.users (id, name) VALUES (1, 'app user')",
+        "INSERT INTO audit.events (id, kind) VALUES (1, 'login')",
+    ] {
+        run(&mut engine, &mut ctx, sql);
+    }
+    engine
+}
+
+#[test]
+fn qualified_names_and_search_path() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+
+    // The default path is `public`, which doesn't have `users`
+    assert!(execute_sql_in_session(&mut engine, "SELECT * FROM users", &mut ctx).is_err());
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SELECT * FROM app.users").len(),
+        1
+    );
+
+    run(&mut engine, &mut ctx, "SET search_path TO app, audit");
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SHOW search_path"),
+        vec![json!({ "search_path": "app, audit" })]
+    );
+    let users = rows(&mut engine, &mut ctx, "SELECT name FROM users");
+    assert_eq!(users, vec![json!({ "name": "app user" })]);
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM events").len(), 1);
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE users SET name = 'renamed' WHERE id = 1",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO users (id, name) VALUES (2, 'b')",
+    );
+    run(&mut engine, &mut ctx, "DELETE FROM users WHERE id = 2");
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SELECT name FROM app.users"),
+        vec![json!({ "name": "renamed" })]
+    );
+
+    // A fresh session starts with the default path again
+    let mut other = SessionContext::new();
+    assert!(execute_sql_in_session(&mut engine, "SELECT * FROM users", &mut other).is_err());
+}
+
+#[test]
+fn unqualified_tables_are_created_in_the_first_existing_schema() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET search_path TO missing, audit, app",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE sessions (id INT, PRIMARY KEY (id))",
+    );
+    assert!(engine.table_exists("audit.sessions"));
+    assert!(!engine.table_exists("sessions"));
+
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "CREATE TABLE nowhere.t (id INT, PRIMARY KEY (id))",
+        &mut ctx,
+    )
+    .is_err());
+}
+
+#[test]
+fn names_in_several_schemas_on_the_path_are_ambiguous() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE audit.users (id INT, PRIMARY KEY (id))",
+    );
+
+    run(&mut engine, &mut ctx, "SET search_path TO app, audit");
+    let err = execute_sql_in_session(&mut engine, "SELECT * FROM users", &mut ctx).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("\"users\" is ambiguous: it exists in schemas app, audit"),
+        "{}",
+        err
+    );
+
+    // Qualifying the name, or leaving one schema off the path, resolves it
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SELECT * FROM app.users").len(),
+        1
+    );
+    run(&mut engine, &mut ctx, "SET search_path = audit");
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM users").len(), 0);
+}
+
+#[test]
+fn schemas_survive_reopen_and_drop_with_cascade() {
+    let temp = TempDir::new().unwrap();
+    let engine = setup(&temp);
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    assert_eq!(engine.list_schemas(), vec!["public", "app", "audit"]);
+    assert_eq!(
+        rows(&mut engine, &mut ctx, "SELECT * FROM app.users").len(),
+        1
+    );
+
+    assert!(execute_sql_in_session(&mut engine, "CREATE SCHEMA app", &mut ctx).is_err());
+    run(&mut engine, &mut ctx, "CREATE SCHEMA IF NOT EXISTS app");
+
+    assert!(execute_sql_in_session(&mut engine, "DROP SCHEMA app", &mut ctx).is_err());
+    run(&mut engine, &mut ctx, "DROP SCHEMA app CASCADE");
+    assert!(!engine.schema_exists("app"));
+    assert!(!engine.table_exists("app.users"));
+    run(&mut engine, &mut ctx, "DROP SCHEMA IF EXISTS app");
+}
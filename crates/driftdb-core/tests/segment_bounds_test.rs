@@ -0,0 +1,97 @@
+//! Appends keep what reads skip by sound: each segment's recorded
+//! sequence bounds take in events copied in with sequences below the
+//! segment's, and analyzed column ranges take in values written since
+//! `ANALYZE`.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::schema::{ColumnDef, Schema};
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::storage::TableStorage;
+use driftdb_core::{Engine, Event, QueryResult};
+
+#[test]
+fn events_below_a_segments_bounds_are_still_found() {
+    let temp = TempDir::new().unwrap();
+    let schema = Schema::new(
+        "items".to_string(),
+        "id".to_string(),
+        vec![ColumnDef {
+            name: "id".to_string(),
+            col_type: "INT".to_string(),
+            index: false,
+        }],
+    );
+    let storage = TableStorage::create(temp.path(), schema, None).unwrap();
+    // Key filters pick segments by their bounds
+    storage.set_key_filter(Some(0.01)).unwrap();
+
+    let copied = |id: u64, sequence: u64| {
+        let mut event = Event::new_insert("items".to_string(), json!(id), json!({"id": id}));
+        event.sequence = sequence;
+        event
+    };
+    for id in 10..=12 {
+        storage.append_existing_event(copied(id, id)).unwrap();
+    }
+    storage.append_existing_event(copied(3, 3)).unwrap();
+
+    let bounds = &storage.segment_bounds()[&1];
+    assert_eq!((bounds.min_sequence, bounds.max_sequence), (3, 12));
+    assert_eq!(
+        storage.rows_with_key_at(&json!(3), Some(5)).unwrap(),
+        Some(vec![json!({"id": 3})])
+    );
+    assert_eq!(
+        storage.rows_with_key_at(&json!(10), Some(5)).unwrap(),
+        Some(vec![])
+    );
+}
+
+#[test]
+fn analyzed_ranges_widen_to_values_appended_since() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE readings (id INT PRIMARY KEY, value INT)",
+    )
+    .unwrap();
+    for i in 1..=10 {
+        execute_sql(
+            &mut engine,
+            &format!(
+                "INSERT INTO readings (id, value) VALUES ({}, {})",
+                i,
+                i * 10
+            ),
+        )
+        .unwrap();
+    }
+    execute_sql(&mut engine, "ANALYZE TABLE readings").unwrap();
+
+    execute_sql(
+        &mut engine,
+        "INSERT INTO readings (id, value) VALUES (11, 5000)",
+    )
+    .unwrap();
+    execute_sql(
+        &mut engine,
+        "INSERT INTO readings (id, value) VALUES (12, -5)",
+    )
+    .unwrap();
+
+    let stats = engine
+        .query_optimizer()
+        .table_statistics("readings")
+        .unwrap();
+    let value = &stats.column_stats["value"];
+    assert_eq!(value.min_value, Some(json!(-5)));
+    assert_eq!(value.max_value, Some(json!(5000)));
+
+    match execute_sql(&mut engine, "SELECT id FROM readings WHERE value > 100").unwrap() {
+        QueryResult::Rows { data } => assert_eq!(data, vec![json!({"id": 11})]),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
@@ -0,0 +1,128 @@
+//! `max_session_memory`: a statement whose sorts, hash tables and results
+//! would take its session over the limit fails with an out-of-memory
+//! error, leaving the session and every other one usable.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+/// 100 customers and 200 orders split between two regions, so joining
+/// them on the region makes 10,000 wide rows
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE customers (id INT PRIMARY KEY, region INT, name VARCHAR)",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE orders (id INT PRIMARY KEY, region INT, note VARCHAR)",
+    );
+    let note = "x".repeat(100);
+    for i in 0..100 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "INSERT INTO customers (id, region, name) VALUES ({}, {}, 'customer {}')",
+                i,
+                i % 2,
+                i
+            ),
+        );
+    }
+    for i in 0..200 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "INSERT INTO orders (id, region, note) VALUES ({}, {}, '{}')",
+                i,
+                i % 2,
+                note
+            ),
+        );
+    }
+    engine
+}
+
+const JOIN: &str =
+    "SELECT o.id, c.id AS customer FROM orders o JOIN customers c ON o.region = c.region";
+
+#[test]
+fn a_statement_over_the_limit_fails_alone() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut limited = SessionContext::new();
+    let mut other = SessionContext::new();
+
+    run(&mut engine, &mut limited, "SET max_session_memory = '1MB'");
+    let err = execute_sql_in_session(&mut engine, JOIN, &mut limited)
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("out of memory"), "{}", err);
+    assert!(err.contains("max_session_memory = 1MB"), "{}", err);
+
+    // What the failed statement held is released, and the session goes on
+    assert_eq!(limited.memory.current(), 0);
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut limited,
+            "SELECT COUNT(*) AS n FROM orders"
+        ),
+        vec![json!({"n": 200})]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut limited,
+            "SELECT id FROM orders ORDER BY id DESC LIMIT 1"
+        ),
+        vec![json!({"id": 199})]
+    );
+    assert!(limited.memory.peak() > 0);
+    assert!(limited.memory.peak() <= 1024 * 1024);
+
+    // Other sessions aren't limited by it
+    assert_eq!(run(&mut engine, &mut other, JOIN).len(), 10_000);
+    assert_eq!(other.memory.current(), 0);
+    assert!(other.memory.peak() > 1024 * 1024);
+}
+
+#[test]
+fn the_limit_can_be_set_for_everyone() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut ctx = SessionContext::new();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET GLOBAL max_session_memory = '512kB'",
+    );
+    let mut session = SessionContext::new();
+    assert!(execute_sql_in_session(&mut engine, JOIN, &mut session).is_err());
+    assert!(execute_sql_in_session(
+        &mut engine,
+        "SELECT o.id, c.id AS customer FROM orders o CROSS JOIN customers c",
+        &mut session
+    )
+    .is_err());
+
+    // A session can lift it for itself
+    run(&mut engine, &mut session, "SET max_session_memory = 0");
+    assert_eq!(run(&mut engine, &mut session, JOIN).len(), 10_000);
+}
@@ -0,0 +1,235 @@
+//! `UNION [ALL]`, `INTERSECT [ALL]` and `EXCEPT [ALL]`: whole rows are
+//! compared, rows take the left side's column names, ORDER BY and LIMIT
+//! apply to the combined rows, and sides of different widths or types are
+//! an error.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn error(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> String {
+    execute_sql_in_session(engine, sql, ctx)
+        .unwrap_err()
+        .to_string()
+}
+
+/// Current orders and an archive sharing order 3 and customer `ada`
+fn setup(temp: &TempDir) -> (Engine, SessionContext) {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for (table, rows) in [
+        ("orders", [(1, "ada", 100), (2, "bob", 200), (3, "cy", 300)]),
+        (
+            "orders_archive",
+            [(3, "cy", 300), (4, "ada", 50), (5, "dee", 75)],
+        ),
+    ] {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "CREATE TABLE {} (id INT PRIMARY KEY, customer VARCHAR, amount INT)",
+                table
+            ),
+        );
+        for (id, customer, amount) in rows {
+            run(
+                &mut engine,
+                &mut ctx,
+                &format!(
+                    "INSERT INTO {} (id, customer, amount) VALUES ({}, '{}', {})",
+                    table, id, customer, amount
+                ),
+            );
+        }
+    }
+    (engine, ctx)
+}
+
+fn column(rows: &[Value], name: &str) -> Vec<Value> {
+    rows.iter().map(|row| row[name].clone()).collect()
+}
+
+/// Customer names, sorted
+fn customers(rows: Vec<Value>) -> Vec<String> {
+    let mut names: Vec<String> = rows
+        .iter()
+        .map(|row| row["customer"].as_str().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn union_all_stitches_tables_together() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM orders UNION ALL SELECT * FROM orders_archive ORDER BY id",
+    );
+    assert_eq!(
+        column(&rows, "id"),
+        [json!(1), json!(2), json!(3), json!(3), json!(4), json!(5)]
+    );
+    assert_eq!(rows[4], json!({"id": 4, "customer": "ada", "amount": 50}));
+
+    // ORDER BY, OFFSET and LIMIT apply to the combined rows
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders UNION ALL SELECT id FROM orders_archive \
+         ORDER BY id DESC LIMIT 3 OFFSET 1",
+    );
+    assert_eq!(
+        rows,
+        vec![json!({"id": 4}), json!({"id": 3}), json!({"id": 3})]
+    );
+
+    // The right side's rows take the left side's column names
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT customer AS who, amount FROM orders WHERE id = 1 \
+         UNION ALL SELECT customer AS name, amount FROM orders_archive WHERE id = 5",
+    );
+    assert_eq!(
+        rows,
+        vec![
+            json!({"who": "ada", "amount": 100}),
+            json!({"who": "dee", "amount": 75})
+        ]
+    );
+}
+
+#[test]
+fn distinct_set_operations_compare_whole_rows() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT customer FROM orders UNION SELECT customer FROM orders_archive \
+         ORDER BY customer",
+    );
+    assert_eq!(
+        column(&rows, "customer"),
+        [json!("ada"), json!("bob"), json!("cy"), json!("dee")]
+    );
+
+    // Both ada rows differ in amount, so only order 3 is on both sides
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT customer, amount FROM orders \
+         INTERSECT SELECT customer, amount FROM orders_archive",
+    );
+    assert_eq!(rows, vec![json!({"customer": "cy", "amount": 300})]);
+
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT customer FROM orders EXCEPT SELECT customer FROM orders_archive",
+    );
+    assert_eq!(customers(rows), ["bob"]);
+
+    // Every customer, and only the ones with orders in both tables, once
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "(SELECT customer FROM orders UNION ALL SELECT customer FROM orders_archive) \
+         INTERSECT SELECT customer FROM orders_archive",
+    );
+    assert_eq!(customers(rows), ["ada", "cy", "dee"]);
+}
+
+#[test]
+fn all_keeps_duplicates() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    // ada, ada, bob, cy, cy, dee
+    let both = "(SELECT customer FROM orders UNION ALL SELECT customer FROM orders_archive)";
+    assert_eq!(
+        customers(run(
+            &mut engine,
+            &mut ctx,
+            &format!("{} INTERSECT ALL SELECT customer FROM orders", both)
+        )),
+        ["ada", "bob", "cy"]
+    );
+    assert_eq!(
+        customers(run(
+            &mut engine,
+            &mut ctx,
+            &format!("{} EXCEPT ALL SELECT customer FROM orders", both)
+        )),
+        ["ada", "cy", "dee"]
+    );
+    assert_eq!(
+        customers(run(
+            &mut engine,
+            &mut ctx,
+            &format!("{} EXCEPT SELECT customer FROM orders", both)
+        )),
+        ["dee"]
+    );
+}
+
+#[test]
+fn sides_must_match_in_width_and_type() {
+    let temp = TempDir::new().unwrap();
+    let (mut engine, mut ctx) = setup(&temp);
+
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT id, customer FROM orders UNION SELECT id FROM orders_archive",
+    );
+    assert!(
+        err.contains("each UNION query must have the same number of columns"),
+        "{}",
+        err
+    );
+    // With `*` the rows show the mismatch
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT * FROM orders EXCEPT SELECT id FROM orders_archive",
+    );
+    assert!(
+        err.contains("each EXCEPT query must have the same number of columns"),
+        "{}",
+        err
+    );
+
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM orders UNION ALL SELECT customer FROM orders_archive",
+    );
+    assert!(
+        err.contains("UNION types integer and text cannot be matched"),
+        "{}",
+        err
+    );
+    // Integers and numerics mix
+    let rows = run(
+        &mut engine,
+        &mut ctx,
+        "SELECT amount FROM orders WHERE id = 1 UNION SELECT 2.5",
+    );
+    assert_eq!(rows.len(), 2);
+}
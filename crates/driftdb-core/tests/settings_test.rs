@@ -0,0 +1,368 @@
+//! Runtime parameters: `SHOW`, `SET` and `RESET` check values against
+//! each parameter's type, a session's `SET` stays in that session, and
+//! `SET GLOBAL` reaches every session that hasn't set its own value but
+//! needs a superuser, as does `ALTER ROLE ... SET`, which reaches that
+//! role's sessions.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::row_level_security::{RlsManager, RowSecurity, SecurityContext};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn show(engine: &mut Engine, ctx: &mut SessionContext, name: &str) -> String {
+    let rows = run(engine, ctx, &format!("SHOW {}", name));
+    let row = rows[0].as_object().unwrap();
+    row.values().next().unwrap().as_str().unwrap().to_string()
+}
+
+fn error(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> String {
+    execute_sql_in_session(engine, sql, ctx)
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn set_checks_values_and_show_reads_them_back() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SHOW work_mem"),
+        vec![json!({"work_mem": "4MB"})]
+    );
+    run(&mut engine, &mut ctx, "SET work_mem = 65536");
+    assert_eq!(show(&mut engine, &mut ctx, "WORK_MEM"), "64MB");
+    run(&mut engine, &mut ctx, "SET SESSION work_mem TO '1536kB'");
+    assert_eq!(show(&mut engine, &mut ctx, "work_mem"), "1536kB");
+
+    run(&mut engine, &mut ctx, "SET statement_timeout = '90s'");
+    assert_eq!(show(&mut engine, &mut ctx, "statement_timeout"), "90s");
+    assert_eq!(
+        ctx.statement_timeout(&engine),
+        Some(Duration::from_secs(90))
+    );
+    run(&mut engine, &mut ctx, "SET TIME ZONE 'Europe/Berlin'");
+    assert_eq!(show(&mut engine, &mut ctx, "timezone"), "Europe/Berlin");
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SHOW datestyle"),
+        vec![json!({"DateStyle": "ISO, MDY"})]
+    );
+
+    let err = error(&mut engine, &mut ctx, "SET work_mem = 'lots'");
+    assert!(
+        err.contains("invalid value for parameter \"work_mem\""),
+        "{}",
+        err
+    );
+    let err = error(&mut engine, &mut ctx, "SET extra_float_digits = 4");
+    assert!(err.contains("outside the valid range"), "{}", err);
+    let err = error(&mut engine, &mut ctx, "SET synchronous_commit TO maybe");
+    assert!(err.contains("synchronous_commit"), "{}", err);
+    let err = error(&mut engine, &mut ctx, "SET server_version = '17'");
+    assert!(err.contains("cannot be changed"), "{}", err);
+    let err = error(&mut engine, &mut ctx, "SET no_such_thing = 1");
+    assert!(
+        err.contains("unrecognized configuration parameter \"no_such_thing\""),
+        "{}",
+        err
+    );
+    // A rejected value leaves the old one in place
+    assert_eq!(show(&mut engine, &mut ctx, "work_mem"), "1536kB");
+
+    let all = run(&mut engine, &mut ctx, "SHOW ALL");
+    let work_mem = all.iter().find(|row| row["name"] == "work_mem").unwrap();
+    assert_eq!(work_mem["setting"], "1536kB");
+    assert!(all.iter().any(|row| row["name"] == "server_version"));
+
+    run(&mut engine, &mut ctx, "RESET work_mem");
+    assert_eq!(show(&mut engine, &mut ctx, "work_mem"), "4MB");
+    run(&mut engine, &mut ctx, "RESET ALL");
+    assert_eq!(show(&mut engine, &mut ctx, "statement_timeout"), "0");
+    assert_eq!(ctx.statement_timeout(&engine), None);
+    assert!(ctx.settings.is_empty());
+}
+
+#[test]
+fn session_settings_do_not_leak_into_other_sessions() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut alice = SessionContext::new();
+    let mut bob = SessionContext::new();
+
+    run(&mut engine, &mut alice, "CREATE SCHEMA app");
+    run(&mut engine, &mut alice, "SET work_mem = '2kB'");
+    run(&mut engine, &mut alice, "SET statement_timeout = 250");
+    run(&mut engine, &mut alice, "SET search_path TO app, public");
+    run(&mut engine, &mut alice, "SET synchronous_commit = off");
+    run(&mut engine, &mut alice, "SET application_name = 'alice'");
+
+    assert_eq!(show(&mut engine, &mut bob, "work_mem"), "4MB");
+    assert_eq!(show(&mut engine, &mut bob, "statement_timeout"), "0");
+    assert_eq!(show(&mut engine, &mut bob, "search_path"), "public");
+    assert_eq!(show(&mut engine, &mut bob, "synchronous_commit"), "full");
+    assert_eq!(show(&mut engine, &mut bob, "application_name"), "");
+    assert_eq!(bob.statement_timeout(&engine), None);
+
+    assert_eq!(show(&mut engine, &mut alice, "work_mem"), "2kB");
+    assert_eq!(show(&mut engine, &mut alice, "search_path"), "app, public");
+    assert_eq!(show(&mut engine, &mut alice, "synchronous_commit"), "async");
+    assert_eq!(
+        alice.statement_timeout(&engine),
+        Some(Duration::from_millis(250))
+    );
+
+    // Only alice's sorts run in her 2kB of work_mem
+    run(
+        &mut engine,
+        &mut bob,
+        "CREATE TABLE events (id INT, label VARCHAR, PRIMARY KEY (id))",
+    );
+    for i in 0..300 {
+        run(
+            &mut engine,
+            &mut bob,
+            &format!(
+                "INSERT INTO events (id, label) VALUES ({}, 'event {}')",
+                i,
+                (i * 37) % 300
+            ),
+        );
+    }
+    let sql = "SELECT id, label FROM public.events ORDER BY label";
+    let sorted = run(&mut engine, &mut bob, sql);
+    assert_eq!(engine.spill_manager().stats().sorts_spilled, 0);
+    assert_eq!(run(&mut engine, &mut alice, sql), sorted);
+    assert_eq!(engine.spill_manager().stats().sorts_spilled, 1);
+}
+
+#[test]
+fn set_global_reaches_sessions_without_their_own_value() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut admin = SessionContext::new();
+    let mut own = SessionContext::new();
+
+    run(&mut engine, &mut own, "SET work_mem = '1MB'");
+    run(&mut engine, &mut admin, "SET GLOBAL work_mem = '8MB'");
+    run(
+        &mut engine,
+        &mut admin,
+        "SET GLOBAL statement_timeout = '2s'",
+    );
+
+    let mut fresh = SessionContext::new();
+    assert_eq!(show(&mut engine, &mut fresh, "work_mem"), "8MB");
+    assert_eq!(
+        fresh.statement_timeout(&engine),
+        Some(Duration::from_secs(2))
+    );
+    assert_eq!(engine.spill_manager().work_mem(), 8 * 1024 * 1024);
+    // A session's own value wins until it resets it
+    assert_eq!(show(&mut engine, &mut own, "work_mem"), "1MB");
+    run(&mut engine, &mut own, "RESET work_mem");
+    assert_eq!(show(&mut engine, &mut own, "work_mem"), "8MB");
+
+    // A global search path resolves unqualified names in every session
+    run(&mut engine, &mut admin, "CREATE SCHEMA app");
+    run(
+        &mut engine,
+        &mut admin,
+        "CREATE TABLE app.widgets (id INT, PRIMARY KEY (id))",
+    );
+    run(
+        &mut engine,
+        &mut admin,
+        "INSERT INTO app.widgets (id) VALUES (7)",
+    );
+    run(
+        &mut engine,
+        &mut admin,
+        "SET GLOBAL search_path = app, public",
+    );
+    assert_eq!(
+        fresh.effective_search_path(&engine),
+        vec!["app".to_string(), "public".to_string()]
+    );
+    assert_eq!(
+        run(&mut engine, &mut fresh, "SELECT id FROM widgets"),
+        vec![json!({"id": 7})]
+    );
+
+    run(&mut engine, &mut admin, "RESET GLOBAL search_path");
+    run(&mut engine, &mut admin, "RESET GLOBAL work_mem");
+    assert_eq!(show(&mut engine, &mut fresh, "search_path"), "public");
+    assert_eq!(show(&mut engine, &mut fresh, "work_mem"), "4MB");
+}
+
+#[test]
+fn set_global_needs_a_superuser() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let manager = Arc::new(RlsManager::new());
+    let mut alice = SessionContext::new();
+    alice.row_security = Some(RowSecurity::new(
+        manager.clone(),
+        SecurityContext::new("alice".to_string(), vec![], false),
+    ));
+    let mut root = SessionContext::new();
+    root.row_security = Some(RowSecurity::new(
+        manager,
+        SecurityContext::new("driftdb".to_string(), vec![], true),
+    ));
+
+    let err = error(&mut engine, &mut alice, "SET GLOBAL work_mem = '8MB'");
+    assert!(err.contains("permission denied"), "{}", err);
+    let err = error(&mut engine, &mut alice, "RESET GLOBAL work_mem");
+    assert!(err.contains("permission denied"), "{}", err);
+    run(&mut engine, &mut alice, "SET work_mem = '8MB'");
+
+    run(&mut engine, &mut root, "SET GLOBAL work_mem = '16MB'");
+    assert_eq!(show(&mut engine, &mut alice, "work_mem"), "8MB");
+    assert_eq!(
+        show(&mut engine, &mut SessionContext::new(), "work_mem"),
+        "16MB"
+    );
+}
+
+fn session_as(
+    manager: &Arc<RlsManager>,
+    user: &str,
+    roles: &[&str],
+    superuser: bool,
+) -> SessionContext {
+    let mut ctx = SessionContext::new();
+    ctx.row_security = Some(RowSecurity::new(
+        manager.clone(),
+        SecurityContext::new(
+            user.to_string(),
+            roles.iter().map(|role| role.to_string()).collect(),
+            superuser,
+        ),
+    ));
+    ctx
+}
+
+#[test]
+fn result_limits_trigger_at_the_threshold() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE events (id INT, label VARCHAR, PRIMARY KEY (id))",
+    );
+    for i in 0..20 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!(
+                "INSERT INTO events (id, label) VALUES ({}, 'event {}')",
+                i, i
+            ),
+        );
+    }
+    assert_eq!(show(&mut engine, &mut ctx, "max_result_rows"), "0");
+    assert_eq!(run(&mut engine, &mut ctx, "SELECT * FROM events").len(), 20);
+
+    // Exactly at the limit is fine; one more row is not
+    run(&mut engine, &mut ctx, "SET max_result_rows = 10");
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT * FROM events WHERE id < 10").len(),
+        10
+    );
+    let err = error(&mut engine, &mut ctx, "SELECT * FROM events WHERE id < 11");
+    assert!(err.contains("11 rows"), "{}", err);
+    assert!(err.contains("max_result_rows = 10"), "{}", err);
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT * FROM events LIMIT 5").len(),
+        5
+    );
+    // Rows read along the way, like a count, don't count
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT COUNT(*) AS n FROM events"),
+        vec![json!({"n": 20})]
+    );
+
+    run(&mut engine, &mut ctx, "RESET max_result_rows");
+    run(&mut engine, &mut ctx, "SET max_result_bytes = '1kB'");
+    assert_eq!(show(&mut engine, &mut ctx, "max_result_bytes"), "1kB");
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT * FROM events WHERE id = 1").len(),
+        1
+    );
+    let err = error(&mut engine, &mut ctx, "SELECT * FROM events");
+    assert!(err.contains("max_result_bytes = 1kB"), "{}", err);
+    run(&mut engine, &mut ctx, "SET max_result_bytes = 0");
+    assert_eq!(run(&mut engine, &mut ctx, "SELECT * FROM events").len(), 20);
+}
+
+#[test]
+fn alter_role_set_applies_to_that_roles_sessions() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let manager = Arc::new(RlsManager::new());
+    let mut root = session_as(&manager, "driftdb", &[], true);
+    let mut analyst = session_as(&manager, "ana", &["analysts"], false);
+
+    run(&mut engine, &mut root, "SET GLOBAL max_result_rows = 1000");
+    run(
+        &mut engine,
+        &mut root,
+        "ALTER ROLE analysts SET max_result_rows = 100",
+    );
+    run(
+        &mut engine,
+        &mut root,
+        "ALTER ROLE driftdb SET max_result_rows TO 0",
+    );
+    assert_eq!(show(&mut engine, &mut analyst, "max_result_rows"), "100");
+    assert_eq!(show(&mut engine, &mut root, "max_result_rows"), "0");
+    assert_eq!(
+        show(&mut engine, &mut SessionContext::new(), "max_result_rows"),
+        "1000"
+    );
+    // A user's own value comes before its roles', and a session's first
+    run(
+        &mut engine,
+        &mut root,
+        "ALTER USER ana SET max_result_rows = 50",
+    );
+    assert_eq!(show(&mut engine, &mut analyst, "max_result_rows"), "50");
+    run(&mut engine, &mut analyst, "SET max_result_rows = 5");
+    assert_eq!(show(&mut engine, &mut analyst, "max_result_rows"), "5");
+
+    let err = error(
+        &mut engine,
+        &mut analyst,
+        "ALTER ROLE analysts RESET max_result_rows",
+    );
+    assert!(err.contains("permission denied"), "{}", err);
+    let err = error(
+        &mut engine,
+        &mut root,
+        "ALTER ROLE analysts SET work_mem = '1MB'",
+    );
+    assert!(err.contains("cannot be set per role"), "{}", err);
+
+    // Role values survive a restart
+    drop(engine);
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let mut analyst = session_as(&manager, "ana", &["analysts"], false);
+    assert_eq!(show(&mut engine, &mut analyst, "max_result_rows"), "50");
+    run(&mut engine, &mut root, "ALTER USER ana RESET ALL");
+    assert_eq!(show(&mut engine, &mut analyst, "max_result_rows"), "100");
+}
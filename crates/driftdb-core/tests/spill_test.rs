@@ -0,0 +1,149 @@
+//! Sorts and hash joins that outgrow `work_mem` spill to temporary files
+//! and return exactly what the in-memory versions do, leaving no files
+//! behind.
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+use driftdb_core::spill::DEFAULT_WORK_MEM;
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    execute_sql(engine, sql).unwrap();
+}
+
+/// 300 customers, every third without orders, and 600 orders, a few of
+/// them for customers that don't exist
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE customers (id INT, name VARCHAR, PRIMARY KEY (id))",
+    );
+    run(
+        &mut engine,
+        "CREATE TABLE orders (id INT, customer_id INT, amount INT, PRIMARY KEY (id))",
+    );
+    for i in 0..300 {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO customers (id, name) VALUES ({}, 'customer {}')",
+                i, i
+            ),
+        );
+    }
+    for i in 0..600 {
+        let customer = if i % 50 == 0 {
+            1000 + i
+        } else {
+            (i * 7) % 300 / 3 * 3 + 1
+        };
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO orders (id, customer_id, amount) VALUES ({}, {}, {})",
+                i,
+                customer,
+                (i * 37) % 500
+            ),
+        );
+    }
+    engine
+}
+
+/// Rows in a canonical order, for queries without ORDER BY
+fn sorted(mut rows: Vec<Value>) -> Vec<Value> {
+    rows.sort_by_key(|row| row.to_string());
+    rows
+}
+
+fn spill_dir_is_empty(engine: &Engine) -> bool {
+    let dir = engine.spill_manager().temp_dir();
+    !dir.exists() || std::fs::read_dir(dir).unwrap().next().is_none()
+}
+
+#[test]
+fn order_by_spills_past_work_mem() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let sql = "SELECT id, customer_id, amount FROM orders ORDER BY amount DESC, customer_id";
+
+    let in_memory = rows(&mut engine, sql);
+    assert_eq!(engine.spill_manager().stats().sorts_spilled, 0);
+
+    engine.spill_manager().set_work_mem(2 * 1024);
+    let spilled = rows(&mut engine, sql);
+    let stats = engine.spill_manager().stats();
+    assert_eq!(stats.sorts_spilled, 1);
+    assert!(stats.files_written > 1);
+    assert!(stats.bytes_written > 0);
+
+    assert_eq!(spilled.len(), 600);
+    assert_eq!(spilled, in_memory);
+    assert!(spill_dir_is_empty(&engine));
+}
+
+#[test]
+fn joins_spill_past_work_mem() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let queries = [
+        "SELECT * FROM orders o JOIN customers c ON o.customer_id = c.id",
+        "SELECT * FROM customers c LEFT JOIN orders o ON c.id = o.customer_id",
+        "SELECT * FROM orders o FULL OUTER JOIN customers c ON o.customer_id = c.id",
+    ];
+
+    let in_memory: Vec<_> = queries
+        .iter()
+        .map(|sql| sorted(rows(&mut engine, sql)))
+        .collect();
+    assert_eq!(engine.spill_manager().stats().joins_spilled, 0);
+
+    engine.spill_manager().set_work_mem(1024);
+    for (sql, expected) in queries.iter().zip(&in_memory) {
+        let spilled = sorted(rows(&mut engine, sql));
+        assert!(!spilled.is_empty(), "{}", sql);
+        assert_eq!(&spilled, expected, "{}", sql);
+    }
+    assert_eq!(
+        engine.spill_manager().stats().joins_spilled,
+        queries.len() as u64
+    );
+    assert!(spill_dir_is_empty(&engine));
+
+    engine.spill_manager().set_work_mem(DEFAULT_WORK_MEM);
+    let before = engine.spill_manager().stats();
+    rows(&mut engine, queries[0]);
+    assert_eq!(engine.spill_manager().stats(), before);
+}
+
+#[test]
+fn spill_directory_is_configurable_and_cleaned_on_open() {
+    let temp = TempDir::new().unwrap();
+    let spill_dir = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    engine.spill_manager().set_temp_dir(spill_dir.path());
+    engine.spill_manager().set_work_mem(1024);
+    rows(&mut engine, "SELECT * FROM orders ORDER BY amount");
+    assert_eq!(engine.spill_manager().stats().sorts_spilled, 1);
+    assert!(spill_dir_is_empty(&engine));
+    assert!(!temp.path().join("tmp").exists());
+    drop(engine);
+
+    // A crash mid-sort leaves its directory behind; reopening removes it
+    let stale = temp.path().join("tmp").join("spill-1-7");
+    std::fs::create_dir_all(&stale).unwrap();
+    std::fs::write(stale.join("00000000.json"), "{}\n").unwrap();
+    let engine = Engine::open(temp.path()).unwrap();
+    assert!(!stale.exists());
+    assert!(spill_dir_is_empty(&engine));
+}
@@ -0,0 +1,74 @@
+//! SQL lint: schema checks against a live database, without executing the
+//! statement being checked.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::sql_lint::{analyze, Risk, StatementCategory};
+use driftdb_core::Engine;
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn issues(engine: &Engine, sql: &str) -> Vec<String> {
+    let mut report = analyze(sql);
+    report.check_schema(engine);
+    report.schema_issues
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE users (id INT, name VARCHAR, nickname VARCHAR, email VARCHAR, PRIMARY KEY (id))",
+        "INSERT INTO users (id, name, nickname, email) VALUES (1, 'Ada', 'ada', 'ada@example.com')",
+        "ALTER TABLE users DROP COLUMN nickname",
+    ] {
+        run(&mut engine, sql);
+    }
+    engine
+}
+
+#[test]
+fn dropped_columns_and_missing_tables_are_reported() {
+    let temp = TempDir::new().unwrap();
+    let engine = setup(&temp);
+
+    assert!(issues(&engine, "SELECT id, name FROM users WHERE email = $1").is_empty());
+    assert_eq!(
+        issues(&engine, "SELECT nickname FROM users"),
+        vec!["column \"nickname\" was dropped or renamed in table \"users\""]
+    );
+    assert_eq!(
+        issues(&engine, "UPDATE users SET phone = $1 WHERE id = $2"),
+        vec!["column \"phone\" does not exist"]
+    );
+    assert_eq!(
+        issues(&engine, "SELECT * FROM accounts"),
+        vec!["relation \"accounts\" does not exist"]
+    );
+
+    // DDL names things that don't exist yet
+    assert!(issues(&engine, "CREATE TABLE accounts (id INT, PRIMARY KEY (id))").is_empty());
+}
+
+#[test]
+fn analysis_does_not_execute() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let report = analyze("DELETE FROM users WHERE id = 1");
+    assert_eq!(report.category, StatementCategory::Write);
+    assert!(report.is_clean());
+
+    let report = analyze("SELECT * FROM users WHERE name = '\" + input + \"'");
+    assert_eq!(report.risks, vec![Risk::StringConcatenation]);
+    assert!(!report.is_clean());
+
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(&mut engine, "SELECT * FROM users", &mut ctx).unwrap() {
+        driftdb_core::QueryResult::Rows { data } => assert_eq!(data.len(), 1),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
@@ -0,0 +1,159 @@
+//! Subqueries in WHERE: `IN (SELECT ...)`, scalar `col = (SELECT ...)`, and
+//! correlated `EXISTS`, for reads and for UPDATE/DELETE targets.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE customers (id VARCHAR, region VARCHAR, PRIMARY KEY (id))",
+        "CREATE TABLE orders (id VARCHAR, customer_id VARCHAR, amount INT, PRIMARY KEY (id))",
+        "INSERT INTO customers (id, region) VALUES ('alice', 'eu'), ('bob', 'us'), ('carol', 'eu')",
+        "INSERT INTO orders (id, customer_id, amount) VALUES ('o1', 'alice', 10), ('o2', 'alice', 20), ('o3', 'bob', 5)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn ids(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<String> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => {
+            let mut ids: Vec<String> = data
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        }
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn in_subquery_against_another_table() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers WHERE id IN (SELECT customer_id FROM orders WHERE amount > 1)",
+        ),
+        vec!["alice", "bob"]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers WHERE id NOT IN (SELECT customer_id FROM orders)",
+        ),
+        vec!["carol"]
+    );
+}
+
+#[test]
+fn scalar_subquery_must_return_at_most_one_row() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers WHERE id = (SELECT customer_id FROM orders WHERE id = 'o3')",
+        ),
+        vec!["bob"]
+    );
+
+    // No rows yields NULL, which matches nothing.
+    assert!(ids(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM customers WHERE id = (SELECT customer_id FROM orders WHERE id = 'none')",
+    )
+    .is_empty());
+
+    let err = execute_sql_in_session(
+        &mut engine,
+        "SELECT id FROM customers WHERE id = (SELECT customer_id FROM orders)",
+        &mut ctx,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("more than one row"), "{}", err);
+}
+
+#[test]
+fn correlated_exists() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers c
+             WHERE EXISTS (SELECT 1 FROM orders o WHERE o.customer_id = c.id AND o.amount >= 10)",
+        ),
+        vec!["alice"]
+    );
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers c
+             WHERE NOT EXISTS (SELECT 1 FROM orders o WHERE o.customer_id = c.id)",
+        ),
+        vec!["carol"]
+    );
+}
+
+#[test]
+fn delete_with_subquery_only_touches_matching_rows() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    execute_sql_in_session(
+        &mut engine,
+        "DELETE FROM orders WHERE customer_id IN (SELECT id FROM customers WHERE region = 'us')",
+        &mut ctx,
+    )
+    .unwrap();
+
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "SELECT id FROM orders"),
+        vec!["o1", "o2"]
+    );
+}
+
+#[test]
+fn unqualified_names_resolve_against_the_subquery_rows_first() {
+    let (_t, mut engine, mut ctx) = setup();
+    // `tickets` declares only its key, so `status` isn't in its schema
+    engine.create_table("tickets", "id", vec![]).unwrap();
+    for sql in [
+        "ALTER TABLE customers ADD COLUMN status VARCHAR",
+        "UPDATE customers SET status = 'open' WHERE id = 'alice'",
+        "INSERT INTO tickets (id, status) VALUES ('t1', 'closed')",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+
+    // `status` is the ticket's, not the customer's
+    assert!(ids(
+        &mut engine,
+        &mut ctx,
+        "SELECT id FROM customers WHERE EXISTS (SELECT 1 FROM tickets WHERE status = 'open')",
+    )
+    .is_empty());
+    assert_eq!(
+        ids(
+            &mut engine,
+            &mut ctx,
+            "SELECT id FROM customers WHERE EXISTS (SELECT 1 FROM tickets WHERE status = 'closed')",
+        ),
+        vec!["alice", "bob", "carol"]
+    );
+}
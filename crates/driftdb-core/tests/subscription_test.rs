@@ -0,0 +1,198 @@
+//! `EventSubscription`: a table's events delivered in order as they are
+//! appended, through the engine, over a channel or straight from the data
+//! directory.
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, EventSubscription, EventType};
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    execute_sql(
+        &mut engine,
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, status VARCHAR)",
+    )
+    .unwrap();
+    execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, status) VALUES (1, 'new')",
+    )
+    .unwrap();
+    engine
+}
+
+#[test]
+fn subscription_delivers_new_events_in_order() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut subscription = engine.subscribe("orders").unwrap();
+    assert_eq!(
+        Some(subscription.position()),
+        engine.table_sequence("orders")
+    );
+
+    for sql in [
+        "INSERT INTO orders (id, status) VALUES (2, 'new')",
+        "UPDATE orders SET status = 'paid' WHERE id = 2",
+        "DELETE FROM orders WHERE id = 1",
+    ] {
+        execute_sql(&mut engine, sql).unwrap();
+    }
+
+    let events = subscription.next_events(Duration::from_secs(5)).unwrap();
+    let kinds: Vec<_> = events.iter().map(|e| e.event_type.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![EventType::Insert, EventType::Patch, EventType::SoftDelete]
+    );
+    assert_eq!(events[0].primary_key, json!(2));
+    assert_eq!(events[2].primary_key, json!(1));
+    assert!(events.windows(2).all(|w| w[0].sequence < w[1].sequence));
+    assert_eq!(
+        Some(subscription.position()),
+        engine.table_sequence("orders")
+    );
+
+    // Each event is delivered once, and an idle wait runs out
+    let started = Instant::now();
+    assert!(subscription
+        .next_events(Duration::from_millis(100))
+        .unwrap()
+        .is_empty());
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    // Seeking back replays from there
+    subscription.seek(0).unwrap();
+    assert_eq!(subscription.poll().unwrap().len(), 4);
+
+    assert!(engine.subscribe("missing").is_err());
+}
+
+#[test]
+fn waiting_subscription_is_woken_by_an_append() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let mut subscription = engine.subscribe("orders").unwrap();
+
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        execute_sql(
+            &mut engine,
+            "INSERT INTO orders (id, status) VALUES (2, 'new')",
+        )
+        .unwrap();
+    });
+
+    let events = subscription.next_events(Duration::from_secs(10)).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].payload["status"], json!("new"));
+    writer.join().unwrap();
+}
+
+#[test]
+fn data_directory_can_be_followed_while_the_engine_is_open() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // No engine and no table lock: the writer keeps the database open
+    let mut subscription = EventSubscription::open(temp.path(), "orders").unwrap();
+    assert!(subscription.poll().unwrap().is_empty());
+
+    for id in 2..=50 {
+        execute_sql(
+            &mut engine,
+            &format!("INSERT INTO orders (id, status) VALUES ({}, 'new')", id),
+        )
+        .unwrap();
+        if id % 10 == 0 {
+            let events = subscription.next_events(Duration::from_secs(5)).unwrap();
+            assert_eq!(events.last().unwrap().primary_key, json!(id));
+        }
+    }
+    assert_eq!(
+        Some(subscription.position()),
+        engine.table_sequence("orders")
+    );
+
+    // Starting from a sequence replays the table from it
+    let mut replay = EventSubscription::open(temp.path(), "orders").unwrap();
+    replay.seek(0).unwrap();
+    let ids: Vec<_> = replay
+        .poll()
+        .unwrap()
+        .iter()
+        .map(|e| e.primary_key.clone())
+        .collect();
+    assert_eq!(ids, (1..=50).map(|id| json!(id)).collect::<Vec<_>>());
+
+    assert!(EventSubscription::open(temp.path(), "missing").is_err());
+}
+
+#[test]
+fn receiver_catches_up_then_follows() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    for id in 2..=5 {
+        execute_sql(
+            &mut engine,
+            &format!("INSERT INTO orders (id, status) VALUES ({}, 'new')", id),
+        )
+        .unwrap();
+    }
+
+    // History after sequence 2, then live events
+    let receiver = engine.subscribe_events("orders", Some(2), 4).unwrap();
+    execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, status) VALUES (6, 'new')",
+    )
+    .unwrap();
+    let ids: Vec<_> = (0..4)
+        .map(|_| {
+            receiver
+                .recv_timeout(Duration::from_secs(5))
+                .unwrap()
+                .unwrap()
+                .primary_key
+        })
+        .collect();
+    assert_eq!(ids, vec![json!(3), json!(4), json!(5), json!(6)]);
+    assert!(receiver.try_recv().is_none());
+}
+
+#[test]
+fn slow_receiver_does_not_stall_writers() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    let receiver = engine.subscribe_events("orders", None, 1).unwrap();
+
+    // Nothing is read while these are written
+    for id in 2..=200 {
+        execute_sql(
+            &mut engine,
+            &format!("INSERT INTO orders (id, status) VALUES ({}, 'new')", id),
+        )
+        .unwrap();
+    }
+
+    let mut sequences = Vec::new();
+    for event in receiver.take(199) {
+        sequences.push(event.unwrap().sequence);
+    }
+    assert!(sequences.windows(2).all(|w| w[1] == w[0] + 1));
+    assert_eq!(sequences.last().copied(), engine.table_sequence("orders"));
+
+    // A dropped receiver leaves the table to its writers
+    let receiver = engine.subscribe_events("orders", Some(0), 1).unwrap();
+    drop(receiver);
+    execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, status) VALUES (201, 'new')",
+    )
+    .unwrap();
+    assert!(engine.subscribe_events("missing", None, 1).is_err());
+}
@@ -0,0 +1,168 @@
+//! The read-only system catalog: `information_schema` views and
+//! `pg_catalog` tables describing the real schemas, queried with plain
+//! SELECT.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email VARCHAR(120), visits INTEGER DEFAULT 0)",
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER, total DOUBLE PRECISION)",
+        "CREATE SCHEMA app",
+        "CREATE TABLE app.settings (name VARCHAR PRIMARY KEY, value JSONB)",
+    ] {
+        execute_sql(&mut engine, sql).unwrap();
+    }
+    engine
+}
+
+#[test]
+fn information_schema_tables_lists_user_tables() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT table_name FROM information_schema.tables \
+             WHERE table_schema = 'public' ORDER BY table_name",
+        ),
+        vec![
+            json!({"table_name": "orders"}),
+            json!({"table_name": "users"})
+        ]
+    );
+
+    let all = rows(
+        &mut engine,
+        "SELECT table_schema, table_name, table_type FROM information_schema.tables",
+    );
+    assert!(all.contains(&json!({
+        "table_schema": "app",
+        "table_name": "settings",
+        "table_type": "BASE TABLE",
+    })));
+    // The catalog lists itself
+    assert!(all.contains(&json!({
+        "table_schema": "information_schema",
+        "table_name": "columns",
+        "table_type": "VIEW",
+    })));
+
+    // A new table shows up on the next read
+    execute_sql(&mut engine, "CREATE TABLE events (id INTEGER PRIMARY KEY)").unwrap();
+    let public = rows(
+        &mut engine,
+        "SELECT table_name FROM information_schema.tables WHERE table_schema = 'public'",
+    );
+    assert_eq!(public.len(), 3);
+}
+
+#[test]
+fn information_schema_columns_describes_each_column() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT column_name, ordinal_position, data_type, is_nullable, \
+             character_maximum_length, column_default \
+             FROM information_schema.columns WHERE table_name = 'users' \
+             ORDER BY ordinal_position",
+        ),
+        vec![
+            json!({"column_name": "id", "ordinal_position": 1, "data_type": "integer",
+                   "is_nullable": "NO", "character_maximum_length": null, "column_default": null}),
+            json!({"column_name": "email", "ordinal_position": 2, "data_type": "character varying",
+                   "is_nullable": "YES", "character_maximum_length": 120, "column_default": null}),
+            json!({"column_name": "visits", "ordinal_position": 3, "data_type": "integer",
+                   "is_nullable": "YES", "character_maximum_length": null, "column_default": "0"}),
+        ]
+    );
+
+    let settings = rows(
+        &mut engine,
+        "SELECT column_name, udt_name FROM information_schema.columns \
+         WHERE table_schema = 'app' AND table_name = 'settings'",
+    );
+    assert_eq!(
+        settings,
+        vec![
+            json!({"column_name": "name", "udt_name": "varchar"}),
+            json!({"column_name": "value", "udt_name": "jsonb"}),
+        ]
+    );
+}
+
+#[test]
+fn pg_class_and_pg_attribute() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let class = rows(
+        &mut engine,
+        "SELECT oid, relkind, relnatts FROM pg_catalog.pg_class WHERE relname = 'orders'",
+    );
+    assert_eq!(class.len(), 1);
+    assert_eq!(class[0]["relkind"], "r");
+    assert_eq!(class[0]["relnatts"], 3);
+    let oid = class[0]["oid"].as_u64().unwrap();
+
+    let attributes = rows(
+        &mut engine,
+        &format!(
+            "SELECT attname, atttypid, attnum, attnotnull FROM pg_attribute \
+             WHERE attrelid = {} ORDER BY attnum",
+            oid
+        ),
+    );
+    assert_eq!(
+        attributes,
+        vec![
+            json!({"attname": "id", "atttypid": 23, "attnum": 1, "attnotnull": true}),
+            json!({"attname": "user_id", "atttypid": 23, "attnum": 2, "attnotnull": false}),
+            json!({"attname": "total", "atttypid": 701, "attnum": 3, "attnotnull": false}),
+        ]
+    );
+
+    // Unqualified catalog names resolve to pg_catalog, and a table's
+    // namespace leads back to its schema name
+    let class = rows(
+        &mut engine,
+        "SELECT relnamespace FROM pg_class WHERE relname = 'settings'",
+    );
+    let namespaces = rows(
+        &mut engine,
+        &format!(
+            "SELECT nspname FROM pg_namespace WHERE oid = {}",
+            class[0]["relnamespace"]
+        ),
+    );
+    assert_eq!(namespaces, vec![json!({"nspname": "app"})]);
+}
+
+#[test]
+fn catalog_is_read_only() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert!(execute_sql(
+        &mut engine,
+        "INSERT INTO information_schema.tables (table_name) VALUES ('x')",
+    )
+    .is_err());
+    assert!(execute_sql(&mut engine, "DELETE FROM pg_catalog.pg_class").is_err());
+}
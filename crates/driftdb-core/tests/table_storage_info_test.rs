@@ -0,0 +1,52 @@
+//! Per-table storage statistics before and after compaction.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{DriftError, Engine};
+
+#[test]
+fn storage_info_tracks_history_and_compaction() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE items (id VARCHAR, qty INT, PRIMARY KEY (id))",
+        "INSERT INTO items (id, qty) VALUES ('a', 1)",
+        "INSERT INTO items (id, qty) VALUES ('b', 1)",
+        "UPDATE items SET qty = 2 WHERE id = 'a'",
+        "UPDATE items SET qty = 3 WHERE id = 'a'",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+
+    let info = engine.table_storage_info("items").unwrap();
+    assert_eq!(info.event_count, 4);
+    assert_eq!(info.live_rows, 2);
+    assert_eq!(info.dead_version_ratio, 0.5);
+    assert_eq!(info.last_compaction_sequence, 0);
+    assert_eq!(info.snapshot_count, 0);
+    assert!(info.logical_bytes > 0);
+    assert!(info.total_bytes > info.logical_bytes);
+
+    engine.create_snapshot("items").unwrap();
+    engine.compact_table("items").unwrap();
+
+    let compacted = engine.table_storage_info("items").unwrap();
+    assert_eq!(compacted.event_count, 2);
+    assert_eq!(compacted.live_rows, 2);
+    assert_eq!(compacted.dead_version_ratio, 0.0);
+    assert_eq!(compacted.last_compaction_sequence, 4);
+    assert_eq!(compacted.snapshot_count, 1);
+    assert_eq!(compacted.logical_bytes, info.logical_bytes);
+}
+
+#[test]
+fn storage_info_for_unknown_table_fails() {
+    let temp = TempDir::new().unwrap();
+    let engine = Engine::init(temp.path()).unwrap();
+    assert!(matches!(
+        engine.table_storage_info("missing"),
+        Err(DriftError::TableNotFound(_))
+    ));
+}
@@ -0,0 +1,137 @@
+//! Tiered storage: closed segments move to the cold tier, queries read
+//! them back through the cache, and a reopened engine finds them there.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::storage::{MemoryBackend, StorageBackend, TierAge, TierPolicy, TieredBackend};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn tiered(root: &Path, cold_after: Duration) -> (Arc<TieredBackend>, MemoryBackend) {
+    let cold = MemoryBackend::new();
+    let backend = TieredBackend::new(
+        Arc::new(MemoryBackend::new()),
+        Arc::new(cold.clone()),
+        root,
+        "/archive",
+        TierPolicy {
+            cold_after,
+            cache_size: 64 * 1024 * 1024,
+            age: TierAge::Written,
+        },
+    );
+    (Arc::new(backend), cold)
+}
+
+/// Twelve 1MB rows, enough to fill the first 10MB segment and start a
+/// second
+fn fill(engine: &mut Engine, ctx: &mut SessionContext) {
+    run(
+        engine,
+        ctx,
+        "CREATE TABLE history (id INTEGER PRIMARY KEY, payload VARCHAR)",
+    );
+    let payload = "x".repeat(1024 * 1024);
+    for id in 1..=12 {
+        run(
+            engine,
+            ctx,
+            &format!(
+                "INSERT INTO history (id, payload) VALUES ({}, '{}')",
+                id, payload
+            ),
+        );
+    }
+}
+
+fn ids(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<i64> {
+    run(engine, ctx, sql)
+        .iter()
+        .map(|row| row["id"].as_i64().unwrap())
+        .collect()
+}
+
+#[test]
+fn cold_segments_are_read_back_transparently() {
+    let temp = TempDir::new().unwrap();
+    let (backend, cold) = tiered(temp.path(), Duration::ZERO);
+    let mut ctx = SessionContext::new();
+    let segments = temp.path().join("tables").join("history").join("segments");
+
+    {
+        let mut engine = Engine::init_with_backend(temp.path(), backend.clone()).unwrap();
+        fill(&mut engine, &mut ctx);
+
+        // Only the first segment is closed; the active one stays hot
+        assert_eq!(engine.relocate_cold_segments().unwrap(), 1);
+        assert!(backend.is_cold(&segments.join("00000001.seg")));
+        assert!(!backend.is_cold(&segments.join("00000002.seg")));
+        assert!(cold.bytes_used() > 10 * 1024 * 1024);
+
+        let all: Vec<i64> = (1..=12).collect();
+        let sql = "SELECT id FROM history ORDER BY id";
+        assert_eq!(ids(&mut engine, &mut ctx, sql), all);
+        let stats = engine.storage_tier_stats().unwrap();
+        assert_eq!(stats.cold_reads, 1);
+        assert!(stats.hot_reads >= 1);
+
+        // The second query is served from the cache
+        assert_eq!(ids(&mut engine, &mut ctx, sql), all);
+        let stats = engine.storage_tier_stats().unwrap();
+        assert_eq!(stats.cold_reads, 1);
+        assert!(stats.cache_hits >= 1);
+
+        // So is time travel into the cold history
+        assert_eq!(
+            ids(
+                &mut engine,
+                &mut ctx,
+                "SELECT id FROM history FOR SYSTEM_TIME AS OF @SEQ:3 ORDER BY id"
+            ),
+            [1, 2, 3]
+        );
+        run(&mut engine, &mut ctx, "DELETE FROM history WHERE id = 1");
+    }
+
+    let mut engine = Engine::open_with_backend(temp.path(), backend.clone()).unwrap();
+    assert!(backend.is_cold(&segments.join("00000001.seg")));
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT COUNT(*) FROM history"),
+        vec![json!({"count(*)": 11})]
+    );
+    assert_eq!(
+        backend.list(&segments).unwrap(),
+        vec![segments.join("00000001.seg"), segments.join("00000002.seg")]
+    );
+}
+
+#[test]
+fn recent_segments_stay_hot() {
+    let temp = TempDir::new().unwrap();
+    let (backend, cold) = tiered(temp.path(), Duration::from_secs(3600));
+    let mut ctx = SessionContext::new();
+
+    let mut engine = Engine::init_with_backend(temp.path(), backend.clone()).unwrap();
+    fill(&mut engine, &mut ctx);
+    assert_eq!(engine.relocate_cold_segments().unwrap(), 0);
+    assert_eq!(cold.bytes_used(), 0);
+
+    assert_eq!(
+        ids(&mut engine, &mut ctx, "SELECT id FROM history").len(),
+        12
+    );
+    let stats = engine.storage_tier_stats().unwrap();
+    assert_eq!((stats.cold_reads, stats.cache_hits), (0, 0));
+}
@@ -0,0 +1,179 @@
+//! `default_transaction_isolation` and `default_transaction_read_only`: a
+//! new session starts from the engine's values (the server's
+//! `--default-isolation` and `--default-transaction-read-only`), `SET`
+//! changes them for that session, and `BEGIN READ ONLY` / `READ WRITE`
+//! override them for one transaction.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::settings;
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+fn show(engine: &mut Engine, ctx: &mut SessionContext, name: &str) -> String {
+    let rows = run(engine, ctx, &format!("SHOW {}", name));
+    let row = rows[0].as_object().unwrap();
+    row.values().next().unwrap().as_str().unwrap().to_string()
+}
+
+fn error(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> String {
+    execute_sql_in_session(engine, sql, ctx)
+        .unwrap_err()
+        .to_string()
+}
+
+/// An engine with a `notes` table, configured the way the server's flags
+/// configure it
+fn setup(temp: &TempDir, isolation: &str, read_only: &str) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        &mut SessionContext::new(),
+        "CREATE TABLE notes (id INT PRIMARY KEY, body VARCHAR)",
+    );
+    for (name, value) in [
+        ("default_transaction_isolation", isolation),
+        ("default_transaction_read_only", read_only),
+    ] {
+        let parameter = settings::lookup(name).unwrap();
+        let value = parameter.parse(value).unwrap();
+        engine.settings().set(parameter, Some(value));
+    }
+    engine
+}
+
+fn ids(engine: &mut Engine, ctx: &mut SessionContext) -> Vec<Value> {
+    run(engine, ctx, "SELECT id FROM notes ORDER BY id")
+        .into_iter()
+        .map(|row| row["id"].clone())
+        .collect()
+}
+
+#[test]
+fn new_sessions_inherit_the_configured_defaults() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp, "serializable", "on");
+    let mut ctx = SessionContext::new();
+
+    assert_eq!(
+        show(&mut engine, &mut ctx, "default_transaction_isolation"),
+        "serializable"
+    );
+    assert_eq!(
+        show(&mut engine, &mut ctx, "default_transaction_read_only"),
+        "on"
+    );
+
+    // Reads work; writes fail with and without an explicit transaction
+    assert!(ids(&mut engine, &mut ctx).is_empty());
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO notes (id, body) VALUES (1, 'a')",
+    );
+    assert!(
+        err.contains("cannot execute INSERT in a read-only transaction"),
+        "{}",
+        err
+    );
+    run(&mut engine, &mut ctx, "BEGIN");
+    let err = error(&mut engine, &mut ctx, "DELETE FROM notes");
+    assert!(
+        err.contains("cannot execute DELETE in a read-only transaction"),
+        "{}",
+        err
+    );
+    run(&mut engine, &mut ctx, "ROLLBACK");
+
+    // BEGIN READ WRITE overrides the default for its transaction only
+    run(&mut engine, &mut ctx, "BEGIN READ WRITE");
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO notes (id, body) VALUES (1, 'a')",
+    );
+    run(&mut engine, &mut ctx, "COMMIT");
+    assert_eq!(ids(&mut engine, &mut ctx), [json!(1)]);
+    let err = error(&mut engine, &mut ctx, "UPDATE notes SET body = 'b'");
+    assert!(err.contains("read-only transaction"), "{}", err);
+}
+
+#[test]
+fn set_changes_the_defaults_for_the_session() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp, "repeatable read", "on");
+    let mut ctx = SessionContext::new();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET default_transaction_read_only = off",
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET default_transaction_isolation = 'READ COMMITTED'",
+    );
+    assert_eq!(
+        show(&mut engine, &mut ctx, "default_transaction_isolation"),
+        "read committed"
+    );
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO notes (id, body) VALUES (1, 'a')",
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "SET default_transaction_isolation = 'snapshot'",
+    );
+    assert!(err.contains("invalid value for parameter"), "{}", err);
+
+    // Another session still starts from the engine's values
+    let mut other = SessionContext::new();
+    assert_eq!(
+        show(&mut engine, &mut other, "default_transaction_isolation"),
+        "repeatable read"
+    );
+    assert!(error(&mut engine, &mut other, "DELETE FROM notes").contains("read-only"));
+    assert_eq!(ids(&mut engine, &mut other), [json!(1)]);
+}
+
+#[test]
+fn begin_modes_override_a_read_write_default() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp, "read committed", "off");
+    let mut ctx = SessionContext::new();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "BEGIN ISOLATION LEVEL SERIALIZABLE, READ ONLY",
+    );
+    let err = error(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO notes (id, body) VALUES (1, 'a')",
+    );
+    assert!(err.contains("read-only transaction"), "{}", err);
+    run(&mut engine, &mut ctx, "ROLLBACK");
+
+    // The next transaction is back to the default
+    run(&mut engine, &mut ctx, "BEGIN");
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO notes (id, body) VALUES (2, 'b')",
+    );
+    run(&mut engine, &mut ctx, "COMMIT");
+    assert_eq!(ids(&mut engine, &mut ctx), [json!(2)]);
+}
@@ -0,0 +1,241 @@
+//! SQL-level triggers: `CREATE FUNCTION ... RETURNS TRIGGER` bodies wired
+//! to tables with `CREATE TRIGGER ... EXECUTE FUNCTION f()`. BEFORE
+//! triggers can rewrite or reject the incoming row; AFTER triggers can
+//! write to other tables inside the originating transaction.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE orders (id VARCHAR, amount INT, status VARCHAR, PRIMARY KEY (id))",
+        "CREATE TABLE audit (id VARCHAR, amount INT, PRIMARY KEY (id))",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn run(
+    engine: &mut Engine,
+    ctx: &mut SessionContext,
+    sql: &str,
+) -> driftdb_core::Result<QueryResult> {
+    execute_sql_in_session(engine, sql, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match run(engine, ctx, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn install_audit_trigger(engine: &mut Engine, ctx: &mut SessionContext) {
+    run(
+        engine,
+        ctx,
+        "CREATE FUNCTION audit_order() RETURNS TRIGGER AS $$
+             INSERT INTO audit (id, amount) VALUES (NEW.id, NEW.amount);
+             RETURN NEW;
+         $$ LANGUAGE plpgsql",
+    )
+    .unwrap();
+    run(
+        engine,
+        ctx,
+        "CREATE TRIGGER orders_audit AFTER INSERT ON orders FOR EACH ROW EXECUTE FUNCTION audit_order()",
+    )
+    .unwrap();
+}
+
+#[test]
+fn after_insert_trigger_appends_audit_record() {
+    let (_t, mut engine, mut ctx) = setup();
+    install_audit_trigger(&mut engine, &mut ctx);
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 40, 'new')",
+    )
+    .unwrap();
+
+    let audit = rows(&mut engine, &mut ctx, "SELECT * FROM audit");
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0]["id"], "o1");
+    assert_eq!(audit[0]["amount"], 40);
+}
+
+#[test]
+fn after_trigger_writes_roll_back_with_the_transaction() {
+    let (_t, mut engine, mut ctx) = setup();
+    install_audit_trigger(&mut engine, &mut ctx);
+
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 40, 'new')",
+    )
+    .unwrap();
+    run(&mut engine, &mut ctx, "ROLLBACK").unwrap();
+
+    assert!(rows(&mut engine, &mut ctx, "SELECT * FROM orders").is_empty());
+    assert!(rows(&mut engine, &mut ctx, "SELECT * FROM audit").is_empty());
+}
+
+#[test]
+fn before_trigger_can_modify_the_row() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE FUNCTION default_status() RETURNS TRIGGER AS $$
+             NEW.status := 'pending';
+             RETURN NEW;
+         $$",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TRIGGER orders_status BEFORE INSERT ON orders FOR EACH ROW WHEN (NEW.status IS NULL) EXECUTE FUNCTION default_status()",
+    )
+    .unwrap();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 10, NULL)",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o2', 10, 'shipped')",
+    )
+    .unwrap();
+
+    let orders = rows(&mut engine, &mut ctx, "SELECT * FROM orders ORDER BY id");
+    assert_eq!(orders[0]["status"], "pending");
+    assert_eq!(orders[1]["status"], "shipped");
+}
+
+#[test]
+fn before_trigger_can_reject_the_row() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE FUNCTION reject_large() RETURNS TRIGGER AS $$
+             RAISE EXCEPTION 'amount exceeds order limit';
+         $$",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TRIGGER orders_check BEFORE INSERT OR UPDATE ON orders FOR EACH ROW WHEN (NEW.amount > 1000) EXECUTE FUNCTION reject_large()",
+    )
+    .unwrap();
+
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 5000, 'new')",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("amount exceeds order limit"),
+        "{}",
+        err
+    );
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o2', 5, 'new')",
+    )
+    .unwrap();
+    assert_eq!(rows(&mut engine, &mut ctx, "SELECT * FROM orders").len(), 1);
+}
+
+#[test]
+fn self_recursive_trigger_is_bounded() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE FUNCTION bump() RETURNS TRIGGER AS $$
+             UPDATE orders SET amount = amount + 1 WHERE id = NEW.id;
+         $$",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TRIGGER orders_bump AFTER UPDATE ON orders FOR EACH ROW EXECUTE FUNCTION bump()",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 0, 'new')",
+    )
+    .unwrap();
+
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE orders SET status = 'paid' WHERE id = 'o1'",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("recursion depth"), "{}", err);
+}
+
+#[test]
+fn trigger_ddl_validates_references() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    // Unknown function
+    assert!(run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TRIGGER t1 AFTER INSERT ON orders EXECUTE FUNCTION missing()",
+    )
+    .is_err());
+
+    install_audit_trigger(&mut engine, &mut ctx);
+
+    // A function can't be dropped while a trigger depends on it
+    assert!(run(&mut engine, &mut ctx, "DROP FUNCTION audit_order()").is_err());
+    run(&mut engine, &mut ctx, "DROP TRIGGER orders_audit ON orders").unwrap();
+    run(&mut engine, &mut ctx, "DROP FUNCTION audit_order()").unwrap();
+    run(&mut engine, &mut ctx, "DROP TRIGGER IF EXISTS orders_audit").unwrap();
+}
+
+#[test]
+fn triggers_and_their_functions_survive_a_restart() {
+    let (temp, mut engine, mut ctx) = setup();
+    install_audit_trigger(&mut engine, &mut ctx);
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO orders (id, amount, status) VALUES ('o1', 40, 'new')",
+    )
+    .unwrap();
+
+    let audit = rows(&mut engine, &mut ctx, "SELECT * FROM audit");
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0]["id"], "o1");
+}
@@ -0,0 +1,187 @@
+//! `transaction_error_behavior`: by default a failed statement aborts its
+//! transaction until ROLLBACK; with `continue` only the failed statement's
+//! writes are rolled back and the transaction carries on.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(
+        &mut engine,
+        "CREATE TABLE t (id VARCHAR, name VARCHAR, PRIMARY KEY (id))",
+        &mut ctx,
+    )
+    .unwrap();
+    (temp, engine, ctx)
+}
+
+fn run(
+    engine: &mut Engine,
+    ctx: &mut SessionContext,
+    sql: &str,
+) -> driftdb_core::Result<QueryResult> {
+    execute_sql_in_session(engine, sql, ctx)
+}
+
+fn select_ids(engine: &mut Engine, ctx: &mut SessionContext) -> Vec<String> {
+    match run(engine, ctx, "SELECT id FROM t").unwrap() {
+        QueryResult::Rows { data } => {
+            let mut ids: Vec<String> = data
+                .iter()
+                .map(|r| r["id"].as_str().unwrap().to_string())
+                .collect();
+            ids.sort();
+            ids
+        }
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn default_behavior_aborts_until_rollback() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('a', 'n')",
+    )
+    .unwrap();
+    assert!(run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('a', 'dup')"
+    )
+    .is_err());
+    assert!(ctx.aborted);
+
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('b', 'n')",
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("current transaction is aborted"), "{}", err);
+
+    run(&mut engine, &mut ctx, "ROLLBACK").unwrap();
+    assert!(!ctx.aborted);
+    assert!(select_ids(&mut engine, &mut ctx).is_empty());
+}
+
+#[test]
+fn continue_rolls_back_only_the_failed_statement() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET transaction_error_behavior = continue",
+    )
+    .unwrap();
+
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('a', 'n')",
+    )
+    .unwrap();
+    // The failing statement's own writes go, 'c' included
+    assert!(run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('c', 'n'), ('a', 'dup')",
+    )
+    .is_err());
+    assert!(!ctx.aborted, "continue mode must not abort the transaction");
+    assert!(run(&mut engine, &mut ctx, "SELECT * FROM missing_table").is_err());
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('b', 'n')",
+    )
+    .unwrap();
+    run(&mut engine, &mut ctx, "COMMIT").unwrap();
+    assert_eq!(select_ids(&mut engine, &mut ctx), vec!["a", "b"]);
+}
+
+#[test]
+fn continue_keeps_user_savepoints() {
+    let (_t, mut engine, mut ctx) = setup();
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET transaction_error_behavior = continue",
+    )
+    .unwrap();
+
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('a', 'n')",
+    )
+    .unwrap();
+    run(&mut engine, &mut ctx, "SAVEPOINT before_b").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('b', 'n')",
+    )
+    .unwrap();
+    assert!(run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO t (id, name) VALUES ('b', 'dup')"
+    )
+    .is_err());
+    run(&mut engine, &mut ctx, "ROLLBACK TO SAVEPOINT before_b").unwrap();
+    run(&mut engine, &mut ctx, "COMMIT").unwrap();
+    assert_eq!(select_ids(&mut engine, &mut ctx), vec!["a"]);
+}
+
+#[test]
+fn setting_is_checked_and_resettable() {
+    let (_t, mut engine, mut ctx) = setup();
+    let show = |engine: &mut Engine, ctx: &mut SessionContext| match run(
+        engine,
+        ctx,
+        "SHOW transaction_error_behavior",
+    )
+    .unwrap()
+    {
+        QueryResult::Rows { data } => data[0]
+            .as_object()
+            .and_then(|row| row.values().next())
+            .and_then(|value| value.as_str())
+            .unwrap()
+            .to_string(),
+        other => panic!("expected Rows, got {:?}", other),
+    };
+
+    assert_eq!(show(&mut engine, &mut ctx), "abort");
+    run(
+        &mut engine,
+        &mut ctx,
+        "SET transaction_error_behavior TO 'continue'",
+    )
+    .unwrap();
+    assert_eq!(show(&mut engine, &mut ctx), "continue");
+    assert!(run(
+        &mut engine,
+        &mut ctx,
+        "SET transaction_error_behavior = skip"
+    )
+    .is_err());
+
+    run(&mut engine, &mut ctx, "RESET transaction_error_behavior").unwrap();
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    assert!(run(&mut engine, &mut ctx, "SELECT * FROM missing_table").is_err());
+    assert!(ctx.aborted);
+}
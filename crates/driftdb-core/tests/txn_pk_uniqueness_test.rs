@@ -261,3 +261,26 @@ fn cross_session_committed_visible_to_new_txn() {
     assert!(dup.is_err(), "session B must see session A's committed row");
     execute_sql_in_session(&mut engine, "ROLLBACK", &mut b).unwrap();
 }
+
+#[test]
+fn same_pk_in_two_tables_in_one_txn() {
+    // Buffered writes are keyed by table as well as PK, so a row in
+    // another table with the same key is neither a duplicate nor a
+    // replacement.
+    let (_t, mut engine, mut ctx) = setup();
+    run(&mut engine, &mut ctx, "CREATE TABLE u (id VARCHAR, name VARCHAR, PRIMARY KEY (id))").unwrap();
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(&mut engine, &mut ctx, "INSERT INTO t (id, name) VALUES ('x', 't-row')").unwrap();
+    run(&mut engine, &mut ctx, "INSERT INTO u (id, name) VALUES ('x', 'u-row')").unwrap();
+    run(&mut engine, &mut ctx, "COMMIT").unwrap();
+
+    for (table, name) in [("t", "t-row"), ("u", "u-row")] {
+        match run(&mut engine, &mut ctx, &format!("SELECT * FROM {}", table)).unwrap() {
+            QueryResult::Rows { data } => {
+                assert_eq!(data.len(), 1);
+                assert_eq!(data[0]["name"], name);
+            }
+            other => panic!("expected Rows, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! `UPDATE ... SET col = <expr>` where the expression reads the row being
+//! updated, and write-write conflicts between concurrent updaters.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE accounts (id INTEGER PRIMARY KEY, owner VARCHAR, balance INTEGER, note VARCHAR)",
+        "INSERT INTO accounts (id, owner, balance, note) VALUES (1, 'ada', 1000, 'main')",
+        "INSERT INTO accounts (id, owner, balance, note) VALUES (2, 'bob', 50, NULL)",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn run(
+    engine: &mut Engine,
+    ctx: &mut SessionContext,
+    sql: &str,
+) -> driftdb_core::Result<QueryResult> {
+    execute_sql_in_session(engine, sql, ctx)
+}
+
+fn account(engine: &mut Engine, ctx: &mut SessionContext, id: i64) -> serde_json::Value {
+    match run(
+        engine,
+        ctx,
+        &format!("SELECT * FROM accounts WHERE id = {}", id),
+    )
+    .unwrap()
+    {
+        QueryResult::Rows { mut data } => data.remove(0),
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn set_expressions_read_the_pre_image() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE accounts SET balance = balance - 100 WHERE id = 1",
+    )
+    .unwrap();
+    assert_eq!(account(&mut engine, &mut ctx, 1)["balance"], json!(900));
+
+    // Every SET sees the row as it was before the UPDATE, not the
+    // values assigned earlier in the same statement
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE accounts SET balance = (balance + 10) * 2, note = note || ':' || owner || ' had ' || balance WHERE id = 1",
+    )
+    .unwrap();
+    let row = account(&mut engine, &mut ctx, 1);
+    assert_eq!(row["balance"], json!(1820));
+    assert_eq!(row["note"], json!("main:ada had 900"));
+
+    // NULL propagates through arithmetic and concatenation
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE accounts SET note = note || 'x', balance = -balance WHERE id = 2",
+    )
+    .unwrap();
+    let row = account(&mut engine, &mut ctx, 2);
+    assert_eq!(row["note"], json!(null));
+    assert_eq!(row["balance"], json!(-50));
+}
+
+#[test]
+fn concurrent_increment_conflicts() {
+    let (_t, mut engine, mut setup_ctx) = setup();
+    let mut a = SessionContext::new();
+    let mut b = SessionContext::new();
+
+    run(&mut engine, &mut a, "BEGIN").unwrap();
+    run(&mut engine, &mut b, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut a,
+        "UPDATE accounts SET balance = balance + 1 WHERE id = 1",
+    )
+    .unwrap();
+
+    // B would compute its increment from the same committed pre-image,
+    // losing A's update; it is refused instead
+    let err = run(
+        &mut engine,
+        &mut b,
+        "UPDATE accounts SET balance = balance + 1 WHERE id = 1",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("concurrent update"),
+        "unexpected error: {}",
+        err
+    );
+
+    // So is an auto-commit update, which A's commit would overwrite
+    assert!(run(
+        &mut engine,
+        &mut setup_ctx,
+        "UPDATE accounts SET balance = balance + 1 WHERE id = 1",
+    )
+    .is_err());
+
+    // Other rows are unaffected by A's pending write
+    run(&mut engine, &mut b, "ROLLBACK").unwrap();
+    run(&mut engine, &mut b, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut b,
+        "UPDATE accounts SET balance = balance + 1 WHERE id = 2",
+    )
+    .unwrap();
+
+    run(&mut engine, &mut a, "COMMIT").unwrap();
+    run(&mut engine, &mut b, "COMMIT").unwrap();
+    assert_eq!(account(&mut engine, &mut setup_ctx, 1)["balance"], json!(1001));
+    assert_eq!(account(&mut engine, &mut setup_ctx, 2)["balance"], json!(51));
+
+    // Once A has committed, a retry starts from A's result
+    run(
+        &mut engine,
+        &mut b,
+        "UPDATE accounts SET balance = balance + 1 WHERE id = 1",
+    )
+    .unwrap();
+    assert_eq!(account(&mut engine, &mut setup_ctx, 1)["balance"], json!(1002));
+}
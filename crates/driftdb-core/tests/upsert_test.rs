@@ -0,0 +1,199 @@
+//! `INSERT ... ON CONFLICT DO NOTHING / DO UPDATE`: conflicting rows are
+//! skipped or merged through a Patch event, new rows insert, and the
+//! statement applies all-or-nothing.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn setup() -> (TempDir, Engine, SessionContext) {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "CREATE TABLE counters (id VARCHAR, hits INT, label VARCHAR, PRIMARY KEY (id))",
+        "INSERT INTO counters (id, hits, label) VALUES ('a', 1, 'first')",
+    ] {
+        execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap();
+    }
+    (temp, engine, ctx)
+}
+
+fn run(
+    engine: &mut Engine,
+    ctx: &mut SessionContext,
+    sql: &str,
+) -> driftdb_core::Result<QueryResult> {
+    execute_sql_in_session(engine, sql, ctx)
+}
+
+fn rows(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<serde_json::Value> {
+    match run(engine, ctx, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+#[test]
+fn do_update_merges_conflicts_and_inserts_new_rows() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let result = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('a', 5, 'again'), ('b', 2, 'second')
+         ON CONFLICT (id) DO UPDATE SET hits = counters.hits + EXCLUDED.hits, label = EXCLUDED.label",
+    )
+    .unwrap();
+    match result {
+        QueryResult::Success { message } => assert_eq!(message, "Inserted 2 row(s)"),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM counters ORDER BY id");
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0]["hits"], 6);
+    assert_eq!(all[0]["label"], "again");
+    assert_eq!(all[1]["hits"], 2);
+}
+
+#[test]
+fn do_update_where_filters_the_merge() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('a', 9, 'ignored')
+         ON CONFLICT (id) DO UPDATE SET label = EXCLUDED.label WHERE counters.hits > 100",
+    )
+    .unwrap();
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM counters");
+    assert_eq!(all[0]["label"], "first");
+}
+
+#[test]
+fn do_nothing_keeps_the_existing_row() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('a', 99, 'dup'), ('c', 3, 'third')
+         ON CONFLICT DO NOTHING",
+    )
+    .unwrap();
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM counters ORDER BY id");
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0]["hits"], 1);
+    assert_eq!(all[1]["id"], "c");
+}
+
+#[test]
+fn statement_is_atomic() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    // The second tuple hits 'a' again, which PostgreSQL rejects; the
+    // insert of 'b' before it must not survive.
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('b', 1, 'x'), ('a', 1, 'y'), ('a', 2, 'z')
+         ON CONFLICT (id) DO UPDATE SET hits = EXCLUDED.hits",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("second time"), "{}", err);
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM counters");
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0]["hits"], 1);
+}
+
+#[test]
+fn upsert_sees_rows_written_earlier_in_the_transaction() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('b', 1, 'new')",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('b', 1, 'new')
+         ON CONFLICT (id) DO UPDATE SET hits = counters.hits + 1",
+    )
+    .unwrap();
+    run(&mut engine, &mut ctx, "COMMIT").unwrap();
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM counters WHERE id = 'b'");
+    assert_eq!(all[0]["hits"], 2);
+}
+
+#[test]
+fn conflict_target_must_be_unique() {
+    let (_t, mut engine, mut ctx) = setup();
+
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('a', 1, 'x') ON CONFLICT (label) DO NOTHING",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("no unique or exclusion constraint"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn unique_column_is_a_conflict_target_but_a_plain_index_is_not() {
+    let (_t, mut engine, mut ctx) = setup();
+    for sql in [
+        "CREATE TABLE users (id VARCHAR PRIMARY KEY, email VARCHAR UNIQUE, name VARCHAR)",
+        "CREATE INDEX ON counters (label)",
+    ] {
+        run(&mut engine, &mut ctx, sql).unwrap();
+    }
+
+    let err = run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO counters (id, hits, label) VALUES ('z', 1, 'first') ON CONFLICT (label) DO NOTHING",
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("no unique or exclusion constraint"),
+        "{}",
+        err
+    );
+
+    // The conflicting row was written earlier in the same transaction
+    run(&mut engine, &mut ctx, "BEGIN").unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO users (id, email, name) VALUES ('u1', 'ada@example.com', 'Ada')",
+    )
+    .unwrap();
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO users (id, email, name) VALUES ('u2', 'ada@example.com', 'Ada L.')
+         ON CONFLICT (email) DO UPDATE SET name = EXCLUDED.name",
+    )
+    .unwrap();
+    run(&mut engine, &mut ctx, "COMMIT").unwrap();
+
+    let all = rows(&mut engine, &mut ctx, "SELECT * FROM users");
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0]["id"], "u1");
+    assert_eq!(all[0]["name"], "Ada L.");
+}
@@ -0,0 +1,142 @@
+//! UUID columns: gen_random_uuid() defaults, literal normalization and
+//! validation, ordering and index lookups.
+
+use serde_json::json;
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    let mut ctx = SessionContext::new();
+    match execute_sql_in_session(engine, sql, &mut ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn run(engine: &mut Engine, sql: &str) {
+    let mut ctx = SessionContext::new();
+    execute_sql_in_session(engine, sql, &mut ctx).unwrap();
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE sessions (id UUID PRIMARY KEY DEFAULT gen_random_uuid(), owner UUID, name VARCHAR)",
+    );
+    engine
+}
+
+#[test]
+fn default_generates_a_fresh_uuid_per_row() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(&mut engine, "INSERT INTO sessions (name) VALUES ('a')");
+    run(&mut engine, "INSERT INTO sessions (name) VALUES ('b')");
+
+    let all = rows(&mut engine, "SELECT * FROM sessions");
+    assert_eq!(all.len(), 2);
+    let ids: Vec<&str> = all.iter().map(|r| r["id"].as_str().unwrap()).collect();
+    assert_ne!(ids[0], ids[1]);
+    for id in &ids {
+        assert_eq!(id.len(), 36);
+        assert_eq!(*id, id.to_lowercase());
+    }
+
+    // The default survives a reopen
+    drop(engine);
+    let mut engine = Engine::open(temp.path()).unwrap();
+    run(&mut engine, "INSERT INTO sessions (name) VALUES ('c')");
+    assert_eq!(rows(&mut engine, "SELECT * FROM sessions").len(), 3);
+}
+
+#[test]
+fn literals_are_normalized_and_validated() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    run(
+        &mut engine,
+        "INSERT INTO sessions (id, owner) VALUES ('A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11', '{b0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11}')",
+    );
+
+    let row = rows(
+        &mut engine,
+        "SELECT * FROM sessions WHERE id = 'a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11'",
+    );
+    assert_eq!(row.len(), 1);
+    assert_eq!(
+        row[0]["owner"],
+        json!("b0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")
+    );
+
+    let mut ctx = SessionContext::new();
+    for sql in [
+        "INSERT INTO sessions (id) VALUES ('not-a-uuid')",
+        "INSERT INTO sessions (owner) VALUES ('b0eebc99-9c0b-4ef8-bb6d')",
+        "UPDATE sessions SET owner = 'zzz' WHERE name IS NULL",
+    ] {
+        let err = execute_sql_in_session(&mut engine, sql, &mut ctx).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid input syntax for type uuid"),
+            "{}: {}",
+            sql,
+            err
+        );
+    }
+    assert_eq!(rows(&mut engine, "SELECT * FROM sessions").len(), 1);
+}
+
+#[test]
+fn uuids_order_and_index() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    for (id, owner) in [
+        (
+            "c0000000-0000-4000-8000-000000000000",
+            "11111111-1111-4111-8111-111111111111",
+        ),
+        (
+            "0a000000-0000-4000-8000-000000000000",
+            "22222222-2222-4222-8222-222222222222",
+        ),
+        (
+            "9f000000-0000-4000-8000-000000000000",
+            "11111111-1111-4111-8111-111111111111",
+        ),
+    ] {
+        run(
+            &mut engine,
+            &format!(
+                "INSERT INTO sessions (id, owner) VALUES ('{}', '{}')",
+                id, owner
+            ),
+        );
+    }
+
+    let ordered: Vec<String> = rows(&mut engine, "SELECT id FROM sessions ORDER BY id")
+        .iter()
+        .map(|r| r["id"].as_str().unwrap()[..2].to_string())
+        .collect();
+    assert_eq!(ordered, vec!["0a", "9f", "c0"]);
+
+    run(&mut engine, "CREATE INDEX idx_owner ON sessions (owner)");
+    let keys = engine
+        .lookup_by_index(
+            "sessions",
+            "owner",
+            &json!("11111111-1111-4111-8111-111111111111"),
+        )
+        .unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(
+        rows(
+            &mut engine,
+            "SELECT * FROM sessions WHERE owner = '11111111-1111-4111-8111-111111111111'"
+        )
+        .len(),
+        2
+    );
+}
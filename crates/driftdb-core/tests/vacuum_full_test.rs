@@ -0,0 +1,175 @@
+//! `VACUUM FULL table`: the table's segments are rewritten as one segment
+//! of its current rows and its snapshots are dropped, so the files shrink
+//! to what the live rows take.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::schema::{ColumnDef, Schema};
+use driftdb_core::sql_bridge::{execute_sql_in_session, SessionContext};
+use driftdb_core::storage::TableStorage;
+use driftdb_core::{Engine, Event, QueryResult};
+
+fn run(engine: &mut Engine, ctx: &mut SessionContext, sql: &str) -> Vec<Value> {
+    match execute_sql_in_session(engine, sql, ctx).unwrap() {
+        QueryResult::Rows { data } => data,
+        _ => vec![],
+    }
+}
+
+/// Bytes of every file under `dir`
+fn disk_usage(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            let metadata = entry.metadata().unwrap();
+            if metadata.is_dir() {
+                disk_usage(&entry.path())
+            } else {
+                metadata.len()
+            }
+        })
+        .sum()
+}
+
+#[test]
+fn deleting_most_rows_then_vacuuming_shrinks_the_files() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = Engine::init(temp.path()).unwrap();
+    let mut ctx = SessionContext::new();
+    run(
+        &mut engine,
+        &mut ctx,
+        "CREATE TABLE docs (id INT PRIMARY KEY, body VARCHAR)",
+    );
+    let body = "x".repeat(200);
+    for id in 0..500 {
+        run(
+            &mut engine,
+            &mut ctx,
+            &format!("INSERT INTO docs (id, body) VALUES ({}, '{}')", id, body),
+        );
+    }
+    run(&mut engine, &mut ctx, "CHECKPOINT TABLE docs");
+    // 80% of the rows go; some of the rest are rewritten
+    run(&mut engine, &mut ctx, "DELETE FROM docs WHERE id >= 100");
+    run(
+        &mut engine,
+        &mut ctx,
+        "UPDATE docs SET body = 'short' WHERE id < 10",
+    );
+
+    let table_dir = temp.path().join("tables").join("docs");
+    let before = disk_usage(&table_dir);
+    let report = run(&mut engine, &mut ctx, "VACUUM FULL docs");
+    let after = disk_usage(&table_dir);
+
+    assert!(
+        after * 4 < before,
+        "{} bytes before, {} after",
+        before,
+        after
+    );
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0]["table"], json!("docs"));
+    assert_eq!(report[0]["rows"], json!(100));
+    let reclaimed = report[0]["bytes_reclaimed"].as_u64().unwrap();
+    assert!(reclaimed * 10 > (before - after) * 9, "{:?}", report);
+    assert_eq!(
+        std::fs::read_dir(table_dir.join("snapshots"))
+            .unwrap()
+            .count(),
+        0
+    );
+
+    // The rows are unchanged and the table takes writes as before
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT COUNT(*) AS n FROM docs"),
+        vec![json!({"n": 100})]
+    );
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT body FROM docs WHERE id = 3"),
+        vec![json!({"body": "short"})]
+    );
+    assert!(run(&mut engine, &mut ctx, "SELECT id FROM docs WHERE id = 200").is_empty());
+    run(
+        &mut engine,
+        &mut ctx,
+        "INSERT INTO docs (id, body) VALUES (200, 'back')",
+    );
+    run(&mut engine, &mut ctx, "DELETE FROM docs WHERE id = 50");
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    assert_eq!(
+        run(&mut engine, &mut ctx, "SELECT COUNT(*) AS n FROM docs"),
+        vec![json!({"n": 100})]
+    );
+    assert_eq!(
+        run(
+            &mut engine,
+            &mut ctx,
+            "SELECT body FROM docs WHERE id = 200"
+        ),
+        vec![json!({"body": "back"})]
+    );
+
+    let err = execute_sql_in_session(&mut engine, "VACUUM FULL missing", &mut ctx).unwrap_err();
+    assert!(err.to_string().contains("missing"), "{}", err);
+}
+
+#[test]
+fn reads_during_a_vacuum_see_the_same_rows() {
+    let temp = TempDir::new().unwrap();
+    let schema = Schema::new(
+        "items".to_string(),
+        "id".to_string(),
+        vec![ColumnDef {
+            name: "id".to_string(),
+            col_type: "INT".to_string(),
+            index: false,
+        }],
+    );
+    let storage = Arc::new(TableStorage::create(temp.path(), schema, None).unwrap());
+    for id in 0..2000u64 {
+        storage
+            .append_event(Event::new_insert(
+                "items".to_string(),
+                json!(id),
+                json!({"id": id}),
+            ))
+            .unwrap();
+    }
+    for id in 500..2000u64 {
+        storage
+            .append_event(Event::new_soft_delete("items".to_string(), json!(id)))
+            .unwrap();
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let storage = storage.clone();
+        let done = done.clone();
+        std::thread::spawn(move || {
+            let mut reads = 0;
+            while !done.load(Ordering::Acquire) || reads == 0 {
+                assert_eq!(storage.reconstruct_state_at(None).unwrap().len(), 500);
+                reads += 1;
+            }
+        })
+    };
+    for _ in 0..3 {
+        let stats = storage.vacuum_full().unwrap();
+        assert_eq!(stats.rows, 500);
+    }
+    done.store(true, Ordering::Release);
+    reader.join().unwrap();
+
+    assert_eq!(storage.segment_files().unwrap().len(), 1);
+    assert_eq!(storage.row_count().unwrap(), 500);
+}
@@ -0,0 +1,183 @@
+//! Verifying declared constraints against current data, including rows
+//! that reached storage without going through SQL's checks.
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+use driftdb_core::events::Event;
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::verify::ConstraintKind;
+use driftdb_core::Engine;
+
+fn run(engine: &mut Engine, sql: &str) {
+    execute_sql(engine, sql).unwrap();
+}
+
+/// A row written straight to storage, the way a bulk import would
+fn import(engine: &mut Engine, table: &str, key: Value, row: Value) {
+    engine
+        .apply_event(Event::new_insert(table.to_string(), key, row))
+        .unwrap();
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    run(
+        &mut engine,
+        "CREATE TABLE customers (id INT, name VARCHAR, PRIMARY KEY (id))",
+    );
+    run(
+        &mut engine,
+        "CREATE TABLE orders (id INT, customer_id INT, amount INT CHECK (amount > 0), \
+         status VARCHAR, PRIMARY KEY (id), \
+         FOREIGN KEY (customer_id) REFERENCES customers(id), \
+         CONSTRAINT known_status CHECK (status IN ('open', 'shipped')))",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO customers (id, name) VALUES (1, 'Ada')",
+    );
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (1, 1, 10, 'open')",
+    );
+    engine
+}
+
+#[test]
+fn clean_data_has_no_violations() {
+    let temp = TempDir::new().unwrap();
+    let engine = setup(&temp);
+
+    let report = engine.verify_constraints("orders").unwrap();
+    assert!(report.is_clean(), "{:#?}", report.violations);
+    // Primary key, one foreign key, two checks
+    assert_eq!(report.constraints_checked, 4);
+    assert_eq!(report.lines()[1], "No violations found");
+
+    let all = engine.verify_all_constraints().unwrap();
+    assert_eq!(all.tables_checked, 2);
+    assert_eq!(all.constraints_checked, 5);
+}
+
+#[test]
+fn check_constraints_are_enforced_on_write() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let err = execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (2, 1, -5, 'open')",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("orders_amount_check"), "{}", err);
+    let err = execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (2, 1, 10 - 15, 'open')",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("orders_amount_check"), "{}", err);
+
+    let err = execute_sql(
+        &mut engine,
+        "UPDATE orders SET status = 'lost' WHERE id = 1",
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("known_status"), "{}", err);
+
+    // A NULL makes the check unknown, which passes
+    run(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (3, 1, NULL, 'open')",
+    );
+}
+
+#[test]
+fn imported_rows_are_reported_by_key() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    // No such customer
+    import(
+        &mut engine,
+        "orders",
+        json!(2),
+        json!({"id": 2, "customer_id": 99, "amount": 5, "status": "open"}),
+    );
+    // Negative amount and an unknown status
+    import(
+        &mut engine,
+        "orders",
+        json!(3),
+        json!({"id": 3, "customer_id": 1, "amount": -1, "status": "lost"}),
+    );
+    // Same id as order 1, stored under a different key
+    import(
+        &mut engine,
+        "orders",
+        json!("1"),
+        json!({"id": 1, "customer_id": 1, "amount": 7, "status": "open"}),
+    );
+
+    let report = engine.verify_constraints("orders").unwrap();
+    let found: Vec<(ConstraintKind, &str, &Value)> = report
+        .violations
+        .iter()
+        .map(|v| (v.kind, v.constraint.as_str(), &v.key))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (ConstraintKind::PrimaryKey, "orders_pkey", &json!(1)),
+            (ConstraintKind::PrimaryKey, "orders_pkey", &json!(1)),
+            (
+                ConstraintKind::ForeignKey,
+                "orders_customer_id_fkey",
+                &json!(2)
+            ),
+            (ConstraintKind::Check, "orders_amount_check", &json!(3)),
+            (ConstraintKind::Check, "known_status", &json!(3)),
+        ]
+    );
+    assert!(report.violations[2]
+        .detail
+        .contains("customer_id = 99 has no match in customers.id"));
+    assert_eq!(report.violations[3].row["amount"], json!(-1));
+    assert!(report
+        .lines()
+        .iter()
+        .any(|line| line
+            == "orders: known_status: key 3: fails CHECK (status IN ('open', 'shipped'))"));
+}
+
+#[test]
+fn constraints_survive_reopen() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+    import(
+        &mut engine,
+        "orders",
+        json!(2),
+        json!({"id": 2, "customer_id": 42, "amount": 0, "status": "open"}),
+    );
+    drop(engine);
+
+    let mut engine = Engine::open(temp.path()).unwrap();
+    let report = engine.verify_all_constraints().unwrap();
+    let kinds: Vec<ConstraintKind> = report.violations.iter().map(|v| v.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![ConstraintKind::ForeignKey, ConstraintKind::Check]
+    );
+
+    // The reopened engine still enforces both on write
+    assert!(execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (4, 42, 1, 'open')",
+    )
+    .is_err());
+    assert!(execute_sql(
+        &mut engine,
+        "INSERT INTO orders (id, customer_id, amount, status) VALUES (4, 1, 0, 'open')",
+    )
+    .is_err());
+}
@@ -0,0 +1,153 @@
+//! BETWEEN, IN lists and IS [NOT] NULL in WHERE, alone and mixed with
+//! conditions only row-level evaluation handles, with SQL's NULL rules:
+//! a NULL is neither in nor between anything, and `NOT IN` a list holding
+//! a NULL matches no row.
+
+use tempfile::TempDir;
+
+use driftdb_core::sql_bridge::execute_sql;
+use driftdb_core::{Engine, QueryResult};
+
+fn rows(engine: &mut Engine, sql: &str) -> Vec<serde_json::Value> {
+    match execute_sql(engine, sql).unwrap() {
+        QueryResult::Rows { data } => data,
+        other => panic!("expected Rows, got {:?}", other),
+    }
+}
+
+fn ids(engine: &mut Engine, filter: &str) -> Vec<i64> {
+    rows(
+        engine,
+        &format!("SELECT id FROM items WHERE {} ORDER BY id", filter),
+    )
+    .iter()
+    .map(|row| row["id"].as_i64().unwrap())
+    .collect()
+}
+
+fn setup(temp: &TempDir) -> Engine {
+    let mut engine = Engine::init(temp.path()).unwrap();
+    for sql in [
+        "CREATE TABLE items (id INTEGER PRIMARY KEY, category VARCHAR, price INTEGER, note VARCHAR)",
+        "INSERT INTO items (id, category, price, note) VALUES (1, 'a', 10, 'x')",
+        "INSERT INTO items (id, category, price, note) VALUES (2, 'b', 20, NULL)",
+        "INSERT INTO items (id, category, price, note) VALUES (3, 'c', 30, 'y')",
+        "INSERT INTO items (id, category, price, note) VALUES (4, NULL, 40, NULL)",
+        "INSERT INTO items (id, category, price, note) VALUES (5, 'a', 50, 'z')",
+    ] {
+        execute_sql(&mut engine, sql).unwrap();
+    }
+    engine
+}
+
+#[test]
+fn between() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "price BETWEEN 20 AND 40"), vec![2, 3, 4]);
+    assert_eq!(ids(&mut engine, "price NOT BETWEEN 20 AND 40"), vec![1, 5]);
+    assert_eq!(
+        ids(&mut engine, "price BETWEEN 20 AND 40 AND note IS NOT NULL"),
+        vec![3]
+    );
+    // A NULL is never between two values
+    assert_eq!(
+        ids(&mut engine, "category BETWEEN 'a' AND 'z'"),
+        vec![1, 2, 3, 5]
+    );
+    assert!(ids(&mut engine, "category NOT BETWEEN 'a' AND 'z'").is_empty());
+}
+
+#[test]
+fn in_lists() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "id IN (1, 3, 5)"), vec![1, 3, 5]);
+    assert_eq!(ids(&mut engine, "category IN ('a', 'c')"), vec![1, 3, 5]);
+    assert_eq!(ids(&mut engine, "category IN ('a', NULL)"), vec![1, 5]);
+    assert_eq!(
+        ids(&mut engine, "price > 15 AND id IN (1, 2, 3)"),
+        vec![2, 3]
+    );
+
+    // The NULL category is not "not in" the list either
+    assert_eq!(ids(&mut engine, "category NOT IN ('a', 'b')"), vec![3]);
+    assert_eq!(ids(&mut engine, "id NOT IN (2, 4)"), vec![1, 3, 5]);
+}
+
+#[test]
+fn not_in_with_a_null_in_the_list_matches_nothing() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert!(ids(&mut engine, "category NOT IN ('a', NULL)").is_empty());
+    assert!(ids(&mut engine, "id NOT IN (1, NULL) AND price > 0").is_empty());
+    // Also when the list is evaluated row by row
+    assert!(ids(&mut engine, "id NOT IN (1, NULL) OR price < 0").is_empty());
+    assert_eq!(
+        ids(&mut engine, "id NOT IN (1, NULL) OR price = 10"),
+        vec![1]
+    );
+
+    // And for writes
+    execute_sql(&mut engine, "DELETE FROM items WHERE id NOT IN (1, NULL)").unwrap();
+    assert_eq!(rows(&mut engine, "SELECT id FROM items").len(), 5);
+    execute_sql(
+        &mut engine,
+        "DELETE FROM items WHERE id NOT IN (1, 2, 3, 4)",
+    )
+    .unwrap();
+    assert_eq!(ids(&mut engine, "price > 0"), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn is_null_and_is_not_null() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    assert_eq!(ids(&mut engine, "note IS NULL"), vec![2, 4]);
+    assert_eq!(ids(&mut engine, "note IS NOT NULL"), vec![1, 3, 5]);
+    assert_eq!(
+        ids(&mut engine, "note IS NULL AND category IS NOT NULL"),
+        vec![2]
+    );
+    // Conjuncts the engine matches narrow the rows an OR is evaluated on
+    assert_eq!(
+        ids(
+            &mut engine,
+            "note IS NOT NULL AND (category = 'c' OR price = 50)"
+        ),
+        vec![3, 5]
+    );
+}
+
+#[test]
+fn double_quoted_text_still_compares_as_a_string() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    // `driftdb select -w category="a"` passes the filter through as written
+    let data = rows(&mut engine, r#"SELECT id FROM items WHERE category = "a""#);
+    let mut found: Vec<i64> = data.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+    found.sort();
+    assert_eq!(found, vec![1, 5]);
+}
+
+#[test]
+fn explain_shows_in_lists() {
+    let temp = TempDir::new().unwrap();
+    let mut engine = setup(&temp);
+
+    let plan = rows(
+        &mut engine,
+        "EXPLAIN SELECT id FROM items WHERE category IN ('a', 'c') AND price BETWEEN 1 AND 9",
+    )
+    .iter()
+    .filter_map(|row| row["QUERY PLAN"].as_str().map(str::to_string))
+    .collect::<Vec<_>>()
+    .join("\n");
+    assert!(plan.contains("category IN ('a', 'c')"), "{}", plan);
+    assert!(plan.contains("price >= 1"), "{}", plan);
+}
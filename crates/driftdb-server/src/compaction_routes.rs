@@ -0,0 +1,85 @@
+//! HTTP routes for compaction progress
+//!
+//! Served from the engine's compaction tracker rather than the engine, so
+//! they answer while a compaction holds the engine's write lock.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use driftdb_core::CompactionTracker;
+use serde_json::{json, Value};
+
+/// Create the compaction progress router
+pub fn create_router(tracker: Arc<CompactionTracker>) -> Router {
+    Router::new()
+        .route("/api/compaction/progress", get(list_progress))
+        .route("/api/compaction/progress/:table", get(table_progress))
+        .with_state(tracker)
+}
+
+/// GET /api/compaction/progress - Running and last finished compactions
+async fn list_progress(State(tracker): State<Arc<CompactionTracker>>) -> Json<Value> {
+    let compactions: Vec<Value> = tracker.all().iter().map(|p| p.to_json()).collect();
+    Json(json!({ "compactions": compactions }))
+}
+
+/// GET /api/compaction/progress/:table - One table's latest compaction
+async fn table_progress(
+    State(tracker): State<Arc<CompactionTracker>>,
+    Path(table): Path<String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    tracker
+        .get(&table)
+        .map(|progress| Json(progress.to_json()))
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": format!("No compaction recorded for table '{}'", table) })),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use driftdb_core::{Engine, Query};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_reports_tracked_compactions() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = Engine::init(temp.path()).unwrap();
+        engine
+            .execute_query(Query::CreateTable {
+                name: "t".to_string(),
+                primary_key: "id".to_string(),
+                indexed_columns: vec![],
+            })
+            .unwrap();
+        let tracker = engine.compaction_tracker();
+
+        let Json(list) = list_progress(State(tracker.clone())).await;
+        assert_eq!(list["compactions"], json!([]));
+
+        // Unknown tables aren't tracked; a failure on a real one is
+        assert!(engine.compact_table("missing").is_err());
+        assert!(engine.compact_table("t").is_err());
+        let Json(list) = list_progress(State(tracker.clone())).await;
+        assert_eq!(list["compactions"].as_array().unwrap().len(), 1);
+
+        let Json(progress) = table_progress(State(tracker.clone()), Path("t".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(progress["status"], "failed");
+        let (status, _) = table_progress(State(tracker), Path("missing".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,177 @@
+//! Connection draining for graceful shutdown
+//!
+//! Shutdown moves the server through three phases. While `Running`,
+//! sessions behave normally. On `Draining` the listener stops accepting,
+//! readiness checks fail so load balancers move traffic away, idle
+//! sessions are told the server is going down and closed, and sessions
+//! inside a transaction keep going until it ends. If the grace period
+//! runs out the phase becomes `Closing`: the remaining sessions roll back
+//! and disconnect.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+/// How long sessions get to roll back and disconnect once the grace
+/// period has expired.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrainPhase {
+    Running,
+    Draining,
+    Closing,
+}
+
+pub struct ConnectionDrain {
+    phase: watch::Sender<DrainPhase>,
+    open_sessions: watch::Sender<usize>,
+}
+
+impl ConnectionDrain {
+    pub fn new() -> Self {
+        Self {
+            phase: watch::Sender::new(DrainPhase::Running),
+            open_sessions: watch::Sender::new(0),
+        }
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<DrainPhase> {
+        self.phase.subscribe()
+    }
+
+    pub fn phase(&self) -> DrainPhase {
+        *self.phase.borrow()
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.phase() != DrainPhase::Running
+    }
+
+    pub fn open_sessions(&self) -> usize {
+        *self.open_sessions.borrow()
+    }
+
+    /// Count a session as open until the returned guard is dropped.
+    pub fn session_opened(self: &Arc<Self>) -> SessionGuard {
+        self.open_sessions.send_modify(|n| *n += 1);
+        SessionGuard {
+            drain: self.clone(),
+        }
+    }
+
+    /// Drain all sessions, waiting up to `grace` for open transactions to
+    /// finish. Returns `false` if sessions had to be forced closed.
+    pub async fn drain(&self, grace: Duration) -> bool {
+        info!(
+            "Draining {} session(s), grace period {:?}",
+            self.open_sessions(),
+            grace
+        );
+        self.phase.send_replace(DrainPhase::Draining);
+
+        if tokio::time::timeout(grace, self.wait_for_sessions())
+            .await
+            .is_ok()
+        {
+            info!("All sessions drained");
+            return true;
+        }
+
+        warn!(
+            "Grace period expired with {} session(s) still open, rolling them back",
+            self.open_sessions()
+        );
+        self.phase.send_replace(DrainPhase::Closing);
+        if tokio::time::timeout(CLOSE_TIMEOUT, self.wait_for_sessions())
+            .await
+            .is_err()
+        {
+            warn!("{} session(s) did not close in time", self.open_sessions());
+        }
+        false
+    }
+
+    async fn wait_for_sessions(&self) {
+        let mut open = self.open_sessions.subscribe();
+        // The sender lives in `self`, so the channel can't close here.
+        let _ = open.wait_for(|n| *n == 0).await;
+    }
+}
+
+impl Default for ConnectionDrain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SessionGuard {
+    drain: Arc<ConnectionDrain>,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.drain.open_sessions.send_modify(|n| *n -= 1);
+    }
+}
+
+/// Resolve once the drain phase requires a session to close: as soon as
+/// draining starts when it is idle, and only at `Closing` while it has a
+/// transaction open.
+pub async fn close_requested(
+    phase: &mut watch::Receiver<DrainPhase>,
+    in_transaction: bool,
+) -> DrainPhase {
+    match phase
+        .wait_for(|phase| match phase {
+            DrainPhase::Running => false,
+            DrainPhase::Draining => !in_transaction,
+            DrainPhase::Closing => true,
+        })
+        .await
+    {
+        Ok(phase) => *phase,
+        Err(_) => DrainPhase::Closing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn drain_waits_for_sessions_to_close() {
+        let drain = Arc::new(ConnectionDrain::new());
+        let guard = drain.session_opened();
+        let mut phase = drain.subscribe();
+
+        let session = tokio::spawn(async move {
+            // Idle session: closes as soon as draining starts.
+            close_requested(&mut phase, false).await;
+            drop(guard);
+        });
+
+        assert!(drain.drain(Duration::from_secs(5)).await);
+        session.await.unwrap();
+        assert_eq!(drain.open_sessions(), 0);
+    }
+
+    #[tokio::test]
+    async fn open_transaction_is_closed_after_the_grace_period() {
+        let drain = Arc::new(ConnectionDrain::new());
+        let guard = drain.session_opened();
+        let mut phase = drain.subscribe();
+
+        let session = tokio::spawn(async move {
+            let reached = close_requested(&mut phase, true).await;
+            drop(guard);
+            reached
+        });
+
+        assert!(!drain.drain(Duration::from_millis(50)).await);
+        assert_eq!(session.await.unwrap(), DrainPhase::Closing);
+        assert_eq!(drain.open_sessions(), 0);
+    }
+}
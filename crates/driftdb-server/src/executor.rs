@@ -3,7 +3,13 @@
 //! Executes SQL queries directly against the DriftDB engine
 
 use anyhow::{anyhow, Result};
-use driftdb_core::{Engine, EngineGuard};
+use driftdb_core::row_level_security::RowSecurity;
+use driftdb_core::{CompactionTracker, Engine, EngineGuard};
+
+use crate::protocol::DataType;
+use crate::result_cache::{read_relations, ResultCache};
+use crate::security::statement_audit::statement_tables;
+use crate::statement_cache::StatementCache;
 use parking_lot::{Mutex as ParkingMutex, RwLock as SyncRwLock};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -46,6 +52,21 @@ pub enum QueryResult {
 }
 
 
+/// Outcome of looking a read up in the result cache
+enum CachedRead {
+    Hit(QueryResult),
+    /// Not cached yet; store the result as described once it's run
+    Miss(CacheableRead),
+    Uncacheable,
+}
+
+/// Where a read's result goes in the result cache
+struct CacheableRead {
+    key: String,
+    tables: std::collections::BTreeMap<String, u64>,
+    catalog_version: u64,
+}
+
 /// Prepared statement storage
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -109,6 +130,12 @@ pub struct QueryExecutor<'a> {
     /// duration of execution, which matches PG's serial-per-connection
     /// statement model.
     session: Arc<ParkingMutex<driftdb_core::sql_bridge::SessionContext>>,
+    /// Parsed statements shared across sessions, when the server has one
+    statement_cache: Option<Arc<StatementCache>>,
+    /// Query results shared across sessions, when the server has one
+    result_cache: Option<Arc<ResultCache>>,
+    /// Compaction progress, readable while a compaction holds the engine
+    compaction_tracker: Option<Arc<CompactionTracker>>,
 }
 
 #[allow(dead_code)]
@@ -210,7 +237,11 @@ impl<'a> QueryExecutor<'a> {
 
         let dialect = GenericDialect {};
         let ast = Parser::parse_sql(&dialect, sql).map_err(|e| anyhow!("Parse error: {}", e))?;
+        Self::extract_table_from_statements(&ast)
+    }
 
+    /// Extract the first FROM table of a parsed SELECT
+    fn extract_table_from_statements(ast: &[sqlparser::ast::Statement]) -> Result<String> {
         if let Some(sqlparser::ast::Statement::Query(query)) = ast.first() {
             if let sqlparser::ast::SetExpr::Select(select) = query.body.as_ref() {
                 if let Some(table_with_joins) = select.from.first() {
@@ -238,6 +269,9 @@ impl<'a> QueryExecutor<'a> {
             session: Arc::new(ParkingMutex::new(
                 driftdb_core::sql_bridge::SessionContext::new(),
             )),
+            statement_cache: None,
+            result_cache: None,
+            compaction_tracker: None,
         }
     }
 
@@ -253,6 +287,9 @@ impl<'a> QueryExecutor<'a> {
             session: Arc::new(ParkingMutex::new(
                 driftdb_core::sql_bridge::SessionContext::new(),
             )),
+            statement_cache: None,
+            result_cache: None,
+            compaction_tracker: None,
         }
     }
 
@@ -271,9 +308,86 @@ impl<'a> QueryExecutor<'a> {
             session: Arc::new(ParkingMutex::new(
                 driftdb_core::sql_bridge::SessionContext::new(),
             )),
+            statement_cache: None,
+            result_cache: None,
+            compaction_tracker: None,
+        }
+    }
+
+    /// Take parsed SELECT and DML statements from `cache` instead of
+    /// parsing them on every execution
+    pub fn with_statement_cache(mut self, cache: Arc<StatementCache>) -> Self {
+        self.statement_cache = Some(cache);
+        self
+    }
+
+    /// Answer repeated reads from `cache` while the tables they read
+    /// haven't changed
+    pub fn with_result_cache(mut self, cache: Arc<ResultCache>) -> Self {
+        self.result_cache = Some(cache);
+        self
+    }
+
+    /// Check the session's inserted rows against `row_security`'s
+    /// INSERT policies
+    pub fn with_row_security(self, row_security: RowSecurity) -> Self {
+        self.session.lock().row_security = Some(row_security);
+        self
+    }
+
+    /// Run statements in `session`, so transaction state and runtime
+    /// parameters carry over from the connection's earlier executors
+    pub fn with_session(
+        mut self,
+        session: Arc<ParkingMutex<driftdb_core::sql_bridge::SessionContext>>,
+    ) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// The session's `statement_timeout`, if it has one
+    pub fn statement_timeout(&self) -> Option<std::time::Duration> {
+        let engine = self.engine_read().ok()?;
+        self.session.lock().statement_timeout(&engine)
+    }
+
+    /// With `read_your_writes` on, wait until the engine has applied the
+    /// session's own writes, up to `read_your_writes_timeout`. The engine
+    /// lock is released between checks so replication can catch up; if it
+    /// doesn't in time the statement fails in sql_bridge.
+    async fn wait_for_session_writes(&self) {
+        let mut deadline = None;
+        loop {
+            // Scoped so neither lock is held across the sleep
+            let behind = {
+                let Ok(engine) = self.engine_read() else {
+                    return;
+                };
+                let session = self.session.lock();
+                if !session.read_your_writes(&engine) {
+                    return;
+                }
+                if session.unapplied_write(&engine).is_none() {
+                    return;
+                }
+                *deadline.get_or_insert_with(|| {
+                    std::time::Instant::now() + session.read_your_writes_timeout(&engine)
+                })
+            };
+            if std::time::Instant::now() >= behind {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
         }
     }
 
+    /// Answer `SHOW COMPACTION PROGRESS` from `tracker`, without waiting
+    /// for the engine lock a running compaction holds
+    pub fn with_compaction_tracker(mut self, tracker: Arc<CompactionTracker>) -> Self {
+        self.compaction_tracker = Some(tracker);
+        self
+    }
+
     /// Set the session ID for this executor
     pub fn set_session_id(&mut self, session_id: String) {
         self.session_id = session_id;
@@ -374,6 +488,16 @@ impl<'a> QueryExecutor<'a> {
         }
 
         // Use SQL bridge for real SQL execution
+        self.wait_for_session_writes().await;
+
+        // Fold identifiers per the session's `identifier_case` up front, so
+        // the statement and result caches key on the names that resolve
+        let folded = {
+            let engine = self.engine_read()?;
+            let case = self.session.lock().identifier_case(&engine);
+            driftdb_core::identifier_case::fold_identifiers(sql, case).into_owned()
+        };
+        let sql = folded.as_str();
 
         // First check if this is a transaction command or legacy command
         let lower = sql.to_lowercase().trim().to_string();
@@ -392,9 +516,33 @@ impl<'a> QueryExecutor<'a> {
             return self.execute_savepoint(sql).await;
         }
 
-        // SHOW and SET still go through the local legacy handler — they're
-        // PostgreSQL-protocol housekeeping (`SHOW TABLES`, `SET search_path`)
-        // that sql_bridge doesn't aim to provide.
+        // Runtime parameters (`work_mem`, `search_path`,
+        // `synchronous_commit`, ...) are session state sql_bridge keeps in
+        // the session. Run as text: the bridge handles these before parsing.
+        if lower.starts_with("set schema ") || is_settings_command(&lower) {
+            let mut engine = self.engine_write()?;
+            let result = self
+                .execute_in_session(&mut engine, sql, None)
+                .map_err(|e| anyhow!("SQL execution failed: {}", e))?;
+            drop(engine);
+            return self.convert_sql_result(result, None);
+        }
+        if lower.trim_end_matches(';').trim_end() == "show compaction progress" {
+            if let Some(tracker) = &self.compaction_tracker {
+                let data = tracker.all().iter().map(|p| p.to_json()).collect();
+                return self
+                    .convert_sql_result(driftdb_core::query::QueryResult::Rows { data }, None);
+            }
+            return self.execute_dml_via_bridge(sql).await;
+        }
+        // Materialized view staleness comes from the engine's view catalog
+        if lower.starts_with("show materialized views") {
+            return self.execute_dml_via_bridge(sql).await;
+        }
+        // Other SHOW and SET still go through the local legacy handler —
+        // they're PostgreSQL-protocol housekeeping (`SHOW TABLES`, client
+        // GUCs the settings registry doesn't know) that sql_bridge doesn't
+        // aim to provide.
         if lower.starts_with("show ") || lower.starts_with("set ") {
             return self.execute_legacy(sql).await;
         }
@@ -445,10 +593,15 @@ impl<'a> QueryExecutor<'a> {
             None
         };
 
-        let result = driftdb_core::sql_bridge::execute_sql(&mut engine, sql);
+        // Through the session so DDL resolves names against its search path
+        let mut session = self.session.lock();
+        let result =
+            driftdb_core::sql_bridge::execute_sql_in_session(&mut engine, sql, &mut session);
+        drop(session);
 
         match result {
             Ok(core_result) => {
+                self.invalidate_cached_statements(sql);
                 // Pass the columns we extracted while holding the lock
                 self.convert_sql_result(core_result, table_columns)
             }
@@ -466,25 +619,96 @@ impl<'a> QueryExecutor<'a> {
     /// way `SELECT name FROM empty_table` still reports `name` in the
     /// column header, matching PostgreSQL.
     async fn execute_select_via_bridge(&self, sql: &str) -> Result<QueryResult> {
+        let parsed = self.cached_parse(sql);
         let mut engine = self.engine_write()?;
 
+        let cached = self.cached_result(&engine, sql, parsed.as_deref());
+        let read = match cached {
+            CachedRead::Hit(result) => return Ok(result),
+            CachedRead::Miss(read) => Some(read),
+            CachedRead::Uncacheable => None,
+        };
+
         // Best-effort projection extraction for empty-result-set headers.
         // Falls through to the table schema if SQL parsing fails or the
         // query uses `SELECT *`.
-        let projection_columns = Self::projection_columns_from_sql(sql).or_else(|| {
-            Self::extract_table_from_sql_static(sql)
-                .ok()
-                .and_then(|name| engine.get_table_columns(&name).ok())
+        let projection_columns = match &parsed {
+            Some(ast) => Self::projection_columns_from_statements(ast),
+            None => Self::projection_columns_from_sql(sql),
+        }
+        .or_else(|| {
+            match &parsed {
+                Some(ast) => Self::extract_table_from_statements(ast),
+                None => Self::extract_table_from_sql_static(sql),
+            }
+            .ok()
+            .and_then(|name| engine.get_table_columns(&name).ok())
         });
 
-        let mut session = self.session.lock();
-        let result =
-            driftdb_core::sql_bridge::execute_sql_in_session(&mut engine, sql, &mut session)
-                .map_err(|e| anyhow!("SQL execution failed: {}", e))?;
-        drop(session);
+        let result = self
+            .execute_in_session(&mut engine, sql, parsed.as_deref())
+            .map_err(|e| anyhow!("SQL execution failed: {}", e))?;
         drop(engine);
+        for table in self.session.lock().rows_scanned.keys() {
+            crate::metrics::record_table_operation(table, "select");
+        }
+
+        let result = self.convert_sql_result(result, projection_columns)?;
+        if let (Some(cache), Some(read)) = (&self.result_cache, read) {
+            cache.insert(read.key, &result, read.tables, read.catalog_version);
+        }
+        Ok(result)
+    }
+
+    /// Look `sql` up in the result cache. On a miss, also returns what the
+    /// result is stored under: the tables it reads, at their sequences
+    /// before it runs, so the stored rows reflect at least those.
+    fn cached_result(
+        &self,
+        engine: &Engine,
+        sql: &str,
+        parsed: Option<&[sqlparser::ast::Statement]>,
+    ) -> CachedRead {
+        let Some(cache) = &self.result_cache else {
+            return CachedRead::Uncacheable;
+        };
+        let search_path = {
+            let session = self.session.lock();
+            // A transaction reads its own uncommitted writes, and a cached
+            // result would skip the result limits the statement checks
+            if session.transaction_id.is_some() || session.limits_results(engine) {
+                return CachedRead::Uncacheable;
+            }
+            session.effective_search_path(engine)
+        };
+        let Some(key) = cache.key(sql, &search_path) else {
+            return CachedRead::Uncacheable;
+        };
 
-        self.convert_sql_result(result, projection_columns)
+        let catalog_version = engine.catalog_version();
+        if let Some(result) = cache.get(&key, catalog_version, |table| engine.table_sequence(table))
+        {
+            return CachedRead::Hit(result);
+        }
+
+        let relations = match parsed {
+            Some(ast) => read_relations(ast),
+            None => match sqlparser::parser::Parser::parse_sql(
+                &sqlparser::dialect::GenericDialect {},
+                sql,
+            ) {
+                Ok(ast) => read_relations(&ast),
+                Err(_) => return CachedRead::Uncacheable,
+            },
+        };
+        match engine.read_dependencies(&relations, &search_path) {
+            Some(tables) => CachedRead::Miss(CacheableRead {
+                key,
+                tables,
+                catalog_version,
+            }),
+            None => CachedRead::Uncacheable,
+        }
     }
 
     /// Execute a DML statement (INSERT / UPDATE / DELETE) through the core
@@ -495,37 +719,103 @@ impl<'a> QueryExecutor<'a> {
     /// for SELECTs but result-row metadata is absent, so the table schema
     /// is the natural column-list fallback.
     async fn execute_dml_via_bridge(&self, sql: &str) -> Result<QueryResult> {
+        let parsed = self.cached_parse(sql);
         let mut engine = self.engine_write()?;
 
-        let table_columns = Self::extract_table_from_sql_static(sql)
-            .ok()
-            .and_then(|name| engine.get_table_columns(&name).ok());
+        let table_columns = match &parsed {
+            Some(ast) => Self::extract_table_from_statements(ast),
+            None => Self::extract_table_from_sql_static(sql),
+        }
+        .ok()
+        .and_then(|name| engine.get_table_columns(&name).ok());
 
-        let mut session = self.session.lock();
-        let result =
-            driftdb_core::sql_bridge::execute_sql_in_session(&mut engine, sql, &mut session)
-                .map_err(|e| anyhow!("SQL execution failed: {}", e))?;
-        drop(session);
+        let result = self
+            .execute_in_session(&mut engine, sql, parsed.as_deref())
+            .map_err(|e| anyhow!("SQL execution failed: {}", e))?;
         drop(engine);
+        if let Some(operation) = dml_operation(sql) {
+            // The statement's target comes first among its tables
+            if let Some(table) = statement_tables(sql).first() {
+                crate::metrics::record_table_operation(table, operation);
+            }
+        }
 
         self.convert_sql_result(result, table_columns)
     }
 
+    /// The parsed form of `sql` from the shared statement cache, or `None`
+    /// when there's no cache or the statement has to run as text
+    fn cached_parse(&self, sql: &str) -> Option<Vec<sqlparser::ast::Statement>> {
+        self.statement_cache.as_ref()?.parse(sql)
+    }
+
+    /// Run `sql` in this connection's session, skipping the parse when
+    /// `parsed` already holds it
+    fn execute_in_session(
+        &self,
+        engine: &mut Engine,
+        sql: &str,
+        parsed: Option<&[sqlparser::ast::Statement]>,
+    ) -> driftdb_core::Result<driftdb_core::QueryResult> {
+        let mut session = self.session.lock();
+        let result = match parsed {
+            Some(ast) => {
+                driftdb_core::sql_bridge::execute_parsed_in_session(engine, sql, ast, &mut session)
+            }
+            None => driftdb_core::sql_bridge::execute_sql_in_session(engine, sql, &mut session),
+        };
+        crate::metrics::record_table_rows_scanned(&session.rows_scanned);
+        result
+    }
+
+    /// Evict cached statements that a successful schema change made stale:
+    /// those referencing the tables `sql` names, or every statement when
+    /// the affected tables can't be told (`DROP SCHEMA ... CASCADE`)
+    fn invalidate_cached_statements(&self, sql: &str) {
+        let Some(cache) = &self.statement_cache else {
+            return;
+        };
+        let lower = sql.trim_start().to_lowercase();
+        if !["create ", "alter ", "drop ", "truncate "]
+            .iter()
+            .any(|keyword| lower.starts_with(keyword))
+        {
+            return;
+        }
+
+        let tables = driftdb_core::sql_lint::analyze(sql).tables;
+        if tables.is_empty() || lower.starts_with("drop schema") {
+            cache.clear();
+        } else {
+            for table in &tables {
+                cache.invalidate_table(table);
+            }
+        }
+    }
+
     /// Parse the SQL and return the user-visible column labels in projection
     /// order. Returns `None` for `SELECT *`, parse failures, or non-SELECT
     /// statements — callers should fall back to the table schema in those
     /// cases.
     fn projection_columns_from_sql(sql: &str) -> Option<Vec<String>> {
-        use sqlparser::ast::{Expr, SelectItem, SetExpr, Statement};
         use sqlparser::dialect::GenericDialect;
         use sqlparser::parser::Parser;
 
         let ast = Parser::parse_sql(&GenericDialect {}, sql).ok()?;
-        let query = match ast.into_iter().next()? {
+        Self::projection_columns_from_statements(&ast)
+    }
+
+    /// [`Self::projection_columns_from_sql`] for an already parsed query
+    fn projection_columns_from_statements(
+        ast: &[sqlparser::ast::Statement],
+    ) -> Option<Vec<String>> {
+        use sqlparser::ast::{Expr, SelectItem, SetExpr, Statement};
+
+        let query = match ast.first()? {
             Statement::Query(q) => q,
             _ => return None,
         };
-        let select = match *query.body {
+        let select = match query.body.as_ref() {
             SetExpr::Select(s) => s,
             _ => return None,
         };
@@ -552,6 +842,106 @@ impl<'a> QueryExecutor<'a> {
         Some(out)
     }
 
+    /// Notices the last statement raised, such as planner hints it
+    /// couldn't follow
+    pub fn take_notices(&self) -> Vec<String> {
+        std::mem::take(&mut self.session.lock().notices)
+    }
+
+    /// Result columns of a SELECT whose wire type comes from the schema
+    /// rather than from their values: DECIMAL/NUMERIC columns (and
+    /// SUM/AVG/MIN/MAX over them), which hold JSON floats, are `numeric`,
+    /// and UUID and BYTEA columns, which hold text, are `uuid` and `bytea`.
+    pub fn typed_result_columns(&self, sql: &str) -> HashMap<String, DataType> {
+        use sqlparser::ast::{
+            Expr, FunctionArg, FunctionArgExpr, FunctionArguments, SelectItem, SetExpr, Statement,
+        };
+        use sqlparser::dialect::GenericDialect;
+        use sqlparser::parser::Parser;
+
+        let Some(table) = Self::extract_table_from_sql_static(sql).ok() else {
+            return HashMap::new();
+        };
+        let mut declared: HashMap<String, DataType> = HashMap::new();
+        if let Ok(engine) = self.engine_read() {
+            for (name, _) in engine.get_decimal_columns(&table).unwrap_or_default() {
+                declared.insert(name, DataType::Numeric);
+            }
+            for name in engine.get_uuid_columns(&table).unwrap_or_default() {
+                declared.insert(name, DataType::Uuid);
+            }
+            for name in engine.get_bytea_columns(&table).unwrap_or_default() {
+                declared.insert(name, DataType::Bytea);
+            }
+        }
+        if declared.is_empty() {
+            return HashMap::new();
+        }
+
+        let Some(select) =
+            Parser::parse_sql(&GenericDialect {}, sql)
+                .ok()
+                .and_then(|ast| match ast.into_iter().next()? {
+                    Statement::Query(q) => match *q.body {
+                        SetExpr::Select(s) => Some(s),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+        else {
+            return HashMap::new();
+        };
+
+        // The label and type a typed expression gets in result rows, if it
+        // is one
+        let typed_label = |expr: &Expr| -> Option<(String, DataType)> {
+            match expr {
+                Expr::Identifier(i) => declared
+                    .get(&i.value)
+                    .map(|&data_type| (i.value.clone(), data_type)),
+                Expr::Function(f) => {
+                    let name = f.name.to_string().to_lowercase();
+                    if !matches!(name.as_str(), "sum" | "avg" | "min" | "max") {
+                        return None;
+                    }
+                    match &f.args {
+                        FunctionArguments::List(list) if list.args.len() == 1 => {
+                            match &list.args[0] {
+                                FunctionArg::Unnamed(FunctionArgExpr::Expr(Expr::Identifier(
+                                    i,
+                                ))) if matches!(
+                                    declared.get(&i.value),
+                                    Some(DataType::Numeric)
+                                ) =>
+                                {
+                                    Some((format!("{}({})", name, i.value), DataType::Numeric))
+                                }
+                                _ => None,
+                            }
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        };
+
+        let mut typed = HashMap::new();
+        for item in &select.projection {
+            match item {
+                SelectItem::Wildcard(_) => typed.extend(declared.clone()),
+                SelectItem::UnnamedExpr(expr) => typed.extend(typed_label(expr)),
+                SelectItem::ExprWithAlias { expr, alias } => {
+                    if let Some((_, data_type)) = typed_label(expr) {
+                        typed.insert(alias.value.clone(), data_type);
+                    }
+                }
+                _ => {}
+            }
+        }
+        typed
+    }
+
     /// Server-local handler for PostgreSQL housekeeping commands that
     /// `sql_bridge` doesn't aim to provide. EXPLAIN was previously handled
     /// here too; it now flows through the bridge via `crate::sql_explain`.
@@ -560,8 +950,9 @@ impl<'a> QueryExecutor<'a> {
             return self.execute_show(sql).await;
         }
         if sql.to_lowercase().starts_with("set ") {
-            // SET is a no-op today — we don't track session GUCs. Returning
-            // Empty keeps psql / JDBC connection setup quiet.
+            // Client GUCs the settings registry doesn't know are accepted
+            // and ignored. Returning Empty keeps psql / JDBC connection
+            // setup quiet.
             return Ok(QueryResult::Empty);
         }
         warn!("Unsupported SQL command: {}", sql);
@@ -588,6 +979,130 @@ impl<'a> QueryExecutor<'a> {
                 columns: vec!["Tables_in_driftdb".to_string()],
                 rows,
             })
+        } else if lower.starts_with("show table stats") {
+            // SHOW TABLE STATS [table]; all tables when none is named
+            let name = sql["show table stats".len()..]
+                .trim()
+                .trim_end_matches(';')
+                .trim()
+                .trim_matches('"');
+            let tables = if name.is_empty() {
+                let mut tables = engine.list_tables();
+                tables.sort();
+                tables
+            } else {
+                vec![name.to_string()]
+            };
+
+            let mut rows = Vec::with_capacity(tables.len());
+            for table in tables {
+                let info = engine.table_storage_info(&table)?;
+                let ratio = |r: f64| {
+                    serde_json::Number::from_f64((r * 1000.0).round() / 1000.0)
+                        .map(Value::Number)
+                        .unwrap_or(Value::Null)
+                };
+                rows.push(vec![
+                    Value::String(info.table.clone()),
+                    Value::from(info.segment_count),
+                    Value::from(info.snapshot_count),
+                    Value::from(info.event_count),
+                    Value::from(info.live_rows),
+                    ratio(info.dead_version_ratio),
+                    Value::from(info.last_compaction_sequence),
+                    Value::from(info.total_bytes),
+                    Value::from(info.logical_bytes),
+                    ratio(info.disk_to_logical_ratio()),
+                ]);
+            }
+
+            Ok(QueryResult::Select {
+                columns: [
+                    "table",
+                    "segments",
+                    "snapshots",
+                    "events",
+                    "live_rows",
+                    "dead_version_ratio",
+                    "last_compaction_sequence",
+                    "total_bytes",
+                    "logical_bytes",
+                    "disk_to_logical_ratio",
+                ]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+                rows,
+            })
+        } else if lower.starts_with("show columns from ") {
+            // One row per column, from the table's schema rather than its DDL
+            let table = show_target(sql, "show columns from ".len());
+            let schema = engine.table_schema(table)?;
+            let rows = schema
+                .columns
+                .iter()
+                .map(|column| {
+                    let is_pk = column.name == schema.primary_key;
+                    let default = schema
+                        .defaults
+                        .get(&column.name)
+                        .cloned()
+                        .or_else(|| added_column_default(&schema, &column.name));
+                    vec![
+                        Value::String(column.name.clone()),
+                        Value::String(column.col_type.to_lowercase()),
+                        // NOT NULL isn't tracked; only the primary key
+                        // can't be NULL
+                        Value::Bool(!is_pk),
+                        default.map_or(Value::Null, Value::String),
+                        Value::Bool(is_pk),
+                    ]
+                })
+                .collect();
+
+            Ok(QueryResult::Select {
+                columns: [
+                    "column_name",
+                    "data_type",
+                    "is_nullable",
+                    "column_default",
+                    "is_primary_key",
+                ]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+                rows,
+            })
+        } else if lower.starts_with("show indexes from ") || lower.starts_with("show index from ") {
+            let from = lower.find(" from ").unwrap_or(0) + " from ".len();
+            let table = show_target(sql, from);
+            let rows = engine
+                .list_indexes(table)?
+                .into_iter()
+                .map(|index| {
+                    vec![
+                        Value::String(index.name),
+                        Value::String(index.column),
+                        Value::String(index.method.to_string()),
+                        Value::Bool(index.unique),
+                        Value::Bool(index.primary),
+                    ]
+                })
+                .collect();
+
+            Ok(QueryResult::Select {
+                columns: [
+                    "index_name",
+                    "column_name",
+                    "method",
+                    "is_unique",
+                    "is_primary",
+                ]
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+                rows,
+            })
         } else if lower.starts_with("show databases") {
             Ok(QueryResult::Select {
                 columns: vec!["Database".to_string()],
@@ -882,6 +1397,59 @@ impl<'a> QueryExecutor<'a> {
     }
 }
 
+/// Whether lowercased `sql` reads or changes a parameter in
+/// `driftdb_core::settings`: `SHOW ALL`, `SET GLOBAL`, `SET TIME ZONE`,
+/// every `RESET`, and `SHOW` or `SET` of a parameter it knows
+fn is_settings_command(sql: &str) -> bool {
+    let known = |name: &str| {
+        let name = name.split('=').next().unwrap_or(name);
+        driftdb_core::settings::lookup(name).is_ok()
+    };
+    let statement = sql.trim_end_matches(';').trim_end();
+    let mut words = statement.split_whitespace();
+    match (words.next(), words.next(), words.next()) {
+        (Some("show"), Some("all"), None) => true,
+        (Some("show"), Some(name), None) => known(name),
+        (Some("reset"), Some(_), _) => true,
+        (Some("set"), Some("global" | "time"), _) => true,
+        (Some("set"), Some("session" | "local"), Some(name)) => known(name),
+        (Some("set"), Some(name), _) => known(name),
+        _ => false,
+    }
+}
+
+/// The per-table metrics operation of an INSERT, UPDATE or DELETE
+fn dml_operation(sql: &str) -> Option<&'static str> {
+    let first = sql.split_whitespace().next()?.to_lowercase();
+    ["insert", "update", "delete"]
+        .into_iter()
+        .find(|operation| first == *operation)
+}
+
+/// The table named after the first `prefix_len` bytes of a SHOW command
+fn show_target(sql: &str, prefix_len: usize) -> &str {
+    sql[prefix_len..]
+        .trim()
+        .trim_end_matches(';')
+        .trim()
+        .trim_matches('"')
+}
+
+/// The default given to a column by `ALTER TABLE ... ADD COLUMN`
+fn added_column_default(schema: &driftdb_core::Schema, column: &str) -> Option<String> {
+    schema.changes.iter().rev().find_map(|change| match change {
+        driftdb_core::schema::ColumnChange::Add {
+            column: added,
+            default: Some(default),
+            ..
+        } if added == column => Some(match default {
+            Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+            other => other.to_string(),
+        }),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -958,8 +1526,17 @@ mod tests {
                 // The plan must include the operator chain we just exercised.
                 assert!(text.contains("Limit"), "missing Limit in plan:\n{}", text);
                 assert!(text.contains("Sort"), "missing Sort in plan:\n{}", text);
-                assert!(text.contains("Seq Scan on users"), "missing Seq Scan:\n{}", text);
-                assert!(text.contains("Filter:"), "missing Filter:\n{}", text);
+                // `id` is the indexed primary key, so the lookup uses it
+                assert!(
+                    text.contains("Index Scan using id on users"),
+                    "missing Index Scan:\n{}",
+                    text
+                );
+                assert!(
+                    text.contains("Index Cond:"),
+                    "missing Index Cond:\n{}",
+                    text
+                );
             }
             other => panic!("expected Select, got {:?}", other),
         }
@@ -1075,6 +1652,213 @@ mod tests {
     }
 
 
+    #[tokio::test]
+    async fn test_show_table_stats() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let executor = QueryExecutor::new(engine);
+
+        executor
+            .execute("CREATE TABLE items (id VARCHAR, qty INT, PRIMARY KEY (id))")
+            .await
+            .unwrap();
+        executor
+            .execute("INSERT INTO items (id, qty) VALUES ('a', 1)")
+            .await
+            .unwrap();
+        executor
+            .execute("UPDATE items SET qty = 2 WHERE id = 'a'")
+            .await
+            .unwrap();
+
+        match executor.execute("SHOW TABLE STATS items").await.unwrap() {
+            QueryResult::Select { columns, rows } => {
+                assert_eq!(rows.len(), 1);
+                let col = |name: &str| columns.iter().position(|c| c == name).unwrap();
+                assert_eq!(rows[0][col("table")], Value::String("items".to_string()));
+                assert_eq!(rows[0][col("events")], Value::from(2u64));
+                assert_eq!(rows[0][col("live_rows")], Value::from(1u64));
+                assert_eq!(rows[0][col("dead_version_ratio")], Value::from(0.5));
+            }
+            other => panic!("expected Select, got {:?}", other),
+        }
+
+        assert!(executor.execute("SHOW TABLE STATS missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_parameters_carry_across_executors() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let session = Arc::new(ParkingMutex::new(
+            driftdb_core::sql_bridge::SessionContext::new(),
+        ));
+        let show = |executor: QueryExecutor<'static>| async move {
+            match executor.execute("SHOW work_mem").await.unwrap() {
+                QueryResult::Select { columns, rows } => {
+                    assert_eq!(columns, vec!["work_mem".to_string()]);
+                    rows[0][0].clone()
+                }
+                other => panic!("expected Select, got {:?}", other),
+            }
+        };
+
+        // One connection, one executor per statement
+        QueryExecutor::new(engine.clone())
+            .with_session(session.clone())
+            .execute("SET work_mem = '64MB'")
+            .await
+            .unwrap();
+        let executor = QueryExecutor::new(engine.clone()).with_session(session.clone());
+        assert_eq!(show(executor).await, Value::from("64MB"));
+        QueryExecutor::new(engine.clone())
+            .with_session(session.clone())
+            .execute("SET statement_timeout = '3s'")
+            .await
+            .unwrap();
+        let executor = QueryExecutor::new(engine.clone()).with_session(session);
+        assert_eq!(
+            executor.statement_timeout(),
+            Some(std::time::Duration::from_secs(3))
+        );
+
+        // Another connection keeps the defaults
+        assert_eq!(
+            show(QueryExecutor::new(engine.clone())).await,
+            Value::from("4MB")
+        );
+
+        // Client GUCs the registry doesn't know are still accepted, but
+        // bad values for the ones it does are not
+        let executor = QueryExecutor::new(engine);
+        assert!(executor.execute("SET jit = off").await.is_ok());
+        assert!(executor.execute("SET work_mem = 'lots'").await.is_err());
+        assert!(is_settings_command("show all"));
+        assert!(!is_settings_command("show tables"));
+    }
+
+    #[tokio::test]
+    async fn test_show_columns_and_indexes() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let executor = QueryExecutor::new(engine);
+
+        executor
+            .execute(
+                "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT, visits INTEGER DEFAULT 0)",
+            )
+            .await
+            .unwrap();
+        executor
+            .execute("CREATE INDEX ON users (email)")
+            .await
+            .unwrap();
+
+        match executor.execute("SHOW COLUMNS FROM users").await.unwrap() {
+            QueryResult::Select { columns, rows } => {
+                assert_eq!(
+                    columns,
+                    [
+                        "column_name",
+                        "data_type",
+                        "is_nullable",
+                        "column_default",
+                        "is_primary_key"
+                    ]
+                );
+                assert_eq!(rows.len(), 3);
+                assert_eq!(rows[0][0], Value::String("id".to_string()));
+                assert_eq!(rows[0][2], Value::Bool(false));
+                assert_eq!(rows[0][4], Value::Bool(true));
+                assert_eq!(rows[1][3], Value::Null);
+                assert_eq!(rows[2][3], Value::String("0".to_string()));
+            }
+            other => panic!("expected Select, got {:?}", other),
+        }
+
+        match executor.execute("SHOW INDEXES FROM users;").await.unwrap() {
+            QueryResult::Select { rows, .. } => {
+                let names: Vec<&Value> = rows.iter().map(|r| &r[0]).collect();
+                assert_eq!(
+                    names,
+                    [
+                        &Value::String("users_pkey".to_string()),
+                        &Value::String("idx_users_email".to_string())
+                    ]
+                );
+            }
+            other => panic!("expected Select, got {:?}", other),
+        }
+
+        assert!(executor.execute("SHOW COLUMNS FROM missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_typed_result_columns() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let executor = QueryExecutor::new(engine);
+
+        executor
+            .execute("CREATE TABLE ledger (id UUID, amount NUMERIC(12,2), note VARCHAR, PRIMARY KEY (id))")
+            .await
+            .unwrap();
+
+        let typed = executor.typed_result_columns("SELECT * FROM ledger");
+        assert_eq!(typed.len(), 2);
+        assert!(matches!(typed.get("amount"), Some(DataType::Numeric)));
+        assert!(matches!(typed.get("id"), Some(DataType::Uuid)));
+
+        let typed = executor.typed_result_columns(
+            "SELECT SUM(amount) AS total, AVG(amount), id AS ref, note FROM ledger",
+        );
+        let mut names: Vec<&String> = typed.keys().collect();
+        names.sort();
+        assert_eq!(names, vec!["avg(amount)", "ref", "total"]);
+        assert!(matches!(typed.get("ref"), Some(DataType::Uuid)));
+        assert!(executor
+            .typed_result_columns("SELECT note FROM ledger")
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bytea_columns_are_typed_and_sent_as_hex() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let executor = QueryExecutor::new(engine);
+
+        executor
+            .execute("CREATE TABLE thumbs (id INTEGER PRIMARY KEY, img BYTEA)")
+            .await
+            .unwrap();
+        executor
+            .execute(r"INSERT INTO thumbs (id, img) VALUES (1, '\x89504E47')")
+            .await
+            .unwrap();
+
+        let typed = executor.typed_result_columns("SELECT * FROM thumbs");
+        assert!(matches!(typed.get("img"), Some(DataType::Bytea)));
+
+        match executor
+            .execute("SELECT img FROM thumbs WHERE id = 1")
+            .await
+            .unwrap()
+        {
+            QueryResult::Select { rows, .. } => {
+                assert_eq!(
+                    crate::protocol::value_to_postgres_text(&rows[0][0]).as_deref(),
+                    Some(r"\x89504e47")
+                );
+            }
+            other => panic!("expected Select, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_compare_values_routes_through_canonical_predicate() {
         use driftdb_core::query::predicate::compare_json_values;
@@ -2215,4 +2999,153 @@ mod tests {
             assert_eq!(columns, vec!["name", "department"]);
         }
     }
+
+    /// Statements sharing a shape reuse one cached parse, and DDL on a
+    /// table evicts the statements that reference it.
+    #[tokio::test]
+    async fn test_statement_cache_hits_and_invalidation() {
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let cache = Arc::new(StatementCache::default());
+        let executor = QueryExecutor::new(engine).with_statement_cache(cache.clone());
+
+        executor
+            .execute("CREATE TABLE t (id INT, name VARCHAR, PRIMARY KEY (id))")
+            .await
+            .unwrap();
+        for id in 1..=3 {
+            executor
+                .execute(&format!(
+                    "INSERT INTO t (id, name) VALUES ({}, 'n{}')",
+                    id, id
+                ))
+                .await
+                .unwrap();
+        }
+        match executor
+            .execute("SELECT name FROM t WHERE id = 2")
+            .await
+            .unwrap()
+        {
+            QueryResult::Select { rows, .. } => {
+                assert_eq!(rows, vec![vec![Value::String("n2".to_string())]])
+            }
+            other => panic!("expected Select, got {:?}", other),
+        }
+        executor
+            .execute("SELECT name FROM t WHERE id = 3")
+            .await
+            .unwrap();
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (3, 2, 2));
+
+        executor
+            .execute("ALTER TABLE t ADD COLUMN email VARCHAR")
+            .await
+            .unwrap();
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    /// Repeated reads are served from the result cache until a table they
+    /// read receives new events; writes to other tables don't matter.
+    #[tokio::test]
+    async fn test_result_cache_follows_table_sequences() {
+        use crate::result_cache::{ResultCache, ResultCacheConfig};
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let cache = Arc::new(ResultCache::new(ResultCacheConfig::default()));
+        let executor = QueryExecutor::new(engine).with_result_cache(cache.clone());
+
+        for table in ["t", "other"] {
+            executor
+                .execute(&format!(
+                    "CREATE TABLE {} (id INT, name VARCHAR, PRIMARY KEY (id))",
+                    table
+                ))
+                .await
+                .unwrap();
+            executor
+                .execute(&format!("INSERT INTO {} (id, name) VALUES (1, 'a')", table))
+                .await
+                .unwrap();
+        }
+
+        async fn count(executor: &QueryExecutor<'_>) -> Value {
+            match executor
+                .execute("SELECT COUNT(*) AS n FROM t")
+                .await
+                .unwrap()
+            {
+                QueryResult::Select { rows, .. } => rows[0][0].clone(),
+                other => panic!("expected Select, got {:?}", other),
+            }
+        }
+        assert_eq!(count(&executor).await, Value::from(1));
+        assert_eq!(count(&executor).await, Value::from(1));
+        executor
+            .execute("INSERT INTO other (id, name) VALUES (2, 'b')")
+            .await
+            .unwrap();
+        assert_eq!(count(&executor).await, Value::from(1));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+
+        executor
+            .execute("INSERT INTO t (id, name) VALUES (2, 'b')")
+            .await
+            .unwrap();
+        assert_eq!(count(&executor).await, Value::from(2));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.invalidations), (2, 2, 1));
+        assert_eq!(count(&executor).await, Value::from(2));
+        assert_eq!(cache.stats().hits, 3);
+    }
+
+    /// A session with result limits doesn't get an over-limit result from
+    /// the cache that an unlimited session filled.
+    #[tokio::test]
+    async fn test_result_cache_skipped_under_result_limits() {
+        use crate::result_cache::{ResultCache, ResultCacheConfig};
+        use driftdb_core::row_level_security::{RlsManager, SecurityContext};
+        use tempfile::TempDir;
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let cache = Arc::new(ResultCache::new(ResultCacheConfig::default()));
+        let manager = Arc::new(RlsManager::new());
+        let session_as = |user: &str, roles: &[&str], superuser: bool| {
+            QueryExecutor::new(engine.clone())
+                .with_result_cache(cache.clone())
+                .with_row_security(RowSecurity::new(
+                    manager.clone(),
+                    SecurityContext::new(
+                        user.to_string(),
+                        roles.iter().map(|role| role.to_string()).collect(),
+                        superuser,
+                    ),
+                ))
+        };
+
+        let root = session_as("driftdb", &[], true);
+        root.execute("CREATE TABLE t (id INT, PRIMARY KEY (id))")
+            .await
+            .unwrap();
+        root.execute("INSERT INTO t (id) VALUES (1), (2), (3)")
+            .await
+            .unwrap();
+        root.execute("ALTER ROLE analysts SET max_result_rows = 2")
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            root.execute("SELECT * FROM t").await.unwrap();
+        }
+        assert_eq!(cache.stats().hits, 1);
+
+        let analyst = session_as("ana", &["analysts"], false);
+        let err = analyst.execute("SELECT * FROM t").await.unwrap_err();
+        assert!(err.to_string().contains("max_result_rows = 2"), "{}", err);
+        assert_eq!(cache.stats().hits, 1);
+    }
 }
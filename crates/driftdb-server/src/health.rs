@@ -59,6 +59,12 @@ async fn liveness_check(State(state): State<HealthState>) -> Result<Json<Value>,
 async fn readiness_check(State(state): State<HealthState>) -> Result<Json<Value>, StatusCode> {
     debug!("Readiness check requested");
 
+    // Draining servers report not ready so load balancers stop routing here
+    if state.session_manager.drain().is_draining() {
+        info!("Server is draining, not ready");
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     // Check if engine is accessible
     let engine_status = match state.engine.try_read() {
         Some(_engine) => {
@@ -215,7 +221,10 @@ mod tests {
         let auth_config = AuthConfig::default();
         let rate_limit_manager = Arc::new(RateLimitManager::new(Default::default(), metrics));
         let slow_query_logger = Arc::new(SlowQueryLogger::new(SlowQueryConfig::default()));
-        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig::default()));
+        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig {
+            log_to_file: false,
+            ..AuditConfig::default()
+        }));
         let rbac_manager = Arc::new(RbacManager::new());
 
         let session_manager = Arc::new(SessionManager::new(
@@ -253,7 +262,10 @@ mod tests {
         let auth_config = AuthConfig::default();
         let rate_limit_manager = Arc::new(RateLimitManager::new(Default::default(), metrics));
         let slow_query_logger = Arc::new(SlowQueryLogger::new(SlowQueryConfig::default()));
-        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig::default()));
+        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig {
+            log_to_file: false,
+            ..AuditConfig::default()
+        }));
         let rbac_manager = Arc::new(RbacManager::new());
 
         let session_manager = Arc::new(SessionManager::new(
@@ -0,0 +1,88 @@
+//! HTTP routes for SQL linting
+//!
+//! Analyzes a statement without executing it, so CI jobs can check queries
+//! for injection-prone patterns and references to dropped columns.
+
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::Json, routing::post, Router};
+use driftdb_core::sql_lint::ValidationReport;
+use driftdb_core::Engine;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::security::SqlValidator;
+
+/// State shared across lint route handlers
+#[derive(Clone)]
+pub struct LintRouteState {
+    engine: Arc<RwLock<Engine>>,
+    validator: Arc<SqlValidator>,
+}
+
+impl LintRouteState {
+    pub fn new(engine: Arc<RwLock<Engine>>) -> Self {
+        Self {
+            engine,
+            validator: Arc::new(SqlValidator::new()),
+        }
+    }
+}
+
+/// Request body for linting a statement
+#[derive(Debug, Deserialize)]
+struct LintRequest {
+    sql: String,
+}
+
+/// Create the lint router
+pub fn create_router(engine: Arc<RwLock<Engine>>) -> Router {
+    let state = LintRouteState::new(engine);
+
+    Router::new()
+        .route("/api/lint", post(lint))
+        .with_state(state)
+}
+
+/// POST /api/lint - Analyze a statement and check it against the schema
+async fn lint(
+    State(state): State<LintRouteState>,
+    Json(req): Json<LintRequest>,
+) -> Result<Json<ValidationReport>, (StatusCode, Json<serde_json::Value>)> {
+    let mut report = state.validator.analyze(&req.sql).map_err(|e| {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(json!({ "error": e.to_string() })),
+        )
+    })?;
+    report.check_schema(&state.engine.read());
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_lint_reports_missing_tables() {
+        let temp = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp.path()).unwrap()));
+        let state = LintRouteState::new(engine);
+
+        let Json(report) = lint(
+            State(state),
+            Json(LintRequest {
+                sql: "SELECT name FROM users".to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+        assert!(report.parses);
+        assert_eq!(
+            report.schema_issues,
+            vec!["relation \"users\" does not exist"]
+        );
+    }
+}
@@ -6,35 +6,54 @@
 mod advanced_pool;
 mod alert_routes;
 mod alerting;
+mod compaction_routes;
+mod drain;
 mod errors;
 mod executor;
 mod health;
+mod lint_routes;
 mod metrics;
 mod performance;
 mod performance_routes;
 mod protocol;
 mod replication;
+mod result_cache;
 mod security;
 mod security_audit;
 mod session;
+mod session_routes;
 mod slow_query_log;
+mod statement_cache;
 mod tls;
 
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::Parser;
 use tokio::net::TcpListener;
 use tracing::{debug, error, info, warn};
 
-use driftdb_core::{Engine, EnginePool, PoolConfig, RateLimitConfig, RateLimitManager};
+use drain::DrainPhase;
+use driftdb_core::durability::SyncMode;
+use driftdb_core::optimizer::{AnalyzeConfig, ParallelScanConfig};
+use driftdb_core::storage::{
+    LocalFileBackend, MappedFileBackend, StorageBackend, TierAge, TierPolicy, TieredBackend,
+};
+use driftdb_core::{
+    AutoAnalyzeConfig, AutoAnalyzer, CompactionConfig, CompactionScheduler, Engine, EnginePool,
+    PoolConfig, RateLimitConfig, RateLimitManager,
+};
 use parking_lot::RwLock as SyncRwLock;
 use performance::{ConnectionPoolOptimizer, PerformanceMonitor, QueryOptimizer};
+use result_cache::{ResultCache, ResultCacheConfig};
+use security::sql_validator::ValidatorConfig;
+use security::statement_audit::{StatementAuditConfig, StatementAuditor, StatementCategory};
 use security_audit::{AuditConfig, SecurityAuditLogger};
-use session::SessionManager;
+use session::{IdleTimeouts, PoolMode, SessionManager, StatementQueueConfig};
 use slow_query_log::{SlowQueryConfig, SlowQueryLogger};
+use statement_cache::{StatementCache, StatementCacheConfig};
 use tls::{TlsConfig, TlsManager};
 
 #[derive(Parser, Debug)]
@@ -49,6 +68,37 @@ struct Args {
     #[arg(short, long, env = "DRIFTDB_LISTEN", default_value = "127.0.0.1:5433")]
     listen: SocketAddr,
 
+    /// Additional PostgreSQL listener for administrators. It accepts every
+    /// statement; the --sql-* policy applies only to --listen.
+    #[arg(long, env = "DRIFTDB_ADMIN_LISTEN")]
+    admin_listen: Option<SocketAddr>,
+
+    /// Statement categories accepted on --listen (comma-separated: ddl,
+    /// write, read, grant, transaction, other, or all)
+    #[arg(long, env = "DRIFTDB_SQL_ALLOW_STATEMENTS", default_value = "all")]
+    sql_allow_statements: String,
+
+    /// Statement categories rejected on --listen, e.g. `ddl,grant`
+    #[arg(long, env = "DRIFTDB_SQL_DENY_STATEMENTS", default_value = "")]
+    sql_deny_statements: String,
+
+    /// Accept several statements in one query string on --listen
+    #[arg(long, env = "DRIFTDB_SQL_MULTI_STATEMENT", default_value = "true")]
+    sql_multi_statement: bool,
+
+    /// Strip SQL comments from queries on --listen before checking and
+    /// running them
+    #[arg(long, env = "DRIFTDB_SQL_STRIP_COMMENTS", default_value = "false")]
+    sql_strip_comments: bool,
+
+    /// Maximum query length in bytes on --listen
+    #[arg(
+        long,
+        env = "DRIFTDB_SQL_MAX_STATEMENT_LENGTH",
+        default_value = "100000"
+    )]
+    sql_max_statement_length: usize,
+
     /// HTTP server listen address for health checks and metrics
     #[arg(long, env = "DRIFTDB_HTTP_LISTEN", default_value = "127.0.0.1:8080")]
     http_listen: SocketAddr,
@@ -66,6 +116,16 @@ struct Args {
     #[arg(long, env = "DRIFTDB_MIN_IDLE_CONNECTIONS", default_value = "10")]
     min_idle_connections: usize,
 
+    /// Create the minimum idle connections at startup rather than at the
+    /// first pool health check
+    #[arg(long, env = "DRIFTDB_POOL_WARMUP", default_value = "true")]
+    pool_warmup: bool,
+
+    /// Check each pooled connection before handing it out, replacing it
+    /// if it is broken
+    #[arg(long, env = "DRIFTDB_POOL_PRE_PING", default_value = "false")]
+    pool_pre_ping: bool,
+
     /// Connection timeout in seconds
     #[arg(long, env = "DRIFTDB_CONNECTION_TIMEOUT", default_value = "30")]
     connection_timeout: u64,
@@ -74,14 +134,67 @@ struct Args {
     #[arg(long, env = "DRIFTDB_IDLE_TIMEOUT", default_value = "600")]
     idle_timeout: u64,
 
+    /// When a client holds a pooled connection: "session" (for its whole
+    /// connection, refusing clients past --max-connections) or
+    /// "transaction" (per statement or transaction, queueing the rest)
+    #[arg(long, env = "DRIFTDB_POOL_MODE", default_value = "session")]
+    pool_mode: String,
+
+    /// Statements allowed to wait for a pooled connection in transaction
+    /// pool mode
+    #[arg(long, env = "DRIFTDB_STATEMENT_QUEUE_DEPTH", default_value = "1000")]
+    statement_queue_depth: usize,
+
+    /// Seconds a statement waits for a pooled connection in transaction
+    /// pool mode before failing
+    #[arg(long, env = "DRIFTDB_STATEMENT_QUEUE_TIMEOUT", default_value = "30")]
+    statement_queue_timeout: u64,
+
+    /// Seconds a client session may sit idle outside a transaction before
+    /// it is closed; 0 to never close it
+    #[arg(long, env = "DRIFTDB_IDLE_SESSION_TIMEOUT", default_value = "0")]
+    idle_session_timeout: u64,
+
+    /// Seconds a client session may sit idle with a transaction open
+    /// before the transaction is rolled back and the session closed; 0 to
+    /// never close it
+    #[arg(long, env = "DRIFTDB_IDLE_IN_TRANSACTION_TIMEOUT", default_value = "0")]
+    idle_in_transaction_timeout: u64,
+
+    /// Seconds to wait on shutdown for open transactions to finish before
+    /// rolling them back
+    #[arg(long, env = "DRIFTDB_SHUTDOWN_GRACE_SECONDS", default_value = "30")]
+    shutdown_grace_seconds: u64,
+
     /// Enable SQL:2011 temporal extensions
     #[arg(long, env = "DRIFTDB_TEMPORAL", default_value = "true")]
     enable_temporal: bool,
 
+    /// Serve reads only; every write fails (hot standby / replica)
+    #[arg(long, env = "DRIFTDB_READ_ONLY", default_value = "false")]
+    read_only: bool,
+
+    /// Compact tables in the background when they cross the compaction
+    /// thresholds
+    #[arg(long, env = "DRIFTDB_AUTO_COMPACTION", default_value = "false")]
+    auto_compaction: bool,
+
+    /// JSON file with compaction thresholds and per-table overrides
+    /// (implies --auto-compaction unless it sets "enabled": false)
+    #[arg(long, env = "DRIFTDB_COMPACTION_CONFIG")]
+    compaction_config: Option<PathBuf>,
+
     /// Enable metrics collection
     #[arg(long, env = "DRIFTDB_METRICS", default_value = "true")]
     enable_metrics: bool,
 
+    /// Tables given their own label in the per-table operation and
+    /// rows-scanned metrics, re-picked each minute as the most active
+    /// ones; activity on further tables is counted under "other". 0 turns
+    /// per-table metrics off
+    #[arg(long, env = "DRIFTDB_TABLE_METRICS_LIMIT", default_value = "100")]
+    table_metrics_limit: usize,
+
     /// Authentication method (trust, md5, scram-sha-256)
     #[arg(long, env = "DRIFTDB_AUTH_METHOD", default_value = "md5")]
     auth_method: String,
@@ -162,6 +275,134 @@ struct Args {
     #[arg(long, env = "DRIFTDB_QUERY_CACHE_SIZE", default_value = "1000")]
     query_cache_size: usize,
 
+    /// Most workers a single table scan may be split across (0 disables
+    /// parallel scans; defaults to the number of cores)
+    #[arg(long, env = "DRIFTDB_MAX_PARALLEL_WORKERS")]
+    max_parallel_workers: Option<usize>,
+
+    /// Rows ANALYZE samples from each larger table to build column
+    /// histograms and most-common-value lists
+    #[arg(long, env = "DRIFTDB_ANALYZE_SAMPLE_ROWS", default_value = "30000")]
+    analyze_sample_rows: usize,
+
+    /// Re-analyze tables in the background once they received more than
+    /// --autoanalyze-threshold changes since their last ANALYZE
+    #[arg(long, env = "DRIFTDB_AUTO_ANALYZE", default_value = "false")]
+    auto_analyze: bool,
+
+    /// Rows written to a table before auto-analyze refreshes its statistics
+    #[arg(long, env = "DRIFTDB_AUTOANALYZE_THRESHOLD", default_value = "1000")]
+    autoanalyze_threshold: u64,
+
+    /// Memory a single sort or hash join may use before spilling to
+    /// temporary files, in kilobytes
+    #[arg(long, env = "DRIFTDB_WORK_MEM", default_value = "4096")]
+    work_mem: usize,
+
+    /// Most rows a query may return to a client unless its session or
+    /// role sets max_result_rows; 0 for no limit
+    #[arg(long, env = "DRIFTDB_MAX_RESULT_ROWS", default_value = "0")]
+    max_result_rows: u64,
+
+    /// Largest result a query may return to a client, in kilobytes of
+    /// estimated size, unless its session or role sets max_result_bytes;
+    /// 0 for no limit
+    #[arg(long, env = "DRIFTDB_MAX_RESULT_BYTES", default_value = "0")]
+    max_result_bytes: u64,
+
+    /// Memory a session's sorts, hash tables and results may hold at
+    /// once, in kilobytes, unless its session or role sets
+    /// max_session_memory; a statement that needs more fails alone with
+    /// an out-of-memory error. 0 for no limit
+    #[arg(long, env = "DRIFTDB_MAX_SESSION_MEMORY", default_value = "0")]
+    max_session_memory: u64,
+
+    /// Isolation level of each new transaction unless its session sets
+    /// default_transaction_isolation: "read-uncommitted", "read-committed",
+    /// "repeatable-read" or "serializable"
+    #[arg(
+        long,
+        env = "DRIFTDB_DEFAULT_ISOLATION",
+        default_value = "read-committed"
+    )]
+    default_isolation: String,
+
+    /// Start every transaction read-only unless its session sets
+    /// default_transaction_read_only
+    #[arg(
+        long,
+        env = "DRIFTDB_DEFAULT_TRANSACTION_READ_ONLY",
+        default_value = "false"
+    )]
+    default_transaction_read_only: bool,
+
+    /// Directory for temporary sort and join files (defaults to `tmp`
+    /// under the data path)
+    #[arg(long, env = "DRIFTDB_TEMP_DIR")]
+    temp_dir: Option<PathBuf>,
+
+    /// Read local segments through memory maps instead of file reads.
+    /// Segments must then never be truncated by other processes.
+    #[arg(long, env = "DRIFTDB_MMAP_READS", default_value = "false")]
+    mmap_reads: bool,
+
+    /// Directory (e.g. an object-store mount) that segments older than
+    /// --cold-after-secs move to; they are read back transparently
+    #[arg(long, env = "DRIFTDB_COLD_STORAGE_DIR")]
+    cold_storage_dir: Option<PathBuf>,
+
+    /// Age after which a segment moves to --cold-storage-dir
+    #[arg(long, env = "DRIFTDB_COLD_AFTER_SECS", default_value = "2592000")]
+    cold_after_secs: u64,
+
+    /// Whether a segment's age counts from its last `write` or its last
+    /// `access`, so that history queries keep reading stays local
+    #[arg(long, env = "DRIFTDB_COLD_AGE_BY", default_value = "write")]
+    cold_age_by: String,
+
+    /// Memory for caching segments read back from cold storage, in
+    /// megabytes
+    #[arg(long, env = "DRIFTDB_COLD_CACHE_MB", default_value = "256")]
+    cold_cache_mb: u64,
+
+    /// When writes are fsynced: `full` on every write, `async` in the
+    /// background every --async-commit-interval-ms (an OS crash loses at
+    /// most that window), or `fsync_off` never (for bulk loads; an OS
+    /// crash can lose anything since). Sessions override it with
+    /// `SET synchronous_commit`.
+    #[arg(long, env = "DRIFTDB_SYNCHRONOUS", default_value = "full")]
+    synchronous: String,
+
+    /// Time between background fsyncs in `async` mode, in milliseconds
+    #[arg(long, env = "DRIFTDB_ASYNC_COMMIT_INTERVAL_MS", default_value = "200")]
+    async_commit_interval_ms: u64,
+
+    /// Maximum number of parsed statement shapes shared across sessions
+    /// (0 disables the cache)
+    #[arg(long, env = "DRIFTDB_MAX_PREPARED_STATEMENTS", default_value = "1000")]
+    max_prepared_statements: usize,
+
+    /// Memory bound for the shared statement cache in megabytes
+    #[arg(
+        long,
+        env = "DRIFTDB_PREPARED_STATEMENT_CACHE_MB",
+        default_value = "64"
+    )]
+    prepared_statement_cache_mb: usize,
+
+    /// Maximum number of query results shared across sessions and reused
+    /// until a table they read changes (0 disables the cache)
+    #[arg(long, env = "DRIFTDB_RESULT_CACHE_ENTRIES", default_value = "0")]
+    result_cache_entries: usize,
+
+    /// Memory bound for the shared result cache in megabytes
+    #[arg(long, env = "DRIFTDB_RESULT_CACHE_MB", default_value = "64")]
+    result_cache_mb: usize,
+
+    /// Longest a cached result is reused, in seconds
+    #[arg(long, env = "DRIFTDB_RESULT_CACHE_TTL", default_value = "60")]
+    result_cache_ttl: u64,
+
     /// Slow query threshold in milliseconds
     #[arg(long, env = "DRIFTDB_SLOW_QUERY_THRESHOLD", default_value = "1000")]
     slow_query_threshold: u64,
@@ -198,6 +439,12 @@ struct Args {
     )]
     audit_log_path: String,
 
+    /// Statement categories recorded in the __audit_log table
+    /// (comma-separated: ddl, write, read, grant, transaction, other, or all).
+    /// Empty disables statement auditing.
+    #[arg(long, env = "DRIFTDB_AUDIT_STATEMENTS", default_value = "")]
+    audit_statements: String,
+
     /// Enable detection of suspicious activity patterns
     #[arg(
         long,
@@ -251,33 +498,212 @@ async fn main() -> Result<()> {
         metrics::init_metrics()?;
         info!("Metrics collection enabled");
     }
+    metrics::set_table_metrics_limit(args.table_metrics_limit);
+
+    let local_backend: Arc<dyn StorageBackend> = if args.mmap_reads {
+        info!("Reading segments through memory maps");
+        Arc::new(MappedFileBackend::new())
+    } else {
+        LocalFileBackend::shared()
+    };
+    let storage_backend: Arc<dyn StorageBackend> = match &args.cold_storage_dir {
+        Some(cold_dir) => {
+            let age = match args.cold_age_by.as_str() {
+                "write" => TierAge::Written,
+                "access" => TierAge::Accessed,
+                other => {
+                    anyhow::bail!("--cold-age-by must be `write` or `access`, not `{}`", other)
+                }
+            };
+            let policy = TierPolicy {
+                cold_after: std::time::Duration::from_secs(args.cold_after_secs),
+                cache_size: args.cold_cache_mb * 1024 * 1024,
+                age,
+            };
+            info!(
+                "Segments older than {}s move to {:?}",
+                args.cold_after_secs, cold_dir
+            );
+            Arc::new(TieredBackend::new(
+                local_backend,
+                LocalFileBackend::shared(),
+                &args.data_path,
+                cold_dir,
+                policy,
+            ))
+        }
+        None => local_backend,
+    };
 
     // Initialize or open the database
-    let engine = if args.data_path.exists() {
+    let engine = if args.read_only {
+        if !args.data_path.exists() {
+            anyhow::bail!(
+                "--read-only requires an existing database at {:?}",
+                args.data_path
+            );
+        }
+        info!("Opening database at {:?} in read-only mode", args.data_path);
+        let mut engine = Engine::open_with_backend(&args.data_path, storage_backend)?;
+        engine.set_read_only(true);
+        engine
+    } else if args.data_path.exists() {
         info!("Opening existing database at {:?}", args.data_path);
-        Engine::open(&args.data_path)?
+        Engine::open_with_backend(&args.data_path, storage_backend)?
     } else {
         info!("Initializing new database at {:?}", args.data_path);
-        Engine::init(&args.data_path)?
+        Engine::init_with_backend(&args.data_path, storage_backend)?
     };
 
+    if let Some(max_parallel_workers) = args.max_parallel_workers {
+        let optimizer = engine.query_optimizer();
+        optimizer.set_parallel_scan_config(ParallelScanConfig {
+            max_parallel_workers,
+            ..optimizer.parallel_scan_config()
+        });
+        info!("Parallel scans limited to {} workers", max_parallel_workers);
+    }
+
+    let optimizer = engine.query_optimizer();
+    optimizer.set_analyze_config(AnalyzeConfig {
+        sample_rows: args.analyze_sample_rows,
+        ..optimizer.analyze_config()
+    });
+
+    let spill = engine.spill_manager();
+    spill.set_work_mem(args.work_mem * 1024);
+    if let Some(temp_dir) = &args.temp_dir {
+        spill.set_temp_dir(temp_dir);
+        if let Err(e) = spill.remove_stale() {
+            warn!(
+                "Failed to remove stale spill files in {:?}: {}",
+                temp_dir, e
+            );
+        }
+    }
+    info!(
+        "Sorts and hash joins spill to {:?} beyond {}KB",
+        spill.temp_dir(),
+        args.work_mem
+    );
+
+    for (name, value) in [
+        ("max_result_rows", args.max_result_rows),
+        ("max_result_bytes", args.max_result_bytes),
+    ] {
+        if value > 0 {
+            let parameter = driftdb_core::settings::lookup(name)?;
+            let value = parameter
+                .parse(&value.to_string())
+                .map_err(|e| anyhow::anyhow!("--{}: {}", name.replace('_', "-"), e))?;
+            info!("Results limited to {} = {}", name, value);
+            engine.settings().set(parameter, Some(value));
+        }
+    }
+    if args.max_session_memory > 0 {
+        let parameter = driftdb_core::settings::lookup("max_session_memory")?;
+        let value = parameter
+            .parse(&args.max_session_memory.to_string())
+            .map_err(|e| anyhow::anyhow!("--max-session-memory: {}", e))?;
+        info!("Session memory limited to {}", value);
+        engine.settings().set(parameter, Some(value));
+    }
+    for (name, flag, value) in [
+        (
+            "default_transaction_isolation",
+            "--default-isolation",
+            args.default_isolation.replace(['-', '_'], " "),
+        ),
+        (
+            "default_transaction_read_only",
+            "--default-transaction-read-only",
+            args.default_transaction_read_only.to_string(),
+        ),
+    ] {
+        let parameter = driftdb_core::settings::lookup(name)?;
+        let value = parameter
+            .parse(&value)
+            .map_err(|e| anyhow::anyhow!("{}: {}", flag, e))?;
+        if value != parameter.default_value() {
+            info!("New transactions default to {} = {}", name, value);
+            engine.settings().set(parameter, Some(value));
+        }
+    }
+
+    let synchronous: SyncMode = args
+        .synchronous
+        .parse()
+        .map_err(|e| anyhow::anyhow!("--synchronous: {}", e))?;
+    let durability = engine.durability();
+    durability.set_async_commit_interval(std::time::Duration::from_millis(
+        args.async_commit_interval_ms,
+    ));
+    durability.set_mode(synchronous)?;
+    match synchronous {
+        SyncMode::Full => info!("Writes are fsynced as they happen"),
+        SyncMode::Async => info!(
+            "Writes are fsynced every {}ms; an OS crash may lose the last interval",
+            args.async_commit_interval_ms
+        ),
+        SyncMode::FsyncOff => warn!("fsync is off; an OS crash may lose recent writes"),
+    }
+
     let engine = Arc::new(SyncRwLock::new(engine));
 
+    // Start the background compaction scheduler if configured
+    let compaction_config = match &args.compaction_config {
+        Some(path) => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read compaction config {:?}", path))?;
+            serde_json::from_str::<CompactionConfig>(&content)
+                .with_context(|| format!("Invalid compaction config {:?}", path))?
+        }
+        None => CompactionConfig {
+            enabled: args.auto_compaction,
+            ..Default::default()
+        },
+    };
+    let compaction_scheduler = if compaction_config.enabled && !args.read_only {
+        Some(CompactionScheduler::new(compaction_config).spawn(engine.clone()))
+    } else {
+        None
+    };
+
+    let auto_analyzer = if args.auto_analyze && !args.read_only {
+        let config = AutoAnalyzeConfig {
+            enabled: true,
+            threshold: args.autoanalyze_threshold,
+            ..Default::default()
+        };
+        Some(AutoAnalyzer::new(config).spawn(engine.clone()))
+    } else {
+        None
+    };
+
     // Create metrics for the pool
     let pool_metrics = Arc::new(driftdb_core::observability::Metrics::new());
 
+    let pool_mode: PoolMode = args.pool_mode.parse()?;
+    // Queued statements are bounded by the queue timeout, not the pool's
+    let connection_timeout = match pool_mode {
+        PoolMode::Session => args.connection_timeout,
+        PoolMode::Transaction => args.connection_timeout.max(args.statement_queue_timeout),
+    };
+
     // Configure connection pool
     let pool_config = PoolConfig {
         min_connections: args.min_idle_connections,
         max_connections: args.max_connections,
-        connection_timeout: std::time::Duration::from_secs(args.connection_timeout),
+        connection_timeout: std::time::Duration::from_secs(connection_timeout),
         idle_timeout: std::time::Duration::from_secs(args.idle_timeout),
+        warmup: args.pool_warmup,
+        pre_ping: args.pool_pre_ping,
         ..Default::default()
     };
 
     info!(
-        "Creating connection pool with {} max connections",
-        args.max_connections
+        "Creating connection pool with {} max connections ({:?} pooling)",
+        args.max_connections, pool_mode
     );
     let engine_pool = EnginePool::new(engine.clone(), pool_config, pool_metrics.clone())?;
 
@@ -395,15 +821,61 @@ async fn main() -> Result<()> {
         warn!("Failed to grant superuser role to default user: {}", e);
     }
 
+    let statement_audit_config = StatementAuditConfig::parse(&args.audit_statements)?;
+    if statement_audit_config.is_enabled() {
+        info!("Statement audit enabled for: {}", args.audit_statements);
+    }
+    let statement_auditor = Arc::new(StatementAuditor::new(statement_audit_config));
+
+    let statement_cache = Arc::new(StatementCache::new(StatementCacheConfig {
+        max_entries: args.max_prepared_statements,
+        max_bytes: args.prepared_statement_cache_mb * 1024 * 1024,
+    }));
+    if statement_cache.is_enabled() {
+        info!(
+            "Statement cache enabled: max_prepared_statements={}, {} MB",
+            args.max_prepared_statements, args.prepared_statement_cache_mb
+        );
+    }
+
+    let result_cache = Arc::new(ResultCache::new(ResultCacheConfig {
+        max_entries: args.result_cache_entries,
+        max_bytes: args.result_cache_mb * 1024 * 1024,
+        ttl: std::time::Duration::from_secs(args.result_cache_ttl),
+    }));
+    if result_cache.is_enabled() {
+        info!(
+            "Result cache enabled: {} entries, {} MB, TTL {}s",
+            args.result_cache_entries, args.result_cache_mb, args.result_cache_ttl
+        );
+    }
+
     // Create session manager with authentication and rate limiting
-    let session_manager = Arc::new(SessionManager::new(
-        engine_pool.clone(),
-        auth_config,
-        rate_limit_manager.clone(),
-        slow_query_logger.clone(),
-        audit_logger.clone(),
-        rbac_manager.clone(),
-    ));
+    let session_manager = Arc::new(
+        SessionManager::new(
+            engine_pool.clone(),
+            auth_config,
+            rate_limit_manager.clone(),
+            slow_query_logger.clone(),
+            audit_logger.clone(),
+            rbac_manager.clone(),
+        )
+        .with_statement_auditor(statement_auditor)
+        .with_statement_cache(statement_cache)
+        .with_result_cache(result_cache)
+        .with_compaction_tracker(engine.read().compaction_tracker())
+        .with_pool_mode(
+            pool_mode,
+            StatementQueueConfig {
+                max_depth: args.statement_queue_depth,
+                wait_timeout: std::time::Duration::from_secs(args.statement_queue_timeout),
+            },
+        )
+        .with_idle_timeouts(IdleTimeouts::from_secs(
+            args.idle_session_timeout,
+            args.idle_in_transaction_timeout,
+        )),
+    );
 
     // Initialize TLS if enabled
     let tls_manager = if args.tls_enabled {
@@ -524,6 +996,8 @@ async fn main() -> Result<()> {
                         // Also update additional pool metrics
                         metrics::POOL_CONNECTIONS_CREATED
                             .set(stats.connection_stats.total_created as f64);
+                        metrics::POOL_CONNECTIONS_RECREATED
+                            .set(stats.connection_stats.connections_recreated as f64);
                         // Could add more metrics here for transactions and requests
                     }
                 }
@@ -560,6 +1034,25 @@ async fn main() -> Result<()> {
         })
     };
 
+    // Move cold segments to cold storage as they age
+    if args.cold_storage_dir.is_some() {
+        let engine_clone = engine.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let engine = engine_clone.clone();
+                match tokio::task::spawn_blocking(move || engine.read().relocate_cold_segments())
+                    .await
+                {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!("Moving segments to cold storage failed: {}", e),
+                    Err(e) => error!("Cold storage task failed: {}", e),
+                }
+            }
+        });
+    }
+
     // Start alert evaluation task if enabled
     let alert_task = if let Some(ref manager) = alert_manager {
         let manager_clone = manager.clone();
@@ -615,15 +1108,29 @@ async fn main() -> Result<()> {
         })
     };
 
+    // SQL policy for the application listener
+    let validator_config = Arc::new(ValidatorConfig {
+        max_statement_length: args.sql_max_statement_length,
+        allowed_statements: StatementCategory::parse_list(&args.sql_allow_statements)?,
+        denied_statements: StatementCategory::parse_list(&args.sql_deny_statements)?,
+        allow_multiple_statements: args.sql_multi_statement,
+        strip_comments: args.sql_strip_comments,
+    });
+
     // Start PostgreSQL protocol server
-    let pg_server = {
+    let mut pg_server = {
         let session_manager_clone = session_manager.clone();
         let tls_manager_clone = tls_manager.clone();
         let pg_addr = args.listen;
 
         tokio::spawn(async move {
-            let result =
-                start_postgres_server(pg_addr, session_manager_clone, tls_manager_clone).await;
+            let result = start_postgres_server(
+                pg_addr,
+                session_manager_clone,
+                tls_manager_clone,
+                validator_config,
+            )
+            .await;
 
             if let Err(e) = result {
                 error!("PostgreSQL server error: {}", e);
@@ -631,7 +1138,33 @@ async fn main() -> Result<()> {
         })
     };
 
+    // Start the admin PostgreSQL listener with the permissive default policy
+    let mut admin_pg_server = args.admin_listen.map(|admin_addr| {
+        let session_manager_clone = session_manager.clone();
+        let tls_manager_clone = tls_manager.clone();
+
+        tokio::spawn(async move {
+            let result = start_postgres_server(
+                admin_addr,
+                session_manager_clone,
+                tls_manager_clone,
+                Arc::new(ValidatorConfig::default()),
+            )
+            .await;
+
+            if let Err(e) = result {
+                error!("Admin PostgreSQL server error: {}", e);
+            }
+        })
+    });
+
     info!("DriftDB PostgreSQL server listening on {}", args.listen);
+    if let Some(admin_addr) = args.admin_listen {
+        info!(
+            "DriftDB admin PostgreSQL server listening on {}",
+            admin_addr
+        );
+    }
     info!("DriftDB HTTP server listening on {}", args.http_listen);
     info!(
         "Connect with: psql -h {} -p {} -d driftdb",
@@ -645,6 +1178,7 @@ async fn main() -> Result<()> {
     );
 
     // Set up graceful shutdown handling
+    let shutdown_grace = std::time::Duration::from_secs(args.shutdown_grace_seconds);
     let shutdown_signal = async {
         tokio::signal::ctrl_c()
             .await
@@ -656,12 +1190,33 @@ async fn main() -> Result<()> {
     tokio::select! {
         _ = shutdown_signal => {
             info!("Shutting down servers...");
+            // Close the listeners first, so new clients are refused rather
+            // than queued while the open sessions drain
+            pg_server.abort();
+            if let Some(task) = &admin_pg_server {
+                task.abort();
+            }
+            session_manager
+                .drain()
+                .drain(shutdown_grace)
+                .await;
         }
-        result = pg_server => {
+        result = &mut pg_server => {
             if let Err(e) = result {
                 error!("PostgreSQL server task failed: {}", e);
             }
         }
+        result = async {
+            if let Some(task) = admin_pg_server.as_mut() {
+                task.await
+            } else {
+                std::future::pending().await
+            }
+        } => {
+            if let Err(e) = result {
+                error!("Admin PostgreSQL server task failed: {}", e);
+            }
+        }
         result = http_server => {
             if let Err(e) = result {
                 error!("HTTP server task failed: {}", e);
@@ -685,6 +1240,14 @@ async fn main() -> Result<()> {
         }
     }
 
+    if let Some(scheduler) = compaction_scheduler {
+        // Waits for a compaction in progress, so keep it off the runtime
+        let _ = tokio::task::spawn_blocking(move || scheduler.stop()).await;
+    }
+    if let Some(analyzer) = auto_analyzer {
+        let _ = tokio::task::spawn_blocking(move || analyzer.stop()).await;
+    }
+
     // Graceful shutdown of connection pool
     info!("Shutting down connection pool...");
     engine_pool.shutdown().await;
@@ -715,8 +1278,15 @@ async fn start_http_server(
     let health_state = health::HealthState::new(engine.clone(), session_manager.clone());
     let health_router = health::create_health_router(health_state);
 
-    // Build protected router (metrics, performance, alerts)
-    let mut protected = Router::new();
+    // Build protected router (metrics, performance, alerts, lint, sessions)
+    let mut protected = Router::new()
+        .merge(lint_routes::create_router(engine.clone()))
+        .merge(compaction_routes::create_router(
+            engine.read().compaction_tracker(),
+        ))
+        .merge(session_routes::create_router(
+            session_manager.sessions().clone(),
+        ));
 
     // Add metrics router if enabled
     if enable_metrics {
@@ -794,14 +1364,24 @@ async fn start_postgres_server(
     addr: SocketAddr,
     session_manager: Arc<SessionManager>,
     tls_manager: Option<Arc<TlsManager>>,
+    validator_config: Arc<ValidatorConfig>,
 ) -> Result<()> {
     // Bind to address
     let listener = TcpListener::bind(addr).await?;
     info!("PostgreSQL server bound to {}", addr);
 
-    // Accept connections
+    // Accept connections until draining starts; dropping the listener
+    // then refuses new connections.
+    let mut drain_phase = session_manager.drain().subscribe();
     loop {
-        match listener.accept().await {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = drain_phase.wait_for(|phase| *phase != DrainPhase::Running) => {
+                info!("PostgreSQL listener closed for shutdown");
+                return Ok(());
+            }
+        };
+        match accepted {
             Ok((tcp_stream, client_addr)) => {
                 info!("New connection from {}", client_addr);
 
@@ -811,6 +1391,7 @@ async fn start_postgres_server(
 
                 let session_mgr = session_manager.clone();
                 let tls_mgr = tls_manager.clone();
+                let validator_config = validator_config.clone();
                 tokio::spawn(async move {
                     // Handle TLS negotiation if enabled
                     let secure_stream = match &tls_mgr {
@@ -832,7 +1413,7 @@ async fn start_postgres_server(
                     };
 
                     let result = session_mgr
-                        .handle_secure_connection(secure_stream, client_addr)
+                        .handle_secure_connection(secure_stream, client_addr, validator_config)
                         .await;
 
                     if !metrics::REGISTRY.gather().is_empty() {
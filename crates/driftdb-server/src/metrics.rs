@@ -4,7 +4,10 @@
 
 #![allow(dead_code, unused_variables, unused_imports)]
 
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use axum::{extract::State, http::StatusCode, response::Response, routing::get, Router};
 use lazy_static::lazy_static;
@@ -41,6 +44,24 @@ lazy_static! {
         &["query_type"]
     ).unwrap();
 
+    /// Queries by the client's `application_name`
+    pub static ref APPLICATION_QUERY_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("driftdb_application_queries_total", "Total number of queries by application_name")
+            .namespace("driftdb"),
+        &["application", "status"]
+    ).unwrap();
+
+    /// Query execution duration by the client's `application_name`
+    pub static ref APPLICATION_QUERY_DURATION: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("driftdb_application_query_duration_seconds", "Query execution duration in seconds by application_name")
+            .namespace("driftdb")
+            .buckets(vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0]),
+        &["application"]
+    ).unwrap();
+
+    /// Application names seen so far, bounded by `MAX_APPLICATION_LABELS`
+    static ref APPLICATION_LABELS: RwLock<HashSet<String>> = RwLock::new(HashSet::new());
+
     /// Active database connections
     pub static ref ACTIVE_CONNECTIONS: Gauge = Gauge::new(
         "driftdb_active_connections",
@@ -119,6 +140,12 @@ lazy_static! {
         "Total number of connections created by the pool"
     ).unwrap();
 
+    /// Pool connections replaced after failing a pre-ping
+    pub static ref POOL_CONNECTIONS_RECREATED: Gauge = Gauge::new(
+        "driftdb_pool_connections_recreated_total",
+        "Total number of pooled connections recreated after failing a pre-ping"
+    ).unwrap();
+
     /// Connection encryption status
     pub static ref CONNECTION_ENCRYPTION: CounterVec = CounterVec::new(
         Opts::new("driftdb_connections_by_encryption", "Total connections by encryption status")
@@ -178,6 +205,19 @@ lazy_static! {
         "Pool utilization percentage (active / total)"
     ).unwrap();
 
+    /// Time statements spent queued for a backend in transaction pool mode
+    pub static ref STATEMENT_QUEUE_WAIT_TIME: HistogramVec = HistogramVec::new(
+        HistogramOpts::new("driftdb_statement_queue_wait_seconds", "Time statements waited for a pooled backend")
+            .namespace("driftdb")
+            .buckets(vec![0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 30.0]),
+        &["result"] // acquired, timeout, rejected
+    ).unwrap();
+
+    pub static ref STATEMENT_QUEUE_DEPTH: Gauge = Gauge::new(
+        "driftdb_statement_queue_depth",
+        "Number of statements currently waiting for a pooled backend"
+    ).unwrap();
+
     /// WAL (Write-Ahead Log) metrics
     pub static ref WAL_WRITES_TOTAL: Counter = Counter::new(
         "driftdb_wal_writes_total",
@@ -259,13 +299,19 @@ lazy_static! {
         "Total bytes written to disk"
     ).unwrap();
 
-    /// Replication metrics (for future use)
+    /// Replication metrics
     pub static ref REPLICATION_LAG_SECONDS: GaugeVec = GaugeVec::new(
         Opts::new("driftdb_replication_lag_seconds", "Replication lag in seconds")
             .namespace("driftdb"),
         &["replica"]
     ).unwrap();
 
+    pub static ref REPLICATION_LAG_BYTES: GaugeVec = GaugeVec::new(
+        Opts::new("driftdb_replication_lag_bytes", "Replication lag in bytes")
+            .namespace("driftdb"),
+        &["replica"]
+    ).unwrap();
+
     pub static ref REPLICATION_BYTES_SENT: CounterVec = CounterVec::new(
         Opts::new("driftdb_replication_bytes_sent_total", "Total bytes sent to replicas")
             .namespace("driftdb"),
@@ -344,12 +390,50 @@ lazy_static! {
             .buckets(vec![1.0, 10.0, 100.0, 1000.0, 10000.0, 100000.0]),
         &["query_type"]
     ).unwrap();
+
+    /// Tiered storage metrics, mirrored from the engine's counters
+    pub static ref STORAGE_TIER_READS: GaugeVec = GaugeVec::new(
+        Opts::new("driftdb_storage_tier_reads", "Segment reads by where they were served from (hot, cache, cold)")
+            .namespace("driftdb"),
+        &["source"]
+    ).unwrap();
+
+    pub static ref STORAGE_TIER_RELOCATED: GaugeVec = GaugeVec::new(
+        Opts::new("driftdb_storage_tier_relocated", "Segments moved to cold storage and back")
+            .namespace("driftdb"),
+        &["direction"]
+    ).unwrap();
+}
+
+// A block of its own: one more item in the block above passes
+// `lazy_static!`'s recursion limit
+lazy_static! {
+    /// Statements by the table they ran against, labeled per `table_label`
+    pub static ref TABLE_OPERATIONS_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("driftdb_table_operations_total", "Total inserts, updates, deletes and selects by table")
+            .namespace("driftdb"),
+        &["table", "operation"]
+    ).unwrap();
+
+    /// Rows read from each table before WHERE filtered them, labeled per
+    /// `table_label`
+    pub static ref TABLE_ROWS_SCANNED_TOTAL: CounterVec = CounterVec::new(
+        Opts::new("driftdb_table_rows_scanned_total", "Total rows read by table")
+            .namespace("driftdb"),
+        &["table"]
+    ).unwrap();
+
+    /// Tables with a label of their own, bounded by the table metrics
+    /// limit
+    static ref TABLE_LABELS: RwLock<TableLabels> = RwLock::new(TableLabels::new());
 }
 
 /// Initialize all metrics with the registry
 pub fn init_metrics() -> anyhow::Result<()> {
     REGISTRY.register(Box::new(QUERY_TOTAL.clone()))?;
     REGISTRY.register(Box::new(QUERY_DURATION.clone()))?;
+    REGISTRY.register(Box::new(APPLICATION_QUERY_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(APPLICATION_QUERY_DURATION.clone()))?;
     REGISTRY.register(Box::new(ACTIVE_CONNECTIONS.clone()))?;
     REGISTRY.register(Box::new(CONNECTIONS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(DATABASE_SIZE_BYTES.clone()))?;
@@ -362,6 +446,7 @@ pub fn init_metrics() -> anyhow::Result<()> {
     REGISTRY.register(Box::new(POOL_ACTIVE.clone()))?;
     REGISTRY.register(Box::new(POOL_WAIT_TIME.clone()))?;
     REGISTRY.register(Box::new(POOL_CONNECTIONS_CREATED.clone()))?;
+    REGISTRY.register(Box::new(POOL_CONNECTIONS_RECREATED.clone()))?;
     REGISTRY.register(Box::new(CONNECTION_ENCRYPTION.clone()))?;
 
     // Register enhanced metrics
@@ -373,6 +458,8 @@ pub fn init_metrics() -> anyhow::Result<()> {
     REGISTRY.register(Box::new(POOL_TIMEOUTS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(POOL_ERRORS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(POOL_UTILIZATION.clone()))?;
+    REGISTRY.register(Box::new(STATEMENT_QUEUE_WAIT_TIME.clone()))?;
+    REGISTRY.register(Box::new(STATEMENT_QUEUE_DEPTH.clone()))?;
     REGISTRY.register(Box::new(WAL_WRITES_TOTAL.clone()))?;
     REGISTRY.register(Box::new(WAL_SYNC_DURATION.clone()))?;
     REGISTRY.register(Box::new(WAL_SIZE_BYTES.clone()))?;
@@ -383,11 +470,14 @@ pub fn init_metrics() -> anyhow::Result<()> {
     REGISTRY.register(Box::new(CACHE_EVICTIONS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(INDEX_SCANS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(TABLE_SCANS_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(TABLE_OPERATIONS_TOTAL.clone()))?;
+    REGISTRY.register(Box::new(TABLE_ROWS_SCANNED_TOTAL.clone()))?;
     REGISTRY.register(Box::new(DISK_READS_TOTAL.clone()))?;
     REGISTRY.register(Box::new(DISK_WRITES_TOTAL.clone()))?;
     REGISTRY.register(Box::new(DISK_READ_BYTES_TOTAL.clone()))?;
     REGISTRY.register(Box::new(DISK_WRITE_BYTES_TOTAL.clone()))?;
     REGISTRY.register(Box::new(REPLICATION_LAG_SECONDS.clone()))?;
+    REGISTRY.register(Box::new(REPLICATION_LAG_BYTES.clone()))?;
     REGISTRY.register(Box::new(REPLICATION_BYTES_SENT.clone()))?;
     REGISTRY.register(Box::new(REPLICATION_STATUS.clone()))?;
     REGISTRY.register(Box::new(RATE_LIMIT_HITS_TOTAL.clone()))?;
@@ -400,10 +490,12 @@ pub fn init_metrics() -> anyhow::Result<()> {
     REGISTRY.register(Box::new(SLOW_QUERIES_TOTAL.clone()))?;
     REGISTRY.register(Box::new(QUERY_ROWS_RETURNED.clone()))?;
     REGISTRY.register(Box::new(QUERY_ROWS_AFFECTED.clone()))?;
+    REGISTRY.register(Box::new(STORAGE_TIER_READS.clone()))?;
+    REGISTRY.register(Box::new(STORAGE_TIER_RELOCATED.clone()))?;
 
     debug!(
         "Metrics initialized successfully - {} metrics registered",
-        51
+        53
     );
     Ok(())
 }
@@ -518,6 +610,27 @@ fn collect_database_size_metrics(engine: &Engine) -> anyhow::Result<()> {
         .with_label_values(&["_total", "all"])
         .set(total_db_size as f64);
 
+    if let Some(tiers) = engine.storage_tier_stats() {
+        for (source, reads) in [
+            ("hot", tiers.hot_reads),
+            ("cache", tiers.cache_hits),
+            ("cold", tiers.cold_reads),
+        ] {
+            STORAGE_TIER_READS
+                .with_label_values(&[source])
+                .set(reads as f64);
+        }
+        STORAGE_TIER_RELOCATED
+            .with_label_values(&["to_cold"])
+            .set(tiers.relocated as f64);
+        STORAGE_TIER_RELOCATED
+            .with_label_values(&["to_hot"])
+            .set(tiers.recalled as f64);
+        CACHE_SIZE_BYTES
+            .with_label_values(&["cold_segments"])
+            .set(tiers.cached_bytes as f64);
+    }
+
     Ok(())
 }
 
@@ -587,6 +700,48 @@ pub fn record_query(query_type: &str, status: &str, duration_seconds: f64) {
         .observe(duration_seconds);
 }
 
+/// Clients choose their own `application_name`, so past this many
+/// distinct names further ones are counted under `other`
+const MAX_APPLICATION_LABELS: usize = 100;
+
+/// Label for `application_name`: `unnamed` when unset, `other` once
+/// `MAX_APPLICATION_LABELS` names have been seen
+fn application_label(application_name: &str) -> String {
+    if application_name.is_empty() {
+        return "unnamed".to_string();
+    }
+    capped_label(
+        &APPLICATION_LABELS,
+        MAX_APPLICATION_LABELS,
+        application_name,
+    )
+}
+
+/// `name` while `labels` has room for it or already holds it, else `other`
+fn capped_label(labels: &RwLock<HashSet<String>>, limit: usize, name: &str) -> String {
+    if labels.read().contains(name) {
+        return name.to_string();
+    }
+    let mut labels = labels.write();
+    if labels.len() < limit {
+        labels.insert(name.to_string());
+        name.to_string()
+    } else {
+        "other".to_string()
+    }
+}
+
+/// Record a query execution against the client's `application_name`
+pub fn record_application_query(application_name: &str, status: &str, duration_seconds: f64) {
+    let label = application_label(application_name);
+    APPLICATION_QUERY_TOTAL
+        .with_label_values(&[&label, status])
+        .inc();
+    APPLICATION_QUERY_DURATION
+        .with_label_values(&[&label])
+        .observe(duration_seconds);
+}
+
 /// Record a new connection
 pub fn record_connection() {
     CONNECTIONS_TOTAL.inc();
@@ -673,6 +828,18 @@ pub fn update_pool_utilization(utilization_percent: f64) {
     POOL_UTILIZATION.set(utilization_percent);
 }
 
+/// Record how long a statement waited for a pooled backend
+pub fn record_statement_queue_wait(result: &str, duration_seconds: f64) {
+    STATEMENT_QUEUE_WAIT_TIME
+        .with_label_values(&[result])
+        .observe(duration_seconds);
+}
+
+/// Update the number of statements waiting for a pooled backend
+pub fn update_statement_queue_depth(depth: usize) {
+    STATEMENT_QUEUE_DEPTH.set(depth as f64);
+}
+
 /// Record WAL write
 pub fn record_wal_write() {
     WAL_WRITES_TOTAL.inc();
@@ -725,6 +892,116 @@ pub fn record_table_scan(table: &str) {
     TABLE_SCANS_TOTAL.with_label_values(&[table]).inc();
 }
 
+/// Tables given a label of their own in the per-table metrics unless
+/// `set_table_metrics_limit` says otherwise
+pub const DEFAULT_TABLE_METRICS_LIMIT: usize = 100;
+
+static TABLE_METRICS_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_TABLE_METRICS_LIMIT);
+
+/// Label the `limit` most active tables by name and count the rest under
+/// `other`; 0 turns the per-table metrics off
+pub fn set_table_metrics_limit(limit: usize) {
+    TABLE_METRICS_LIMIT.store(limit, Ordering::Relaxed);
+}
+
+/// How often the labeled tables are re-picked from recent activity
+const TABLE_RANK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Every `operation` label of the per-table operation counter
+const TABLE_OPERATIONS: [&str; 4] = ["insert", "update", "delete", "select"];
+
+/// Which tables get a label of their own. Tables are labeled as they show
+/// up while there is room; every `TABLE_RANK_INTERVAL` the labels go to
+/// the tables most active in that interval, so a table that becomes hot
+/// later takes over from one that went quiet.
+struct TableLabels {
+    labeled: HashSet<String>,
+    /// Statements recorded per table since the last re-pick
+    activity: HashMap<String, u64>,
+    ranked_at: Instant,
+}
+
+impl TableLabels {
+    fn new() -> Self {
+        Self {
+            labeled: HashSet::new(),
+            activity: HashMap::new(),
+            ranked_at: Instant::now(),
+        }
+    }
+
+    /// Count activity on `table` and return its label, along with the
+    /// tables that lost theirs if the labels were re-picked
+    fn label(&mut self, limit: usize, table: &str, now: Instant) -> (String, Vec<String>) {
+        *self.activity.entry(table.to_string()).or_insert(0) += 1;
+        let mut dropped = Vec::new();
+        if now.duration_since(self.ranked_at) >= TABLE_RANK_INTERVAL {
+            dropped = self.rank(limit);
+            self.ranked_at = now;
+        }
+        let label = if self.labeled.contains(table) || self.labeled.len() < limit {
+            self.labeled.insert(table.to_string());
+            table.to_string()
+        } else {
+            "other".to_string()
+        };
+        (label, dropped)
+    }
+
+    /// Give the labels to the `limit` most active tables, returning the
+    /// tables that lost theirs
+    fn rank(&mut self, limit: usize) -> Vec<String> {
+        let mut ranked: Vec<(String, u64)> = self.activity.drain().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        let top: HashSet<String> = ranked
+            .into_iter()
+            .take(limit)
+            .map(|(table, _)| table)
+            .collect();
+        let dropped = self.labeled.difference(&top).cloned().collect();
+        self.labeled = top;
+        dropped
+    }
+}
+
+/// Label for `table`, `None` when per-table metrics are off. A table that
+/// loses its label has its series removed, so the number of series stays
+/// bounded by the limit.
+fn table_label(table: &str) -> Option<String> {
+    let limit = TABLE_METRICS_LIMIT.load(Ordering::Relaxed);
+    if limit == 0 {
+        return None;
+    }
+    let (label, dropped) = TABLE_LABELS.write().label(limit, table, Instant::now());
+    for table in dropped {
+        for operation in TABLE_OPERATIONS {
+            let _ = TABLE_OPERATIONS_TOTAL.remove_label_values(&[&table, operation]);
+        }
+        let _ = TABLE_ROWS_SCANNED_TOTAL.remove_label_values(&[&table]);
+    }
+    Some(label)
+}
+
+/// Record an `insert`, `update`, `delete` or `select` against `table`
+pub fn record_table_operation(table: &str, operation: &str) {
+    if let Some(label) = table_label(table) {
+        TABLE_OPERATIONS_TOTAL
+            .with_label_values(&[&label, operation])
+            .inc();
+    }
+}
+
+/// Record the rows a statement read from each table
+pub fn record_table_rows_scanned(scanned: &BTreeMap<String, u64>) {
+    for (table, rows) in scanned {
+        if let Some(label) = table_label(table) {
+            TABLE_ROWS_SCANNED_TOTAL
+                .with_label_values(&[&label])
+                .inc_by(*rows as f64);
+        }
+    }
+}
+
 /// Record disk read
 pub fn record_disk_read(bytes: usize) {
     DISK_READS_TOTAL.inc();
@@ -744,6 +1021,13 @@ pub fn update_replication_lag(replica: &str, lag_seconds: f64) {
         .set(lag_seconds);
 }
 
+/// Update replication lag in bytes
+pub fn update_replication_lag_bytes(replica: &str, lag_bytes: u64) {
+    REPLICATION_LAG_BYTES
+        .with_label_values(&[replica])
+        .set(lag_bytes as f64);
+}
+
 /// Record replication bytes sent
 pub fn record_replication_bytes_sent(replica: &str, bytes: usize) {
     REPLICATION_BYTES_SENT
@@ -871,6 +1155,75 @@ mod tests {
         assert!(!metric_families.is_empty());
     }
 
+    #[test]
+    fn test_record_application_query() {
+        record_application_query("reporting", "success", 0.2);
+        record_application_query("", "error", 0.1);
+
+        assert!(
+            APPLICATION_QUERY_TOTAL
+                .with_label_values(&["reporting", "success"])
+                .get()
+                >= 1.0
+        );
+        assert!(
+            APPLICATION_QUERY_TOTAL
+                .with_label_values(&["unnamed", "error"])
+                .get()
+                >= 1.0
+        );
+    }
+
+    #[test]
+    fn test_capped_label() {
+        let labels = RwLock::new(HashSet::new());
+        assert_eq!(capped_label(&labels, 2, "users"), "users");
+        assert_eq!(capped_label(&labels, 2, "orders"), "orders");
+        assert_eq!(capped_label(&labels, 2, "audit_log"), "other");
+        // Tables already labeled keep their label
+        assert_eq!(capped_label(&labels, 2, "users"), "users");
+    }
+
+    #[test]
+    fn test_table_labels_follow_activity() {
+        let mut labels = TableLabels::new();
+        let start = labels.ranked_at;
+        assert_eq!(labels.label(2, "users", start).0, "users");
+        assert_eq!(labels.label(2, "orders", start).0, "orders");
+        assert_eq!(labels.label(2, "audit_log", start).0, "other");
+
+        // audit_log turns hot while orders goes quiet
+        for _ in 0..5 {
+            labels.label(2, "audit_log", start);
+            labels.label(2, "users", start);
+        }
+        let later = start + TABLE_RANK_INTERVAL;
+        let (label, dropped) = labels.label(2, "audit_log", later);
+        assert_eq!(label, "audit_log");
+        assert_eq!(dropped, ["orders"]);
+        assert_eq!(labels.label(2, "users", later).0, "users");
+        assert_eq!(labels.label(2, "orders", later).0, "other");
+    }
+
+    #[test]
+    fn test_record_table_activity() {
+        record_table_operation("metrics_test_orders", "insert");
+        record_table_rows_scanned(&BTreeMap::from([("metrics_test_orders".to_string(), 42)]));
+
+        assert!(
+            TABLE_OPERATIONS_TOTAL
+                .with_label_values(&["metrics_test_orders", "insert"])
+                .get()
+                >= 1.0
+        );
+        assert!(
+            TABLE_ROWS_SCANNED_TOTAL
+                .with_label_values(&["metrics_test_orders"])
+                .get()
+                >= 42.0
+        );
+    }
+
     #[tokio::test]
     async fn test_metrics_endpoint() {
         use crate::protocol::auth::AuthConfig;
@@ -894,7 +1247,10 @@ mod tests {
         let auth_config = AuthConfig::default();
         let rate_limit_manager = Arc::new(RateLimitManager::new(Default::default(), pool_metrics));
         let slow_query_logger = Arc::new(SlowQueryLogger::new(SlowQueryConfig::default()));
-        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig::default()));
+        let audit_logger = Arc::new(SecurityAuditLogger::new(AuditConfig {
+            log_to_file: false,
+            ..AuditConfig::default()
+        }));
         let rbac_manager = Arc::new(RbacManager::new());
 
         let session_manager = Arc::new(SessionManager::new(
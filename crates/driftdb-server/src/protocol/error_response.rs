@@ -0,0 +1,224 @@
+//! ErrorResponse for a statement the executor failed. Besides the SQLSTATE
+//! from [`error_codes::for_query_error`], DriftDB's message is split into
+//! PostgreSQL's message, detail and hint fields where it carries them, and
+//! the position of the offending token is reported so psql can point at it.
+
+use super::{error_codes, Message};
+
+/// ErrorResponse for `sql` failing with `error`
+pub fn query_error(sql: &str, error: &str) -> Message {
+    let code = error_codes::for_query_error(error);
+    let mut message = error;
+    let mut detail = None;
+    let mut hint = None;
+    if code == error_codes::UNIQUE_VIOLATION {
+        // `... on table "users": key (id)=(1) already exists`
+        if let Some(at) = error.find(": key (") {
+            message = &error[..at];
+            detail = Some(sentence(&error[at + 2..]));
+        }
+    } else if code == error_codes::PROGRAM_LIMIT_EXCEEDED || code == error_codes::OUT_OF_MEMORY {
+        // `...; add a LIMIT or raise max_result_rows`
+        if let Some((head, advice)) = error.rsplit_once("; ") {
+            message = head;
+            hint = Some(sentence(advice));
+        }
+    }
+
+    let mut response = Message::error(code, message);
+    if let Some(detail) = detail {
+        response = response.with_detail(&detail);
+    }
+    if let Some(hint) = hint {
+        response = response.with_hint(&hint);
+    }
+    if let Some(position) = error_position(sql, error) {
+        response = response.with_position(position);
+    }
+    response
+}
+
+/// `text` capitalized and ending in a period, as PostgreSQL writes
+/// details and hints
+fn sentence(text: &str) -> String {
+    let mut chars = text.chars();
+    let first: String = chars
+        .next()
+        .into_iter()
+        .flat_map(char::to_uppercase)
+        .collect();
+    format!("{}{}.", first, chars.as_str().trim_end_matches('.'))
+}
+
+/// 1-based character position in `sql` of what `error` is about: where
+/// the parser gave up, or the first mention of the table, column or
+/// function that doesn't exist
+fn error_position(sql: &str, error: &str) -> Option<usize> {
+    if error.contains("found: EOF") {
+        return Some(sql.trim_end().trim_end_matches(';').chars().count() + 1);
+    }
+    if let Some(at) = error.rfind(" at Line: ") {
+        let (line, column) = error[at + " at Line: ".len()..].split_once(", Column: ")?;
+        let line = line.parse().ok()?;
+        let column: String = column.chars().take_while(char::is_ascii_digit).collect();
+        return line_column_position(sql, line, column.parse().ok()?);
+    }
+    token_position(sql, &missing_name(error)?)
+}
+
+fn line_column_position(sql: &str, line: usize, column: usize) -> Option<usize> {
+    let mut position = 0;
+    for (index, text) in sql.split('\n').enumerate() {
+        let width = text.chars().count();
+        if index + 1 == line {
+            return Some(position + column.clamp(1, width + 1));
+        }
+        position += width + 1;
+    }
+    None
+}
+
+/// The name in `Table not found: x`, `relation "x" does not exist`,
+/// `column "x" does not exist` or `function x(...) does not exist`
+fn missing_name(error: &str) -> Option<String> {
+    if let Some((_, table)) = error.split_once("Table not found: ") {
+        return table.split_whitespace().next().map(str::to_string);
+    }
+    for prefix in ["relation \"", "column \""] {
+        if let Some((_, rest)) = error.split_once(prefix) {
+            if let Some((name, _)) = rest.split_once("\" does not exist") {
+                return Some(name.to_string());
+            }
+        }
+    }
+    let (_, call) = error.split_once("function ")?;
+    let (call, _) = call.split_once(") does not exist")?;
+    call.split_once('(').map(|(name, _)| name.to_string())
+}
+
+/// 1-based character position of the first whole-word, case-insensitive
+/// mention of `name` in `sql` outside string literals
+fn token_position(sql: &str, name: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let sql: Vec<char> = sql.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    if name.is_empty() || name.len() > sql.len() {
+        return None;
+    }
+    (0..=sql.len() - name.len())
+        .find(|&start| {
+            let end = start + name.len();
+            sql[start..end]
+                .iter()
+                .zip(&name)
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+                && (start == 0 || !is_word(sql[start - 1]))
+                && sql.get(end).is_none_or(|&c| !is_word(c))
+                && sql[..start].iter().filter(|&&c| c == '\'').count() % 2 == 0
+        })
+        .map(|start| start + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(message: &Message, code: u8) -> Option<&str> {
+        match message {
+            Message::ErrorResponse { fields } => fields.get(&code).map(String::as_str),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_sqlstate_for_common_errors() {
+        let response = query_error(
+            "INSERT INTO users (id) VALUES (1)",
+            "Query error: Invalid query: duplicate key value violates unique constraint \
+             on table \"users\": key (id)=(1) already exists",
+        );
+        assert_eq!(field(&response, b'C'), Some(error_codes::UNIQUE_VIOLATION));
+        assert_eq!(field(&response, b'S'), Some("ERROR"));
+        assert_eq!(field(&response, b'V'), Some("ERROR"));
+        assert_eq!(
+            field(&response, b'M'),
+            Some("Query error: Invalid query: duplicate key value violates unique constraint on table \"users\"")
+        );
+        assert_eq!(field(&response, b'D'), Some("Key (id)=(1) already exists."));
+
+        let response = query_error(
+            "SELECT * FROM orders o JOIN custmers c ON o.customer_id = c.id",
+            "Query error: Table not found: custmers",
+        );
+        assert_eq!(field(&response, b'C'), Some(error_codes::UNDEFINED_TABLE));
+        assert_eq!(field(&response, b'P'), Some("29"));
+
+        let response = query_error(
+            "SELECT * FROM orders",
+            "Query error: result too large: query returned 11 rows, over max_result_rows = 10; \
+             add a LIMIT or raise max_result_rows",
+        );
+        assert_eq!(
+            field(&response, b'C'),
+            Some(error_codes::PROGRAM_LIMIT_EXCEEDED)
+        );
+        assert_eq!(
+            field(&response, b'H'),
+            Some("Add a LIMIT or raise max_result_rows.")
+        );
+        assert_eq!(field(&response, b'P'), None);
+    }
+
+    #[test]
+    fn test_position_of_the_offending_token() {
+        // Where the parser stopped, counted in characters across lines
+        let sql = "SELECT id\nFROM café WHERE WHERE";
+        assert_eq!(
+            error_position(
+                sql,
+                "Parse error: sql parser error: Expected: end of statement, found: WHERE \
+                 at Line: 2, Column: 17"
+            ),
+            Some(27)
+        );
+        assert_eq!(
+            error_position(
+                "SELECT id FROM",
+                "Parse error: sql parser error: Expected: identifier, found: EOF"
+            ),
+            Some(15)
+        );
+
+        // The first whole-word mention, skipping literals and longer names
+        assert_eq!(
+            error_position(
+                "SELECT names, 'nam' FROM users WHERE u.NAM = 1",
+                "Query error: Invalid query: column \"nam\" does not exist"
+            ),
+            Some(40)
+        );
+        assert_eq!(
+            error_position(
+                "SELECT id, lower(age) FROM users",
+                "Query error: Invalid query: function lower(integer) does not exist"
+            ),
+            Some(12)
+        );
+        assert_eq!(
+            error_position("SELECT 1", "Query error: Deadlock detected"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_notice_is_encoded() {
+        let bytes = Message::notice("row-level security hid 2 rows").encode();
+        assert_eq!(bytes[0], b'N');
+        let body = String::from_utf8_lossy(&bytes[5..]);
+        assert!(body.contains("row-level security hid 2 rows"), "{}", body);
+        assert_eq!(
+            u32::from_be_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]) as usize,
+            bytes.len() - 1
+        );
+    }
+}
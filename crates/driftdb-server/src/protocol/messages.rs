@@ -255,8 +255,12 @@ impl Message {
                 }
             }
 
-            Message::ErrorResponse { fields } => {
-                buf.put_u8(b'E');
+            Message::ErrorResponse { fields } | Message::NoticeResponse { fields } => {
+                buf.put_u8(if matches!(self, Message::ErrorResponse { .. }) {
+                    b'E'
+                } else {
+                    b'N'
+                });
 
                 // Calculate length
                 let mut len = 4; // Length itself
@@ -309,15 +313,51 @@ impl Message {
     pub fn error(code: &str, message: &str) -> Self {
         let mut fields = HashMap::new();
         fields.insert(b'S', "ERROR".to_string());
+        fields.insert(b'V', "ERROR".to_string());
         fields.insert(b'C', code.to_string());
         fields.insert(b'M', message.to_string());
         Message::ErrorResponse { fields }
     }
 
-    #[allow(dead_code)]
+    /// An error that ends the session; the server closes the connection
+    /// after sending it.
+    pub fn fatal(code: &str, message: &str) -> Self {
+        let mut fields = HashMap::new();
+        fields.insert(b'S', "FATAL".to_string());
+        fields.insert(b'V', "FATAL".to_string());
+        fields.insert(b'C', code.to_string());
+        fields.insert(b'M', message.to_string());
+        Message::ErrorResponse { fields }
+    }
+
+    /// This ErrorResponse with a `DETAIL` line
+    pub fn with_detail(self, detail: &str) -> Self {
+        self.with_field(b'D', detail.to_string())
+    }
+
+    /// This ErrorResponse with a `HINT` line
+    pub fn with_hint(self, hint: &str) -> Self {
+        self.with_field(b'H', hint.to_string())
+    }
+
+    /// This ErrorResponse pointing at the 1-based character `position` in
+    /// the statement, which psql marks with a caret
+    pub fn with_position(self, position: usize) -> Self {
+        self.with_field(b'P', position.to_string())
+    }
+
+    fn with_field(mut self, code: u8, value: String) -> Self {
+        if let Message::ErrorResponse { fields } | Message::NoticeResponse { fields } = &mut self {
+            fields.insert(code, value);
+        }
+        self
+    }
+
     pub fn notice(message: &str) -> Self {
         let mut fields = HashMap::new();
         fields.insert(b'S', "NOTICE".to_string());
+        fields.insert(b'V', "NOTICE".to_string());
+        fields.insert(b'C', "00000".to_string());
         fields.insert(b'M', message.to_string());
         Message::NoticeResponse { fields }
     }
@@ -5,6 +5,7 @@
 
 pub mod auth;
 pub mod codec;
+pub mod error_response;
 pub mod messages;
 
 pub use messages::Message;
@@ -13,6 +14,21 @@ pub use messages::Message;
 #[allow(dead_code)]
 pub const PROTOCOL_VERSION: i32 = 196608; // 3.0
 
+/// DriftDB extensions and protocol features this server supports,
+/// advertised at startup in the `driftdb_features` ParameterStatus
+pub const SERVER_FEATURES: &[&str] = &[
+    "extended_query",
+    "temporal",
+    "cancel_request",
+    "schema_introspection",
+];
+
+/// The `server_version` ParameterStatus: the PostgreSQL version clients
+/// should assume, with the DriftDB release
+pub fn server_version() -> String {
+    format!("14.0 (DriftDB {})", env!("CARGO_PKG_VERSION"))
+}
+
 /// Transaction status for ReadyForQuery message
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TransactionStatus {
@@ -39,17 +55,20 @@ impl TransactionStatus {
 #[allow(dead_code)]
 pub enum DataType {
     Bool = 16,
+    Bytea = 17,
     Int2 = 21,
     Int4 = 23,
     Int8 = 20,
     Float4 = 700,
     Float8 = 701,
+    Numeric = 1700,
     Text = 25,
     Varchar = 1043,
     Timestamp = 1114,
     TimestampTz = 1184,
     Json = 114,
     Jsonb = 3802,
+    Uuid = 2950,
 }
 
 /// Field description for row results
@@ -85,6 +104,7 @@ impl FieldDescription {
             DataType::Int8 => 8,
             DataType::Float4 => 4,
             DataType::Float8 => 8,
+            DataType::Uuid => 16,
             _ => -1, // Variable length
         }
     }
@@ -118,8 +138,108 @@ pub mod error_codes {
     pub const INVALID_CURSOR_NAME: &str = "34000";
     pub const INVALID_SQL_STATEMENT_NAME: &str = "26000";
     pub const UNDEFINED_TABLE: &str = "42P01";
+    pub const UNDEFINED_OBJECT: &str = "42704";
     pub const SYNTAX_ERROR: &str = "42601";
     pub const INSUFFICIENT_PRIVILEGE: &str = "42501";
+    pub const OUT_OF_MEMORY: &str = "53200";
     pub const TOO_MANY_CONNECTIONS: &str = "53300";
+    pub const QUERY_CANCELED: &str = "57014";
+    pub const ADMIN_SHUTDOWN: &str = "57P01";
+    pub const CANNOT_CONNECT_NOW: &str = "57P03";
+    pub const IDLE_SESSION_TIMEOUT: &str = "57P05";
+    pub const IDLE_IN_TRANSACTION_SESSION_TIMEOUT: &str = "25P03";
     pub const INTERNAL_ERROR: &str = "XX000";
+    pub const NOT_NULL_VIOLATION: &str = "23502";
+    pub const FOREIGN_KEY_VIOLATION: &str = "23503";
+    pub const UNIQUE_VIOLATION: &str = "23505";
+    pub const CHECK_VIOLATION: &str = "23514";
+    pub const INVALID_TEXT_REPRESENTATION: &str = "22P02";
+    pub const READ_ONLY_SQL_TRANSACTION: &str = "25006";
+    pub const DEADLOCK_DETECTED: &str = "40P01";
+    pub const PROGRAM_LIMIT_EXCEEDED: &str = "54000";
+
+    /// SQLSTATE for a statement that failed with `message`, so clients can
+    /// tell constraint violations apart; anything unrecognized keeps the
+    /// generic syntax error code.
+    pub fn for_query_error(message: &str) -> &'static str {
+        if message.contains("duplicate key value violates unique constraint") {
+            UNIQUE_VIOLATION
+        } else if message.contains("Foreign key violation") {
+            FOREIGN_KEY_VIOLATION
+        } else if message.contains("NOT NULL constraint violation") {
+            NOT_NULL_VIOLATION
+        } else if message.contains("violates check constraint") {
+            CHECK_VIOLATION
+        } else if message.contains("invalid input syntax for type") {
+            INVALID_TEXT_REPRESENTATION
+        } else if message.contains("in read-only mode")
+            || message.contains("in a read-only transaction")
+        {
+            READ_ONLY_SQL_TRANSACTION
+        } else if message.contains("Deadlock detected") {
+            DEADLOCK_DETECTED
+        } else if message.contains("result too large") {
+            PROGRAM_LIMIT_EXCEEDED
+        } else if message.contains("out of memory: ") {
+            OUT_OF_MEMORY
+        } else if message.contains("Table not found") {
+            UNDEFINED_TABLE
+        } else {
+            SYNTAX_ERROR
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_for_query_error() {
+            assert_eq!(
+                for_query_error(
+                    "Invalid query: duplicate key value violates unique constraint on table \"users\": key (id)=(1) already exists"
+                ),
+                UNIQUE_VIOLATION
+            );
+            assert_eq!(
+                for_query_error("Foreign key violation: value 9 in column 'user_id' does not exist in 'users'.'id'"),
+                FOREIGN_KEY_VIOLATION
+            );
+            assert_eq!(
+                for_query_error("invalid input syntax for type uuid: \"nope\""),
+                INVALID_TEXT_REPRESENTATION
+            );
+            assert_eq!(for_query_error("Table not found: missing"), UNDEFINED_TABLE);
+            assert_eq!(
+                for_query_error(
+                    "Deadlock detected: transaction 2 waits for a lock on users:1 held by transaction 1; \
+                     transaction 1 waits for a lock on users:2 held by transaction 2; \
+                     transaction 2 was aborted"
+                ),
+                DEADLOCK_DETECTED
+            );
+            assert_eq!(
+                for_query_error(
+                    "result too large: query returned 11 rows, over max_result_rows = 10; \
+                     add a LIMIT or raise max_result_rows"
+                ),
+                PROGRAM_LIMIT_EXCEEDED
+            );
+            assert_eq!(
+                for_query_error(
+                    "out of memory: statement needs more memory than max_session_memory = 64MB allows; \
+                     narrow the query, add a LIMIT or raise max_session_memory"
+                ),
+                OUT_OF_MEMORY
+            );
+            assert_eq!(
+                for_query_error("Invalid query: cannot execute INSERT in a read-only transaction"),
+                READ_ONLY_SQL_TRANSACTION
+            );
+            assert_eq!(
+                for_query_error("Parse error: unexpected token"),
+                SYNTAX_ERROR
+            );
+        }
+    }
 }
@@ -9,14 +9,19 @@
 #![allow(dead_code)]
 
 pub mod replica;
+pub mod resync;
 pub mod stream;
 
 use std::sync::Arc;
 
+use anyhow::Result;
+use driftdb_core::Engine;
+
 pub use replica::{ReplicaManager, ReplicaManagerConfig};
-pub use stream::{ReplicationMessage, StreamingConfig, WalStreamer};
+pub use resync::BaseSnapshot;
+pub use stream::{ReplicationMessage, SendBufferDepth, StreamingConfig, WalStreamer};
 
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 use tracing::info;
 
 /// Main replication coordinator
@@ -83,10 +88,53 @@ impl ReplicationCoordinator {
         }
     }
 
+    /// Answer a replica's `RequestBaseSnapshot`: export the primary's
+    /// data at the current LSN, stream it, and let the replica resume
+    /// streaming after that LSN. Returns the LSN.
+    pub async fn send_base_snapshot(
+        &self,
+        replica_id: replica::ReplicaId,
+        engine: &parking_lot::RwLock<Engine>,
+        sender: &mpsc::Sender<ReplicationMessage>,
+    ) -> Result<u64> {
+        let snapshot = {
+            let streamer = self.wal_streamer.read().await;
+            // Commits need the engine, so holding it pins the LSN too
+            let engine = engine.read();
+            let spool = engine
+                .base_path()
+                .join("tmp")
+                .join(format!("base-snapshot-{}.jsonl", replica_id));
+            BaseSnapshot::export(&engine, streamer.lsn(), &spool)?
+        };
+        let lsn = snapshot.lsn;
+        snapshot.send(sender).await?;
+        self.replica_manager.complete_resync(replica_id, lsn);
+        Ok(lsn)
+    }
+
     /// Check health of all replicas
     pub async fn check_replica_health(&self) {
-        let current_lsn = self.current_lsn().await;
-        self.replica_manager.check_health(current_lsn);
+        let streamer = self.wal_streamer.read().await;
+        self.replica_manager
+            .check_health(streamer.lsn(), |applied| streamer.unapplied_since(applied));
+    }
+
+    /// Check replica health and lag every `health_check_interval` until
+    /// the coordinator is dropped
+    pub fn spawn_health_monitor(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let coordinator = Arc::downgrade(self);
+        let period = self.replica_manager.health_check_interval();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                let Some(coordinator) = coordinator.upgrade() else {
+                    break;
+                };
+                coordinator.check_replica_health().await;
+            }
+        })
     }
 
     /// Get replication statistics
@@ -102,6 +150,32 @@ impl ReplicationCoordinator {
         let total_entries_sent: u64 = replicas.iter().map(|r| r.entries_sent).sum();
 
         let max_lag = replicas.iter().map(|r| r.lag_bytes).max().unwrap_or(0);
+        let heartbeat_timeout = self.replica_manager.heartbeat_timeout();
+        let mut lag: Vec<ReplicaLag> = replicas
+            .iter()
+            .map(|r| ReplicaLag {
+                replica_id: r.id,
+                name: r.name.clone(),
+                lag_bytes: r.lag_bytes,
+                lag_seconds: r.lag_duration.as_secs_f64(),
+                seconds_since_ack: r.since_last_ack().as_secs_f64(),
+                healthy: r.is_healthy(heartbeat_timeout),
+            })
+            .collect();
+        lag.sort_by(|a, b| a.name.cmp(&b.name));
+        let max_lag_seconds = lag.iter().map(|r| r.lag_seconds).fold(0.0, f64::max);
+        let depths = self.wal_streamer.read().await.buffer_depths();
+        let mut send_buffers: Vec<ReplicaSendBuffer> = replicas
+            .iter()
+            .filter_map(|r| {
+                depths.get(&r.id).map(|depth| ReplicaSendBuffer {
+                    replica_id: r.id,
+                    name: r.name.clone(),
+                    depth: *depth,
+                })
+            })
+            .collect();
+        send_buffers.sort_by(|a, b| a.name.cmp(&b.name));
 
         ReplicationStats {
             total_replicas,
@@ -111,6 +185,9 @@ impl ReplicationCoordinator {
             total_bytes_sent,
             total_entries_sent,
             max_lag_bytes: max_lag,
+            max_lag_seconds,
+            lag,
+            send_buffers,
         }
     }
 }
@@ -132,10 +209,39 @@ pub struct ReplicationStats {
     pub total_entries_sent: u64,
     /// Maximum lag across all replicas
     pub max_lag_bytes: u64,
+    /// Maximum age of unapplied data across all replicas
+    pub max_lag_seconds: f64,
+    /// Lag and liveness of each replica
+    pub lag: Vec<ReplicaLag>,
+    /// Send buffer depth of each streaming replica
+    pub send_buffers: Vec<ReplicaSendBuffer>,
+}
+
+/// One replica's lag and liveness
+#[derive(Debug, Clone)]
+pub struct ReplicaLag {
+    pub replica_id: replica::ReplicaId,
+    pub name: String,
+    pub lag_bytes: u64,
+    /// Age of the oldest WAL the replica hasn't applied
+    pub lag_seconds: f64,
+    /// Time since its last status update
+    pub seconds_since_ack: f64,
+    /// Acknowledged within the heartbeat timeout
+    pub healthy: bool,
+}
+
+/// One streaming replica's send buffer
+#[derive(Debug, Clone)]
+pub struct ReplicaSendBuffer {
+    pub replica_id: replica::ReplicaId,
+    pub name: String,
+    pub depth: SendBufferDepth,
 }
 
 #[cfg(test)]
 mod tests {
+    use super::resync::BaseSnapshotReceiver;
     use super::*;
 
     #[tokio::test]
@@ -188,5 +294,156 @@ mod tests {
         assert_eq!(stats.healthy_replicas, 0);
         assert_eq!(stats.sync_replicas, 0);
         assert_eq!(stats.current_lsn, 0);
+        assert!(stats.send_buffers.is_empty());
+        assert!(stats.lag.is_empty());
+        assert_eq!(stats.max_lag_seconds, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_stats_report_lag_per_replica() {
+        use replica::ReplicationMode;
+        use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+        let coordinator = ReplicationCoordinator::new(
+            ReplicaManagerConfig::default(),
+            StreamingConfig::default(),
+            "primary".to_string(),
+        );
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let id = coordinator
+            .replica_manager
+            .register_replica("reader".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+        {
+            let streamer = coordinator.wal_streamer.read().await;
+            let three_seconds_ago = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_micros() as u64
+                - 3_000_000;
+            for lsn in 41..=50 {
+                streamer
+                    .broadcast_entry(stream::StreamingWalEntry {
+                        lsn,
+                        transaction_id: None,
+                        operation: "INSERT".to_string(),
+                        data: vec![],
+                        timestamp: three_seconds_ago,
+                        checksum: 0,
+                    })
+                    .await
+                    .unwrap();
+            }
+            streamer.handle_status_update(id, 40, 40, 40).await;
+        }
+        coordinator.check_replica_health().await;
+
+        let stats = coordinator.get_stats().await;
+        assert_eq!(stats.lag.len(), 1);
+        let lag = &stats.lag[0];
+        assert_eq!((lag.name.as_str(), lag.healthy), ("reader", true));
+        assert_eq!(lag.lag_bytes, 10);
+        assert!(lag.lag_seconds >= 3.0, "{}", lag.lag_seconds);
+        assert_eq!(stats.max_lag_seconds, lag.lag_seconds);
+        assert!(lag.seconds_since_ack < 60.0);
+    }
+
+    #[tokio::test]
+    async fn test_replica_resyncs_from_base_snapshot() {
+        use driftdb_core::sql_bridge::execute_sql;
+        use driftdb_core::QueryResult;
+        use replica::ReplicationMode;
+        use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+        use stream::StreamingWalEntry;
+
+        let primary_dir = tempfile::TempDir::new().unwrap();
+        let mut engine = Engine::init(primary_dir.path()).unwrap();
+        execute_sql(
+            &mut engine,
+            "CREATE TABLE items (id INT, name VARCHAR, PRIMARY KEY (id))",
+        )
+        .unwrap();
+        execute_sql(&mut engine, "INSERT INTO items (id, name) VALUES (1, 'a')").unwrap();
+        let engine = parking_lot::RwLock::new(engine);
+
+        let coordinator = ReplicationCoordinator::new(
+            ReplicaManagerConfig::default(),
+            StreamingConfig::default(),
+            "primary".to_string(),
+        );
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let replica_id = coordinator
+            .replica_manager
+            .register_replica("replica".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+        {
+            let streamer = coordinator.wal_streamer.read().await;
+            for lsn in 1..=3 {
+                streamer
+                    .broadcast_entry(StreamingWalEntry {
+                        lsn,
+                        transaction_id: None,
+                        operation: "INSERT".to_string(),
+                        data: vec![],
+                        timestamp: 0,
+                        checksum: 0,
+                    })
+                    .await
+                    .unwrap();
+            }
+            // Archived while the replica was down
+            streamer.discard_through(3);
+        }
+
+        // The replica's stale copy
+        let replica_root = tempfile::TempDir::new().unwrap();
+        let replica_dir = replica_root.path().join("data");
+        drop(Engine::init(&replica_dir).unwrap());
+
+        let (tx, mut rx) = mpsc::channel(64);
+        coordinator
+            .wal_streamer
+            .read()
+            .await
+            .stream_to_replica(replica_id, 1, tx.clone())
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(ReplicationMessage::ResyncRequired { .. })
+        ));
+
+        let lsn = coordinator
+            .send_base_snapshot(replica_id, &engine, &tx)
+            .await
+            .unwrap();
+        assert_eq!(lsn, 3);
+        drop(tx);
+
+        let mut receiver =
+            BaseSnapshotReceiver::new(replica_root.path().join("base.jsonl")).unwrap();
+        let mut end = None;
+        while let Some(msg) = rx.recv().await {
+            end = receiver.receive(msg).unwrap();
+        }
+        let (info, start) = receiver.install(&replica_dir, end.unwrap(), 1).unwrap();
+        assert!(info.sequences.contains_key("items"));
+        assert!(matches!(
+            start,
+            ReplicationMessage::StartReplication { start_lsn: 4, .. }
+        ));
+
+        let mut replica = Engine::open(&replica_dir).unwrap();
+        match execute_sql(&mut replica, "SELECT name FROM items").unwrap() {
+            QueryResult::Rows { data } => assert_eq!(data.len(), 1),
+            other => panic!("expected rows, got {:?}", other),
+        }
+        assert!(
+            !coordinator
+                .replica_manager
+                .get_replica(replica_id)
+                .unwrap()
+                .needs_resync
+        );
     }
 }
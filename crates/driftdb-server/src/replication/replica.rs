@@ -43,6 +43,15 @@ pub enum ReplicationMode {
     Sync,
 }
 
+/// How a replica's stream can start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamStart {
+    /// The WAL it asked for is still retained
+    FromLsn,
+    /// It has to load a base snapshot first
+    NeedsBaseSnapshot,
+}
+
 /// Information about a connected replica
 #[derive(Debug, Clone)]
 pub struct ReplicaInfo {
@@ -70,10 +79,14 @@ pub struct ReplicaInfo {
     pub entries_sent: u64,
     /// Replication lag in bytes (primary LSN - replica LSN)
     pub lag_bytes: u64,
-    /// Estimated replication lag in time
+    /// Age of the oldest WAL the replica hasn't applied: how stale its
+    /// data is. Zero when caught up.
     pub lag_duration: Duration,
     /// Number of consecutive failures
     pub failure_count: u32,
+    /// Dropped for falling too far behind; must resync from a base
+    /// backup before streaming again
+    pub needs_resync: bool,
 }
 
 impl ReplicaInfo {
@@ -94,6 +107,7 @@ impl ReplicaInfo {
             lag_bytes: 0,
             lag_duration: Duration::from_secs(0),
             failure_count: 0,
+            needs_resync: false,
         }
     }
 
@@ -102,18 +116,22 @@ impl ReplicaInfo {
         self.last_heartbeat.elapsed() < timeout && self.state != ReplicaState::Failed
     }
 
-    /// Calculate current replication lag
-    pub fn calculate_lag(&mut self, current_lsn: u64) {
-        if current_lsn >= self.last_applied_lsn {
-            self.lag_bytes = current_lsn - self.last_applied_lsn;
-        } else {
-            self.lag_bytes = 0;
-        }
+    /// Calculate current replication lag. `unapplied_since` is the
+    /// primary's timestamp (microseconds) of the oldest WAL entry the
+    /// replica hasn't applied, if it is still known.
+    pub fn calculate_lag(&mut self, current_lsn: u64, unapplied_since: Option<u64>) {
+        self.lag_bytes = current_lsn.saturating_sub(self.last_applied_lsn);
+        self.lag_duration = match unapplied_since {
+            _ if self.lag_bytes == 0 => Duration::ZERO,
+            Some(since) => Duration::from_micros(now_micros().saturating_sub(since)),
+            // Older than any WAL still retained; keep the last measurement
+            None => self.lag_duration,
+        };
+    }
 
-        // Estimate time lag based on lag bytes (rough approximation)
-        // Assume ~1MB/sec replication speed
-        let lag_seconds = self.lag_bytes as f64 / 1_000_000.0;
-        self.lag_duration = Duration::from_secs_f64(lag_seconds);
+    /// Time since the replica last acknowledged (sent a status update)
+    pub fn since_last_ack(&self) -> Duration {
+        self.last_heartbeat.elapsed()
     }
 
     /// Update heartbeat timestamp
@@ -135,12 +153,21 @@ impl ReplicaInfo {
     }
 }
 
+/// Current time in microseconds, the unit of WAL entry timestamps
+fn now_micros() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
 /// Configuration for replica management
 #[derive(Debug, Clone)]
 pub struct ReplicaManagerConfig {
     /// Maximum number of replicas
     pub max_replicas: usize,
-    /// Heartbeat timeout (mark replica as unhealthy if no heartbeat)
+    /// Heartbeat timeout (mark replica as unhealthy if no status update
+    /// acknowledges a keepalive or WAL within it)
     pub heartbeat_timeout: Duration,
     /// Health check interval
     pub health_check_interval: Duration,
@@ -222,27 +249,30 @@ impl ReplicaManager {
         }
     }
 
-    /// Update replica position (LSN)
+    /// Update replica position (LSN). `unapplied_since` is as for
+    /// [`ReplicaInfo::calculate_lag`].
     pub fn update_replica_position(
         &self,
         id: ReplicaId,
         received_lsn: u64,
         applied_lsn: u64,
         current_lsn: u64,
+        unapplied_since: Option<u64>,
     ) {
         let mut replicas = self.replicas.write();
         if let Some(replica) = replicas.get_mut(&id) {
             replica.last_received_lsn = received_lsn;
             replica.last_applied_lsn = applied_lsn;
-            replica.calculate_lag(current_lsn);
+            replica.calculate_lag(current_lsn, unapplied_since);
 
             debug!(
-                "Updated replica {} position: received={}, applied={}, lag={}",
-                replica.name, received_lsn, applied_lsn, replica.lag_bytes
+                "Updated replica {} position: received={}, applied={}, lag={} ({:?})",
+                replica.name, received_lsn, applied_lsn, replica.lag_bytes, replica.lag_duration
             );
 
             // Update metrics
-            metrics::record_replication_lag(replica.lag_bytes as f64 / 1024.0); // KB
+            metrics::update_replication_lag(&replica.name, replica.lag_duration.as_secs_f64());
+            metrics::update_replication_lag_bytes(&replica.name, replica.lag_bytes);
         }
     }
 
@@ -286,6 +316,62 @@ impl ReplicaManager {
         }
     }
 
+    /// Disconnect a replica that overflowed its send buffer. It can't
+    /// stream again until it resyncs.
+    pub fn mark_needs_resync(&self, id: ReplicaId) {
+        let mut replicas = self.replicas.write();
+        if let Some(replica) = replicas.get_mut(&id) {
+            replica.state = ReplicaState::Disconnected;
+            replica.needs_resync = true;
+        }
+    }
+
+    /// Decide how a replica asking for WAL from `start_lsn` starts
+    /// streaming. A replica flagged for resync, or asking for WAL older
+    /// than `oldest_available_lsn`, is sent to resync from a base
+    /// snapshot; `None` if the replica isn't registered.
+    pub fn start_streaming(
+        &self,
+        id: ReplicaId,
+        start_lsn: u64,
+        oldest_available_lsn: u64,
+    ) -> Option<StreamStart> {
+        let mut replicas = self.replicas.write();
+        let replica = replicas.get_mut(&id)?;
+        // LSN 0 is a replica with no data, which can start from scratch
+        // only if no WAL was ever discarded
+        let wal_gone = start_lsn < oldest_available_lsn && oldest_available_lsn > 1;
+        if replica.needs_resync || wal_gone {
+            if !replica.needs_resync {
+                info!(
+                    "Replica {} ({}) asked for LSN {} but WAL before {} is gone; starting resync",
+                    replica.name, id, start_lsn, oldest_available_lsn
+                );
+            }
+            replica.needs_resync = true;
+            replica.state = ReplicaState::CatchingUp;
+            return Some(StreamStart::NeedsBaseSnapshot);
+        }
+        replica.state = ReplicaState::Streaming;
+        Some(StreamStart::FromLsn)
+    }
+
+    /// Record that a replica received a base snapshot taken at `lsn`, so
+    /// it can stream from there
+    pub fn complete_resync(&self, id: ReplicaId, lsn: u64) {
+        let mut replicas = self.replicas.write();
+        if let Some(replica) = replicas.get_mut(&id) {
+            info!(
+                "Replica {} ({}) resynced from base snapshot at LSN {}",
+                replica.name, id, lsn
+            );
+            replica.needs_resync = false;
+            replica.last_received_lsn = lsn;
+            replica.last_applied_lsn = lsn;
+            replica.failure_count = 0;
+        }
+    }
+
     /// Get replica info
     pub fn get_replica(&self, id: ReplicaId) -> Option<ReplicaInfo> {
         self.replicas.read().get(&id).cloned()
@@ -306,15 +392,17 @@ impl ReplicaManager {
             .collect()
     }
 
-    /// Check health of all replicas
-    pub fn check_health(&self, current_lsn: u64) {
+    /// Check health of all replicas, refreshing their lag.
+    /// `unapplied_since` maps a replica's applied LSN to the timestamp of
+    /// the oldest WAL entry after it, as for [`ReplicaInfo::calculate_lag`].
+    pub fn check_health(&self, current_lsn: u64, unapplied_since: impl Fn(u64) -> Option<u64>) {
         let mut replicas = self.replicas.write();
 
         for replica in replicas.values_mut() {
-            // Update lag
-            replica.calculate_lag(current_lsn);
+            // Update lag; it keeps growing between status updates
+            replica.calculate_lag(current_lsn, unapplied_since(replica.last_applied_lsn));
 
-            // Check heartbeat timeout
+            // No ack within the heartbeat timeout
             if !replica.is_healthy(self.config.heartbeat_timeout)
                 && replica.state != ReplicaState::Disconnected
                 && replica.state != ReplicaState::Failed
@@ -333,9 +421,27 @@ impl ReplicaManager {
                     replica.name, replica.id, replica.lag_bytes
                 );
             }
+
+            metrics::update_replication_lag(&replica.name, replica.lag_duration.as_secs_f64());
+            metrics::update_replication_lag_bytes(&replica.name, replica.lag_bytes);
+            metrics::update_replication_status(
+                &replica.name,
+                replica.is_healthy(self.config.heartbeat_timeout),
+            );
         }
     }
 
+    /// How often [`ReplicaManager::check_health`] should run
+    pub fn health_check_interval(&self) -> Duration {
+        self.config.health_check_interval
+    }
+
+    /// How long a replica may go without acknowledging before it is
+    /// unhealthy
+    pub fn heartbeat_timeout(&self) -> Duration {
+        self.config.heartbeat_timeout
+    }
+
     /// Get count of healthy replicas
     pub fn healthy_replica_count(&self) -> usize {
         self.replicas
@@ -385,7 +491,7 @@ mod tests {
             .register_replica("replica-1".to_string(), addr, ReplicationMode::Async)
             .unwrap();
 
-        manager.update_replica_position(id, 100, 90, 150);
+        manager.update_replica_position(id, 100, 90, 150, None);
 
         let replica = manager.get_replica(id).unwrap();
         assert_eq!(replica.last_received_lsn, 100);
@@ -435,4 +541,103 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Maximum replica limit"));
     }
+
+    #[test]
+    fn test_start_streaming_detects_missing_wal() {
+        let manager = ReplicaManager::new(ReplicaManagerConfig::default());
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let id = manager
+            .register_replica("replica-1".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+
+        // Nothing discarded yet, so even LSN 0 can stream
+        assert_eq!(
+            manager.start_streaming(id, 0, 1),
+            Some(StreamStart::FromLsn)
+        );
+        assert_eq!(
+            manager.get_replica(id).unwrap().state,
+            ReplicaState::Streaming
+        );
+        assert_eq!(
+            manager.start_streaming(id, 50, 50),
+            Some(StreamStart::FromLsn)
+        );
+
+        assert_eq!(
+            manager.start_streaming(id, 40, 50),
+            Some(StreamStart::NeedsBaseSnapshot)
+        );
+        let replica = manager.get_replica(id).unwrap();
+        assert!(replica.needs_resync);
+        assert_eq!(replica.state, ReplicaState::CatchingUp);
+        // Stays flagged until the snapshot is delivered
+        assert_eq!(
+            manager.start_streaming(id, 60, 50),
+            Some(StreamStart::NeedsBaseSnapshot)
+        );
+
+        manager.complete_resync(id, 70);
+        assert_eq!(manager.get_replica(id).unwrap().last_applied_lsn, 70);
+        assert_eq!(
+            manager.start_streaming(id, 71, 50),
+            Some(StreamStart::FromLsn)
+        );
+        assert_eq!(manager.start_streaming(Uuid::new_v4(), 0, 1), None);
+    }
+
+    #[test]
+    fn test_lag_in_seconds_is_age_of_unapplied_wal() {
+        let manager = ReplicaManager::new(ReplicaManagerConfig::default());
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let id = manager
+            .register_replica("replica-1".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+
+        let five_seconds_ago = now_micros() - 5_000_000;
+        manager.update_replica_position(id, 100, 90, 150, Some(five_seconds_ago));
+        let lag = manager.get_replica(id).unwrap().lag_duration;
+        assert!(
+            lag >= Duration::from_secs(5) && lag < Duration::from_secs(60),
+            "{:?}",
+            lag
+        );
+
+        // The oldest unapplied WAL is no longer known: keep the last value
+        manager.check_health(150, |_| None);
+        assert_eq!(manager.get_replica(id).unwrap().lag_duration, lag);
+
+        // Caught up
+        manager.update_replica_position(id, 150, 150, 150, None);
+        let replica = manager.get_replica(id).unwrap();
+        assert_eq!(
+            (replica.lag_bytes, replica.lag_duration),
+            (0, Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_replica_without_ack_is_unhealthy() {
+        let config = ReplicaManagerConfig {
+            heartbeat_timeout: Duration::from_millis(20),
+            ..Default::default()
+        };
+        let manager = ReplicaManager::new(config);
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let id = manager
+            .register_replica("replica-1".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+        manager.update_replica_state(id, ReplicaState::Streaming);
+
+        std::thread::sleep(Duration::from_millis(40));
+        manager.check_health(0, |_| None);
+        assert_eq!(
+            manager.get_replica(id).unwrap().state,
+            ReplicaState::Disconnected
+        );
+        assert_eq!(manager.healthy_replica_count(), 0);
+
+        manager.update_heartbeat(id);
+        assert_eq!(manager.healthy_replica_count(), 1);
+    }
 }
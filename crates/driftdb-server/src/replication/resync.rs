@@ -0,0 +1,182 @@
+//! Resyncing a replica from a base snapshot
+//!
+//! A replica that needs WAL the primary no longer retains can't catch up by
+//! streaming. Instead:
+//!
+//! 1. the primary answers its `StartReplication` with `ResyncRequired`
+//! 2. the replica sends `RequestBaseSnapshot`
+//! 3. the primary exports a snapshot ([`Engine::export_snapshot`]), noting
+//!    the LSN it was taken at, and sends it as `BaseSnapshotChunk`s and a
+//!    closing `BaseSnapshotEnd { lsn }`
+//! 4. the replica spools the chunks, swaps the snapshot in for its data
+//!    directory and sends `StartReplication` from `lsn + 1`
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use driftdb_core::{Engine, SnapshotInfo};
+use tokio::sync::mpsc;
+use tracing::info;
+
+use super::stream::ReplicationMessage;
+
+/// Bytes per `BaseSnapshotChunk`
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A base snapshot exported on the primary, spooled to disk until sent
+#[derive(Debug)]
+pub struct BaseSnapshot {
+    path: PathBuf,
+    /// LSN the snapshot was taken at
+    pub lsn: u64,
+    pub info: SnapshotInfo,
+}
+
+impl BaseSnapshot {
+    /// Export `engine` to `spool_path`. The caller holds the engine for
+    /// the whole call and passes the streamer's LSN read under the same
+    /// hold, so WAL after `lsn` is exactly what the snapshot lacks.
+    pub fn export(engine: &Engine, lsn: u64, spool_path: &Path) -> Result<Self> {
+        if let Some(parent) = spool_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::File::create(spool_path)
+            .with_context(|| format!("Failed to create {}", spool_path.display()))?;
+        let info = engine.export_snapshot(BufWriter::new(file))?;
+        Ok(Self {
+            path: spool_path.to_path_buf(),
+            lsn,
+            info,
+        })
+    }
+
+    /// Send the snapshot as chunks and a closing `BaseSnapshotEnd`,
+    /// removing the spool file afterwards
+    pub async fn send(self, sender: &mpsc::Sender<ReplicationMessage>) -> Result<()> {
+        let result = self.send_chunks(sender).await;
+        let _ = fs::remove_file(&self.path);
+        result
+    }
+
+    async fn send_chunks(&self, sender: &mpsc::Sender<ReplicationMessage>) -> Result<()> {
+        let mut file = fs::File::open(&self.path)?;
+        let mut sent = 0usize;
+        loop {
+            let mut data = vec![0u8; CHUNK_SIZE];
+            let n = file.read(&mut data)?;
+            if n == 0 {
+                break;
+            }
+            data.truncate(n);
+            sent += n;
+            sender
+                .send(ReplicationMessage::BaseSnapshotChunk { data })
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to send base snapshot: {}", e))?;
+        }
+        sender
+            .send(ReplicationMessage::BaseSnapshotEnd { lsn: self.lsn })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to send base snapshot: {}", e))?;
+        info!(
+            "Sent base snapshot at LSN {} ({} bytes, {} events)",
+            self.lsn, sent, self.info.events
+        );
+        Ok(())
+    }
+}
+
+/// The replica's end of a resync: spools the chunks and installs the
+/// snapshot once it ends
+pub struct BaseSnapshotReceiver {
+    spool_path: PathBuf,
+    spool: BufWriter<fs::File>,
+}
+
+impl BaseSnapshotReceiver {
+    pub fn new(spool_path: impl Into<PathBuf>) -> Result<Self> {
+        let spool_path = spool_path.into();
+        if let Some(parent) = spool_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let spool = BufWriter::new(fs::File::create(&spool_path)?);
+        Ok(Self { spool_path, spool })
+    }
+
+    /// Take one message of the snapshot stream, returning the snapshot's
+    /// LSN once it is complete
+    pub fn receive(&mut self, msg: ReplicationMessage) -> Result<Option<u64>> {
+        match msg {
+            ReplicationMessage::BaseSnapshotChunk { data } => {
+                self.spool.write_all(&data)?;
+                Ok(None)
+            }
+            ReplicationMessage::BaseSnapshotEnd { lsn } => {
+                self.spool.flush()?;
+                Ok(Some(lsn))
+            }
+            other => Err(anyhow::anyhow!(
+                "Unexpected message during base snapshot: {:?}",
+                other
+            )),
+        }
+    }
+
+    /// Replace `data_dir` with the received snapshot, taken at `lsn`, and
+    /// return the `StartReplication` that resumes streaming after it. The
+    /// replica's engine must be closed: the old directory is only removed
+    /// once the new one is fully imported.
+    pub fn install(
+        self,
+        data_dir: &Path,
+        lsn: u64,
+        timeline: u64,
+    ) -> Result<(SnapshotInfo, ReplicationMessage)> {
+        drop(self.spool);
+        let staging = sibling(data_dir, "resync");
+        let previous = sibling(data_dir, "pre-resync");
+        for dir in [&staging, &previous] {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+        }
+
+        let spool = fs::File::open(&self.spool_path)?;
+        let info =
+            Engine::import_snapshot(&staging, spool).context("Failed to import base snapshot")?;
+        if data_dir.exists() {
+            fs::rename(data_dir, &previous)?;
+        }
+        fs::rename(&staging, data_dir)?;
+        if previous.exists() {
+            fs::remove_dir_all(&previous)?;
+        }
+        let _ = fs::remove_file(&self.spool_path);
+
+        info!(
+            "Installed base snapshot at LSN {} into {} ({} events)",
+            lsn,
+            data_dir.display(),
+            info.events
+        );
+        Ok((
+            info,
+            ReplicationMessage::StartReplication {
+                start_lsn: lsn + 1,
+                timeline,
+            },
+        ))
+    }
+}
+
+/// `<dir>.<suffix>`, next to `dir`
+fn sibling(dir: &Path, suffix: &str) -> PathBuf {
+    let mut name = dir.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    dir.with_file_name(name)
+}
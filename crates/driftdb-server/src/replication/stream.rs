@@ -1,23 +1,36 @@
 //! WAL streaming protocol for replication
 //!
 //! Handles continuous streaming of WAL entries from primary to replicas.
+//!
+//! Each streaming replica has its own send queue, bounded by
+//! `StreamingConfig::max_send_buffer` bytes. A replica that can't keep up
+//! never makes the primary buffer more than that:
+//! - for a sync replica, commits wait for room in its queue, and fail
+//!   after `backpressure_timeout`
+//! - an async replica is dropped instead: its queue is discarded and it is
+//!   marked disconnected, and it must resync from a base backup before
+//!   streaming again
+//!
+//! The streamer also keeps the most recent `max_retained_wal` bytes of WAL
+//! so a reconnecting replica can catch up. One that asks for WAL older than
+//! that gets `ResyncRequired` and resyncs from a base snapshot instead (see
+//! [`super::resync`]).
 
 #![allow(dead_code)]
 
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::{broadcast, mpsc, RwLock};
-use tokio::time::{interval, timeout};
+use tokio::sync::{mpsc, Notify};
+use tokio::time::{interval, timeout, Instant};
 use tracing::{debug, error, info, warn};
 
-use super::replica::{ReplicaId, ReplicaManager};
+use super::replica::{ReplicaId, ReplicaManager, ReplicationMode, StreamStart};
 use anyhow::Result;
 
-/// Maximum size of the WAL entry broadcast channel
-const WAL_CHANNEL_SIZE: usize = 10000;
-
 /// Replication message types sent over the wire
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ReplicationMessage {
@@ -53,6 +66,18 @@ pub enum ReplicationMessage {
     HotStandbyFeedback { timestamp: u64, oldest_xid: u64 },
     /// Error message
     Error { message: String },
+    /// The WAL a replica asked for is no longer retained; it has to
+    /// resync from a base snapshot
+    ResyncRequired {
+        oldest_available_lsn: u64,
+        current_lsn: u64,
+    },
+    /// Replica asks for a base snapshot to resync from
+    RequestBaseSnapshot,
+    /// Part of a base snapshot stream
+    BaseSnapshotChunk { data: Vec<u8> },
+    /// End of a base snapshot taken at `lsn`; streaming resumes after it
+    BaseSnapshotEnd { lsn: u64 },
 }
 
 /// WAL entry for streaming
@@ -103,8 +128,12 @@ pub struct StreamingConfig {
     pub keepalive_interval: Duration,
     /// Status update timeout (if replica doesn't send status)
     pub status_timeout: Duration,
-    /// Maximum WAL send buffer size
+    /// High-water mark of each replica's send buffer, in bytes
     pub max_send_buffer: usize,
+    /// How long a commit waits for room in a sync replica's full buffer
+    pub backpressure_timeout: Duration,
+    /// Bytes of recent WAL kept for replicas catching up
+    pub max_retained_wal: usize,
     /// Batch multiple WAL entries into single message
     pub batch_entries: bool,
     /// Maximum batch size
@@ -117,18 +146,128 @@ impl Default for StreamingConfig {
             keepalive_interval: Duration::from_secs(10),
             status_timeout: Duration::from_secs(60),
             max_send_buffer: 10 * 1024 * 1024, // 10MB
+            backpressure_timeout: Duration::from_secs(30),
+            max_retained_wal: 64 * 1024 * 1024, // 64MB
             batch_entries: true,
             max_batch_size: 100,
         }
     }
 }
 
-/// WAL streaming manager - broadcasts WAL entries to all replicas
+/// Current depth of one replica's send buffer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendBufferDepth {
+    /// WAL entries waiting to be sent
+    pub entries: usize,
+    /// Their size in bytes
+    pub bytes: usize,
+}
+
+#[derive(Default)]
+struct SendQueueState {
+    entries: VecDeque<StreamingWalEntry>,
+    bytes: usize,
+    /// Set when the replica was dropped for falling behind
+    closed: bool,
+}
+
+/// WAL entries waiting to go to one replica
+#[derive(Default)]
+struct SendQueue {
+    state: parking_lot::Mutex<SendQueueState>,
+    /// Signalled when an entry is queued or the queue closes
+    ready: Notify,
+    /// Signalled when an entry leaves the queue
+    drained: Notify,
+}
+
+impl SendQueue {
+    /// Queue `entry` unless that takes the buffer past `high_water`. An
+    /// entry always fits an empty buffer, however large.
+    fn try_push(&self, entry: StreamingWalEntry, high_water: usize) -> bool {
+        let size = entry.size();
+        let mut state = self.state.lock();
+        if state.closed {
+            return true;
+        }
+        if !state.entries.is_empty() && state.bytes + size > high_water {
+            return false;
+        }
+        state.bytes += size;
+        state.entries.push_back(entry);
+        drop(state);
+        self.ready.notify_one();
+        true
+    }
+
+    /// The next entry to send, or `None` once the queue is closed
+    async fn pop(&self) -> Option<StreamingWalEntry> {
+        loop {
+            {
+                let mut state = self.state.lock();
+                if let Some(entry) = state.entries.pop_front() {
+                    state.bytes -= entry.size();
+                    drop(state);
+                    self.drained.notify_one();
+                    return Some(entry);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    fn close(&self) {
+        let mut state = self.state.lock();
+        state.closed = true;
+        state.entries.clear();
+        state.bytes = 0;
+        drop(state);
+        self.ready.notify_one();
+        self.drained.notify_one();
+    }
+
+    fn depth(&self) -> SendBufferDepth {
+        let state = self.state.lock();
+        SendBufferDepth {
+            entries: state.entries.len(),
+            bytes: state.bytes,
+        }
+    }
+}
+
+/// Recent WAL kept for replicas catching up
+#[derive(Default)]
+struct RetainedWal {
+    entries: VecDeque<StreamingWalEntry>,
+    bytes: usize,
+    /// Highest LSN no longer retained, 0 if nothing was dropped
+    discarded_through: u64,
+}
+
+impl RetainedWal {
+    fn trim(&mut self, max_bytes: usize) {
+        while self.bytes > max_bytes {
+            let Some(entry) = self.entries.pop_front() else {
+                break;
+            };
+            self.bytes -= entry.size();
+            self.discarded_through = self.discarded_through.max(entry.lsn);
+        }
+    }
+}
+
+/// WAL streaming manager - fans WAL entries out to every streaming replica
 pub struct WalStreamer {
-    /// Broadcast channel for new WAL entries
-    wal_tx: broadcast::Sender<StreamingWalEntry>,
+    /// Recent WAL, and the lock ordering new entries against replicas
+    /// attaching
+    retained: parking_lot::Mutex<RetainedWal>,
+    /// Send queue of each streaming replica
+    queues: parking_lot::RwLock<HashMap<ReplicaId, Arc<SendQueue>>>,
     /// Current LSN
-    current_lsn: Arc<RwLock<u64>>,
+    current_lsn: AtomicU64,
     /// Replica manager
     replica_manager: Arc<ReplicaManager>,
     /// Configuration
@@ -138,49 +277,187 @@ pub struct WalStreamer {
 impl WalStreamer {
     /// Create a new WAL streamer
     pub fn new(replica_manager: Arc<ReplicaManager>, config: StreamingConfig) -> Self {
-        let (wal_tx, _) = broadcast::channel(WAL_CHANNEL_SIZE);
-
         Self {
-            wal_tx,
-            current_lsn: Arc::new(RwLock::new(0)),
+            retained: parking_lot::Mutex::new(RetainedWal::default()),
+            queues: parking_lot::RwLock::new(HashMap::new()),
+            current_lsn: AtomicU64::new(0),
             replica_manager,
             config,
         }
     }
 
-    /// Broadcast a new WAL entry to all subscribed replicas
+    /// Queue a new WAL entry for every streaming replica. Waits while a
+    /// sync replica's buffer is full, and fails if it stays full for
+    /// `backpressure_timeout`.
     pub async fn broadcast_entry(&self, entry: StreamingWalEntry) -> Result<()> {
         // Update current LSN
-        {
-            let mut lsn = self.current_lsn.write().await;
-            *lsn = entry.lsn;
+        self.current_lsn.store(entry.lsn, Ordering::Release);
+
+        let queues: Vec<(ReplicaId, Arc<SendQueue>)> = {
+            let mut retained = self.retained.lock();
+            retained.bytes += entry.size();
+            retained.entries.push_back(entry.clone());
+            retained.trim(self.config.max_retained_wal);
+            self.queues
+                .read()
+                .iter()
+                .map(|(id, queue)| (*id, queue.clone()))
+                .collect()
+        };
+        for (replica_id, queue) in &queues {
+            match self
+                .replica_manager
+                .get_replica(*replica_id)
+                .map(|r| r.mode)
+            {
+                Some(ReplicationMode::Sync) => {
+                    self.push_with_backpressure(*replica_id, queue, entry.clone())
+                        .await?
+                }
+                Some(ReplicationMode::Async) => {
+                    if !queue.try_push(entry.clone(), self.config.max_send_buffer) {
+                        self.drop_for_resync(*replica_id, queue);
+                    }
+                }
+                // Unregistered while streaming
+                None => self.detach(*replica_id, queue),
+            }
         }
 
-        // Broadcast to all subscribers
-        match self.wal_tx.send(entry.clone()) {
-            Ok(receiver_count) => {
-                debug!(
-                    "Broadcasted WAL entry LSN {} to {} replicas",
-                    entry.lsn, receiver_count
+        debug!(
+            "Queued WAL entry LSN {} for {} replicas",
+            entry.lsn,
+            queues.len()
+        );
+        Ok(())
+    }
+
+    async fn push_with_backpressure(
+        &self,
+        replica_id: ReplicaId,
+        queue: &SendQueue,
+        entry: StreamingWalEntry,
+    ) -> Result<()> {
+        let deadline = Instant::now() + self.config.backpressure_timeout;
+        loop {
+            if queue.try_push(entry.clone(), self.config.max_send_buffer) {
+                return Ok(());
+            }
+            if tokio::time::timeout_at(deadline, queue.drained.notified())
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Sync replica {} send buffer stayed full for {:?}",
+                    replica_id, self.config.backpressure_timeout
                 );
-                Ok(())
+                return Err(anyhow::anyhow!(
+                    "synchronous replica {} is more than {} bytes behind",
+                    replica_id,
+                    self.config.max_send_buffer
+                ));
             }
-            Err(e) => {
-                warn!("Failed to broadcast WAL entry: {}", e);
-                // This is not a fatal error - might just mean no active replicas
-                Ok(())
+        }
+    }
+
+    /// Drop an async replica whose buffer overflowed: its stream ends and
+    /// it has to resync before streaming again
+    fn drop_for_resync(&self, replica_id: ReplicaId, queue: &Arc<SendQueue>) {
+        warn!(
+            "Replica {} fell more than {} bytes behind; disconnecting it until it resyncs",
+            replica_id, self.config.max_send_buffer
+        );
+        self.detach(replica_id, queue);
+        queue.close();
+        self.replica_manager.mark_needs_resync(replica_id);
+    }
+
+    /// Give `replica_id` a send queue, returning it with the retained
+    /// entries from `start_lsn` on that predate it
+    fn attach(
+        &self,
+        replica_id: ReplicaId,
+        start_lsn: u64,
+    ) -> (Arc<SendQueue>, Vec<StreamingWalEntry>) {
+        let retained = self.retained.lock();
+        let backlog = retained
+            .entries
+            .iter()
+            .filter(|entry| entry.lsn >= start_lsn)
+            .cloned()
+            .collect();
+        let queue = Arc::new(SendQueue::default());
+        if let Some(previous) = self.queues.write().insert(replica_id, queue.clone()) {
+            previous.close();
+        }
+        (queue, backlog)
+    }
+
+    /// Lowest LSN still retained for catch-up; replicas needing anything
+    /// older must resync
+    pub fn oldest_available_lsn(&self) -> u64 {
+        self.retained.lock().discarded_through + 1
+    }
+
+    /// Timestamp of the oldest retained WAL entry after `applied_lsn`:
+    /// how far back a replica that applied up to there is. When that WAL
+    /// is no longer retained, the oldest retained entry's, which
+    /// understates the lag; `None` if nothing later is retained.
+    pub fn unapplied_since(&self, applied_lsn: u64) -> Option<u64> {
+        let retained = self.retained.lock();
+        let first_unapplied = retained
+            .entries
+            .partition_point(|entry| entry.lsn <= applied_lsn);
+        retained
+            .entries
+            .get(first_unapplied)
+            .map(|entry| entry.timestamp)
+    }
+
+    /// Stop retaining WAL up to `lsn`, e.g. once it has been recycled or
+    /// archived
+    pub fn discard_through(&self, lsn: u64) {
+        let mut retained = self.retained.lock();
+        while retained
+            .entries
+            .front()
+            .is_some_and(|entry| entry.lsn <= lsn)
+        {
+            if let Some(entry) = retained.entries.pop_front() {
+                retained.bytes -= entry.size();
             }
         }
+        retained.discarded_through = retained.discarded_through.max(lsn);
+    }
+
+    /// Remove `replica_id`'s queue, unless a newer stream replaced it
+    fn detach(&self, replica_id: ReplicaId, queue: &Arc<SendQueue>) {
+        let mut queues = self.queues.write();
+        if queues
+            .get(&replica_id)
+            .is_some_and(|current| Arc::ptr_eq(current, queue))
+        {
+            queues.remove(&replica_id);
+        }
+    }
+
+    /// Depth of each streaming replica's send buffer
+    pub fn buffer_depths(&self) -> HashMap<ReplicaId, SendBufferDepth> {
+        self.queues
+            .read()
+            .iter()
+            .map(|(id, queue)| (*id, queue.depth()))
+            .collect()
     }
 
     /// Get current LSN
     pub async fn current_lsn(&self) -> u64 {
-        *self.current_lsn.read().await
+        self.lsn()
     }
 
-    /// Subscribe to WAL stream for a replica
-    pub fn subscribe(&self) -> broadcast::Receiver<StreamingWalEntry> {
-        self.wal_tx.subscribe()
+    /// Current LSN, for callers that can't await
+    pub fn lsn(&self) -> u64 {
+        self.current_lsn.load(Ordering::Acquire)
     }
 
     /// Start streaming to a specific replica
@@ -195,57 +472,90 @@ impl WalStreamer {
             replica_id, start_lsn
         );
 
-        // Subscribe to WAL broadcast
-        let mut wal_rx = self.subscribe();
-
-        // Get replica info
-        let replica = self
+        let oldest_available_lsn = self.oldest_available_lsn();
+        match self
             .replica_manager
-            .get_replica(replica_id)
-            .ok_or_else(|| anyhow::anyhow!("Replica not found"))?;
+            .start_streaming(replica_id, start_lsn, oldest_available_lsn)
+            .ok_or_else(|| anyhow::anyhow!("Replica not found"))?
+        {
+            StreamStart::FromLsn => {}
+            StreamStart::NeedsBaseSnapshot => {
+                let msg = ReplicationMessage::ResyncRequired {
+                    oldest_available_lsn,
+                    current_lsn: self.current_lsn().await,
+                };
+                return sender
+                    .send(msg)
+                    .await
+                    .map_err(|e| anyhow::anyhow!("Failed to send resync request: {}", e));
+            }
+        }
+
+        let (queue, backlog) = self.attach(replica_id, start_lsn);
+        let result = self
+            .stream_queue(replica_id, start_lsn, backlog, &queue, &sender)
+            .await;
+        self.detach(replica_id, &queue);
+        result
+    }
+
+    async fn stream_queue(
+        &self,
+        replica_id: ReplicaId,
+        start_lsn: u64,
+        backlog: Vec<StreamingWalEntry>,
+        queue: &SendQueue,
+        sender: &mpsc::Sender<ReplicationMessage>,
+    ) -> Result<()> {
+        // Catch up from retained WAL before the live queue
+        if !backlog.is_empty() {
+            info!(
+                "Sending {} retained WAL entries to replica {}",
+                backlog.len(),
+                replica_id
+            );
+        }
+        for entry in backlog {
+            let entry_size = entry.size();
+            if let Err(e) = self.send_wal_entry(sender, entry).await {
+                error!("Failed to send WAL entry to replica {}: {}", replica_id, e);
+                self.replica_manager.record_failure(replica_id);
+                return Err(e);
+            }
+            self.replica_manager
+                .record_bytes_sent(replica_id, entry_size as u64, 1);
+        }
 
         // Set up keepalive timer
         let mut keepalive = interval(self.config.keepalive_interval);
 
-        // TODO: If start_lsn < current_lsn, need to send historical WAL first
-        // For now, we just start streaming from current position
-
         loop {
             tokio::select! {
-                // Receive new WAL entry
-                entry_result = wal_rx.recv() => {
-                    match entry_result {
-                        Ok(entry) => {
-                            // Only send entries >= start_lsn
-                            if entry.lsn >= start_lsn {
-                                // Get size before moving entry
-                                let entry_size = entry.size();
-
-                                if let Err(e) = self.send_wal_entry(&sender, entry).await {
-                                    error!("Failed to send WAL entry to replica {}: {}", replica_id, e);
-                                    self.replica_manager.record_failure(replica_id);
-                                    return Err(e);
-                                }
-
-                                // Update metrics
-                                self.replica_manager.record_bytes_sent(
-                                    replica_id,
-                                    entry_size as u64,
-                                    1,
-                                );
-                            }
-                        }
-                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
-                            warn!(
-                                "Replica {} lagged behind, skipped {} entries",
-                                replica.name, skipped
-                            );
-                            // Continue streaming - replica will catch up
-                        }
-                        Err(broadcast::error::RecvError::Closed) => {
-                            info!("WAL broadcast channel closed, stopping stream to replica {}", replica_id);
-                            return Ok(());
+                // Next queued WAL entry
+                entry = queue.pop() => {
+                    let Some(entry) = entry else {
+                        return Err(anyhow::anyhow!(
+                            "Replica {} fell more than {} bytes behind and must resync",
+                            replica_id, self.config.max_send_buffer
+                        ));
+                    };
+                    // Only send entries >= start_lsn
+                    if entry.lsn >= start_lsn {
+                        // Get size before moving entry
+                        let entry_size = entry.size();
+
+                        if let Err(e) = self.send_wal_entry(sender, entry).await {
+                            error!("Failed to send WAL entry to replica {}: {}", replica_id, e);
+                            self.replica_manager.record_failure(replica_id);
+                            return Err(e);
                         }
+
+                        // Update metrics
+                        self.replica_manager.record_bytes_sent(
+                            replica_id,
+                            entry_size as u64,
+                            1,
+                        );
                     }
                 }
 
@@ -309,6 +619,7 @@ impl WalStreamer {
             received_lsn,
             applied_lsn,
             current_lsn,
+            self.unapplied_since(applied_lsn),
         );
 
         // Update heartbeat
@@ -393,7 +704,7 @@ pub fn deserialize_message(data: &[u8]) -> Result<ReplicationMessage> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::replication::replica::{ReplicaManagerConfig, ReplicationMode};
+    use crate::replication::replica::{ReplicaManagerConfig, ReplicaState, ReplicationMode};
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     #[tokio::test]
@@ -451,22 +762,189 @@ mod tests {
         let streamer = WalStreamer::new(replica_manager.clone(), StreamingConfig::default());
 
         // Broadcast an entry to set current LSN
+        let entry_timestamp = current_timestamp() - 2_000_000;
         let entry = StreamingWalEntry {
             lsn: 100,
             transaction_id: None,
             operation: "INSERT".to_string(),
             data: vec![],
-            timestamp: current_timestamp(),
+            timestamp: entry_timestamp,
             checksum: 0,
         };
         streamer.broadcast_entry(entry).await.unwrap();
 
         // Handle status update
         streamer.handle_status_update(replica_id, 90, 85, 85).await;
+        assert_eq!(streamer.unapplied_since(85), Some(entry_timestamp));
+        assert_eq!(streamer.unapplied_since(100), None);
 
         let replica = replica_manager.get_replica(replica_id).unwrap();
         assert_eq!(replica.last_received_lsn, 90);
         assert_eq!(replica.last_applied_lsn, 85);
         assert_eq!(replica.lag_bytes, 15); // 100 - 85
+                                           // Applied data is as old as entry 100, written two seconds ago
+        assert!(replica.lag_duration >= Duration::from_secs(2));
+    }
+
+    fn wal_entry(lsn: u64) -> StreamingWalEntry {
+        StreamingWalEntry {
+            lsn,
+            transaction_id: None,
+            operation: "INSERT".to_string(),
+            data: vec![0; 100],
+            timestamp: current_timestamp(),
+            checksum: 0,
+        }
+    }
+
+    /// A streamer whose buffers hold two entries, with `mode` replica
+    /// streaming into a channel nobody reads yet
+    async fn stalled_replica(
+        mode: ReplicationMode,
+    ) -> (
+        Arc<ReplicaManager>,
+        Arc<WalStreamer>,
+        ReplicaId,
+        mpsc::Receiver<ReplicationMessage>,
+        tokio::task::JoinHandle<Result<()>>,
+    ) {
+        let replica_manager = Arc::new(ReplicaManager::new(ReplicaManagerConfig::default()));
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let replica_id = replica_manager
+            .register_replica("slow".to_string(), addr, mode)
+            .unwrap();
+        let config = StreamingConfig {
+            max_send_buffer: 2 * wal_entry(0).size(),
+            backpressure_timeout: Duration::from_millis(50),
+            keepalive_interval: Duration::from_secs(3600),
+            ..Default::default()
+        };
+        let streamer = Arc::new(WalStreamer::new(replica_manager.clone(), config));
+
+        let (tx, rx) = mpsc::channel(1);
+        let stream = tokio::spawn({
+            let streamer = streamer.clone();
+            async move { streamer.stream_to_replica(replica_id, 0, tx).await }
+        });
+        while !streamer.buffer_depths().contains_key(&replica_id) {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        (replica_manager, streamer, replica_id, rx, stream)
+    }
+
+    #[tokio::test]
+    async fn test_async_replica_overflow_requires_resync() {
+        let (replica_manager, streamer, replica_id, rx, stream) =
+            stalled_replica(ReplicationMode::Async).await;
+
+        // Commits never wait on an async replica
+        for lsn in 1..=10 {
+            streamer.broadcast_entry(wal_entry(lsn)).await.unwrap();
+        }
+
+        let replica = replica_manager.get_replica(replica_id).unwrap();
+        assert_eq!(replica.state, ReplicaState::Disconnected);
+        assert!(replica.needs_resync);
+        assert!(streamer.buffer_depths().is_empty());
+
+        drop(rx);
+        assert!(stream.await.unwrap().is_err());
+
+        // Reconnecting gets it sent to resync, however recent its LSN
+        let (tx, mut rx) = mpsc::channel(1);
+        streamer
+            .stream_to_replica(replica_id, 10, tx)
+            .await
+            .unwrap();
+        assert!(matches!(
+            rx.recv().await,
+            Some(ReplicationMessage::ResyncRequired { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sync_replica_applies_backpressure() {
+        let (replica_manager, streamer, replica_id, mut rx, _stream) =
+            stalled_replica(ReplicationMode::Sync).await;
+        let high_water = 2 * wal_entry(0).size();
+
+        let mut lsn = 0;
+        let err = loop {
+            lsn += 1;
+            assert!(lsn < 10, "buffer never filled");
+            if let Err(e) = streamer.broadcast_entry(wal_entry(lsn)).await {
+                break e;
+            }
+        };
+        assert!(err.to_string().contains("bytes behind"), "{}", err);
+        let depth = streamer.buffer_depths()[&replica_id];
+        assert_eq!(depth.entries, 2);
+        assert!(depth.bytes <= high_water);
+        assert!(
+            !replica_manager
+                .get_replica(replica_id)
+                .unwrap()
+                .needs_resync
+        );
+
+        // Once the replica reads again, commits go through
+        tokio::spawn(async move { while rx.recv().await.is_some() {} });
+        streamer.broadcast_entry(wal_entry(lsn)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconnecting_replica_catches_up_from_retained_wal() {
+        let replica_manager = Arc::new(ReplicaManager::new(ReplicaManagerConfig::default()));
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 5433);
+        let replica_id = replica_manager
+            .register_replica("returning".to_string(), addr, ReplicationMode::Async)
+            .unwrap();
+        let config = StreamingConfig {
+            keepalive_interval: Duration::from_secs(3600),
+            max_retained_wal: 4 * wal_entry(0).size(),
+            ..Default::default()
+        };
+        let streamer = Arc::new(WalStreamer::new(replica_manager.clone(), config));
+        for lsn in 1..=6 {
+            streamer.broadcast_entry(wal_entry(lsn)).await.unwrap();
+        }
+        // Four entries fit, so 1 and 2 are gone
+        assert_eq!(streamer.oldest_available_lsn(), 3);
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let stream = tokio::spawn({
+            let streamer = streamer.clone();
+            async move { streamer.stream_to_replica(replica_id, 4, tx).await }
+        });
+        let mut received = Vec::new();
+        while received.len() < 3 {
+            if let Some(ReplicationMessage::WalData { start_lsn, .. }) = rx.recv().await {
+                received.push(start_lsn);
+            }
+        }
+        assert_eq!(received, vec![4, 5, 6]);
+        assert_eq!(
+            replica_manager.get_replica(replica_id).unwrap().state,
+            ReplicaState::Streaming
+        );
+        stream.abort();
+
+        // WAL recycled past what the replica needs
+        streamer.discard_through(5);
+        let (tx, mut rx) = mpsc::channel(1);
+        streamer.stream_to_replica(replica_id, 4, tx).await.unwrap();
+        match rx.recv().await {
+            Some(ReplicationMessage::ResyncRequired {
+                oldest_available_lsn,
+                current_lsn,
+            }) => assert_eq!((oldest_available_lsn, current_lsn), (6, 6)),
+            other => panic!("expected ResyncRequired, got {:?}", other),
+        }
+        assert!(
+            replica_manager
+                .get_replica(replica_id)
+                .unwrap()
+                .needs_resync
+        );
     }
 }
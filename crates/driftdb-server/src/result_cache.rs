@@ -0,0 +1,405 @@
+//! Shared cache of query results
+//!
+//! Dashboards tend to run the same reads every few seconds over data that
+//! barely changes. The cache keeps the result of a `SELECT` or `WITH`
+//! query together with the sequence every table it read was at. Tables
+//! only change by appending events, so while none of those sequences (nor
+//! the catalog version) has moved, running the query again would return
+//! the same rows. A lookup that finds one of the tables has advanced drops
+//! the entry and runs the query.
+//!
+//! Keys are the normalized statement text and its literals, as for the
+//! [statement cache](crate::statement_cache), plus the session's search
+//! path. Statements in a transaction, temporal queries, reads of
+//! materialized views, and queries calling functions whose value changes
+//! between calls (`now()`, `random()`, ...) are not cached.
+
+use std::collections::{BTreeMap, HashSet};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::Serialize;
+use sqlparser::ast::{ObjectName, Query, Statement, Visit, Visitor};
+
+use crate::executor::QueryResult;
+use crate::metrics;
+use crate::statement_cache::normalize;
+
+/// Label for this cache in the `driftdb_cache_*` metrics
+const CACHE_TYPE: &str = "query_result";
+
+/// Functions whose value differs from one call to the next
+const VOLATILE_FUNCTIONS: &[&str] = &[
+    "NOW(",
+    "RANDOM(",
+    "CURRENT_TIMESTAMP",
+    "CURRENT_DATE",
+    "CURRENT_TIME",
+    "LOCALTIMESTAMP",
+    "LOCALTIME",
+    "CLOCK_TIMESTAMP",
+    "NEXTVAL(",
+    "CURRVAL(",
+    "GEN_RANDOM_UUID",
+    "UUID_GENERATE",
+];
+
+/// Result cache limits
+#[derive(Debug, Clone)]
+pub struct ResultCacheConfig {
+    /// Maximum number of cached results; 0 disables the cache
+    pub max_entries: usize,
+    /// Approximate upper bound on memory held by cached results
+    pub max_bytes: usize,
+    /// How long a result may be served, however unchanged its tables
+    pub ttl: Duration,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_bytes: 64 * 1024 * 1024,
+            ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Cache counters, as reported by [`ResultCache::stats`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResultCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    /// Entries dropped because a table they read has new events
+    pub invalidations: u64,
+    /// Entries dropped for outliving the TTL
+    pub expirations: u64,
+    pub entries: usize,
+    pub size_bytes: usize,
+}
+
+struct Entry {
+    result: QueryResult,
+    /// Sequence of every table the query read, when it ran
+    tables: BTreeMap<String, u64>,
+    catalog_version: u64,
+    stored_at: Instant,
+    size: usize,
+}
+
+struct CacheState {
+    entries: LruCache<String, Entry>,
+    size_bytes: usize,
+    stats: ResultCacheStats,
+}
+
+/// Query results shared by every session
+pub struct ResultCache {
+    config: ResultCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl ResultCache {
+    pub fn new(config: ResultCacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState {
+                entries: LruCache::unbounded(),
+                size_bytes: 0,
+                stats: ResultCacheStats::default(),
+            }),
+        }
+    }
+
+    /// A cache that never holds anything
+    pub fn disabled() -> Self {
+        Self::new(ResultCacheConfig {
+            max_entries: 0,
+            ..Default::default()
+        })
+    }
+
+    /// Whether the cache holds anything at all; a zero limit disables it
+    pub fn is_enabled(&self) -> bool {
+        self.config.max_entries > 0 && self.config.max_bytes > 0
+    }
+
+    /// The key `sql` is cached under when run with `search_path`, or
+    /// `None` when its result mustn't be cached
+    pub fn key(&self, sql: &str, search_path: &[String]) -> Option<String> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let (shape, literals) = normalize(sql)?;
+        let upper = shape.to_uppercase();
+        if !(upper.starts_with("SELECT") || upper.starts_with("WITH"))
+            || VOLATILE_FUNCTIONS.iter().any(|f| upper.contains(f))
+        {
+            return None;
+        }
+        Some(format!(
+            "{}\u{0}{:?}\u{0}{}",
+            shape,
+            literals,
+            search_path.join(",")
+        ))
+    }
+
+    /// The result stored under `key`, if every table it read is still at
+    /// the sequence it was read at. `sequence_of` reports a table's
+    /// current sequence; an entry that's fallen behind is dropped.
+    pub fn get(
+        &self,
+        key: &str,
+        catalog_version: u64,
+        sequence_of: impl Fn(&str) -> Option<u64>,
+    ) -> Option<QueryResult> {
+        let mut state = self.state.lock();
+        let Some(entry) = state.entries.get(key) else {
+            state.stats.misses += 1;
+            metrics::record_cache_miss(CACHE_TYPE);
+            return None;
+        };
+
+        let expired = entry.stored_at.elapsed() > self.config.ttl;
+        let current = entry.catalog_version == catalog_version
+            && entry
+                .tables
+                .iter()
+                .all(|(table, sequence)| sequence_of(table) == Some(*sequence));
+        if !expired && current {
+            let result = entry.result.clone();
+            state.stats.hits += 1;
+            metrics::record_cache_hit(CACHE_TYPE);
+            return Some(result);
+        }
+
+        if let Some(entry) = state.entries.pop(key) {
+            state.size_bytes -= entry.size;
+        }
+        if expired {
+            state.stats.expirations += 1;
+        } else {
+            state.stats.invalidations += 1;
+        }
+        state.stats.misses += 1;
+        metrics::record_cache_miss(CACHE_TYPE);
+        metrics::update_cache_size(CACHE_TYPE, state.size_bytes);
+        None
+    }
+
+    /// Store `result` of the query cached under `key`, which read `tables`
+    /// at the given sequences
+    pub fn insert(
+        &self,
+        key: String,
+        result: &QueryResult,
+        tables: BTreeMap<String, u64>,
+        catalog_version: u64,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        // The result's debug form is a fair proxy for its heap footprint
+        let size = key.len() + format!("{:?}", result).len();
+        if size > self.config.max_bytes {
+            return;
+        }
+        let entry = Entry {
+            result: result.clone(),
+            tables,
+            catalog_version,
+            stored_at: Instant::now(),
+            size,
+        };
+
+        let mut state = self.state.lock();
+        state.size_bytes += size;
+        if let Some(old) = state.entries.put(key, entry) {
+            // Another session ran the same query concurrently
+            state.size_bytes -= old.size;
+        }
+        while state.entries.len() > self.config.max_entries
+            || state.size_bytes > self.config.max_bytes
+        {
+            match state.entries.pop_lru() {
+                Some((_, evicted)) => {
+                    state.size_bytes -= evicted.size;
+                    state.stats.evictions += 1;
+                    metrics::record_cache_eviction(CACHE_TYPE);
+                }
+                None => break,
+            }
+        }
+        metrics::update_cache_size(CACHE_TYPE, state.size_bytes);
+    }
+
+    #[cfg(test)]
+    pub fn stats(&self) -> ResultCacheStats {
+        let state = self.state.lock();
+        ResultCacheStats {
+            entries: state.entries.len(),
+            size_bytes: state.size_bytes,
+            ..state.stats.clone()
+        }
+    }
+}
+
+/// Relations a query names, as written, leaving out those defined by its
+/// own `WITH` clauses
+pub fn read_relations(statements: &[Statement]) -> Vec<String> {
+    #[derive(Default)]
+    struct Relations {
+        names: Vec<String>,
+        ctes: HashSet<String>,
+    }
+
+    impl Visitor for Relations {
+        type Break = ();
+
+        fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+            if let Some(with) = &query.with {
+                for cte in &with.cte_tables {
+                    self.ctes.insert(cte.alias.name.value.clone());
+                }
+            }
+            ControlFlow::Continue(())
+        }
+
+        fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<()> {
+            let name = relation
+                .0
+                .iter()
+                .map(|ident| ident.value.as_str())
+                .collect::<Vec<_>>()
+                .join(".");
+            self.names.push(name);
+            ControlFlow::Continue(())
+        }
+    }
+
+    let mut relations = Relations::default();
+    for statement in statements {
+        let _ = statement.visit(&mut relations);
+    }
+    let Relations { mut names, ctes } = relations;
+    names.retain(|name| !ctes.contains(name));
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use sqlparser::dialect::GenericDialect;
+    use sqlparser::parser::Parser;
+
+    fn rows(n: i64) -> QueryResult {
+        QueryResult::Select {
+            columns: vec!["n".to_string()],
+            rows: vec![vec![Value::from(n)]],
+        }
+    }
+
+    fn tables(entries: &[(&str, u64)]) -> BTreeMap<String, u64> {
+        entries.iter().map(|(t, s)| (t.to_string(), *s)).collect()
+    }
+
+    #[test]
+    fn test_key_covers_literals_and_search_path() {
+        let cache = ResultCache::new(ResultCacheConfig::default());
+        let public = vec!["public".to_string()];
+        let app = vec!["app".to_string(), "public".to_string()];
+
+        let key = cache.key("SELECT * FROM t WHERE id = 1", &public).unwrap();
+        assert_ne!(
+            Some(&key),
+            cache.key("SELECT * FROM t WHERE id = 2", &public).as_ref()
+        );
+        assert_ne!(
+            Some(&key),
+            cache.key("SELECT * FROM t WHERE id = 1", &app).as_ref()
+        );
+        assert_eq!(
+            Some(key),
+            cache.key("SELECT  *  FROM t WHERE id = 1;", &public)
+        );
+
+        assert!(cache
+            .key("INSERT INTO t (id) VALUES (1)", &public)
+            .is_none());
+        assert!(cache.key("SELECT now() FROM t", &public).is_none());
+        assert!(cache
+            .key("SELECT * FROM t WHERE d < CURRENT_DATE", &public)
+            .is_none());
+        assert!(cache
+            .key("SELECT * FROM t FOR SYSTEM_TIME AS OF @seq:3", &public)
+            .is_none());
+        assert!(ResultCache::disabled().key("SELECT 1", &public).is_none());
+    }
+
+    #[test]
+    fn test_hit_until_a_table_advances() {
+        let cache = ResultCache::new(ResultCacheConfig::default());
+        cache.insert("q".to_string(), &rows(1), tables(&[("a", 5), ("b", 9)]), 1);
+
+        let at = |a: u64| move |table: &str| Some(if table == "a" { a } else { 9 });
+        assert!(cache.get("q", 1, at(5)).is_some());
+        assert!(cache.get("q", 1, at(5)).is_some());
+        // A catalog change or a new event on `a` makes the entry stale
+        assert!(cache.get("q", 2, at(5)).is_none());
+        assert_eq!(cache.stats().entries, 0);
+
+        cache.insert("q".to_string(), &rows(1), tables(&[("a", 5)]), 1);
+        assert!(cache.get("q", 1, at(6)).is_none());
+        assert!(cache.get("q", 1, at(6)).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 3));
+        assert_eq!((stats.invalidations, stats.entries), (2, 0));
+    }
+
+    #[test]
+    fn test_ttl_and_eviction() {
+        let cache = ResultCache::new(ResultCacheConfig {
+            ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        cache.insert("q".to_string(), &rows(1), BTreeMap::new(), 1);
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(cache.get("q", 1, |_| None).is_none());
+        assert_eq!(cache.stats().expirations, 1);
+
+        let cache = ResultCache::new(ResultCacheConfig {
+            max_entries: 2,
+            ..Default::default()
+        });
+        for key in ["a", "b", "a", "c"] {
+            if cache.get(key, 1, |_| None).is_none() {
+                cache.insert(key.to_string(), &rows(1), BTreeMap::new(), 1);
+            }
+        }
+        // `a` was used more recently than `b`, so `b` went
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (2, 1));
+        assert!(cache.get("a", 1, |_| None).is_some());
+        assert!(cache.get("b", 1, |_| None).is_none());
+    }
+
+    #[test]
+    fn test_read_relations_skips_ctes() {
+        let parse = |sql: &str| Parser::parse_sql(&GenericDialect {}, sql).unwrap();
+        assert_eq!(
+            read_relations(&parse(
+                "WITH recent AS (SELECT * FROM orders WHERE id > 10) \
+                 SELECT * FROM recent JOIN app.users u ON u.id = recent.user_id"
+            )),
+            vec!["app.users".to_string(), "orders".to_string()]
+        );
+    }
+}
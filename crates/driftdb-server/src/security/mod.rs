@@ -7,10 +7,14 @@
 //! - Security logging and monitoring
 //! - Role-Based Access Control (RBAC)
 //! - RBAC permission enforcement
+//! - Table and column privileges (GRANT/REVOKE)
+//! - Statement-level audit log
 
+pub mod object_privileges;
 pub mod rbac;
 pub mod rbac_enforcement;
 pub mod sql_validator;
+pub mod statement_audit;
 
 pub use rbac::{Permission, RbacManager};
 pub use sql_validator::SqlValidator;
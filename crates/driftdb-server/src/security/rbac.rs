@@ -5,6 +5,9 @@
 //! - Fine-grained permissions for all database operations
 //! - Role-permission mappings
 //! - User-role assignments
+//! - Role membership: a role inherits the permissions of roles it is a
+//!   member of (`GRANT parent TO child`)
+//! - Table and column privileges granted to roles (`GRANT ... ON table`)
 //! - Permission enforcement at query execution time
 //! - Security audit integration
 
@@ -347,10 +350,97 @@ impl Role {
     }
 }
 
+/// Privileges that can be granted on a single table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TablePrivilege {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl TablePrivilege {
+    /// All table privileges, in the order `GRANT ALL` expands to
+    pub fn all() -> [TablePrivilege; 4] {
+        [
+            TablePrivilege::Select,
+            TablePrivilege::Insert,
+            TablePrivilege::Update,
+            TablePrivilege::Delete,
+        ]
+    }
+
+    /// The role-wide permission that implies this privilege on every table
+    pub fn permission(&self) -> Permission {
+        match self {
+            TablePrivilege::Select => Permission::Select,
+            TablePrivilege::Insert => Permission::Insert,
+            TablePrivilege::Update => Permission::Update,
+            TablePrivilege::Delete => Permission::Delete,
+        }
+    }
+}
+
+impl std::fmt::Display for TablePrivilege {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TablePrivilege::Select => write!(f, "SELECT"),
+            TablePrivilege::Insert => write!(f, "INSERT"),
+            TablePrivilege::Update => write!(f, "UPDATE"),
+            TablePrivilege::Delete => write!(f, "DELETE"),
+        }
+    }
+}
+
+/// Privileges a role holds on one table. A privilege maps to `None` when
+/// it covers the whole table, or to the columns it is limited to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableGrant {
+    pub privileges: HashMap<TablePrivilege, Option<HashSet<String>>>,
+}
+
+/// Who a statement runs as: a user acting with all of their roles, or
+/// with just the role chosen by `SET ROLE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Principal<'a> {
+    pub username: &'a str,
+    pub role: Option<&'a str>,
+}
+
+impl<'a> Principal<'a> {
+    pub fn user(username: &'a str) -> Self {
+        Self {
+            username,
+            role: None,
+        }
+    }
+
+    pub fn with_role(username: &'a str, role: Option<&'a str>) -> Self {
+        Self { username, role }
+    }
+}
+
+impl<'a> From<&'a str> for Principal<'a> {
+    fn from(username: &'a str) -> Self {
+        Principal::user(username)
+    }
+}
+
+impl std::fmt::Display for Principal<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.role {
+            Some(role) => write!(f, "{} (as role {})", self.username, role),
+            None => write!(f, "{}", self.username),
+        }
+    }
+}
+
 /// RBAC Manager for managing roles and permissions
 pub struct RbacManager {
     roles: Arc<RwLock<HashMap<String, Role>>>,
     user_roles: Arc<RwLock<HashMap<String, HashSet<String>>>>, // username -> role names
+    table_grants: Arc<RwLock<HashMap<String, HashMap<String, TableGrant>>>>, // role -> table -> grant
+    role_parents: Arc<RwLock<HashMap<String, HashSet<String>>>>, // role -> roles it is a member of
 }
 
 impl RbacManager {
@@ -374,6 +464,8 @@ impl RbacManager {
         Self {
             roles: Arc::new(RwLock::new(roles)),
             user_roles: Arc::new(RwLock::new(HashMap::new())),
+            table_grants: Arc::new(RwLock::new(HashMap::new())),
+            role_parents: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -414,26 +506,142 @@ impl RbacManager {
         ))
     }
 
-    /// Get all roles assigned to a user
+    /// Get all roles a user holds, directly or inherited through role
+    /// membership
     pub fn get_user_roles(&self, username: &str) -> Vec<Role> {
-        let user_roles = self.user_roles.read();
+        let role_names = self.user_role_names(username);
         let roles = self.roles.read();
+        role_names
+            .iter()
+            .filter_map(|name| roles.get(name).cloned())
+            .collect()
+    }
 
-        if let Some(role_names) = user_roles.get(username) {
-            role_names
-                .iter()
-                .filter_map(|name| roles.get(name).cloned())
-                .collect()
-        } else {
-            Vec::new()
+    /// Names of the roles whose permissions apply to a principal: the role
+    /// set with `SET ROLE` if any, otherwise the user's own roles, plus
+    /// everything those roles inherit
+    pub fn principal_role_names<'a>(&self, principal: impl Into<Principal<'a>>) -> Vec<String> {
+        match principal.into() {
+            Principal {
+                role: Some(role), ..
+            } => self.expand_roles(vec![role.to_string()]),
+            Principal { username, .. } => self.user_role_names(username),
         }
     }
 
+    /// Whether a user holds a role, directly or through membership
+    pub fn is_member_of(&self, username: &str, role_name: &str) -> bool {
+        self.user_role_names(username)
+            .iter()
+            .any(|name| name == role_name)
+    }
+
+    /// Names of the roles a user holds, including inherited ones
+    fn user_role_names(&self, username: &str) -> Vec<String> {
+        let direct: Vec<String> = self
+            .user_roles
+            .read()
+            .get(username)
+            .map(|names| names.iter().cloned().collect())
+            .unwrap_or_default();
+        self.expand_roles(direct)
+    }
+
+    /// The given roles plus every role they inherit from, each listed once
+    fn expand_roles(&self, start: Vec<String>) -> Vec<String> {
+        let parents = self.role_parents.read();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut ordered = Vec::new();
+        let mut pending = start;
+        while let Some(name) = pending.pop() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            if let Some(inherited) = parents.get(&name) {
+                pending.extend(inherited.iter().cloned());
+            }
+            ordered.push(name);
+        }
+        ordered
+    }
+
+    /// Make `member` a member of `role`, so it inherits `role`'s
+    /// permissions and table privileges. Memberships that would form a
+    /// cycle are rejected.
+    pub fn grant_role_membership(&self, role_name: &str, member: &str) -> Result<()> {
+        {
+            let roles = self.roles.read();
+            for name in [role_name, member] {
+                if !roles.contains_key(name) {
+                    return Err(anyhow!("Role '{}' does not exist", name));
+                }
+            }
+            if roles[member].is_system_role {
+                return Err(anyhow!("Cannot modify system role '{}'", member));
+            }
+        }
+
+        if self
+            .expand_roles(vec![role_name.to_string()])
+            .iter()
+            .any(|name| name == member)
+        {
+            return Err(anyhow!(
+                "Role '{}' is a member of role '{}'; granting would create a cycle",
+                role_name,
+                member
+            ));
+        }
+
+        self.role_parents
+            .write()
+            .entry(member.to_string())
+            .or_default()
+            .insert(role_name.to_string());
+
+        info!("Granted role '{}' to role '{}'", role_name, member);
+        Ok(())
+    }
+
+    /// Remove `member` from `role`
+    pub fn revoke_role_membership(&self, role_name: &str, member: &str) -> Result<()> {
+        let mut parents = self.role_parents.write();
+        if let Some(inherited) = parents.get_mut(member) {
+            if inherited.remove(role_name) {
+                info!("Revoked role '{}' from role '{}'", role_name, member);
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!(
+            "Role '{}' is not a member of role '{}'",
+            member,
+            role_name
+        ))
+    }
+
+    /// Roles a role is a direct member of
+    pub fn get_role_parents(&self, role_name: &str) -> Vec<String> {
+        let parents = self.role_parents.read();
+        let mut names: Vec<String> = parents
+            .get(role_name)
+            .map(|names| names.iter().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
     /// Check if user has a specific permission
-    pub fn has_permission(&self, username: &str, permission: Permission) -> bool {
-        let user_roles = self.get_user_roles(username);
+    pub fn has_permission<'a>(
+        &self,
+        principal: impl Into<Principal<'a>>,
+        permission: Permission,
+    ) -> bool {
+        let username = principal.into();
+        let role_names = self.principal_role_names(username);
+        let roles = self.roles.read();
 
-        for role in user_roles {
+        for role in role_names.iter().filter_map(|name| roles.get(name)) {
             if role.has_permission(permission) {
                 debug!(
                     "User '{}' has permission {:?} via role '{}'",
@@ -451,7 +659,12 @@ impl RbacManager {
     }
 
     /// Check if user has permission, returning error if not
-    pub fn require_permission(&self, username: &str, permission: Permission) -> Result<()> {
+    pub fn require_permission<'a>(
+        &self,
+        principal: impl Into<Principal<'a>>,
+        permission: Permission,
+    ) -> Result<()> {
+        let username = principal.into();
         if self.has_permission(username, permission) {
             Ok(())
         } else {
@@ -506,6 +719,13 @@ impl RbacManager {
         for roles_set in user_roles.values_mut() {
             roles_set.remove(name);
         }
+        self.table_grants.write().remove(name);
+
+        let mut parents = self.role_parents.write();
+        parents.remove(name);
+        for inherited in parents.values_mut() {
+            inherited.remove(name);
+        }
 
         info!("Deleted custom role '{}'", name);
         Ok(())
@@ -575,6 +795,193 @@ impl RbacManager {
 
         all_permissions
     }
+
+    /// Grant a privilege on a table to a role, optionally limited to some
+    /// columns. Only SELECT can be limited to columns.
+    pub fn grant_table_privilege(
+        &self,
+        role_name: &str,
+        table: &str,
+        privilege: TablePrivilege,
+        columns: Option<Vec<String>>,
+    ) -> Result<()> {
+        if !self.roles.read().contains_key(role_name) {
+            return Err(anyhow!("Role '{}' does not exist", role_name));
+        }
+        if columns.is_some() && privilege != TablePrivilege::Select {
+            return Err(anyhow!(
+                "Column privileges are only supported for SELECT, not {}",
+                privilege
+            ));
+        }
+
+        let table = table.to_lowercase();
+        let mut grants = self.table_grants.write();
+        let grant = grants
+            .entry(role_name.to_string())
+            .or_default()
+            .entry(table.clone())
+            .or_default();
+        match (grant.privileges.get_mut(&privilege), columns) {
+            // Already granted on the whole table
+            (Some(None), _) => {}
+            (Some(Some(existing)), Some(columns)) => {
+                existing.extend(columns.iter().map(|c| c.to_lowercase()));
+            }
+            (_, columns) => {
+                grant.privileges.insert(
+                    privilege,
+                    columns.map(|cols| cols.iter().map(|c| c.to_lowercase()).collect()),
+                );
+            }
+        }
+
+        info!(
+            "Granted {} on '{}' to role '{}'",
+            privilege, table, role_name
+        );
+        Ok(())
+    }
+
+    /// Revoke a privilege on a table from a role. With `columns`, only those
+    /// column privileges are revoked; a grant on the whole table is kept.
+    pub fn revoke_table_privilege(
+        &self,
+        role_name: &str,
+        table: &str,
+        privilege: TablePrivilege,
+        columns: Option<Vec<String>>,
+    ) -> Result<()> {
+        if !self.roles.read().contains_key(role_name) {
+            return Err(anyhow!("Role '{}' does not exist", role_name));
+        }
+
+        let table = table.to_lowercase();
+        let mut grants = self.table_grants.write();
+        let Some(role_grants) = grants.get_mut(role_name) else {
+            return Ok(());
+        };
+        if let Some(grant) = role_grants.get_mut(&table) {
+            match columns {
+                None => {
+                    grant.privileges.remove(&privilege);
+                }
+                Some(columns) => {
+                    if let Some(Some(existing)) = grant.privileges.get_mut(&privilege) {
+                        for column in &columns {
+                            existing.remove(&column.to_lowercase());
+                        }
+                        if existing.is_empty() {
+                            grant.privileges.remove(&privilege);
+                        }
+                    }
+                }
+            }
+            if grant.privileges.is_empty() {
+                role_grants.remove(&table);
+            }
+        }
+
+        info!(
+            "Revoked {} on '{}' from role '{}'",
+            privilege, table, role_name
+        );
+        Ok(())
+    }
+
+    /// Privileges a role holds on a table
+    pub fn get_table_grant(&self, role_name: &str, table: &str) -> Option<TableGrant> {
+        let grants = self.table_grants.read();
+        grants.get(role_name)?.get(&table.to_lowercase()).cloned()
+    }
+
+    /// Whether any role has been granted anything on a table
+    pub fn table_has_grants(&self, table: &str) -> bool {
+        let table = table.to_lowercase();
+        let grants = self.table_grants.read();
+        grants.values().any(|tables| tables.contains_key(&table))
+    }
+
+    /// Tables at least one role has been granted privileges on
+    pub fn granted_tables(&self) -> Vec<String> {
+        let grants = self.table_grants.read();
+        let mut tables: Vec<String> = grants
+            .values()
+            .flat_map(|tables| tables.keys().cloned())
+            .collect();
+        tables.sort();
+        tables.dedup();
+        tables
+    }
+
+    /// Check that a user may use `privilege` on `table`, returning an error
+    /// naming the missing privilege if not. `columns` lists the columns a
+    /// SELECT reads, or `None` when it reads all of them.
+    ///
+    /// Role-wide permissions (e.g. `Insert` on the `user` role) cover every
+    /// table. Tables nobody has been granted privileges on stay governed by
+    /// those role permissions alone, so object privileges are opt-in per
+    /// table.
+    pub fn check_table_privilege<'a>(
+        &self,
+        principal: impl Into<Principal<'a>>,
+        table: &str,
+        privilege: TablePrivilege,
+        columns: Option<&[String]>,
+    ) -> Result<()> {
+        let username = principal.into();
+        if self.has_permission(username, privilege.permission()) || !self.table_has_grants(table) {
+            return Ok(());
+        }
+
+        let table = table.to_lowercase();
+        let role_names = self.principal_role_names(username);
+        let grants = self.table_grants.read();
+        let mut granted_columns = HashSet::new();
+        for role_name in &role_names {
+            let Some(grant) = grants.get(role_name).and_then(|tables| tables.get(&table)) else {
+                continue;
+            };
+            match grant.privileges.get(&privilege) {
+                Some(None) => return Ok(()),
+                Some(Some(cols)) => granted_columns.extend(cols.iter().cloned()),
+                None => {}
+            }
+        }
+
+        if !granted_columns.is_empty() {
+            if let Some(columns) = columns {
+                match columns
+                    .iter()
+                    .find(|c| !granted_columns.contains(&c.to_lowercase()))
+                {
+                    None => return Ok(()),
+                    Some(column) => {
+                        warn!(
+                            "Permission denied for user '{}': missing {} on {}.{}",
+                            username, privilege, table, column
+                        );
+                        return Err(anyhow!(
+                            "permission denied for column {} of table {}: missing {} privilege",
+                            column,
+                            table,
+                            privilege
+                        ));
+                    }
+                }
+            }
+        }
+
+        warn!(
+            "Permission denied for user '{}': missing {} on {}",
+            username, privilege, table
+        );
+        Err(anyhow!(
+            "permission denied for table {}: missing {} privilege",
+            table,
+            privilege
+        ))
+    }
 }
 
 impl Default for RbacManager {
@@ -729,4 +1136,166 @@ mod tests {
         let result = rbac.add_permission_to_role("readonly", Permission::Insert);
         assert!(result.is_err());
     }
+
+    fn custom_role(rbac: &RbacManager, name: &str, permissions: &[Permission]) {
+        rbac.create_custom_role(
+            name.to_string(),
+            permissions.iter().copied().collect(),
+            format!("{} role", name),
+        )
+        .unwrap();
+    }
+
+    /// `db_admin` inherits `writer`, which inherits `read_only`
+    fn role_hierarchy() -> RbacManager {
+        let rbac = RbacManager::new();
+        custom_role(&rbac, "read_only", &[Permission::Select]);
+        custom_role(
+            &rbac,
+            "writer",
+            &[Permission::Insert, Permission::Update, Permission::Delete],
+        );
+        custom_role(&rbac, "db_admin", &[Permission::CreateTable]);
+        rbac.grant_role_membership("read_only", "writer").unwrap();
+        rbac.grant_role_membership("writer", "db_admin").unwrap();
+        rbac
+    }
+
+    #[test]
+    fn test_child_role_inherits_permissions() {
+        let rbac = role_hierarchy();
+        rbac.grant_role("ivan", "db_admin").unwrap();
+        rbac.grant_role("judy", "writer").unwrap();
+
+        assert!(rbac.has_permission("ivan", Permission::CreateTable));
+        assert!(rbac.has_permission("ivan", Permission::Insert));
+        assert!(rbac.has_permission("ivan", Permission::Select));
+        assert!(rbac.has_permission("judy", Permission::Select));
+        assert!(!rbac.has_permission("judy", Permission::CreateTable));
+
+        // Table privileges are inherited too
+        rbac.grant_table_privilege("read_only", "orders", TablePrivilege::Select, None)
+            .unwrap();
+        rbac.remove_permission_from_role("read_only", Permission::Select)
+            .unwrap();
+        assert!(rbac
+            .check_table_privilege("ivan", "orders", TablePrivilege::Select, None)
+            .is_ok());
+
+        rbac.revoke_role_membership("read_only", "writer").unwrap();
+        assert!(rbac
+            .check_table_privilege("ivan", "orders", TablePrivilege::Select, None)
+            .is_err());
+        assert!(rbac.has_permission("ivan", Permission::Insert));
+    }
+
+    #[test]
+    fn test_principal_with_role_uses_only_that_role() {
+        let rbac = role_hierarchy();
+        rbac.grant_role("kim", "superuser").unwrap();
+        rbac.grant_role("kim", "db_admin").unwrap();
+
+        let as_reader = Principal::with_role("kim", Some("read_only"));
+        assert!(rbac.has_permission(as_reader, Permission::Select));
+        assert!(!rbac.has_permission(as_reader, Permission::Insert));
+        assert!(rbac.has_permission("kim", Permission::Insert));
+
+        let as_writer = Principal::with_role("kim", Some("writer"));
+        assert_eq!(
+            rbac.principal_role_names(as_writer),
+            vec!["writer".to_string(), "read_only".to_string()]
+        );
+        assert!(rbac.is_member_of("kim", "read_only"));
+        assert!(!rbac.is_member_of("kim", "readonly"));
+    }
+
+    #[test]
+    fn test_role_membership_cycle_is_rejected() {
+        let rbac = role_hierarchy();
+
+        let err = rbac
+            .grant_role_membership("db_admin", "read_only")
+            .unwrap_err();
+        assert!(err.to_string().contains("cycle"), "{}", err);
+        assert!(rbac.grant_role_membership("writer", "writer").is_err());
+        assert!(rbac.get_role_parents("read_only").is_empty());
+
+        // System roles can be inherited from but not changed
+        assert!(rbac.grant_role_membership("readonly", "writer").is_ok());
+        assert!(rbac.grant_role_membership("writer", "readonly").is_err());
+    }
+
+    fn analyst_rbac() -> RbacManager {
+        let rbac = RbacManager::new();
+        rbac.create_custom_role(
+            "analyst".to_string(),
+            HashSet::new(),
+            "Reads selected tables".to_string(),
+        )
+        .unwrap();
+        rbac.grant_role("grace", "analyst").unwrap();
+        rbac
+    }
+
+    #[test]
+    fn test_role_without_insert_grant_is_blocked() {
+        let rbac = analyst_rbac();
+        rbac.grant_table_privilege("analyst", "orders", TablePrivilege::Select, None)
+            .unwrap();
+
+        assert!(rbac
+            .check_table_privilege("grace", "orders", TablePrivilege::Select, None)
+            .is_ok());
+        let err = rbac
+            .check_table_privilege("grace", "orders", TablePrivilege::Insert, None)
+            .unwrap_err();
+        assert!(err.to_string().contains("missing INSERT"), "{}", err);
+
+        // Role-wide permissions still cover granted tables
+        rbac.grant_role("heidi", "user").unwrap();
+        assert!(rbac
+            .check_table_privilege("heidi", "orders", TablePrivilege::Insert, None)
+            .is_ok());
+
+        rbac.revoke_table_privilege("analyst", "orders", TablePrivilege::Select, None)
+            .unwrap();
+        assert!(!rbac.table_has_grants("orders"));
+    }
+
+    #[test]
+    fn test_column_level_select_grant() {
+        let rbac = analyst_rbac();
+        rbac.grant_table_privilege(
+            "analyst",
+            "Customers",
+            TablePrivilege::Select,
+            Some(vec!["id".to_string(), "region".to_string()]),
+        )
+        .unwrap();
+
+        let allowed = ["id".to_string(), "REGION".to_string()];
+        assert!(rbac
+            .check_table_privilege("grace", "customers", TablePrivilege::Select, Some(&allowed))
+            .is_ok());
+
+        let secret = ["id".to_string(), "email".to_string()];
+        let err = rbac
+            .check_table_privilege("grace", "customers", TablePrivilege::Select, Some(&secret))
+            .unwrap_err();
+        assert!(err.to_string().contains("column email"), "{}", err);
+
+        // SELECT * needs every column
+        assert!(rbac
+            .check_table_privilege("grace", "customers", TablePrivilege::Select, None)
+            .is_err());
+
+        assert!(rbac
+            .grant_table_privilege(
+                "analyst",
+                "customers",
+                TablePrivilege::Insert,
+                Some(vec!["id".to_string()]),
+            )
+            .is_err());
+    }
 }
@@ -6,21 +6,26 @@
 #![allow(dead_code)]
 
 use anyhow::{anyhow, Result};
+use sqlparser::ast::{ObjectType, Query, SetExpr, Statement};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::{debug, warn};
 
+use super::rbac::Principal;
 use super::{Permission, RbacManager};
 use crate::security_audit::{AuditEventType, AuditOutcome, AuditSeverity, SecurityAuditLogger};
 
 /// Check if user has permission to execute a query type
-pub fn check_query_permission(
+pub fn check_query_permission<'a>(
     rbac_manager: &Arc<RbacManager>,
-    username: &str,
+    principal: impl Into<Principal<'a>>,
     query_type: &str,
     audit_logger: Option<&Arc<SecurityAuditLogger>>,
     client_addr: &str,
 ) -> Result<()> {
+    let principal = principal.into();
     let permission = match query_type.to_uppercase().as_str() {
         "SELECT" => Permission::Select,
         "INSERT" => Permission::Insert,
@@ -55,21 +60,21 @@ pub fn check_query_permission(
 
     debug!(
         "Checking permission {:?} for user '{}'",
-        permission, username
+        permission, principal
     );
 
-    match rbac_manager.require_permission(username, permission) {
+    match rbac_manager.require_permission(principal, permission) {
         Ok(_) => {
             debug!(
                 "Permission granted: {:?} for user '{}'",
-                permission, username
+                permission, principal
             );
             Ok(())
         }
         Err(e) => {
             warn!(
                 "Permission denied: {:?} for user '{}' - {}",
-                permission, username, e
+                permission, principal, e
             );
 
             // Log to audit logger if provided
@@ -79,7 +84,7 @@ pub fn check_query_permission(
                     .unwrap_or_else(|_| "127.0.0.1:0".parse().unwrap());
                 logger.log_event(
                     AuditEventType::PermissionDenied,
-                    Some(username.to_string()),
+                    Some(principal.username.to_string()),
                     addr,
                     AuditSeverity::Warning,
                     format!(
@@ -97,7 +102,7 @@ pub fn check_query_permission(
 
             Err(anyhow!(
                 "Permission denied: user '{}' does not have '{}' permission for {} operation",
-                username,
+                principal,
                 permission,
                 query_type
             ))
@@ -105,6 +110,138 @@ pub fn check_query_permission(
     }
 }
 
+/// Query types a statement needs permission for, in the form
+/// `check_query_permission` expects, read from its parsed form: a
+/// `WITH ... INSERT` needs INSERT, `CREATE UNIQUE INDEX` needs CREATE
+/// INDEX. `None` for statements with no mapping, which a session with a
+/// role set refuses. SQL sqlparser can't parse maps by its leading
+/// keywords, and only for DriftDB's own read and maintenance commands.
+pub fn statement_query_types(sql: &str) -> Option<Vec<&'static str>> {
+    let Ok(statements) = Parser::parse_sql(&GenericDialect {}, sql) else {
+        return unparsed_query_type(sql).map(|query_type| vec![query_type]);
+    };
+    let mut types = Vec::new();
+    for statement in &statements {
+        statement_types(statement, &mut types)?;
+    }
+    Some(types)
+}
+
+fn statement_types(statement: &Statement, types: &mut Vec<&'static str>) -> Option<()> {
+    let query_type = match statement {
+        Statement::Query(query) => return query_types(query, types),
+        Statement::Explain { statement, .. } => return statement_types(statement, types),
+        Statement::Insert(_) => "INSERT",
+        Statement::Update { .. } => "UPDATE",
+        Statement::Delete(_) => "DELETE",
+        Statement::CreateTable(_) => "CREATE TABLE",
+        Statement::AlterTable { .. } => "ALTER TABLE",
+        Statement::Truncate { .. } => "TRUNCATE TABLE",
+        Statement::CreateIndex(_) => "CREATE INDEX",
+        Statement::Drop {
+            object_type: ObjectType::Table,
+            ..
+        } => "DROP TABLE",
+        Statement::Drop {
+            object_type: ObjectType::Index,
+            ..
+        } => "DROP INDEX",
+        Statement::CreateDatabase { .. } => "CREATE DATABASE",
+        Statement::StartTransaction { .. } => "BEGIN",
+        Statement::Commit { .. } => "COMMIT",
+        Statement::Rollback { .. } => "ROLLBACK",
+        // Session settings, savepoints and introspection
+        Statement::SetVariable { .. }
+        | Statement::SetRole { .. }
+        | Statement::SetTimeZone { .. }
+        | Statement::SetNames { .. }
+        | Statement::SetNamesDefault {}
+        | Statement::SetTransaction { .. }
+        | Statement::ShowVariable { .. }
+        | Statement::ShowVariables { .. }
+        | Statement::ShowStatus { .. }
+        | Statement::ShowTables { .. }
+        | Statement::ShowColumns { .. }
+        | Statement::ShowCreate { .. }
+        | Statement::ExplainTable { .. }
+        | Statement::Savepoint { .. }
+        | Statement::ReleaseSavepoint { .. } => return Some(()),
+        _ => return None,
+    };
+    if !types.contains(&query_type) {
+        types.push(query_type);
+    }
+    Some(())
+}
+
+fn query_types(query: &Query, types: &mut Vec<&'static str>) -> Option<()> {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            query_types(&cte.query, types)?;
+        }
+    }
+    set_expr_types(&query.body, types)
+}
+
+fn set_expr_types(body: &SetExpr, types: &mut Vec<&'static str>) -> Option<()> {
+    match body {
+        SetExpr::Insert(statement) | SetExpr::Update(statement) => {
+            statement_types(statement, types)
+        }
+        SetExpr::Query(query) => query_types(query, types),
+        SetExpr::SetOperation { left, right, .. } => {
+            set_expr_types(left, types)?;
+            set_expr_types(right, types)
+        }
+        SetExpr::Select(_) | SetExpr::Values(_) | SetExpr::Table(_) => {
+            if !types.contains(&"SELECT") {
+                types.push("SELECT");
+            }
+            Some(())
+        }
+    }
+}
+
+fn unparsed_query_type(sql: &str) -> Option<&'static str> {
+    let words: Vec<String> = sql
+        .split_whitespace()
+        .take(2)
+        .map(|w| w.trim_end_matches(';').to_uppercase())
+        .collect();
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    match words.as_slice() {
+        ["SELECT", ..] => Some("SELECT"),
+        ["CREATE", "SNAPSHOT"] => Some("CREATE SNAPSHOT"),
+        ["RESTORE", "SNAPSHOT"] => Some("RESTORE SNAPSHOT"),
+        ["COMPACT", ..] => Some("COMPACT"),
+        _ => None,
+    }
+}
+
+/// Parse `SET ROLE name`, `SET ROLE NONE` and `RESET ROLE`. Returns
+/// `Some(None)` when the statement clears the role and `None` for any
+/// other statement.
+pub fn parse_set_role(sql: &str) -> Option<Option<String>> {
+    let tokens: Vec<&str> = sql
+        .trim()
+        .trim_end_matches(';')
+        .split_whitespace()
+        .collect();
+    let upper: Vec<String> = tokens.iter().map(|t| t.to_uppercase()).collect();
+    match upper
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["RESET", "ROLE"] | ["SET", "ROLE", "NONE"] => Some(None),
+        ["SET", "ROLE", _] => Some(Some(
+            tokens[2].trim_matches('"').trim_matches('\'').to_string(),
+        )),
+        _ => None,
+    }
+}
+
 /// Check if user has permission to view users list
 pub fn check_view_users_permission(rbac_manager: &Arc<RbacManager>, username: &str) -> Result<()> {
     rbac_manager.require_permission(username, Permission::ViewUsers)
@@ -127,11 +264,11 @@ pub fn check_view_metrics_permission(
 }
 
 /// Check if user has permission to view audit log
-pub fn check_view_audit_log_permission(
+pub fn check_view_audit_log_permission<'a>(
     rbac_manager: &Arc<RbacManager>,
-    username: &str,
+    principal: impl Into<Principal<'a>>,
 ) -> Result<()> {
-    rbac_manager.require_permission(username, Permission::ViewAuditLog)
+    rbac_manager.require_permission(principal, Permission::ViewAuditLog)
 }
 
 /// Check if user has permission to grant roles
@@ -196,6 +333,69 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_set_role_restricts_a_superuser_until_reset() {
+        let rbac = Arc::new(RbacManager::new());
+        rbac.grant_role("eve", "superuser").unwrap();
+
+        let query_type = "INSERT";
+        assert!(check_query_permission(&rbac, "eve", query_type, None, "127.0.0.1").is_ok());
+
+        let role = parse_set_role("SET ROLE readonly").unwrap();
+        let as_readonly = Principal::with_role("eve", role.as_deref());
+        let err =
+            check_query_permission(&rbac, as_readonly, query_type, None, "127.0.0.1").unwrap_err();
+        assert!(err.to_string().contains("as role readonly"), "{}", err);
+        assert!(check_query_permission(&rbac, as_readonly, "SELECT", None, "127.0.0.1").is_ok());
+
+        let role = parse_set_role("RESET ROLE;").unwrap();
+        let reset = Principal::with_role("eve", role.as_deref());
+        assert!(check_query_permission(&rbac, reset, query_type, None, "127.0.0.1").is_ok());
+    }
+
+    #[test]
+    fn test_parse_set_role() {
+        assert_eq!(
+            parse_set_role("set role \"Analyst\""),
+            Some(Some("Analyst".to_string()))
+        );
+        assert_eq!(parse_set_role("SET ROLE NONE"), Some(None));
+        assert_eq!(parse_set_role("SET search_path TO public"), None);
+    }
+
+    #[test]
+    fn test_statement_query_types_come_from_the_parsed_statement() {
+        let types = |sql| statement_query_types(sql);
+        assert_eq!(types("create table t (id int)"), Some(vec!["CREATE TABLE"]));
+        assert_eq!(types("DELETE FROM t"), Some(vec!["DELETE"]));
+        assert_eq!(types("TRUNCATE t"), Some(vec!["TRUNCATE TABLE"]));
+        assert_eq!(
+            types("CREATE UNIQUE INDEX idx ON t (email)"),
+            Some(vec!["CREATE INDEX"])
+        );
+        assert_eq!(
+            types("WITH s AS (SELECT id FROM a) INSERT INTO t SELECT id FROM s"),
+            Some(vec!["SELECT", "INSERT"])
+        );
+        assert_eq!(types("SET search_path TO app"), Some(vec![]));
+        assert_eq!(types("SELECT * FROM t AS OF @seq:5"), Some(vec!["SELECT"]));
+
+        // No mapping, parsed or not
+        assert_eq!(types("CREATE VIEW v AS SELECT 1"), None);
+        assert_eq!(types("VACUUM t"), None);
+    }
+
+    #[test]
+    fn test_unmapped_statements_are_refused_under_a_role() {
+        let rbac = Arc::new(RbacManager::new());
+        rbac.grant_role("eve", "superuser").unwrap();
+        let as_readonly = Principal::with_role("eve", Some("readonly"));
+
+        let types = statement_query_types("TRUNCATE items").unwrap();
+        assert!(check_query_permission(&rbac, as_readonly, types[0], None, "127.0.0.1").is_err());
+        assert!(statement_query_types("CREATE VIEW v AS SELECT 1").is_none());
+    }
+
     #[test]
     fn test_check_create_table_permission() {
         let rbac = Arc::new(RbacManager::new());
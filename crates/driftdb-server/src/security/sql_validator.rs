@@ -1,41 +1,142 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+
 use crate::errors::security_error;
+use crate::security::statement_audit::StatementCategory;
 use anyhow::{anyhow, Result};
+use driftdb_core::sql_lint::{self, Risk, ValidationReport};
 use tracing::{debug, warn};
 
+/// Which statements a listener accepts. The PostgreSQL listener can run a
+/// restrictive policy for applications while an admin listener keeps the
+/// permissive default.
+#[derive(Debug, Clone)]
+pub struct ValidatorConfig {
+    /// Maximum query length to prevent DoS
+    pub max_statement_length: usize,
+    /// Statement categories accepted; empty accepts every category
+    pub allowed_statements: HashSet<StatementCategory>,
+    /// Statement categories rejected, checked after `allowed_statements`
+    pub denied_statements: HashSet<StatementCategory>,
+    /// Accept several `;`-separated statements in one query string
+    pub allow_multiple_statements: bool,
+    /// Remove `--` and `/* */` comments before the query is checked and
+    /// executed
+    pub strip_comments: bool,
+}
+
+impl Default for ValidatorConfig {
+    fn default() -> Self {
+        Self {
+            max_statement_length: 100_000, // 100KB max query
+            allowed_statements: HashSet::new(),
+            denied_statements: HashSet::new(),
+            allow_multiple_statements: true,
+            strip_comments: false,
+        }
+    }
+}
+
+/// A query rejected by the listener's policy rather than by an injection
+/// check, so sessions can report it with a permission error
+#[derive(Debug)]
+pub struct PolicyViolation(pub String);
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
 /// SQL validation module to prevent injection attacks
 /// Uses a smarter approach that detects actual injection patterns
 /// rather than blocking legitimate SQL syntax
 pub struct SqlValidator {
-    /// Maximum query length to prevent DoS
-    max_query_length: usize,
+    config: ValidatorConfig,
 }
 
 impl SqlValidator {
     pub fn new() -> Self {
-        Self {
-            max_query_length: 100_000, // 100KB max query
-        }
+        Self::with_config(ValidatorConfig::default())
+    }
+
+    pub fn with_config(config: ValidatorConfig) -> Self {
+        Self { config }
     }
 
     /// Validates a SQL query for safety before execution
     /// Uses pattern detection to identify likely injection attempts
+    #[cfg(test)]
     pub fn validate_query(&self, sql: &str) -> Result<()> {
-        self.validate_query_with_context(sql, "unknown")
+        self.prepare_query(sql).map(|_| ())
     }
 
-    /// Validates a SQL query with client context for better error reporting
-    pub fn validate_query_with_context(&self, sql: &str, client_addr: &str) -> Result<()> {
+    /// Validates a SQL query and returns the text to execute, which has its
+    /// comments removed when the policy strips them
+    pub fn prepare_query<'a>(&self, sql: &'a str) -> Result<Cow<'a, str>> {
+        self.prepare_query_with_context(sql, "unknown")
+    }
+
+    fn prepare_query_with_context<'a>(
+        &self,
+        sql: &'a str,
+        client_addr: &str,
+    ) -> Result<Cow<'a, str>> {
         debug!("Validating SQL query: {}", sql);
 
         // Check query length
-        if sql.len() > self.max_query_length {
+        if sql.len() > self.config.max_statement_length {
             warn!("Query exceeds maximum length: {} bytes", sql.len());
             return Err(anyhow!(
                 "Query too long (max {} bytes)",
-                self.max_query_length
+                self.config.max_statement_length
             ));
         }
 
+        let sql = if self.config.strip_comments {
+            strip_comments_keeping_hints(sql)
+        } else {
+            Cow::Borrowed(sql)
+        };
+        self.check_policy(&sql)?;
+        self.check_injection(&sql, client_addr)?;
+        Ok(sql)
+    }
+
+    /// Enforce the statement count and category rules
+    fn check_policy(&self, sql: &str) -> Result<()> {
+        let uncommented = strip_comments(sql);
+        let statements = split_statements(&uncommented);
+        if statements.len() > 1 && !self.config.allow_multiple_statements {
+            warn!("Multiple statements rejected by SQL policy");
+            return Err(PolicyViolation(
+                "multiple statements in one query are not allowed on this connection".to_string(),
+            )
+            .into());
+        }
+
+        for statement in statements {
+            for category in StatementCategory::classify_all(statement) {
+                let allowed = (self.config.allowed_statements.is_empty()
+                    || self.config.allowed_statements.contains(&category))
+                    && !self.config.denied_statements.contains(&category);
+                if !allowed {
+                    warn!("{} statement rejected by SQL policy", category.as_str());
+                    return Err(PolicyViolation(format!(
+                        "{} statements are not allowed on this connection",
+                        category.as_str()
+                    ))
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_injection(&self, sql: &str, client_addr: &str) -> Result<()> {
         let sql_upper = sql.to_uppercase();
 
         // Explicitly allow transaction commands - these are safe standalone commands
@@ -49,262 +150,125 @@ impl SqlValidator {
         }
 
         // Detect common injection patterns
-        if self.detect_comment_injection(&sql_upper) {
-            let error = security_error("SQL comment injection detected", client_addr, Some(sql));
-            error.log();
-            return Err(anyhow!("SQL injection attempt detected: comment injection"));
-        }
-
-        if self.detect_stacked_queries(&sql_upper) {
-            warn!("Stacked queries injection detected");
-            return Err(anyhow!("SQL injection attempt detected: stacked queries"));
-        }
-
-        if self.detect_union_injection(&sql_upper) {
-            warn!("UNION injection detected");
-            return Err(anyhow!("SQL injection attempt detected: UNION injection"));
-        }
-
-        if self.detect_tautology_injection(&sql_upper) {
-            warn!("Tautology injection detected");
-            return Err(anyhow!("SQL injection attempt detected: tautology"));
-        }
-
-        if self.detect_system_command_injection(&sql_upper) {
-            warn!("System command injection detected");
-            return Err(anyhow!("SQL injection attempt detected: system commands"));
-        }
-
-        if self.detect_timing_attack(&sql_upper) {
-            warn!("Timing attack detected");
-            return Err(anyhow!("SQL injection attempt detected: timing attack"));
-        }
-
-        // Check for null bytes
-        if sql.contains('\0') {
-            warn!("Query contains null bytes");
-            return Err(anyhow!("Query contains null bytes"));
+        if let Some(risk) = sql_lint::detect_risks(sql)
+            .into_iter()
+            .find(|risk| risk.is_injection())
+        {
+            match risk {
+                Risk::CommentInjection => {
+                    let error =
+                        security_error("SQL comment injection detected", client_addr, Some(sql));
+                    error.log();
+                }
+                Risk::NullByte => {
+                    warn!("Query contains null bytes");
+                    return Err(anyhow!("Query contains null bytes"));
+                }
+                other => warn!("SQL injection detected: {}", other.description()),
+            }
+            return Err(anyhow!(
+                "SQL injection attempt detected: {}",
+                risk.description()
+            ));
         }
 
         debug!("SQL query validation passed");
         Ok(())
     }
 
-    /// Detect comment-based injection attempts
-    fn detect_comment_injection(&self, sql: &str) -> bool {
-        // Look for suspicious comment patterns that terminate queries
-        let patterns = [
-            "'; --",
-            "\"; --",
-            "') --",
-            "\") --",
-            "'; #",
-            "\"; #",
-            " OR 1=1--",
-            " OR '1'='1'--",
-            "' --",
-        ];
-
-        for pattern in patterns {
-            if sql.contains(pattern) {
-                return true;
-            }
-        }
-
-        // Check for comment after DROP, DELETE, UPDATE without WHERE
-        if (sql.contains("DROP ") || sql.contains("DELETE FROM")) && sql.contains("--") {
-            // More suspicious if there's a comment after dangerous operations
-            let parts: Vec<&str> = sql.split("--").collect();
-            if parts.len() > 1 && !parts[0].contains("WHERE") {
-                return true;
-            }
+    /// Analyze a query without executing it: whether it parses, what it
+    /// references and which risky patterns it contains. Used by the lint
+    /// endpoint.
+    pub fn analyze(&self, sql: &str) -> Result<ValidationReport> {
+        if sql.len() > self.config.max_statement_length {
+            return Err(anyhow!(
+                "Query too long (max {} bytes)",
+                self.config.max_statement_length
+            ));
         }
-
-        false
+        Ok(sql_lint::analyze(sql))
     }
+}
 
-    /// Detect stacked query injection (multiple queries separated by semicolon)
-    fn detect_stacked_queries(&self, sql: &str) -> bool {
-        // Look for patterns like '; DROP TABLE
-        let dangerous_after_semicolon = [
-            "; DROP ",
-            "; DELETE ",
-            "; INSERT ",
-            "; UPDATE ",
-            "; ALTER ",
-            "; EXEC",
-            "; TRUNCATE",
-        ];
-
-        for pattern in dangerous_after_semicolon {
-            if sql.contains(pattern) {
-                return true;
-            }
-        }
-
-        // Check for "; CREATE " but allow safe DDL like "; CREATE INDEX"
-        if sql.contains("; CREATE ") && !sql.contains("; CREATE INDEX") {
-            return true;
-        }
-
-        // Also check for quotes followed by semicolon and dangerous commands
-        let quote_patterns = ["'; DROP", "'; DELETE", "\"; DROP", "\"; DELETE"];
-
-        for pattern in quote_patterns {
-            if sql.contains(pattern) {
-                return true;
-            }
-        }
-
-        false
+/// Remove `--` and `/* */` comments outside quoted strings and identifiers.
+/// Each comment becomes a single space.
+fn strip_comments(sql: &str) -> Cow<'_, str> {
+    if !sql.contains("--") && !sql.contains("/*") {
+        return Cow::Borrowed(sql);
     }
 
-    /// Detect UNION-based injection
-    fn detect_union_injection(&self, sql: &str) -> bool {
-        // UNION SELECT is almost always an injection when combined with certain patterns
-        if sql.contains("UNION") {
-            // Check for common injection patterns with UNION
-            let suspicious_patterns = [
-                "UNION ALL SELECT",
-                "UNION SELECT", // Added general UNION SELECT
-                "UNION ALL",
-                "UNION DISTINCT",
-                " UNION ", // UNION with spaces (common in injections)
-                "'UNION",
-                "\"UNION",
-                ")UNION",
-                "UNION(",
-                "UNION/*", // UNION with comment
-                "UNION--", // UNION with comment
-                "UNION#",  // UNION with comment
-                "UNION SELECT NULL",
-                "UNION SELECT 1",
-                "UNION SELECT @@VERSION",
-                "UNION SELECT USER()",
-                "UNION SELECT DATABASE()",
-                "UNION SELECT SCHEMA_NAME",
-                "UNION SELECT PASSWORD",    // Common target
-                "UNION SELECT TABLE_NAME",  // Information schema access
-                "UNION SELECT COLUMN_NAME", // Information schema access
-            ];
-
-            for pattern in suspicious_patterns {
-                if sql.contains(pattern) {
-                    return true;
-                }
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            // A doubled quote closes and reopens, which keeps it inside
+            out.push(c);
+            if c == q {
+                quote = None;
             }
-
-            // Check if UNION appears after a quote (likely injection)
-            if sql.contains("' UNION") || sql.contains("\" UNION") {
-                return true;
+            continue;
+        }
+        match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                out.push(c);
             }
-
-            // Check for UNION in subqueries (less common but still dangerous)
-            if sql.contains("(SELECT") && sql.contains("UNION") {
-                return true;
+            '-' if chars.peek() == Some(&'-') => {
+                while chars.next_if(|&n| n != '\n').is_some() {}
+                out.push(' ');
             }
-
-            // If UNION is used with FROM clause referencing different tables
-            // This is a common injection pattern
-            if sql.contains("FROM") && sql.contains("UNION") {
-                // Check if it's trying to access system tables
-                let system_tables = ["INFORMATION_SCHEMA", "MYSQL", "SYS", "PG_", "SQLITE_"];
-                for table in system_tables {
-                    if sql.contains(table) {
-                        return true;
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for n in chars.by_ref() {
+                    if prev == '*' && n == '/' {
+                        break;
                     }
+                    prev = n;
                 }
+                out.push(' ');
             }
+            _ => out.push(c),
         }
-
-        false
     }
+    Cow::Owned(out)
+}
 
-    /// Detect tautology-based injection (always true conditions)
-    fn detect_tautology_injection(&self, sql: &str) -> bool {
-        // Detect injection payloads that start with a quote to break out of a string
-        // e.g., "' OR '1'='1" or "' OR 1=1"
-        let trimmed = sql.trim();
-        if (trimmed.starts_with('\'') || trimmed.starts_with('"'))
-            && trimmed.to_uppercase().contains(" OR ")
-        {
-            return true;
-        }
-
-        // Common tautology patterns
-        let patterns = [
-            " OR 1=1",
-            " OR '1'='1'",
-            " OR \"1\"=\"1\"",
-            " OR 'A'='A'",
-            " OR ''=''",
-            " OR 1=1 --",
-            " OR TRUE",
-            "WHERE 1=1 AND",
-            "WHERE '1'='1' AND",
-        ];
-
-        for pattern in patterns {
-            if sql.contains(pattern) {
-                // Make sure it's not in a string literal
-                // This is a simple check - could be made more sophisticated
-                let before_pattern = sql.split(pattern).next().unwrap_or("");
-                let single_quotes = before_pattern.matches('\'').count();
-                let double_quotes = before_pattern.matches('"').count();
-
-                // If quotes are balanced, it's likely not in a string
-                if single_quotes % 2 == 0 && double_quotes % 2 == 0 {
-                    return true;
-                }
-            }
-        }
-
-        false
-    }
-
-    /// Detect attempts to execute system commands
-    fn detect_system_command_injection(&self, sql: &str) -> bool {
-        let dangerous_functions = [
-            "XP_CMDSHELL",
-            "SP_EXECUTESQL",
-            "EXEC(",
-            "EXECUTE(",
-            "LOAD_FILE",
-            "INTO OUTFILE",
-            "INTO DUMPFILE",
-            "../",
-            "..\\",
-            "/ETC/PASSWD",
-            "C:\\",
-        ];
-
-        for func in dangerous_functions {
-            if sql.contains(func) {
-                return true;
-            }
+/// [`strip_comments`], but a leading `/*+ ... */` planner hint comment is
+/// kept for the planner
+fn strip_comments_keeping_hints(sql: &str) -> Cow<'_, str> {
+    let trimmed = sql.trim_start();
+    match trimmed.strip_prefix("/*+").and_then(|rest| rest.find("*/")) {
+        Some(end) => {
+            let (hint, rest) = trimmed.split_at(end + "/*+*/".len());
+            Cow::Owned(format!("{}{}", hint, strip_comments(rest)))
         }
-
-        false
+        None => strip_comments(sql),
     }
+}
 
-    /// Detect timing-based blind SQL injection attempts
-    fn detect_timing_attack(&self, sql: &str) -> bool {
-        let timing_functions = [
-            "SLEEP(",
-            "WAITFOR DELAY",
-            "BENCHMARK(",
-            "PG_SLEEP(",
-            "DBMS_LOCK.SLEEP",
-        ];
-
-        for func in timing_functions {
-            if sql.contains(func) {
-                return true;
+/// The non-empty `;`-separated statements of comment-free `sql`, ignoring
+/// semicolons inside quotes
+fn split_statements(sql: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in sql.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ';') => {
+                statements.push(&sql[start..i]);
+                start = i + 1;
             }
+            _ => {}
         }
-
-        false
     }
+    statements.push(&sql[start..]);
+    statements.retain(|s| !s.trim().is_empty());
+    statements
 }
 
 impl Default for SqlValidator {
@@ -383,5 +347,147 @@ mod tests {
         let long_query = "SELECT ".repeat(50000) + " * FROM users";
 
         assert!(validator.validate_query(&long_query).is_err());
+        assert!(validator.analyze(&long_query).is_err());
+    }
+
+    #[test]
+    fn test_analyze_reports_without_blocking() {
+        let validator = SqlValidator::new();
+
+        // String building is reported by the lint but isn't an injection
+        let sql = "SELECT * FROM users WHERE name = '\" + name + \"'";
+        assert!(validator.validate_query(sql).is_ok());
+        let report = validator.analyze(sql).unwrap();
+        assert_eq!(report.risks, vec![Risk::StringConcatenation]);
+        assert_eq!(report.tables, vec!["users"]);
+
+        let err = validator
+            .validate_query("SELECT * FROM users WHERE name = 'admin' OR '1'='1'")
+            .unwrap_err();
+        assert_eq!(err.to_string(), "SQL injection attempt detected: tautology");
+    }
+
+    fn app_policy() -> SqlValidator {
+        SqlValidator::with_config(ValidatorConfig {
+            denied_statements: StatementCategory::parse_list("ddl,grant").unwrap(),
+            allow_multiple_statements: false,
+            ..ValidatorConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_policy_rejects_denied_statements() {
+        let validator = app_policy();
+        assert!(validator
+            .validate_query("SELECT * FROM users WHERE id = $1")
+            .is_ok());
+        assert!(validator.validate_query("BEGIN").is_ok());
+
+        let err = validator.validate_query("DROP TABLE users").unwrap_err();
+        assert!(err.is::<PolicyViolation>());
+        assert_eq!(
+            err.to_string(),
+            "ddl statements are not allowed on this connection"
+        );
+        assert!(validator
+            .validate_query("GRANT SELECT ON users TO bob")
+            .unwrap_err()
+            .is::<PolicyViolation>());
+
+        let reads_only = SqlValidator::with_config(ValidatorConfig {
+            allowed_statements: StatementCategory::parse_list("read").unwrap(),
+            ..ValidatorConfig::default()
+        });
+        assert!(reads_only.validate_query("SELECT 1").is_ok());
+        assert!(reads_only
+            .validate_query("INSERT INTO users (id) VALUES (1)")
+            .is_err());
+    }
+
+    #[test]
+    fn test_policy_sees_writes_behind_read_prefixes() {
+        let validator = SqlValidator::with_config(ValidatorConfig {
+            denied_statements: StatementCategory::parse_list("write,ddl").unwrap(),
+            ..ValidatorConfig::default()
+        });
+        for sql in [
+            "EXPLAIN ANALYZE DELETE FROM t WHERE id = 1",
+            "EXPLAIN ANALYZE VERBOSE INSERT INTO t (id) VALUES (1)",
+            "EXPLAIN (ANALYZE, FORMAT JSON) UPDATE t SET v = 1",
+            "WITH moved AS (SELECT 1) INSERT INTO t (id) SELECT * FROM moved",
+        ] {
+            let err = validator.validate_query(sql).unwrap_err();
+            assert!(err.is::<PolicyViolation>(), "{}: {}", sql, err);
+        }
+
+        // Explaining without running stays a read
+        for sql in [
+            "EXPLAIN DELETE FROM t WHERE id = 1",
+            "EXPLAIN (FORMAT JSON) DELETE FROM t",
+            "WITH x AS (SELECT 1) SELECT * FROM x",
+        ] {
+            assert!(validator.validate_query(sql).is_ok(), "{}", sql);
+        }
+
+        // A parenthesised query is a read, not some other statement
+        let reads_only = SqlValidator::with_config(ValidatorConfig {
+            allowed_statements: StatementCategory::parse_list("read").unwrap(),
+            ..ValidatorConfig::default()
+        });
+        assert!(reads_only.validate_query("(SELECT 1)").is_ok());
+        assert!(reads_only
+            .validate_query("EXPLAIN ANALYZE DELETE FROM t")
+            .is_err());
+    }
+
+    #[test]
+    fn test_policy_multiple_statements() {
+        let validator = app_policy();
+        let err = validator.validate_query("SELECT 1; SELECT 2").unwrap_err();
+        assert!(err.is::<PolicyViolation>());
+
+        // Semicolons in strings and comments and a trailing one don't count
+        assert!(validator
+            .validate_query("SELECT * FROM notes WHERE body = 'a; b';")
+            .is_ok());
+        assert!(validator
+            .validate_query("SELECT 1 /* first; second */")
+            .is_ok());
+
+        assert!(SqlValidator::new()
+            .validate_query("SELECT 1; SELECT 2")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        assert_eq!(
+            strip_comments("SELECT 1 -- trailing\nFROM t /* inline */ WHERE a = '--x'"),
+            "SELECT 1  \nFROM t   WHERE a = '--x'"
+        );
+        assert!(matches!(strip_comments("SELECT 1"), Cow::Borrowed(_)));
+
+        let validator = SqlValidator::with_config(ValidatorConfig {
+            strip_comments: true,
+            ..ValidatorConfig::default()
+        });
+        assert_eq!(
+            validator
+                .prepare_query("SELECT * FROM users -- list users")
+                .unwrap(),
+            "SELECT * FROM users  "
+        );
+        assert_eq!(
+            validator
+                .prepare_query("/*+ SeqScan(users) */ SELECT * /* all */ FROM users")
+                .unwrap(),
+            "/*+ SeqScan(users) */ SELECT *   FROM users"
+        );
+
+        // Comments can't hide a second statement from the policy
+        let validator = app_policy();
+        assert!(validator
+            .validate_query("SELECT 1 --\n; DROP TABLE users")
+            .is_err());
     }
 }
@@ -0,0 +1,752 @@
+//! Statement-level audit log
+//!
+//! Records who ran which statements into the `__audit_log` DriftDB table.
+//! Being a regular table, the log can be time-travelled like any other,
+//! and every row carries a SHA-256 hash chained to the previous row so
+//! edits or gaps are detectable with [`StatementAuditor::verify`]. Which
+//! statement categories are recorded is configurable to keep volume down.
+
+#![allow(dead_code)]
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::ops::ControlFlow;
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use sqlparser::ast::{visit_relations, ObjectName, Query, SetExpr, Statement};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use tracing::{debug, error};
+
+use driftdb_core::explain;
+use driftdb_core::schema::ColumnDef;
+use driftdb_core::search_path;
+use driftdb_core::Engine;
+
+use super::object_privileges::sql_words;
+
+/// Table the audit trail is stored in
+pub const AUDIT_TABLE: &str = "__audit_log";
+
+/// Statement classes that can be audited independently
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatementCategory {
+    /// CREATE / ALTER / DROP / TRUNCATE
+    Ddl,
+    /// INSERT / UPDATE / DELETE
+    Write,
+    /// SELECT, SHOW, EXPLAIN
+    Read,
+    /// GRANT / REVOKE and user or role management
+    Grant,
+    /// BEGIN / COMMIT / ROLLBACK / SAVEPOINT
+    Transaction,
+    Other,
+}
+
+impl StatementCategory {
+    pub const ALL: [StatementCategory; 6] = [
+        StatementCategory::Ddl,
+        StatementCategory::Write,
+        StatementCategory::Read,
+        StatementCategory::Grant,
+        StatementCategory::Transaction,
+        StatementCategory::Other,
+    ];
+
+    /// Classify a statement by its leading keywords. `EXPLAIN ANALYZE` runs
+    /// its statement, so it takes that statement's category.
+    pub fn classify(sql: &str) -> Self {
+        let upper = sql.trim_start().trim_start_matches('(').to_uppercase();
+        let mut words = upper.split_whitespace();
+        let first = words.next().unwrap_or("");
+        let second = words.next().unwrap_or("");
+
+        match first {
+            "EXPLAIN" if second == "ANALYZE" => {
+                let rest: Vec<&str> = words.skip_while(|word| *word == "VERBOSE").collect();
+                Self::classify(&rest.join(" "))
+            }
+            "GRANT" | "REVOKE" => StatementCategory::Grant,
+            "CREATE" | "ALTER" | "DROP" if matches!(second, "USER" | "ROLE") => {
+                StatementCategory::Grant
+            }
+            "SET" if second == "ROLE" => StatementCategory::Grant,
+            "CREATE" | "ALTER" | "DROP" | "TRUNCATE" | "COMMENT" => StatementCategory::Ddl,
+            "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "COPY" => StatementCategory::Write,
+            "SELECT" | "WITH" | "SHOW" | "EXPLAIN" | "VALUES" | "TABLE" => StatementCategory::Read,
+            "BEGIN" | "START" | "COMMIT" | "END" | "ROLLBACK" | "SAVEPOINT" | "RELEASE" => {
+                StatementCategory::Transaction
+            }
+            _ => StatementCategory::Other,
+        }
+    }
+
+    /// The categories of every statement in `sql`, read from its parsed
+    /// form so a write can't pass for a read: `EXPLAIN ANALYZE` (or
+    /// `EXPLAIN (ANALYZE)`) runs the statement it explains, and a `WITH`
+    /// query can insert or update. SQL sqlparser can't parse is classified
+    /// by its leading keywords.
+    pub fn classify_all(sql: &str) -> Vec<Self> {
+        let (sql, analyze) = match explain::split_option_list(sql) {
+            Some(Ok((sql, options))) => (Cow::Owned(sql), options.analyze),
+            _ => (Cow::Borrowed(sql), false),
+        };
+        let Ok(statements) = Parser::parse_sql(&GenericDialect {}, &sql) else {
+            return vec![Self::classify(&sql)];
+        };
+        let mut categories = Vec::new();
+        for statement in &statements {
+            match statement {
+                Statement::Explain { statement, .. } if analyze => {
+                    statement_categories(statement, &mut categories)
+                }
+                other => statement_categories(other, &mut categories),
+            }
+        }
+        categories
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StatementCategory::Ddl => "ddl",
+            StatementCategory::Write => "write",
+            StatementCategory::Read => "read",
+            StatementCategory::Grant => "grant",
+            StatementCategory::Transaction => "transaction",
+            StatementCategory::Other => "other",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Parse a comma-separated category list such as `ddl,write,grant`.
+    /// `all` selects every category.
+    pub fn parse_list(spec: &str) -> Result<HashSet<Self>> {
+        let mut categories = HashSet::new();
+        for name in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            if name.eq_ignore_ascii_case("all") {
+                categories.extend(StatementCategory::ALL);
+            } else {
+                categories.insert(
+                    StatementCategory::from_name(name)
+                        .ok_or_else(|| anyhow!("Unknown statement category '{}'", name))?,
+                );
+            }
+        }
+        Ok(categories)
+    }
+}
+
+fn statement_categories(statement: &Statement, categories: &mut Vec<StatementCategory>) {
+    match statement {
+        Statement::Query(query) => query_categories(query, categories),
+        Statement::Explain {
+            analyze: true,
+            statement,
+            ..
+        } => statement_categories(statement, categories),
+        Statement::Explain { .. } => push_category(StatementCategory::Read, categories),
+        other => push_category(StatementCategory::classify(&other.to_string()), categories),
+    }
+}
+
+fn query_categories(query: &Query, categories: &mut Vec<StatementCategory>) {
+    if let Some(with) = &query.with {
+        for cte in &with.cte_tables {
+            query_categories(&cte.query, categories);
+        }
+    }
+    set_expr_categories(&query.body, categories);
+}
+
+fn set_expr_categories(body: &SetExpr, categories: &mut Vec<StatementCategory>) {
+    match body {
+        SetExpr::Insert(statement) | SetExpr::Update(statement) => {
+            statement_categories(statement, categories)
+        }
+        SetExpr::Query(query) => query_categories(query, categories),
+        SetExpr::SetOperation { left, right, .. } => {
+            set_expr_categories(left, categories);
+            set_expr_categories(right, categories);
+        }
+        SetExpr::Select(_) | SetExpr::Values(_) | SetExpr::Table(_) => {
+            push_category(StatementCategory::Read, categories)
+        }
+    }
+}
+
+fn push_category(category: StatementCategory, categories: &mut Vec<StatementCategory>) {
+    if !categories.contains(&category) {
+        categories.push(category);
+    }
+}
+
+/// Which statement categories are audited
+#[derive(Debug, Clone, Default)]
+pub struct StatementAuditConfig {
+    pub categories: HashSet<StatementCategory>,
+}
+
+impl StatementAuditConfig {
+    /// Parse a comma-separated category list such as `ddl,write,grant`.
+    /// `all` selects every category; an empty list disables auditing.
+    pub fn parse(spec: &str) -> Result<Self> {
+        Ok(Self {
+            categories: StatementCategory::parse_list(spec)?,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.categories.is_empty()
+    }
+}
+
+/// One audited statement
+#[derive(Debug, Clone)]
+pub struct StatementAuditEntry {
+    pub username: Option<String>,
+    pub client_addr: SocketAddr,
+    /// `application_name` of the session, if the client set one
+    pub application_name: Option<String>,
+    pub statement: String,
+    pub category: StatementCategory,
+    pub tables: Vec<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl StatementAuditEntry {
+    /// An entry for `sql` with its passwords redacted (see
+    /// [`redact_passwords`]) and the tables it names
+    pub fn new(
+        sql: &str,
+        category: StatementCategory,
+        username: Option<String>,
+        client_addr: SocketAddr,
+        application_name: Option<String>,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            username,
+            client_addr,
+            application_name,
+            statement: redact_passwords(sql),
+            category,
+            tables: statement_tables(sql),
+            success: error.is_none(),
+            error,
+        }
+    }
+}
+
+/// Sequence number and hash of the newest audit row
+struct ChainHead {
+    seq: u64,
+    hash: String,
+}
+
+pub struct StatementAuditor {
+    config: StatementAuditConfig,
+    /// Loaded from the table on first write
+    head: Mutex<Option<ChainHead>>,
+}
+
+impl StatementAuditor {
+    pub fn new(config: StatementAuditConfig) -> Self {
+        Self {
+            config,
+            head: Mutex::new(None),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(StatementAuditConfig::default())
+    }
+
+    pub fn should_audit(&self, category: StatementCategory) -> bool {
+        self.config.categories.contains(&category)
+    }
+
+    /// Append `entry` to the audit table, creating the table on first use.
+    /// A read-only engine (standby) can't take writes, so nothing is
+    /// recorded there.
+    pub fn record(&self, engine: &mut Engine, entry: StatementAuditEntry) -> Result<()> {
+        if !self.should_audit(entry.category) || engine.is_read_only() {
+            return Ok(());
+        }
+
+        let mut head = self.head.lock();
+        if head.is_none() {
+            ensure_audit_table(engine)?;
+            *head = Some(load_chain_head(engine)?);
+        }
+        let prev = head.as_ref().expect("chain head loaded above");
+
+        let seq = prev.seq + 1;
+        let mut row = json!({
+            "seq": seq,
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "username": entry.username,
+            "client_addr": entry.client_addr.to_string(),
+            "application_name": entry.application_name,
+            "category": entry.category.as_str(),
+            "statement": entry.statement,
+            "tables": entry.tables,
+            "success": entry.success,
+            "error": entry.error,
+            "prev_hash": prev.hash,
+        });
+        let hash = row_hash(&row);
+        row["hash"] = Value::String(hash.clone());
+
+        engine
+            .insert_record(AUDIT_TABLE, row)
+            .map_err(|e| anyhow!("Failed to write audit record: {}", e))?;
+        *head = Some(ChainHead { seq, hash });
+        Ok(())
+    }
+
+    /// Like [`record`](Self::record), but logs failures instead of
+    /// returning them so auditing never fails the audited statement.
+    pub fn record_or_log(&self, engine: &mut Engine, entry: StatementAuditEntry) {
+        if let Err(e) = self.record(engine, entry) {
+            error!("Statement audit: {}", e);
+        }
+    }
+
+    /// Walk the audit table in sequence order and check every row's hash
+    /// and link to its predecessor. Returns the first row that fails, or
+    /// `None` when the chain is intact.
+    pub fn verify(engine: &Engine) -> Result<Option<u64>> {
+        let mut rows = audit_rows(engine)?;
+        rows.sort_by_key(|row| row["seq"].as_u64().unwrap_or(0));
+
+        let mut prev_hash = String::new();
+        for (expected_seq, row) in (1u64..).zip(rows.iter()) {
+            let seq = row["seq"].as_u64().unwrap_or(0);
+            let mut body = row.clone();
+            let stored_hash = body
+                .as_object_mut()
+                .and_then(|o| o.remove("hash"))
+                .and_then(|h| h.as_str().map(str::to_string))
+                .unwrap_or_default();
+            if seq != expected_seq
+                || body["prev_hash"].as_str() != Some(prev_hash.as_str())
+                || row_hash(&body) != stored_hash
+            {
+                return Ok(Some(seq));
+            }
+            prev_hash = stored_hash;
+        }
+        Ok(None)
+    }
+}
+
+fn ensure_audit_table(engine: &mut Engine) -> Result<()> {
+    if engine.list_tables().iter().any(|t| t == AUDIT_TABLE) {
+        return Ok(());
+    }
+    let column = |name: &str, col_type: &str| ColumnDef {
+        name: name.to_string(),
+        col_type: col_type.to_string(),
+        index: false,
+    };
+    let columns = vec![
+        column("seq", "BIGINT"),
+        column("ts", "TIMESTAMP"),
+        ColumnDef {
+            index: true,
+            ..column("username", "VARCHAR")
+        },
+        column("client_addr", "VARCHAR"),
+        column("application_name", "VARCHAR"),
+        ColumnDef {
+            index: true,
+            ..column("category", "VARCHAR")
+        },
+        column("statement", "TEXT"),
+        column("tables", "JSON"),
+        column("success", "BOOLEAN"),
+        column("error", "TEXT"),
+        column("prev_hash", "VARCHAR"),
+        column("hash", "VARCHAR"),
+    ];
+    engine
+        .create_table_with_columns(AUDIT_TABLE, "seq", columns)
+        .map_err(|e| anyhow!("Failed to create audit table: {}", e))?;
+    debug!("Created statement audit table {}", AUDIT_TABLE);
+    Ok(())
+}
+
+fn audit_rows(engine: &Engine) -> Result<Vec<Value>> {
+    if !engine.list_tables().iter().any(|t| t == AUDIT_TABLE) {
+        return Ok(Vec::new());
+    }
+    Ok(engine.get_table_data_at(AUDIT_TABLE, u64::MAX)?)
+}
+
+fn load_chain_head(engine: &Engine) -> Result<ChainHead> {
+    let head = audit_rows(engine)?
+        .into_iter()
+        .max_by_key(|row| row["seq"].as_u64().unwrap_or(0))
+        .map(|row| ChainHead {
+            seq: row["seq"].as_u64().unwrap_or(0),
+            hash: row["hash"].as_str().unwrap_or_default().to_string(),
+        });
+    Ok(head.unwrap_or(ChainHead {
+        seq: 0,
+        hash: String::new(),
+    }))
+}
+
+/// SHA-256 over the row's canonical JSON (keys sorted), excluding `hash`
+fn row_hash(row: &Value) -> String {
+    use sha2::{Digest, Sha256};
+
+    let canonical: std::collections::BTreeMap<_, _> = row
+        .as_object()
+        .map(|o| o.iter().filter(|(k, _)| k.as_str() != "hash").collect())
+        .unwrap_or_default();
+    let data = serde_json::to_string(&canonical).unwrap_or_default();
+    format!("{:x}", Sha256::digest(data.as_bytes()))
+}
+
+/// `sql` with the value after each `PASSWORD` keyword replaced by `'***'`,
+/// so `CREATE USER ... WITH PASSWORD '...'` and `ALTER USER ... PASSWORD`
+/// don't put passwords in the audit log, where the hash chain would keep
+/// them for good. Only user and role management is touched, so a
+/// `password` column elsewhere is left alone, and quoted text is copied as
+/// is.
+pub fn redact_passwords(sql: &str) -> String {
+    if StatementCategory::classify(sql) != StatementCategory::Grant {
+        return sql.to_string();
+    }
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::with_capacity(sql.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\'' || c == '"' {
+            let end = quoted_end(&chars, i);
+            out.extend(&chars[i..end]);
+            i = end;
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            out.push_str(&word);
+            if !word.eq_ignore_ascii_case("PASSWORD") {
+                continue;
+            }
+            while i < chars.len() && chars[i].is_whitespace() {
+                out.push(chars[i]);
+                i += 1;
+            }
+            let value_start = i;
+            let value_end = match chars.get(i) {
+                Some('\'' | '"') => quoted_end(&chars, i),
+                _ => {
+                    let mut end = i;
+                    while end < chars.len() && !chars[end].is_whitespace() && chars[end] != ';' {
+                        end += 1;
+                    }
+                    end
+                }
+            };
+            let value: String = chars[value_start..value_end].iter().collect();
+            if value.is_empty() || value.eq_ignore_ascii_case("NULL") {
+                out.push_str(&value);
+            } else {
+                out.push_str("'***'");
+            }
+            i = value_end;
+        } else {
+            out.push(c);
+            i += 1;
+        }
+    }
+    out
+}
+
+/// End (exclusive) of the quoted text opening at `start`, where a doubled
+/// quote character stands for itself. Unterminated text runs to the end.
+fn quoted_end(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// Tables a statement reads or writes, by engine name. Every relation the
+/// statement names counts, including those in CTEs and subqueries (and the
+/// CTE names themselves). Statements sqlparser can't parse yield an empty
+/// list.
+pub fn statement_tables(sql: &str) -> Vec<String> {
+    parsed_statement_tables(sql).unwrap_or_default()
+}
+
+/// Whether `sql` reads or writes `table`. SQL that names the table but
+/// doesn't parse is an error, so callers can refuse what they can't check.
+pub fn references_table(sql: &str, table: &str) -> Result<bool> {
+    if !sql_words(sql).iter().any(|word| word == table) {
+        return Ok(false);
+    }
+    match parsed_statement_tables(sql) {
+        Some(tables) => Ok(tables.iter().any(|t| t == table)),
+        None => Err(anyhow!(
+            "cannot check access to {}: statement does not parse",
+            table
+        )),
+    }
+}
+
+fn parsed_statement_tables(sql: &str) -> Option<Vec<String>> {
+    let statements = Parser::parse_sql(&GenericDialect {}, sql).ok()?;
+    let mut tables = Vec::new();
+    for statement in &statements {
+        let _ = visit_relations(statement, |name| {
+            push_name(name, &mut tables);
+            ControlFlow::<()>::Continue(())
+        });
+        // The visitor doesn't treat DROP's names as relations
+        if let Statement::Drop { names, .. } = statement {
+            for name in names {
+                push_name(name, &mut tables);
+            }
+        }
+    }
+    Some(tables)
+}
+
+fn push_name(name: &ObjectName, tables: &mut Vec<String>) {
+    let name = table_name(name);
+    if !tables.contains(&name) {
+        tables.push(name);
+    }
+}
+
+/// The engine name a table reference resolves to on the default search
+/// path: quotes removed, unquoted identifiers folded to lowercase and the
+/// `public` schema dropped. Unqualified names are taken as written, which
+/// for the audit table (always in `public`) can only err toward matching.
+fn table_name(name: &ObjectName) -> String {
+    let parts: Vec<String> = name
+        .0
+        .iter()
+        .map(|ident| match ident.quote_style {
+            Some(_) => ident.value.clone(),
+            None => ident.value.to_lowercase(),
+        })
+        .collect();
+    match parts.as_slice() {
+        [schema, table] => search_path::storage_name(schema, table),
+        _ => parts.join("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(statement: &str) -> StatementAuditEntry {
+        StatementAuditEntry::new(
+            statement,
+            StatementCategory::classify(statement),
+            Some("alice".to_string()),
+            "127.0.0.1:5555".parse().unwrap(),
+            Some("psql".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn classifies_statements() {
+        assert_eq!(
+            StatementCategory::classify("create table t (id int)"),
+            StatementCategory::Ddl
+        );
+        assert_eq!(
+            StatementCategory::classify("CREATE USER bob"),
+            StatementCategory::Grant
+        );
+        assert_eq!(
+            StatementCategory::classify("DELETE FROM t"),
+            StatementCategory::Write
+        );
+        assert_eq!(
+            StatementCategory::classify("SELECT 1"),
+            StatementCategory::Read
+        );
+        assert_eq!(
+            StatementCategory::classify("EXPLAIN ANALYZE DELETE FROM t"),
+            StatementCategory::Write
+        );
+        assert_eq!(
+            StatementCategory::classify("(SELECT 1)"),
+            StatementCategory::Read
+        );
+        assert_eq!(
+            StatementCategory::classify_all("WITH x AS (SELECT 1) INSERT INTO t SELECT * FROM x"),
+            vec![StatementCategory::Read, StatementCategory::Write]
+        );
+    }
+
+    #[test]
+    fn extracts_target_tables() {
+        assert_eq!(
+            statement_tables("SELECT * FROM a JOIN b ON a.id = b.id"),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            statement_tables("INSERT INTO t (id) SELECT id FROM s"),
+            vec!["t", "s"]
+        );
+        assert_eq!(
+            statement_tables("DELETE FROM \"Mixed\" WHERE id IN (SELECT id FROM public.T)"),
+            vec!["Mixed", "t"]
+        );
+    }
+
+    #[test]
+    fn redacts_passwords() {
+        assert_eq!(
+            redact_passwords("CREATE USER 'bob' WITH PASSWORD 'it''s secret1' SUPERUSER;"),
+            "CREATE USER 'bob' WITH PASSWORD '***' SUPERUSER;"
+        );
+        assert_eq!(
+            redact_passwords("alter user bob password \"hunter22\""),
+            "alter user bob password '***'"
+        );
+        assert_eq!(
+            redact_passwords("ALTER USER bob WITH ENCRYPTED PASSWORD hunter22;"),
+            "ALTER USER bob WITH ENCRYPTED PASSWORD '***';"
+        );
+        assert_eq!(
+            redact_passwords("ALTER ROLE bob PASSWORD NULL"),
+            "ALTER ROLE bob PASSWORD NULL"
+        );
+        assert_eq!(
+            redact_passwords("CREATE ROLE 'password' PASSWORD 'abc12345'"),
+            "CREATE ROLE 'password' PASSWORD '***'"
+        );
+        // Not user management
+        for sql in [
+            "SELECT password FROM users",
+            "UPDATE users SET password = 'x'",
+        ] {
+            assert_eq!(redact_passwords(sql), sql);
+        }
+    }
+
+    #[test]
+    fn finds_the_audit_table_however_it_is_named() {
+        for sql in [
+            "DELETE FROM \"__audit_log\"",
+            "SELECT * FROM public.__audit_log",
+            "WITH x AS (SELECT * FROM __audit_log) SELECT * FROM x",
+            "SELECT id FROM t WHERE id IN (SELECT seq FROM __audit_log)",
+            "SELECT * FROM (t JOIN __AUDIT_LOG ON true)",
+        ] {
+            assert!(references_table(sql, AUDIT_TABLE).unwrap(), "{}", sql);
+        }
+        for sql in [
+            "SELECT * FROM t",
+            "SELECT '__audit_log' FROM t",
+            "SELECT * FROM app.__audit_log",
+        ] {
+            assert!(!references_table(sql, AUDIT_TABLE).unwrap(), "{}", sql);
+        }
+
+        // Unparseable SQL naming the table can't be checked
+        assert!(references_table("DELETE __audit_log WHERE (", AUDIT_TABLE).is_err());
+        assert!(!references_table("DELETE t WHERE (", AUDIT_TABLE).unwrap());
+    }
+
+    #[test]
+    fn records_only_configured_categories_and_chains_hashes() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = Engine::init(temp.path()).unwrap();
+        let auditor = StatementAuditor::new(StatementAuditConfig::parse("ddl,write").unwrap());
+
+        auditor
+            .record(&mut engine, entry("CREATE TABLE t (id INT)"))
+            .unwrap();
+        auditor
+            .record(&mut engine, entry("SELECT * FROM t"))
+            .unwrap();
+        auditor
+            .record(&mut engine, entry("INSERT INTO t (id) VALUES (1)"))
+            .unwrap();
+
+        let rows = audit_rows(&engine).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row["application_name"] == "psql"));
+        assert_eq!(StatementAuditor::verify(&engine).unwrap(), None);
+
+        // A fresh auditor continues the existing chain.
+        let auditor = StatementAuditor::new(StatementAuditConfig::parse("all").unwrap());
+        auditor
+            .record(&mut engine, entry("SELECT * FROM t"))
+            .unwrap();
+        assert_eq!(audit_rows(&engine).unwrap().len(), 3);
+        assert_eq!(StatementAuditor::verify(&engine).unwrap(), None);
+    }
+
+    #[test]
+    fn stores_user_management_without_passwords() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = Engine::init(temp.path()).unwrap();
+        let auditor = StatementAuditor::new(StatementAuditConfig::parse("grant").unwrap());
+        auditor
+            .record(
+                &mut engine,
+                entry("CREATE USER 'bob' WITH PASSWORD 'secret123' SUPERUSER"),
+            )
+            .unwrap();
+        auditor
+            .record(&mut engine, entry("ALTER USER bob PASSWORD 'secret456'"))
+            .unwrap();
+
+        let rows = audit_rows(&engine).unwrap();
+        assert_eq!(rows.len(), 2);
+        for row in &rows {
+            let statement = row["statement"].as_str().unwrap();
+            assert!(!statement.contains("secret"), "{}", statement);
+            assert!(statement.contains("PASSWORD '***'"), "{}", statement);
+        }
+    }
+
+    #[test]
+    fn verify_detects_edited_rows() {
+        let temp = TempDir::new().unwrap();
+        let mut engine = Engine::init(temp.path()).unwrap();
+        let auditor = StatementAuditor::new(StatementAuditConfig::parse("all").unwrap());
+        auditor.record(&mut engine, entry("DROP TABLE x")).unwrap();
+        auditor.record(&mut engine, entry("DROP TABLE y")).unwrap();
+
+        engine
+            .update_record(AUDIT_TABLE, json!(1), json!({"statement": "SELECT 1"}))
+            .unwrap();
+        assert_eq!(StatementAuditor::verify(&engine).unwrap(), Some(1));
+    }
+}
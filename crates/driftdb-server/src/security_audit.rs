@@ -680,7 +680,11 @@ mod tests {
 
     #[test]
     fn test_audit_statistics() {
-        let config = AuditConfig::default();
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = AuditConfig {
+            log_file_path: dir.path().join("security_audit.log").display().to_string(),
+            ..AuditConfig::default()
+        };
         let logger = SecurityAuditLogger::new(config);
 
         // Log various events
@@ -706,7 +710,11 @@ mod tests {
 
     #[test]
     fn test_integrity_verification() {
-        let config = AuditConfig::default();
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = AuditConfig {
+            log_file_path: dir.path().join("security_audit.log").display().to_string(),
+            ..AuditConfig::default()
+        };
         let logger = SecurityAuditLogger::new(config);
 
         logger.log_login_success("user1".to_string(), test_addr(), "session1".to_string());
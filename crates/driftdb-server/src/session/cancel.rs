@@ -0,0 +1,129 @@
+//! Query cancellation: routing a CancelRequest, which arrives on its own
+//! connection, to the session running the statement
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// Protocol code a CancelRequest carries in place of a protocol version
+pub const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// Length of a CancelRequest packet: length, code, process id, secret key
+pub const CANCEL_REQUEST_LEN: usize = 16;
+
+/// Live sessions by the process id and secret key sent in BackendKeyData
+#[derive(Default)]
+pub struct CancelRegistry {
+    sessions: Mutex<HashMap<i32, (i32, Arc<Notify>)>>,
+}
+
+impl CancelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a session; its statements wait on the returned signal
+    pub fn register(&self, process_id: i32, secret_key: i32) -> Arc<Notify> {
+        let signal = Arc::new(Notify::new());
+        self.sessions
+            .lock()
+            .insert(process_id, (secret_key, signal.clone()));
+        signal
+    }
+
+    pub fn unregister(&self, process_id: i32) {
+        self.sessions.lock().remove(&process_id);
+    }
+
+    /// Cancel the statement `process_id` is running, if the key matches.
+    /// Like PostgreSQL, a cancel that arrives between statements does
+    /// nothing; it is never held over for the next one.
+    pub fn cancel(&self, process_id: i32, secret_key: i32) -> bool {
+        match self.sessions.lock().get(&process_id) {
+            Some((key, signal)) if *key == secret_key => {
+                signal.notify_waiters();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The process id and secret key of a complete CancelRequest packet
+pub fn parse_cancel_request(packet: &[u8]) -> Option<(i32, i32)> {
+    if packet.len() < CANCEL_REQUEST_LEN {
+        return None;
+    }
+    let word =
+        |i: usize| i32::from_be_bytes([packet[i], packet[i + 1], packet[i + 2], packet[i + 3]]);
+    if word(0) as usize != CANCEL_REQUEST_LEN || word(4) != CANCEL_REQUEST_CODE {
+        return None;
+    }
+    Some((word(8), word(12)))
+}
+
+/// Whether the packet starting in `buf` is a CancelRequest; needs 8 bytes
+pub fn is_cancel_request(buf: &[u8]) -> bool {
+    buf.len() >= 8 && i32::from_be_bytes([buf[4], buf[5], buf[6], buf[7]]) == CANCEL_REQUEST_CODE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn packet(process_id: i32, secret_key: i32) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(&(CANCEL_REQUEST_LEN as i32).to_be_bytes());
+        buf.extend_from_slice(&CANCEL_REQUEST_CODE.to_be_bytes());
+        buf.extend_from_slice(&process_id.to_be_bytes());
+        buf.extend_from_slice(&secret_key.to_be_bytes());
+        buf
+    }
+
+    #[test]
+    fn test_parse_cancel_request() {
+        let buf = packet(1001, -42);
+        assert!(is_cancel_request(&buf));
+        assert_eq!(parse_cancel_request(&buf), Some((1001, -42)));
+
+        // A startup message carries the protocol version instead
+        let mut startup = packet(0, 0);
+        startup[4..8].copy_from_slice(&196608i32.to_be_bytes());
+        assert!(!is_cancel_request(&startup));
+        assert_eq!(parse_cancel_request(&startup), None);
+        assert_eq!(parse_cancel_request(&buf[..12]), None);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_wakes_running_statement() {
+        let registry = CancelRegistry::new();
+        let signal = registry.register(1001, 7);
+
+        let running = signal.notified();
+        tokio::pin!(running);
+        running.as_mut().enable();
+        assert!(!registry.cancel(1001, 8), "wrong key must not cancel");
+        assert!(!registry.cancel(1002, 7));
+        assert!(registry.cancel(1001, 7));
+        tokio::time::timeout(Duration::from_secs(1), running)
+            .await
+            .expect("statement should be cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_between_statements_is_dropped() {
+        let registry = CancelRegistry::new();
+        let signal = registry.register(1001, 7);
+        assert!(registry.cancel(1001, 7));
+
+        // The next statement must not see the earlier cancel
+        let next = tokio::time::timeout(Duration::from_millis(50), signal.notified()).await;
+        assert!(next.is_err());
+
+        registry.unregister(1001);
+        assert!(!registry.cancel(1001, 7));
+    }
+}
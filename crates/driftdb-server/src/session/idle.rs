@@ -0,0 +1,105 @@
+//! Closing sessions that sit idle between queries, so connections leaked
+//! by crashed clients don't hold a slot forever
+
+use std::time::Duration;
+
+use crate::protocol::error_codes;
+
+/// How long a session may wait for its client's next message before it
+/// is closed; `None` waits forever
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdleTimeouts {
+    /// Outside a transaction
+    pub session: Option<Duration>,
+    /// With a transaction open, which closing the session rolls back
+    pub in_transaction: Option<Duration>,
+}
+
+/// The FATAL ErrorResponse an idle session is closed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleExpiry {
+    pub code: &'static str,
+    pub message: &'static str,
+}
+
+impl IdleTimeouts {
+    /// Both limits in seconds, 0 for none
+    pub fn from_secs(session: u64, in_transaction: u64) -> Self {
+        let limit = |secs| (secs > 0).then(|| Duration::from_secs(secs));
+        Self {
+            session: limit(session),
+            in_transaction: limit(in_transaction),
+        }
+    }
+
+    /// The limit for a session waiting in or out of a transaction
+    pub fn limit(&self, in_transaction: bool) -> Option<Duration> {
+        if in_transaction {
+            self.in_transaction
+        } else {
+            self.session
+        }
+    }
+
+    /// Resolve once a session waiting in or out of a transaction has been
+    /// idle longer than its limit; never without one
+    pub async fn expired(self, in_transaction: bool) -> IdleExpiry {
+        match self.limit(in_transaction) {
+            Some(limit) => tokio::time::sleep(limit).await,
+            None => std::future::pending().await,
+        }
+        if in_transaction {
+            IdleExpiry {
+                code: error_codes::IDLE_IN_TRANSACTION_SESSION_TIMEOUT,
+                message: "terminating connection due to idle-in-transaction timeout",
+            }
+        } else {
+            IdleExpiry {
+                code: error_codes::IDLE_SESSION_TIMEOUT,
+                message: "terminating connection due to idle-session timeout",
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_disables_a_limit() {
+        let timeouts = IdleTimeouts::from_secs(300, 0);
+        assert_eq!(timeouts.limit(false), Some(Duration::from_secs(300)));
+        assert_eq!(timeouts.limit(true), None);
+        assert_eq!(IdleTimeouts::from_secs(0, 0), IdleTimeouts::default());
+    }
+
+    #[tokio::test]
+    async fn test_open_transaction_uses_its_own_limit() {
+        let timeouts = IdleTimeouts {
+            session: Some(Duration::from_millis(10)),
+            in_transaction: None,
+        };
+        let expiry = tokio::time::timeout(Duration::from_secs(1), timeouts.expired(false))
+            .await
+            .expect("idle session should time out");
+        assert_eq!(expiry.code, error_codes::IDLE_SESSION_TIMEOUT);
+
+        // A session inside a transaction isn't held to the plain limit
+        let waiting = tokio::time::timeout(Duration::from_millis(50), timeouts.expired(true)).await;
+        assert!(waiting.is_err());
+
+        let timeouts = IdleTimeouts {
+            in_transaction: Some(Duration::from_millis(10)),
+            ..timeouts
+        };
+        let expiry = tokio::time::timeout(Duration::from_secs(1), timeouts.expired(true))
+            .await
+            .expect("idle transaction should time out");
+        assert_eq!(
+            expiry.code,
+            error_codes::IDLE_IN_TRANSACTION_SESSION_TIMEOUT
+        );
+        assert!(expiry.message.contains("idle-in-transaction"));
+    }
+}
@@ -2,8 +2,13 @@
 
 #![allow(dead_code)]
 
+mod cancel;
+mod idle;
+mod pooling;
 mod prepared;
+mod registry;
 
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
@@ -15,18 +20,38 @@ use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 use tracing::{debug, error, info, warn};
 
+pub use self::cancel::CancelRegistry;
+use self::idle::IdleExpiry;
+pub use self::idle::IdleTimeouts;
+pub use self::pooling::{PoolMode, StatementQueue, StatementQueueConfig};
 use self::prepared::PreparedStatementManager;
+pub use self::registry::{SessionInfo, SessionRegistry};
+use crate::drain::{close_requested, ConnectionDrain, DrainPhase};
 use crate::executor::QueryExecutor;
 use crate::protocol::{self, Message, TransactionStatus};
+use crate::result_cache::ResultCache;
+use crate::security::object_privileges::{check_statement_privileges, parse_grant_statement};
+use crate::security::rbac::Principal;
+use crate::security::rbac_enforcement::{
+    check_query_permission, check_view_audit_log_permission, parse_set_role, statement_query_types,
+};
+use crate::security::statement_audit::{
+    references_table, StatementAuditEntry, StatementAuditor, StatementCategory, AUDIT_TABLE,
+};
+use crate::security::sql_validator::{PolicyViolation, ValidatorConfig};
 use crate::security::{RbacManager, SqlValidator};
 use crate::security_audit::SecurityAuditLogger;
 use crate::slow_query_log::SlowQueryLogger;
+use crate::statement_cache::StatementCache;
 use crate::tls::SecureStream;
 // `crate::transaction` was retired with the DML migration; transaction
-// state now lives in `sql_bridge::SessionContext`, held inside the
-// QueryExecutor.
-use driftdb_core::row_level_security::{PolicyAction, PolicyResult, RlsManager, SecurityContext};
-use driftdb_core::{EngineGuard, EnginePool, RateLimitManager};
+// state now lives in `sql_bridge::SessionContext`, held by the session and
+// handed to each QueryExecutor.
+use driftdb_core::row_level_security::{
+    PolicyAction, PolicyResult, RlsManager, RowSecurity, SecurityContext,
+};
+use driftdb_core::sql_bridge::SessionContext;
+use driftdb_core::{CompactionTracker, EngineGuard, EnginePool, RateLimitManager};
 
 pub struct SessionManager {
     engine_pool: EnginePool,
@@ -37,6 +62,16 @@ pub struct SessionManager {
     audit_logger: Arc<SecurityAuditLogger>,
     rbac_manager: Arc<RbacManager>,
     rls_manager: Arc<RlsManager>,
+    drain: Arc<ConnectionDrain>,
+    statement_auditor: Arc<StatementAuditor>,
+    statement_cache: Arc<StatementCache>,
+    result_cache: Arc<ResultCache>,
+    compaction_tracker: Option<Arc<CompactionTracker>>,
+    pool_mode: PoolMode,
+    statement_queue: Arc<StatementQueue>,
+    idle_timeouts: IdleTimeouts,
+    cancel_registry: Arc<CancelRegistry>,
+    sessions: Arc<SessionRegistry>,
 }
 
 impl SessionManager {
@@ -57,9 +92,62 @@ impl SessionManager {
             audit_logger,
             rbac_manager,
             rls_manager: Arc::new(RlsManager::new()),
+            drain: Arc::new(ConnectionDrain::new()),
+            statement_auditor: Arc::new(StatementAuditor::disabled()),
+            statement_cache: Arc::new(StatementCache::default()),
+            result_cache: Arc::new(ResultCache::disabled()),
+            compaction_tracker: None,
+            pool_mode: PoolMode::Session,
+            statement_queue: Arc::new(StatementQueue::new(StatementQueueConfig::default())),
+            idle_timeouts: IdleTimeouts::default(),
+            cancel_registry: Arc::new(CancelRegistry::new()),
+            sessions: Arc::new(SessionRegistry::new()),
         }
     }
 
+    /// Record statements in the audit table according to `auditor`'s
+    /// configuration.
+    pub fn with_statement_auditor(mut self, auditor: Arc<StatementAuditor>) -> Self {
+        self.statement_auditor = auditor;
+        self
+    }
+
+    /// Share `cache` of parsed statements between this manager's sessions
+    pub fn with_statement_cache(mut self, cache: Arc<StatementCache>) -> Self {
+        self.statement_cache = cache;
+        self
+    }
+
+    /// Share `cache` of query results between this manager's sessions
+    pub fn with_result_cache(mut self, cache: Arc<ResultCache>) -> Self {
+        self.result_cache = cache;
+        self
+    }
+
+    /// Report compaction progress from `tracker` to this manager's sessions
+    pub fn with_compaction_tracker(mut self, tracker: Arc<CompactionTracker>) -> Self {
+        self.compaction_tracker = Some(tracker);
+        self
+    }
+
+    /// Hold pooled backends per `mode`; in transaction mode statements
+    /// wait for a backend within `queue`'s limits.
+    pub fn with_pool_mode(mut self, mode: PoolMode, queue: StatementQueueConfig) -> Self {
+        self.pool_mode = mode;
+        self.statement_queue = Arc::new(StatementQueue::new(queue));
+        self
+    }
+
+    /// Close sessions left idle longer than `timeouts` allow
+    pub fn with_idle_timeouts(mut self, timeouts: IdleTimeouts) -> Self {
+        self.idle_timeouts = timeouts;
+        self
+    }
+
+    pub fn drain(&self) -> &Arc<ConnectionDrain> {
+        &self.drain
+    }
+
     pub fn rbac_manager(&self) -> &Arc<RbacManager> {
         &self.rbac_manager
     }
@@ -73,10 +161,18 @@ impl SessionManager {
         &self.rate_limit_manager
     }
 
+    /// Connected sessions, for the admin sessions endpoint
+    pub fn sessions(&self) -> &Arc<SessionRegistry> {
+        &self.sessions
+    }
+
+    /// Serve one client, validating its statements against
+    /// `validator_config`, the policy of the listener it connected to
     pub async fn handle_secure_connection(
         self: Arc<Self>,
         stream: SecureStream,
         addr: SocketAddr,
+        validator_config: Arc<ValidatorConfig>,
     ) -> Result<()> {
         // Get peer address
         let peer_addr = stream.peer_addr().unwrap_or(addr);
@@ -130,7 +226,7 @@ impl SessionManager {
         }
 
         // Handle the same way as regular connections but with SecureStream
-        self.handle_connection_internal(stream, peer_addr, is_encrypted)
+        self.handle_connection_internal(stream, peer_addr, is_encrypted, validator_config)
             .await
     }
 
@@ -138,10 +234,11 @@ impl SessionManager {
         self: Arc<Self>,
         stream: TcpStream,
         addr: SocketAddr,
+        validator_config: Arc<ValidatorConfig>,
     ) -> Result<()> {
         // Wrap TcpStream in SecureStream::Plain for unified handling
         let secure_stream = SecureStream::Plain(stream);
-        self.handle_connection_internal(secure_stream, addr, false)
+        self.handle_connection_internal(secure_stream, addr, false, validator_config)
             .await
     }
 
@@ -150,6 +247,7 @@ impl SessionManager {
         mut stream: SecureStream,
         addr: SocketAddr,
         is_encrypted: bool,
+        validator_config: Arc<ValidatorConfig>,
     ) -> Result<()> {
         // Check rate limiting first
         if !self.rate_limit_manager.allow_connection(addr) {
@@ -161,25 +259,57 @@ impl SessionManager {
             return Ok(());
         }
 
-        // Try to acquire a connection from the pool
-        let engine_guard = match self.engine_pool.acquire(addr).await {
-            Ok(guard) => guard,
-            Err(e) => {
-                warn!(
-                    "Connection limit reached or pool error, rejecting {}: {}",
-                    addr, e
-                );
-                // Release the rate limit connection since we're not using it
-                self.rate_limit_manager.release_connection(addr);
-                // Send an error response to the client before closing
-                let _ = stream.write_all(b"N").await; // SSL not supported response
-                return Ok(());
-            }
+        // A connection accepted just before the listener closed
+        if self.drain.is_draining() {
+            self.rate_limit_manager.release_connection(addr);
+            let error = Message::fatal(
+                protocol::error_codes::CANNOT_CONNECT_NOW,
+                "the database system is shutting down",
+            );
+            let _ = stream
+                .write_all(&protocol::codec::encode_message(&error))
+                .await;
+            return Ok(());
+        }
+        // A CancelRequest comes on a connection of its own, which must not
+        // wait for a backend: the pool may be full of the statements it's
+        // trying to cancel.
+        let mut buffer = BytesMut::with_capacity(8192);
+        if self.read_cancel_request(&mut stream, &mut buffer).await? {
+            self.rate_limit_manager.release_connection(addr);
+            return Ok(());
+        }
+
+        let _open_session = self.drain.session_opened();
+
+        // In session mode the connection holds a backend for its lifetime;
+        // in transaction mode it checks one out per statement.
+        let engine_guard = match self.pool_mode {
+            PoolMode::Transaction => None,
+            PoolMode::Session => match self.engine_pool.acquire(addr).await {
+                Ok(guard) => Some(guard),
+                Err(e) => {
+                    warn!(
+                        "Connection limit reached or pool error, rejecting {}: {}",
+                        addr, e
+                    );
+                    // Release the rate limit connection since we're not using it
+                    self.rate_limit_manager.release_connection(addr);
+                    // Send an error response to the client before closing
+                    let _ = stream.write_all(b"N").await; // SSL not supported response
+                    return Ok(());
+                }
+            },
         };
 
         // Create session
         let process_id = self.next_process_id.fetch_add(1, Ordering::SeqCst) as i32;
         let secret_key = rand::random::<i32>();
+        let cancel_signal = self.cancel_registry.register(process_id, secret_key);
+        let sql_session = SessionContext::new();
+        let info = self
+            .sessions
+            .register(process_id, addr, sql_session.memory.clone());
 
         let session = Session {
             process_id,
@@ -189,23 +319,40 @@ impl SessionManager {
             database: "driftdb".to_string(),
             transaction_status: TransactionStatus::Idle,
             engine_guard,
+            engine_pool: self.engine_pool.clone(),
+            pool_mode: self.pool_mode,
+            statement_queue: self.statement_queue.clone(),
+            idle_timeouts: self.idle_timeouts,
             auth_db: self.auth_db.clone(),
             rate_limit_manager: self.rate_limit_manager.clone(),
             authenticated: false,
             auth_challenge: None,
             prepared_statements: PreparedStatementManager::new(),
-            sql_validator: SqlValidator::new(),
+            sql_validator: SqlValidator::with_config((*validator_config).clone()),
             slow_query_logger: self.slow_query_logger.clone(),
             audit_logger: self.audit_logger.clone(),
             is_encrypted,
             rls_manager: self.rls_manager.clone(),
+            drain_phase: self.drain.subscribe(),
+            rbac_manager: self.rbac_manager.clone(),
+            statement_auditor: self.statement_auditor.clone(),
+            statement_cache: self.statement_cache.clone(),
+            result_cache: self.result_cache.clone(),
+            compaction_tracker: self.compaction_tracker.clone(),
+            current_role: None,
+            sql_session: Arc::new(parking_lot::Mutex::new(sql_session)),
+            statement_error: parking_lot::Mutex::new(None),
+            cancel_signal,
+            info,
         };
 
         // Handle session
-        let result = session.run(&mut stream).await;
+        let result = session.run(&mut stream, buffer).await;
 
         // Clean up rate limiting state
         self.rate_limit_manager.release_connection(addr);
+        self.cancel_registry.unregister(process_id);
+        self.sessions.unregister(process_id);
 
         if let Err(e) = result {
             error!("Session error from {}: {}", addr, e);
@@ -215,6 +362,42 @@ impl SessionManager {
 
         Ok(())
     }
+
+    /// Read enough of a new connection to tell whether it is a
+    /// CancelRequest, and if so act on it. Bytes read from any other
+    /// connection stay in `buffer` for the session.
+    async fn read_cancel_request(
+        &self,
+        stream: &mut SecureStream,
+        buffer: &mut BytesMut,
+    ) -> Result<bool> {
+        while buffer.len() < 8 {
+            if stream.read_buf(buffer).await? == 0 {
+                return Ok(false);
+            }
+        }
+        if !cancel::is_cancel_request(buffer) {
+            return Ok(false);
+        }
+        while buffer.len() < cancel::CANCEL_REQUEST_LEN {
+            if stream.read_buf(buffer).await? == 0 {
+                return Ok(true);
+            }
+        }
+        // The server answers a CancelRequest by closing the connection,
+        // whether or not it matched a session
+        match cancel::parse_cancel_request(buffer) {
+            Some((process_id, secret_key)) => {
+                if self.cancel_registry.cancel(process_id, secret_key) {
+                    info!("Cancel request for session {}", process_id);
+                } else {
+                    debug!("Ignoring cancel request for unknown session {}", process_id);
+                }
+            }
+            None => debug!("Ignoring malformed cancel request"),
+        }
+        Ok(true)
+    }
 }
 
 struct Session {
@@ -224,7 +407,13 @@ struct Session {
     username: Option<String>,
     database: String,
     transaction_status: TransactionStatus,
-    engine_guard: EngineGuard,
+    /// Backend from the engine pool; always held in session mode, only
+    /// while a statement or transaction runs in transaction mode
+    engine_guard: Option<EngineGuard>,
+    engine_pool: EnginePool,
+    pool_mode: PoolMode,
+    statement_queue: Arc<StatementQueue>,
+    idle_timeouts: IdleTimeouts,
     auth_db: Arc<protocol::auth::UserDb>,
     rate_limit_manager: Arc<RateLimitManager>,
     authenticated: bool,
@@ -235,25 +424,61 @@ struct Session {
     audit_logger: Arc<SecurityAuditLogger>,
     is_encrypted: bool,
     rls_manager: Arc<RlsManager>,
+    drain_phase: tokio::sync::watch::Receiver<DrainPhase>,
+    rbac_manager: Arc<RbacManager>,
+    statement_auditor: Arc<StatementAuditor>,
+    statement_cache: Arc<StatementCache>,
+    result_cache: Arc<ResultCache>,
+    compaction_tracker: Option<Arc<CompactionTracker>>,
+    /// Role chosen with `SET ROLE`; while set, statements run with that
+    /// role's permissions instead of the user's own roles
+    current_role: Option<String>,
+    /// Open transaction, search path and runtime parameters, carried from
+    /// each statement's executor to the next
+    sql_session: Arc<parking_lot::Mutex<SessionContext>>,
+    /// Message of the last ErrorResponse sent, so the statement audit can
+    /// record failures whichever path reported them.
+    statement_error: parking_lot::Mutex<Option<String>>,
+    /// Fired by a CancelRequest carrying this session's key
+    cancel_signal: Arc<tokio::sync::Notify>,
+    /// What the admin sessions endpoint reports about this session
+    info: Arc<SessionInfo>,
 }
 
 impl Session {
-    async fn run(mut self, stream: &mut SecureStream) -> Result<()> {
-        let mut buffer = BytesMut::with_capacity(8192);
+    /// Serve the connection; `buffer` holds any bytes already read from it
+    async fn run(mut self, stream: &mut SecureStream, mut buffer: BytesMut) -> Result<()> {
         let mut startup_done = false;
+        // Decode what was read before the session started before reading more
+        let mut pending = !buffer.is_empty();
 
         loop {
-            // Read from stream
-            info!(
-                "Waiting for data from {}, startup_done={}",
-                self.addr, startup_done
-            );
-            let n = stream.read_buf(&mut buffer).await?;
-            if n == 0 {
-                debug!("Connection closed by client {}", self.addr);
-                break;
+            if !std::mem::take(&mut pending) {
+                // Read from stream
+                info!(
+                    "Waiting for data from {}, startup_done={}",
+                    self.addr, startup_done
+                );
+                let in_transaction = self.transaction_status != TransactionStatus::Idle;
+                let mut drain_phase = self.drain_phase.clone();
+                let idle_timeouts = self.idle_timeouts;
+                let n = tokio::select! {
+                    n = stream.read_buf(&mut buffer) => n?,
+                    phase = close_requested(&mut drain_phase, in_transaction) => {
+                        self.close_for_shutdown(stream, phase).await?;
+                        break;
+                    }
+                    expiry = idle_timeouts.expired(in_transaction) => {
+                        self.close_for_idle_timeout(stream, expiry).await?;
+                        break;
+                    }
+                };
+                if n == 0 {
+                    debug!("Connection closed by client {}", self.addr);
+                    break;
+                }
+                info!("Read {} bytes from {}", n, self.addr);
             }
-            info!("Read {} bytes from {}", n, self.addr);
 
             // Decode messages
             while let Some(msg) = protocol::codec::decode_message(&mut buffer, startup_done)? {
@@ -289,8 +514,14 @@ impl Session {
                                 "Authentication required",
                             );
                             self.send_message(stream, &error).await?;
+                        } else if let Err(e) = self.checkout_backend().await {
+                            self.send_backend_unavailable(stream, &e).await?;
+                            self.send_ready_for_query(stream).await?;
                         } else {
+                            self.statement_error.lock().take();
                             self.handle_query(stream, &sql).await?;
+                            self.audit_statement(&sql);
+                            self.release_backend();
                             self.send_ready_for_query(stream).await?;
                         }
                     }
@@ -348,6 +579,8 @@ impl Session {
                                 "Authentication required",
                             );
                             self.send_message(stream, &error).await?;
+                        } else if let Err(e) = self.checkout_backend().await {
+                            self.send_backend_unavailable(stream, &e).await?;
                         } else {
                             self.handle_execute(stream, portal_name, max_rows).await?;
                         }
@@ -379,6 +612,7 @@ impl Session {
 
                     Message::Sync => {
                         // Sync message completes the extended query protocol sequence
+                        self.release_backend();
                         self.send_ready_for_query(stream).await?;
                     }
 
@@ -402,6 +636,102 @@ impl Session {
         Ok(())
     }
 
+    /// The pooled backend a session's statements run on. Takes the field
+    /// rather than `&self` so callers can still update other fields while
+    /// an executor borrows it.
+    fn backend(engine_guard: &Option<EngineGuard>, addr: SocketAddr) -> Result<&EngineGuard> {
+        engine_guard
+            .as_ref()
+            .ok_or_else(|| anyhow!("no backend checked out for {}", addr))
+    }
+
+    /// Make sure a backend is held before running a statement, waiting in
+    /// the statement queue in transaction mode.
+    async fn checkout_backend(&mut self) -> Result<()> {
+        if self.engine_guard.is_none() {
+            let guard = self
+                .statement_queue
+                .acquire(&self.engine_pool, self.addr)
+                .await?;
+            self.engine_guard = Some(guard);
+        }
+        Ok(())
+    }
+
+    /// In transaction mode, hand the backend back once no transaction is
+    /// open.
+    fn release_backend(&mut self) {
+        if self.pool_mode == PoolMode::Transaction
+            && self.transaction_status == TransactionStatus::Idle
+        {
+            self.engine_guard = None;
+        }
+    }
+
+    async fn send_backend_unavailable(
+        &self,
+        stream: &mut SecureStream,
+        e: &anyhow::Error,
+    ) -> Result<()> {
+        warn!("No backend for statement from {}: {}", self.addr, e);
+        let error = Message::error(protocol::error_codes::TOO_MANY_CONNECTIONS, &e.to_string());
+        self.send_message(stream, &error).await
+    }
+
+    /// End the session because the server is draining. A transaction still
+    /// open at `Closing` is rolled back first.
+    async fn close_for_shutdown(
+        &mut self,
+        stream: &mut SecureStream,
+        phase: DrainPhase,
+    ) -> Result<()> {
+        if phase == DrainPhase::Closing {
+            self.rollback_open_transaction("at shutdown").await?;
+        }
+
+        let error = Message::fatal(
+            protocol::error_codes::ADMIN_SHUTDOWN,
+            "terminating connection due to administrator command",
+        );
+        // The client may already be gone.
+        let _ = self.send_message(stream, &error).await;
+        Ok(())
+    }
+
+    /// End the session because its client sent nothing for longer than
+    /// the idle timeouts allow, rolling back a transaction it left open.
+    async fn close_for_idle_timeout(
+        &mut self,
+        stream: &mut SecureStream,
+        expiry: IdleExpiry,
+    ) -> Result<()> {
+        warn!("Closing idle session for {}: {}", self.addr, expiry.message);
+        self.rollback_open_transaction("after idle timeout").await?;
+        let error = Message::fatal(expiry.code, expiry.message);
+        let _ = self.send_message(stream, &error).await;
+        Ok(())
+    }
+
+    /// Roll back the session's open transaction, if any, before it closes
+    async fn rollback_open_transaction(&mut self, when: &str) -> Result<()> {
+        if self.transaction_status != TransactionStatus::Idle {
+            info!("Rolling back open transaction for {} {}", self.addr, when);
+            let session_id = format!("session_{}", self.process_id);
+            let executor = QueryExecutor::new_with_guard_and_session_id(
+                Self::backend(&self.engine_guard, self.addr)?,
+                session_id,
+            )
+            .with_statement_cache(self.statement_cache.clone())
+            .with_session(self.sql_session.clone());
+            if let Err(e) = executor.execute("ROLLBACK").await {
+                warn!("Rollback {} failed for {}: {}", when, self.addr, e);
+            }
+            self.transaction_status = TransactionStatus::Idle;
+            self.release_backend();
+        }
+        Ok(())
+    }
+
     async fn handle_startup(
         &mut self,
         stream: &mut SecureStream,
@@ -409,9 +739,13 @@ impl Session {
     ) -> Result<()> {
         // Extract connection parameters
         self.username = parameters.get("user").cloned();
+        self.info.set_user(self.username.clone());
         if let Some(db) = parameters.get("database") {
             self.database = db.clone();
         }
+        if let Some(name) = parameters.get("application_name") {
+            self.set_application_name(name);
+        }
 
         let username = self.username.as_deref().unwrap_or("anonymous");
         info!("Startup: user={}, database={}", username, self.database);
@@ -489,15 +823,24 @@ impl Session {
         self.send_message(stream, &key_data).await?;
 
         // Send parameter status messages
-        self.send_parameter_status(stream, "server_version", "14.0 (DriftDB 0.2.0)")
+        self.send_parameter_status(stream, "server_version", &protocol::server_version())
             .await?;
+        self.send_parameter_status(stream, "driftdb_version", env!("CARGO_PKG_VERSION"))
+            .await?;
+        self.send_parameter_status(
+            stream,
+            "driftdb_features",
+            &protocol::SERVER_FEATURES.join(","),
+        )
+        .await?;
         self.send_parameter_status(stream, "server_encoding", "UTF8")
             .await?;
         self.send_parameter_status(stream, "client_encoding", "UTF8")
             .await?;
         self.send_parameter_status(stream, "DateStyle", "ISO, MDY")
             .await?;
-        self.send_parameter_status(stream, "application_name", "")
+        let application_name = self.application_name();
+        self.send_parameter_status(stream, "application_name", &application_name)
             .await?;
 
         // Send ready for query
@@ -635,33 +978,80 @@ impl Session {
         }
 
         // Validate SQL before execution
-        if let Err(validation_error) = self.sql_validator.validate_query(sql) {
-            warn!(
-                "SQL validation failed for query from {}: {}",
-                self.addr, validation_error
-            );
-            let error = Message::error(
-                protocol::error_codes::SYNTAX_ERROR,
-                &format!("SQL validation failed: {}", validation_error),
-            );
+        let prepared = match self.sql_validator.prepare_query(sql) {
+            Ok(prepared) => prepared,
+            Err(validation_error) => {
+                warn!(
+                    "SQL validation failed for query from {}: {}",
+                    self.addr, validation_error
+                );
+                let error = validation_failure(&validation_error);
+                self.send_message(stream, &error).await?;
+                return Ok(());
+            }
+        };
+        let sql: &str = &prepared;
+
+        if let Err(denied) = self.check_audit_table_access(sql) {
+            let error = Message::error(protocol::error_codes::INSUFFICIENT_PRIVILEGE, &denied);
+            self.send_message(stream, &error).await?;
+            return Ok(());
+        }
+
+        if let Some(response) = self.handle_set_role(sql) {
+            self.send_message(stream, &response).await?;
+            return Ok(());
+        }
+
+        if let Some(response) = self.handle_privilege_change(sql) {
+            self.send_message(stream, &response).await?;
+            return Ok(());
+        }
+
+        if let Err(denied) = self.check_table_privileges(sql) {
+            let error = Message::error(protocol::error_codes::INSUFFICIENT_PRIVILEGE, &denied);
             self.send_message(stream, &error).await?;
             return Ok(());
         }
 
-        // Execute query through sql_bridge — transaction state is held in
-        // the QueryExecutor's own SessionContext, so each new executor for
-        // this connection starts fresh in auto-commit mode. (The previous
+        // Execute query through sql_bridge — transaction state and runtime
+        // parameters live in the connection's SessionContext, which each
+        // new executor for this connection picks up. (The previous
         // pattern shared a TransactionManager across statements; the
         // SessionContext now plays that role inside sql_bridge.)
         let session_id = format!("session_{}", self.process_id);
-        let executor = QueryExecutor::new_with_guard_and_session_id(&self.engine_guard, session_id);
-        match executor.execute(sql).await {
+        let executor = QueryExecutor::new_with_guard_and_session_id(
+            Self::backend(&self.engine_guard, self.addr)?,
+            session_id,
+        )
+        .with_statement_cache(self.statement_cache.clone())
+        .with_result_cache(self.result_cache.clone())
+        .with_session(self.sql_session.clone())
+        .with_row_security(RowSecurity::new(
+            self.rls_manager.clone(),
+            self.security_context(),
+        ));
+        let executor = match &self.compaction_tracker {
+            Some(tracker) => executor.with_compaction_tracker(tracker.clone()),
+            None => executor,
+        };
+        let outcome = match Self::execute_cancellable(&executor, sql, &self.cancel_signal).await {
+            Ok(outcome) => outcome,
+            Err(reason) => {
+                self.send_query_canceled(stream, &query_type, start_time, reason)
+                    .await?;
+                return Ok(());
+            }
+        };
+        match outcome {
             Ok(mut result) => {
+                let typed = executor.typed_result_columns(sql);
                 let duration = start_time.elapsed();
                 let duration_secs = duration.as_secs_f64();
 
                 // Apply Row-Level Security filtering for SELECT results
-                result = self.apply_rls_filter(sql, result);
+                let mut notices = executor.take_notices();
+                result = self.apply_rls_filter(sql, result, &mut notices);
 
                 // Update transaction status based on the command
                 let sql_upper = sql.trim().to_uppercase();
@@ -674,6 +1064,11 @@ impl Session {
                 // Record successful query metrics if registry is available
                 if !crate::metrics::REGISTRY.gather().is_empty() {
                     crate::metrics::record_query(&query_type, "success", duration_secs);
+                    crate::metrics::record_application_query(
+                        &self.application_name(),
+                        "success",
+                        duration_secs,
+                    );
                 }
 
                 // Log slow query if it exceeds threshold
@@ -693,11 +1088,15 @@ impl Session {
                         .clone()
                         .unwrap_or_else(|| "anonymous".to_string()),
                     self.database.clone(),
+                    self.application_name(),
                     rows_affected,
                     None,
                 );
 
-                self.send_query_result(stream, result).await?;
+                for notice in notices {
+                    self.send_message(stream, &Message::notice(&notice)).await?;
+                }
+                self.send_typed_query_result(stream, result, &typed).await?;
             }
             Err(e) => {
                 let duration = start_time.elapsed();
@@ -708,26 +1107,47 @@ impl Session {
                 // Record failed query metrics if registry is available
                 if !crate::metrics::REGISTRY.gather().is_empty() {
                     crate::metrics::record_query(&query_type, "error", duration_secs);
+                    crate::metrics::record_application_query(
+                        &self.application_name(),
+                        "error",
+                        duration_secs,
+                    );
                     crate::metrics::record_error("query", &query_type);
                 }
 
-                // Log slow query even if it failed
-                self.slow_query_logger.log_query(
-                    sql.to_string(),
-                    duration,
-                    self.addr.to_string(),
-                    self.username
-                        .clone()
-                        .unwrap_or_else(|| "anonymous".to_string()),
-                    self.database.clone(),
-                    None,
-                    Some(format!("error: {}", e)),
-                );
+                let message = format!("Query error: {}", e);
+                let code = protocol::error_codes::for_query_error(&message);
+
+                // Log slow query even if it failed, and a deadlock however
+                // long it took
+                let user = self
+                    .username
+                    .clone()
+                    .unwrap_or_else(|| "anonymous".to_string());
+                if code == protocol::error_codes::DEADLOCK_DETECTED {
+                    self.slow_query_logger.log_deadlock(
+                        sql.to_string(),
+                        duration,
+                        self.addr.to_string(),
+                        user,
+                        self.database.clone(),
+                        self.application_name(),
+                        e.to_string(),
+                    );
+                } else {
+                    self.slow_query_logger.log_query(
+                        sql.to_string(),
+                        duration,
+                        self.addr.to_string(),
+                        user,
+                        self.database.clone(),
+                        self.application_name(),
+                        None,
+                        Some(format!("error: {}", e)),
+                    );
+                }
 
-                let error = Message::error(
-                    protocol::error_codes::SYNTAX_ERROR,
-                    &format!("Query error: {}", e),
-                );
+                let error = protocol::error_response::query_error(sql, &message);
                 self.send_message(stream, &error).await?;
             }
         }
@@ -735,12 +1155,63 @@ impl Session {
         Ok(())
     }
 
+    /// Run `sql`, or say why it was canceled if a CancelRequest arrives
+    /// or the session's `statement_timeout` passes first. Only reads are
+    /// abandoned part way; a write always runs to completion, since
+    /// outside a transaction there is nothing to roll it back.
+    async fn execute_cancellable(
+        executor: &QueryExecutor<'_>,
+        sql: &str,
+        cancel_signal: &tokio::sync::Notify,
+    ) -> std::result::Result<Result<crate::executor::QueryResult>, &'static str> {
+        if determine_query_type(sql) != "SELECT" {
+            return Ok(executor.execute(sql).await);
+        }
+        let timeout = executor.statement_timeout();
+        let timeout = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            outcome = executor.execute(sql) => Ok(outcome),
+            _ = cancel_signal.notified() => Err("canceling statement due to user request"),
+            _ = timeout => Err("canceling statement due to statement timeout"),
+        }
+    }
+
+    async fn send_query_canceled(
+        &self,
+        stream: &mut SecureStream,
+        query_type: &str,
+        start_time: std::time::Instant,
+        reason: &str,
+    ) -> Result<()> {
+        info!("Statement from {} cancelled: {}", self.addr, reason);
+        if !crate::metrics::REGISTRY.gather().is_empty() {
+            let duration_secs = start_time.elapsed().as_secs_f64();
+            crate::metrics::record_query(query_type, "cancelled", duration_secs);
+            crate::metrics::record_application_query(
+                &self.application_name(),
+                "cancelled",
+                duration_secs,
+            );
+        }
+        let error = Message::error(protocol::error_codes::QUERY_CANCELED, reason);
+        self.send_message(stream, &error).await
+    }
+
     /// Apply Row-Level Security policies to a query result.
     /// For SELECT results, rows that fail the USING expression are dropped.
+    /// `INSERT/UPDATE/DELETE ... RETURNING` output is filtered the same way
+    /// against the write's target table, so RETURNING can't expose rows the
+    /// session could not SELECT. Hidden rows are counted in a notice.
     fn apply_rls_filter(
         &self,
         sql: &str,
         result: crate::executor::QueryResult,
+        notices: &mut Vec<String>,
     ) -> crate::executor::QueryResult {
         use crate::executor::QueryResult;
 
@@ -750,7 +1221,8 @@ impl Session {
         };
 
         // Extract the primary table name from the SQL
-        let table_name = extract_table_name_from_select(sql);
+        let table_name =
+            extract_dml_target_table(sql).or_else(|| extract_table_name_from_select(sql));
         let table_name = match table_name {
             Some(t) => t,
             None => {
@@ -758,18 +1230,7 @@ impl Session {
             }
         };
 
-        // Build security context
-        let username = self
-            .username
-            .clone()
-            .unwrap_or_else(|| "anonymous".to_string());
-        let context = SecurityContext {
-            username: username.clone(),
-            roles: vec![],
-            is_superuser: username == "driftdb" || username == "postgres",
-            session_id: Some(format!("session_{}", self.process_id)),
-            variables: std::collections::HashMap::new(),
-        };
+        let context = self.security_context();
 
         // Check access
         let policy_result = match self
@@ -780,28 +1241,57 @@ impl Session {
             Err(_) => return QueryResult::Select { columns, rows },
         };
 
-        match policy_result {
-            PolicyResult::Allow => QueryResult::Select { columns, rows },
-            PolicyResult::Deny => QueryResult::Select {
-                columns,
-                rows: vec![],
-            },
+        let total = rows.len();
+        let rows: Vec<_> = match policy_result {
+            PolicyResult::Allow => rows,
+            PolicyResult::Deny => vec![],
             PolicyResult::Filter(filter_expr) => {
                 // Apply the filter expression to each row
-                let filtered_rows = rows
-                    .into_iter()
-                    .filter(|row| {
-                        rls_row_matches_filter(&columns, row, &filter_expr)
-                    })
-                    .collect();
-                QueryResult::Select {
-                    columns,
-                    rows: filtered_rows,
-                }
+                rows.into_iter()
+                    .filter(|row| rls_row_matches_filter(&columns, row, &filter_expr))
+                    .collect()
             }
+        };
+        if rows.len() < total {
+            notices.push(format!(
+                "row-level security filtered {} of {} rows from \"{}\"",
+                total - rows.len(),
+                total,
+                table_name
+            ));
+        }
+        QueryResult::Select { columns, rows }
+    }
+
+    /// RLS context for the session's statements. While a role is set, only
+    /// that role (and the roles it inherits) counts.
+    fn security_context(&self) -> SecurityContext {
+        let username = self
+            .username
+            .clone()
+            .unwrap_or_else(|| "anonymous".to_string());
+        let roles = self.rbac_manager.principal_role_names(self.principal());
+        let is_superuser = match &self.current_role {
+            Some(_) => roles.iter().any(|role| role == "superuser"),
+            None => username == "driftdb" || username == "postgres",
+        };
+        SecurityContext {
+            username,
+            roles,
+            is_superuser,
+            session_id: Some(format!("session_{}", self.process_id)),
+            variables: std::collections::HashMap::new(),
         }
     }
 
+    /// Who the session's statements run as for RBAC checks
+    fn principal(&self) -> Principal<'_> {
+        Principal::with_role(
+            self.username.as_deref().unwrap_or_default(),
+            self.current_role.as_deref(),
+        )
+    }
+
     /// Expose the RLS manager for policy management commands.
     pub fn rls_manager(&self) -> &Arc<RlsManager> {
         &self.rls_manager
@@ -1092,13 +1582,25 @@ impl Session {
         &mut self,
         stream: &mut SecureStream,
         result: crate::executor::QueryResult,
+    ) -> Result<()> {
+        self.send_typed_query_result(stream, result, &HashMap::new())
+            .await
+    }
+
+    /// Send a result, describing the `typed` columns with their schema
+    /// types and inferring the rest from their values
+    async fn send_typed_query_result(
+        &mut self,
+        stream: &mut SecureStream,
+        result: crate::executor::QueryResult,
+        typed: &HashMap<String, protocol::DataType>,
     ) -> Result<()> {
         use crate::executor::QueryResult;
 
         match result {
             QueryResult::Select { columns, rows } => {
                 // Infer proper PostgreSQL data types from the actual data
-                let column_types = Self::infer_column_types(&columns, &rows);
+                let column_types = Self::infer_column_types(&columns, &rows, typed);
 
                 // Send row description with proper data types
                 let fields = columns
@@ -1225,7 +1727,178 @@ impl Session {
         self.send_message(stream, &msg).await
     }
 
+    /// The audit table is append-only for clients, and reading it needs
+    /// the `ViewAuditLog` permission. Statements naming it that can't be
+    /// parsed are refused.
+    fn check_audit_table_access(&self, sql: &str) -> std::result::Result<(), String> {
+        if !references_table(sql, AUDIT_TABLE).map_err(|e| e.to_string())? {
+            return Ok(());
+        }
+        if StatementCategory::classify(sql) != StatementCategory::Read {
+            return Err(format!("{} is append-only", AUDIT_TABLE));
+        }
+        let username = self
+            .username
+            .as_deref()
+            .ok_or_else(|| "Authentication required".to_string())?;
+        check_view_audit_log_permission(
+            &self.rbac_manager,
+            Principal::with_role(username, self.current_role.as_deref()),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// Check the table and column privileges `sql` needs. Sessions without
+    /// a user hold no roles, so they can't use tables with grants. While a
+    /// role is set, the statement also needs that role's permission for
+    /// each of its query types, and statements without one are refused.
+    fn check_table_privileges(&self, sql: &str) -> std::result::Result<(), String> {
+        if let Some(role) = &self.current_role {
+            let query_types = statement_query_types(sql).ok_or_else(|| {
+                format!(
+                    "Permission denied: statement is not allowed while role {} is set",
+                    role
+                )
+            })?;
+            for query_type in query_types {
+                check_query_permission(
+                    &self.rbac_manager,
+                    self.principal(),
+                    query_type,
+                    Some(&self.audit_logger),
+                    &self.addr.to_string(),
+                )
+                .map_err(|e| e.to_string())?;
+            }
+        }
+        check_statement_privileges(&self.rbac_manager, self.principal(), sql)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Handle `SET ROLE name`, `SET ROLE NONE` and `RESET ROLE`, returning
+    /// the response to send. Returns `None` for any other statement.
+    fn handle_set_role(&mut self, sql: &str) -> Option<Message> {
+        let role = parse_set_role(sql)?;
+        let username = self.username.clone().unwrap_or_default();
+
+        if let Some(role) = &role {
+            if self.rbac_manager.get_role(role).is_none() {
+                return Some(Message::error(
+                    protocol::error_codes::UNDEFINED_OBJECT,
+                    &format!("role \"{}\" does not exist", role),
+                ));
+            }
+            if !self.rbac_manager.is_member_of(&username, role)
+                && !self.rbac_manager.is_member_of(&username, "superuser")
+            {
+                return Some(Message::error(
+                    protocol::error_codes::INSUFFICIENT_PRIVILEGE,
+                    &format!("permission denied to set role \"{}\"", role),
+                ));
+            }
+        }
+
+        info!(
+            "Session {} for {} set role {:?}",
+            self.process_id, username, role
+        );
+        let tag = if role.is_some() { "SET" } else { "RESET" };
+        self.current_role = role;
+        Some(Message::CommandComplete {
+            tag: tag.to_string(),
+        })
+    }
+
+    /// Apply a `GRANT`/`REVOKE` of table privileges or role membership,
+    /// returning the response to send. Returns `None` for any other
+    /// statement.
+    fn handle_privilege_change(&self, sql: &str) -> Option<Message> {
+        let change = match parse_grant_statement(sql)? {
+            Ok(change) => change,
+            Err(e) => {
+                return Some(Message::error(
+                    protocol::error_codes::SYNTAX_ERROR,
+                    &e.to_string(),
+                ))
+            }
+        };
+
+        let username = self.principal();
+        if let Err(e) = self
+            .rbac_manager
+            .require_permission(username, change.required_permission())
+        {
+            return Some(Message::error(
+                protocol::error_codes::INSUFFICIENT_PRIVILEGE,
+                &e.to_string(),
+            ));
+        }
+        if let Err(e) = change.apply(&self.rbac_manager) {
+            return Some(Message::error(
+                protocol::error_codes::UNDEFINED_OBJECT,
+                &e.to_string(),
+            ));
+        }
+
+        info!("{} by {}: {:?}", change.command_tag(), username, change);
+        Some(Message::CommandComplete {
+            tag: change.command_tag().to_string(),
+        })
+    }
+
+    /// The session's `application_name`, from the startup packet or a
+    /// later `SET application_name`; empty when the client never set one
+    fn application_name(&self) -> String {
+        match self.sql_session.lock().settings.get("application_name") {
+            Some(driftdb_core::settings::Value::Text(name)) => name.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// Take `application_name` from the startup packet, cut to 63 bytes
+    /// as PostgreSQL does
+    fn set_application_name(&mut self, name: &str) {
+        let mut end = name.len().min(63);
+        while !name.is_char_boundary(end) {
+            end -= 1;
+        }
+        let Ok(parameter) = driftdb_core::settings::lookup("application_name") else {
+            return;
+        };
+        self.sql_session.lock().settings.insert(
+            parameter.name.to_string(),
+            driftdb_core::settings::Value::Text(name[..end].to_string()),
+        );
+    }
+
+    /// Record the statement just handled in the audit log, if its category
+    /// is audited.
+    fn audit_statement(&self, sql: &str) {
+        let error = self.statement_error.lock().take();
+        let category = StatementCategory::classify(sql);
+        if !self.statement_auditor.should_audit(category) {
+            return;
+        }
+        let entry = StatementAuditEntry::new(
+            sql,
+            category,
+            self.username.clone(),
+            self.addr,
+            Some(self.application_name()).filter(|name| !name.is_empty()),
+            error,
+        );
+        let Some(guard) = &self.engine_guard else {
+            warn!("No backend held to audit statement from {}", self.addr);
+            return;
+        };
+        let mut engine = guard.write();
+        self.statement_auditor.record_or_log(&mut engine, entry);
+    }
+
     async fn send_message(&self, stream: &mut SecureStream, msg: &Message) -> Result<()> {
+        if let Message::ErrorResponse { fields } = msg {
+            *self.statement_error.lock() = fields.get(&b'M').cloned();
+        }
         let bytes = protocol::codec::encode_message(msg);
         stream.write_all(&bytes).await?;
         stream.flush().await?;
@@ -1233,6 +1906,23 @@ impl Session {
     }
 }
 
+/// ErrorResponse for a statement the SQL validator rejected. Policy
+/// violations are permission errors; everything else is reported as a
+/// syntax error.
+fn validation_failure(validation_error: &anyhow::Error) -> Message {
+    if validation_error.is::<PolicyViolation>() {
+        Message::error(
+            protocol::error_codes::INSUFFICIENT_PRIVILEGE,
+            &validation_error.to_string(),
+        )
+    } else {
+        Message::error(
+            protocol::error_codes::SYNTAX_ERROR,
+            &format!("SQL validation failed: {}", validation_error),
+        )
+    }
+}
+
 /// Extract the primary FROM table name from a SELECT query (best-effort).
 fn extract_table_name_from_select(sql: &str) -> Option<String> {
     let lower = sql.to_lowercase();
@@ -1251,6 +1941,27 @@ fn extract_table_name_from_select(sql: &str) -> Option<String> {
     }
 }
 
+/// Target table of an `INSERT INTO t` / `UPDATE t` / `DELETE FROM t`
+/// statement, used to apply RLS to its RETURNING rows.
+fn extract_dml_target_table(sql: &str) -> Option<String> {
+    let mut tokens = sql.split_whitespace();
+    let first = tokens.next()?.to_uppercase();
+    let table = match first.as_str() {
+        "INSERT" | "DELETE" => {
+            tokens.next()?;
+            tokens.next()?
+        }
+        "UPDATE" => tokens.next()?,
+        _ => return None,
+    };
+    let table = table.split('(').next()?.trim_end_matches(';');
+    if table.is_empty() {
+        None
+    } else {
+        Some(table.to_string())
+    }
+}
+
 /// Evaluate a simple RLS filter expression (e.g. `"user_id = 'alice'"`)
 /// against a row represented as a parallel columns/values pair.
 fn rls_row_matches_filter(columns: &[String], row: &[Value], filter_expr: &str) -> bool {
@@ -1437,111 +2148,198 @@ impl Session {
         {
             Ok(sql) => {
                 info!("Executing prepared statement: {}", sql);
+                self.statement_error.lock().take();
+                self.execute_portal_sql(stream, &portal_name, &sql, start_time)
+                    .await?;
+                self.audit_statement(&sql);
+            }
+            Err(e) => {
+                error!("Portal execution error: {}", e);
+                let error = Message::error(
+                    protocol::error_codes::INVALID_CURSOR_NAME,
+                    &format!("Portal not found: {}", e),
+                );
+                self.send_message(stream, &error).await?;
+            }
+        }
 
-                // Validate SQL before execution
-                if let Err(validation_error) = self.sql_validator.validate_query(&sql) {
-                    warn!(
-                        "SQL validation failed for prepared statement from {}: {}",
-                        self.addr, validation_error
-                    );
-                    let error = Message::error(
-                        protocol::error_codes::SYNTAX_ERROR,
-                        &format!("SQL validation failed: {}", validation_error),
-                    );
-                    self.send_message(stream, &error).await?;
-                    return Ok(());
-                }
+        Ok(())
+    }
 
-                // Execute through sql_bridge — see note in the parallel
-                // construction above; same shape.
-                let session_id = format!("session_{}", self.process_id);
-                let executor =
-                    QueryExecutor::new_with_guard_and_session_id(&self.engine_guard, session_id);
-                match executor.execute(&sql).await {
-                    Ok(result) => {
-                        let duration = start_time.elapsed();
-                        let duration_secs = duration.as_secs_f64();
-
-                        // Update transaction status based on the command
-                        let sql_upper = sql.trim().to_uppercase();
-                        if sql_upper.starts_with("BEGIN") {
-                            self.transaction_status = TransactionStatus::InTransaction;
-                        } else if sql_upper.starts_with("COMMIT")
-                            || sql_upper.starts_with("ROLLBACK")
-                        {
-                            self.transaction_status = TransactionStatus::Idle;
-                        }
+    /// Run the SQL of a bound portal and send its result.
+    async fn execute_portal_sql(
+        &mut self,
+        stream: &mut SecureStream,
+        portal_name: &str,
+        sql: &str,
+        start_time: std::time::Instant,
+    ) -> Result<()> {
+        // Validate SQL before execution
+        let prepared = match self.sql_validator.prepare_query(sql) {
+            Ok(prepared) => prepared,
+            Err(validation_error) => {
+                warn!(
+                    "SQL validation failed for prepared statement from {}: {}",
+                    self.addr, validation_error
+                );
+                let error = validation_failure(&validation_error);
+                self.send_message(stream, &error).await?;
+                return Ok(());
+            }
+        };
+        let sql: &str = &prepared;
 
-                        // Record successful query metrics if registry is available
-                        if !crate::metrics::REGISTRY.gather().is_empty() {
-                            let query_type = determine_query_type(&sql);
-                            crate::metrics::record_query(&query_type, "success", duration_secs);
-                        }
+        if let Err(denied) = self.check_audit_table_access(sql) {
+            let error = Message::error(protocol::error_codes::INSUFFICIENT_PRIVILEGE, &denied);
+            self.send_message(stream, &error).await?;
+            return Ok(());
+        }
 
-                        // Log slow query if it exceeds threshold
-                        let rows_affected = match &result {
-                            crate::executor::QueryResult::Select { rows, .. } => {
-                                Some(rows.len() as u64)
-                            }
-                            crate::executor::QueryResult::Insert { count } => Some(*count as u64),
-                            crate::executor::QueryResult::Update { count } => Some(*count as u64),
-                            crate::executor::QueryResult::Delete { count } => Some(*count as u64),
-                            _ => None,
-                        };
+        if let Some(response) = self.handle_set_role(sql) {
+            self.send_message(stream, &response).await?;
+            return Ok(());
+        }
 
-                        self.slow_query_logger.log_query(
-                            sql.to_string(),
-                            duration,
-                            self.addr.to_string(),
-                            self.username
-                                .clone()
-                                .unwrap_or_else(|| "anonymous".to_string()),
-                            self.database.clone(),
-                            rows_affected,
-                            Some(format!("prepared_statement={}", portal_name)),
-                        );
+        if let Some(response) = self.handle_privilege_change(sql) {
+            self.send_message(stream, &response).await?;
+            return Ok(());
+        }
 
-                        self.send_query_result(stream, result).await?;
-                    }
-                    Err(e) => {
-                        let duration = start_time.elapsed();
-                        let duration_secs = duration.as_secs_f64();
-                        error!("Execute error: {}", e);
-
-                        // Record failed query metrics if registry is available
-                        if !crate::metrics::REGISTRY.gather().is_empty() {
-                            let query_type = determine_query_type(&sql);
-                            crate::metrics::record_query(&query_type, "error", duration_secs);
-                            crate::metrics::record_error("query", &query_type);
-                        }
+        if let Err(denied) = self.check_table_privileges(sql) {
+            let error = Message::error(protocol::error_codes::INSUFFICIENT_PRIVILEGE, &denied);
+            self.send_message(stream, &error).await?;
+            return Ok(());
+        }
 
-                        // Log slow query even if it failed
-                        self.slow_query_logger.log_query(
-                            sql.to_string(),
-                            duration,
-                            self.addr.to_string(),
-                            self.username
-                                .clone()
-                                .unwrap_or_else(|| "anonymous".to_string()),
-                            self.database.clone(),
-                            None,
-                            Some(format!("prepared_statement={}, error: {}", portal_name, e)),
-                        );
+        // Execute through sql_bridge — see note in the parallel
+        // construction above; same shape.
+        let session_id = format!("session_{}", self.process_id);
+        let executor = QueryExecutor::new_with_guard_and_session_id(
+            Self::backend(&self.engine_guard, self.addr)?,
+            session_id,
+        )
+        .with_statement_cache(self.statement_cache.clone())
+        .with_result_cache(self.result_cache.clone())
+        .with_session(self.sql_session.clone())
+        .with_row_security(RowSecurity::new(
+            self.rls_manager.clone(),
+            self.security_context(),
+        ));
+        let executor = match &self.compaction_tracker {
+            Some(tracker) => executor.with_compaction_tracker(tracker.clone()),
+            None => executor,
+        };
+        let outcome = match Self::execute_cancellable(&executor, sql, &self.cancel_signal).await {
+            Ok(outcome) => outcome,
+            Err(reason) => {
+                let query_type = determine_query_type(sql);
+                self.send_query_canceled(stream, &query_type, start_time, reason)
+                    .await?;
+                return Ok(());
+            }
+        };
+        match outcome {
+            Ok(result) => {
+                let typed = executor.typed_result_columns(sql);
+                let duration = start_time.elapsed();
+                let duration_secs = duration.as_secs_f64();
 
-                        let error = Message::error(
-                            protocol::error_codes::SYNTAX_ERROR,
-                            &format!("Execute error: {}", e),
-                        );
-                        self.send_message(stream, &error).await?;
-                    }
+                // Update transaction status based on the command
+                let sql_upper = sql.trim().to_uppercase();
+                if sql_upper.starts_with("BEGIN") {
+                    self.transaction_status = TransactionStatus::InTransaction;
+                } else if sql_upper.starts_with("COMMIT") || sql_upper.starts_with("ROLLBACK") {
+                    self.transaction_status = TransactionStatus::Idle;
+                }
+
+                // Record successful query metrics if registry is available
+                if !crate::metrics::REGISTRY.gather().is_empty() {
+                    let query_type = determine_query_type(sql);
+                    crate::metrics::record_query(&query_type, "success", duration_secs);
+                    crate::metrics::record_application_query(
+                        &self.application_name(),
+                        "success",
+                        duration_secs,
+                    );
                 }
+
+                // Log slow query if it exceeds threshold
+                let rows_affected = match &result {
+                    crate::executor::QueryResult::Select { rows, .. } => Some(rows.len() as u64),
+                    crate::executor::QueryResult::Insert { count } => Some(*count as u64),
+                    crate::executor::QueryResult::Update { count } => Some(*count as u64),
+                    crate::executor::QueryResult::Delete { count } => Some(*count as u64),
+                    _ => None,
+                };
+
+                self.slow_query_logger.log_query(
+                    sql.to_string(),
+                    duration,
+                    self.addr.to_string(),
+                    self.username
+                        .clone()
+                        .unwrap_or_else(|| "anonymous".to_string()),
+                    self.database.clone(),
+                    self.application_name(),
+                    rows_affected,
+                    Some(format!("prepared_statement={}", portal_name)),
+                );
+
+                for notice in executor.take_notices() {
+                    self.send_message(stream, &Message::notice(&notice)).await?;
+                }
+                self.send_typed_query_result(stream, result, &typed).await?;
             }
             Err(e) => {
-                error!("Portal execution error: {}", e);
-                let error = Message::error(
-                    protocol::error_codes::INVALID_CURSOR_NAME,
-                    &format!("Portal not found: {}", e),
-                );
+                let duration = start_time.elapsed();
+                let duration_secs = duration.as_secs_f64();
+                error!("Execute error: {}", e);
+
+                // Record failed query metrics if registry is available
+                if !crate::metrics::REGISTRY.gather().is_empty() {
+                    let query_type = determine_query_type(sql);
+                    crate::metrics::record_query(&query_type, "error", duration_secs);
+                    crate::metrics::record_application_query(
+                        &self.application_name(),
+                        "error",
+                        duration_secs,
+                    );
+                    crate::metrics::record_error("query", &query_type);
+                }
+
+                let message = format!("Execute error: {}", e);
+                let code = protocol::error_codes::for_query_error(&message);
+
+                // Log slow query even if it failed, and a deadlock however
+                // long it took
+                let user = self
+                    .username
+                    .clone()
+                    .unwrap_or_else(|| "anonymous".to_string());
+                if code == protocol::error_codes::DEADLOCK_DETECTED {
+                    self.slow_query_logger.log_deadlock(
+                        sql.to_string(),
+                        duration,
+                        self.addr.to_string(),
+                        user,
+                        self.database.clone(),
+                        self.application_name(),
+                        format!("prepared_statement={}, {}", portal_name, e),
+                    );
+                } else {
+                    self.slow_query_logger.log_query(
+                        sql.to_string(),
+                        duration,
+                        self.addr.to_string(),
+                        user,
+                        self.database.clone(),
+                        self.application_name(),
+                        None,
+                        Some(format!("prepared_statement={}, error: {}", portal_name, e)),
+                    );
+                }
+
+                let error = protocol::error_response::query_error(sql, &message);
                 self.send_message(stream, &error).await?;
             }
         }
@@ -1673,17 +2471,20 @@ impl Session {
         }
     }
 
-    /// Infer PostgreSQL data types for columns from sample data
-    fn infer_column_types(columns: &[String], rows: &[Vec<Value>]) -> Vec<protocol::DataType> {
-        if rows.is_empty() {
-            // No data, default to Text for all columns
-            return vec![protocol::DataType::Text; columns.len()];
-        }
-
+    /// Infer PostgreSQL data types for columns from sample data. Columns
+    /// in `typed` take their schema type instead.
+    fn infer_column_types(
+        columns: &[String],
+        rows: &[Vec<Value>],
+        typed: &HashMap<String, protocol::DataType>,
+    ) -> Vec<protocol::DataType> {
         columns
             .iter()
             .enumerate()
-            .map(|(col_idx, _)| {
+            .map(|(col_idx, name)| {
+                if let Some(&data_type) = typed.get(name) {
+                    return data_type;
+                }
                 // Sample the first few non-null values to infer type
                 for row in rows.iter().take(5) {
                     // Sample up to 5 rows
@@ -0,0 +1,213 @@
+//! Transaction pooling: sharing the engine pool's backends among more
+//! clients than it has connections
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use driftdb_core::{EngineGuard, EnginePool};
+
+/// When a client session holds a pooled backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolMode {
+    /// For the whole connection; clients past the pool size are refused
+    Session,
+    /// For one statement, or one transaction when a block is open; clients
+    /// past the pool size are accepted and their statements queue
+    Transaction,
+}
+
+impl FromStr for PoolMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "session" => Ok(PoolMode::Session),
+            "transaction" => Ok(PoolMode::Transaction),
+            other => Err(anyhow!(
+                "unknown pool mode '{}' (expected session or transaction)",
+                other
+            )),
+        }
+    }
+}
+
+/// Bounds on statements waiting for a backend in transaction mode
+#[derive(Debug, Clone)]
+pub struct StatementQueueConfig {
+    /// Statements allowed to wait at once; further ones fail immediately
+    pub max_depth: usize,
+    /// How long a statement waits before it fails
+    pub wait_timeout: Duration,
+}
+
+impl Default for StatementQueueConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 1000,
+            wait_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Hands out backends from the engine pool to queued statements
+pub struct StatementQueue {
+    config: StatementQueueConfig,
+    waiting: AtomicUsize,
+}
+
+/// Decrements the waiting count however the wait ends
+struct Waiting<'a>(&'a StatementQueue);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let depth = self.0.waiting.fetch_sub(1, Ordering::SeqCst) - 1;
+        crate::metrics::update_statement_queue_depth(depth);
+    }
+}
+
+impl StatementQueue {
+    pub fn new(config: StatementQueueConfig) -> Self {
+        Self {
+            config,
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Statements currently waiting for a backend
+    pub fn depth(&self) -> usize {
+        self.waiting.load(Ordering::SeqCst)
+    }
+
+    /// Wait for a backend from `pool` for a statement from `addr`. Fails at
+    /// once when the queue is full, or after the wait timeout.
+    pub async fn acquire(&self, pool: &EnginePool, addr: SocketAddr) -> Result<EngineGuard> {
+        let started = Instant::now();
+        let depth = self.waiting.fetch_add(1, Ordering::SeqCst) + 1;
+        let _waiting = Waiting(self);
+        if depth > self.config.max_depth {
+            crate::metrics::record_statement_queue_wait("rejected", 0.0);
+            return Err(anyhow!(
+                "statement queue is full ({} waiting)",
+                self.config.max_depth
+            ));
+        }
+        crate::metrics::update_statement_queue_depth(depth);
+
+        let outcome = tokio::time::timeout(self.config.wait_timeout, pool.acquire(addr)).await;
+        let waited = started.elapsed().as_secs_f64();
+        match outcome {
+            Ok(Ok(guard)) => {
+                crate::metrics::record_statement_queue_wait("acquired", waited);
+                Ok(guard)
+            }
+            Ok(Err(e)) => {
+                crate::metrics::record_statement_queue_wait("timeout", waited);
+                Err(anyhow!("no backend available: {}", e))
+            }
+            Err(_) => {
+                crate::metrics::record_statement_queue_wait("timeout", waited);
+                Err(anyhow!(
+                    "timed out after {:?} waiting for a backend",
+                    self.config.wait_timeout
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use driftdb_core::connection::PoolConfig;
+    use driftdb_core::observability::Metrics;
+    use driftdb_core::Engine;
+    use parking_lot::RwLock;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn pool(max_connections: usize) -> (TempDir, EnginePool) {
+        let temp_dir = TempDir::new().unwrap();
+        let engine = Arc::new(RwLock::new(Engine::init(temp_dir.path()).unwrap()));
+        let config = PoolConfig {
+            min_connections: 0,
+            max_connections,
+            connection_timeout: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let pool = EnginePool::new(engine, config, Arc::new(Metrics::new())).unwrap();
+        (temp_dir, pool)
+    }
+
+    fn client(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_parse_pool_mode() {
+        assert_eq!("session".parse::<PoolMode>().unwrap(), PoolMode::Session);
+        assert_eq!(
+            "Transaction".parse::<PoolMode>().unwrap(),
+            PoolMode::Transaction
+        );
+        assert!("statement".parse::<PoolMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_waiter_gets_backend_once_released() {
+        let (_dir, pool) = pool(1);
+        let queue = Arc::new(StatementQueue::new(StatementQueueConfig::default()));
+
+        let held = queue.acquire(&pool, client(5001)).await.unwrap();
+        let waiter = {
+            let queue = queue.clone();
+            let pool = pool.clone();
+            tokio::spawn(async move { queue.acquire(&pool, client(5002)).await.is_ok() })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(queue.depth(), 1);
+
+        drop(held);
+        assert!(waiter.await.unwrap());
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let (_dir, pool) = pool(1);
+        let queue = StatementQueue::new(StatementQueueConfig {
+            max_depth: 10,
+            wait_timeout: Duration::from_millis(50),
+        });
+
+        let _held = queue.acquire(&pool, client(5001)).await.unwrap();
+        let err = queue.acquire(&pool, client(5002)).await.err().unwrap();
+        assert!(err.to_string().contains("timed out"), "{}", err);
+        assert_eq!(queue.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_rejects_immediately() {
+        let (_dir, pool) = pool(1);
+        let queue = Arc::new(StatementQueue::new(StatementQueueConfig {
+            max_depth: 1,
+            wait_timeout: Duration::from_secs(5),
+        }));
+
+        let held = queue.acquire(&pool, client(5001)).await.unwrap();
+        let waiter = {
+            let queue = queue.clone();
+            let pool = pool.clone();
+            tokio::spawn(async move { queue.acquire(&pool, client(5002)).await.is_ok() })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let err = queue.acquire(&pool, client(5003)).await.err().unwrap();
+        assert!(err.to_string().contains("queue is full"), "{}", err);
+
+        drop(held);
+        assert!(waiter.await.unwrap());
+    }
+}
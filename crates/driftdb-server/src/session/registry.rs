@@ -0,0 +1,106 @@
+//! Live sessions, for the admin sessions endpoint
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use driftdb_core::session_memory::SessionMemory;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+
+/// One connected session, readable while its statements run
+pub struct SessionInfo {
+    pub process_id: i32,
+    pub addr: SocketAddr,
+    pub connected_at: SystemTime,
+    user: Mutex<Option<String>>,
+    /// Shared with the session's `SessionContext`, so reading it doesn't
+    /// wait for the statement holding the context
+    memory: Arc<SessionMemory>,
+}
+
+impl SessionInfo {
+    pub fn set_user(&self, user: Option<String>) {
+        *self.user.lock() = user;
+    }
+
+    pub fn to_json(&self) -> Value {
+        let connected_at = self
+            .connected_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        json!({
+            "process_id": self.process_id,
+            "client_addr": self.addr.to_string(),
+            "user": *self.user.lock(),
+            "connected_at_ms": connected_at,
+            "memory_bytes": self.memory.current(),
+            "peak_memory_bytes": self.memory.peak(),
+        })
+    }
+}
+
+/// Live sessions by process id
+#[derive(Default)]
+pub struct SessionRegistry {
+    sessions: Mutex<BTreeMap<i32, Arc<SessionInfo>>>,
+}
+
+impl SessionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(
+        &self,
+        process_id: i32,
+        addr: SocketAddr,
+        memory: Arc<SessionMemory>,
+    ) -> Arc<SessionInfo> {
+        let info = Arc::new(SessionInfo {
+            process_id,
+            addr,
+            connected_at: SystemTime::now(),
+            user: Mutex::new(None),
+            memory,
+        });
+        self.sessions.lock().insert(process_id, info.clone());
+        info
+    }
+
+    pub fn unregister(&self, process_id: i32) {
+        self.sessions.lock().remove(&process_id);
+    }
+
+    /// Every live session, oldest first
+    pub fn all(&self) -> Vec<Arc<SessionInfo>> {
+        self.sessions.lock().values().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_live_sessions() {
+        let registry = SessionRegistry::new();
+        let addr: SocketAddr = "127.0.0.1:5433".parse().unwrap();
+        let info = registry.register(1000, addr, Arc::new(SessionMemory::default()));
+        info.set_user(Some("alice".to_string()));
+        registry.register(1001, addr, Arc::new(SessionMemory::default()));
+
+        let sessions = registry.all();
+        assert_eq!(sessions.len(), 2);
+        let first = sessions[0].to_json();
+        assert_eq!(first["process_id"], 1000);
+        assert_eq!(first["user"], "alice");
+        assert_eq!(first["memory_bytes"], 0);
+        assert_eq!(first["peak_memory_bytes"], 0);
+
+        registry.unregister(1000);
+        assert_eq!(registry.all()[0].process_id, 1001);
+    }
+}
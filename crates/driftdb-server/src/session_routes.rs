@@ -0,0 +1,25 @@
+//! HTTP route listing connected sessions
+//!
+//! Served from the session registry rather than the sessions themselves,
+//! so a session's memory can be read while its statement is running.
+
+use std::sync::Arc;
+
+use axum::{extract::State, response::Json, routing::get, Router};
+use serde_json::{json, Value};
+
+use crate::session::SessionRegistry;
+
+/// Create the sessions router
+pub fn create_router(registry: Arc<SessionRegistry>) -> Router {
+    Router::new()
+        .route("/api/sessions", get(list_sessions))
+        .with_state(registry)
+}
+
+/// GET /api/sessions - Connected sessions with their current and peak
+/// memory
+async fn list_sessions(State(registry): State<Arc<SessionRegistry>>) -> Json<Value> {
+    let sessions: Vec<Value> = registry.all().iter().map(|s| s.to_json()).collect();
+    Json(json!({ "sessions": sessions }))
+}
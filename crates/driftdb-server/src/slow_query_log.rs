@@ -57,6 +57,9 @@ pub struct SlowQueryEntry {
     pub user: String,
     /// Database name
     pub database: String,
+    /// `application_name` the client reported, empty if unset
+    #[serde(default)]
+    pub application_name: String,
     /// Number of rows returned/affected
     pub rows_affected: Option<u64>,
     /// Additional context (transaction ID, etc.)
@@ -87,6 +90,7 @@ impl SlowQueryLogger {
         client_addr: String,
         user: String,
         database: String,
+        application_name: String,
         rows_affected: Option<u64>,
         context: Option<String>,
     ) {
@@ -98,6 +102,69 @@ impl SlowQueryLogger {
             return;
         }
 
+        let request_id = self.record(
+            query.clone(),
+            duration_ms,
+            client_addr.clone(),
+            user.clone(),
+            database.clone(),
+            application_name.clone(),
+            rows_affected,
+            context,
+        );
+
+        if self.config.read().log_to_stdout {
+            warn!(
+                "SLOW QUERY [{}ms] request_id={} user={} database={} application={} client={} query={}",
+                duration_ms, request_id, user, database, application_name, client_addr, query
+            );
+        }
+    }
+
+    /// Log a statement aborted to break a deadlock, however long it ran,
+    /// with the cycle it was part of. Always also logged as a warning, so
+    /// the queries of the transactions involved can be found.
+    #[allow(clippy::too_many_arguments)]
+    pub fn log_deadlock(
+        &self,
+        query: String,
+        duration: Duration,
+        client_addr: String,
+        user: String,
+        database: String,
+        application_name: String,
+        deadlock: String,
+    ) {
+        let request_id = self.record(
+            query.clone(),
+            duration.as_millis() as u64,
+            client_addr.clone(),
+            user.clone(),
+            database.clone(),
+            application_name.clone(),
+            None,
+            Some(deadlock.clone()),
+        );
+        warn!(
+            "DEADLOCK request_id={} user={} database={} application={} client={} query={} detail={}",
+            request_id, user, database, application_name, client_addr, query, deadlock
+        );
+    }
+
+    /// Store an entry and write it to the log file if configured,
+    /// returning its request id
+    #[allow(clippy::too_many_arguments)]
+    fn record(
+        &self,
+        query: String,
+        duration_ms: u64,
+        client_addr: String,
+        user: String,
+        database: String,
+        application_name: String,
+        rows_affected: Option<u64>,
+        context: Option<String>,
+    ) -> String {
         let request_id = Uuid::new_v4().to_string();
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -106,12 +173,13 @@ impl SlowQueryLogger {
 
         let entry = SlowQueryEntry {
             request_id: request_id.clone(),
-            query: query.clone(),
+            query,
             duration_ms,
             timestamp,
-            client_addr: client_addr.clone(),
-            user: user.clone(),
-            database: database.clone(),
+            client_addr,
+            user,
+            database,
+            application_name,
             rows_affected,
             context,
         };
@@ -129,19 +197,10 @@ impl SlowQueryLogger {
             }
         }
 
-        // Log to configured outputs
-        let config = self.config.read();
-
-        if config.log_to_stdout {
-            warn!(
-                "SLOW QUERY [{}ms] request_id={} user={} database={} client={} query={}",
-                duration_ms, request_id, user, database, client_addr, query
-            );
-        }
-
-        if config.log_to_file {
+        if self.config.read().log_to_file {
             self.log_to_file(&entry);
         }
+        request_id
     }
 
     /// Write slow query to log file
@@ -314,6 +373,7 @@ mod tests {
             "127.0.0.1:5432".to_string(),
             "testuser".to_string(),
             "testdb".to_string(),
+            "psql".to_string(),
             Some(100),
             Some("txn_123".to_string()),
         );
@@ -325,6 +385,7 @@ mod tests {
             "127.0.0.1:5432".to_string(),
             "testuser".to_string(),
             "testdb".to_string(),
+            "psql".to_string(),
             Some(1),
             None,
         );
@@ -333,6 +394,40 @@ mod tests {
         assert_eq!(recent.len(), 1);
         assert_eq!(recent[0].query, "SELECT * FROM users WHERE age > 30");
         assert_eq!(recent[0].duration_ms, 150);
+        assert_eq!(recent[0].application_name, "psql");
+    }
+
+    #[test]
+    fn test_deadlocks_are_logged_whatever_their_duration() {
+        let logger = SlowQueryLogger::new(SlowQueryConfig {
+            slow_threshold_ms: 1000,
+            max_stored_queries: 10,
+            log_to_file: false,
+            log_to_stdout: false,
+            log_file_path: "/tmp/test_deadlocks.log".to_string(),
+        });
+
+        logger.log_deadlock(
+            "UPDATE accounts SET balance = 0 WHERE id = 2".to_string(),
+            Duration::from_millis(3),
+            "127.0.0.1:5432".to_string(),
+            "testuser".to_string(),
+            "testdb".to_string(),
+            "psql".to_string(),
+            "transaction 8 waits for a lock on accounts:1 held by transaction 7; \
+             transaction 7 waits for a lock on accounts:2 held by transaction 8; \
+             transaction 8 was aborted"
+                .to_string(),
+        );
+
+        let recent = logger.get_recent_queries(10);
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].duration_ms, 3);
+        assert!(recent[0]
+            .context
+            .as_deref()
+            .unwrap()
+            .contains("transaction 8 was aborted"));
     }
 
     #[test]
@@ -355,6 +450,7 @@ mod tests {
                 "localhost".to_string(),
                 "user".to_string(),
                 "db".to_string(),
+                String::new(),
                 None,
                 None,
             );
@@ -398,6 +494,7 @@ mod tests {
                 "localhost".to_string(),
                 "user".to_string(),
                 "db".to_string(),
+                String::new(),
                 None,
                 None,
             );
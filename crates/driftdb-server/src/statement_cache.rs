@@ -0,0 +1,482 @@
+//! Shared cache of parsed statements
+//!
+//! Applications tend to run the same few query shapes over and over, and
+//! parsing is a noticeable share of the CPU a short query costs. The cache
+//! keys statements by their normalized text — whitespace collapsed and
+//! literals replaced by `$n` placeholders — so `WHERE id = 1` and
+//! `WHERE id = 2` share one entry. A hit binds the statement's literals
+//! into a copy of the cached AST instead of parsing it again.
+//!
+//! Only plain `SELECT`, `WITH`, `INSERT`, `UPDATE` and `DELETE` statements
+//! are cached; everything else is parsed as text by the core as before.
+//! The first time a shape is seen, the bound template is checked against a
+//! normal parse of the statement, and shapes whose literals can't be
+//! swapped for placeholders (typed literals such as `DATE '...'`, for
+//! example) are remembered as uncacheable. Entries record the tables they
+//! reference so DDL on a table evicts them.
+
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::Serialize;
+use sqlparser::ast::{visit_expressions_mut, visit_relations, Expr, Statement, Value};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use crate::metrics;
+
+/// Label for this cache in the `driftdb_cache_*` metrics
+const CACHE_TYPE: &str = "prepared_statement";
+
+/// Statement cache limits
+#[derive(Debug, Clone)]
+pub struct StatementCacheConfig {
+    /// Maximum number of cached statement shapes
+    pub max_entries: usize,
+    /// Approximate upper bound on memory held by cached statements
+    pub max_bytes: usize,
+}
+
+impl Default for StatementCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1000,
+            max_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Cache counters, as reported by [`StatementCache::stats`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StatementCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+    pub size_bytes: usize,
+}
+
+/// A literal lifted out of a statement during normalization
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Literal {
+    Number(String),
+    String(String),
+}
+
+impl Literal {
+    fn to_value(&self) -> Value {
+        match self {
+            Literal::Number(n) => Value::Number(n.clone(), false),
+            Literal::String(s) => Value::SingleQuotedString(s.clone()),
+        }
+    }
+}
+
+enum Entry {
+    /// The parsed shape, with placeholders where the literals were
+    Template {
+        ast: Arc<Vec<Statement>>,
+        /// Lowercased names of the relations the statement references
+        tables: Vec<String>,
+        size: usize,
+    },
+    /// A shape whose literals can't be replaced by placeholders
+    Uncacheable { size: usize },
+}
+
+impl Entry {
+    fn size(&self) -> usize {
+        match self {
+            Entry::Template { size, .. } | Entry::Uncacheable { size } => *size,
+        }
+    }
+}
+
+struct CacheState {
+    entries: LruCache<String, Entry>,
+    size_bytes: usize,
+    stats: StatementCacheStats,
+}
+
+/// Parsed statements shared by every session
+pub struct StatementCache {
+    config: StatementCacheConfig,
+    state: Mutex<CacheState>,
+}
+
+impl StatementCache {
+    pub fn new(config: StatementCacheConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(CacheState {
+                entries: LruCache::unbounded(),
+                size_bytes: 0,
+                stats: StatementCacheStats::default(),
+            }),
+        }
+    }
+
+    /// Whether the cache holds anything at all; a zero limit disables it
+    pub fn is_enabled(&self) -> bool {
+        self.config.max_entries > 0 && self.config.max_bytes > 0
+    }
+
+    /// The parsed form of `sql`, taken from the cache when its shape has
+    /// been seen before. `None` means the statement isn't cacheable and
+    /// should be executed as text.
+    pub fn parse(&self, sql: &str) -> Option<Vec<Statement>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let (key, literals) = normalize(sql)?;
+
+        {
+            let mut state = self.state.lock();
+            if let Some(entry) = state.entries.get(&key) {
+                let bound = match entry {
+                    Entry::Template { ast, .. } => Some(bind(ast, &literals)),
+                    Entry::Uncacheable { .. } => None,
+                };
+                state.stats.hits += 1;
+                metrics::record_cache_hit(CACHE_TYPE);
+                return bound;
+            }
+            state.stats.misses += 1;
+            metrics::record_cache_miss(CACHE_TYPE);
+        }
+
+        // Parse outside the lock so a miss doesn't stall other sessions
+        let (entry, parsed) = compile(&key, sql, &literals);
+        self.insert(key, entry);
+        parsed
+    }
+
+    /// Evict cached statements that reference `table`, after its schema
+    /// changed. Schema-qualified names match on the table part.
+    pub fn invalidate_table(&self, table: &str) {
+        let table = relation_name(table);
+        let mut state = self.state.lock();
+        let stale: Vec<String> = state
+            .entries
+            .iter()
+            .filter(|(_, entry)| match entry {
+                Entry::Template { tables, .. } => tables.contains(&table),
+                Entry::Uncacheable { .. } => false,
+            })
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            if let Some(entry) = state.entries.pop(&key) {
+                state.size_bytes -= entry.size();
+                state.stats.invalidations += 1;
+            }
+        }
+        metrics::update_cache_size(CACHE_TYPE, state.size_bytes);
+    }
+
+    /// Evict every cached statement
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.stats.invalidations += state.entries.len() as u64;
+        state.entries.clear();
+        state.size_bytes = 0;
+        metrics::update_cache_size(CACHE_TYPE, 0);
+    }
+
+    #[cfg(test)]
+    pub fn stats(&self) -> StatementCacheStats {
+        let state = self.state.lock();
+        StatementCacheStats {
+            entries: state.entries.len(),
+            size_bytes: state.size_bytes,
+            ..state.stats.clone()
+        }
+    }
+
+    fn insert(&self, key: String, entry: Entry) {
+        let mut state = self.state.lock();
+        state.size_bytes += entry.size();
+        if let Some(old) = state.entries.put(key, entry) {
+            // Another session compiled the same shape concurrently
+            state.size_bytes -= old.size();
+        }
+
+        while state.entries.len() > self.config.max_entries
+            || (state.size_bytes > self.config.max_bytes && state.entries.len() > 1)
+        {
+            match state.entries.pop_lru() {
+                Some((_, evicted)) => {
+                    state.size_bytes -= evicted.size();
+                    state.stats.evictions += 1;
+                    metrics::record_cache_eviction(CACHE_TYPE);
+                }
+                None => break,
+            }
+        }
+        metrics::update_cache_size(CACHE_TYPE, state.size_bytes);
+    }
+}
+
+impl Default for StatementCache {
+    fn default() -> Self {
+        Self::new(StatementCacheConfig::default())
+    }
+}
+
+/// Parse a new shape and check that binding `literals` into it reproduces
+/// the parse of `sql`. Returns the entry to cache and the parse of `sql`.
+fn compile(key: &str, sql: &str, literals: &[Literal]) -> (Entry, Option<Vec<Statement>>) {
+    let dialect = GenericDialect {};
+    let parsed = Parser::parse_sql(&dialect, sql).ok();
+
+    let template = Parser::parse_sql(&dialect, key)
+        .ok()
+        .filter(|template| parsed.as_deref() == Some(bind(template, literals).as_slice()));
+
+    let entry = match template {
+        Some(ast) => {
+            let mut tables = Vec::new();
+            for statement in &ast {
+                let _ = visit_relations(statement, |name| {
+                    if let Some(ident) = name.0.last() {
+                        tables.push(ident.value.to_lowercase());
+                    }
+                    ControlFlow::<()>::Continue(())
+                });
+            }
+            tables.sort();
+            tables.dedup();
+            // The AST's debug form is a fair proxy for its heap footprint
+            let size = key.len() + format!("{:?}", ast).len();
+            Entry::Template {
+                ast: Arc::new(ast),
+                tables,
+                size,
+            }
+        }
+        None => Entry::Uncacheable { size: key.len() },
+    };
+    (entry, parsed)
+}
+
+/// A copy of `template` with placeholder `$n` replaced by `literals[n - 1]`
+fn bind(template: &[Statement], literals: &[Literal]) -> Vec<Statement> {
+    let mut ast = template.to_vec();
+    let _ = visit_expressions_mut(&mut ast, |expr| {
+        if let Expr::Value(Value::Placeholder(placeholder)) = expr {
+            let literal = placeholder[1..]
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| literals.get(n.wrapping_sub(1)));
+            if let Some(literal) = literal {
+                *expr = Expr::Value(literal.to_value());
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    ast
+}
+
+/// The lowercased table part of a possibly schema-qualified name
+fn relation_name(name: &str) -> String {
+    name.rsplit('.').next().unwrap_or(name).to_lowercase()
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '$'
+}
+
+/// Normalize a cacheable statement into its cache key and the literals
+/// lifted out of it. `None` for statements the cache doesn't handle.
+pub(crate) fn normalize(sql: &str) -> Option<(String, Vec<Literal>)> {
+    let sql = sql.trim().trim_end_matches(';').trim_end();
+    let upper: String = sql.chars().take(7).collect::<String>().to_uppercase();
+    let cacheable = ["SELECT", "WITH", "INSERT", "UPDATE", "DELETE"]
+        .iter()
+        .any(|keyword| {
+            upper.starts_with(keyword)
+                && !upper[keyword.len()..].starts_with(|c: char| is_ident_char(c))
+        });
+    // Temporal clauses and placeholders are handled on the text path
+    if !cacheable || sql.contains('$') || sql.to_uppercase().contains("SYSTEM_TIME") {
+        return None;
+    }
+
+    let mut key = String::with_capacity(sql.len());
+    let mut literals = Vec::new();
+    let mut chars = sql.chars().peekable();
+    let mut prev = ' ';
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                key.push(' ');
+                prev = ' ';
+                continue;
+            }
+            '\'' => {
+                // Prefixed strings (E'...', X'...') have their own parsing
+                if is_ident_char(prev) {
+                    return None;
+                }
+                let mut value = String::new();
+                loop {
+                    match chars.next()? {
+                        '\'' if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            value.push('\'');
+                        }
+                        '\'' => break,
+                        c => value.push(c),
+                    }
+                }
+                literals.push(Literal::String(value));
+                key.push_str(&format!("${}", literals.len()));
+                prev = '\'';
+                continue;
+            }
+            '"' => {
+                key.push('"');
+                loop {
+                    let c = chars.next()?;
+                    key.push(c);
+                    if c == '"' {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => return None,
+            '/' if chars.peek() == Some(&'*') => return None,
+            c if c.is_ascii_digit() && !is_ident_char(prev) => {
+                let mut number = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign = (c == '+' || c == '-') && number.ends_with(['e', 'E']);
+                    if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign {
+                        number.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if chars.peek().is_some_and(|&c| is_ident_char(c)) {
+                    return None;
+                }
+                literals.push(Literal::Number(number));
+                key.push_str(&format!("${}", literals.len()));
+                prev = '0';
+                continue;
+            }
+            c => key.push(c),
+        }
+        prev = c;
+    }
+
+    Some((key, literals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(sql: &str) -> Vec<Statement> {
+        Parser::parse_sql(&GenericDialect {}, sql).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_lifts_literals() {
+        let (key, literals) =
+            normalize("SELECT  name FROM users\n WHERE id = 42 AND tag = 'it''s';").unwrap();
+        assert_eq!(key, "SELECT name FROM users WHERE id = $1 AND tag = $2");
+        assert_eq!(
+            literals,
+            vec![
+                Literal::Number("42".to_string()),
+                Literal::String("it's".to_string())
+            ]
+        );
+
+        // Digits inside identifiers stay put
+        let (key, _) = normalize("SELECT col1 FROM t2 WHERE \"x 1\" = 1.5e-3").unwrap();
+        assert_eq!(key, "SELECT col1 FROM t2 WHERE \"x 1\" = $1");
+
+        assert!(normalize("CREATE TABLE t (id INT)").is_none());
+        assert!(normalize("SELECT * FROM t WHERE id = $1").is_none());
+        assert!(normalize("SELECT * FROM t -- comment").is_none());
+        assert!(normalize("SELECT * FROM t FOR SYSTEM_TIME AS OF @seq:3").is_none());
+        assert!(normalize("SELECTED").is_none());
+    }
+
+    #[test]
+    fn test_hits_share_a_shape() {
+        let cache = StatementCache::default();
+
+        let first = cache.parse("SELECT * FROM users WHERE id = 1").unwrap();
+        assert_eq!(first, parse("SELECT * FROM users WHERE id = 1"));
+        let second = cache.parse("SELECT * FROM users WHERE id = 2").unwrap();
+        assert_eq!(second, parse("SELECT * FROM users WHERE id = 2"));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    }
+
+    #[test]
+    fn test_shapes_that_cannot_be_templated() {
+        let cache = StatementCache::default();
+        // The first sighting still hands back the statement's own parse
+        assert!(cache
+            .parse("SELECT * FROM t WHERE d = DATE '2024-01-01'")
+            .is_some());
+        assert!(cache
+            .parse("SELECT * FROM t WHERE d = DATE '2024-02-01'")
+            .is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+
+        assert!(cache.parse("NOT SQL").is_none());
+    }
+
+    #[test]
+    fn test_invalidate_table() {
+        let cache = StatementCache::default();
+        cache.parse("SELECT * FROM users WHERE id = 1");
+        cache.parse("SELECT * FROM orders o JOIN app.items i ON o.id = i.order_id");
+
+        cache.invalidate_table("public.users");
+        assert_eq!(cache.stats().entries, 1);
+        cache.invalidate_table("app.items");
+        assert_eq!(cache.stats().entries, 0);
+        assert_eq!(cache.stats().invalidations, 2);
+    }
+
+    #[test]
+    fn test_eviction_bounds() {
+        let cache = StatementCache::new(StatementCacheConfig {
+            max_entries: 2,
+            max_bytes: usize::MAX,
+        });
+        cache.parse("SELECT * FROM a");
+        cache.parse("SELECT * FROM b");
+        cache.parse("SELECT * FROM a");
+        cache.parse("SELECT * FROM c");
+
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.evictions), (2, 1));
+        // `a` was used more recently than `b`, so `b` went
+        cache.parse("SELECT * FROM a");
+        assert_eq!(cache.stats().hits, 2);
+
+        let tiny = StatementCache::new(StatementCacheConfig {
+            max_entries: 100,
+            max_bytes: 1,
+        });
+        tiny.parse("SELECT * FROM a");
+        tiny.parse("SELECT * FROM b");
+        assert_eq!(tiny.stats().entries, 1);
+    }
+}